@@ -237,7 +237,7 @@ async fn main() -> anyhow::Result<()> {
     let mut eth_sender_config = ETHSenderConfig::from_env();
 
     eth_sender_config.sender.operator_private_key =
-        H256::from_str(key_without_prefix).expect("Cannot deserialize private key");
+        Some(H256::from_str(key_without_prefix).expect("Cannot deserialize private key"));
 
     let mut storage = StorageProcessor::establish_connection().await?;
     let client = EthereumGateway::from_config(
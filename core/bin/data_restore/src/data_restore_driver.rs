@@ -255,7 +255,12 @@ impl<T: Transport> DataRestoreDriver<T> {
         );
 
         self.tree_state.state.root_hash();
-        let tree_cache = self.tree_state.state.get_balance_tree().get_internals();
+        let tree_cache = self
+            .tree_state
+            .state
+            .get_balance_tree()
+            .expect("Data restore keeps the account tree in memory")
+            .get_internals();
         interactor
             .update_tree_cache(
                 self.tree_state.block_number,
@@ -65,6 +65,7 @@ impl Default for MockProverConfigs {
             witness_generator: zksync_config::configs::prover::WitnessGenerator {
                 prepare_data_interval: 5000,
                 witness_generators: 2,
+                account_tree_path: None,
             },
         };
 
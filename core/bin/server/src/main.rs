@@ -6,7 +6,10 @@ use structopt::StructOpt;
 
 use serde::{Deserialize, Serialize};
 
-use zksync_api::fee_ticker::{run_updaters, FeeTicker, TickerInfo};
+use zksync_api::{
+    api_server::fee_quotes_cleaner::run_fee_quotes_cleaner,
+    fee_ticker::{run_updaters, FeeTicker, TickerInfo},
+};
 use zksync_core::{genesis_init, run_core, wait_for_tasks};
 use zksync_eth_client::EthereumGateway;
 use zksync_forced_exit_requests::run_forced_exit_requests_actors;
@@ -17,13 +20,17 @@ use tokio::task::JoinHandle;
 use zksync_config::configs::api::{PrivateApiConfig, PrometheusConfig, TokenConfig};
 use zksync_config::{
     configs::api::{CommonApiConfig, JsonRpcConfig, ProverApiConfig, RestApiConfig, Web3Config},
+    configs::eth_client::OperatorSignerType,
     ChainConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
     ForcedExitRequestsConfig, GatewayWatcherConfig, ProverConfig, TickerConfig, ZkSyncConfig,
 };
 use zksync_core::rejected_tx_cleaner::run_rejected_tx_cleaner;
 use zksync_mempool::run_mempool_tx_handler;
-use zksync_prometheus_exporter::{run_operation_counter, run_prometheus_exporter};
+use zksync_prometheus_exporter::{
+    run_eth_client_health_exporter, run_operation_counter, run_prometheus_exporter,
+};
 use zksync_storage::ConnectionPool;
+use zksync_types::{tx::PackedEthSignature, Address, H256};
 
 const DEFAULT_CHANNEL_CAPACITY: usize = 32_768;
 
@@ -159,10 +166,49 @@ async fn run_server(components: &ComponentsToRun) {
 
     if components.0.contains(&Component::Web3Api) {
         // Run web3 api
+        let ticker_config = TickerConfig::from_env();
+        let ticker = FeeTicker::new_with_default_validator(
+            Box::new(TickerInfo::new(
+                read_only_connection_pool.clone(),
+                ticker_config.price_staleness(),
+            )),
+            ticker_config,
+            ChainConfig::from_env().max_blocks_to_aggregate(),
+            read_only_connection_pool.clone(),
+        );
+        // Transfers sent via `eth_sendRawTransaction` are verified and passed to the mempool
+        // the same way as the ones sent via the zkSync API.
+        let (sign_check_sender, sign_check_receiver) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+        tasks.push(zksync_api::signature_checker::start_sign_checker(
+            create_eth_gateway(),
+            sign_check_receiver,
+        ));
+        let chain_config = ChainConfig::from_env();
+        let (mempool_tx_request_sender, mempool_tx_request_receiver) =
+            mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+        tasks.push(run_mempool_tx_handler(
+            connection_pool.clone(),
+            mempool_tx_request_receiver,
+            chain_config.state_keeper.block_chunk_sizes,
+            chain_config.mempool,
+        ));
         tasks.push(zksync_api::api_server::web3::start_rpc_server(
             connection_pool.clone(),
+            sign_check_sender.clone(),
+            ticker.clone(),
+            &Web3Config::from_env(),
+            &CommonApiConfig::from_env(),
+            &TokenConfig::from_env(),
+            mempool_tx_request_sender.clone(),
+        ));
+        tasks.push(zksync_api::api_server::web3::start_ws_server(
+            connection_pool.clone(),
+            sign_check_sender,
+            ticker,
             &Web3Config::from_env(),
+            &CommonApiConfig::from_env(),
             &TokenConfig::from_env(),
+            mempool_tx_request_sender,
         ));
     }
 
@@ -190,6 +236,9 @@ async fn run_server(components: &ComponentsToRun) {
         {
             tasks.push(task);
         }
+        if eth_gateway.is_multiplexed() && components.0.contains(&Component::Prometheus) {
+            tasks.push(run_eth_client_health_exporter(eth_gateway.clone()));
+        }
 
         // Run signer
         let (sign_check_sender, sign_check_receiver) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
@@ -203,7 +252,10 @@ async fn run_server(components: &ComponentsToRun) {
         let token_config = TokenConfig::from_env();
         let chain_config = ChainConfig::from_env();
         let fee_ticker_config = TickerConfig::from_env();
-        let ticker_info = Box::new(TickerInfo::new(read_only_connection_pool.clone()));
+        let ticker_info = Box::new(TickerInfo::new(
+            read_only_connection_pool.clone(),
+            fee_ticker_config.price_staleness(),
+        ));
 
         let ticker = FeeTicker::new_with_default_validator(
             ticker_info,
@@ -212,6 +264,12 @@ async fn run_server(components: &ComponentsToRun) {
             read_only_connection_pool.clone(),
         );
 
+        // Remove the fee quotes given by the API once they expire.
+        tasks.push(run_fee_quotes_cleaner(
+            &common_config,
+            connection_pool.clone(),
+        ));
+
         if components.0.contains(&Component::RpcWebSocketApi) {
             let (mempool_tx_request_sender, mempool_tx_request_receiver) =
                 mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
@@ -219,6 +277,7 @@ async fn run_server(components: &ComponentsToRun) {
                 connection_pool.clone(),
                 mempool_tx_request_receiver,
                 chain_config.state_keeper.block_chunk_sizes.clone(),
+                chain_config.mempool.clone(),
             ));
             tasks.push(zksync_api::api_server::rpc_subscriptions::start_ws_server(
                 read_only_connection_pool.clone(),
@@ -240,6 +299,7 @@ async fn run_server(components: &ComponentsToRun) {
                 connection_pool.clone(),
                 mempool_tx_request_receiver,
                 chain_config.state_keeper.block_chunk_sizes.clone(),
+                chain_config.mempool.clone(),
             ));
             tasks.push(zksync_api::api_server::rpc_server::start_rpc_server(
                 read_only_connection_pool.clone(),
@@ -260,6 +320,7 @@ async fn run_server(components: &ComponentsToRun) {
                 connection_pool.clone(),
                 mempool_tx_request_receiver,
                 chain_config.state_keeper.block_chunk_sizes,
+                chain_config.mempool,
             ));
             let private_config = PrivateApiConfig::from_env();
             tasks.push(zksync_api::api_server::rest::start_server_thread_detached(
@@ -352,6 +413,7 @@ pub fn run_forced_exit(connection_pool: ConnectionPool) -> Vec<JoinHandle<()>> {
         connection_pool.clone(),
         mempool_tx_request_receiver,
         chain_config.state_keeper.block_chunk_sizes,
+        chain_config.mempool,
     );
     let forced_exit_task = run_forced_exit_requests_actors(
         connection_pool,
@@ -360,6 +422,7 @@ pub fn run_forced_exit(connection_pool: ConnectionPool) -> Vec<JoinHandle<()>> {
         common_config,
         contract_config,
         eth_client_config.web3_url(),
+        eth_client_config.web3_ws_url.clone(),
     );
     vec![mempool_task, forced_exit_task]
 }
@@ -383,12 +446,62 @@ pub fn run_eth_sender(connection_pool: ConnectionPool) -> JoinHandle<()> {
         contracts.contract_addr,
     );
 
-    zksync_eth_sender::run_eth_sender(connection_pool, eth_gateway, eth_sender_config)
+    // With the remote signer, the private keys are not present on the host,
+    // so the additional operator accounts are identified by their addresses.
+    let additional_operators: Vec<(Address, Option<H256>)> = match eth_client_config.operator_signer
+    {
+        OperatorSignerType::PrivateKey => eth_sender_config
+            .sender
+            .additional_operator_private_keys
+            .iter()
+            .map(|private_key| {
+                let address = PackedEthSignature::address_from_private_key(private_key)
+                    .expect("Invalid additional operator private key");
+                (address, Some(*private_key))
+            })
+            .collect(),
+        OperatorSignerType::JsonRpc => {
+            if !eth_sender_config
+                .sender
+                .additional_operator_private_keys
+                .is_empty()
+            {
+                vlog::warn!(
+                        "Additional operator private keys are ignored, since the transactions are signed by the remote signer"
+                    );
+            }
+            eth_sender_config
+                .sender
+                .additional_operator_addresses
+                .iter()
+                .map(|address| (*address, None))
+                .collect()
+        }
+    };
+    let additional_operators = additional_operators
+        .into_iter()
+        .map(|(address, private_key)| {
+            let gateway = EthereumGateway::with_operator(
+                &eth_client_config,
+                address,
+                private_key,
+                contracts.contract_addr,
+            );
+            (address, gateway)
+        })
+        .collect();
+
+    zksync_eth_sender::run_eth_sender(
+        connection_pool,
+        eth_gateway,
+        additional_operators,
+        eth_sender_config,
+    )
 }
 
 pub fn run_price_updaters(connection_pool: ConnectionPool) -> Vec<JoinHandle<()>> {
     let ticker_config = TickerConfig::from_env();
-    run_updaters(connection_pool, &ticker_config)
+    run_updaters(connection_pool, create_eth_gateway(), &ticker_config)
 }
 
 pub fn create_eth_gateway() -> EthereumGateway {
@@ -0,0 +1,488 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{ensure, format_err};
+use structopt::StructOpt;
+use zksync_config::DBConfig;
+use zksync_crypto::{
+    convert::FeConvert, merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256,
+    params::account_tree_depth, Fr,
+};
+use zksync_storage::StorageProcessor;
+use zksync_types::{
+    aggregated_operations::{
+        AggregatedActionType, AggregatedOperation, BlocksCommitOperation, BlocksExecuteOperation,
+    },
+    Account, AccountId, AccountTree, AccountUpdate, BlockNumber, PubKeyHash, TokenKind,
+};
+
+use crate::snapshot::StateSnapshot;
+
+mod snapshot;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "state_snapshot",
+    about = "Tool to export the state at a verified block to a file and to bootstrap a node from it."
+)]
+enum Opt {
+    /// Exports the state at the verified block to the file.
+    Export {
+        /// Block to export the state for. The last verified block is used by default.
+        #[structopt(long)]
+        block: Option<u32>,
+        /// Path to the output file.
+        #[structopt(long)]
+        output: PathBuf,
+    },
+    /// Populates the storage with the state from the snapshot file.
+    Import {
+        /// Path to the snapshot file.
+        #[structopt(long)]
+        input: PathBuf,
+    },
+}
+
+/// Builds the account tree from the accounts and the stored tree internals and checks that
+/// its root hash matches the one of the snapshot block.
+async fn restore_tree(
+    accounts: Vec<(AccountId, Account)>,
+    tree_cache: SparseMerkleTreeSerializableCacheBN256,
+    expected_root_hash: Fr,
+) -> anyhow::Result<()> {
+    let root_hash = tokio::task::spawn_blocking(move || {
+        let mut tree = AccountTree::new(account_tree_depth());
+        for (id, account) in accounts {
+            tree.insert(*id, account);
+        }
+        tree.set_internals(tree_cache);
+        tree.root_hash()
+    })
+    .await?;
+
+    ensure!(
+        root_hash == expected_root_hash,
+        "Root hash of the restored tree {} doesn't match the one of the snapshot block {}",
+        root_hash.to_hex(),
+        expected_root_hash.to_hex()
+    );
+    Ok(())
+}
+
+/// Builds the account tree from scratch and checks that its root hash matches the expected one.
+/// Returns the tree internals to be stored in the snapshot.
+async fn calculate_tree(
+    accounts: Vec<(AccountId, Account)>,
+    expected_root_hash: Fr,
+) -> anyhow::Result<SparseMerkleTreeSerializableCacheBN256> {
+    // Calculating hashes for the whole tree may take a while, so it's done
+    // outside of the async runtime.
+    let (root_hash, internals) = tokio::task::spawn_blocking(move || {
+        let mut tree = AccountTree::new(account_tree_depth());
+        for (id, account) in accounts {
+            tree.insert(*id, account);
+        }
+        (tree.root_hash(), tree.get_internals())
+    })
+    .await?;
+
+    ensure!(
+        root_hash == expected_root_hash,
+        "Root hash of the account tree {} doesn't match the one stored in the block {}",
+        root_hash.to_hex(),
+        expected_root_hash.to_hex()
+    );
+    Ok(internals)
+}
+
+/// Collects the state at the verified block. The last verified block is used by default.
+async fn create_snapshot(
+    storage: &mut StorageProcessor<'_>,
+    block: Option<BlockNumber>,
+) -> anyhow::Result<StateSnapshot> {
+    let mut transaction = storage.start_transaction().await?;
+
+    let last_verified_block = transaction
+        .chain()
+        .block_schema()
+        .get_last_verified_confirmed_block()
+        .await?;
+    let block_number = block.unwrap_or(last_verified_block);
+    ensure!(
+        block_number <= last_verified_block,
+        "Block {} is not verified yet, the last verified block is {}",
+        block_number,
+        last_verified_block
+    );
+    let block = transaction
+        .chain()
+        .block_schema()
+        .get_block(block_number)
+        .await?
+        .ok_or_else(|| format_err!("Block {} is not found in the database", block_number))?;
+
+    let (state_block, accounts) = transaction
+        .chain()
+        .state_schema()
+        .load_committed_state(Some(block_number))
+        .await?;
+    ensure!(
+        state_block == block_number,
+        "Loaded state for block {} instead of {}",
+        state_block,
+        block_number
+    );
+    let nfts = transaction
+        .chain()
+        .state_schema()
+        .load_committed_nft_tokens(Some(block_number))
+        .await?;
+    // NFT tokens are created along with the NFTs themselves.
+    let mut tokens: Vec<_> = transaction
+        .tokens_schema()
+        .load_tokens()
+        .await?
+        .into_values()
+        .filter(|token| token.kind != TokenKind::NFT)
+        .collect();
+    tokens.sort_by_key(|token| token.id);
+    transaction.commit().await?;
+
+    let mut accounts: Vec<_> = accounts.into_iter().collect();
+    accounts.sort_by_key(|(id, _)| *id);
+    println!(
+        "Loaded {} accounts and {} NFTs for block {}",
+        accounts.len(),
+        nfts.len(),
+        block_number
+    );
+
+    let tree_cache = calculate_tree(accounts.clone(), block.new_root_hash).await?;
+    Ok(StateSnapshot {
+        block,
+        tokens,
+        nfts,
+        accounts,
+        tree_cache,
+    })
+}
+
+async fn export(
+    storage: &mut StorageProcessor<'_>,
+    block: Option<u32>,
+    output: PathBuf,
+) -> anyhow::Result<()> {
+    let snapshot = create_snapshot(storage, block.map(BlockNumber)).await?;
+    tokio::fs::write(&output, snapshot.encode()?).await?;
+
+    println!("Snapshot is written to {}", output.display());
+    Ok(())
+}
+
+/// Converts the snapshot state into the list of updates which, being applied to the
+/// empty state, produce the snapshot state.
+fn snapshot_updates(snapshot: &StateSnapshot) -> anyhow::Result<Vec<(AccountId, AccountUpdate)>> {
+    let nonces: HashMap<_, _> = snapshot
+        .accounts
+        .iter()
+        .map(|(id, account)| (*id, account.nonce))
+        .collect();
+
+    let mut updates = Vec::new();
+    for (id, account) in &snapshot.accounts {
+        updates.push((
+            *id,
+            AccountUpdate::Create {
+                address: account.address,
+                nonce: account.nonce,
+            },
+        ));
+    }
+    // NFT tokens must be created before any balance of them is set.
+    // Mint update overwrites the creator nonce, so the current one is used.
+    for nft in &snapshot.nfts {
+        let nonce = *nonces.get(&nft.creator_id).ok_or_else(|| {
+            format_err!(
+                "Creator {} of the NFT {} is not found in the snapshot",
+                nft.creator_id,
+                nft.id
+            )
+        })?;
+        updates.push((
+            nft.creator_id,
+            AccountUpdate::MintNFT {
+                token: nft.clone(),
+                nonce,
+            },
+        ));
+    }
+    for (id, account) in &snapshot.accounts {
+        for (token, balance) in account.get_nonzero_balances() {
+            updates.push((
+                *id,
+                AccountUpdate::UpdateBalance {
+                    old_nonce: account.nonce,
+                    new_nonce: account.nonce,
+                    balance_update: (token, 0u32.into(), balance.0),
+                },
+            ));
+        }
+        if account.pub_key_hash != PubKeyHash::default() {
+            updates.push((
+                *id,
+                AccountUpdate::ChangePubKeyHash {
+                    old_pub_key_hash: PubKeyHash::default(),
+                    new_pub_key_hash: account.pub_key_hash,
+                    old_nonce: account.nonce,
+                    new_nonce: account.nonce,
+                },
+            ));
+        }
+    }
+    Ok(updates)
+}
+
+/// Populates the empty database with the snapshot state. The snapshot block is stored
+/// as committed and executed, so the node continues from the next block.
+async fn import_snapshot(
+    storage: &mut StorageProcessor<'_>,
+    snapshot: StateSnapshot,
+) -> anyhow::Result<()> {
+    let block_number = snapshot.block.block_number;
+    restore_tree(
+        snapshot.accounts.clone(),
+        snapshot.tree_cache.clone(),
+        snapshot.block.new_root_hash,
+    )
+    .await?;
+    let updates = snapshot_updates(&snapshot)?;
+
+    let mut transaction = storage.start_transaction().await?;
+
+    let (_, existing_accounts) = transaction
+        .chain()
+        .state_schema()
+        .load_verified_state()
+        .await?;
+    let last_saved_block = transaction
+        .chain()
+        .block_schema()
+        .get_last_saved_block()
+        .await?;
+    ensure!(
+        existing_accounts.is_empty() && *last_saved_block == 0,
+        "Database already contains the state up to block {}, \
+         the snapshot can only be imported into the empty database",
+        last_saved_block
+    );
+
+    for token in snapshot.tokens.iter().cloned() {
+        transaction
+            .tokens_schema()
+            .store_or_update_token(token)
+            .await?;
+    }
+    transaction
+        .chain()
+        .block_schema()
+        .save_full_block(snapshot.block.clone())
+        .await?;
+    transaction
+        .chain()
+        .state_schema()
+        .commit_state_update(block_number, &updates, 0)
+        .await?;
+    transaction
+        .chain()
+        .state_schema()
+        .apply_state_update(block_number)
+        .await?;
+
+    // The snapshot block is marked as committed and executed, so it's treated as the last
+    // verified one and its header is used for the commitment of the next block.
+    let commit_operation = BlocksCommitOperation {
+        last_committed_block: snapshot.block.clone(),
+        blocks: vec![snapshot.block.clone()],
+    };
+    let execute_operation = BlocksExecuteOperation {
+        blocks: vec![snapshot.block],
+    };
+    transaction
+        .chain()
+        .operations_schema()
+        .store_aggregated_action(AggregatedOperation::CommitBlocks(commit_operation))
+        .await?;
+    transaction
+        .chain()
+        .operations_schema()
+        .store_aggregated_action(AggregatedOperation::ExecuteBlocks(execute_operation))
+        .await?;
+    for action_type in &[
+        AggregatedActionType::CommitBlocks,
+        AggregatedActionType::ExecuteBlocks,
+    ] {
+        transaction
+            .chain()
+            .operations_schema()
+            .confirm_aggregated_operations(block_number, block_number, *action_type)
+            .await?;
+    }
+    transaction.ethereum_schema().initialize_eth_data().await?;
+    transaction
+        .data_restore_schema()
+        .initialize_eth_stats(block_number, block_number, block_number)
+        .await?;
+
+    transaction
+        .chain()
+        .tree_cache_schema_bincode()
+        .store_account_tree_cache(block_number, snapshot.tree_cache.encode_bincode())
+        .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+async fn import(storage: &mut StorageProcessor<'_>, input: PathBuf) -> anyhow::Result<()> {
+    let snapshot = StateSnapshot::decode(&tokio::fs::read(&input).await?)?;
+    let block_number = snapshot.block.block_number;
+    println!(
+        "Loaded snapshot for block {} with {} accounts and {} NFTs",
+        block_number,
+        snapshot.accounts.len(),
+        snapshot.nfts.len()
+    );
+
+    import_snapshot(storage, snapshot).await?;
+
+    println!("State for block {} is imported", block_number);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let opt = Opt::from_args();
+
+    let mut storage = StorageProcessor::establish_connection().await?;
+    println!("Database URL is {}", DBConfig::from_env().url);
+
+    match opt {
+        Opt::Export { block, output } => export(&mut storage, block, output).await,
+        Opt::Import { input } => import(&mut storage, input).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db_test_macro::test as db_test;
+    use zksync_types::{block::Block, Address, Token, TokenId, H256, U256};
+
+    fn test_snapshot() -> StateSnapshot {
+        let mut first = Account::default_with_address(&Address::repeat_byte(0x11));
+        first.set_balance(TokenId(0), 100u32.into());
+        first.set_balance(TokenId(1), 5u32.into());
+        first.pub_key_hash = PubKeyHash::from_bytes(&[0x33; 20]).unwrap();
+        let second = Account::default_with_address(&Address::repeat_byte(0x22));
+        let accounts = vec![(AccountId(1), first), (AccountId(2), second)];
+
+        let mut tree = AccountTree::new(account_tree_depth());
+        for (id, account) in accounts.iter().cloned() {
+            tree.insert(*id, account);
+        }
+        let block = Block::new(
+            BlockNumber(3),
+            tree.root_hash(),
+            AccountId(1),
+            Vec::new(),
+            (0, 0),
+            10,
+            U256::zero(),
+            U256::zero(),
+            H256::repeat_byte(0x44),
+            0,
+        );
+
+        StateSnapshot {
+            block,
+            tokens: vec![Token::new(
+                TokenId(1),
+                Address::repeat_byte(0x55),
+                "TST",
+                18,
+                TokenKind::ERC20,
+            )],
+            nfts: Vec::new(),
+            accounts,
+            tree_cache: tree.get_internals(),
+        }
+    }
+
+    fn assert_same_state(actual: &StateSnapshot, expected: &StateSnapshot) {
+        assert_eq!(
+            serde_json::to_value(&actual.block).unwrap(),
+            serde_json::to_value(&expected.block).unwrap()
+        );
+        assert_eq!(actual.nfts, expected.nfts);
+        assert_eq!(actual.accounts, expected.accounts);
+        for token in &expected.tokens {
+            assert!(actual.tokens.contains(token), "{:?} is missing", token);
+        }
+    }
+
+    /// Checks that the snapshot imported into the empty database is exported back unchanged.
+    #[db_test]
+    async fn export_import_roundtrip(mut storage: StorageProcessor<'_>) -> anyhow::Result<()> {
+        let snapshot = test_snapshot();
+
+        // The first import is rolled back, so the exported snapshot can be imported
+        // into the empty database again.
+        let exported = {
+            let mut transaction = storage.start_transaction().await?;
+            import_snapshot(&mut transaction, snapshot.clone()).await?;
+            create_snapshot(&mut transaction, None).await?
+        };
+        assert_same_state(&exported, &snapshot);
+        let exported = StateSnapshot::decode(&exported.encode()?)?;
+
+        import_snapshot(&mut storage, exported.clone()).await?;
+        let block_number = exported.block.block_number;
+        assert_eq!(
+            storage
+                .chain()
+                .block_schema()
+                .get_last_verified_confirmed_block()
+                .await?,
+            block_number
+        );
+        assert_eq!(
+            storage
+                .chain()
+                .block_schema()
+                .get_last_committed_confirmed_block()
+                .await?,
+            block_number
+        );
+        assert!(storage
+            .chain()
+            .tree_cache_schema_bincode()
+            .get_account_tree_cache_block(block_number)
+            .await?
+            .is_some());
+
+        let reexported = create_snapshot(&mut storage, None).await?;
+        assert_same_state(&reexported, &exported);
+
+        // The state can't be overwritten.
+        assert!(import_snapshot(&mut storage, exported).await.is_err());
+        Ok(())
+    }
+
+    #[db_test]
+    async fn snapshot_with_invalid_tree_is_rejected(
+        mut storage: StorageProcessor<'_>,
+    ) -> anyhow::Result<()> {
+        let mut snapshot = test_snapshot();
+        snapshot.tree_cache = AccountTree::new(account_tree_depth()).get_internals();
+        assert!(import_snapshot(&mut storage, snapshot).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+//! Snapshot file format.
+//!
+//! The file consists of a fixed-size header followed by the JSON-encoded snapshot:
+//!
+//! - 8 bytes: magic value `ZKSNAPSH`;
+//! - 4 bytes: format version (big-endian);
+//! - 32 bytes: SHA-256 checksum of the payload;
+//! - the rest: payload.
+
+use std::convert::TryInto;
+
+use anyhow::{ensure, format_err};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
+use zksync_types::{block::Block, Account, AccountId, Token, NFT};
+
+const MAGIC: &[u8; 8] = b"ZKSNAPSH";
+/// Version of the snapshot format. Must be increased on every incompatible change
+/// of the `StateSnapshot` structure.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const HEADER_SIZE: usize = MAGIC.len() + 4 + 32;
+
+/// State of the network at a certain verified block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// The snapshot block. It's stored as a whole, since the next block commitment
+    /// depends on its header and operations.
+    pub block: Block,
+    /// Tokens which may be referenced by the account balances (except for NFTs).
+    pub tokens: Vec<Token>,
+    /// NFTs minted up to the snapshot block.
+    pub nfts: Vec<NFT>,
+    pub accounts: Vec<(AccountId, Account)>,
+    /// Internals of the account tree, so the hashes don't have to be recalculated on import.
+    /// They are checked against the root hash of the snapshot block.
+    pub tree_cache: SparseMerkleTreeSerializableCacheBN256,
+}
+
+impl StateSnapshot {
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let payload = serde_json::to_vec(self)?;
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + payload.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&SNAPSHOT_VERSION.to_be_bytes());
+        data.extend_from_slice(&Sha256::digest(&payload));
+        data.extend(payload);
+        Ok(data)
+    }
+
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        ensure!(
+            data.len() >= HEADER_SIZE && &data[..MAGIC.len()] == MAGIC,
+            "File is not a state snapshot"
+        );
+        let (version, rest) = data[MAGIC.len()..].split_at(4);
+        let version = u32::from_be_bytes(version.try_into().unwrap());
+        ensure!(
+            version == SNAPSHOT_VERSION,
+            "Unsupported snapshot version {}, expected {}",
+            version,
+            SNAPSHOT_VERSION
+        );
+
+        let (checksum, payload) = rest.split_at(32);
+        ensure!(
+            Sha256::digest(payload).as_slice() == checksum,
+            "Snapshot checksum mismatch, the file is corrupted"
+        );
+
+        serde_json::from_slice(payload).map_err(|err| format_err!("Malformed snapshot: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_crypto::params::account_tree_depth;
+    use zksync_types::{AccountTree, Address, BlockNumber, TokenId, H256, U256};
+
+    fn sample_snapshot() -> StateSnapshot {
+        let mut account = Account::default_with_address(&Address::repeat_byte(0x11));
+        account.set_balance(TokenId(0), 100u32.into());
+
+        let mut tree = AccountTree::new(account_tree_depth());
+        tree.insert(1, account.clone());
+        let block = Block::new(
+            BlockNumber(5),
+            tree.root_hash(),
+            AccountId(0),
+            Vec::new(),
+            (0, 0),
+            10,
+            U256::zero(),
+            U256::zero(),
+            H256::repeat_byte(0x22),
+            0,
+        );
+
+        StateSnapshot {
+            block,
+            tokens: vec![Token::default()],
+            nfts: Vec::new(),
+            accounts: vec![(AccountId(1), account)],
+            tree_cache: tree.get_internals(),
+        }
+    }
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let snapshot = sample_snapshot();
+        let decoded = StateSnapshot::decode(&snapshot.encode().unwrap()).unwrap();
+
+        assert_eq!(decoded.block.block_number, snapshot.block.block_number);
+        assert_eq!(decoded.block.new_root_hash, snapshot.block.new_root_hash);
+        assert_eq!(
+            decoded.block.block_commitment,
+            snapshot.block.block_commitment
+        );
+        assert_eq!(decoded.tokens, snapshot.tokens);
+        assert_eq!(decoded.accounts.len(), 1);
+        assert_eq!(
+            decoded.accounts[0].1.address,
+            snapshot.accounts[0].1.address
+        );
+        assert_eq!(
+            decoded.tree_cache.encode_bincode(),
+            snapshot.tree_cache.encode_bincode()
+        );
+    }
+
+    #[test]
+    fn corrupted_snapshot_is_rejected() {
+        let mut data = sample_snapshot().encode().unwrap();
+        let last = data.len() - 2;
+        data[last] ^= 1;
+        assert!(StateSnapshot::decode(&data).is_err());
+
+        let mut data = sample_snapshot().encode().unwrap();
+        data[MAGIC.len() + 3] += 1;
+        assert!(StateSnapshot::decode(&data).is_err());
+    }
+}
@@ -143,6 +143,7 @@ impl OperationNotifier {
                             committed: true,
                             verified: action == ActionType::VERIFY,
                         }),
+                        replaced_by: None,
                     };
                     self.tx_subs.notify(hash, action, resp);
                 }
@@ -258,6 +259,7 @@ impl OperationNotifier {
                     committed: receipt.success,
                     verified: receipt.verified,
                 }),
+                replaced_by: None,
             };
             match action {
                 ActionType::COMMIT => {
@@ -0,0 +1,34 @@
+//! The cleaner is responsible for removing expired fee quotes from the database.
+//!
+//! Quotes are only checked while they are valid, so there is no point in keeping them
+//! after they expire. The cleaner runs once per quote validity period.
+
+// External uses
+use tokio::{task::JoinHandle, time};
+
+// Workspace deps
+use zksync_config::configs::api::CommonApiConfig;
+use zksync_storage::ConnectionPool;
+
+#[must_use]
+pub fn run_fee_quotes_cleaner(config: &CommonApiConfig, db_pool: ConnectionPool) -> JoinHandle<()> {
+    let mut timer = time::interval(config.fee_quote_validity());
+
+    tokio::spawn(async move {
+        loop {
+            timer.tick().await;
+
+            let mut storage = match db_pool.access_storage().await {
+                Ok(storage) => storage,
+                Err(e) => {
+                    vlog::error!("Fee quotes cleaner couldn't access the database: {}", e);
+                    continue;
+                }
+            };
+            match storage.misc_schema().remove_expired_fee_quotes().await {
+                Ok(removed) => vlog::debug!("Removed {} expired fee quotes", removed),
+                Err(e) => vlog::error!("Can't delete expired fee quotes: {:?}", e),
+            }
+        }
+    })
+}
@@ -1,21 +1,25 @@
 //! Helpers collection shared between the different API implementations.
 
 // Built-in uses
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 // External uses
 use num::BigUint;
+use tokio::sync::Mutex;
 
 // Workspace uses
 use zksync_api_types::v02::account::{DepositingAccountBalances, DepositingFunds, OngoingDeposit};
-use zksync_storage::StorageProcessor;
+use zksync_crypto::{convert::FeConvert, params::account_tree_depth, Fr};
+use zksync_storage::{QueryResult, StorageProcessor};
 use zksync_token_db_cache::TokenDBCache;
-use zksync_types::{Address, H256};
+use zksync_types::{
+    AccountId, AccountMerkleProof, AccountTree, Address, BlockNumber, TokenId, H256,
+};
 use zksync_utils::remove_prefix;
 
 // Local uses
 use super::rest::v02::error::Error;
-use crate::fee_ticker::PriceError;
+use crate::{fee_ticker::PriceError, utils::shared_lru_cache::AsyncLruCache};
 
 pub fn try_parse_hash(query: &str) -> Result<H256, hex::FromHexError> {
     const HASH_SIZE: usize = 32; // 32 bytes
@@ -84,3 +88,136 @@ pub async fn get_depositing(
         .collect();
     depositing_from_pending_ops(storage, tokens, pending_ops, confirmations_for_eth_event).await
 }
+
+/// Number of the most recent finalized blocks for which account proofs can be built.
+/// Proofs for older blocks are not served, since every block requires restoring its account tree.
+pub const ACCOUNT_PROOF_BLOCKS_WINDOW: u32 = 2;
+
+/// Number of the restored account trees kept in memory to build account proofs,
+/// enough to keep the trees of all the blocks proofs are served for.
+pub const ACCOUNT_TREES_CACHE_SIZE: usize = ACCOUNT_PROOF_BLOCKS_WINDOW as usize;
+
+/// Account trees of the finalized blocks restored to build account proofs.
+///
+/// Restoring the tree takes time proportional to the number of accounts, so trees
+/// are restored one at a time, only for the last [`ACCOUNT_PROOF_BLOCKS_WINDOW`] finalized blocks,
+/// and the recently used ones are kept in memory.
+#[derive(Clone)]
+pub struct AccountTreesCache {
+    trees: AsyncLruCache<BlockNumber, Arc<AccountTree>>,
+    restore_lock: Arc<Mutex<()>>,
+}
+
+impl AccountTreesCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            trees: AsyncLruCache::new(capacity),
+            restore_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Checks that the account proof can be built for the block, i.e. that it's one of
+    /// the last [`ACCOUNT_PROOF_BLOCKS_WINDOW`] finalized blocks.
+    pub fn is_proof_available(
+        block_number: BlockNumber,
+        last_finalized_block: BlockNumber,
+    ) -> bool {
+        block_number <= last_finalized_block
+            && *last_finalized_block - *block_number < ACCOUNT_PROOF_BLOCKS_WINDOW
+    }
+
+    /// Builds the Merkle proof of the account balance for the finalized block.
+    /// The block is expected to be checked with [`AccountTreesCache::is_proof_available`].
+    pub async fn get_account_proof(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        account_id: AccountId,
+        token_id: TokenId,
+        block_number: BlockNumber,
+    ) -> QueryResult<AccountMerkleProof> {
+        let tree = self.get_tree(storage, block_number).await?;
+        let proof = tokio::task::spawn_blocking(move || {
+            AccountMerkleProof::new(&tree, block_number, account_id, token_id)
+        })
+        .await?;
+        Ok(proof)
+    }
+
+    async fn get_tree(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        block_number: BlockNumber,
+    ) -> QueryResult<Arc<AccountTree>> {
+        if let Some(tree) = self.trees.get(&block_number).await {
+            return Ok(tree);
+        }
+
+        let _restore_guard = self.restore_lock.lock().await;
+        // The tree may have been restored by another request while we were waiting.
+        if let Some(tree) = self.trees.get(&block_number).await {
+            return Ok(tree);
+        }
+        let tree = Arc::new(restore_account_tree(storage, block_number).await?);
+        self.trees.insert(block_number, tree.clone()).await;
+        Ok(tree)
+    }
+}
+
+/// Restores the account tree of the finalized block and checks its root hash.
+/// The tree cache is used when it's available for the block.
+async fn restore_account_tree(
+    storage: &mut StorageProcessor<'_>,
+    block_number: BlockNumber,
+) -> QueryResult<AccountTree> {
+    let mut transaction = storage.start_transaction().await?;
+
+    let block_root_hash = transaction
+        .chain()
+        .block_schema()
+        .get_storage_block(block_number)
+        .await?
+        .map(|block| Fr::from_bytes(&block.root_hash))
+        .ok_or_else(|| anyhow::format_err!("Block {} is not found", block_number))??;
+    let (state_block, accounts) = transaction
+        .chain()
+        .state_schema()
+        .load_committed_state(Some(block_number))
+        .await?;
+    anyhow::ensure!(
+        state_block == block_number,
+        "Loaded state for block {} instead of {}",
+        state_block,
+        block_number
+    );
+    let tree_cache = transaction
+        .chain()
+        .tree_cache_schema_bincode()
+        .load_account_tree_cache_incremental(block_number)
+        .await?
+        .filter(|(cache_block, _)| *cache_block == block_number)
+        .map(|(_, cache)| cache);
+    transaction.commit().await?;
+
+    // Restoring the tree may require recalculating all the hashes, so it's done
+    // outside of the async runtime.
+    let (tree, root_hash) = tokio::task::spawn_blocking(move || {
+        let mut tree = AccountTree::new(account_tree_depth());
+        for (id, account) in accounts {
+            tree.insert(*id, account);
+        }
+        if let Some(tree_cache) = tree_cache {
+            tree.set_internals(tree_cache);
+        }
+        // Calculate the root hash once, so the hashes are cached for all the proofs.
+        let root_hash = tree.root_hash();
+        (tree, root_hash)
+    })
+    .await?;
+
+    anyhow::ensure!(
+        root_hash == block_root_hash,
+        "Restored root hash for block {} doesn't match the committed one",
+        block_number
+    );
+    Ok(tree)
+}
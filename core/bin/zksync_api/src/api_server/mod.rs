@@ -5,12 +5,14 @@
 //! `mod rpc_subscriptions` - JSON rpc via WebSocket (for request reply functions and subscriptions)
 
 mod event_notify;
+pub mod fee_quotes_cleaner;
 pub mod forced_exit_checker;
 mod helpers;
 pub mod rest;
 pub mod rpc_server;
 pub mod rpc_subscriptions;
 mod tx_sender;
+mod tx_simulator;
 pub mod web3;
 
 /// Amount of threads used by each server to serve requests.
@@ -10,7 +10,10 @@ use zksync_utils::panic_notify::{spawn_panic_handler, ThreadPanicNotify};
 use self::v01::api_decl::ApiV01;
 use crate::signature_checker::VerifySignatureRequest;
 
-use super::tx_sender::TxSender;
+use super::{
+    helpers::{AccountTreesCache, ACCOUNT_TREES_CACHE_SIZE},
+    tx_sender::TxSender,
+};
 
 use crate::api_server::rest::network_status::SharedNetworkStatus;
 use crate::fee_ticker::FeeTicker;
@@ -31,6 +34,9 @@ async fn start_server(
     bind_to: SocketAddr,
     mempool_tx_sender: mpsc::Sender<MempoolTransactionRequest>,
 ) {
+    // Restored account trees are shared between the workers, so the trees are restored only once.
+    let account_trees = AccountTreesCache::new(ACCOUNT_TREES_CACHE_SIZE);
+
     HttpServer::new(move || {
         let api_v01 = api_v01.clone();
         // This api stores forced exit requests, it's necessary to use main database connection
@@ -54,7 +60,12 @@ async fn start_server(
                 &api_v01.config.api.token_config,
                 mempool_tx_sender.clone(),
             );
-            v02::api_scope(tx_sender, &api_v01.config, api_v01.network_status.clone())
+            v02::api_scope(
+                tx_sender,
+                &api_v01.config,
+                api_v01.network_status.clone(),
+                account_trees.clone(),
+            )
         };
         App::new()
             .wrap(
@@ -10,7 +10,10 @@ use actix_web::{web, Scope};
 
 // Workspace uses
 use zksync_api_types::v02::{
-    account::{Account, AccountAddressOrId, AccountState, IncomingAccountTxsQuery},
+    account::{
+        Account, AccountAddressOrId, AccountState, IncomingAccountProofQuery,
+        IncomingAccountTxsQuery,
+    },
     pagination::{
         parse_query, AccountTxsRequest, ApiEither, Paginated, PaginationQuery, PendingOpsRequest,
     },
@@ -19,7 +22,9 @@ use zksync_api_types::v02::{
 use zksync_crypto::params::{MIN_NFT_TOKEN_ID, NFT_TOKEN_ID_VAL};
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_token_db_cache::TokenDBCache;
-use zksync_types::{tx::TxHash, AccountId, Address, BlockNumber, SerialId, TokenLike};
+use zksync_types::{
+    tx::TxHash, AccountId, AccountMerkleProof, Address, BlockNumber, SerialId, TokenLike,
+};
 
 // Local uses
 use super::{
@@ -27,21 +32,32 @@ use super::{
     paginate_trait::Paginate,
     response::ApiResult,
 };
-use crate::{api_server::helpers::get_depositing, api_try, fee_ticker::PriceError};
+use crate::{
+    api_server::helpers::{get_depositing, AccountTreesCache},
+    api_try,
+    fee_ticker::PriceError,
+};
 
 /// Shared data between `api/v02/accounts` endpoints.
 #[derive(Clone)]
 struct ApiAccountData {
     pool: ConnectionPool,
     tokens: TokenDBCache,
+    account_trees: AccountTreesCache,
     confirmations_for_eth_event: u64,
 }
 
 impl ApiAccountData {
-    fn new(pool: ConnectionPool, tokens: TokenDBCache, confirmations_for_eth_event: u64) -> Self {
+    fn new(
+        pool: ConnectionPool,
+        tokens: TokenDBCache,
+        account_trees: AccountTreesCache,
+        confirmations_for_eth_event: u64,
+    ) -> Self {
         Self {
             pool,
             tokens,
+            account_trees,
             confirmations_for_eth_event,
         }
     }
@@ -231,6 +247,41 @@ impl ApiAccountData {
         result
     }
 
+    async fn account_info_at_block(
+        &self,
+        account_id: AccountId,
+        block_number: BlockNumber,
+    ) -> Result<Option<Account>, Error> {
+        let mut storage = self.pool.access_storage().await.map_err(Error::storage)?;
+        let mut transaction = storage.start_transaction().await.map_err(Error::storage)?;
+        let last_committed_block = transaction
+            .chain()
+            .block_schema()
+            .get_last_committed_confirmed_block()
+            .await
+            .map_err(Error::storage)?;
+        if block_number > last_committed_block {
+            return Err(Error::from(InvalidDataError::BlockNotFound));
+        }
+
+        let (last_block, account) = transaction
+            .chain()
+            .account_schema()
+            .account_state_for_block(account_id, block_number)
+            .await
+            .map_err(Error::storage)?;
+        let result = if let Some(account) = account {
+            Ok(Some(
+                self.api_account(account, account_id, last_block, &mut transaction)
+                    .await?,
+            ))
+        } else {
+            Ok(None)
+        };
+        transaction.commit().await.map_err(Error::storage)?;
+        result
+    }
+
     async fn account_full_info(
         &self,
         address: Address,
@@ -323,6 +374,41 @@ impl ApiAccountData {
         storage.paginate_checked(&new_query).await
     }
 
+    async fn account_proof(
+        &self,
+        account_id: AccountId,
+        token_like: TokenLike,
+        block: Option<BlockNumber>,
+    ) -> Result<AccountMerkleProof, Error> {
+        let mut storage = self.pool.access_storage().await.map_err(Error::storage)?;
+        let token_id = self
+            .tokens
+            .get_token(&mut storage, token_like.clone())
+            .await
+            .map_err(Error::storage)?
+            .ok_or_else(|| Error::from(PriceError::token_not_found(token_like)))?
+            .id;
+
+        let last_finalized_block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await
+            .map_err(Error::storage)?;
+        let block = block.unwrap_or(last_finalized_block);
+        if block > last_finalized_block {
+            return Err(Error::from(InvalidDataError::BlockNotFinalized));
+        }
+        if !AccountTreesCache::is_proof_available(block, last_finalized_block) {
+            return Err(Error::from(InvalidDataError::BlockProofUnavailable));
+        }
+
+        self.account_trees
+            .get_account_proof(&mut storage, account_id, token_id, block)
+            .await
+            .map_err(Error::storage)
+    }
+
     /// Pending deposits can be matched only with addresses,
     /// while pending full exits can be matched only with account ids.
     /// If the account isn't created yet it doesn't have an id
@@ -379,6 +465,25 @@ async fn account_finalized_info(
     res
 }
 
+async fn account_info_at_block(
+    data: web::Data<ApiAccountData>,
+    path: web::Path<(String, u32)>,
+) -> ApiResult<Option<Account>> {
+    let start = Instant::now();
+    let (account_id_or_address, block_number) = path.into_inner();
+    let address_or_id = api_try!(data.parse_account_id_or_address(&account_id_or_address));
+    let account_id = api_try!(data.get_id_by_address_or_id(address_or_id).await);
+    let res = if let Some(account_id) = account_id {
+        data.account_info_at_block(account_id, BlockNumber(block_number))
+            .await
+            .into()
+    } else {
+        ApiResult::Ok(None)
+    };
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "account_info_at_block");
+    res
+}
+
 async fn account_full_info(
     data: web::Data<ApiAccountData>,
     account_id_or_address: web::Path<String>,
@@ -451,12 +556,34 @@ async fn account_pending_txs(
     res
 }
 
+async fn account_proof(
+    data: web::Data<ApiAccountData>,
+    account_id_or_address: web::Path<String>,
+    web::Query(query): web::Query<IncomingAccountProofQuery>,
+) -> ApiResult<AccountMerkleProof> {
+    let start = Instant::now();
+    let address_or_id = api_try!(data.parse_account_id_or_address(&account_id_or_address));
+    let account_id = api_try!(data
+        .get_id_by_address_or_id(address_or_id)
+        .await
+        .and_then(|id| id.ok_or_else(|| Error::from(InvalidDataError::AccountNotFound))));
+    let token_like = TokenLike::parse(&query.token);
+
+    let res = data
+        .account_proof(account_id, token_like, query.block)
+        .await
+        .into();
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "account_proof");
+    res
+}
+
 pub fn api_scope(
     pool: ConnectionPool,
     tokens: TokenDBCache,
+    account_trees: AccountTreesCache,
     confirmations_for_eth_event: u64,
 ) -> Scope {
-    let data = ApiAccountData::new(pool, tokens, confirmations_for_eth_event);
+    let data = ApiAccountData::new(pool, tokens, account_trees, confirmations_for_eth_event);
 
     web::scope("accounts")
         .app_data(web::Data::new(data))
@@ -468,6 +595,10 @@ pub fn api_scope(
             "{account_id_or_address}/finalized",
             web::get().to(account_finalized_info),
         )
+        .route(
+            "{account_id_or_address}/blocks/{block_number}",
+            web::get().to(account_info_at_block),
+        )
         .route("{account_id_or_address}", web::get().to(account_full_info))
         .route(
             "{account_id_or_address}/transactions",
@@ -477,14 +608,21 @@ pub fn api_scope(
             "{account_id_or_address}/transactions/pending",
             web::get().to(account_pending_txs),
         )
+        .route(
+            "{account_id_or_address}/proof",
+            web::get().to(account_proof),
+        )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api_server::rest::v02::{
-        test_utils::{deserialize_response_result, TestServerConfig},
-        SharedData,
+    use crate::api_server::{
+        helpers::ACCOUNT_TREES_CACHE_SIZE,
+        rest::v02::{
+            test_utils::{deserialize_response_result, TestServerConfig},
+            SharedData,
+        },
     };
     use num::BigUint;
     use serde::Deserialize;
@@ -534,6 +672,7 @@ mod tests {
                         TokenDBCache::new(
                             cfg.config.api.token_config.invalidate_token_cache_period(),
                         ),
+                        AccountTreesCache::new(ACCOUNT_TREES_CACHE_SIZE),
                         cfg.config.eth_watch.confirmations_for_eth_event,
                     )
                 },
@@ -611,6 +750,28 @@ mod tests {
             .await?;
         let account_finalized_info: Option<Account> = deserialize_response_result(response)?;
 
+        let last_committed_block = server
+            .pool
+            .access_storage()
+            .await?
+            .chain()
+            .block_schema()
+            .get_last_committed_confirmed_block()
+            .await?;
+        let response = client
+            .account_info_at_block(&account_id.to_string(), last_committed_block)
+            .await?;
+        let account_info_at_block: Option<Account> = deserialize_response_result(response)?;
+        assert_eq!(
+            account_info_at_block,
+            Some(account_committed_info_by_id.clone())
+        );
+
+        let response = client
+            .account_info_at_block(&account_id.to_string(), last_committed_block + 1)
+            .await?;
+        assert!(response.error.is_some());
+
         {
             let mut storage = server.pool.access_storage().await?;
             storage
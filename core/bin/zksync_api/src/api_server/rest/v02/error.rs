@@ -9,9 +9,13 @@ use thiserror::Error;
 // Workspace uses
 use zksync_api_types::v02::pagination::{UnknownFromParameter, MAX_LIMIT};
 use zksync_crypto::params::MIN_NFT_TOKEN_ID;
+use zksync_types::tx::error::TxAddError;
 
 // Local uses
-use crate::{api_server::tx_sender::SubmitError, fee_ticker::PriceError};
+use crate::{
+    api_server::{helpers::ACCOUNT_PROOF_BLOCKS_WINDOW, tx_sender::SubmitError},
+    fee_ticker::PriceError,
+};
 
 #[derive(Serialize_repr, Debug, Deserialize_repr, Clone, PartialEq)]
 #[repr(u16)]
@@ -27,6 +31,9 @@ pub enum ErrorCode {
     PaginationLimitTooBig = 206,
     QueryDeserializationError = 207,
     InvalidNFTTokenId = 208,
+    BlockNotFinalized = 209,
+    BlockNotFound = 210,
+    BlockProofUnavailable = 211,
     StorageError = 300,
     TokenNotFound = 500,
     ExternalApiError = 501,
@@ -39,6 +46,12 @@ pub enum ErrorCode {
     InappropriateFeeToken = 606,
     CommunicationCoreServer = 607,
     Toggle2FAError = 608,
+    NonceGapTooBig = 609,
+    TooManyPendingTxs = 610,
+    MempoolFull = 611,
+    ReplacementFeeTooLow = 612,
+    PendingTxNotFound = 613,
+    TxAlreadyExecuted = 614,
     Other = 60_000,
 }
 
@@ -103,6 +116,15 @@ pub enum InvalidDataError {
     PaginationLimitTooBig,
     #[error("NFT token ID should be greater than or equal to {}", MIN_NFT_TOKEN_ID)]
     InvalidNFTTokenId,
+    #[error("Block is not finalized yet")]
+    BlockNotFinalized,
+    #[error("Block is not found")]
+    BlockNotFound,
+    #[error(
+        "Account proofs are only available for the last {} finalized blocks",
+        ACCOUNT_PROOF_BLOCKS_WINDOW
+    )]
+    BlockProofUnavailable,
 }
 
 impl ApiError for InvalidDataError {
@@ -120,6 +142,9 @@ impl ApiError for InvalidDataError {
             Self::TransactionNotFound => ErrorCode::TransactionNotFound,
             Self::PaginationLimitTooBig => ErrorCode::PaginationLimitTooBig,
             Self::InvalidNFTTokenId => ErrorCode::InvalidNFTTokenId,
+            Self::BlockNotFinalized => ErrorCode::BlockNotFinalized,
+            Self::BlockNotFound => ErrorCode::BlockNotFound,
+            Self::BlockProofUnavailable => ErrorCode::BlockProofUnavailable,
         }
     }
 }
@@ -195,6 +220,12 @@ impl ApiError for SubmitError {
             Self::InvalidParams(_) => ErrorCode::InvalidParams,
             Self::UnsupportedFastProcessing => ErrorCode::UnsupportedFastProcessing,
             Self::IncorrectTx(_) => ErrorCode::IncorrectTx,
+            Self::TxAdd(TxAddError::NonceGapTooBig) => ErrorCode::NonceGapTooBig,
+            Self::TxAdd(TxAddError::TooManyPendingTxs) => ErrorCode::TooManyPendingTxs,
+            Self::TxAdd(TxAddError::MempoolFull) => ErrorCode::MempoolFull,
+            Self::TxAdd(TxAddError::ReplacementFeeTooLow) => ErrorCode::ReplacementFeeTooLow,
+            Self::TxAdd(TxAddError::PendingTxNotFound) => ErrorCode::PendingTxNotFound,
+            Self::TxAdd(TxAddError::TxAlreadyExecuted) => ErrorCode::TxAlreadyExecuted,
             Self::TxAdd(_) => ErrorCode::TxAddError,
             Self::InappropriateFeeToken => ErrorCode::InappropriateFeeToken,
             Self::MempoolCommunication(_) => ErrorCode::CommunicationCoreServer,
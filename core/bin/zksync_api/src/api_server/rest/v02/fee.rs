@@ -10,7 +10,9 @@ use actix_web::{
 };
 
 // Workspace uses
-use zksync_api_types::v02::fee::{ApiFee, BatchFeeRequest, TxFeeRequest};
+use zksync_api_types::v02::fee::{
+    ApiFee, ApiFeeQuote, ApiSubsidyProgram, BatchFeeRequest, TxFeeQuoteRequest, TxFeeRequest,
+};
 
 // Local uses
 use super::{error::Error, response::ApiResult};
@@ -45,13 +47,16 @@ async fn get_tx_fee(
     if !token_allowed {
         return Error::from(SubmitError::InappropriateFeeToken).into();
     }
-    // TODO implement subsidies for v02 api ZKS-888
+    // TODO implement CPK subsidies for v02 api ZKS-888
     let res = data
         .tx_sender
-        .ticker
-        .get_fee_from_ticker_in_wei(body.tx_type.into(), body.token_like, body.address)
+        .get_tx_fee_with_subsidy(
+            body.tx_type.into(),
+            body.token_like,
+            body.sender,
+            body.address,
+        )
         .await
-        .map(|fee| fee.normal_fee.into())
         .map_err(Error::from)
         .into();
     metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "get_tx_fee");
@@ -89,6 +94,38 @@ async fn get_batch_fee(
     res
 }
 
+async fn get_tx_fee_quote(
+    data: web::Data<ApiFeeData>,
+    Json(body): Json<TxFeeQuoteRequest>,
+) -> ApiResult<ApiFeeQuote> {
+    let start = Instant::now();
+    let res = data
+        .tx_sender
+        .quote_tx_fee(
+            body.tx_type.into(),
+            body.token_like,
+            body.address,
+            body.sender,
+        )
+        .await
+        .map_err(Error::from)
+        .into();
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "get_tx_fee_quote");
+    res
+}
+
+async fn get_subsidy_programs(data: web::Data<ApiFeeData>) -> ApiResult<Vec<ApiSubsidyProgram>> {
+    let start = Instant::now();
+    let res = data
+        .tx_sender
+        .subsidy_programs_usage()
+        .await
+        .map_err(Error::from)
+        .into();
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "get_subsidy_programs");
+    res
+}
+
 pub fn api_scope(tx_sender: TxSender) -> Scope {
     let data = ApiFeeData::new(tx_sender);
 
@@ -96,6 +133,8 @@ pub fn api_scope(tx_sender: TxSender) -> Scope {
         .app_data(web::Data::new(data))
         .route("", web::post().to(get_tx_fee))
         .route("/batch", web::post().to(get_batch_fee))
+        .route("/quote", web::post().to(get_tx_fee_quote))
+        .route("/subsidies", web::get().to(get_subsidy_programs))
 }
 
 #[cfg(test)]
@@ -108,6 +147,7 @@ mod tests {
         SharedData,
     };
     use crate::fee_ticker::validator::cache::TokenInMemoryCache;
+    use bigdecimal::BigDecimal;
     use chrono::Utc;
     use futures::channel::mpsc;
     use num::rational::Ratio;
@@ -117,9 +157,10 @@ mod tests {
         fee::{ApiTxFeeTypes, TxInBatchFeeRequest},
         ApiVersion,
     };
+    use zksync_config::configs::api::{SubsidizedTxType, SubsidyProgramConfig};
     use zksync_types::{
         tokens::{TokenLike, TokenMarketVolume},
-        Address, Token, TokenId, TokenKind,
+        Address, OutputFeeType, Token, TokenId, TokenKind,
     };
 
     #[actix_rt::test]
@@ -128,7 +169,17 @@ mod tests {
         ignore = "Use `zk test rust-api` command to perform this test"
     )]
     async fn fee_scope() -> anyhow::Result<()> {
-        let cfg = TestServerConfig::default();
+        let mut cfg = TestServerConfig::default();
+        cfg.config.api.common.subsidy_programs = vec![SubsidyProgramConfig {
+            name: "SponsoredTransfers".to_string(),
+            tx_types: vec![SubsidizedTxType::Transfer],
+            tokens: vec![TokenId(2)],
+            senders: Vec::new(),
+            recipients: Vec::new(),
+            subsidy_percent: 100,
+            budget_usd_scaled: 1_000_000_000_000,
+            daily_cap_usd_scaled: 1_000_000_000_000,
+        }];
 
         let (mempool_tx_request_sender, _mempool_tx_request_receiver) = mpsc::channel(100);
 
@@ -197,6 +248,64 @@ mod tests {
         assert_eq!(api_fee.gas_fee, BigUint::from(1u32));
         assert_eq!(api_fee.zkp_fee, BigUint::from(1u32));
         assert_eq!(api_fee.total_fee, BigUint::from(2u32));
+        assert!(api_fee.subsidy.is_none());
+
+        let response = client
+            .get_txs_fee(ApiTxFeeTypes::Transfer, address, allowed_token.clone())
+            .await?;
+        let api_fee: ApiFee = deserialize_response_result(response)?;
+        assert_eq!(api_fee.total_fee, BigUint::from(0u32));
+        let subsidy = api_fee.subsidy.expect("Transfer fee must be subsidized");
+        assert_eq!(subsidy.program, "SponsoredTransfers");
+        assert!(subsidy.normal_fee > BigUint::from(0u32));
+
+        let response = client.get_subsidy_programs().await?;
+        let programs: Vec<ApiSubsidyProgram> = deserialize_response_result(response)?;
+        assert_eq!(programs.len(), 1);
+        assert_eq!(programs[0].name, "SponsoredTransfers");
+        assert_eq!(programs[0].subsidy_percent, 100);
+        assert_eq!(programs[0].used_usd, BigDecimal::from(0));
+
+        let sender = Address::repeat_byte(0x42);
+        let response = client
+            .get_tx_fee_quote(
+                ApiTxFeeTypes::Withdraw,
+                address,
+                allowed_token.clone(),
+                sender,
+            )
+            .await?;
+        let quote: ApiFeeQuote = deserialize_response_result(response)?;
+        assert_eq!(quote.sender, sender);
+        assert_eq!(quote.address, address);
+        assert_eq!(quote.fee_type, OutputFeeType::Withdraw);
+        assert_eq!(quote.token_id, TokenId(2));
+        assert_eq!(quote.total_fee, BigUint::from(2u32));
+        assert!(quote.valid_until > quote.valid_from);
+
+        // The unused quote is returned again instead of creating a new one.
+        let response = client
+            .get_tx_fee_quote(
+                ApiTxFeeTypes::Withdraw,
+                address,
+                allowed_token.clone(),
+                sender,
+            )
+            .await?;
+        let same_quote: ApiFeeQuote = deserialize_response_result(response)?;
+        assert_eq!(same_quote.quote_id, quote.quote_id);
+
+        // The quotes given to the other accounts are not shared.
+        let response = client
+            .get_tx_fee_quote(
+                ApiTxFeeTypes::Withdraw,
+                address,
+                allowed_token.clone(),
+                Address::repeat_byte(0x43),
+            )
+            .await?;
+        let other_quote: ApiFeeQuote = deserialize_response_result(response)?;
+        assert_ne!(other_quote.quote_id, quote.quote_id);
 
         let tx = TxInBatchFeeRequest {
             tx_type: ApiTxFeeTypes::Withdraw,
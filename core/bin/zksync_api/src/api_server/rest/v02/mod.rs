@@ -10,7 +10,7 @@ use zksync_config::ZkSyncConfig;
 use zksync_types::network::Network;
 
 // Local uses
-use crate::api_server::tx_sender::TxSender;
+use crate::api_server::{helpers::AccountTreesCache, tx_sender::TxSender};
 
 mod account;
 mod block;
@@ -36,6 +36,7 @@ pub(crate) fn api_scope(
     tx_sender: TxSender,
     zk_config: &ZkSyncConfig,
     network_status: SharedNetworkStatus,
+    account_trees: AccountTreesCache,
 ) -> Scope {
     let data = SharedData {
         net: zk_config.chain.eth.network,
@@ -46,6 +47,7 @@ pub(crate) fn api_scope(
         .service(account::api_scope(
             tx_sender.pool.clone(),
             tx_sender.tokens.clone(),
+            account_trees,
             zk_config.eth_watch.confirmations_for_eth_event,
         ))
         .service(block::api_scope(
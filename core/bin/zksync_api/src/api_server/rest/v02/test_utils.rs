@@ -21,6 +21,7 @@ use zksync_api_types::v02::Response;
 use zksync_config::ZkSyncConfig;
 use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
 use zksync_storage::{
+    chain::block::records::BlockChunksUsage,
     chain::operations::records::NewExecutedPriorityOperation,
     chain::operations::OperationsSchema,
     prover::ProverSchema,
@@ -499,7 +500,9 @@ impl TestServerConfig {
                     Some((id, op)),
                     100,
                     100u32.into(),
+                    None,
                     Default::default(),
+                    None,
                 )
                 .await?;
             storage
@@ -585,7 +588,9 @@ impl TestServerConfig {
                         Some((id, op)),
                         100,
                         100u32.into(),
+                        None,
                         Default::default(),
+                        None,
                     )
                     .await?;
                 let eth_tx_hash = dummy_ethereum_tx_hash(id);
@@ -626,7 +631,9 @@ impl TestServerConfig {
                         Some((id, op)),
                         100,
                         100u32.into(),
+                        None,
                         Default::default(),
+                        None,
                     )
                     .await?;
                 storage
@@ -872,6 +879,14 @@ impl FeeTickerInfo for DummyFeeTickerInfo {
         Ok(BigUint::from(1u64))
     }
 
+    async fn get_recent_gas_prices_wei(&self, _limit: u32) -> Result<Vec<BigUint>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn get_recent_blocks_chunks(&self, _limit: u32) -> Result<Vec<BlockChunksUsage>, Error> {
+        Ok(Vec::new())
+    }
+
     async fn get_token(&self, token: TokenLike) -> Result<Token, Error> {
         Ok(match token {
             TokenLike::Id(id) => Token {
@@ -912,6 +927,11 @@ pub fn get_test_ticker_config() -> TickerConfig {
         scale_fee_coefficient: Ratio::new(BigUint::from(150u32), BigUint::from(100u32)),
         max_blocks_to_aggregate: 5,
         subsidy_cpk_price_usd: scaled_u64_to_ratio(SUBSIDY_CPK_PRICE_USD_SCALED),
+        gas_price_samples: 20,
+        min_gas_price_wei: BigUint::zero(),
+        max_gas_price_wei: BigUint::from(u64::MAX),
+        fill_rate_blocks: 50,
+        min_block_fill_rate: Ratio::new(BigUint::from(1u32), BigUint::from(10u32)),
     }
 }
 pub fn dummy_fee_ticker(
@@ -9,15 +9,12 @@ use actix_web::{
 };
 
 // Workspace uses
-use zksync_api_types::{
-    v02::transaction::{
-        ApiTxBatch, IncomingTxBatch, L1Receipt, L1Transaction, Receipt, SubmitBatchResponse,
-        Toggle2FA, Toggle2FAResponse, Transaction, TransactionData, TxData, TxHashSerializeWrapper,
-        TxInBlockStatus,
-    },
-    TxWithSignature,
+use zksync_api_types::v02::transaction::{
+    ApiTxBatch, CancelTx, CancelTxResponse, IncomingTx, IncomingTxBatch, L1Receipt, L1Transaction,
+    L2Receipt, Receipt, SubmitBatchResponse, Toggle2FA, Toggle2FAResponse, Transaction,
+    TransactionData, TxData, TxHashSerializeWrapper, TxInBlockStatus, TxSimulationResult,
 };
-use zksync_types::{tx::TxHash, EthBlockId};
+use zksync_types::{tx::TxHash, EthBlockId, ZkSyncTx};
 
 // Local uses
 use super::{error::Error, response::ApiResult};
@@ -53,7 +50,23 @@ impl ApiTransactionData {
         {
             Ok(Some(receipt))
         }
-        // 2. Try to find the pending operation.
+        // 2. Try to find the transaction replaced in the mempool.
+        else if storage
+            .chain()
+            .mempool_schema()
+            .get_replacement_tx_hash(tx_hash)
+            .await
+            .map_err(Error::storage)?
+            .is_some()
+        {
+            Ok(Some(Receipt::L2(L2Receipt {
+                tx_hash,
+                rollup_block: None,
+                status: TxInBlockStatus::Replaced,
+                fail_reason: None,
+            })))
+        }
+        // 3. Try to find the pending operation.
         else if let Some(op) = storage
             .chain()
             .mempool_schema()
@@ -68,7 +81,7 @@ impl ApiTransactionData {
                 id: op.serial_id,
             })))
         }
-        // 3. No operation found, return nothing.
+        // 4. No operation found, return nothing.
         else {
             Ok(None)
         }
@@ -89,6 +102,14 @@ impl ApiTransactionData {
             .map_err(Error::storage)?
         {
             Ok(Some(data))
+        } else if let Some(data) = storage
+            .chain()
+            .mempool_schema()
+            .get_replaced_tx_data(tx_hash)
+            .await
+            .map_err(Error::storage)?
+        {
+            Ok(Some(data))
         } else if let Some(op) = storage
             .chain()
             .mempool_schema()
@@ -162,12 +183,12 @@ async fn tx_data(
 
 async fn submit_tx(
     data: web::Data<ApiTransactionData>,
-    Json(body): Json<TxWithSignature>,
+    Json(body): Json<IncomingTx>,
 ) -> ApiResult<TxHashSerializeWrapper> {
     let start = Instant::now();
     let tx_hash = data
         .tx_sender
-        .submit_tx(body.tx, body.signature, None)
+        .submit_tx(body.tx, body.signature, body.fee_quote_id, None)
         .await;
 
     if let Err(err) = &tx_hash {
@@ -225,6 +246,47 @@ async fn toggle_2fa(
     response.into()
 }
 
+async fn cancel_tx(
+    data: web::Data<ApiTransactionData>,
+    Json(cancel_tx): Json<CancelTx>,
+) -> ApiResult<CancelTxResponse> {
+    let start = Instant::now();
+    let response = data
+        .tx_sender
+        .cancel_tx(cancel_tx)
+        .await
+        .map_err(Error::from);
+
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "cancel_tx");
+    response.into()
+}
+
+async fn simulate_tx(
+    data: web::Data<ApiTransactionData>,
+    Json(tx): Json<ZkSyncTx>,
+) -> ApiResult<TxSimulationResult> {
+    let start = Instant::now();
+    let response = data.tx_sender.simulate_tx(tx).await.map_err(Error::from);
+
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "simulate_tx");
+    response.into()
+}
+
+async fn simulate_batch(
+    data: web::Data<ApiTransactionData>,
+    Json(txs): Json<Vec<ZkSyncTx>>,
+) -> ApiResult<TxSimulationResult> {
+    let start = Instant::now();
+    let response = data
+        .tx_sender
+        .simulate_batch(txs)
+        .await
+        .map_err(Error::from);
+
+    metrics::histogram!("api", start.elapsed(), "type" => "v02", "endpoint_name" => "simulate_batch");
+    response.into()
+}
+
 async fn get_batch(
     data: web::Data<ApiTransactionData>,
     batch_hash: web::Path<TxHash>,
@@ -245,7 +307,10 @@ pub fn api_scope(tx_sender: TxSender) -> Scope {
         .route("{tx_hash}/data", web::get().to(tx_data))
         .route("/batches", web::post().to(submit_batch))
         .route("/batches/{batch_hash}", web::get().to(get_batch))
+        .route("/batches/simulate", web::post().to(simulate_batch))
+        .route("/simulate", web::post().to(simulate_tx))
         .route("/toggle2FA", web::post().to(toggle_2fa))
+        .route("/cancel", web::post().to(cancel_tx))
 }
 
 #[cfg(test)]
@@ -265,9 +330,12 @@ mod tests {
     use std::collections::HashMap;
     use std::str::FromStr;
     use tokio::task::JoinHandle;
-    use zksync_api_types::v02::{
-        transaction::{L2Receipt, TxHashSerializeWrapper},
-        ApiVersion,
+    use zksync_api_types::{
+        v02::{
+            transaction::{L2Receipt, TxHashSerializeWrapper},
+            ApiVersion,
+        },
+        TxWithSignature,
     };
     use zksync_mempool::MempoolTransactionRequest;
     use zksync_types::{
@@ -291,9 +359,15 @@ mod tests {
                     MempoolTransactionRequest::NewPriorityOps(_, _, resp) => {
                         resp.send(Ok(())).unwrap_or_default()
                     }
+                    MempoolTransactionRequest::RetractPriorityOps(_, resp) => {
+                        resp.send(Ok(())).unwrap_or_default()
+                    }
                     MempoolTransactionRequest::NewTxsBatch(_, _, resp) => {
                         resp.send(Ok(())).unwrap_or_default()
                     }
+                    MempoolTransactionRequest::CancelTx(_, tx_hash, resp) => {
+                        resp.send(Ok(vec![tx_hash])).unwrap_or_default()
+                    }
                 }
             }
         });
@@ -365,6 +439,21 @@ mod tests {
         let tx_hash: TxHash = deserialize_response_result(response)?;
         assert_eq!(tx.hash(), tx_hash);
 
+        // The sender of the generated transactions doesn't exist in the committed state.
+        let response = client.simulate_tx(&tx).await?;
+        let simulation: TxSimulationResult = deserialize_response_result(response)?;
+        assert!(!simulation.success);
+        assert_eq!(
+            simulation.fail_reason.as_deref(),
+            Some("Account does not exist")
+        );
+        assert_eq!(simulation.failed_tx_index, None);
+
+        let response = client.simulate_batch(&[tx.clone(), tx.clone()]).await?;
+        let simulation: TxSimulationResult = deserialize_response_result(response)?;
+        assert!(!simulation.success);
+        assert_eq!(simulation.failed_tx_index, Some(1));
+
         let TestTransactions { acc, txs } = TestServerConfig::gen_zk_txs(1_00);
         let eth = Token::new(TokenId(0), Default::default(), "ETH", 18, TokenKind::ERC20);
         let (good_batch, expected_tx_hashes): (Vec<_>, Vec<_>) = txs
@@ -424,7 +513,7 @@ mod tests {
             storage
                 .chain()
                 .mempool_schema()
-                .insert_batch(&txs, Vec::new())
+                .insert_batch(&txs, Vec::new(), &Default::default())
                 .await?;
         };
 
@@ -470,11 +559,14 @@ mod tests {
             storage
                 .chain()
                 .mempool_schema()
-                .insert_tx(&SignedZkSyncTx {
-                    tx,
-                    eth_sign_data: None,
-                    created_at: Utc::now(),
-                })
+                .insert_tx(
+                    &SignedZkSyncTx {
+                        tx,
+                        eth_sign_data: None,
+                        created_at: Utc::now(),
+                    },
+                    &Default::default(),
+                )
                 .await?;
 
             tx_hash
@@ -11,6 +11,7 @@ pub enum RpcErrorCodes {
     IncorrectTx = 103,
     FeeTooLow = 104,
     InappropriateFeeToken = 105,
+    NonceGapTooBig = 106,
 
     MissingEthSignature = 200,
     EIP1271SignatureVerificationFail = 201,
@@ -22,6 +23,10 @@ pub enum RpcErrorCodes {
     OperationsLimitReached = 302,
     UnsupportedFastProcessing = 303,
     Toggle2FA = 304,
+    TooManyPendingTxs = 305,
+    MempoolFull = 306,
+    PendingTxNotFound = 307,
+    TxAlreadyExecuted = 308,
 }
 
 impl From<TxAddError> for RpcErrorCodes {
@@ -41,6 +46,12 @@ impl From<TxAddError> for RpcErrorCodes {
             TxAddError::BatchTooBig => Self::Other,
             TxAddError::BatchWithdrawalsOverload => Self::Other,
             TxAddError::EthSignaturesLimitExceeded => Self::Other,
+            TxAddError::NonceGapTooBig => Self::NonceGapTooBig,
+            TxAddError::TooManyPendingTxs => Self::TooManyPendingTxs,
+            TxAddError::MempoolFull => Self::MempoolFull,
+            TxAddError::ReplacementFeeTooLow => Self::FeeTooLow,
+            TxAddError::PendingTxNotFound => Self::PendingTxNotFound,
+            TxAddError::TxAlreadyExecuted => Self::TxAlreadyExecuted,
         }
     }
 }
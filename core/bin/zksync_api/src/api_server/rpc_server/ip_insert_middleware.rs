@@ -48,6 +48,10 @@ fn get_call_with_ip_if_needed(
     // Methods, which should have the information about the ip appended to them
     let methods_with_ip: HashMap<&'static str, MethodWithIpDescription> = HashMap::from_iter([
         ("tx_submit", MethodWithIpDescription::new(1, 4)),
+        (
+            "tx_submit_with_fee_quote",
+            MethodWithIpDescription::new(3, 4),
+        ),
         ("submit_txs_batch", MethodWithIpDescription::new(1, 3)),
         ("get_tx_fee", MethodWithIpDescription::new(3, 4)),
         (
@@ -30,7 +30,10 @@ pub mod types;
 
 pub use self::rpc_trait::Rpc;
 use self::types::*;
-use super::tx_sender::TxSender;
+use super::{
+    helpers::{AccountTreesCache, ACCOUNT_TREES_CACHE_SIZE},
+    tx_sender::TxSender,
+};
 use crate::fee_ticker::FeeTicker;
 use ip_insert_middleware::IpInsertMiddleWare;
 use zksync_mempool::MempoolTransactionRequest;
@@ -40,6 +43,7 @@ pub struct RpcApp {
     cache_of_executed_priority_operations: AsyncLruCache<u32, StoredExecutedPriorityOperation>,
     cache_of_transaction_receipts: AsyncLruCache<Vec<u8>, TxReceiptResponse>,
     cache_of_complete_withdrawal_tx_hashes: AsyncLruCache<TxHash, String>,
+    account_trees: AccountTreesCache,
 
     pub confirmations_for_eth_event: u64,
 
@@ -71,6 +75,7 @@ impl RpcApp {
             cache_of_executed_priority_operations: AsyncLruCache::new(api_requests_caches_size),
             cache_of_transaction_receipts: AsyncLruCache::new(api_requests_caches_size),
             cache_of_complete_withdrawal_tx_hashes: AsyncLruCache::new(api_requests_caches_size),
+            account_trees: AccountTreesCache::new(ACCOUNT_TREES_CACHE_SIZE),
 
             confirmations_for_eth_event,
 
@@ -6,21 +6,26 @@ use jsonrpc_core::{Error, Result};
 // Workspace uses
 use zksync_api_types::{
     v02::{
-        fee::ApiTxFeeTypes,
+        fee::{ApiFeeQuote, ApiTxFeeTypes},
         token::ApiNFT,
-        transaction::{Toggle2FA, Toggle2FAResponse},
+        transaction::{
+            CancelTx, CancelTxResponse, Toggle2FA, Toggle2FAResponse, TxSimulationResult,
+        },
     },
     TxWithSignature,
 };
 use zksync_crypto::params::MIN_NFT_TOKEN_ID;
 use zksync_types::{
     tx::{EthBatchSignatures, TxEthSignatureVariant, TxHash},
-    AccountId, Address, Fee, Token, TokenId, TokenLike, TotalFee, TxFeeTypes, ZkSyncTx,
+    AccountId, AccountMerkleProof, Address, BlockNumber, Fee, Token, TokenId, TokenLike, TotalFee,
+    TxFeeTypes, ZkSyncTx,
 };
 // Local uses
 use crate::{
     api_server::{
-        helpers::get_depositing, rpc_server::error::RpcErrorCodes, tx_sender::SubmitError,
+        helpers::{get_depositing, AccountTreesCache, ACCOUNT_PROOF_BLOCKS_WINDOW},
+        rpc_server::error::RpcErrorCodes,
+        tx_sender::SubmitError,
     },
     fee_ticker::TokenPriceRequestType,
 };
@@ -125,13 +130,30 @@ impl RpcApp {
                     committed: true,
                     verified: stored_receipt.verified,
                 }),
+                replaced_by: None,
             }
         } else {
+            let replaced_by = self
+                .access_storage()
+                .await?
+                .chain()
+                .mempool_schema()
+                .get_replacement_tx_hash(tx_hash)
+                .await
+                .map_err(|err| {
+                    vlog::warn!(
+                        "Internal Server Error: '{}'; input: {}",
+                        err,
+                        tx_hash.to_string()
+                    );
+                    Error::internal_error()
+                })?;
             TransactionInfoResp {
                 executed: false,
                 success: None,
                 fail_reason: None,
                 block: None,
+                replaced_by,
             }
         })
     }
@@ -169,6 +191,38 @@ impl RpcApp {
         result.map_err(Error::from)
     }
 
+    pub async fn _impl_tx_submit_with_fee_quote(
+        self,
+        tx: Box<ZkSyncTx>,
+        signature: Box<TxEthSignatureVariant>,
+        fee_quote_id: i64,
+        extracted_request_metadata: Option<RequestMetadata>,
+    ) -> Result<TxHash> {
+        let start = Instant::now();
+
+        let result = self
+            .tx_sender
+            .submit_tx(
+                *tx,
+                *signature,
+                Some(fee_quote_id),
+                extracted_request_metadata,
+            )
+            .await;
+        if let Err(err) = &result {
+            let err_label = match err {
+                SubmitError::IncorrectTx(err) => err.clone(),
+                SubmitError::TxAdd(err) => err.to_string(),
+                _ => "other".to_string(),
+            };
+            let labels = vec![("stage", "api".to_string()), ("error", err_label)];
+            metrics::increment_counter!("rejected_txs", &labels);
+        }
+
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "tx_submit_with_fee_quote");
+        result.map_err(Error::from)
+    }
+
     pub async fn _impl_submit_txs_batch(
         self,
         txs: Vec<TxWithSignature>,
@@ -203,6 +257,26 @@ impl RpcApp {
         result.map_err(Error::from)
     }
 
+    pub async fn _impl_simulate_tx(self, tx: Box<ZkSyncTx>) -> Result<TxSimulationResult> {
+        let start = Instant::now();
+        let result = self.tx_sender.simulate_tx(*tx).await.map_err(Error::from);
+
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "simulate_tx");
+        result
+    }
+
+    pub async fn _impl_simulate_txs_batch(self, txs: Vec<ZkSyncTx>) -> Result<TxSimulationResult> {
+        let start = Instant::now();
+        let result = self
+            .tx_sender
+            .simulate_batch(txs)
+            .await
+            .map_err(Error::from);
+
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "simulate_txs_batch");
+        result
+    }
+
     pub async fn _impl_contract_address(self) -> Result<ContractAddressResp> {
         let start = Instant::now();
         let mut storage = self.access_storage().await?;
@@ -317,6 +391,22 @@ impl RpcApp {
         Ok(fee)
     }
 
+    pub async fn _impl_get_tx_fee_quote(
+        self,
+        tx_type: ApiTxFeeTypes,
+        address: Address,
+        token: TokenLike,
+        sender: Address,
+    ) -> Result<ApiFeeQuote> {
+        let start = Instant::now();
+        let quote = self
+            .tx_sender
+            .quote_tx_fee(tx_type.into(), token, address, sender)
+            .await?;
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "get_tx_fee_quote");
+        Ok(quote)
+    }
+
     pub async fn _impl_get_txs_batch_fee_in_wei(
         self,
         tx_types: Vec<ApiTxFeeTypes>,
@@ -422,6 +512,64 @@ impl RpcApp {
         Ok(owner_id)
     }
 
+    pub async fn _impl_get_account_proof(
+        self,
+        account_id: AccountId,
+        token: TokenLike,
+        block: Option<BlockNumber>,
+    ) -> Result<AccountMerkleProof> {
+        let start = Instant::now();
+        let mut storage = self.access_storage().await?;
+
+        let token_id = self
+            .tx_sender
+            .tokens
+            .get_token(&mut storage, token)
+            .await
+            .map_err(|err| {
+                vlog::warn!("Internal Server Error: '{}'; input: N/A", err);
+                Error::internal_error()
+            })?
+            .ok_or_else(|| Error::invalid_params("Token not found"))?
+            .id;
+        let last_finalized_block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await
+            .map_err(|err| {
+                vlog::warn!("Internal Server Error: '{}'; input: N/A", err);
+                Error::internal_error()
+            })?;
+        let block = block.unwrap_or(last_finalized_block);
+        if block > last_finalized_block {
+            return Err(Error::invalid_params("Block is not finalized yet"));
+        }
+        if !AccountTreesCache::is_proof_available(block, last_finalized_block) {
+            return Err(Error::invalid_params(format!(
+                "Account proofs are only available for the last {} finalized blocks",
+                ACCOUNT_PROOF_BLOCKS_WINDOW
+            )));
+        }
+
+        let proof = self
+            .account_trees
+            .get_account_proof(&mut storage, account_id, token_id, block)
+            .await
+            .map_err(|err| {
+                vlog::warn!(
+                    "Internal Server Error: '{}'; input: {}, {}",
+                    err,
+                    account_id,
+                    block
+                );
+                Error::internal_error()
+            })?;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "get_account_proof");
+        Ok(proof)
+    }
+
     pub async fn _impl_toggle_2fa(self, toggle_2fa: Toggle2FA) -> Result<Toggle2FAResponse> {
         let start = Instant::now();
         let response = self
@@ -434,6 +582,18 @@ impl RpcApp {
         response
     }
 
+    pub async fn _impl_cancel_tx(self, cancel_tx: CancelTx) -> Result<CancelTxResponse> {
+        let start = Instant::now();
+        let response = self
+            .tx_sender
+            .cancel_tx(cancel_tx)
+            .await
+            .map_err(Error::from);
+
+        metrics::histogram!("api", start.elapsed(), "type" => "rpc", "endpoint_name" => "cancel_tx");
+        response
+    }
+
     pub async fn _impl_get_nft_id_by_tx_hash(self, tx_hash: TxHash) -> Result<Option<TokenId>> {
         let start = Instant::now();
 
@@ -7,16 +7,19 @@ use jsonrpc_derive::rpc;
 // Workspace uses
 use zksync_api_types::{
     v02::{
-        fee::ApiTxFeeTypes,
+        fee::{ApiFeeQuote, ApiTxFeeTypes},
         token::ApiNFT,
-        transaction::{Toggle2FA, Toggle2FAResponse},
+        transaction::{
+            CancelTx, CancelTxResponse, Toggle2FA, Toggle2FAResponse, TxSimulationResult,
+        },
     },
     TxWithSignature,
 };
 use zksync_crypto::params::ZKSYNC_VERSION;
 use zksync_types::{
     tx::{EthBatchSignatures, TxEthSignatureVariant, TxHash},
-    AccountId, Address, Fee, Token, TokenId, TokenLike, TotalFee, ZkSyncTx,
+    AccountId, AccountMerkleProof, Address, BlockNumber, Fee, Token, TokenId, TokenLike, TotalFee,
+    ZkSyncTx,
 };
 
 // Local uses
@@ -51,6 +54,16 @@ pub trait Rpc {
         extracted_request_metadata: Option<RequestMetadata>,
     ) -> BoxFutureResult<TxHash>;
 
+    /// Submits the transaction which pays the fee quoted with `get_tx_fee_quote`.
+    #[rpc(name = "tx_submit_with_fee_quote", returns = "TxHash")]
+    fn tx_submit_with_fee_quote(
+        &self,
+        tx: Box<ZkSyncTx>,
+        signature: Box<TxEthSignatureVariant>,
+        fee_quote_id: i64,
+        extracted_request_metadata: Option<RequestMetadata>,
+    ) -> BoxFutureResult<TxHash>;
+
     #[rpc(name = "submit_txs_batch", returns = "Vec<TxHash>")]
     fn submit_txs_batch(
         &self,
@@ -59,6 +72,12 @@ pub trait Rpc {
         extracted_request_metadata: Option<RequestMetadata>,
     ) -> BoxFutureResult<Vec<TxHash>>;
 
+    #[rpc(name = "simulate_tx", returns = "TxSimulationResult")]
+    fn simulate_tx(&self, tx: Box<ZkSyncTx>) -> BoxFutureResult<TxSimulationResult>;
+
+    #[rpc(name = "simulate_txs_batch", returns = "TxSimulationResult")]
+    fn simulate_txs_batch(&self, txs: Vec<ZkSyncTx>) -> BoxFutureResult<TxSimulationResult>;
+
     #[rpc(name = "contract_address", returns = "ContractAddressResp")]
     fn contract_address(&self) -> BoxFutureResult<ContractAddressResp>;
 
@@ -86,6 +105,17 @@ pub trait Rpc {
         extracted_request_metadata: Option<RequestMetadata>,
     ) -> BoxFutureResult<TotalFee>;
 
+    /// Returns the fee for the transaction of the `sender` which is accepted until the quote expires,
+    /// even if the actual fee grows.
+    #[rpc(name = "get_tx_fee_quote", returns = "ApiFeeQuote")]
+    fn get_tx_fee_quote(
+        &self,
+        tx_type: ApiTxFeeTypes,
+        address: Address,
+        token_like: TokenLike,
+        sender: Address,
+    ) -> BoxFutureResult<ApiFeeQuote>;
+
     #[rpc(name = "get_token_price", returns = "BigDecimal")]
     fn get_token_price(&self, token_like: TokenLike) -> BoxFutureResult<BigDecimal>;
 
@@ -105,9 +135,20 @@ pub trait Rpc {
     #[rpc(name = "get_nft_owner", returns = "Option<AccountId>")]
     fn get_nft_owner(&self, id: TokenId) -> BoxFutureResult<Option<AccountId>>;
 
+    #[rpc(name = "get_account_proof", returns = "AccountMerkleProof")]
+    fn get_account_proof(
+        &self,
+        account_id: AccountId,
+        token: TokenLike,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<AccountMerkleProof>;
+
     #[rpc(name = "toggle_2fa", returns = "Toggle2FAResponse")]
     fn toggle_2fa(&self, toggle_2fa: Toggle2FA) -> BoxFutureResult<Toggle2FAResponse>;
 
+    #[rpc(name = "cancel_tx", returns = "CancelTxResponse")]
+    fn cancel_tx(&self, cancel_tx: CancelTx) -> BoxFutureResult<CancelTxResponse>;
+
     #[rpc(name = "get_nft_id_by_tx_hash", returns = "Option<TokenId>")]
     fn get_nft_id_by_tx_hash(&self, tx_hash: TxHash) -> BoxFutureResult<Option<TokenId>>;
 }
@@ -136,6 +177,17 @@ impl Rpc for RpcApp {
         spawn!(self._impl_tx_submit(tx, signature, fast_processing, meta))
     }
 
+    // Important: the last parameter should have name `meta` and be of type `RequestMetadata`
+    fn tx_submit_with_fee_quote(
+        &self,
+        tx: Box<ZkSyncTx>,
+        signature: Box<TxEthSignatureVariant>,
+        fee_quote_id: i64,
+        meta: Option<RequestMetadata>,
+    ) -> BoxFutureResult<TxHash> {
+        spawn!(self._impl_tx_submit_with_fee_quote(tx, signature, fee_quote_id, meta))
+    }
+
     // Important: the last parameter should have name `meta` and be of type `RequestMetadata`
     fn submit_txs_batch(
         &self,
@@ -146,6 +198,14 @@ impl Rpc for RpcApp {
         spawn!(self._impl_submit_txs_batch(txs, eth_signatures, meta))
     }
 
+    fn simulate_tx(&self, tx: Box<ZkSyncTx>) -> BoxFutureResult<TxSimulationResult> {
+        spawn!(self._impl_simulate_tx(tx))
+    }
+
+    fn simulate_txs_batch(&self, txs: Vec<ZkSyncTx>) -> BoxFutureResult<TxSimulationResult> {
+        spawn!(self._impl_simulate_txs_batch(txs))
+    }
+
     fn contract_address(&self) -> BoxFutureResult<ContractAddressResp> {
         spawn!(self._impl_contract_address())
     }
@@ -176,6 +236,16 @@ impl Rpc for RpcApp {
         spawn!(self._impl_get_txs_batch_fee_in_wei(tx_types, addresses, token_like, meta))
     }
 
+    fn get_tx_fee_quote(
+        &self,
+        tx_type: ApiTxFeeTypes,
+        address: Address,
+        token_like: TokenLike,
+        sender: Address,
+    ) -> BoxFutureResult<ApiFeeQuote> {
+        spawn!(self._impl_get_tx_fee_quote(tx_type, address, token_like, sender))
+    }
+
     fn get_token_price(&self, token_like: TokenLike) -> BoxFutureResult<BigDecimal> {
         spawn!(self._impl_get_token_price(token_like))
     }
@@ -203,10 +273,23 @@ impl Rpc for RpcApp {
         spawn!(self._impl_get_nft_owner(id))
     }
 
+    fn get_account_proof(
+        &self,
+        account_id: AccountId,
+        token: TokenLike,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<AccountMerkleProof> {
+        spawn!(self._impl_get_account_proof(account_id, token, block))
+    }
+
     fn toggle_2fa(&self, toggle_2fa: Toggle2FA) -> BoxFutureResult<Toggle2FAResponse> {
         spawn!(self._impl_toggle_2fa(toggle_2fa))
     }
 
+    fn cancel_tx(&self, cancel_tx: CancelTx) -> BoxFutureResult<CancelTxResponse> {
+        spawn!(self._impl_cancel_tx(cancel_tx))
+    }
+
     fn get_nft_id_by_tx_hash(&self, tx_hash: TxHash) -> BoxFutureResult<Option<TokenId>> {
         spawn!(self._impl_get_nft_id_by_tx_hash(tx_hash))
     }
@@ -12,7 +12,7 @@ use zksync_api_types::v02::{
 use zksync_crypto::params::{MIN_NFT_TOKEN_ID, NFT_TOKEN_ID_VAL};
 use zksync_storage::StorageProcessor;
 use zksync_token_db_cache::TokenDBCache;
-use zksync_types::{Account, AccountId, Address, Nonce, PubKeyHash, TokenId};
+use zksync_types::{tx::TxHash, Account, AccountId, Address, Nonce, PubKeyHash, TokenId};
 use zksync_utils::BigUintSerdeWrapper;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -110,6 +110,8 @@ pub struct TransactionInfoResp {
     pub success: Option<bool>,
     pub fail_reason: Option<String>,
     pub block: Option<BlockInfo>,
+    /// Hash of the transaction that replaced this one in the mempool, if any.
+    pub replaced_by: Option<TxHash>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -10,7 +10,7 @@ use std::{
 
 // External uses
 use bigdecimal::BigDecimal;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use futures::{
     channel::{mpsc, oneshot},
     prelude::*,
@@ -22,10 +22,16 @@ use thiserror::Error;
 
 // Workspace uses
 use zksync_api_types::{
-    v02::transaction::{SubmitBatchResponse, Toggle2FA, Toggle2FAResponse, TxHashSerializeWrapper},
+    v02::{
+        fee::{ApiFee, ApiFeeQuote, ApiFeeSubsidy, ApiSubsidyProgram},
+        transaction::{
+            CancelTx, CancelTxResponse, SubmitBatchResponse, Toggle2FA, Toggle2FAResponse,
+            TxHashSerializeWrapper, TxSimulationResult,
+        },
+    },
     TxWithSignature,
 };
-use zksync_storage::misc::records::Subsidy;
+use zksync_storage::misc::records::{FeeQuoteUsage, Subsidy};
 use zksync_storage::{chain::account::records::EthAccountType, ConnectionPool};
 use zksync_token_db_cache::TokenDBCache;
 use zksync_types::{
@@ -33,10 +39,12 @@ use zksync_types::{
         EthBatchSignData, EthBatchSignatures, EthSignData, Order, SignedZkSyncTx, TxEthSignature,
         TxEthSignatureVariant, TxHash,
     },
-    AccountId, Address, PubKeyHash, Token, TokenId, TokenLike, TxFeeTypes, ZkSyncTx, H160,
+    AccountId, Address, Fee, OutputFeeType, PubKeyHash, Token, TokenId, TokenLike, TxFeeTypes,
+    ZkSyncTx, H160,
 };
 use zksync_utils::{
-    big_decimal_to_ratio, biguint_to_big_decimal, ratio_to_scaled_u64, scaled_big_decimal_to_ratio,
+    big_decimal_to_ratio, biguint_to_big_decimal, ratio_to_big_decimal, ratio_to_scaled_u64,
+    scaled_big_decimal_to_ratio,
 };
 
 // Local uses
@@ -44,17 +52,19 @@ use crate::{
     api_server::forced_exit_checker::{ForcedExitAccountAgeChecker, ForcedExitChecker},
     fee_ticker::{ResponseBatchFee, ResponseFee, TokenPriceRequestType},
     signature_checker::{
-        BatchRequest, OrderRequest, RequestData, Toggle2FARequest, TxRequest, VerifiedTx,
-        VerifySignatureRequest,
+        BatchRequest, CancelTxRequest, OrderRequest, RequestData, Toggle2FARequest, TxRequest,
+        VerifiedTx, VerifySignatureRequest,
     },
     tx_error::Toggle2FAError,
     utils::block_details_cache::BlockDetailsCache,
 };
-use zksync_config::configs::api::{CommonApiConfig, TokenConfig};
+use zksync_config::configs::api::{
+    CommonApiConfig, SubsidizedTxType, SubsidyProgramConfig, TokenConfig,
+};
 use zksync_mempool::MempoolTransactionRequest;
 use zksync_types::tx::error::TxAddError;
 
-use super::rpc_server::types::RequestMetadata;
+use super::{rpc_server::types::RequestMetadata, tx_simulator};
 use crate::fee_ticker::{FeeTicker, PriceError};
 
 const VALIDNESS_INTERVAL_MINUTES: i64 = 40;
@@ -80,6 +90,12 @@ pub struct TxSender {
     pub current_subsidy_type: String,
     pub max_subsidy_usd: Ratio<BigUint>,
     pub subsidized_ips: HashSet<String>,
+
+    /// Time during which the quoted fees are accepted.
+    pub fee_quote_validity: Duration,
+
+    /// Programs subsidizing the fees of single transactions.
+    pub subsidy_programs: Vec<SubsidyProgramConfig>,
 }
 
 #[derive(Debug, Error)]
@@ -172,6 +188,9 @@ impl TxSender {
             current_subsidy_type: config.subsidy_name.clone(),
             max_subsidy_usd: config.max_subsidy_usd(),
             subsidized_ips: config.subsidized_ips.clone().into_iter().collect(),
+            fee_quote_validity: Duration::from_std(config.fee_quote_validity())
+                .expect("Invalid fee quote validity"),
+            subsidy_programs: config.subsidy_programs.clone(),
         }
     }
 
@@ -259,18 +278,7 @@ impl TxSender {
         &self,
         toggle_2fa: Toggle2FA,
     ) -> Result<(), SubmitError> {
-        let current_time = Utc::now();
-        let request_time = toggle_2fa.timestamp;
-        let validness_interval = Duration::minutes(VALIDNESS_INTERVAL_MINUTES);
-
-        if current_time - validness_interval > request_time
-            || current_time + validness_interval < request_time
-        {
-            return Err(SubmitError::InvalidParams(format!(
-                "Timestamp differs by more than {} minutes",
-                VALIDNESS_INTERVAL_MINUTES
-            )));
-        }
+        check_request_timestamp(toggle_2fa.timestamp)?;
 
         let message = toggle_2fa.get_ethereum_sign_message().into_bytes();
 
@@ -295,6 +303,111 @@ impl TxSender {
         Ok(())
     }
 
+    /// Removes a pending transaction or batch from the mempool on behalf of the account.
+    pub async fn cancel_tx(&self, cancel_tx: CancelTx) -> Result<CancelTxResponse, SubmitError> {
+        check_request_timestamp(cancel_tx.timestamp)?;
+
+        let address = self
+            .get_address_by_id(cancel_tx.account_id)
+            .await
+            .or(Err(SubmitError::TxAdd(TxAddError::DbError)))?;
+        let message = cancel_tx.get_sign_message().into_bytes();
+
+        if let Some(signature) = &cancel_tx.signature {
+            let signer_pub_key_hash = signature
+                .verify_musig(&message)
+                .map(|pub_key| PubKeyHash::from_pubkey(&pub_key));
+            let account = self
+                .pool
+                .access_storage()
+                .await
+                .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?
+                .chain()
+                .account_schema()
+                .last_committed_state_for_account(cancel_tx.account_id)
+                .await
+                .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?
+                .1;
+            let account_pub_key_hash = account.map(|account| account.pub_key_hash);
+
+            if signer_pub_key_hash.is_none() || signer_pub_key_hash != account_pub_key_hash {
+                return Err(SubmitError::IncorrectTx(
+                    "Cancellation request is not signed by the account key".to_string(),
+                ));
+            }
+        } else if let Some(signature) = cancel_tx.eth_signature.clone() {
+            let (sender, receiever) = oneshot::channel();
+            let request = VerifySignatureRequest {
+                data: RequestData::CancelTx(CancelTxRequest {
+                    sign_data: EthSignData { signature, message },
+                    sender: address,
+                }),
+                response: sender,
+            };
+            send_verify_request_and_recv(request, self.sign_verify_requests.clone(), receiever)
+                .await?;
+        } else {
+            return Err(SubmitError::TxAdd(TxAddError::MissingEthSignature));
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let item = MempoolTransactionRequest::CancelTx(address, cancel_tx.tx_hash, sender);
+        let mut mempool_sender = self.mempool_tx_sender.clone();
+        mempool_sender
+            .send(item)
+            .await
+            .map_err(SubmitError::mempool_communication)?;
+
+        let cancelled_txs = receiver.await.map_err(SubmitError::internal)??;
+        Ok(CancelTxResponse {
+            cancelled_txs: cancelled_txs
+                .into_iter()
+                .map(TxHashSerializeWrapper)
+                .collect(),
+        })
+    }
+
+    /// Executes the transaction against the last committed state without sending it to the mempool.
+    pub async fn simulate_tx(&self, tx: ZkSyncTx) -> Result<TxSimulationResult, SubmitError> {
+        if tx.is_close() {
+            return Err(SubmitError::AccountCloseDisabled);
+        }
+
+        let mut storage = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(SubmitError::internal)?;
+        tx_simulator::simulate_tx(&mut storage, tx)
+            .await
+            .map_err(SubmitError::internal)
+    }
+
+    /// Executes the batch against the last committed state without sending it to the mempool.
+    pub async fn simulate_batch(
+        &self,
+        txs: Vec<ZkSyncTx>,
+    ) -> Result<TxSimulationResult, SubmitError> {
+        if txs.is_empty() {
+            return Err(SubmitError::TxAdd(TxAddError::EmptyBatch));
+        }
+        if txs.len() > self.max_number_of_transactions_per_batch {
+            return Err(SubmitError::TxAdd(TxAddError::BatchTooBig));
+        }
+        if txs.iter().any(|tx| tx.is_close()) {
+            return Err(SubmitError::AccountCloseDisabled);
+        }
+
+        let mut storage = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(SubmitError::internal)?;
+        tx_simulator::simulate_batch(&mut storage, txs)
+            .await
+            .map_err(SubmitError::internal)
+    }
+
     async fn verify_order_eth_signature(
         &self,
         order: &Order,
@@ -380,7 +493,7 @@ impl TxSender {
         }
 
         let result = self
-            .submit_tx(tx, signature, extracted_request_metadata)
+            .submit_tx(tx, signature, None, extracted_request_metadata)
             .await;
 
         if let Err(err) = &result {
@@ -444,6 +557,7 @@ impl TxSender {
     pub async fn store_subsidy_data(
         &self,
         hash: TxHash,
+        subsidy_type: String,
         normal_fee: BigUint,
         subsidized_fee: BigUint,
         token_id: TokenId,
@@ -467,7 +581,7 @@ impl TxSender {
             token_id,
             token_amount: biguint_to_big_decimal(subsidized_fee),
             full_cost_token: biguint_to_big_decimal(normal_fee),
-            subsidy_type: self.current_subsidy_type.clone(),
+            subsidy_type,
             tx_hash: hash,
         };
 
@@ -481,11 +595,422 @@ impl TxSender {
         Ok(())
     }
 
+    /// Checks that the new subsidy fits both the total budget and the daily cap of the program.
+    async fn can_subsidize_program(
+        &self,
+        program: &SubsidyProgramConfig,
+        new_subsidy_usd: &Ratio<BigUint>,
+    ) -> Result<bool, anyhow::Error> {
+        let mut storage = self.pool.access_storage().await?;
+        let used_usd = storage
+            .misc_schema()
+            .get_total_used_subsidy_for_type(&program.name)
+            .await?;
+        let used_last_day_usd = storage
+            .misc_schema()
+            .get_used_subsidy_for_type_since(&program.name, Utc::now() - Duration::days(1))
+            .await?;
+
+        let used_usd = scaled_big_decimal_to_ratio(used_usd)? + new_subsidy_usd;
+        let used_last_day_usd = scaled_big_decimal_to_ratio(used_last_day_usd)? + new_subsidy_usd;
+        Ok(used_usd <= program.budget_usd() && used_last_day_usd <= program.daily_cap_usd())
+    }
+
+    /// Stores the subsidy of the program for the transaction, which is the part of the normal fee
+    /// not paid by the user. Fails if the subsidy doesn't fit the budget or the daily cap of the program anymore.
+    async fn reserve_program_subsidy(
+        &self,
+        program: &str,
+        tx_hash: TxHash,
+        normal_fee: BigUint,
+        provided_fee: BigUint,
+        token_id: TokenId,
+    ) -> Result<(), SubmitError> {
+        // The user pays the full fee, so there is nothing to subsidize.
+        if provided_fee >= normal_fee {
+            return Ok(());
+        }
+        let program = self
+            .subsidy_programs
+            .iter()
+            .find(|config| config.name == program)
+            .ok_or_else(|| SubmitError::other(format!("Unknown subsidy program {}", program)))?;
+
+        let token_price_in_usd = self
+            .ticker
+            .get_token_price(TokenLike::Id(token_id), TokenPriceRequestType::USDForOneWei)
+            .await?;
+        let token_price_in_usd =
+            big_decimal_to_ratio(&token_price_in_usd).map_err(SubmitError::Internal)?;
+        let full_cost_usd = token_price_in_usd.clone() * &normal_fee;
+        let subsidy_usd = token_price_in_usd * (&normal_fee - &provided_fee);
+
+        let subsidy = Subsidy {
+            usd_amount_scaled: ratio_to_scaled_u64(subsidy_usd),
+            full_cost_usd_scaled: ratio_to_scaled_u64(full_cost_usd),
+            token_id,
+            token_amount: biguint_to_big_decimal(provided_fee),
+            full_cost_token: biguint_to_big_decimal(normal_fee),
+            subsidy_type: program.name.clone(),
+            tx_hash,
+        };
+        let stored = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?
+            .misc_schema()
+            .store_subsidy_within_limits(
+                subsidy,
+                program.budget_usd_scaled,
+                program.daily_cap_usd_scaled,
+                Utc::now() - Duration::days(1),
+            )
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?;
+        if !stored {
+            metrics::increment_counter!("tx_sender.submit_tx.subsidy_program_exhausted");
+            return Err(SubmitError::TxAdd(TxAddError::TxFeeTooLow));
+        }
+        Ok(())
+    }
+
+    /// Removes the subsidy of the program stored for the transaction which was then rejected.
+    async fn release_program_subsidy(&self, program: &str, tx_hash: TxHash) {
+        let result = match self.pool.access_storage().await {
+            Ok(mut storage) => storage.misc_schema().remove_subsidy(tx_hash, program).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            vlog::error!(
+                "Failed to remove the subsidy of the program {}: {}",
+                program,
+                err
+            );
+        }
+    }
+
+    /// Finds the first subsidy program matching the transaction which can still pay its share of the fee.
+    /// Returns the name of the program and the fee data with the fee to be paid by the user as the subsidized one.
+    pub async fn find_subsidy_program(
+        &self,
+        tx_type: TxFeeTypes,
+        token: TokenLike,
+        sender: Option<Address>,
+        recipient: Address,
+        normal_fee: Fee,
+    ) -> Result<Option<(String, ResponseFee)>, SubmitError> {
+        if self.subsidy_programs.is_empty() {
+            return Ok(None);
+        }
+        let token = self.ticker.get_token(token).await?;
+        let mut programs = self
+            .subsidy_programs
+            .iter()
+            .filter(|program| {
+                subsidy_program_matches(program, tx_type, token.id, sender, recipient)
+            })
+            .peekable();
+        if programs.peek().is_none() {
+            return Ok(None);
+        }
+
+        let token_price_in_usd = self
+            .ticker
+            .get_token_price(TokenLike::Id(token.id), TokenPriceRequestType::USDForOneWei)
+            .await?;
+        let token_price_in_usd =
+            big_decimal_to_ratio(&token_price_in_usd).map_err(SubmitError::Internal)?;
+
+        for program in programs {
+            let user_share = Ratio::new(
+                BigUint::from(100 - u32::from(program.subsidy_percent)),
+                BigUint::from(100u32),
+            );
+            let subsidized_fee = Fee::new(
+                normal_fee.fee_type,
+                Ratio::from(normal_fee.zkp_fee.clone()) * &user_share,
+                Ratio::from(normal_fee.gas_fee.clone()) * &user_share,
+                normal_fee.gas_tx_amount.clone(),
+                normal_fee.gas_price_wei.clone(),
+            );
+            if subsidized_fee.total_fee >= normal_fee.total_fee {
+                continue;
+            }
+
+            let subsidy_size_usd =
+                token_price_in_usd.clone() * (&normal_fee.total_fee - &subsidized_fee.total_fee);
+            if self
+                .can_subsidize_program(program, &subsidy_size_usd)
+                .await
+                .map_err(SubmitError::Internal)?
+            {
+                return Ok(Some((
+                    program.name.clone(),
+                    ResponseFee {
+                        normal_fee,
+                        subsidized_fee,
+                        subsidy_size_usd,
+                    },
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Calculates the fee for a single transaction, applying the subsidy of the matching program.
+    pub async fn get_tx_fee_with_subsidy(
+        &self,
+        tx_type: TxFeeTypes,
+        token: TokenLike,
+        sender: Option<Address>,
+        recipient: Address,
+    ) -> Result<ApiFee, SubmitError> {
+        let normal_fee = self
+            .ticker
+            .get_fee_from_ticker_in_wei(tx_type, token.clone(), recipient)
+            .await?
+            .normal_fee;
+
+        let fee = match self
+            .find_subsidy_program(tx_type, token, sender, recipient, normal_fee.clone())
+            .await?
+        {
+            Some((program, fee_data)) => ApiFee {
+                subsidy: Some(ApiFeeSubsidy {
+                    program,
+                    normal_fee: fee_data.normal_fee.total_fee,
+                }),
+                ..fee_data.subsidized_fee.into()
+            },
+            None => normal_fee.into(),
+        };
+        Ok(fee)
+    }
+
+    /// Returns the subsidy programs along with the amount of subsidies they have paid.
+    pub async fn subsidy_programs_usage(&self) -> Result<Vec<ApiSubsidyProgram>, SubmitError> {
+        let mut storage = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(SubmitError::internal)?;
+        let day_ago = Utc::now() - Duration::days(1);
+
+        let mut programs = Vec::with_capacity(self.subsidy_programs.len());
+        for program in &self.subsidy_programs {
+            let used_usd = storage
+                .misc_schema()
+                .get_total_used_subsidy_for_type(&program.name)
+                .await
+                .map_err(SubmitError::internal)?;
+            let used_last_day_usd = storage
+                .misc_schema()
+                .get_used_subsidy_for_type_since(&program.name, day_ago)
+                .await
+                .map_err(SubmitError::internal)?;
+
+            programs.push(ApiSubsidyProgram {
+                name: program.name.clone(),
+                subsidy_percent: program.subsidy_percent,
+                budget_usd: ratio_to_big_decimal(&program.budget_usd(), 6),
+                daily_cap_usd: ratio_to_big_decimal(&program.daily_cap_usd(), 6),
+                used_usd: ratio_to_big_decimal(&scaled_big_decimal_to_ratio(used_usd)?, 6),
+                used_last_day_usd: ratio_to_big_decimal(
+                    &scaled_big_decimal_to_ratio(used_last_day_usd)?,
+                    6,
+                ),
+            });
+        }
+        Ok(programs)
+    }
+
+    /// Calculates the fee for the transaction and stores it as a quote given to the `sender`, so that
+    /// a single transaction of the same type to the same address paying at least the quoted amount in
+    /// the same token is accepted until the quote expires, if it's submitted with the quote ID.
+    /// The unused quote is returned again while it's valid for at least a half of the validity period,
+    /// so repeated requests don't create new quotes.
+    pub async fn quote_tx_fee(
+        &self,
+        tx_type: TxFeeTypes,
+        token: TokenLike,
+        address: Address,
+        sender: Address,
+    ) -> Result<ApiFeeQuote, SubmitError> {
+        let fee_allowed = self.ticker.token_allowed_for_fees(token.clone()).await?;
+        if !fee_allowed {
+            return Err(SubmitError::InappropriateFeeToken);
+        }
+        let token = self.ticker.get_token(token).await?;
+
+        // Subsidies depend on the origin of the request and are limited, so only the normal fee is quoted.
+        let fee = self
+            .ticker
+            .get_fee_from_ticker_in_wei(tx_type, TokenLike::Id(token.id), address)
+            .await?
+            .normal_fee;
+
+        let mut storage = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(SubmitError::internal)?;
+        let now = Utc::now();
+        let unused_quote = storage
+            .misc_schema()
+            .find_unused_fee_quote(
+                sender,
+                address,
+                fee.fee_type,
+                token.id,
+                now + self.fee_quote_validity / 2,
+            )
+            .await
+            .map_err(SubmitError::internal)?;
+        let quote = match unused_quote {
+            Some(quote) => quote,
+            None => storage
+                .misc_schema()
+                .store_fee_quote(
+                    sender,
+                    address,
+                    fee.fee_type,
+                    token.id,
+                    biguint_to_big_decimal(fee.total_fee),
+                    now + self.fee_quote_validity,
+                )
+                .await
+                .map_err(SubmitError::internal)?,
+        };
+
+        Ok(ApiFeeQuote {
+            quote_id: quote.id,
+            sender,
+            address,
+            fee_type: fee.fee_type,
+            token_id: token.id,
+            total_fee: big_decimal_to_ratio(&quote.amount)
+                .map_err(SubmitError::internal)?
+                .to_integer(),
+            valid_from: quote.created_at,
+            valid_until: quote.valid_until,
+        })
+    }
+
+    /// Checks that the quote was given to the sender of the transaction for its type, recipient and token,
+    /// and that the provided fee isn't lower than the quoted one.
+    async fn is_fee_quoted(
+        &self,
+        fee_quote_id: i64,
+        sender: Address,
+        address: Address,
+        fee_type: OutputFeeType,
+        token_id: TokenId,
+        provided_fee: &BigDecimal,
+    ) -> Result<bool, SubmitError> {
+        let quote = self
+            .pool
+            .access_storage()
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?
+            .misc_schema()
+            .get_fee_quote(fee_quote_id)
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?;
+
+        let quote = match quote {
+            Some(quote) => quote,
+            None => return Ok(false),
+        };
+        let fee_type = serde_json::to_value(fee_type).map_err(SubmitError::internal)?;
+        Ok(quote.sender == sender.as_bytes()
+            && quote.recipient == address.as_bytes()
+            && quote.fee_type == fee_type
+            && quote.token_id == token_id.0 as i32
+            && &quote.amount <= provided_fee
+            && quote.valid_until > Utc::now()
+            && quote.tx_hash.is_none())
+    }
+
+    /// Marks the quote as used by the transaction, so it can't be used by the other one.
+    async fn use_fee_quote(
+        &self,
+        fee_quote_id: i64,
+        tx_hash: TxHash,
+    ) -> Result<FeeQuoteUsage, SubmitError> {
+        self.pool
+            .access_storage()
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))?
+            .misc_schema()
+            .use_fee_quote(fee_quote_id, tx_hash)
+            .await
+            .map_err(|_| SubmitError::TxAdd(TxAddError::DbError))
+    }
+
+    /// Makes the quote available again after the transaction which used it was rejected.
+    async fn release_fee_quote(&self, fee_quote_id: i64, tx_hash: TxHash) {
+        let result = match self.pool.access_storage().await {
+            Ok(mut storage) => {
+                storage
+                    .misc_schema()
+                    .release_fee_quote(fee_quote_id, tx_hash)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            vlog::error!("Failed to release the fee quote {}: {}", fee_quote_id, err);
+        }
+    }
+
+    /// Submits the transaction. If the provided fee is lower than the required one, the transaction
+    /// is still accepted if it pays the fee quoted with `fee_quote_id`.
     pub async fn submit_tx(
         &self,
         tx: ZkSyncTx,
         signature: TxEthSignatureVariant,
+        fee_quote_id: Option<i64>,
+        extracted_request_metadata: Option<RequestMetadata>,
+    ) -> Result<TxHash, SubmitError> {
+        self.process_tx(
+            tx,
+            signature,
+            fee_quote_id,
+            extracted_request_metadata,
+            false,
+        )
+        .await
+    }
+
+    /// Submits the transaction authorized by the Ethereum transaction which is signed by the owner
+    /// of the account (e.g. sent via the web3 API). It replaces the Ethereum signature of the transaction
+    /// message, while the zkSync signature is still required.
+    pub async fn submit_eth_authorized_tx(
+        &self,
+        tx: ZkSyncTx,
+        eth_signer: Address,
+    ) -> Result<TxHash, SubmitError> {
+        let tx_sender = self
+            .get_tx_sender(&tx)
+            .await
+            .or(Err(SubmitError::TxAdd(TxAddError::DbError)))?;
+        if tx_sender != eth_signer {
+            return Err(SubmitError::TxAdd(TxAddError::IncorrectEthSignature));
+        }
+
+        self.process_tx(tx, TxEthSignatureVariant::Single(None), None, None, true)
+            .await
+    }
+
+    async fn process_tx(
+        &self,
+        tx: ZkSyncTx,
+        signature: TxEthSignatureVariant,
+        fee_quote_id: Option<i64>,
         extracted_request_metadata: Option<RequestMetadata>,
+        eth_authorized: bool,
     ) -> Result<TxHash, SubmitError> {
         let labels = vec![
             ("stage", "api".to_string()),
@@ -505,9 +1030,13 @@ impl TxSender {
 
         // Resolve the token.
         let token = self.token_info_from_id(tx.token_id()).await?;
-        let msg_to_sign = tx
-            .get_ethereum_sign_message(token.clone())
-            .map(String::into_bytes);
+        // The owner of the account has already authorized the transaction with the Ethereum one.
+        let msg_to_sign = if eth_authorized {
+            None
+        } else {
+            tx.get_ethereum_sign_message(token.clone())
+                .map(String::into_bytes)
+        };
 
         let is_whitelisted_initiator = tx
             .account_id()
@@ -522,7 +1051,16 @@ impl TxSender {
 
         let sign_verify_channel = self.sign_verify_requests.clone();
 
-        let mut fee_data_for_subsidy: Option<ResponseFee> = None;
+        let mut fee_data_for_subsidy: Option<(String, ResponseFee)> = None;
+        // The subsidy program covering the fee, along with the normal and the provided fees.
+        let mut program_subsidy: Option<(String, BigUint, BigUint)> = None;
+        // The quote which guarantees the fee of the transaction, if the provided fee is too low otherwise.
+        let mut used_fee_quote: Option<i64> = None;
+
+        let tx_sender = self
+            .get_tx_sender(&tx)
+            .await
+            .or(Err(SubmitError::TxAdd(TxAddError::DbError)))?;
 
         if let Some((tx_type, token, address, provided_fee)) = tx_fee_info {
             let should_enforce_fee = !matches!(tx_type, TxFeeTypes::ChangePubKey { .. })
@@ -548,8 +1086,25 @@ impl TxSender {
                 )
                 .await?
             {
-                fee_data_for_subsidy = Some(required_fee_data.clone());
+                fee_data_for_subsidy =
+                    Some((self.current_subsidy_type.clone(), required_fee_data.clone()));
                 required_fee_data.subsidized_fee
+            } else if let Some((program, program_fee_data)) = self
+                .find_subsidy_program(
+                    tx_type,
+                    token.clone(),
+                    Some(tx_sender),
+                    address,
+                    required_fee_data.normal_fee.clone(),
+                )
+                .await?
+            {
+                program_subsidy = Some((
+                    program,
+                    program_fee_data.normal_fee.total_fee,
+                    provided_fee.clone(),
+                ));
+                program_fee_data.subsidized_fee
             } else {
                 required_fee_data.normal_fee
             };
@@ -558,17 +1113,34 @@ impl TxSender {
             let required_fee: BigDecimal = required_fee_data.total_fee.to_bigint().unwrap().into();
             let provided_fee: BigDecimal = provided_fee.to_bigint().unwrap().into();
             // Scaling the fee required since the price may change between signing the transaction and sending it to the server.
-            let scaled_provided_fee = scale_user_fee_up(provided_fee);
-            if required_fee >= scaled_provided_fee && should_enforce_fee {
-                return Err(SubmitError::TxAdd(TxAddError::TxFeeTooLow));
+            let scaled_provided_fee = scale_user_fee_up(provided_fee.clone());
+            // Transactions fully covered by a subsidy don't have to pay any fee.
+            let fee_required = !required_fee_data.total_fee.is_zero();
+            if fee_required && required_fee >= scaled_provided_fee && should_enforce_fee {
+                // The fee may still be guaranteed by a quote given before the prices changed.
+                let fee_quote_id =
+                    fee_quote_id.ok_or(SubmitError::TxAdd(TxAddError::TxFeeTooLow))?;
+                let token_id = self.ticker.get_token(token).await?.id;
+                if !self
+                    .is_fee_quoted(
+                        fee_quote_id,
+                        tx_sender,
+                        address,
+                        required_fee_data.fee_type,
+                        token_id,
+                        &provided_fee,
+                    )
+                    .await?
+                {
+                    return Err(SubmitError::TxAdd(TxAddError::TxFeeTooLow));
+                }
+                used_fee_quote = Some(fee_quote_id);
+                // The transaction pays the quoted fee, so it isn't subsidized.
+                fee_data_for_subsidy = None;
+                program_subsidy = None;
             }
         }
 
-        let tx_sender = self
-            .get_tx_sender(&tx)
-            .await
-            .or(Err(SubmitError::TxAdd(TxAddError::DbError)))?;
-
         let verified_tx = verify_tx_info_message_signature(
             &tx,
             tx_sender,
@@ -592,18 +1164,62 @@ impl TxSender {
                 .await?;
         }
 
+        // The quote is marked as used before the transaction gets to the mempool,
+        // so the concurrent transactions can't use it as well. The quote already used by
+        // the same transaction (e.g. if it's resubmitted) is not released on failure,
+        // since it belongs to the previous submission.
+        let mut acquired_fee_quote = None;
+        if let Some(fee_quote_id) = used_fee_quote {
+            match self.use_fee_quote(fee_quote_id, tx.hash()).await? {
+                FeeQuoteUsage::Used => acquired_fee_quote = Some(fee_quote_id),
+                FeeQuoteUsage::AlreadyUsed => {}
+                FeeQuoteUsage::Unavailable => {
+                    return Err(SubmitError::TxAdd(TxAddError::TxFeeTooLow))
+                }
+            }
+        }
+
+        // The same goes for the subsidy of the program, so the concurrent transactions
+        // can't exceed its budget.
+        let mut reserved_subsidy: Option<String> = None;
+        if let Some((program, normal_fee, provided_fee)) = program_subsidy {
+            if let Err(err) = self
+                .reserve_program_subsidy(&program, tx.hash(), normal_fee, provided_fee, token.id)
+                .await
+            {
+                if let Some(fee_quote_id) = acquired_fee_quote {
+                    self.release_fee_quote(fee_quote_id, tx.hash()).await;
+                }
+                return Err(err);
+            }
+            reserved_subsidy = Some(program);
+        }
+
         let (sender, receiver) = oneshot::channel();
         let item = MempoolTransactionRequest::NewTx(Box::new(verified_tx), sender);
         let mut mempool_sender = self.mempool_tx_sender.clone();
-        mempool_sender
-            .send(item)
-            .await
-            .map_err(SubmitError::internal)?;
-
-        receiver.await.map_err(SubmitError::internal)??;
+        let mempool_result = match mempool_sender.send(item).await {
+            Ok(()) => receiver
+                .await
+                .map_err(SubmitError::internal)
+                .and_then(|result| result.map_err(SubmitError::from)),
+            Err(err) => Err(SubmitError::internal(err)),
+        };
+        if let Err(err) = mempool_result {
+            if let Some(fee_quote_id) = acquired_fee_quote {
+                self.release_fee_quote(fee_quote_id, tx.hash()).await;
+            }
+            if let Some(program) = reserved_subsidy {
+                self.release_program_subsidy(&program, tx.hash()).await;
+            }
+            return Err(err);
+        }
+        if used_fee_quote.is_some() {
+            metrics::increment_counter!("tx_sender.submit_tx.quoted_fee_accepted");
+        }
 
-        // fee_data_for_subsidy has Some value only if the batch of transactions is subsidised
-        if let Some(fee_data_for_subsidy) = fee_data_for_subsidy {
+        // fee_data_for_subsidy has Some value only if the transaction is subsidised
+        if let Some((subsidy_type, fee_data_for_subsidy)) = fee_data_for_subsidy {
             // The following two bad scenarios are possible when applying subsidy for the tx:
             // - The subsidy is stored, but the tx is then rejected by the state keeper
             // - The tx is accepted by the state keeper, but the the `store_subsidy_data` returns an error for some reason
@@ -612,6 +1228,7 @@ impl TxSender {
             // which is not worth it for subsidies (we prefer stability here)
             self.store_subsidy_data(
                 tx.hash(),
+                subsidy_type,
                 fee_data_for_subsidy.normal_fee.total_fee,
                 fee_data_for_subsidy.subsidized_fee.total_fee,
                 token.id,
@@ -910,6 +1527,7 @@ impl TxSender {
             // which is not worth it for subsidies (we prefer stability here)
             self.store_subsidy_data(
                 batch_hash,
+                self.current_subsidy_type.clone(),
                 fee_data.normal_fee.total_fee,
                 fee_data.subsidized_fee.total_fee,
                 subsidy_token_id,
@@ -1014,6 +1632,23 @@ impl TxSender {
     }
 }
 
+/// Checks that the timestamp of the signed request is close enough to the current time,
+/// so the signed request can't be reused later.
+fn check_request_timestamp(request_time: DateTime<Utc>) -> Result<(), SubmitError> {
+    let current_time = Utc::now();
+    let validness_interval = Duration::minutes(VALIDNESS_INTERVAL_MINUTES);
+
+    if current_time - validness_interval > request_time
+        || current_time + validness_interval < request_time
+    {
+        return Err(SubmitError::InvalidParams(format!(
+            "Timestamp differs by more than {} minutes",
+            VALIDNESS_INTERVAL_MINUTES
+        )));
+    }
+    Ok(())
+}
+
 async fn send_verify_request_and_recv(
     request: VerifySignatureRequest,
     mut req_channel: mpsc::Sender<VerifySignatureRequest>,
@@ -1188,6 +1823,41 @@ async fn verify_txs_batch_signature(
     send_verify_request_and_recv(request, req_channel, receiver).await
 }
 
+fn subsidized_tx_type(tx_type: TxFeeTypes) -> SubsidizedTxType {
+    match tx_type {
+        TxFeeTypes::Transfer => SubsidizedTxType::Transfer,
+        TxFeeTypes::Withdraw => SubsidizedTxType::Withdraw,
+        TxFeeTypes::FastWithdraw => SubsidizedTxType::FastWithdraw,
+        TxFeeTypes::WithdrawNFT => SubsidizedTxType::WithdrawNFT,
+        TxFeeTypes::FastWithdrawNFT => SubsidizedTxType::FastWithdrawNFT,
+        TxFeeTypes::ChangePubKey(_) => SubsidizedTxType::ChangePubKey,
+        TxFeeTypes::MintNFT => SubsidizedTxType::MintNFT,
+        TxFeeTypes::Swap => SubsidizedTxType::Swap,
+    }
+}
+
+/// Checks whether the transaction matches the rules of the subsidy program.
+/// Programs limited to specific senders never match transactions with an unknown sender.
+fn subsidy_program_matches(
+    program: &SubsidyProgramConfig,
+    tx_type: TxFeeTypes,
+    token_id: TokenId,
+    sender: Option<Address>,
+    recipient: Address,
+) -> bool {
+    let tx_type_matches =
+        program.tx_types.is_empty() || program.tx_types.contains(&subsidized_tx_type(tx_type));
+    let token_matches = program.tokens.is_empty() || program.tokens.contains(&token_id);
+    let sender_matches = program.senders.is_empty()
+        || sender
+            .map(|sender| program.senders.contains(&sender))
+            .unwrap_or(false);
+    let recipient_matches =
+        program.recipients.is_empty() || program.recipients.contains(&recipient);
+
+    tx_type_matches && token_matches && sender_matches && recipient_matches
+}
+
 /// Scales the fee provided by user up to check whether the provided fee is enough to cover our expenses for
 /// maintaining the protocol.
 ///
@@ -1225,6 +1895,87 @@ fn scale_user_fee_up(provided_total_usd_fee: BigDecimal) -> BigDecimal {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api_server::rest::v02::test_utils::{
+        dummy_fee_ticker, dummy_sign_verifier, TestServerConfig,
+    };
+    use crate::fee_ticker::validator::cache::TokenInMemoryCache;
+    use zksync_test_account::ZkSyncAccount;
+    use zksync_types::{
+        helpers::closest_packable_fee_amount,
+        tokens::{ChangePubKeyFeeTypeArg, TokenMarketVolume},
+        tx::ChangePubKeyType,
+        TokenKind,
+    };
+
+    /// Mempool which accepts all the transactions.
+    fn mempool_loopback() -> mpsc::Sender<MempoolTransactionRequest> {
+        let (sender, mut receiver) = mpsc::channel(10);
+        actix_rt::spawn(async move {
+            while let Some(request) = receiver.next().await {
+                if let MempoolTransactionRequest::NewTx(_, resp) = request {
+                    resp.send(Ok(())).unwrap_or_default();
+                }
+            }
+        });
+        sender
+    }
+
+    fn test_tx_sender(cfg: &TestServerConfig) -> TxSender {
+        let mut tokens = HashMap::new();
+        tokens.insert(
+            TokenLike::Id(TokenId(0)),
+            Token::new(TokenId(0), Default::default(), "ETH", 18, TokenKind::ERC20),
+        );
+        let mut market = HashMap::new();
+        market.insert(
+            TokenId(0),
+            TokenMarketVolume {
+                market_volume: Ratio::from_integer(BigUint::from(400u32)),
+                last_updated: Utc::now(),
+            },
+        );
+        let cache = TokenInMemoryCache::new()
+            .with_tokens(tokens)
+            .with_market(market);
+        let prices = vec![(TokenLike::Id(TokenId(0)), 10500_u64.into())];
+
+        let mut tx_sender = TxSender::new(
+            cfg.pool.clone(),
+            dummy_sign_verifier(),
+            dummy_fee_ticker(&prices, Some(cache)),
+            &cfg.config.api.common,
+            &cfg.config.api.token_config,
+            mempool_loopback(),
+        );
+        tx_sender.subsidy_programs = Vec::new();
+        tx_sender
+    }
+
+    /// Signs the transfer of ETH paying the `fee`.
+    fn sign_transfer(
+        from: &ZkSyncAccount,
+        to: Address,
+        fee: BigUint,
+    ) -> (ZkSyncTx, TxEthSignatureVariant) {
+        let (transfer, eth_signature) = from.sign_transfer(
+            TokenId(0),
+            "ETH",
+            10u64.into(),
+            fee,
+            &to,
+            None,
+            true,
+            Default::default(),
+        );
+        (
+            ZkSyncTx::Transfer(Box::new(transfer)),
+            TxEthSignatureVariant::Single(eth_signature.map(TxEthSignature::EthereumSignature)),
+        )
+    }
+
+    fn is_fee_too_low(result: Result<TxHash, SubmitError>) -> bool {
+        matches!(result, Err(SubmitError::TxAdd(TxAddError::TxFeeTooLow)))
+    }
 
     #[test]
     fn test_scaling_user_fee_by_two() {
@@ -1255,4 +2006,269 @@ mod tests {
 
         assert_eq!(provided_fee_scaled_by_five_percent, scaled_fee);
     }
+
+    #[test]
+    fn test_subsidy_program_matching() {
+        let partner = Address::from_low_u64_be(1);
+        let recipient = Address::from_low_u64_be(2);
+        let program = SubsidyProgramConfig {
+            name: "PartnerTransfers".to_string(),
+            tx_types: vec![SubsidizedTxType::Transfer, SubsidizedTxType::ChangePubKey],
+            tokens: vec![TokenId(0)],
+            senders: vec![partner],
+            recipients: Vec::new(),
+            subsidy_percent: 100,
+            budget_usd_scaled: 1_000_000,
+            daily_cap_usd_scaled: 100_000,
+        };
+        let cpk_type = TxFeeTypes::ChangePubKey(ChangePubKeyFeeTypeArg::ContractsV4Version(
+            ChangePubKeyType::ECDSA,
+        ));
+
+        assert!(subsidy_program_matches(
+            &program,
+            TxFeeTypes::Transfer,
+            TokenId(0),
+            Some(partner),
+            recipient
+        ));
+        assert!(subsidy_program_matches(
+            &program,
+            cpk_type,
+            TokenId(0),
+            Some(partner),
+            recipient
+        ));
+        // Another transaction type.
+        assert!(!subsidy_program_matches(
+            &program,
+            TxFeeTypes::Withdraw,
+            TokenId(0),
+            Some(partner),
+            recipient
+        ));
+        // Another token.
+        assert!(!subsidy_program_matches(
+            &program,
+            TxFeeTypes::Transfer,
+            TokenId(1),
+            Some(partner),
+            recipient
+        ));
+        // Another sender or the unknown one.
+        assert!(!subsidy_program_matches(
+            &program,
+            TxFeeTypes::Transfer,
+            TokenId(0),
+            Some(recipient),
+            recipient
+        ));
+        assert!(!subsidy_program_matches(
+            &program,
+            TxFeeTypes::Transfer,
+            TokenId(0),
+            None,
+            recipient
+        ));
+
+        // Program without rules matches any transaction.
+        let program = SubsidyProgramConfig {
+            tx_types: Vec::new(),
+            tokens: Vec::new(),
+            senders: Vec::new(),
+            ..program
+        };
+        assert!(subsidy_program_matches(
+            &program,
+            TxFeeTypes::Withdraw,
+            TokenId(1),
+            None,
+            recipient
+        ));
+    }
+
+    /// Checks that the transaction paying the fee which is too low now is accepted only with
+    /// the unexpired quote given to its sender for the same recipient and token, and only once.
+    #[actix_rt::test]
+    #[cfg_attr(
+        not(feature = "api_test"),
+        ignore = "Use `zk test rust-api` command to perform this test"
+    )]
+    async fn submit_tx_with_fee_quote() -> anyhow::Result<()> {
+        let cfg = TestServerConfig::default();
+        cfg.fill_database().await?;
+        let tx_sender = test_tx_sender(&cfg);
+
+        let from = ZkSyncAccount::rand();
+        from.set_account_id(Some(AccountId(0xf00d)));
+        let recipient = Address::random();
+
+        // The transaction doesn't pay any fee, so it's accepted only with the quote.
+        let required_fee = tx_sender
+            .ticker
+            .get_fee_from_ticker_in_wei(TxFeeTypes::Transfer, TokenLike::Id(TokenId(0)), recipient)
+            .await?
+            .normal_fee;
+        assert_eq!(required_fee.fee_type, OutputFeeType::Transfer);
+        assert!(!required_fee.total_fee.is_zero());
+
+        let mut storage = cfg.pool.access_storage().await?;
+        let valid_until = Utc::now() + Duration::minutes(10);
+        let mut quote_ids = Vec::new();
+        for (sender, token_id, valid_until) in [
+            (from.address, TokenId(0), valid_until),
+            // Quote for the other token.
+            (from.address, TokenId(1), valid_until),
+            // Expired quote.
+            (from.address, TokenId(0), Utc::now() - Duration::minutes(1)),
+            // Quote given to the other account.
+            (Address::random(), TokenId(0), valid_until),
+        ] {
+            let quote = storage
+                .misc_schema()
+                .store_fee_quote(
+                    sender,
+                    recipient,
+                    OutputFeeType::Transfer,
+                    token_id,
+                    BigDecimal::from(0),
+                    valid_until,
+                )
+                .await?;
+            quote_ids.push(quote.id);
+        }
+        drop(storage);
+        let valid_quote = quote_ids[0];
+
+        let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+        assert!(is_fee_too_low(
+            tx_sender
+                .submit_tx(tx.clone(), signature.clone(), None, None)
+                .await
+        ));
+        for &quote_id in &quote_ids[1..] {
+            assert!(is_fee_too_low(
+                tx_sender
+                    .submit_tx(tx.clone(), signature.clone(), Some(quote_id), None)
+                    .await
+            ));
+        }
+
+        let tx_hash = tx_sender
+            .submit_tx(tx.clone(), signature, Some(valid_quote), None)
+            .await?;
+        assert_eq!(tx_hash, tx.hash());
+
+        // The quote is used by the accepted transaction, so the other one can't use it.
+        let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+        assert!(is_fee_too_low(
+            tx_sender
+                .submit_tx(tx, signature, Some(valid_quote), None)
+                .await
+        ));
+
+        Ok(())
+    }
+
+    /// Checks that the subsidies of the program are limited by its budget and daily cap,
+    /// and that only the part of the normal fee not paid by the user is accounted.
+    #[actix_rt::test]
+    #[cfg_attr(
+        not(feature = "api_test"),
+        ignore = "Use `zk test rust-api` command to perform this test"
+    )]
+    async fn submit_tx_with_subsidy_program() -> anyhow::Result<()> {
+        let cfg = TestServerConfig::default();
+        cfg.fill_database().await?;
+        let mut tx_sender = test_tx_sender(&cfg);
+
+        let from = ZkSyncAccount::rand();
+        from.set_account_id(Some(AccountId(0xf00d)));
+        let recipient = Address::random();
+
+        let normal_fee = tx_sender
+            .ticker
+            .get_fee_from_ticker_in_wei(TxFeeTypes::Transfer, TokenLike::Id(TokenId(0)), recipient)
+            .await?
+            .normal_fee
+            .total_fee;
+        let token_price_in_usd = tx_sender
+            .ticker
+            .get_token_price(
+                TokenLike::Id(TokenId(0)),
+                TokenPriceRequestType::USDForOneWei,
+            )
+            .await?;
+        let subsidy_usd_scaled =
+            ratio_to_scaled_u64(big_decimal_to_ratio(&token_price_in_usd)? * &normal_fee);
+        assert!(subsidy_usd_scaled > 0);
+
+        // The subsidies are accounted by the name of the program, so it's unique for every run.
+        let budget_program = SubsidyProgramConfig {
+            name: format!("BudgetTest{:x}", from.address),
+            tx_types: vec![SubsidizedTxType::Transfer],
+            tokens: vec![TokenId(0)],
+            senders: vec![from.address],
+            recipients: Vec::new(),
+            subsidy_percent: 100,
+            budget_usd_scaled: 2 * subsidy_usd_scaled,
+            daily_cap_usd_scaled: 10 * subsidy_usd_scaled,
+        };
+        let daily_cap_program = SubsidyProgramConfig {
+            name: format!("DailyCapTest{:x}", from.address),
+            budget_usd_scaled: 10 * subsidy_usd_scaled,
+            daily_cap_usd_scaled: subsidy_usd_scaled,
+            ..budget_program.clone()
+        };
+        let used_usd_scaled = |program: &SubsidyProgramConfig| {
+            let pool = cfg.pool.clone();
+            let name = program.name.clone();
+            async move {
+                pool.access_storage()
+                    .await?
+                    .misc_schema()
+                    .get_total_used_subsidy_for_type(&name)
+                    .await
+            }
+        };
+
+        tx_sender.subsidy_programs = vec![budget_program.clone()];
+        // The transaction paying the full fee doesn't use the budget.
+        let full_fee = closest_packable_fee_amount(&(normal_fee.clone() * 2u32));
+        assert!(full_fee >= normal_fee);
+        let (tx, signature) = sign_transfer(&from, recipient, full_fee);
+        tx_sender.submit_tx(tx, signature, None, None).await?;
+        assert_eq!(used_usd_scaled(&budget_program).await?, BigDecimal::from(0));
+
+        for _ in 0..2 {
+            let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+            tx_sender.submit_tx(tx, signature, None, None).await?;
+        }
+        assert_eq!(
+            used_usd_scaled(&budget_program).await?,
+            BigDecimal::from(2 * subsidy_usd_scaled)
+        );
+        let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+        assert!(is_fee_too_low(
+            tx_sender.submit_tx(tx, signature, None, None).await
+        ));
+        assert_eq!(
+            used_usd_scaled(&budget_program).await?,
+            BigDecimal::from(2 * subsidy_usd_scaled)
+        );
+
+        tx_sender.subsidy_programs = vec![daily_cap_program.clone()];
+        let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+        tx_sender.submit_tx(tx, signature, None, None).await?;
+        let (tx, signature) = sign_transfer(&from, recipient, BigUint::zero());
+        assert!(is_fee_too_low(
+            tx_sender.submit_tx(tx, signature, None, None).await
+        ));
+        assert_eq!(
+            used_usd_scaled(&daily_cap_program).await?,
+            BigDecimal::from(subsidy_usd_scaled)
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,211 @@
+//! Execution of transactions against the last committed state without sending them to the mempool.
+//!
+//! The API server doesn't maintain the whole state of the network, so only the accounts
+//! (and NFTs) affected by the transactions are loaded from the database into a throwaway
+//! `ZkSyncState`. This is enough for the state handlers, since transactions can't
+//! touch any other accounts.
+
+// Built-in uses
+use std::collections::HashSet;
+
+// External uses
+use chrono::Utc;
+
+// Workspace uses
+use zksync_api_types::v02::transaction::{SimulatedFee, SimulatedTx, TxSimulationResult};
+use zksync_crypto::params::NFT_STORAGE_ACCOUNT_ID;
+use zksync_state::state::{OpSuccess, ZkSyncState};
+use zksync_storage::{QueryResult, StorageProcessor};
+use zksync_types::{tx::TxHash, AccountId, AccountMap, Address, SignedZkSyncTx, TokenId, ZkSyncTx};
+
+/// Executes a single transaction and returns the resulting account updates,
+/// or the reason of the failure.
+pub async fn simulate_tx(
+    storage: &mut StorageProcessor<'_>,
+    tx: ZkSyncTx,
+) -> QueryResult<TxSimulationResult> {
+    let mut state = load_state_for_txs(storage, std::slice::from_ref(&tx)).await?;
+    let block_timestamp = Utc::now().timestamp() as u64;
+
+    let tx_hash = tx.hash();
+    let result = state
+        .execute_tx(tx, block_timestamp)
+        .map(|success| (tx_hash, success))
+        .map_err(|err| (err.to_string(), None));
+
+    Ok(simulation_result(vec![result]))
+}
+
+/// Executes the transactions atomically as a batch. If any of them fails, the whole
+/// batch is considered failed.
+pub async fn simulate_batch(
+    storage: &mut StorageProcessor<'_>,
+    txs: Vec<ZkSyncTx>,
+) -> QueryResult<TxSimulationResult> {
+    let mut state = load_state_for_txs(storage, &txs).await?;
+    let block_timestamp = Utc::now().timestamp() as u64;
+
+    let tx_hashes: Vec<_> = txs.iter().map(ZkSyncTx::hash).collect();
+    let signed_txs: Vec<_> = txs.into_iter().map(SignedZkSyncTx::from).collect();
+    let results = state
+        .execute_txs_batch(&signed_txs, block_timestamp)
+        .into_iter()
+        .zip(tx_hashes)
+        .map(|(result, tx_hash)| {
+            result
+                .map(|success| (tx_hash, success))
+                .map_err(|err| (err.reason.to_string(), Some(err.failed_tx_index)))
+        })
+        .collect();
+
+    Ok(simulation_result(results))
+}
+
+type ExecutionResult = Result<(TxHash, OpSuccess), (String, Option<usize>)>;
+
+fn simulation_result(results: Vec<ExecutionResult>) -> TxSimulationResult {
+    let mut simulated_txs = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok((tx_hash, success)) => simulated_txs.push(simulated_tx(tx_hash, success)),
+            Err((fail_reason, failed_tx_index)) => {
+                return TxSimulationResult {
+                    success: false,
+                    txs: Vec::new(),
+                    chunks: 0,
+                    fail_reason: Some(fail_reason),
+                    failed_tx_index,
+                };
+            }
+        }
+    }
+
+    TxSimulationResult {
+        success: true,
+        chunks: simulated_txs.iter().map(|tx| tx.chunks).sum(),
+        txs: simulated_txs,
+        fail_reason: None,
+        failed_tx_index: None,
+    }
+}
+
+fn simulated_tx(tx_hash: TxHash, success: OpSuccess) -> SimulatedTx {
+    SimulatedTx {
+        tx_hash,
+        chunks: success.executed_op.chunks(),
+        fee: success.fee.map(|fee| SimulatedFee {
+            token: fee.token,
+            amount: fee.amount,
+        }),
+        updates: success.updates,
+    }
+}
+
+/// Creates a state containing the last committed versions of all the accounts
+/// the transactions may touch.
+async fn load_state_for_txs(
+    storage: &mut StorageProcessor<'_>,
+    txs: &[ZkSyncTx],
+) -> QueryResult<ZkSyncState> {
+    let mut account_ids = HashSet::new();
+    let mut addresses = HashSet::new();
+    let mut nft_ids = HashSet::new();
+    for tx in txs {
+        collect_affected_accounts(tx, &mut account_ids, &mut addresses, &mut nft_ids);
+    }
+
+    let mut transaction = storage.start_transaction().await?;
+
+    // NFT withdrawals also require the creator account to be present.
+    let mut nfts = Vec::new();
+    for token_id in nft_ids {
+        if let Some(nft) = transaction.tokens_schema().get_nft(token_id).await? {
+            addresses.insert(nft.creator_address);
+            nfts.push(nft);
+        }
+    }
+
+    for address in addresses {
+        if let Some(account_id) = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?
+        {
+            account_ids.insert(account_id);
+        }
+    }
+
+    let mut accounts = AccountMap::default();
+    for account_id in account_ids {
+        let (_, committed_state) = transaction
+            .chain()
+            .account_schema()
+            .last_committed_state_for_account(account_id)
+            .await?;
+        if let Some(account) = committed_state {
+            accounts.insert(account_id, account);
+        }
+    }
+
+    let next_free_id = transaction
+        .chain()
+        .account_schema()
+        .next_free_account_id()
+        .await?;
+    transaction.commit().await?;
+
+    let mut state = ZkSyncState::from_partial_acc_map(accounts, next_free_id);
+    state.nfts = nfts.into_iter().map(|nft| (nft.id, nft)).collect();
+    Ok(state)
+}
+
+fn collect_affected_accounts(
+    tx: &ZkSyncTx,
+    account_ids: &mut HashSet<AccountId>,
+    addresses: &mut HashSet<Address>,
+    nft_ids: &mut HashSet<TokenId>,
+) {
+    match tx {
+        ZkSyncTx::Transfer(tx) => {
+            account_ids.insert(tx.account_id);
+            addresses.extend([tx.from, tx.to]);
+        }
+        ZkSyncTx::Withdraw(tx) => {
+            account_ids.insert(tx.account_id);
+            addresses.insert(tx.from);
+        }
+        ZkSyncTx::Close(tx) => {
+            addresses.insert(tx.account);
+        }
+        ZkSyncTx::ChangePubKey(tx) => {
+            account_ids.insert(tx.account_id);
+            addresses.insert(tx.account);
+        }
+        ZkSyncTx::ForcedExit(tx) => {
+            account_ids.insert(tx.initiator_account_id);
+            addresses.insert(tx.target);
+        }
+        ZkSyncTx::Swap(tx) => {
+            account_ids.extend([
+                tx.submitter_id,
+                tx.orders.0.account_id,
+                tx.orders.1.account_id,
+            ]);
+            addresses.extend([
+                tx.submitter_address,
+                tx.orders.0.recipient_address,
+                tx.orders.1.recipient_address,
+            ]);
+        }
+        ZkSyncTx::MintNFT(tx) => {
+            account_ids.extend([tx.creator_id, NFT_STORAGE_ACCOUNT_ID]);
+            addresses.extend([tx.creator_address, tx.recipient]);
+        }
+        ZkSyncTx::WithdrawNFT(tx) => {
+            account_ids.insert(tx.account_id);
+            addresses.insert(tx.from);
+            nft_ids.insert(tx.token);
+        }
+    }
+}
@@ -5,13 +5,13 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 // External uses
-use ethabi::{encode, Contract, Function, Token as AbiToken};
+use ethabi::{decode, encode, Contract, Function, ParamType, Token as AbiToken};
 use jsonrpc_core::{Error, ErrorCode, Result};
 use tiny_keccak::keccak256;
 // Workspace uses
 use zksync_storage::StorageProcessor;
 use zksync_token_db_cache::TokenDBCache;
-use zksync_types::{TokenId, TokenKind, NFT};
+use zksync_types::{BlockNumber, Token, TokenId, TokenKind, NFT};
 
 // Local uses
 use super::{
@@ -34,6 +34,8 @@ pub struct CallsHelper {
 impl CallsHelper {
     const SHA256_MULTI_HASH: [u8; 2] = [18, 32]; // 0x1220
     const ALPHABET: &'static str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    /// Selector of the ERC20 `transfer(address,uint256)` function.
+    const ERC20_TRANSFER_SELECTOR: Selector = [0xa9, 0x05, 0x9c, 0xbb];
 
     fn revert_error(message: &str) -> Error {
         Error {
@@ -93,11 +95,60 @@ impl CallsHelper {
         }
     }
 
+    /// Returns the token and the recipient of the transfer made by the call: either the plain ETH transfer
+    /// (call without data) or the `transfer` call of the ERC20 token contract.
+    /// Returns `None` if the call is not a transfer.
+    pub async fn transfer(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: &[u8],
+    ) -> Result<Option<(TokenId, H160)>> {
+        if data.is_empty() {
+            return Ok(Some((TokenId(0), to)));
+        }
+        let transfer = self.erc20_transfer(storage, to, data).await?;
+        Ok(transfer.map(|(token, recipient, _)| (token.id, recipient)))
+    }
+
+    /// Decodes the `transfer` call of the ERC20 token contract.
+    /// Returns the token, the recipient and the amount of the transfer,
+    /// or `None` if the call is not a transfer.
+    pub async fn erc20_transfer(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        to: H160,
+        data: &[u8],
+    ) -> Result<Option<(Token, H160, U256)>> {
+        let token = self
+            .tokens
+            .get_token(storage, to)
+            .await
+            .map_err(|_| Error::internal_error())?;
+        let token = match token {
+            Some(token) if matches!(token.kind, TokenKind::ERC20) => token,
+            _ => return Ok(None),
+        };
+        if data.len() < 4 || data[0..4] != Self::ERC20_TRANSFER_SELECTOR {
+            return Ok(None);
+        }
+        let params = decode(&[ParamType::Address, ParamType::Uint(256)], &data[4..])
+            .map_err(|_| Error::invalid_params("Invalid input of the ERC20 transfer call"))?;
+        match (
+            params[0].clone().into_address(),
+            params[1].clone().into_uint(),
+        ) {
+            (Some(recipient), Some(amount)) => Ok(Some((token, recipient, amount))),
+            _ => Ok(None),
+        }
+    }
+
     pub async fn execute(
         &self,
         storage: &mut StorageProcessor<'_>,
         to: H160,
         data: Vec<u8>,
+        block_number: BlockNumber,
     ) -> Result<Vec<u8>> {
         let mut transaction = storage
             .start_transaction()
@@ -213,7 +264,7 @@ impl CallsHelper {
                     let balance = transaction
                         .chain()
                         .account_schema()
-                        .get_account_nft_balance(address)
+                        .get_account_nft_balance_for_block(address, block_number)
                         .await
                         .map_err(|_| Error::internal_error())?;
                     encode(&[AbiToken::Uint(U256::from(balance))])
@@ -227,7 +278,7 @@ impl CallsHelper {
                         let owner_id = transaction
                             .chain()
                             .account_schema()
-                            .get_nft_owner(nft.id)
+                            .get_nft_owner_for_block(nft.id, block_number)
                             .await
                             .map_err(|_| Error::internal_error())?;
                         let owner_address = if let Some(owner_id) = owner_id {
@@ -275,12 +326,6 @@ impl CallsHelper {
                 "decimals" => encode(&[AbiToken::Uint(U256::from(token.decimals))]),
                 "totalSupply" | "allowance" => encode(&[AbiToken::Uint(U256::max_value())]),
                 "balanceOf" => {
-                    let block = transaction
-                        .chain()
-                        .block_schema()
-                        .get_last_verified_confirmed_block()
-                        .await
-                        .map_err(|_| Error::internal_error())?;
                     let address = params[0]
                         .clone()
                         .into_address()
@@ -288,7 +333,7 @@ impl CallsHelper {
                     let balance = transaction
                         .chain()
                         .account_schema()
-                        .get_account_balance_for_block(address, block, token.id)
+                        .get_account_balance_for_block(address, block_number, token.id)
                         .await
                         .map_err(|_| Error::internal_error())?;
                     encode(&[AbiToken::Uint(u256_from_biguint(balance))])
@@ -12,6 +12,12 @@ pub fn u256_from_biguint(number: BigUint) -> U256 {
     U256::from_dec_str(&number.to_string()).unwrap()
 }
 
+pub fn biguint_from_u256(number: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    number.to_big_endian(&mut bytes);
+    BigUint::from_bytes_be(&bytes)
+}
+
 pub async fn resolve_block_number(
     storage: &mut StorageProcessor<'_>,
     number: Option<BlockNumber>,
@@ -0,0 +1,113 @@
+//! Storage of the filters installed with `eth_newFilter` and `eth_newBlockFilter`.
+//!
+//! Filters are kept in memory of the API server, and each of them remembers the last block
+//! returned by `eth_getFilterChanges`. Filters which were not polled for a while are uninstalled.
+
+// Built-in uses
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+// External uses
+// Workspace uses
+use zksync_types::BlockNumber;
+// Local uses
+use super::types::{Filter, U256};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstalledFilter {
+    /// Filter of the logs, installed with `eth_newFilter`.
+    Logs(Filter),
+    /// Filter of the new blocks, installed with `eth_newBlockFilter`.
+    Blocks,
+}
+
+#[derive(Debug)]
+struct FilterState {
+    filter: InstalledFilter,
+    /// The last block returned to the client.
+    last_block: BlockNumber,
+    last_poll: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct Filters {
+    filters: Arc<Mutex<HashMap<U256, FilterState>>>,
+    max_filters: usize,
+    timeout: Duration,
+}
+
+impl Filters {
+    pub fn new(max_filters: usize, timeout: Duration) -> Self {
+        Self {
+            filters: Default::default(),
+            max_filters,
+            timeout,
+        }
+    }
+
+    /// Installs the filter, which will return the changes after the `last_block`.
+    /// Returns `None` if the limit of the installed filters is reached.
+    pub fn install(&self, filter: InstalledFilter, last_block: BlockNumber) -> Option<U256> {
+        let mut filters = self.filters.lock().unwrap();
+        let now = Instant::now();
+        let timeout = self.timeout;
+        filters.retain(|_, state| now.duration_since(state.last_poll) < timeout);
+        if filters.len() >= self.max_filters {
+            return None;
+        }
+
+        let id = loop {
+            let id = U256::from(zksync_crypto::rand::random::<u64>());
+            if !filters.contains_key(&id) {
+                break id;
+            }
+        };
+        filters.insert(
+            id,
+            FilterState {
+                filter,
+                last_block,
+                last_poll: now,
+            },
+        );
+        Some(id)
+    }
+
+    /// Returns the filter and the last block returned to the client.
+    /// Returns `None` if the filter doesn't exist or has expired.
+    pub fn get(&self, id: U256) -> Option<(InstalledFilter, BlockNumber)> {
+        let mut filters = self.filters.lock().unwrap();
+        let now = Instant::now();
+        match filters.get_mut(&id) {
+            Some(state) if now.duration_since(state.last_poll) < self.timeout => {
+                state.last_poll = now;
+                Some((state.filter.clone(), state.last_block))
+            }
+            Some(_) => {
+                filters.remove(&id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Checks whether the filter exists and hasn't expired, without resetting its timeout.
+    pub fn is_installed(&self, id: U256) -> bool {
+        match self.filters.lock().unwrap().get(&id) {
+            Some(state) => state.last_poll.elapsed() < self.timeout,
+            None => false,
+        }
+    }
+
+    /// Remembers the last block returned to the client.
+    pub fn update_last_block(&self, id: U256, last_block: BlockNumber) {
+        if let Some(state) = self.filters.lock().unwrap().get_mut(&id) {
+            state.last_block = state.last_block.max(last_block);
+        }
+    }
+
+    /// Uninstalls the filter. Returns `false` if the filter didn't exist.
+    pub fn uninstall(&self, id: U256) -> bool {
+        self.filters.lock().unwrap().remove(&id).is_some()
+    }
+}
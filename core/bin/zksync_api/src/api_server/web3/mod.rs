@@ -1,6 +1,6 @@
 // Built-in uses
 // External uses
-
+use futures::channel::mpsc;
 use jsonrpc_core::{Error, IoHandler, MetaIoHandler, Metadata, Middleware, Result};
 use jsonrpc_http_server::ServerBuilder;
 // Workspace uses
@@ -8,20 +8,28 @@ use jsonrpc_http_server::ServerBuilder;
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_utils::panic_notify::{spawn_panic_handler, ThreadPanicNotify};
 // Local uses
-use self::{calls::CallsHelper, logs::LogsHelper, rpc_trait::Web3Rpc};
+use self::{calls::CallsHelper, filters::Filters, logs::LogsHelper, rpc_trait::Web3Rpc};
+use super::tx_sender::TxSender;
+use crate::{fee_ticker::FeeTicker, signature_checker::VerifySignatureRequest};
 
 use tokio::task::JoinHandle;
-use zksync_config::configs::api::{TokenConfig, Web3Config};
+use zksync_config::configs::api::{CommonApiConfig, TokenConfig, Web3Config};
+use zksync_mempool::MempoolTransactionRequest;
 
 mod calls;
 mod converter;
+mod filters;
 mod logs;
+mod raw_tx;
 mod rpc_impl;
 mod rpc_trait;
+mod subscriptions;
 #[cfg(test)]
 mod tests;
 mod types;
 
+pub use self::subscriptions::start_ws_server;
+
 pub const ZKSYNC_PROXY_ADDRESS: &str = "1000000000000000000000000000000000000000";
 pub const NFT_FACTORY_ADDRESS: &str = "2000000000000000000000000000000000000000";
 
@@ -30,6 +38,8 @@ pub struct Web3RpcApp {
     connection_pool: ConnectionPool,
     logs_helper: LogsHelper,
     calls_helper: CallsHelper,
+    filters: Filters,
+    tx_sender: TxSender,
     max_block_range: u32,
     chain_id: u32,
 }
@@ -37,6 +47,7 @@ pub struct Web3RpcApp {
 impl Web3RpcApp {
     pub fn new(
         connection_pool: ConnectionPool,
+        tx_sender: TxSender,
         config: &Web3Config,
         token_config: &TokenConfig,
     ) -> Self {
@@ -44,6 +55,8 @@ impl Web3RpcApp {
             connection_pool,
             logs_helper: LogsHelper::new(token_config.invalidate_token_cache_period()),
             calls_helper: CallsHelper::new(token_config.invalidate_token_cache_period()),
+            filters: Filters::new(config.max_filters, config.filter_timeout()),
+            tx_sender,
             max_block_range: config.max_block_range,
             chain_id: config.chain_id,
         }
@@ -63,12 +76,24 @@ impl Web3RpcApp {
 
 pub fn start_rpc_server(
     connection_pool: ConnectionPool,
+    sign_verify_request_sender: mpsc::Sender<VerifySignatureRequest>,
+    ticker: FeeTicker,
     web3_config: &Web3Config,
+    common_api_config: &CommonApiConfig,
     token_config: &TokenConfig,
+    mempool_tx_sender: mpsc::Sender<MempoolTransactionRequest>,
 ) -> JoinHandle<()> {
     let addr = web3_config.bind_addr();
 
-    let rpc_app = Web3RpcApp::new(connection_pool, web3_config, token_config);
+    let tx_sender = TxSender::new(
+        connection_pool.clone(),
+        sign_verify_request_sender,
+        ticker,
+        common_api_config,
+        token_config,
+        mempool_tx_sender,
+    );
+    let rpc_app = Web3RpcApp::new(connection_pool, tx_sender, web3_config, token_config);
     let (handler, panic_sender) = spawn_panic_handler();
 
     std::thread::spawn(move || {
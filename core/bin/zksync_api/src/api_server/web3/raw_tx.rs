@@ -0,0 +1,86 @@
+//! Decoding of the raw Ethereum transactions sent via `eth_sendRawTransaction`.
+//!
+//! Only the legacy transactions signed according to EIP-155 are accepted, so the transaction
+//! can't be replayed on the other chain.
+
+// Built-in uses
+// External uses
+use jsonrpc_core::{Error, Result};
+use rlp::{Rlp, RlpStream};
+use web3::signing::{keccak256, recover};
+// Workspace uses
+// Local uses
+use super::types::{H160, U256};
+
+/// Legacy Ethereum transaction along with its signer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedEthTransaction {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas: U256,
+    pub to: H160,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub chain_id: u64,
+    pub from: H160,
+}
+
+impl SignedEthTransaction {
+    /// Decodes the EIP-155 transaction and recovers its signer.
+    pub fn decode(raw: &[u8]) -> Result<Self> {
+        let invalid_tx = |_| Error::invalid_params("Invalid RLP encoding of the transaction");
+
+        let rlp = Rlp::new(raw);
+        if !rlp.is_list() {
+            return Err(Error::invalid_params(
+                "Only legacy transactions are supported",
+            ));
+        }
+        if rlp.item_count().map_err(invalid_tx)? != 9 {
+            return Err(Error::invalid_params(
+                "Transaction must consist of 9 RLP items",
+            ));
+        }
+
+        let v: u64 = rlp.val_at(6).map_err(invalid_tx)?;
+        // EIP-155: `v = chain_id * 2 + 35 + recovery_id`.
+        if v < 35 {
+            return Err(Error::invalid_params(
+                "Transaction must be signed according to EIP-155",
+            ));
+        }
+        let chain_id = (v - 35) / 2;
+        let recovery_id = (v - 35) % 2;
+
+        let r: U256 = rlp.val_at(7).map_err(invalid_tx)?;
+        let s: U256 = rlp.val_at(8).map_err(invalid_tx)?;
+        let mut signature = [0u8; 64];
+        r.to_big_endian(&mut signature[..32]);
+        s.to_big_endian(&mut signature[32..]);
+
+        // The signed message is the transaction with `chain_id, 0, 0` instead of the signature.
+        let mut stream = RlpStream::new_list(9);
+        for i in 0..6 {
+            stream.append_raw(rlp.at(i).map_err(invalid_tx)?.as_raw(), 1);
+        }
+        stream.append(&chain_id);
+        stream.append(&0u8);
+        stream.append(&0u8);
+        let message_hash = keccak256(&stream.out());
+        let from = recover(&message_hash, &signature, recovery_id as i32)
+            .map_err(|_| Error::invalid_params("Invalid signature of the transaction"))?;
+
+        Ok(Self {
+            nonce: rlp.val_at(0).map_err(invalid_tx)?,
+            gas_price: rlp.val_at(1).map_err(invalid_tx)?,
+            gas: rlp.val_at(2).map_err(invalid_tx)?,
+            to: rlp
+                .val_at(3)
+                .map_err(|_| Error::invalid_params("Contracts can't be deployed to zkSync"))?,
+            value: rlp.val_at(4).map_err(invalid_tx)?,
+            data: rlp.val_at(5).map_err(invalid_tx)?,
+            chain_id,
+            from,
+        })
+    }
+}
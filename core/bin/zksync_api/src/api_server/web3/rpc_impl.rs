@@ -2,24 +2,44 @@
 use ethabi::Address;
 use std::time::Instant;
 // External uses
-use jsonrpc_core::{Error, Result};
+use jsonrpc_core::{Error, ErrorCode, Result};
+use num::{Integer, Zero};
+use tiny_keccak::keccak256;
 // Workspace uses
 use zksync_crypto::convert::FeConvert;
 use zksync_storage::{
     chain::{block::records::StorageBlock, operations_ext::records::Web3TxReceipt},
     StorageProcessor,
 };
-use zksync_types::{ExecutedOperations, TokenId, ZkSyncOp};
+use zksync_types::{
+    helpers::{
+        closest_greater_or_eq_packable_fee_amount, closest_packable_fee_amount,
+        is_token_amount_packable,
+    },
+    tx::TxSignature,
+    ExecutedOperations, Nonce, TokenId, TokenLike, Transfer, TxFeeTypes, ZkSyncOp, ZkSyncTx,
+};
 // Local uses
 use super::{
-    converter::{resolve_block_number, transaction_from_tx_data, u256_from_biguint},
+    converter::{
+        biguint_from_u256, resolve_block_number, transaction_from_tx_data, u256_from_biguint,
+    },
+    filters::InstalledFilter,
+    raw_tx::SignedEthTransaction,
     types::{
-        BlockInfo, BlockNumber, Bytes, CallRequest, CommonLogData, Filter, Log, Transaction,
-        TransactionReceipt, TxData, H160, H2048, H256, U256, U64,
+        BlockInfo, BlockNumber, Bytes, CallRequest, CommonLogData, Filter, FilterChanges, Log,
+        Transaction, TransactionReceipt, TxData, H160, H2048, H256, U256, U64,
     },
     Web3RpcApp,
 };
 
+/// Error code for the exceeded limits, as defined in EIP-1474.
+pub(super) const LIMIT_EXCEEDED_ERROR_CODE: i64 = -32005;
+/// Length of the ERC20 `transfer(address,uint256)` call data.
+const ERC20_TRANSFER_CALL_LENGTH: usize = 4 + 32 + 32;
+/// Length of the packed zkSync signature: the public key and the signature itself.
+const ZKSYNC_SIGNATURE_LENGTH: usize = 32 + 64;
+
 impl Web3RpcApp {
     pub async fn _impl_block_number(self) -> Result<U64> {
         let start = Instant::now();
@@ -252,68 +272,383 @@ impl Web3RpcApp {
             )));
         }
 
-        let topics = if let Some(mut topics) = filter.topics {
-            // If there is non-null topic at the non-first position then return empty vec,
-            // since all our logs contain exactly one topic.
-            let has_not_first = topics
-                .iter()
-                .enumerate()
-                .any(|(i, topic)| i > 0 && topic.is_some());
-            if has_not_first {
-                return Ok(Vec::new());
-            } else if topics.is_empty() {
-                Vec::new()
-            } else {
-                topics.remove(0).unwrap_or_default().0
-            }
-        } else {
-            Vec::new()
+        let result = self
+            .filtered_logs(&mut transaction, from_block, to_block, &filter)
+            .await?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|_| Error::internal_error())?;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_logs");
+        Ok(result)
+    }
+
+    /// Submits the ERC20 `transfer` call signed by the owner of the account as the zkSync `Transfer`.
+    /// The fee of the transfer is `gas * gasPrice` of the signed transaction in the transferred token,
+    /// rounded down to the closest packable amount, and must cover the fee required by the fee ticker.
+    ///
+    /// Every zkSync transaction must be signed with the zkSync key of the account (it's checked by the circuit),
+    /// which can't be derived from the Ethereum signature on the server side. So the zkSync signature of the
+    /// resulting transfer (packed public key and signature, 96 bytes) must follow the arguments of the call.
+    ///
+    /// The returned hash is the hash of the raw transaction, the transfer can be queried by it
+    /// via `eth_getTransactionByHash` and `eth_getTransactionReceipt`.
+    pub async fn _impl_send_raw_transaction(self, tx: Bytes) -> Result<H256> {
+        let start = Instant::now();
+        let eth_tx = SignedEthTransaction::decode(&tx.0)?;
+        if eth_tx.chain_id != u64::from(self.chain_id) {
+            return Err(Error::invalid_params(format!(
+                "Transaction is signed for the chain {}, expected {}",
+                eth_tx.chain_id, self.chain_id
+            )));
+        }
+        if !eth_tx.value.is_zero() {
+            return Err(Error::invalid_params(
+                "ETH can't be sent along with the ERC20 transfer",
+            ));
+        }
+        if eth_tx.data.len() != ERC20_TRANSFER_CALL_LENGTH + ZKSYNC_SIGNATURE_LENGTH {
+            return Err(Error::invalid_params(
+                "Transaction must be the ERC20 `transfer` call followed by the zkSync signature",
+            ));
+        }
+        let (call, signature) = eth_tx.data.split_at(ERC20_TRANSFER_CALL_LENGTH);
+        let signature = TxSignature::deserialize_from_packed_bytes(signature)
+            .map_err(|_| Error::invalid_params("Invalid zkSync signature"))?;
+
+        let (token, recipient, amount, account_id) = {
+            let mut storage = self.access_storage().await?;
+            let (token, recipient, amount) = self
+                .calls_helper
+                .erc20_transfer(&mut storage, eth_tx.to, call)
+                .await?
+                .ok_or_else(|| Error::invalid_params("Only ERC20 transfers can be sent"))?;
+            let account_id = storage
+                .chain()
+                .account_schema()
+                .account_id_by_address(eth_tx.from)
+                .await
+                .map_err(|_| Error::internal_error())?
+                .ok_or_else(|| Error::invalid_params("Account of the sender doesn't exist"))?;
+            (token, recipient, amount, account_id)
         };
-        let addresses = filter.address.map(|a| a.0).unwrap_or_default();
-        let mut result = Vec::new();
+        if eth_tx.nonce > U256::from(u32::MAX) {
+            return Err(Error::invalid_params("Nonce is too big"));
+        }
+        let amount = biguint_from_u256(amount);
+        if !is_token_amount_packable(&amount) {
+            return Err(Error::invalid_params("Transfer amount is not packable"));
+        }
 
-        let receipts = transaction
+        let fee = biguint_from_u256(eth_tx.gas.saturating_mul(eth_tx.gas_price));
+        let transfer = ZkSyncTx::Transfer(Box::new(Transfer::new(
+            account_id,
+            eth_tx.from,
+            recipient,
+            token.id,
+            amount,
+            closest_packable_fee_amount(&fee),
+            Nonce(eth_tx.nonce.as_u32()),
+            Default::default(),
+            Some(signature),
+        )));
+
+        // The mapping is stored before the submission, so the transaction can be found by the returned hash
+        // as soon as it's executed. Resubmission of the same raw transaction results in the same transfer.
+        let eth_hash = H256::from(keccak256(&tx.0));
+        self.access_storage()
+            .await?
             .chain()
             .operations_ext_schema()
-            .web3_receipts(from_block, to_block)
+            .store_web3_raw_transaction(eth_hash.as_bytes(), transfer.hash())
             .await
             .map_err(|_| Error::internal_error())?;
-        for receipt in receipts {
-            let logs = self.logs_from_receipt(&mut transaction, receipt).await?;
-            let filtered = logs.into_iter().filter(|log| {
-                if !topics.is_empty() && !topics.contains(&log.topics[0]) {
-                    return false;
-                }
-                if !addresses.is_empty() && !addresses.contains(&log.address) {
-                    return false;
-                }
-                true
-            });
-            result.extend(filtered);
-        }
+        self.tx_sender
+            .submit_eth_authorized_tx(transfer, eth_tx.from)
+            .await?;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "send_raw_transaction");
+        Ok(eth_hash)
+    }
+
+    pub async fn _impl_new_filter(self, filter: Filter) -> Result<U256> {
+        let start = Instant::now();
+        let result = self.install_filter(InstalledFilter::Logs(filter)).await;
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "new_filter");
+        result
+    }
 
+    pub async fn _impl_new_block_filter(self) -> Result<U256> {
+        let start = Instant::now();
+        let result = self.install_filter(InstalledFilter::Blocks).await;
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "new_block_filter");
+        result
+    }
+
+    pub async fn _impl_get_filter_changes(self, id: U256) -> Result<FilterChanges> {
+        let start = Instant::now();
+        let (filter, last_block) = self
+            .filters
+            .get(id)
+            .ok_or_else(|| Error::invalid_params("Filter not found"))?;
+
+        let mut storage = self.access_storage().await?;
+        let mut transaction = storage
+            .start_transaction()
+            .await
+            .map_err(|_| Error::internal_error())?;
+        let latest_block = transaction
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await
+            .map_err(|_| Error::internal_error())?;
+        // The amount of blocks processed at once is limited the same way as in `eth_getLogs`,
+        // the rest of the changes will be returned on the next poll.
+        let from_block = last_block + 1;
+        let to_block = latest_block.min(last_block + self.max_block_range);
+
+        let result = match filter {
+            InstalledFilter::Blocks => {
+                let mut hashes = Vec::new();
+                for block_number in *from_block..=*to_block {
+                    let block = Self::storage_block(
+                        &mut transaction,
+                        zksync_types::BlockNumber(block_number),
+                    )
+                    .await?
+                    .ok_or_else(Error::internal_error)?;
+                    hashes.push(H256::from_slice(&block.root_hash));
+                }
+                FilterChanges::Hashes(hashes)
+            }
+            InstalledFilter::Logs(filter) => {
+                // Only the blocks within the range of the filter are checked.
+                let from_block = match filter.from_block {
+                    Some(BlockNumber::Number(number)) if number.as_u64() > *from_block as u64 => {
+                        zksync_types::BlockNumber(number.as_u64() as u32)
+                    }
+                    _ => from_block,
+                };
+                let to_block = match filter.to_block {
+                    Some(BlockNumber::Number(number)) if number.as_u64() < *to_block as u64 => {
+                        zksync_types::BlockNumber(number.as_u64() as u32)
+                    }
+                    _ => to_block,
+                };
+                let logs = if from_block <= to_block {
+                    self.filtered_logs(&mut transaction, from_block, to_block, &filter)
+                        .await?
+                } else {
+                    Vec::new()
+                };
+                FilterChanges::Logs(logs)
+            }
+        };
         transaction
             .commit()
             .await
             .map_err(|_| Error::internal_error())?;
+        self.filters.update_last_block(id, to_block);
 
-        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_logs");
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_filter_changes");
         Ok(result)
     }
 
-    pub async fn _impl_call(self, req: CallRequest, _block: Option<BlockNumber>) -> Result<Bytes> {
+    pub async fn _impl_get_filter_logs(self, id: U256) -> Result<Vec<Log>> {
+        let filter = match self.filters.get(id) {
+            Some((InstalledFilter::Logs(filter), _)) => filter,
+            Some((InstalledFilter::Blocks, _)) => {
+                return Err(Error::invalid_params("Filter is not a log filter"));
+            }
+            None => return Err(Error::invalid_params("Filter not found")),
+        };
+        self._impl_get_logs(filter).await
+    }
+
+    async fn install_filter(&self, filter: InstalledFilter) -> Result<U256> {
+        let mut storage = self.access_storage().await?;
+        let last_block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await
+            .map_err(|_| Error::internal_error())?;
+        self.filters
+            .install(filter, last_block)
+            .ok_or_else(|| Error {
+                code: ErrorCode::ServerError(LIMIT_EXCEEDED_ERROR_CODE),
+                message: "Too many filters are installed".to_string(),
+                data: None,
+            })
+    }
+
+    /// Returns the logs of the blocks in the given range which match the filter.
+    pub(crate) async fn filtered_logs(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: zksync_types::BlockNumber,
+        to_block: zksync_types::BlockNumber,
+        filter: &Filter,
+    ) -> Result<Vec<Log>> {
+        let receipts = storage
+            .chain()
+            .operations_ext_schema()
+            .web3_receipts(from_block, to_block)
+            .await
+            .map_err(|_| Error::internal_error())?;
+
+        let mut result = Vec::new();
+        for receipt in receipts {
+            let logs = self.logs_from_receipt(storage, receipt).await?;
+            result.extend(logs.into_iter().filter(|log| filter.matches(log)));
+        }
+        Ok(result)
+    }
+
+    pub async fn _impl_call(self, req: CallRequest, block: Option<BlockNumber>) -> Result<Bytes> {
         let start = Instant::now();
         let mut storage = self.access_storage().await?;
+        let block_number = resolve_block_number(&mut storage, block)
+            .await?
+            .ok_or_else(|| Error::invalid_params("Block with such number doesn't exist yet"))?;
 
         let result = self
             .calls_helper
-            .execute(&mut storage, req.to, req.data.unwrap_or_default().0)
+            .execute(
+                &mut storage,
+                req.to,
+                req.data.unwrap_or_default().0,
+                block_number,
+            )
             .await;
 
         metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "call");
         result.map(Bytes)
     }
 
+    /// Returns the nonce of the account. For the `pending` and `committed` blocks (and by default)
+    /// the nonce from the last committed state is returned, since it's the one the next
+    /// transaction of the account should use.
+    pub async fn _impl_get_transaction_count(
+        self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> Result<U256> {
+        let start = Instant::now();
+        let mut storage = self.access_storage().await?;
+        let mut transaction = storage
+            .start_transaction()
+            .await
+            .map_err(|_| Error::internal_error())?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await
+            .map_err(|_| Error::internal_error())?;
+        let account = if let Some(account_id) = account_id {
+            match block {
+                None | Some(BlockNumber::Pending) | Some(BlockNumber::Committed) => {
+                    transaction
+                        .chain()
+                        .account_schema()
+                        .last_committed_state_for_account(account_id)
+                        .await
+                        .map_err(|_| Error::internal_error())?
+                        .1
+                }
+                block => {
+                    let block_number = resolve_block_number(&mut transaction, block)
+                        .await?
+                        .ok_or_else(|| {
+                            Error::invalid_params("Block with such number doesn't exist yet")
+                        })?;
+                    transaction
+                        .chain()
+                        .account_schema()
+                        .account_state_for_block(account_id, block_number)
+                        .await
+                        .map_err(|_| Error::internal_error())?
+                        .1
+                }
+            }
+        } else {
+            None
+        };
+        transaction
+            .commit()
+            .await
+            .map_err(|_| Error::internal_error())?;
+
+        let result = account
+            .map(|account| U256::from(*account.nonce))
+            .unwrap_or_default();
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "get_transaction_count");
+        Ok(result)
+    }
+
+    /// Returns the gas price the fees are calculated with by the fee ticker.
+    pub async fn _impl_gas_price(self) -> Result<U256> {
+        let start = Instant::now();
+        let fee = self
+            .tx_sender
+            .ticker
+            .get_fee_from_ticker_in_wei(
+                TxFeeTypes::Transfer,
+                TokenLike::Id(TokenId(0)),
+                Address::zero(),
+            )
+            .await
+            .map_err(|_| Error::internal_error())?
+            .normal_fee;
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "gas_price");
+        Ok(u256_from_biguint(fee.gas_price_wei))
+    }
+
+    /// Estimates the gas of the transfer made by the call, so that the gas multiplied by the gas price
+    /// returned by `eth_gasPrice` covers the fee of the corresponding zkSync `Transfer` in the transferred token.
+    /// That's how the fee of the transaction sent via `eth_sendRawTransaction` is calculated.
+    pub async fn _impl_estimate_gas(
+        self,
+        req: CallRequest,
+        _block: Option<BlockNumber>,
+    ) -> Result<U256> {
+        let start = Instant::now();
+        let (token, recipient) = {
+            let mut storage = self.access_storage().await?;
+            self.calls_helper
+                .transfer(&mut storage, req.to, &req.data.unwrap_or_default().0)
+                .await?
+                .ok_or_else(|| {
+                    Error::invalid_params("Only ETH and ERC20 transfers can be estimated")
+                })?
+        };
+
+        let fee = self
+            .tx_sender
+            .ticker
+            .get_fee_from_ticker_in_wei(TxFeeTypes::Transfer, TokenLike::Id(token), recipient)
+            .await
+            .map_err(|_| Error::internal_error())?
+            .normal_fee;
+        // The fee is rounded down to the closest packable amount on submission,
+        // so the estimated fee must not be less than the packable one.
+        let total_fee = closest_greater_or_eq_packable_fee_amount(&fee.total_fee);
+        let gas = if fee.gas_price_wei.is_zero() {
+            fee.gas_tx_amount
+        } else {
+            total_fee.div_ceil(&fee.gas_price_wei)
+        };
+
+        metrics::histogram!("api", start.elapsed(), "type" => "web3", "endpoint_name" => "estimate_gas");
+        Ok(u256_from_biguint(gas))
+    }
+
     pub(crate) async fn logs_from_receipt(
         &self,
         storage: &mut StorageProcessor<'_>,
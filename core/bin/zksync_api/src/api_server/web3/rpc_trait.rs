@@ -4,8 +4,8 @@ use jsonrpc_derive::rpc;
 // Local uses
 use super::{
     types::{
-        BlockInfo, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction, TransactionReceipt,
-        H160, H256, U256, U64,
+        BlockInfo, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Log, Transaction,
+        TransactionReceipt, H160, H256, U256, U64,
     },
     Web3RpcApp,
 };
@@ -37,7 +37,7 @@ pub trait Web3Rpc {
     fn hashrate(&self) -> Result<U256>;
 
     #[rpc(name = "eth_gasPrice", returns = "U256")]
-    fn gas_price(&self) -> Result<U256>;
+    fn gas_price(&self) -> BoxFutureResult<U256>;
 
     #[rpc(name = "eth_accounts", returns = "Vec<H160>")]
     fn accounts(&self) -> Result<Vec<H160>>;
@@ -94,6 +94,37 @@ pub trait Web3Rpc {
 
     #[rpc(name = "eth_call", returns = "Bytes")]
     fn call(&self, req: CallRequest, _block: Option<BlockNumber>) -> BoxFutureResult<Bytes>;
+
+    #[rpc(name = "eth_sendRawTransaction", returns = "H256")]
+    fn send_raw_transaction(&self, tx: Bytes) -> BoxFutureResult<H256>;
+
+    #[rpc(name = "eth_estimateGas", returns = "U256")]
+    fn estimate_gas(&self, req: CallRequest, _block: Option<BlockNumber>) -> BoxFutureResult<U256>;
+
+    #[rpc(name = "eth_getTransactionCount", returns = "U256")]
+    fn get_transaction_count(
+        &self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<U256>;
+
+    #[rpc(name = "eth_chainId", returns = "U64")]
+    fn chain_id(&self) -> Result<U64>;
+
+    #[rpc(name = "eth_newFilter", returns = "U256")]
+    fn new_filter(&self, filter: Filter) -> BoxFutureResult<U256>;
+
+    #[rpc(name = "eth_newBlockFilter", returns = "U256")]
+    fn new_block_filter(&self) -> BoxFutureResult<U256>;
+
+    #[rpc(name = "eth_getFilterChanges", returns = "FilterChanges")]
+    fn get_filter_changes(&self, id: U256) -> BoxFutureResult<FilterChanges>;
+
+    #[rpc(name = "eth_getFilterLogs", returns = "Vec<Log>")]
+    fn get_filter_logs(&self, id: U256) -> BoxFutureResult<Vec<Log>>;
+
+    #[rpc(name = "eth_uninstallFilter", returns = "bool")]
+    fn uninstall_filter(&self, id: U256) -> Result<bool>;
 }
 
 impl Web3Rpc for Web3RpcApp {
@@ -117,8 +148,8 @@ impl Web3Rpc for Web3RpcApp {
         Ok(U256::zero())
     }
 
-    fn gas_price(&self) -> Result<U256> {
-        Ok(U256::zero())
+    fn gas_price(&self) -> BoxFutureResult<U256> {
+        spawn!(self._impl_gas_price())
     }
 
     fn accounts(&self) -> Result<Vec<H160>> {
@@ -183,4 +214,44 @@ impl Web3Rpc for Web3RpcApp {
     fn call(&self, req: CallRequest, block: Option<BlockNumber>) -> BoxFutureResult<Bytes> {
         spawn! { self._impl_call(req, block) }
     }
+
+    fn send_raw_transaction(&self, tx: Bytes) -> BoxFutureResult<H256> {
+        spawn!(self._impl_send_raw_transaction(tx))
+    }
+
+    fn estimate_gas(&self, req: CallRequest, block: Option<BlockNumber>) -> BoxFutureResult<U256> {
+        spawn!(self._impl_estimate_gas(req, block))
+    }
+
+    fn get_transaction_count(
+        &self,
+        address: H160,
+        block: Option<BlockNumber>,
+    ) -> BoxFutureResult<U256> {
+        spawn!(self._impl_get_transaction_count(address, block))
+    }
+
+    fn chain_id(&self) -> Result<U64> {
+        Ok(U64::from(self.chain_id))
+    }
+
+    fn new_filter(&self, filter: Filter) -> BoxFutureResult<U256> {
+        spawn!(self._impl_new_filter(filter))
+    }
+
+    fn new_block_filter(&self) -> BoxFutureResult<U256> {
+        spawn!(self._impl_new_block_filter())
+    }
+
+    fn get_filter_changes(&self, id: U256) -> BoxFutureResult<FilterChanges> {
+        spawn!(self._impl_get_filter_changes(id))
+    }
+
+    fn get_filter_logs(&self, id: U256) -> BoxFutureResult<Vec<Log>> {
+        spawn!(self._impl_get_filter_logs(id))
+    }
+
+    fn uninstall_filter(&self, id: U256) -> Result<bool> {
+        Ok(self.filters.uninstall(id))
+    }
 }
@@ -0,0 +1,456 @@
+//! WebSocket server of the web3 API supporting the `eth_subscribe` method.
+//!
+//! Subscribers are notified about the new blocks once they are finalized, since only
+//! the finalized blocks are visible through the web3 API. The new blocks are picked up
+//! from the `events` table, the same source which feeds the `zksync_event_listener`.
+//!
+//! Subscriptions and filters created over a connection are bound to it: their amount is limited
+//! per connection, and they are removed once the connection is closed.
+
+// Built-in uses
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+// External uses
+use futures::channel::mpsc;
+use jsonrpc_core::{Error, ErrorCode, MetaIoHandler, Metadata, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{
+    typed::{Sink, Subscriber},
+    PubSubHandler, PubSubMetadata, Session, SubscriptionId,
+};
+use jsonrpc_ws_server::RequestContext;
+use tokio::task::JoinHandle;
+// Workspace uses
+use zksync_config::configs::api::{CommonApiConfig, TokenConfig, Web3Config};
+use zksync_mempool::MempoolTransactionRequest;
+use zksync_storage::{event::EventType, ConnectionPool, StorageProcessor};
+use zksync_types::{
+    event::{
+        block::{BlockEvent, BlockStatus},
+        EventData, EventId, ZkSyncEvent,
+    },
+    BlockNumber,
+};
+use zksync_utils::panic_notify::{spawn_panic_handler, ThreadPanicNotify};
+// Local uses
+use super::{
+    rpc_impl::LIMIT_EXCEEDED_ERROR_CODE,
+    rpc_trait::BoxFutureResult,
+    types::{Filter, PubSubResult, SubscriptionKind, U256},
+    Web3RpcApp,
+};
+use crate::{
+    api_server::tx_sender::TxSender, fee_ticker::FeeTicker,
+    signature_checker::VerifySignatureRequest,
+};
+
+#[rpc]
+pub trait Web3PubSub {
+    type Metadata;
+
+    #[pubsub(subscription = "eth_subscription", subscribe, name = "eth_subscribe")]
+    fn subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<PubSubResult>,
+        kind: SubscriptionKind,
+        params: Option<Filter>,
+    );
+
+    #[pubsub(
+        subscription = "eth_subscription",
+        unsubscribe,
+        name = "eth_unsubscribe"
+    )]
+    fn unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        subscription: SubscriptionId,
+    ) -> Result<bool>;
+
+    // Filter methods of the WebSocket server are overridden to bind the filters to the connection.
+
+    #[rpc(meta, name = "eth_newFilter", returns = "U256")]
+    fn new_filter(&self, meta: Self::Metadata, filter: Filter) -> BoxFutureResult<U256>;
+
+    #[rpc(meta, name = "eth_newBlockFilter", returns = "U256")]
+    fn new_block_filter(&self, meta: Self::Metadata) -> BoxFutureResult<U256>;
+
+    #[rpc(meta, name = "eth_uninstallFilter", returns = "bool")]
+    fn uninstall_filter(&self, meta: Self::Metadata, id: U256) -> Result<bool>;
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+    kind: SubscriptionKind,
+    filter: Filter,
+    sink: Sink<PubSubResult>,
+}
+
+/// Active `eth_subscribe` subscriptions shared between the RPC handlers and the notifier.
+#[derive(Debug, Clone, Default)]
+struct Subscriptions(Arc<Mutex<HashMap<SubscriptionId, Subscription>>>);
+
+impl Subscriptions {
+    fn snapshot(&self) -> Vec<(SubscriptionId, Subscription)> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, subscription)| (id.clone(), subscription.clone()))
+            .collect()
+    }
+
+    fn remove(&self, id: &SubscriptionId) -> bool {
+        self.0.lock().unwrap().remove(id).is_some()
+    }
+}
+
+/// Subscriptions and filters created over a single WebSocket connection.
+#[derive(Debug, Default)]
+struct ConnectionResources {
+    subscriptions: HashSet<SubscriptionId>,
+    filters: HashSet<U256>,
+    /// Amount of the filters which are being installed.
+    pending_filters: usize,
+    /// Set once the connection is closed, so the resources created afterwards are released at once.
+    closed: bool,
+}
+
+/// Metadata of the WebSocket connection.
+#[derive(Clone)]
+pub(super) struct WsMetadata {
+    session: Arc<Session>,
+    resources: Arc<Mutex<ConnectionResources>>,
+}
+
+impl Metadata for WsMetadata {}
+
+impl PubSubMetadata for WsMetadata {
+    fn session(&self) -> Option<Arc<Session>> {
+        Some(self.session.clone())
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Web3SubApp {
+    rpc_app: Web3RpcApp,
+    subscriptions: Subscriptions,
+    max_subscriptions_per_connection: usize,
+}
+
+impl Web3SubApp {
+    pub(super) fn new(rpc_app: Web3RpcApp, max_subscriptions_per_connection: usize) -> Self {
+        Self {
+            rpc_app,
+            subscriptions: Subscriptions::default(),
+            max_subscriptions_per_connection,
+        }
+    }
+
+    /// Creates the metadata of the new connection, which releases
+    /// the subscriptions and filters of the connection once it's closed.
+    pub(super) fn connection_metadata(&self, session: Arc<Session>) -> WsMetadata {
+        let resources = Arc::new(Mutex::new(ConnectionResources::default()));
+        let subscriptions = self.subscriptions.clone();
+        let filters = self.rpc_app.filters.clone();
+        let connection_resources = resources.clone();
+        session.on_drop(move || {
+            let mut resources = connection_resources.lock().unwrap();
+            resources.closed = true;
+            for id in resources.subscriptions.drain() {
+                subscriptions.remove(&id);
+            }
+            for id in resources.filters.drain() {
+                filters.uninstall(id);
+            }
+        });
+        WsMetadata { session, resources }
+    }
+
+    /// Checks that the connection can create one more subscription or filter.
+    fn check_limit(&self, resources: &mut ConnectionResources) -> Result<()> {
+        let filters = &self.rpc_app.filters;
+        resources.filters.retain(|id| filters.is_installed(*id));
+        let created =
+            resources.subscriptions.len() + resources.filters.len() + resources.pending_filters;
+        if created >= self.max_subscriptions_per_connection {
+            return Err(Error {
+                code: ErrorCode::ServerError(LIMIT_EXCEEDED_ERROR_CODE),
+                message: "Too many subscriptions and filters are created by the connection"
+                    .to_string(),
+                data: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn install_filter(
+        &self,
+        meta: WsMetadata,
+        install: impl Future<Output = Result<U256>> + Send + 'static,
+    ) -> BoxFutureResult<U256> {
+        {
+            let mut resources = meta.resources.lock().unwrap();
+            if let Err(err) = self.check_limit(&mut resources) {
+                return Box::pin(futures::future::err(err));
+            }
+            resources.pending_filters += 1;
+        }
+
+        let filters = self.rpc_app.filters.clone();
+        Box::pin(async move {
+            let result = install.await;
+            let mut resources = meta.resources.lock().unwrap();
+            resources.pending_filters -= 1;
+            if let Ok(id) = result {
+                if resources.closed {
+                    filters.uninstall(id);
+                } else {
+                    resources.filters.insert(id);
+                }
+            }
+            result
+        })
+    }
+}
+
+impl Web3PubSub for Web3SubApp {
+    type Metadata = WsMetadata;
+
+    fn subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<PubSubResult>,
+        kind: SubscriptionKind,
+        params: Option<Filter>,
+    ) {
+        if kind == SubscriptionKind::NewHeads && params.is_some() {
+            subscriber
+                .reject(Error::invalid_params(
+                    "`newHeads` subscription doesn't accept parameters",
+                ))
+                .unwrap_or_default();
+            return;
+        }
+        let mut resources = meta.resources.lock().unwrap();
+        if let Err(err) = self.check_limit(&mut resources) {
+            subscriber.reject(err).unwrap_or_default();
+            return;
+        }
+
+        let id = SubscriptionId::String(format!("0x{:x}", zksync_crypto::rand::random::<u128>()));
+        if let Ok(sink) = subscriber.assign_id(id.clone()) {
+            if resources.closed {
+                return;
+            }
+            resources.subscriptions.insert(id.clone());
+            let subscription = Subscription {
+                kind,
+                filter: params.unwrap_or_default(),
+                sink,
+            };
+            self.subscriptions
+                .0
+                .lock()
+                .unwrap()
+                .insert(id, subscription);
+        }
+    }
+
+    fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+        if let Some(meta) = meta {
+            meta.resources.lock().unwrap().subscriptions.remove(&id);
+        }
+        Ok(self.subscriptions.remove(&id))
+    }
+
+    fn new_filter(&self, meta: Self::Metadata, filter: Filter) -> BoxFutureResult<U256> {
+        let install = self.rpc_app.clone()._impl_new_filter(filter);
+        self.install_filter(meta, install)
+    }
+
+    fn new_block_filter(&self, meta: Self::Metadata) -> BoxFutureResult<U256> {
+        let install = self.rpc_app.clone()._impl_new_block_filter();
+        self.install_filter(meta, install)
+    }
+
+    fn uninstall_filter(&self, meta: Self::Metadata, id: U256) -> Result<bool> {
+        meta.resources.lock().unwrap().filters.remove(&id);
+        Ok(self.rpc_app.filters.uninstall(id))
+    }
+}
+
+/// Actor which polls the `events` table for the finalized blocks and notifies the subscribers.
+struct SubscriptionNotifier {
+    rpc_app: Web3RpcApp,
+    subscriptions: Subscriptions,
+    last_event_id: EventId,
+}
+
+impl SubscriptionNotifier {
+    async fn run(mut self, config: Web3Config) {
+        let mut timer = tokio::time::interval(config.subscriptions_poll_interval());
+        loop {
+            timer.tick().await;
+            if let Err(err) = self.notify_new_blocks().await {
+                vlog::warn!("Failed to notify the web3 subscribers: {}", err);
+            }
+        }
+    }
+
+    async fn notify_new_blocks(&mut self) -> anyhow::Result<()> {
+        let mut storage = self.rpc_app.connection_pool.access_storage().await?;
+        let events = storage
+            .event_schema()
+            .fetch_new_events_of_type(self.last_event_id, EventType::Block)
+            .await?;
+
+        for event in events {
+            let event = ZkSyncEvent::try_from(event)?;
+            self.last_event_id = event.id;
+            match event.data {
+                EventData::Block(BlockEvent {
+                    status: BlockStatus::Finalized,
+                    ..
+                }) => self.notify_block(&mut storage, event.block_number).await?,
+                _ => continue,
+            }
+        }
+        Ok(())
+    }
+
+    async fn notify_block(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        block_number: BlockNumber,
+    ) -> anyhow::Result<()> {
+        let subscriptions = self.subscriptions.snapshot();
+        if subscriptions.is_empty() {
+            return Ok(());
+        }
+
+        let header = if subscriptions
+            .iter()
+            .any(|(_, subscription)| subscription.kind == SubscriptionKind::NewHeads)
+        {
+            let block = Web3RpcApp::block_by_number(storage, block_number, false)
+                .await
+                .map_err(|err| anyhow::format_err!("Failed to load block: {}", err.message))?;
+            Some(block)
+        } else {
+            None
+        };
+        let logs = if subscriptions
+            .iter()
+            .any(|(_, subscription)| subscription.kind == SubscriptionKind::Logs)
+        {
+            self.rpc_app
+                .filtered_logs(storage, block_number, block_number, &Filter::default())
+                .await
+                .map_err(|err| anyhow::format_err!("Failed to load logs: {}", err.message))?
+        } else {
+            Vec::new()
+        };
+
+        for (id, subscription) in subscriptions {
+            let notifications = match subscription.kind {
+                SubscriptionKind::NewHeads => header
+                    .clone()
+                    .map(PubSubResult::Header)
+                    .into_iter()
+                    .collect(),
+                SubscriptionKind::Logs => logs
+                    .iter()
+                    .filter(|log| subscription.filter.matches(log))
+                    .cloned()
+                    .map(PubSubResult::Log)
+                    .collect::<Vec<_>>(),
+            };
+            for notification in notifications {
+                // The error means that the subscriber has disconnected.
+                if subscription.sink.notify(Ok(notification)).is_err() {
+                    self.subscriptions.remove(&id);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[must_use]
+pub fn start_ws_server(
+    connection_pool: ConnectionPool,
+    sign_verify_request_sender: mpsc::Sender<VerifySignatureRequest>,
+    ticker: FeeTicker,
+    web3_config: &Web3Config,
+    common_api_config: &CommonApiConfig,
+    token_config: &TokenConfig,
+    mempool_tx_sender: mpsc::Sender<MempoolTransactionRequest>,
+) -> JoinHandle<()> {
+    let addr = web3_config.ws_bind_addr();
+
+    let tx_sender = TxSender::new(
+        connection_pool.clone(),
+        sign_verify_request_sender,
+        ticker,
+        common_api_config,
+        token_config,
+        mempool_tx_sender,
+    );
+    let rpc_app = Web3RpcApp::new(connection_pool, tx_sender, web3_config, token_config);
+    let sub_app = Web3SubApp::new(
+        rpc_app.clone(),
+        web3_config.max_subscriptions_per_connection,
+    );
+
+    let notifier_rpc_app = rpc_app.clone();
+    let notifier_subscriptions = sub_app.subscriptions.clone();
+    let notifier_config = web3_config.clone();
+    tokio::spawn(async move {
+        // Only the blocks finalized after the start of the server are sent to the subscribers.
+        let last_event_id = notifier_rpc_app
+            .connection_pool
+            .access_storage()
+            .await
+            .expect("Unable to access storage")
+            .event_schema()
+            .get_last_event_id()
+            .await
+            .expect("Unable to load the last event id")
+            .unwrap_or(EventId(0));
+        let notifier = SubscriptionNotifier {
+            rpc_app: notifier_rpc_app,
+            subscriptions: notifier_subscriptions,
+            last_event_id,
+        };
+        notifier.run(notifier_config).await
+    });
+
+    let (handler, panic_sender) = spawn_panic_handler();
+
+    std::thread::spawn(move || {
+        let _panic_sentinel = ThreadPanicNotify(panic_sender);
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+
+        rpc_app.extend(&mut io);
+        // Overrides the filter methods of the `rpc_app`.
+        io.extend_with(sub_app.clone().to_delegate());
+
+        let server = jsonrpc_ws_server::ServerBuilder::with_meta_extractor(
+            io,
+            move |context: &RequestContext| {
+                sub_app.connection_metadata(Arc::new(Session::new(context.sender())))
+            },
+        )
+        .max_connections(1000)
+        .start(&addr)
+        .expect("Unable to start web3 ws server");
+
+        server.wait().expect("web3 ws server start");
+    });
+    handler
+}
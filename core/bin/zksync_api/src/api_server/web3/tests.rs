@@ -1,36 +1,82 @@
 // Built-in uses
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 // External uses
 use ethabi::{ParamType, Token};
+use futures::channel::mpsc;
 use futures::future::{join, join5, Future};
 use jsonrpc_core::{Error, ErrorCode, IoHandler, Params};
 use jsonrpc_core_client::{RawClient, RpcError, RpcResult};
+use jsonrpc_pubsub::{typed::Subscriber, Session};
 use num::BigUint;
 use serde_json::{Map, Value};
 // Workspace uses
 
+use zksync_eth_signer::{EthereumSigner, PrivateKeySigner, RawTransaction};
 use zksync_storage::{chain::operations_ext::records::Web3TxReceipt, ConnectionPool};
 use zksync_test_account::ZkSyncAccount;
 use zksync_types::{
-    tx::ChangePubKeyType, AccountId, AccountUpdate, BlockNumber, ChangePubKeyOp, CloseOp, Deposit,
-    DepositOp, ForcedExitOp, FullExit, FullExitOp, MintNFTOp, Nonce, SwapOp, TokenId, TransferOp,
-    WithdrawNFTOp, WithdrawOp, ZkSyncOp, NFT,
+    helpers::closest_greater_or_eq_packable_fee_amount,
+    tx::{ChangePubKeyType, PackedEthSignature, TxHash},
+    AccountId, AccountUpdate, BlockNumber, ChangePubKeyOp, CloseOp, Deposit, DepositOp,
+    ForcedExitOp, FullExit, FullExitOp, MintNFTOp, Nonce, SwapOp, TokenId, TokenLike, TransferOp,
+    TxFeeTypes, WithdrawNFTOp, WithdrawOp, ZkSyncOp, NFT,
 };
 // Local uses
 use super::{
     calls::CallsHelper,
     converter::{transaction_from_tx_data, u256_from_biguint},
-    types::{BlockInfo, Event, Log, Transaction, TransactionReceipt, H160, H256, U256, U64},
+    filters::{Filters, InstalledFilter},
+    raw_tx::SignedEthTransaction,
+    rpc_impl::LIMIT_EXCEEDED_ERROR_CODE,
+    subscriptions::{Web3PubSub, Web3SubApp},
+    types::{
+        BlockInfo, BlockNumber as Web3BlockNumber, Event, Filter, FilterChanges, Log,
+        SubscriptionKind, Transaction, TransactionReceipt, H160, H256, U256, U64,
+    },
     Web3RpcApp, NFT_FACTORY_ADDRESS, ZKSYNC_PROXY_ADDRESS,
 };
-use crate::api_server::rest::v02::test_utils::TestServerConfig;
-use zksync_config::configs::api::{TokenConfig, Web3Config};
+use crate::{
+    api_server::{
+        rest::v02::test_utils::{dummy_fee_ticker, TestServerConfig},
+        tx_sender::TxSender,
+    },
+    fee_ticker::FeeTicker,
+};
+use zksync_config::configs::api::{CommonApiConfig, TokenConfig, Web3Config};
+
+fn dummy_ticker() -> FeeTicker {
+    dummy_fee_ticker(
+        &[
+            (TokenLike::Id(TokenId(0)), 2000.into()),
+            (TokenLike::Id(TokenId(1)), 1.into()),
+        ],
+        None,
+    )
+}
+
+fn dummy_tx_sender(pool: ConnectionPool) -> TxSender {
+    TxSender::new(
+        pool,
+        mpsc::channel(1).0,
+        dummy_ticker(),
+        &CommonApiConfig::from_env(),
+        &TokenConfig::from_env(),
+        mpsc::channel(1).0,
+    )
+}
 
 async fn local_client() -> anyhow::Result<(RawClient, impl Future<Output = RpcResult<()>>)> {
     let cfg = TestServerConfig::default();
     cfg.fill_database().await?;
 
-    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config);
+    let rpc_app = Web3RpcApp::new(
+        cfg.pool.clone(),
+        dummy_tx_sender(cfg.pool),
+        &cfg.config.api.web3,
+        &cfg.config.api.token_config,
+    );
     let mut io = IoHandler::new();
     rpc_app.extend(&mut io);
 
@@ -91,7 +137,15 @@ async fn static_methods() -> anyhow::Result<()> {
     assert_eq!(net_version.unwrap().as_str().unwrap(), "240");
     assert!(!mining.unwrap().as_bool().unwrap());
     assert_eq!(hashrate.unwrap().as_str().unwrap(), "0x0");
-    assert_eq!(gas_price.unwrap().as_str().unwrap(), "0x0");
+    let expected_gas_price = dummy_ticker()
+        .get_fee_from_ticker_in_wei(TxFeeTypes::Transfer, TokenId(0).into(), H160::zero())
+        .await?
+        .normal_fee
+        .gas_price_wei;
+    assert_eq!(
+        serde_json::from_value::<U256>(gas_price.unwrap()).unwrap(),
+        u256_from_biguint(expected_gas_price)
+    );
     assert!(accounts.unwrap().as_array().unwrap().is_empty());
     assert_eq!(
         get_uncle_count_by_block_hash.unwrap().as_str().unwrap(),
@@ -104,6 +158,116 @@ async fn static_methods() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Checks that `eth_sendRawTransaction` rejects the malformed transactions.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn send_raw_transaction() -> anyhow::Result<()> {
+    let fut = {
+        let (client, server) = local_client().await?;
+        join(
+            client.call_method(
+                "eth_sendRawTransaction",
+                Params::Array(vec![Value::String("0xf86c".to_string())]),
+            ),
+            server,
+        )
+    };
+    let error = fut.await.0.unwrap_err();
+    assert!(matches!(
+        error,
+        RpcError::JsonRpcError(Error {
+            code: ErrorCode::InvalidParams,
+            ..
+        })
+    ));
+    Ok(())
+}
+
+/// Checks that the raw EIP-155 transactions are decoded and their signer is recovered.
+#[tokio::test]
+async fn decode_raw_transaction() {
+    let private_key = H256::repeat_byte(0x11);
+    let signer = PrivateKeySigner::new(private_key);
+    let data = hex::decode(
+        "a9059cbb0000000000000000000000002222222222222222222222222222222222222222\
+         0000000000000000000000000000000000000000000000000000000000000064",
+    )
+    .unwrap();
+    let raw_tx = RawTransaction {
+        nonce: 5.into(),
+        to: Some(H160::repeat_byte(0x33)),
+        gas: 100_000.into(),
+        gas_price: 1_000_000_000.into(),
+        value: 0.into(),
+        data: data.clone(),
+        chain_id: 9,
+        ..Default::default()
+    };
+    let signed = signer.sign_transaction(raw_tx).await.unwrap();
+
+    let tx = SignedEthTransaction::decode(&signed).unwrap();
+    assert_eq!(
+        tx,
+        SignedEthTransaction {
+            nonce: 5.into(),
+            gas_price: 1_000_000_000.into(),
+            gas: 100_000.into(),
+            to: H160::repeat_byte(0x33),
+            value: 0.into(),
+            data,
+            chain_id: 9,
+            from: PackedEthSignature::address_from_private_key(&private_key).unwrap(),
+        }
+    );
+
+    // The signature doesn't match the transaction anymore.
+    // The nonce follows the 2-byte header of the list.
+    let mut tampered = signed.clone();
+    assert_eq!(tampered[2], 5);
+    tampered[2] = 6;
+    let tampered_signer = SignedEthTransaction::decode(&tampered)
+        .ok()
+        .map(|tx| tx.from);
+    assert_ne!(tampered_signer, Some(tx.from));
+
+    // Typed transactions are not supported.
+    let raw_tx = RawTransaction {
+        nonce: 5.into(),
+        to: Some(H160::repeat_byte(0x33)),
+        gas: 100_000.into(),
+        gas_price: 1_000_000_000.into(),
+        chain_id: 9,
+        transaction_type: Some(2.into()),
+        max_fee_per_gas: Some(1_000_000_000.into()),
+        max_priority_fee_per_gas: Some(1_000_000_000.into()),
+        ..Default::default()
+    };
+    let signed = signer.sign_transaction(raw_tx).await.unwrap();
+    SignedEthTransaction::decode(&signed).unwrap_err();
+}
+
+/// Tests `eth_chainId` method
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn chain_id() -> anyhow::Result<()> {
+    let fut = {
+        let (client, server) = local_client().await?;
+        join(client.call_method("eth_chainId", Params::None), server)
+    };
+    let chain_id = fut.await.0.unwrap();
+    assert_eq!(
+        serde_json::from_value::<U64>(chain_id).unwrap(),
+        U64::from(TestServerConfig::default().config.api.web3.chain_id)
+    );
+    Ok(())
+}
+
 /// Tests `eth_blockNumber` method
 #[tokio::test(flavor = "multi_thread")]
 #[cfg_attr(
@@ -225,6 +389,91 @@ async fn get_balance() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests `eth_getTransactionCount` method
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn get_transaction_count() -> anyhow::Result<()> {
+    let pool = ConnectionPool::new(Some(1));
+    let address = H160::from_str("09d1ef5f45cfa30225edff40cebf657b4226b27b").unwrap();
+    async fn call_get_transaction_count(params: Vec<Value>) -> anyhow::Result<U256> {
+        let (client, server) = local_client().await?;
+        let (result, _) = join(
+            client.call_method("eth_getTransactionCount", Params::Array(params)),
+            server,
+        )
+        .await;
+        Ok(serde_json::from_value(result.unwrap())?)
+    }
+
+    // By default, the committed nonce of the account is returned.
+    let expected_nonce = {
+        let mut storage = pool.access_storage().await?;
+        let account = storage
+            .chain()
+            .account_schema()
+            .account_state_by_address(address)
+            .await?
+            .committed
+            .unwrap()
+            .1;
+        U256::from(*account.nonce)
+    };
+    let nonce = call_get_transaction_count(vec![Value::String(format!("{:#?}", address))]).await?;
+    assert_eq!(nonce, expected_nonce);
+    let nonce = call_get_transaction_count(vec![
+        Value::String(format!("{:#?}", address)),
+        Value::String("pending".to_string()),
+    ])
+    .await?;
+    assert_eq!(nonce, expected_nonce);
+
+    // Checks that the nonce is taken from the state of the account as of the given block.
+    let nonce = call_get_transaction_count(vec![
+        Value::String(format!("{:#?}", address)),
+        Value::String("earliest".to_string()),
+    ])
+    .await?;
+    assert_eq!(nonce, U256::zero());
+
+    let expected_nonce = {
+        let mut storage = pool.access_storage().await?;
+        let account_id = storage
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?
+            .unwrap();
+        let account = storage
+            .chain()
+            .account_schema()
+            .account_state_for_block(account_id, BlockNumber(3))
+            .await?
+            .1;
+        account
+            .map(|account| U256::from(*account.nonce))
+            .unwrap_or_default()
+    };
+    let nonce = call_get_transaction_count(vec![
+        Value::String(format!("{:#?}", address)),
+        Value::String("0x3".to_string()),
+    ])
+    .await?;
+    assert_eq!(nonce, expected_nonce);
+
+    // Checks that the nonce of the nonexistent account is zero.
+    let nonce = call_get_transaction_count(vec![Value::String(format!(
+        "{:#?}",
+        H160::repeat_byte(0xaa)
+    ))])
+    .await?;
+    assert_eq!(nonce, U256::zero());
+
+    Ok(())
+}
+
 /// Tests `eth_getBlockTransactionCountByHash` and `eth_getBlockTransactionCountByNumber` methods
 #[tokio::test(flavor = "multi_thread")]
 #[cfg_attr(
@@ -374,6 +623,35 @@ async fn get_transaction_by_hash() -> anyhow::Result<()> {
         expected
     );
 
+    // Checks that the transaction submitted via `eth_sendRawTransaction` is found by the hash of the raw transaction.
+    let eth_hash = H256::repeat_byte(0x42);
+    {
+        let mut storage = pool.access_storage().await?;
+        storage
+            .chain()
+            .operations_ext_schema()
+            .store_web3_raw_transaction(eth_hash.as_bytes(), TxHash::from_slice(&tx_hash).unwrap())
+            .await?;
+    }
+    let fut = {
+        let (client, server) = local_client().await?;
+        join(
+            client.call_method(
+                "eth_getTransactionByHash",
+                Params::Array(vec![serde_json::to_value(eth_hash).unwrap()]),
+            ),
+            server,
+        )
+    };
+    let transaction = fut.await.0.unwrap();
+    assert_eq!(
+        serde_json::from_value::<Transaction>(transaction).unwrap(),
+        Transaction {
+            hash: eth_hash,
+            ..expected
+        }
+    );
+
     Ok(())
 }
 
@@ -465,7 +743,12 @@ async fn get_block() -> anyhow::Result<()> {
 async fn create_logs() -> anyhow::Result<()> {
     let cfg = TestServerConfig::default();
     cfg.fill_database().await?;
-    let rpc_app = Web3RpcApp::new(cfg.pool, &cfg.config.api.web3, &cfg.config.api.token_config);
+    let rpc_app = Web3RpcApp::new(
+        cfg.pool.clone(),
+        dummy_tx_sender(cfg.pool),
+        &cfg.config.api.web3,
+        &cfg.config.api.token_config,
+    );
 
     let from_account_id = AccountId(3);
     let from_account = ZkSyncAccount::rand_with_seed([1, 2, 3, 4]);
@@ -855,6 +1138,7 @@ async fn get_transaction_receipt() -> anyhow::Result<()> {
             .unwrap();
         let rpc_app = Web3RpcApp::new(
             pool.clone(),
+            dummy_tx_sender(pool.clone()),
             &Web3Config::from_env(),
             &TokenConfig::from_env(),
         );
@@ -878,6 +1162,7 @@ async fn get_logs() -> anyhow::Result<()> {
     let pool = ConnectionPool::new(Some(1));
     let rpc_app = Web3RpcApp::new(
         pool.clone(),
+        dummy_tx_sender(pool.clone()),
         &Web3Config::from_env(),
         &TokenConfig::from_env(),
     );
@@ -910,8 +1195,19 @@ async fn get_logs() -> anyhow::Result<()> {
                 url: "".to_string(),
                 max_block_range: 3,
                 chain_id: 9,
+                ws_port: 0,
+                ws_url: "".to_string(),
+                max_filters: 10,
+                filter_timeout_sec: 300,
+                subscriptions_poll_interval_ms: 1000,
+                max_subscriptions_per_connection: 100,
             };
-            let rpc_app = Web3RpcApp::new(pool.clone(), &config, &TokenConfig::from_env());
+            let rpc_app = Web3RpcApp::new(
+                pool.clone(),
+                dummy_tx_sender(pool.clone()),
+                &config,
+                &TokenConfig::from_env(),
+            );
             let mut io = IoHandler::new();
             rpc_app.extend(&mut io);
 
@@ -1042,6 +1338,298 @@ async fn get_logs() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests `eth_newFilter`, `eth_newBlockFilter`, `eth_getFilterChanges` and `eth_uninstallFilter` methods.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn filters() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let rpc_app = Web3RpcApp::new(
+        cfg.pool.clone(),
+        dummy_tx_sender(cfg.pool),
+        &cfg.config.api.web3,
+        &cfg.config.api.token_config,
+    );
+
+    // Filters installed via API don't return the blocks finalized before the installation.
+    let fut = {
+        let mut io = IoHandler::new();
+        rpc_app.clone().extend(&mut io);
+        let (client, server) =
+            jsonrpc_core_client::transports::local::connect::<RawClient, _, _>(io);
+        join(
+            async move {
+                let id = client
+                    .call_method("eth_newBlockFilter", Params::None)
+                    .await?;
+                let changes = client
+                    .call_method("eth_getFilterChanges", Params::Array(vec![id.clone()]))
+                    .await?;
+                let uninstalled = client
+                    .call_method("eth_uninstallFilter", Params::Array(vec![id.clone()]))
+                    .await?;
+                let uninstalled_twice = client
+                    .call_method("eth_uninstallFilter", Params::Array(vec![id.clone()]))
+                    .await?;
+                let error = client
+                    .call_method("eth_getFilterChanges", Params::Array(vec![id]))
+                    .await
+                    .unwrap_err();
+                RpcResult::Ok((changes, uninstalled, uninstalled_twice, error))
+            },
+            server,
+        )
+    };
+    let (changes, uninstalled, uninstalled_twice, error) = fut.await.0.unwrap();
+    assert_eq!(
+        serde_json::from_value::<FilterChanges>(changes).unwrap(),
+        FilterChanges::Hashes(Vec::new())
+    );
+    assert_eq!(uninstalled, Value::Bool(true));
+    assert_eq!(uninstalled_twice, Value::Bool(false));
+    assert!(matches!(
+        error,
+        RpcError::JsonRpcError(Error {
+            code: ErrorCode::InvalidParams,
+            ..
+        })
+    ));
+
+    // Block filter returns the hashes of the blocks after the last poll.
+    let id = rpc_app
+        .filters
+        .install(InstalledFilter::Blocks, BlockNumber(0))
+        .unwrap();
+    let changes = rpc_app.clone()._impl_get_filter_changes(id).await?;
+    let expected = {
+        let mut storage = rpc_app.connection_pool.access_storage().await?;
+        let latest_block = storage
+            .chain()
+            .block_schema()
+            .get_last_verified_confirmed_block()
+            .await?;
+        let last_block = latest_block.min(BlockNumber(rpc_app.max_block_range));
+        let mut hashes = Vec::new();
+        for block_number in 1..=*last_block {
+            let block =
+                Web3RpcApp::block_by_number(&mut storage, BlockNumber(block_number), false).await?;
+            match block {
+                BlockInfo::BlockWithHashes(block) => hashes.push(block.hash.unwrap()),
+                BlockInfo::BlockWithTxs(_) => unreachable!(),
+            }
+        }
+        hashes
+    };
+    assert!(!expected.is_empty());
+    assert_eq!(changes, FilterChanges::Hashes(expected.clone()));
+    // The same blocks are not returned twice.
+    match rpc_app.clone()._impl_get_filter_changes(id).await? {
+        FilterChanges::Hashes(hashes) => {
+            assert!(hashes.iter().all(|hash| !expected.contains(hash)))
+        }
+        FilterChanges::Logs(_) => panic!("block hashes expected"),
+    }
+
+    // Log filter returns the logs of the new blocks within the filter range.
+    let filter = Filter {
+        from_block: Some(Web3BlockNumber::Number(1.into())),
+        to_block: Some(Web3BlockNumber::Number(1.into())),
+        ..Default::default()
+    };
+    let id = rpc_app
+        .filters
+        .install(InstalledFilter::Logs(filter.clone()), BlockNumber(0))
+        .unwrap();
+    let expected = rpc_app.clone()._impl_get_logs(filter).await?;
+    assert_eq!(expected.len(), 14);
+    let changes = rpc_app.clone()._impl_get_filter_changes(id).await?;
+    assert_eq!(changes, FilterChanges::Logs(expected.clone()));
+    let changes = rpc_app.clone()._impl_get_filter_changes(id).await?;
+    assert_eq!(changes, FilterChanges::Logs(Vec::new()));
+    // `eth_getFilterLogs` returns all the logs matching the filter.
+    let logs = rpc_app.clone()._impl_get_filter_logs(id).await?;
+    assert_eq!(logs, expected);
+
+    Ok(())
+}
+
+/// Checks the limit and the expiration of the installed filters.
+#[test]
+fn filters_storage() {
+    let filters = Filters::new(1, Duration::from_secs(60));
+    let id = filters
+        .install(InstalledFilter::Blocks, BlockNumber(1))
+        .unwrap();
+    assert!(filters
+        .install(InstalledFilter::Blocks, BlockNumber(1))
+        .is_none());
+    filters.update_last_block(id, BlockNumber(5));
+    assert_eq!(
+        filters.get(id),
+        Some((InstalledFilter::Blocks, BlockNumber(5)))
+    );
+    assert!(filters.uninstall(id));
+    assert!(filters.get(id).is_none());
+    assert!(filters
+        .install(InstalledFilter::Blocks, BlockNumber(1))
+        .is_some());
+
+    // Expired filters are uninstalled.
+    let filters = Filters::new(1, Duration::from_secs(0));
+    let id = filters
+        .install(InstalledFilter::Blocks, BlockNumber(1))
+        .unwrap();
+    assert!(filters.get(id).is_none());
+    assert!(filters
+        .install(InstalledFilter::Blocks, BlockNumber(1))
+        .is_some());
+}
+
+/// Checks that the subscriptions and filters are limited per WebSocket connection,
+/// and that they are removed once the connection is closed.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn ws_connection_resources() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let rpc_app = Web3RpcApp::new(
+        cfg.pool.clone(),
+        dummy_tx_sender(cfg.pool),
+        &cfg.config.api.web3,
+        &cfg.config.api.token_config,
+    );
+    let sub_app = Web3SubApp::new(rpc_app.clone(), 2);
+    let (sender, _receiver) = futures::channel::mpsc::unbounded();
+    let meta = sub_app.connection_metadata(Arc::new(Session::new(sender)));
+
+    let (subscriber, id_receiver, _notifications) = Subscriber::new_test("eth_subscription");
+    sub_app.subscribe(meta.clone(), subscriber, SubscriptionKind::NewHeads, None);
+    let subscription_id = id_receiver.await?.unwrap();
+    let filter_id = sub_app.new_block_filter(meta.clone()).await.unwrap();
+
+    // The connection can't create more subscriptions and filters than the limit.
+    let error = sub_app
+        .new_filter(meta.clone(), Filter::default())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.code,
+        ErrorCode::ServerError(LIMIT_EXCEEDED_ERROR_CODE)
+    );
+    let (subscriber, id_receiver, _notifications) = Subscriber::new_test("eth_subscription");
+    sub_app.subscribe(meta.clone(), subscriber, SubscriptionKind::NewHeads, None);
+    assert!(id_receiver.await?.is_err());
+
+    // Uninstalled filters don't count towards the limit.
+    assert!(sub_app.uninstall_filter(meta.clone(), filter_id).unwrap());
+    let filter_id = sub_app.new_block_filter(meta.clone()).await.unwrap();
+
+    // Closing the connection removes its subscriptions and filters.
+    drop(meta);
+    assert!(!rpc_app.filters.is_installed(filter_id));
+    assert!(!sub_app.unsubscribe(None, subscription_id).unwrap());
+    Ok(())
+}
+
+/// Tests `eth_estimateGas` method
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(
+    not(feature = "api_test"),
+    ignore = "Use `zk test rust-api` command to perform this test"
+)]
+async fn estimate_gas() -> anyhow::Result<()> {
+    let cfg = TestServerConfig::default();
+    cfg.fill_database().await?;
+    let pool = ConnectionPool::new(Some(1));
+
+    let (token, recipient) = {
+        let mut storage = pool.access_storage().await?;
+        let token = storage
+            .tokens_schema()
+            .get_token(TokenId(1).into())
+            .await?
+            .unwrap();
+        let recipient = storage
+            .chain()
+            .account_schema()
+            .account_address_by_id(AccountId(3))
+            .await?
+            .unwrap();
+        (token, recipient)
+    };
+    // Gas multiplied by the gas price is the packable fee of the `Transfer` paid in the transferred token.
+    let expected_gas = |token: TokenId| async move {
+        let fee = dummy_ticker()
+            .get_fee_from_ticker_in_wei(TxFeeTypes::Transfer, token.into(), recipient)
+            .await
+            .unwrap()
+            .normal_fee;
+        let total_fee = closest_greater_or_eq_packable_fee_amount(&fee.total_fee);
+        u256_from_biguint((total_fee + &fee.gas_price_wei - 1u32) / fee.gas_price_wei)
+    };
+
+    async fn call_estimate_gas(req: Map<String, Value>) -> RpcResult<Value> {
+        let (client, server) = local_client().await.unwrap();
+        join(
+            client.call_method("eth_estimateGas", Params::Array(vec![Value::Object(req)])),
+            server,
+        )
+        .await
+        .0
+    }
+
+    // Checks the plain ETH transfer.
+    let mut req = Map::new();
+    req.insert("to".to_string(), Value::String(format!("{:#?}", recipient)));
+    let gas = call_estimate_gas(req).await.unwrap();
+    assert_eq!(
+        serde_json::from_value::<U256>(gas).unwrap(),
+        expected_gas(TokenId(0)).await
+    );
+
+    // Checks the ERC20 transfer, the token and the recipient are taken from the call data.
+    let data = ethabi::encode(&[Token::Address(recipient), Token::Uint(U256::from(1))]);
+    let mut req = Map::new();
+    req.insert(
+        "to".to_string(),
+        Value::String(format!("{:#?}", token.address)),
+    );
+    req.insert(
+        "data".to_string(),
+        Value::String(format!("0xa9059cbb{}", hex::encode(data))),
+    );
+    let gas = call_estimate_gas(req).await.unwrap();
+    assert_eq!(
+        serde_json::from_value::<U256>(gas).unwrap(),
+        expected_gas(token.id).await
+    );
+    assert_ne!(expected_gas(token.id).await, expected_gas(TokenId(0)).await);
+
+    // Checks that calls other than transfers can't be estimated.
+    let mut req = Map::new();
+    req.insert(
+        "to".to_string(),
+        Value::String(format!("{:#?}", token.address)),
+    );
+    req.insert("data".to_string(), Value::String("0x06fdde03".to_string()));
+    let error = call_estimate_gas(req).await.unwrap_err();
+    assert!(matches!(
+        error,
+        RpcError::JsonRpcError(Error {
+            code: ErrorCode::InvalidParams,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
 /// Tests `eth_call` method for erc20 contracts
 #[tokio::test(flavor = "multi_thread")]
 #[cfg_attr(
@@ -1200,6 +1788,44 @@ async fn erc20_calls() -> anyhow::Result<()> {
     .unwrap();
     assert_eq!(outputs[0].clone().into_uint().unwrap(), balance);
 
+    // Test `balanceOf` function for the past block.
+    let fut = {
+        let (client, server) = local_client().await?;
+        let mut req = Map::new();
+        req.insert(
+            "to".to_string(),
+            Value::String(format!("{:#?}", token.address)),
+        );
+        let encoded_address = ethabi::encode(&[Token::Address(address)]);
+        let mut data = "0x70a08231".to_string();
+        data.push_str(hex::encode(encoded_address).as_str());
+        req.insert("data".to_string(), Value::String(data));
+        join(
+            client.call_method(
+                "eth_call",
+                Params::Array(vec![Value::Object(req), Value::String("0x1".to_string())]),
+            ),
+            server,
+        )
+    };
+    let resp_data = fut.await.0.unwrap();
+    let resp_data = serde_json::from_value::<String>(resp_data).unwrap();
+    let outputs = ethabi::decode(
+        &[ParamType::Uint(256)],
+        &hex::decode(resp_data.strip_prefix("0x").unwrap()).unwrap(),
+    )
+    .unwrap();
+    let expected_balance = {
+        let mut storage = pool.access_storage().await?;
+        let balance = storage
+            .chain()
+            .account_schema()
+            .get_account_balance_for_block(address, BlockNumber(1), token.id)
+            .await?;
+        u256_from_biguint(balance)
+    };
+    assert_eq!(outputs[0].clone().into_uint().unwrap(), expected_balance);
+
     // Test `allowance` function.
     let fut = {
         let (client, server) = local_client().await?;
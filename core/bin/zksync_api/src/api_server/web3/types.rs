@@ -248,6 +248,58 @@ pub struct Filter {
     pub topics: Option<Vec<Option<ValueOrArray<H256>>>>,
 }
 
+impl Filter {
+    /// Checks whether the log matches the address and topics of the filter.
+    pub fn matches(&self, log: &Log) -> bool {
+        if let Some(topics) = &self.topics {
+            for (i, topic) in topics.iter().enumerate() {
+                let topic = match topic {
+                    Some(topic) if !topic.0.is_empty() => topic,
+                    _ => continue,
+                };
+                match log.topics.get(i) {
+                    Some(log_topic) if topic.0.contains(log_topic) => {}
+                    _ => return false,
+                }
+            }
+        }
+        if let Some(addresses) = &self.address {
+            if !addresses.0.is_empty() && !addresses.0.contains(&log.address) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Kind of the `eth_subscribe` subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+    /// Headers of the new blocks.
+    NewHeads,
+    /// Logs of the new blocks matching the filter.
+    Logs,
+}
+
+/// Notification sent to the `eth_subscribe` subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PubSubResult {
+    Header(BlockInfo),
+    Log(Log),
+}
+
+/// Result of the `eth_getFilterChanges` method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterChanges {
+    /// Hashes of the new blocks for the block filters.
+    Hashes(Vec<H256>),
+    /// New logs for the log filters.
+    Logs(Vec<Log>),
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum Event {
     ZkSyncTransfer,
@@ -97,7 +97,7 @@ mod tests {
     use zksync_contracts::zksync_contract;
     use zksync_eth_client::ethereum_gateway::EthereumGateway;
     use zksync_eth_client::ETHDirectClient;
-    use zksync_eth_signer::PrivateKeySigner;
+    use zksync_eth_signer::{EthereumSignerBackend, PrivateKeySigner};
     use zksync_types::{
         tx::{EIP1271Signature, PackedEthSignature},
         Address,
@@ -124,7 +124,7 @@ mod tests {
             transport,
             zksync_contract(),
             Default::default(),
-            PrivateKeySigner::new(Default::default()),
+            EthereumSignerBackend::PrivateKey(PrivateKeySigner::new(Default::default())),
             Default::default(),
             0,
             1.0,
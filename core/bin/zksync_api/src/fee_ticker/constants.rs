@@ -3,7 +3,8 @@ use zksync_types::{
     ChangePubKeyOp, MintNFTOp, SwapOp, TransferOp, TransferToNewOp, WithdrawNFTOp, WithdrawOp,
 };
 
-/// Gas cost per chunk to cover constant cost of commit, execute and prove transactions
+/// Gas cost per chunk to cover constant cost of commit, execute and prove transactions.
+/// The fee ticker replaces it with the cost derived from the recent blocks when they are available.
 pub(crate) const AMORTIZED_COST_PER_CHUNK: u64 = 200;
 // Base operation costs estimated via `gas_price` test.
 // Factor of AMORTIZED_COST_PER_CHUNK * CHUNKS accounts for constant overhead of the commit, execute, prove for blocks of 680 chunks
@@ -24,13 +24,16 @@ use tokio::time::Instant;
 
 // Workspace deps
 
-use zksync_config::configs::ticker::TokenPriceSource;
+use zksync_config::configs::ticker::{TokenPriceSource, TwapPoolConfig};
+use zksync_eth_client::EthereumGateway;
 use zksync_storage::ConnectionPool;
 use zksync_token_db_cache::TokenDBCache;
 use zksync_types::{
-    gas_counter::GasCounter, tokens::ChangePubKeyFeeTypeArg, tx::ChangePubKeyType, Address,
-    BatchFee, ChangePubKeyOp, Fee, MintNFTOp, OutputFeeType, SwapOp, Token, TokenId, TokenLike,
-    TransferOp, TransferToNewOp, TxFeeTypes, WithdrawNFTOp, WithdrawOp,
+    gas_counter::{CommitCost, GasCounter, VerifyCost},
+    tokens::ChangePubKeyFeeTypeArg,
+    tx::ChangePubKeyType,
+    Address, BatchFee, ChangePubKeyOp, Fee, MintNFTOp, OutputFeeType, SwapOp, Token, TokenId,
+    TokenLike, TransferOp, TransferToNewOp, TxFeeTypes, WithdrawNFTOp, WithdrawOp,
 };
 use zksync_utils::{big_decimal_to_ratio, ratio_to_big_decimal};
 
@@ -40,8 +43,12 @@ pub use crate::fee_ticker::ticker_info::{FeeTickerInfo, TickerInfo};
 use crate::fee_ticker::validator::FeeTokenValidator;
 use crate::fee_ticker::{
     ticker_api::{
-        coingecko::CoinGeckoAPI, coinmarkercap::CoinMarketCapAPI, FeeTickerAPI, TickerApi,
-        CONNECTION_TIMEOUT,
+        coingecko::CoinGeckoAPI,
+        coinmarkercap::CoinMarketCapAPI,
+        composite::{BoxedTokenPriceAPI, CompositeTokenPriceAPI},
+        stored::StoredTokenPriceAPI,
+        uniswap_twap::{TwapPool, UniswapTwapAPI},
+        FeeTickerAPI, TickerApi, CONNECTION_TIMEOUT,
     },
     validator::{watcher::UniswapTokenWatcher, MarketUpdater},
 };
@@ -143,6 +150,13 @@ pub struct TickerConfig {
     pub scale_fee_coefficient: Ratio<BigUint>,
     pub max_blocks_to_aggregate: u32,
     pub subsidy_cpk_price_usd: Ratio<BigUint>,
+    /// Number of the latest Ethereum transactions whose gas prices are used for fees.
+    pub gas_price_samples: u32,
+    pub min_gas_price_wei: BigUint,
+    pub max_gas_price_wei: BigUint,
+    /// Number of the latest blocks used to calculate the average block fill rate.
+    pub fill_rate_blocks: u32,
+    pub min_block_fill_rate: Ratio<BigUint>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -206,6 +220,7 @@ const TOKEN_INVALIDATE_CACHE: Duration = Duration::from_secs(5 * 60);
 #[must_use]
 pub fn run_updaters(
     db_pool: ConnectionPool,
+    eth_gateway: EthereumGateway,
     config: &zksync_config::TickerConfig,
 ) -> Vec<JoinHandle<()>> {
     let cache = (db_pool.clone(), TokenDBCache::new(TOKEN_INVALIDATE_CACHE));
@@ -221,30 +236,104 @@ pub fn run_updaters(
         .connect_timeout(CONNECTION_TIMEOUT)
         .build()
         .expect("Failed to build reqwest::Client");
-    let (price_source, base_url) = config.price_source();
-    let price_updater = match price_source {
-        TokenPriceSource::CoinMarketCap => {
-            let token_price_api =
-                CoinMarketCapAPI::new(client, base_url.parse().expect("Correct CoinMarketCap url"));
-
-            let ticker_api = TickerApi::new(db_pool, token_price_api);
-            tokio::spawn(ticker_api.keep_price_updated())
+    let price_sources = config.price_sources();
+    let max_deviation = config.price_max_deviation();
+    let max_staleness = config.price_staleness();
+    let twap_pools = config.twap_pools.clone();
+    let twap_period = config.twap_period();
+    let price_updater = tokio::spawn(async move {
+        let mut sources = Vec::with_capacity(price_sources.len() + 1);
+        for (price_source, base_url) in price_sources {
+            sources.push(token_price_api(client.clone(), price_source, base_url).await);
         }
-
-        TokenPriceSource::CoinGecko => tokio::spawn(async move {
-            let token_price_api =
-                CoinGeckoAPI::new(client, base_url.parse().expect("Correct CoinGecko url"))
-                    .await
-                    .expect("failed to init CoinGecko client");
-            let ticker_api = TickerApi::new(db_pool, token_price_api);
-
-            ticker_api.keep_price_updated().await;
-        }),
-    };
+        if !twap_pools.is_empty() {
+            // Quote tokens are priced by the ticker itself, so their stored prices are used.
+            let quote_prices = StoredTokenPriceAPI::new(db_pool.clone(), max_staleness);
+            sources.push(Box::new(UniswapTwapAPI::new(
+                eth_gateway.clone(),
+                load_twap_pools(&db_pool, &eth_gateway, twap_pools).await,
+                twap_period,
+                Box::new(quote_prices),
+            )));
+        }
+        // Prices of the single source are used as is, since it's the median of itself.
+        let stored_prices = StoredTokenPriceAPI::new(db_pool.clone(), max_staleness);
+        let token_price_api =
+            CompositeTokenPriceAPI::new(sources, max_deviation, Some(Box::new(stored_prices)));
+        let ticker_api = TickerApi::new(db_pool, token_price_api);
+
+        ticker_api.keep_price_updated().await;
+    });
     tasks.push(price_updater);
     tasks
 }
 
+/// Resolves the quote tokens and the order of the tokens of the configured Uniswap pools,
+/// skipping the pools which can't be used.
+async fn load_twap_pools(
+    db_pool: &ConnectionPool,
+    eth_gateway: &EthereumGateway,
+    pools: Vec<TwapPoolConfig>,
+) -> HashMap<Address, TwapPool> {
+    let mut storage = db_pool
+        .access_storage()
+        .await
+        .expect("Failed to access storage");
+    let mut result = HashMap::with_capacity(pools.len());
+    for pool in pools {
+        let quote_token = storage
+            .tokens_schema()
+            .get_token(TokenLike::Address(pool.quote_token))
+            .await
+            .expect("Failed to load token");
+        match quote_token {
+            Some(quote_token) => {
+                match TwapPool::load(
+                    eth_gateway,
+                    pool.version,
+                    pool.pool,
+                    pool.token,
+                    quote_token,
+                )
+                .await
+                {
+                    Ok(twap_pool) => {
+                        result.insert(pool.token, twap_pool);
+                    }
+                    Err(err) => {
+                        vlog::error!("Uniswap pool {:?} is skipped: {}", pool.pool, err)
+                    }
+                }
+            }
+            None => vlog::warn!(
+                "Uniswap pool {:?} is skipped: quote token {:?} is not supported",
+                pool.pool,
+                pool.quote_token
+            ),
+        }
+    }
+    result
+}
+
+/// Creates the API of the given token price source.
+async fn token_price_api(
+    client: reqwest::Client,
+    price_source: TokenPriceSource,
+    base_url: String,
+) -> BoxedTokenPriceAPI {
+    match price_source {
+        TokenPriceSource::CoinMarketCap => Box::new(CoinMarketCapAPI::new(
+            client,
+            base_url.parse().expect("Correct CoinMarketCap url"),
+        )),
+        TokenPriceSource::CoinGecko => Box::new(
+            CoinGeckoAPI::new(client, base_url.parse().expect("Correct CoinGecko url"))
+                .await
+                .expect("failed to init CoinGecko client"),
+        ),
+    }
+}
+
 impl FeeTicker {
     pub fn new(
         info: Box<dyn FeeTickerInfo>,
@@ -265,6 +354,7 @@ impl FeeTicker {
         connection_pool: ConnectionPool,
     ) -> Self {
         let cache = (connection_pool, TokenDBCache::new(TOKEN_INVALIDATE_CACHE));
+        let (min_gas_price_wei, max_gas_price_wei) = config.l1_gas_price_bounds();
         let ticker_config = TickerConfig {
             zkp_cost_chunk_usd: Ratio::from_integer(BigUint::from(10u32).pow(3u32)).inv(),
             gas_cost_tx: GasOperationsCost::from_constants(config.fast_processing_coeff),
@@ -275,6 +365,11 @@ impl FeeTicker {
             ),
             max_blocks_to_aggregate,
             subsidy_cpk_price_usd: config.subsidy_cpk_price_usd(),
+            gas_price_samples: config.gas_price_samples,
+            min_gas_price_wei,
+            max_gas_price_wei,
+            fill_rate_blocks: config.fill_rate_blocks,
+            min_block_fill_rate: config.min_block_fill_rate(),
         };
         let validator = FeeTokenValidator::new(
             cache,
@@ -332,12 +427,15 @@ impl FeeTicker {
         let zkp_cost_chunk = self.config.zkp_cost_chunk_usd.clone();
         let token = self.info.get_token(token).await?;
 
-        let gas_price_wei = self.info.get_gas_price_wei().await?;
+        let gas_price_wei = self.gas_price_wei().await?;
         let scale_gas_price = Self::risk_gas_price_estimate(gas_price_wei.clone());
         let wei_price_usd = self.wei_price_usd().await?;
         let token_usd_risk = self.token_usd_risk(&token).await?;
+        let cost_per_chunk = self.amortized_cost_per_chunk().await?;
 
-        let (fee_type, gas_tx_amount, op_chunks) = self.gas_tx_amount(tx_type, recipient).await?;
+        let (fee_type, gas_tx_amount, op_chunks) = self
+            .gas_tx_amount(tx_type, recipient, &cost_per_chunk)
+            .await?;
 
         let zkp_fee = (zkp_cost_chunk * op_chunks) * &token_usd_risk;
         let mut normal_gas_fee =
@@ -415,10 +513,11 @@ impl FeeTicker {
 
         let token = self.info.get_token(token).await?;
 
-        let gas_price_wei = self.info.get_gas_price_wei().await?;
+        let gas_price_wei = self.gas_price_wei().await?;
         let scale_gas_price = Self::risk_gas_price_estimate(gas_price_wei.clone());
         let wei_price_usd = self.wei_price_usd().await?;
         let token_usd_risk = self.token_usd_risk(&token).await?;
+        let cost_per_chunk = self.amortized_cost_per_chunk().await?;
 
         let mut total_normal_gas_tx_amount = Ratio::from(BigUint::zero());
         let mut total_op_chunks = Ratio::from(BigUint::zero());
@@ -461,8 +560,9 @@ impl FeeTicker {
         };
 
         for (tx_type, recipient) in txs {
-            let (output_fee_type, gas_tx_amount, op_chunks) =
-                self.gas_tx_amount(tx_type, recipient).await?;
+            let (output_fee_type, gas_tx_amount, op_chunks) = self
+                .gas_tx_amount(tx_type, recipient, &cost_per_chunk)
+                .await?;
             // Increase fee only for L2 operations
             let gas_tx_amount: Ratio<BigUint> = if matches!(
                 output_fee_type,
@@ -557,10 +657,63 @@ impl FeeTicker {
         self.info.is_account_new(address).await
     }
 
+    /// Resolves the token the same way as the fee calculation does.
+    pub async fn get_token(&self, token: TokenLike) -> anyhow::Result<Token> {
+        self.info.get_token(token).await
+    }
+
+    /// Returns the L1 gas price to calculate fees with: the median of the gas prices recently
+    /// paid by the operator, bounded by the configured limits.
+    async fn gas_price_wei(&self) -> anyhow::Result<BigUint> {
+        let mut gas_prices = self
+            .info
+            .get_recent_gas_prices_wei(self.config.gas_price_samples)
+            .await?;
+        let gas_price = if gas_prices.is_empty() {
+            self.info.get_gas_price_wei().await?
+        } else {
+            gas_prices.sort();
+            gas_prices.swap_remove(gas_prices.len() / 2)
+        };
+        Ok(gas_price
+            .max(self.config.min_gas_price_wei.clone())
+            .min(self.config.max_gas_price_wei.clone()))
+    }
+
+    /// Returns the gas cost of the block-level L1 operations amortized per chunk.
+    ///
+    /// Costs of committing, proving and executing a block are shared by its chunks,
+    /// so the cost per chunk depends on how full the recent blocks were. The fill rate
+    /// is bounded from below, so almost empty blocks don't make fees skyrocket.
+    async fn amortized_cost_per_chunk(&self) -> anyhow::Result<BigUint> {
+        let blocks = self
+            .info
+            .get_recent_blocks_chunks(self.config.fill_rate_blocks)
+            .await?;
+        let chunks_used: usize = blocks.iter().map(|block| block.chunks_used).sum();
+        let capacity: usize = blocks.iter().map(|block| block.block_size).sum();
+        if capacity == 0 {
+            return Ok(BigUint::from(AMORTIZED_COST_PER_CHUNK));
+        }
+
+        let fill_rate = std::cmp::max(
+            Ratio::new(BigUint::from(chunks_used), BigUint::from(capacity)),
+            self.config.min_block_fill_rate.clone(),
+        );
+        let used_capacity = Ratio::from_integer(BigUint::from(capacity)) * fill_rate;
+
+        let blocks_cost =
+            block_gas_cost(self.config.max_blocks_to_aggregate) * BigUint::from(blocks.len());
+        Ok((Ratio::from_integer(blocks_cost) / used_capacity)
+            .ceil()
+            .to_integer())
+    }
+
     async fn gas_tx_amount(
         &self,
         tx_type: TxFeeTypes,
         recipient: Address,
+        cost_per_chunk: &BigUint,
     ) -> anyhow::Result<(OutputFeeType, BigUint, BigUint)> {
         let start = Instant::now();
         let (fee_type, op_chunks) = match tx_type {
@@ -586,14 +739,17 @@ impl FeeTicker {
             fee_type,
             OutputFeeType::FastWithdraw | OutputFeeType::FastWithdrawNFT
         ) {
-            self.calculate_fast_withdrawal_gas_cost(op_chunks).await?
+            self.calculate_fast_withdrawal_gas_cost(op_chunks, cost_per_chunk)
+                .await?
         } else {
-            self.config
+            let standard_cost = self
+                .config
                 .gas_cost_tx
                 .standard_cost
                 .get(&fee_type)
                 .cloned()
-                .unwrap()
+                .unwrap();
+            amortize_chunks_cost(standard_cost, op_chunks, cost_per_chunk)
         };
 
         // Convert chunks amount to `BigUint`.
@@ -604,15 +760,16 @@ impl FeeTicker {
     async fn calculate_fast_withdrawal_gas_cost(
         &self,
         chunk_size: usize,
+        cost_per_chunk: &BigUint,
     ) -> anyhow::Result<BigUint> {
         let start = Instant::now();
         let future_blocks = self.info.blocks_in_future_aggregated_operations().await?;
         let remaining_pending_chunks = self.info.remaining_chunks_in_pending_block().await?;
-        let additional_cost = remaining_pending_chunks.map_or(0, |chunks| {
+        let additional_cost = remaining_pending_chunks.map_or_else(BigUint::zero, |chunks| {
             if chunk_size > chunks {
-                0
+                BigUint::zero()
             } else {
-                chunks * AMORTIZED_COST_PER_CHUNK as usize
+                BigUint::from(chunks) * cost_per_chunk
             }
         });
 
@@ -633,9 +790,7 @@ impl FeeTicker {
             future_blocks.blocks_to_prove,
         );
         metrics::histogram!("ticker.calculate_fast_withdrawal_gas_cost", start.elapsed());
-        Ok(BigUint::from(
-            commit_cost + execute_cost + proof_cost + additional_cost,
-        ))
+        Ok(BigUint::from(commit_cost + execute_cost + proof_cost) + additional_cost)
     }
 
     pub async fn token_allowed_for_fees(&self, token: TokenLike) -> anyhow::Result<bool> {
@@ -643,6 +798,33 @@ impl FeeTicker {
     }
 }
 
+/// Returns the block-level L1 gas cost of a single block: the base costs of its commitment and execution
+/// and its share of the aggregated commit, prove and execute transactions. Costs of the operations
+/// included into the block are not taken into account, since they are charged by the operations themselves.
+fn block_gas_cost(max_blocks_to_aggregate: u32) -> BigUint {
+    let aggregated_txs_cost = GasCounter::BASE_COMMIT_BLOCKS_TX_COST
+        + GasCounter::BASE_PROOF_BLOCKS_TX_COST
+        + GasCounter::BASE_EXECUTE_BLOCKS_TX_COST;
+
+    BigUint::from(CommitCost::BASE_COST)
+        + BigUint::from(VerifyCost::BASE_COST)
+        + BigUint::from(aggregated_txs_cost / max_blocks_to_aggregate.max(1) as usize)
+}
+
+/// Replaces the constant amortized cost of chunks included into the standard operation cost
+/// with the provided one.
+fn amortize_chunks_cost(
+    standard_cost: BigUint,
+    op_chunks: usize,
+    cost_per_chunk: &BigUint,
+) -> BigUint {
+    let constant_part = BigUint::from(AMORTIZED_COST_PER_CHUNK * op_chunks as u64);
+    if standard_cost < constant_part {
+        return standard_cost;
+    }
+    standard_cost - constant_part + BigUint::from(op_chunks) * cost_per_chunk
+}
+
 fn calculate_cost(base_cost: usize, max_blocks: u32, future_blocks: u32) -> usize {
     base_cost - (base_cost / max_blocks as usize) * future_blocks.rem_euclid(max_blocks) as usize
 }
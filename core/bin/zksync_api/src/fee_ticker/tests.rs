@@ -5,6 +5,7 @@ use bigdecimal::BigDecimal;
 use chrono::Utc;
 use futures::executor::block_on;
 use std::str::FromStr;
+use zksync_storage::chain::block::records::BlockChunksUsage;
 use zksync_types::{Address, Token, TokenId, TokenKind, TokenPrice};
 use zksync_utils::{
     ratio_to_big_decimal, ratio_to_scaled_u64, scaled_u64_to_ratio, UnsignedRatioSerializeAsDecimal,
@@ -114,6 +115,11 @@ pub fn get_test_ticker_config() -> TickerConfig {
         scale_fee_coefficient: Ratio::new(BigUint::from(150u32), BigUint::from(100u32)),
         max_blocks_to_aggregate: 5,
         subsidy_cpk_price_usd: scaled_u64_to_ratio(SUBSIDY_CPK_PRICE_USD_SCALED),
+        gas_price_samples: 20,
+        min_gas_price_wei: BigUint::zero(),
+        max_gas_price_wei: BigUint::from(u64::MAX),
+        fill_rate_blocks: 50,
+        min_block_fill_rate: Ratio::new(BigUint::from(1u32), BigUint::from(10u32)),
     }
 }
 
@@ -130,6 +136,8 @@ impl FeeTickerAPI for MockApiProvider {
 struct MockTickerInfo {
     pub future_blocks: BlocksInFutureAggregatedOperations,
     pub remaining_chunks: Option<usize>,
+    pub recent_gas_prices: Vec<BigUint>,
+    pub recent_blocks_chunks: Vec<BlockChunksUsage>,
 }
 
 impl Default for MockTickerInfo {
@@ -141,6 +149,8 @@ impl Default for MockTickerInfo {
                 blocks_to_execute: 0,
             },
             remaining_chunks: None,
+            recent_gas_prices: Vec::new(),
+            recent_blocks_chunks: Vec::new(),
         }
     }
 }
@@ -180,6 +190,14 @@ impl FeeTickerInfo for MockTickerInfo {
         Ok(BigUint::from(10u32).pow(7u32)) // 10 GWei
     }
 
+    async fn get_recent_gas_prices_wei(&self, _limit: u32) -> anyhow::Result<Vec<BigUint>> {
+        Ok(self.recent_gas_prices.clone())
+    }
+
+    async fn get_recent_blocks_chunks(&self, _limit: u32) -> anyhow::Result<Vec<BlockChunksUsage>> {
+        Ok(self.recent_blocks_chunks.clone())
+    }
+
     async fn get_token(&self, token: TokenLike) -> Result<Token, anyhow::Error> {
         for test_token in TestToken::all_tokens() {
             if TokenLike::Id(test_token.id) == token {
@@ -708,3 +726,84 @@ fn test_zero_price_token_fee() {
     ))
     .unwrap_err();
 }
+
+/// Checks that fees follow the gas prices recently paid on L1 and the fill rate of the recent blocks.
+#[test]
+fn test_l1_conditions_fee() {
+    let validator = FeeTokenValidator::new(
+        TokenInMemoryCache::new(),
+        chrono::Duration::seconds(100),
+        BigDecimal::from(100),
+        Default::default(),
+    );
+
+    let config = get_test_ticker_config();
+    let mut ticker = FeeTicker::new(Box::new(MockTickerInfo::default()), config, validator);
+    let gwei = BigUint::from(10u32).pow(9u32);
+
+    let mut transfer_fee = |info: MockTickerInfo, max_gas_price_wei: BigUint| {
+        ticker.info = Box::new(info);
+        ticker.config.max_gas_price_wei = max_gas_price_wei;
+        block_on(ticker.get_fee_from_ticker_in_wei(
+            TxFeeTypes::Transfer,
+            TokenId(0).into(),
+            Address::default(),
+        ))
+        .unwrap()
+        .normal_fee
+    };
+    let unbounded = BigUint::from(u64::MAX);
+
+    // Without the recent data the constant costs and the average gas price are used.
+    let fee = transfer_fee(MockTickerInfo::default(), unbounded.clone());
+    assert_eq!(fee.gas_price_wei, BigUint::from(10u32).pow(7u32));
+    assert_eq!(
+        fee.gas_tx_amount,
+        BigUint::from(constants::BASE_TRANSFER_COST)
+    );
+
+    // The median of the recently paid gas prices is used within the bounds.
+    let info = MockTickerInfo {
+        recent_gas_prices: vec![&gwei * 20u32, &gwei * 5u32, &gwei * 30u32],
+        ..Default::default()
+    };
+    let fee = transfer_fee(info.clone(), unbounded.clone());
+    assert_eq!(fee.gas_price_wei, &gwei * 20u32);
+    let fee = transfer_fee(info, &gwei * 15u32);
+    assert_eq!(fee.gas_price_wei, &gwei * 15u32);
+
+    // Block costs are amortized over the chunks used in the recent blocks.
+    let block = |chunks_used: usize| BlockChunksUsage {
+        chunks_used,
+        block_size: 680,
+    };
+    let mut transfer_gas = |blocks_chunks: Vec<BlockChunksUsage>| {
+        let info = MockTickerInfo {
+            recent_blocks_chunks: blocks_chunks,
+            ..Default::default()
+        };
+        transfer_fee(info, unbounded.clone()).gas_tx_amount
+    };
+    let full_blocks_gas = transfer_gas(vec![block(680), block(680)]);
+    // Base commit and execute costs along with the share of the aggregated transactions (5 blocks each)
+    // are amortized over the 680 chunks of the block: `(51_000 + 10_000 + 2_400_000 / 5) / 680`, rounded up.
+    assert_eq!(block_gas_cost(5), BigUint::from(541_000u32));
+    let cost_per_chunk = 796u64;
+    assert_eq!(
+        full_blocks_gas,
+        BigUint::from(
+            constants::BASE_TRANSFER_COST
+                - constants::AMORTIZED_COST_PER_CHUNK * TransferOp::CHUNKS as u64
+                + cost_per_chunk * TransferOp::CHUNKS as u64
+        )
+    );
+
+    let half_full_blocks_gas = transfer_gas(vec![block(680), block(0)]);
+    assert!(half_full_blocks_gas > full_blocks_gas);
+
+    // Fill rate below the lower bound doesn't increase the fee further.
+    let empty_blocks_gas = transfer_gas(vec![block(0), block(10)]);
+    let barely_filled_blocks_gas = transfer_gas(vec![block(68), block(68)]);
+    assert!(empty_blocks_gas > half_full_blocks_gas);
+    assert_eq!(empty_blocks_gas, barely_filled_blocks_gas);
+}
@@ -0,0 +1,372 @@
+//! Token price API aggregating the prices reported by several sources.
+//!
+//! All the sources are queried concurrently, and the median of the reported prices is used.
+//! Prices deviating from the median by more than the configured fraction are considered outliers
+//! and don't participate in the resulting median, so a single misbehaving source can't affect it.
+//! If no price can be obtained from the sources, the price reported by the fallback API
+//! (e.g. the last stored price of the token, unless it's too old) is used.
+
+use super::TokenPriceAPI;
+use crate::fee_ticker::PriceError;
+use async_trait::async_trait;
+use futures::future::join_all;
+use num::rational::Ratio;
+use num::BigUint;
+use std::time::Instant;
+use zksync_types::{Token, TokenPrice};
+
+pub type BoxedTokenPriceAPI = Box<dyn TokenPriceAPI + Send + Sync>;
+
+pub struct CompositeTokenPriceAPI {
+    sources: Vec<BoxedTokenPriceAPI>,
+    /// Maximum deviation of the price from the median, as a fraction of the median.
+    max_deviation: Ratio<BigUint>,
+    /// API used when the sources don't agree on the price.
+    fallback: Option<BoxedTokenPriceAPI>,
+}
+
+impl CompositeTokenPriceAPI {
+    pub fn new(
+        sources: Vec<BoxedTokenPriceAPI>,
+        max_deviation: Ratio<BigUint>,
+        fallback: Option<BoxedTokenPriceAPI>,
+    ) -> Self {
+        Self {
+            sources,
+            max_deviation,
+            fallback,
+        }
+    }
+
+    async fn fallback_price(&self, token: &Token) -> Result<TokenPrice, PriceError> {
+        let price = match &self.fallback {
+            Some(fallback) => fallback.get_price(token).await,
+            None => Err(PriceError::api_error("There is no fallback price API")),
+        };
+
+        match price {
+            Ok(price) => {
+                vlog::warn!(
+                    "Using the fallback price of token {} updated at {}",
+                    token.symbol,
+                    price.last_updated
+                );
+                metrics::increment_counter!("ticker.composite.fallback_price");
+                Ok(price)
+            }
+            Err(err) => Err(PriceError::api_error(format!(
+                "No price sources agreed on the price of token {} and there is no fallback price: {}",
+                token.symbol, err
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenPriceAPI for CompositeTokenPriceAPI {
+    async fn get_price(&self, token: &Token) -> Result<TokenPrice, PriceError> {
+        let start = Instant::now();
+        let responses = join_all(self.sources.iter().map(|source| source.get_price(token))).await;
+
+        let mut prices = Vec::new();
+        let mut token_not_found = 0;
+        for response in responses {
+            match response {
+                Ok(price) => prices.push(price),
+                Err(PriceError::TokenNotFound(_)) => token_not_found += 1,
+                Err(err) => vlog::warn!(
+                    "Failed to get the price of token {} from the source: {}",
+                    token.symbol,
+                    err
+                ),
+            }
+        }
+        // The token is not listed by any of the sources, so its price is not known at all.
+        if token_not_found == self.sources.len() {
+            return Err(PriceError::token_not_found(format!(
+                "Token '{}, {:?}' is not listed on any of the price sources",
+                token.symbol, token.address
+            )));
+        }
+
+        let price = match aggregate_prices(prices, &self.max_deviation) {
+            Some(price) => Ok(price),
+            None => self.fallback_price(token).await,
+        };
+        metrics::histogram!("ticker.composite.get_price", start.elapsed());
+        price
+    }
+}
+
+fn abs_diff(lhs: &Ratio<BigUint>, rhs: &Ratio<BigUint>) -> Ratio<BigUint> {
+    if lhs > rhs {
+        lhs - rhs
+    } else {
+        rhs - lhs
+    }
+}
+
+fn median(mut prices: Vec<Ratio<BigUint>>) -> Option<Ratio<BigUint>> {
+    prices.sort();
+    let len = prices.len();
+    if len == 0 {
+        None
+    } else if len % 2 == 1 {
+        Some(prices[len / 2].clone())
+    } else {
+        Some((&prices[len / 2 - 1] + &prices[len / 2]) / BigUint::from(2u32))
+    }
+}
+
+/// Returns the median of the prices, ignoring the ones deviating from the median of all the prices
+/// by more than `max_deviation` of it. The price is considered as updated at the moment of the oldest
+/// of the accepted prices.
+///
+/// Returns `None` if there are no prices or all of them are considered outliers.
+pub fn aggregate_prices(
+    prices: Vec<TokenPrice>,
+    max_deviation: &Ratio<BigUint>,
+) -> Option<TokenPrice> {
+    let overall_median = median(prices.iter().map(|price| price.usd_price.clone()).collect())?;
+    let max_diff = &overall_median * max_deviation;
+
+    let (accepted, outliers): (Vec<_>, Vec<_>) = prices
+        .into_iter()
+        .partition(|price| abs_diff(&price.usd_price, &overall_median) <= max_diff);
+    if !outliers.is_empty() {
+        metrics::counter!("ticker.composite.outlier_prices", outliers.len() as u64);
+    }
+
+    let usd_price = median(
+        accepted
+            .iter()
+            .map(|price| price.usd_price.clone())
+            .collect(),
+    )?;
+    let last_updated = accepted.iter().map(|price| price.last_updated).min()?;
+    Some(TokenPrice {
+        usd_price,
+        last_updated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee_ticker::ticker_api::{coingecko::CoinGeckoAPI, coinmarkercap::CoinMarketCapAPI};
+    use actix_web::{web, App, HttpResponse};
+    use chrono::Utc;
+    use serde_json::json;
+    use zksync_types::{Address, TokenId, TokenKind};
+
+    /// Starts the server mocking both CoinGecko and CoinMarketCap APIs, which reports the given price
+    /// for every token or fails if there is no price.
+    fn mock_price_server(price: Option<&'static str>) -> actix_test::TestServer {
+        actix_test::start(move || {
+            App::new()
+                .route(
+                    "/api/v3/coins/list",
+                    web::get().to(|| async { HttpResponse::Ok().json(json!([])) }),
+                )
+                .route(
+                    "/api/v3/coins/{coin_id}/market_chart",
+                    web::get().to(move || async move {
+                        match price {
+                            Some(price) => HttpResponse::Ok().json(json!({
+                                "prices": [[Utc::now().timestamp_millis(), price]]
+                            })),
+                            None => HttpResponse::InternalServerError().finish(),
+                        }
+                    }),
+                )
+                .route(
+                    "/cryptocurrency/quotes/latest",
+                    web::get().to(move || async move {
+                        match price {
+                            Some(price) => HttpResponse::Ok().json(json!({
+                                "data": {
+                                    "ETH": {
+                                        "quote": {
+                                            "USD": {
+                                                "price": price,
+                                                "last_updated": Utc::now().to_rfc3339()
+                                            }
+                                        }
+                                    }
+                                }
+                            })),
+                            None => HttpResponse::InternalServerError().finish(),
+                        }
+                    }),
+                )
+        })
+    }
+
+    async fn coingecko_source(server: &actix_test::TestServer) -> BoxedTokenPriceAPI {
+        let api = CoinGeckoAPI::new(reqwest::Client::new(), server.url("").parse().unwrap())
+            .await
+            .unwrap();
+        Box::new(api)
+    }
+
+    fn coinmarketcap_source(server: &actix_test::TestServer) -> BoxedTokenPriceAPI {
+        Box::new(CoinMarketCapAPI::new(
+            reqwest::Client::new(),
+            server.url("").parse().unwrap(),
+        ))
+    }
+
+    /// Price API reporting the given price for every token.
+    struct MockPriceAPI(Option<TokenPrice>);
+
+    #[async_trait]
+    impl TokenPriceAPI for MockPriceAPI {
+        async fn get_price(&self, _token: &Token) -> Result<TokenPrice, PriceError> {
+            self.0
+                .clone()
+                .ok_or_else(|| PriceError::api_error("Price is not available"))
+        }
+    }
+
+    fn eth() -> Token {
+        Token::new(TokenId(0), Default::default(), "ETH", 18, TokenKind::ERC20)
+    }
+
+    fn price(value: u64) -> Ratio<BigUint> {
+        Ratio::from_integer(BigUint::from(value))
+    }
+
+    fn max_deviation() -> Ratio<BigUint> {
+        Ratio::new(BigUint::from(10u32), BigUint::from(100u32))
+    }
+
+    #[test]
+    fn prices_aggregation() {
+        let token_price = |value: u64, age_secs: i64| TokenPrice {
+            usd_price: price(value),
+            last_updated: Utc::now() - chrono::Duration::seconds(age_secs),
+        };
+
+        assert!(aggregate_prices(Vec::new(), &max_deviation()).is_none());
+
+        // The median of the odd number of prices is the middle one.
+        let prices = vec![
+            token_price(100, 0),
+            token_price(96, 10),
+            token_price(104, 0),
+        ];
+        let oldest = prices[1].last_updated;
+        let aggregated = aggregate_prices(prices, &max_deviation()).unwrap();
+        assert_eq!(aggregated.usd_price, price(100));
+        assert_eq!(aggregated.last_updated, oldest);
+
+        // The median of the even number of prices is the average of the middle ones.
+        let prices = vec![token_price(100, 0), token_price(102, 0)];
+        let aggregated = aggregate_prices(prices, &max_deviation()).unwrap();
+        assert_eq!(aggregated.usd_price, price(101));
+
+        // Outliers are not taken into account.
+        let prices = vec![
+            token_price(100, 0),
+            token_price(104, 0),
+            token_price(102, 0),
+            token_price(1000, 100),
+            token_price(1, 100),
+        ];
+        let aggregated = aggregate_prices(prices, &max_deviation()).unwrap();
+        assert_eq!(aggregated.usd_price, price(102));
+        assert!(Utc::now() - aggregated.last_updated < chrono::Duration::seconds(100));
+
+        // There is no agreement between the sources.
+        let prices = vec![token_price(100, 0), token_price(200, 0)];
+        assert!(aggregate_prices(prices, &max_deviation()).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn composite_price_api() {
+        let servers = vec![
+            mock_price_server(Some("100")),
+            mock_price_server(Some("104")),
+            mock_price_server(Some("101")),
+            // Misbehaving source.
+            mock_price_server(Some("5000")),
+            // Unavailable source.
+            mock_price_server(None),
+        ];
+        let sources = vec![
+            coingecko_source(&servers[0]).await,
+            coinmarketcap_source(&servers[1]),
+            coingecko_source(&servers[2]).await,
+            coinmarketcap_source(&servers[3]),
+            coingecko_source(&servers[4]).await,
+        ];
+
+        let api = CompositeTokenPriceAPI::new(sources, max_deviation(), None);
+        let token_price = api.get_price(&eth()).await.unwrap();
+        // The misbehaving source is ignored.
+        assert_eq!(token_price.usd_price, price(101));
+    }
+
+    #[actix_rt::test]
+    async fn composite_price_api_token_not_found() {
+        let server = mock_price_server(Some("100"));
+        let sources = vec![
+            coingecko_source(&server).await,
+            coingecko_source(&server).await,
+        ];
+        let api = CompositeTokenPriceAPI::new(sources, max_deviation(), None);
+
+        // The token is not listed in the mocked CoinGecko tokens list.
+        let token = Token::new(
+            TokenId(1),
+            Address::repeat_byte(1),
+            "DAI",
+            18,
+            TokenKind::ERC20,
+        );
+        let err = api.get_price(&token).await.unwrap_err();
+        assert!(matches!(err, PriceError::TokenNotFound(_)));
+    }
+
+    #[actix_rt::test]
+    async fn composite_price_api_fallback() {
+        let servers = vec![
+            mock_price_server(Some("100")),
+            mock_price_server(Some("200")),
+            mock_price_server(None),
+        ];
+        async fn sources(servers: &[actix_test::TestServer]) -> Vec<BoxedTokenPriceAPI> {
+            vec![
+                coingecko_source(&servers[0]).await,
+                coinmarketcap_source(&servers[1]),
+                coingecko_source(&servers[2]).await,
+            ]
+        }
+
+        // Sources don't agree on the price, so the fallback price is used.
+        let fallback_price = TokenPrice {
+            usd_price: price(150),
+            last_updated: Utc::now() - chrono::Duration::seconds(60),
+        };
+        let api = CompositeTokenPriceAPI::new(
+            sources(&servers).await,
+            max_deviation(),
+            Some(Box::new(MockPriceAPI(Some(fallback_price.clone())))),
+        );
+        let token_price = api.get_price(&eth()).await.unwrap();
+        assert_eq!(token_price.usd_price, fallback_price.usd_price);
+
+        // The fallback price is not available (e.g. the stored price is too old).
+        let api = CompositeTokenPriceAPI::new(
+            sources(&servers).await,
+            max_deviation(),
+            Some(Box::new(MockPriceAPI(None))),
+        );
+        let err = api.get_price(&eth()).await.unwrap_err();
+        assert!(matches!(err, PriceError::ApiError(_)));
+
+        let api = CompositeTokenPriceAPI::new(sources(&servers).await, max_deviation(), None);
+        let err = api.get_price(&eth()).await.unwrap_err();
+        assert!(matches!(err, PriceError::ApiError(_)));
+    }
+}
@@ -12,6 +12,9 @@ use zksync_types::{Token, TokenId, TokenPrice};
 
 pub mod coingecko;
 pub mod coinmarkercap;
+pub mod composite;
+pub mod stored;
+pub mod uniswap_twap;
 
 const UPDATE_PRICE_INTERVAL_SECS: u64 = 10 * 60;
 /// The limit of time we are willing to wait for response.
@@ -0,0 +1,54 @@
+use super::TokenPriceAPI;
+use crate::fee_ticker::PriceError;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::time::Duration;
+use zksync_storage::ConnectionPool;
+use zksync_types::{Token, TokenPrice};
+
+/// Token price API returning the last price stored by the ticker, unless it's too old.
+#[derive(Debug, Clone)]
+pub struct StoredTokenPriceAPI {
+    db_pool: ConnectionPool,
+    max_staleness: chrono::Duration,
+}
+
+impl StoredTokenPriceAPI {
+    pub fn new(db_pool: ConnectionPool, max_staleness: Duration) -> Self {
+        Self {
+            db_pool,
+            max_staleness: chrono::Duration::from_std(max_staleness)
+                .expect("Invalid price staleness bound"),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenPriceAPI for StoredTokenPriceAPI {
+    async fn get_price(&self, token: &Token) -> Result<TokenPrice, PriceError> {
+        let mut storage = self
+            .db_pool
+            .access_storage()
+            .await
+            .map_err(PriceError::db_error)?;
+        let price = storage
+            .tokens_schema()
+            .get_historical_ticker_price(token.id)
+            .await
+            .map_err(PriceError::db_error)?
+            .ok_or_else(|| {
+                PriceError::token_not_found(format!(
+                    "There is no stored price of token {}",
+                    token.symbol
+                ))
+            })?;
+
+        if Utc::now() - price.last_updated > self.max_staleness {
+            return Err(PriceError::api_error(format!(
+                "The stored price of token {} updated at {} is too old",
+                token.symbol, price.last_updated
+            )));
+        }
+        Ok(price)
+    }
+}
@@ -0,0 +1,576 @@
+//! Token price API reading the time-weighted average prices directly from the Uniswap pools.
+//!
+//! Every supported token is priced through the configured pool against the quote token of the pool,
+//! and the USD price of the quote token is obtained separately.
+//! Uniswap V3 pools provide the TWAP via their tick accumulator (`observe`), so the price is available
+//! right away. Uniswap V2 pairs only expose the cumulative price, which is snapshotted on every request;
+//! the TWAP is calculated between the current and a snapshot made at least the averaging period ago,
+//! so the prices of V2 pairs become available once that period passes after the start.
+
+use super::{composite::BoxedTokenPriceAPI, TokenPriceAPI};
+use crate::fee_ticker::PriceError;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use num::{rational::Ratio, traits::Pow, BigUint, FromPrimitive, Zero};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use web3::contract::{tokens::Detokenize, tokens::Tokenize, Options};
+use web3::types::{BlockId, BlockNumber};
+use zksync_config::configs::ticker::UniswapVersion;
+use zksync_contracts::{uniswap_v2_pair_contract, uniswap_v3_pool_contract};
+use zksync_eth_client::EthereumGateway;
+use zksync_types::{Address, Token, TokenPrice, U256};
+use zksync_utils::big_decimal_to_ratio;
+
+/// Resolution of the Uniswap V2 fixed point price encoding (UQ112x112).
+const UQ112_RESOLUTION: usize = 112;
+
+/// Uniswap pool the price of the token is obtained from.
+#[derive(Debug, Clone)]
+pub struct TwapPool {
+    pub version: UniswapVersion,
+    pub address: Address,
+    /// zkSync token corresponding to the other token of the pool.
+    pub quote_token: Token,
+    /// Whether the priced token is `token0` of the pool, i.e. the pool price isn't inverted.
+    pub is_token0: bool,
+}
+
+impl TwapPool {
+    /// Creates the pool pricing the `token`, resolving the order of the tokens in the pool.
+    pub async fn load(
+        gateway: &EthereumGateway,
+        version: UniswapVersion,
+        address: Address,
+        token: Address,
+        quote_token: Token,
+    ) -> Result<Self, PriceError> {
+        let contract = match version {
+            UniswapVersion::V2 => uniswap_v2_pair_contract(),
+            UniswapVersion::V3 => uniswap_v3_pool_contract(),
+        };
+        let token0: Address = call_pool(
+            gateway,
+            address,
+            contract,
+            "token0",
+            (),
+            BlockNumber::Latest,
+        )
+        .await?;
+        if token0 != token && token0 != quote_token.address {
+            return Err(PriceError::api_error(format!(
+                "Pool {:?} doesn't trade the token {:?}",
+                address, token
+            )));
+        }
+        Ok(Self {
+            version,
+            address,
+            quote_token,
+            is_token0: token0 == token,
+        })
+    }
+}
+
+/// Cumulative price of the Uniswap V2 pair at the moment of the block timestamp.
+#[derive(Debug, Clone, Copy)]
+struct CumulativePrice {
+    timestamp: u64,
+    price0_cumulative: U256,
+}
+
+pub struct UniswapTwapAPI {
+    gateway: EthereumGateway,
+    /// Pools by the address of the token priced through them.
+    pools: HashMap<Address, TwapPool>,
+    period: u32,
+    /// API providing USD prices of the quote tokens.
+    quote_prices: BoxedTokenPriceAPI,
+    v2_pair_contract: ethabi::Contract,
+    v3_pool_contract: ethabi::Contract,
+    /// Snapshots of the cumulative prices of the Uniswap V2 pairs, ordered by the timestamp.
+    v2_observations: Mutex<HashMap<Address, VecDeque<CumulativePrice>>>,
+}
+
+impl UniswapTwapAPI {
+    pub fn new(
+        gateway: EthereumGateway,
+        pools: HashMap<Address, TwapPool>,
+        period: Duration,
+        quote_prices: BoxedTokenPriceAPI,
+    ) -> Self {
+        Self {
+            gateway,
+            pools,
+            period: period.as_secs() as u32,
+            quote_prices,
+            v2_pair_contract: uniswap_v2_pair_contract(),
+            v3_pool_contract: uniswap_v3_pool_contract(),
+            v2_observations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn call<R, P>(
+        &self,
+        pool: &TwapPool,
+        func: &str,
+        params: P,
+        block: BlockNumber,
+    ) -> Result<R, PriceError>
+    where
+        R: Detokenize + Unpin,
+        P: Tokenize + Clone,
+    {
+        let contract = match pool.version {
+            UniswapVersion::V2 => self.v2_pair_contract.clone(),
+            UniswapVersion::V3 => self.v3_pool_contract.clone(),
+        };
+        call_pool(&self.gateway, pool.address, contract, func, params, block).await
+    }
+
+    /// Returns the TWAP of the Uniswap V3 pool, i.e. the price of `token0` in the units of `token1`.
+    async fn v3_price(&self, pool: &TwapPool) -> Result<Ratio<BigUint>, PriceError> {
+        let (tick_cumulatives, _): (Vec<U256>, Vec<U256>) = self
+            .call(
+                pool,
+                "observe",
+                vec![U256::from(self.period), U256::zero()],
+                BlockNumber::Latest,
+            )
+            .await?;
+        if tick_cumulatives.len() != 2 {
+            return Err(PriceError::api_error(format!(
+                "Unexpected observation of the pool {:?}",
+                pool.address
+            )));
+        }
+        // Tick cumulatives are `int56`, so the lowest 64 bits of the two's complement
+        // representation are enough to restore the value.
+        let delta = tick_cumulatives[1].low_u64() as i64 - tick_cumulatives[0].low_u64() as i64;
+        let tick = mean_tick(delta, self.period);
+
+        let price = BigDecimal::from_f64(1.0001f64.powi(tick)).ok_or_else(|| {
+            PriceError::api_error(format!("Invalid mean tick {} of the pool", tick))
+        })?;
+        big_decimal_to_ratio(&price).map_err(PriceError::api_error)
+    }
+
+    /// Returns the TWAP of the Uniswap V2 pair, i.e. the price of `token0` in the units of `token1`.
+    async fn v2_price(&self, pool: &TwapPool) -> Result<Ratio<BigUint>, PriceError> {
+        // The state of the pair and the timestamp must be taken from the same block.
+        let block_number = self
+            .gateway
+            .block_number()
+            .await
+            .map_err(PriceError::api_error)?;
+        let block = BlockNumber::Number(block_number);
+        let now = self
+            .gateway
+            .block(BlockId::Number(block))
+            .await
+            .map_err(PriceError::api_error)?
+            .ok_or_else(|| {
+                PriceError::api_error(format!("Block {} is not available", block_number))
+            })?
+            .timestamp
+            .as_u64();
+        let (reserve0, reserve1, timestamp_last): (U256, U256, U256) =
+            self.call(pool, "getReserves", (), block).await?;
+        let price0_cumulative_last: U256 =
+            self.call(pool, "price0CumulativeLast", (), block).await?;
+        if reserve0.is_zero() {
+            return Err(PriceError::api_error(format!(
+                "The pair {:?} has no liquidity",
+                pool.address
+            )));
+        }
+
+        // The cumulative price is only updated on the first trade in the block, so the time
+        // passed since then is accounted with the current price, as the pair itself does.
+        // Timestamps are stored by the pair modulo 2^32.
+        let elapsed = (now as u32).wrapping_sub(timestamp_last.low_u32());
+        let spot_price = (reserve1 << UQ112_RESOLUTION) / reserve0;
+        let current = CumulativePrice {
+            timestamp: now,
+            price0_cumulative: price0_cumulative_last
+                .overflowing_add(spot_price.overflowing_mul(elapsed.into()).0)
+                .0,
+        };
+
+        let past = {
+            let mut observations = self.v2_observations.lock().unwrap();
+            let observations = observations.entry(pool.address).or_default();
+            if observations
+                .back()
+                .map_or(true, |last| last.timestamp < current.timestamp)
+            {
+                observations.push_back(current);
+            }
+            // Only the latest of the observations older than the period is needed.
+            let period_start = now.saturating_sub(self.period as u64);
+            while observations.len() > 1 && observations[1].timestamp <= period_start {
+                observations.pop_front();
+            }
+            observations
+                .front()
+                .copied()
+                .filter(|observation| observation.timestamp <= period_start)
+        };
+        let past = past.ok_or_else(|| {
+            PriceError::api_error(format!(
+                "Not enough observations of the pair {:?} yet",
+                pool.address
+            ))
+        })?;
+
+        let price_delta = current
+            .price0_cumulative
+            .overflowing_sub(past.price0_cumulative)
+            .0;
+        Ok(Ratio::new(
+            u256_to_biguint(price_delta),
+            BigUint::from(current.timestamp - past.timestamp) << UQ112_RESOLUTION,
+        ))
+    }
+}
+
+#[async_trait]
+impl TokenPriceAPI for UniswapTwapAPI {
+    async fn get_price(&self, token: &Token) -> Result<TokenPrice, PriceError> {
+        let start = Instant::now();
+        let pool = self.pools.get(&token.address).ok_or_else(|| {
+            PriceError::token_not_found(format!(
+                "There is no Uniswap pool configured for token '{}, {:?}'",
+                token.symbol, token.address
+            ))
+        })?;
+
+        let price0 = match pool.version {
+            UniswapVersion::V2 => self.v2_price(pool).await?,
+            UniswapVersion::V3 => self.v3_price(pool).await?,
+        };
+        let raw_price = if pool.is_token0 {
+            price0
+        } else if price0.is_zero() {
+            return Err(PriceError::api_error(format!(
+                "Zero price of the pool {:?}",
+                pool.address
+            )));
+        } else {
+            price0.recip()
+        };
+
+        let quote_price = self.quote_prices.get_price(&pool.quote_token).await?;
+        if quote_price.usd_price.is_zero() {
+            return Err(PriceError::api_error(format!(
+                "Unknown price of the quote token {}",
+                pool.quote_token.symbol
+            )));
+        }
+        // Pools operate with the smallest units of the tokens.
+        let usd_price = raw_price
+            * quote_price.usd_price
+            * Ratio::new(
+                BigUint::from(10u32).pow(token.decimals as u32),
+                BigUint::from(10u32).pow(pool.quote_token.decimals as u32),
+            );
+
+        metrics::histogram!("ticker.uniswap_twap.get_price", start.elapsed());
+        Ok(TokenPrice {
+            usd_price,
+            last_updated: quote_price.last_updated,
+        })
+    }
+}
+
+async fn call_pool<R, P>(
+    gateway: &EthereumGateway,
+    address: Address,
+    contract: ethabi::Contract,
+    func: &str,
+    params: P,
+    block: BlockNumber,
+) -> Result<R, PriceError>
+where
+    R: Detokenize + Unpin,
+    P: Tokenize + Clone,
+{
+    gateway
+        .call_contract_function(
+            func,
+            params,
+            None::<Address>,
+            Options::default(),
+            BlockId::Number(block),
+            address,
+            contract,
+        )
+        .await
+        .map_err(|err| {
+            PriceError::api_error(format!(
+                "Failed to call {} of the pool {:?}: {}",
+                func, address, err
+            ))
+        })
+}
+
+/// Mean tick over the period, rounded towards negative infinity like the Uniswap oracle library does.
+fn mean_tick(tick_cumulative_delta: i64, period: u32) -> i32 {
+    let period = period as i64;
+    let mut tick = tick_cumulative_delta / period;
+    if tick_cumulative_delta < 0 && tick_cumulative_delta % period != 0 {
+        tick -= 1;
+    }
+    tick as i32
+}
+
+fn u256_to_biguint(value: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    BigUint::from_bytes_be(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ethabi::Token as AbiToken;
+    use num::traits::Signed;
+    use num::BigInt;
+    use std::str::FromStr;
+    use zksync_eth_client::clients::mock::MockEthereum;
+    use zksync_types::{TokenId, TokenKind};
+
+    const PERIOD: u32 = 1800;
+
+    /// Prices all the quote tokens with the same USD price.
+    struct QuotePriceAPI(Ratio<BigUint>);
+
+    #[async_trait]
+    impl TokenPriceAPI for QuotePriceAPI {
+        async fn get_price(&self, _token: &Token) -> Result<TokenPrice, PriceError> {
+            Ok(TokenPrice {
+                usd_price: self.0.clone(),
+                last_updated: Utc::now(),
+            })
+        }
+    }
+
+    fn address(value: &str) -> Address {
+        Address::from_str(value).unwrap()
+    }
+
+    fn tick_cumulative(value: i64) -> AbiToken {
+        let abs = U256::from(value.unsigned_abs());
+        AbiToken::Int(if value < 0 {
+            U256::zero().overflowing_sub(abs).0
+        } else {
+            abs
+        })
+    }
+
+    fn assert_price_close(actual: &Ratio<BigUint>, expected: Ratio<BigUint>) {
+        let to_signed = |value: &Ratio<BigUint>| {
+            Ratio::new(
+                BigInt::from(value.numer().clone()),
+                BigInt::from(value.denom().clone()),
+            )
+        };
+        let expected = to_signed(&expected);
+        let deviation = ((to_signed(actual) - &expected) / &expected).abs();
+        assert!(
+            deviation < Ratio::new(1.into(), 1000.into()),
+            "Price {} differs from the expected {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn mean_tick_rounding() {
+        assert_eq!(mean_tick(7, 2), 3);
+        assert_eq!(mean_tick(-7, 2), -4);
+        assert_eq!(mean_tick(-8, 2), -4);
+    }
+
+    #[tokio::test]
+    async fn uniswap_v3_twap() {
+        let token = Token::new(
+            TokenId(1),
+            address("514910771af9ca656af840dff83e8264ecf986ca"),
+            "LINK",
+            18,
+            TokenKind::ERC20,
+        );
+        let usdc = Token::new(
+            TokenId(2),
+            address("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+            "USDC",
+            6,
+            TokenKind::ERC20,
+        );
+        let pool_address = address("a6cc3c2531fdaa6ae1a3ca84c2855806728693e8");
+
+        let client = MockEthereum::default();
+        // The token is `token1` of the pool, so the pool price is the amount of the token for one USDC.
+        // 1.0001^276324 is approximately 10^12, so 1 LINK costs 1 USDC.
+        client
+            .set_contract_call_result(
+                pool_address,
+                "token0",
+                vec![AbiToken::Address(usdc.address)],
+            )
+            .await;
+        client
+            .set_contract_call_result(
+                pool_address,
+                "observe",
+                vec![
+                    AbiToken::Array(vec![
+                        tick_cumulative(-1_000_000),
+                        tick_cumulative(-1_000_000 + 276324 * PERIOD as i64),
+                    ]),
+                    AbiToken::Array(vec![AbiToken::Uint(0.into()), AbiToken::Uint(0.into())]),
+                ],
+            )
+            .await;
+
+        let gateway = EthereumGateway::Mock(client);
+        let pool = TwapPool::load(
+            &gateway,
+            UniswapVersion::V3,
+            pool_address,
+            token.address,
+            usdc.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(!pool.is_token0);
+        // The pool which doesn't trade the token can't be used.
+        let unrelated_token = address("6b175474e89094c44da98b954eedeac495271d0f");
+        TwapPool::load(
+            &gateway,
+            UniswapVersion::V3,
+            pool_address,
+            unrelated_token,
+            Token::new(TokenId(3), unrelated_token, "DAI", 18, TokenKind::ERC20),
+        )
+        .await
+        .unwrap_err();
+
+        let pools = vec![(token.address, pool)].into_iter().collect();
+        let api = UniswapTwapAPI::new(
+            gateway,
+            pools,
+            Duration::from_secs(PERIOD as u64),
+            Box::new(QuotePriceAPI(Ratio::from_integer(2u32.into()))),
+        );
+
+        let price = api.get_price(&token).await.unwrap();
+        assert_price_close(&price.usd_price, Ratio::from_integer(2u32.into()));
+
+        // Tokens without the configured pool are not supported.
+        let unknown_token = Token::new(
+            TokenId(3),
+            address("6b175474e89094c44da98b954eedeac495271d0f"),
+            "DAI",
+            18,
+            TokenKind::ERC20,
+        );
+        assert!(matches!(
+            api.get_price(&unknown_token).await,
+            Err(PriceError::TokenNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn uniswap_v2_twap() {
+        let token = Token::new(
+            TokenId(1),
+            address("1f9840a85d5af5bf1d1762f925bdaddc4201f984"),
+            "UNI",
+            18,
+            TokenKind::ERC20,
+        );
+        let eth = Token::new(TokenId(0), Address::zero(), "ETH", 18, TokenKind::ERC20);
+        let pair_address = address("d3d2e2692501a5c9ca623199d38826e513033a17");
+
+        // 1 UNI costs 2^-8 ETH.
+        let reserve0 = U256::from(1u64) << 70;
+        let reserve1 = U256::from(1u64) << 62;
+        let spot_price = (reserve1 << UQ112_RESOLUTION) / reserve0;
+        let initial_cumulative = U256::from(123_456_789u64) << UQ112_RESOLUTION;
+
+        let client = MockEthereum::default();
+        client
+            .set_contract_call_result(
+                pair_address,
+                "token0",
+                vec![AbiToken::Address(token.address)],
+            )
+            .await;
+        let set_pair_state = |timestamp_last: u64, cumulative: U256| {
+            let client = client.clone();
+            async move {
+                client
+                    .set_contract_call_result(
+                        pair_address,
+                        "getReserves",
+                        vec![
+                            AbiToken::Uint(reserve0),
+                            AbiToken::Uint(reserve1),
+                            AbiToken::Uint(timestamp_last.into()),
+                        ],
+                    )
+                    .await;
+                client
+                    .set_contract_call_result(
+                        pair_address,
+                        "price0CumulativeLast",
+                        vec![AbiToken::Uint(cumulative)],
+                    )
+                    .await;
+            }
+        };
+        set_pair_state(1000, initial_cumulative).await;
+        client.set_block_timestamp(1000).await;
+
+        let gateway = EthereumGateway::Mock(client.clone());
+        let pool = TwapPool::load(
+            &gateway,
+            UniswapVersion::V2,
+            pair_address,
+            token.address,
+            eth,
+        )
+        .await
+        .unwrap();
+        assert!(pool.is_token0);
+
+        let pools = vec![(token.address, pool)].into_iter().collect();
+        let api = UniswapTwapAPI::new(
+            gateway,
+            pools,
+            Duration::from_secs(PERIOD as u64),
+            Box::new(QuotePriceAPI(Ratio::from_integer(2000u32.into()))),
+        );
+
+        // There is no observation made the period ago yet.
+        assert!(matches!(
+            api.get_price(&token).await,
+            Err(PriceError::ApiError(_))
+        ));
+
+        // The last trade happened before the end of the period, the rest is accounted by the current price.
+        set_pair_state(1600, initial_cumulative + spot_price * 600).await;
+        client.set_block_timestamp(1000 + PERIOD as u64).await;
+
+        let price = api.get_price(&token).await.unwrap();
+        assert_eq!(
+            price.usd_price,
+            Ratio::new(BigUint::from(2000u32), BigUint::from(256u32))
+        );
+    }
+}
@@ -5,7 +5,7 @@
 #[cfg(test)]
 use std::any::Any;
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 // External deps
 use anyhow::format_err;
 use async_trait::async_trait;
@@ -13,7 +13,7 @@ use chrono::Utc;
 use num::rational::Ratio;
 use num::BigUint;
 // Workspace deps
-use zksync_storage::ConnectionPool;
+use zksync_storage::{chain::block::records::BlockChunksUsage, ConnectionPool};
 use zksync_token_db_cache::TokenDBCache;
 use zksync_types::aggregated_operations::AggregatedActionType;
 use zksync_types::{Address, Token, TokenId, TokenLike, TokenPrice};
@@ -58,6 +58,12 @@ pub trait FeeTickerInfo: FeeTickerClone + Send + Sync + 'static {
     /// Get current gas price in ETH
     async fn get_gas_price_wei(&self) -> Result<BigUint, anyhow::Error>;
 
+    /// Get gas prices of the latest confirmed Ethereum transactions sent by the operator, newest first.
+    async fn get_recent_gas_prices_wei(&self, limit: u32) -> anyhow::Result<Vec<BigUint>>;
+
+    /// Get the amount of used chunks and the size of the latest blocks, newest first.
+    async fn get_recent_blocks_chunks(&self, limit: u32) -> anyhow::Result<Vec<BlockChunksUsage>>;
+
     async fn get_token(&self, token: TokenLike) -> Result<Token, anyhow::Error>;
 
     /// Make boxed value to any. Helpful for downcasting in tests
@@ -69,13 +75,17 @@ pub trait FeeTickerInfo: FeeTickerClone + Send + Sync + 'static {
 pub struct TickerInfo {
     db: ConnectionPool,
     token_db_cache: TokenDBCache,
+    /// Maximum age of the stored token price to calculate fees with.
+    price_staleness: chrono::Duration,
 }
 
 impl TickerInfo {
-    pub fn new(db: ConnectionPool) -> Self {
+    pub fn new(db: ConnectionPool, price_staleness: Duration) -> Self {
         Self {
             db,
             token_db_cache: Default::default(),
+            price_staleness: chrono::Duration::from_std(price_staleness)
+                .expect("Invalid price staleness bound"),
         }
     }
 }
@@ -207,6 +217,15 @@ impl FeeTickerInfo for TickerInfo {
             .map_err(|e| vlog::warn!("Failed to get historical ticker price: {}", e));
 
         if let Ok(Some(historical_price)) = historical_price {
+            // The price is not updated anymore (e.g. all the price sources are down),
+            // so it can't be used to calculate fees.
+            if Utc::now() - historical_price.last_updated > self.price_staleness {
+                metrics::histogram!("ticker_info.get_last_token_price", start.elapsed(), "type" => "stale");
+                return Err(PriceError::api_error(format!(
+                    "The price of token {} updated at {} is too old",
+                    token.symbol, historical_price.last_updated
+                )));
+            }
             return Ok(historical_price);
         }
 
@@ -235,6 +254,34 @@ impl FeeTickerInfo for TickerInfo {
         Ok(average_gas_price)
     }
 
+    async fn get_recent_gas_prices_wei(&self, limit: u32) -> anyhow::Result<Vec<BigUint>> {
+        let start = Instant::now();
+        let mut storage = self.db.access_storage().await?;
+        let gas_prices = storage
+            .ethereum_schema()
+            .load_recent_gas_prices(limit)
+            .await?
+            .into_iter()
+            .map(|gas_price| BigUint::from(gas_price.as_u64()))
+            .collect();
+
+        metrics::histogram!("ticker_info.get_recent_gas_prices_wei", start.elapsed());
+        Ok(gas_prices)
+    }
+
+    async fn get_recent_blocks_chunks(&self, limit: u32) -> anyhow::Result<Vec<BlockChunksUsage>> {
+        let start = Instant::now();
+        let mut storage = self.db.access_storage().await?;
+        let blocks_chunks = storage
+            .chain()
+            .block_schema()
+            .load_recent_blocks_chunks(limit)
+            .await?;
+
+        metrics::histogram!("ticker_info.get_recent_blocks_chunks", start.elapsed());
+        Ok(blocks_chunks)
+    }
+
     async fn get_token(&self, token: TokenLike) -> Result<Token, anyhow::Error> {
         let start = Instant::now();
         // Try to find the token in the cache first.
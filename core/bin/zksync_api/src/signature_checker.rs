@@ -33,6 +33,7 @@ pub enum TxVariant {
     Batch(Vec<SignedZkSyncTx>, Option<EthBatchSignData>),
     Order(Box<Order>),
     Toggle2FA,
+    CancelTx,
 }
 
 /// Wrapper on a `TxVariant` which guarantees that (a batch of)
@@ -71,6 +72,7 @@ impl VerifiedTx {
             TxVariant::Batch(_, _) => panic!("called `unwrap_tx` on a `Batch` value"),
             TxVariant::Order(_) => panic!("called `unwrap_tx` on an `Order` value"),
             TxVariant::Toggle2FA => panic!("called `unwrap_tx` on an `Toggle2FA` value"),
+            TxVariant::CancelTx => panic!("called `unwrap_tx` on a `CancelTx` value"),
         }
     }
 
@@ -81,6 +83,7 @@ impl VerifiedTx {
             TxVariant::Tx(_) => panic!("called `unwrap_batch` on a `Tx` value"),
             TxVariant::Order(_) => panic!("called `unwrap_batch` on an `Order` value"),
             TxVariant::Toggle2FA => panic!("called `unwrap_batch` on an `Toggle2FA` value"),
+            TxVariant::CancelTx => panic!("called `unwrap_batch` on a `CancelTx` value"),
         }
     }
 }
@@ -143,6 +146,18 @@ async fn verify_eth_signature(
                 return Err(TxAddError::IncorrectEthSignature);
             }
         }
+        RequestData::CancelTx(request) => {
+            let signature_correct = verify_ethereum_signature(
+                &request.sign_data.signature,
+                &request.sign_data.message,
+                request.sender,
+                eth_checker,
+            )
+            .await;
+            if !signature_correct {
+                return Err(TxAddError::IncorrectEthSignature);
+            }
+        }
     }
 
     Ok(())
@@ -309,6 +324,7 @@ fn verify_tx_correctness(tx: &mut TxVariant) -> Result<(), TxAddError> {
             .check_correctness()
             .map_err(|err| TxAddError::IncorrectTx(TransactionError::OrderError(err)))?,
         TxVariant::Toggle2FA => {} // There is no data to check correctness of
+        TxVariant::CancelTx => {}
     }
     Ok(())
 }
@@ -346,6 +362,12 @@ pub struct Toggle2FARequest {
     pub sender: Address,
 }
 
+#[derive(Debug)]
+pub struct CancelTxRequest {
+    pub sign_data: EthSignData,
+    pub sender: Address,
+}
+
 /// Request for the signature check.
 #[derive(Debug)]
 pub struct VerifySignatureRequest {
@@ -360,6 +382,7 @@ pub enum RequestData {
     Batch(BatchRequest),
     Order(OrderRequest),
     Toggle2FA(Toggle2FARequest),
+    CancelTx(CancelTxRequest),
 }
 
 impl RequestData {
@@ -371,6 +394,7 @@ impl RequestData {
             }
             RequestData::Order(request) => TxVariant::Order(request.order.clone()),
             RequestData::Toggle2FA(_) => TxVariant::Toggle2FA,
+            RequestData::CancelTx(_) => TxVariant::CancelTx,
         }
     }
 }
@@ -6,16 +6,19 @@ use std::fmt::Debug;
 use web3::{
     contract::Options,
     transports::http,
-    types::{BlockNumber, FilterBuilder, Log},
+    types::{BlockId, BlockNumber, FilterBuilder, Log},
     Web3,
 };
 
 use zksync_contracts::{governance_contract, zksync_contract};
 use zksync_eth_client::ethereum_gateway::EthereumGateway;
 use zksync_types::{
-    Address, NewTokenEvent, Nonce, PriorityOp, RegisterNFTFactoryEvent, H160, U256,
+    Address, NewTokenEvent, Nonce, PriorityOp, RegisterNFTFactoryEvent, H160, H256, U256,
 };
 
+use super::subscription::{EthSubscriber, EthWsSubscriber, L1EventStream};
+
+#[derive(Clone)]
 struct ContractTopics {
     new_priority_request: Hash,
     new_token: Hash,
@@ -42,7 +45,7 @@ impl ContractTopics {
 }
 
 #[async_trait::async_trait]
-pub trait EthClient {
+pub trait EthClient: EthSubscriber {
     async fn get_priority_op_events(
         &self,
         from: BlockNumber,
@@ -59,30 +62,42 @@ pub trait EthClient {
         to: BlockNumber,
     ) -> anyhow::Result<Vec<NewTokenEvent>>;
     async fn block_number(&self) -> anyhow::Result<u64>;
+    /// Returns the hash of the block with the given number, or `None` if there is no such block
+    /// in the chain.
+    async fn get_block_hash(&self, block_number: u64) -> anyhow::Result<Option<H256>>;
     async fn get_auth_fact(&self, address: Address, nonce: Nonce) -> anyhow::Result<Vec<u8>>;
     async fn get_auth_fact_reset_time(&self, address: Address, nonce: Nonce)
         -> anyhow::Result<u64>;
 }
 
+#[derive(Clone)]
 pub struct EthHttpClient {
     client: EthereumGateway,
     topics: ContractTopics,
     zksync_contract_addr: H160,
     governance_contract_addr: H160,
+    subscriber: Option<EthWsSubscriber>,
 }
 
 impl EthHttpClient {
+    /// Creates the client. If the WebSocket API address is provided, the client will also
+    /// subscribe to the events of the watched contracts.
     pub fn new(
         client: EthereumGateway,
         zksync_contract_addr: H160,
         governance_contract_addr: H160,
+        web3_ws_url: Option<String>,
     ) -> Self {
         let topics = ContractTopics::new(&zksync_contract(), &governance_contract());
+        let subscriber = web3_ws_url.map(|url| {
+            EthWsSubscriber::new(url, vec![zksync_contract_addr, governance_contract_addr])
+        });
         Self {
             client,
             topics,
             zksync_contract_addr,
             governance_contract_addr,
+            subscriber,
         }
     }
 
@@ -126,6 +141,16 @@ impl EthHttpClient {
     }
 }
 
+#[async_trait::async_trait]
+impl EthSubscriber for EthHttpClient {
+    async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>> {
+        match &self.subscriber {
+            Some(subscriber) => subscriber.subscribe().await,
+            None => Ok(None),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl EthClient for EthHttpClient {
     async fn get_priority_op_events(
@@ -224,6 +249,14 @@ impl EthClient for EthHttpClient {
         Ok(self.client.block_number().await?.as_u64())
     }
 
+    async fn get_block_hash(&self, block_number: u64) -> anyhow::Result<Option<H256>> {
+        let block = self
+            .client
+            .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+            .await?;
+        Ok(block.and_then(|block| block.hash))
+    }
+
     async fn get_auth_fact(&self, address: Address, nonce: Nonce) -> anyhow::Result<Vec<u8>> {
         self.client
             .call_main_contract_function(
@@ -1,8 +1,8 @@
 // Built-in deps
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 // External uses
 // Workspace deps
-use zksync_types::{NewTokenEvent, PriorityOp, RegisterNFTFactoryEvent, SerialId};
+use zksync_types::{NewTokenEvent, PriorityOp, RegisterNFTFactoryEvent, SerialId, H256};
 // Local deps
 use super::received_ops::ReceivedPriorityOp;
 
@@ -39,6 +39,9 @@ pub struct ETHState {
     new_tokens: Vec<NewTokenEvent>,
     /// List of events denoting registered factories for NFT withdrawing
     register_nft_factory_events: Vec<RegisterNFTFactoryEvent>,
+    /// Hashes of the Ethereum blocks containing the recent priority operations,
+    /// keyed by the block number. Used to detect the chain reorgs.
+    block_hashes: BTreeMap<u64, H256>,
 }
 
 impl ETHState {
@@ -49,6 +52,7 @@ impl ETHState {
         priority_queue: HashMap<SerialId, ReceivedPriorityOp>,
         new_tokens: Vec<NewTokenEvent>,
         register_nft_factory_events: Vec<RegisterNFTFactoryEvent>,
+        block_hashes: BTreeMap<u64, H256>,
     ) -> Self {
         assert!(
             last_ethereum_block_backup <= last_ethereum_block,
@@ -67,6 +71,7 @@ impl ETHState {
             priority_queue,
             new_tokens,
             register_nft_factory_events,
+            block_hashes,
         }
     }
 
@@ -94,10 +99,26 @@ impl ETHState {
         self.next_priority_op_id
     }
 
+    pub fn block_hashes(&self) -> &BTreeMap<u64, H256> {
+        &self.block_hashes
+    }
+
     pub fn reset_last_ethereum_block(&mut self) {
         self.last_ethereum_block = self.last_ethereum_block_backup;
     }
 
+    /// Moves the state back to the block preceding `first_reverted_block`, so the blocks replaced
+    /// by the chain reorg will be processed again. Unconfirmed operations from the reverted blocks
+    /// are discarded, while the confirmed ones are kept, since they may be already executed.
+    pub fn revert_blocks(&mut self, first_reverted_block: u64) {
+        let last_valid_block = first_reverted_block.saturating_sub(1);
+        self.last_ethereum_block = self.last_ethereum_block.min(last_valid_block);
+        self.last_ethereum_block_backup = self.last_ethereum_block_backup.min(last_valid_block);
+        self.unconfirmed_queue
+            .retain(|op| op.eth_block < first_reverted_block);
+        self.block_hashes.split_off(&first_reverted_block);
+    }
+
     #[cfg(test)]
     pub(crate) fn last_ethereum_block_backup(&self) -> u64 {
         self.last_ethereum_block_backup
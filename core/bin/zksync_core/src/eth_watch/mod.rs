@@ -4,9 +4,15 @@
 //!
 //! Poll interval is configured using the `ETH_POLL_INTERVAL` constant.
 //! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` environment variable.
+//! If the WebSocket API of the Ethereum node is configured, the node is polled upon the notifications
+//! about the new blocks and logs instead (see the `subscription` module).
+//!
+//! The watcher also tracks the hashes of the blocks with the recent priority operations to detect the chain reorgs.
+//! Unconfirmed operations reorged out of the chain are retracted from the mempool, while the reorg of the confirmed
+//! operations is reported as a critical error, since they can't be safely reverted.
 
 // Built-in deps
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::time::{Duration, Instant};
 
 // External uses
@@ -21,18 +27,24 @@ use itertools::Itertools;
 use tokio::{task::JoinHandle, time};
 use web3::types::BlockNumber;
 
-use zksync_config::{ContractsConfig, ETHWatchConfig};
+use zksync_config::{ContractsConfig, ETHClientConfig, ETHWatchConfig};
 use zksync_crypto::params::PRIORITY_EXPIRATION;
 use zksync_eth_client::ethereum_gateway::EthereumGateway;
 use zksync_mempool::MempoolTransactionRequest;
-use zksync_types::{NewTokenEvent, PriorityOp, RegisterNFTFactoryEvent, SerialId};
+use zksync_types::{NewTokenEvent, PriorityOp, RegisterNFTFactoryEvent, SerialId, H256};
 
 // Local deps
-use self::{client::EthClient, eth_state::ETHState, received_ops::sift_outdated_ops};
+use self::{
+    client::EthClient,
+    eth_state::ETHState,
+    received_ops::sift_outdated_ops,
+    subscription::{EthSubscriber, PendingPoll, PollTrigger},
+};
 
 mod client;
 mod eth_state;
 mod received_ops;
+pub mod subscription;
 
 #[cfg(test)]
 mod tests;
@@ -41,6 +53,10 @@ mod tests;
 /// before repeating the request.
 const RATE_LIMIT_DELAY: Duration = Duration::from_secs(30);
 
+/// Amount of blocks after the confirmation during which the blocks with priority operations
+/// are still checked for the reorgs.
+const REORG_DETECTION_DEPTH: u64 = 64;
+
 /// Ethereum Watcher operating mode.
 ///
 /// Normally Ethereum watcher will always poll the Ethereum node upon request,
@@ -84,6 +100,7 @@ pub struct EthWatch<W: EthClient> {
     /// All ethereum events are accepted after sufficient confirmations to eliminate risk of block reorg.
     number_of_confirmations_for_event: u64,
     mode: WatcherMode,
+    pending_poll: PendingPoll,
 }
 
 impl<W: EthClient> EthWatch<W> {
@@ -98,9 +115,15 @@ impl<W: EthClient> EthWatch<W> {
             eth_state: ETHState::default(),
             mode: WatcherMode::Working,
             number_of_confirmations_for_event,
+            pending_poll: PendingPoll::default(),
         }
     }
 
+    /// Returns the flag of the requested poll, shared with the `PollTrigger` task.
+    pub fn pending_poll(&self) -> PendingPoll {
+        self.pending_poll.clone()
+    }
+
     /// Atomically replaces the stored Ethereum state.
     fn set_new_state(&mut self, new_state: ETHState) {
         self.eth_state = new_state;
@@ -173,6 +196,28 @@ impl<W: EthClient> EthWatch<W> {
             }
         }
 
+        // Unconfirmed operations that neither got confirmed nor are still waiting for confirmations
+        // have been reorged out of the chain.
+        let retracted_ops: Vec<_> = self
+            .eth_state
+            .unconfirmed_queue()
+            .iter()
+            .map(|op| op.serial_id)
+            .filter(|serial_id| {
+                !priority_queue.contains_key(serial_id)
+                    && !updated_state
+                        .unconfirmed_queue()
+                        .iter()
+                        .any(|op| op.serial_id == *serial_id)
+            })
+            .collect();
+        self.retract_priority_ops(retracted_ops).await?;
+
+        // Keep tracking the blocks which are still subject to the reorg detection.
+        let mut block_hashes = self.eth_state.block_hashes().clone();
+        block_hashes.extend(updated_state.block_hashes());
+        let block_hashes = block_hashes.split_off(&self.first_watched_block(last_ethereum_block));
+
         // Extend the existing token events with the new ones.
         let mut new_tokens = self.eth_state.new_tokens().to_vec();
         for token in updated_state.new_tokens() {
@@ -198,11 +243,121 @@ impl<W: EthClient> EthWatch<W> {
             priority_queue,
             new_tokens,
             register_nft_factory_events,
+            block_hashes,
         );
         self.set_new_state(new_state);
         Ok(())
     }
 
+    /// Returns the first block which should be checked for reorgs.
+    fn first_watched_block(&self, current_ethereum_block: u64) -> u64 {
+        current_ethereum_block
+            .saturating_sub(self.number_of_confirmations_for_event)
+            .saturating_sub(REORG_DETECTION_DEPTH)
+    }
+
+    /// Fetches the hashes of the blocks containing the given priority operations.
+    /// Blocks that are too deep to be checked for reorgs are skipped.
+    async fn get_block_hashes(
+        &self,
+        ops: &[PriorityOp],
+        current_ethereum_block: u64,
+    ) -> anyhow::Result<BTreeMap<u64, H256>> {
+        let first_watched_block = self.first_watched_block(current_ethereum_block);
+        let blocks: BTreeSet<_> = ops
+            .iter()
+            .map(|op| op.eth_block)
+            .filter(|block| *block >= first_watched_block)
+            .collect();
+
+        let mut block_hashes = BTreeMap::new();
+        for block in blocks {
+            if let Some(hash) = self.client.get_block_hash(block).await? {
+                block_hashes.insert(block, hash);
+            }
+        }
+        Ok(block_hashes)
+    }
+
+    /// Checks that the blocks with the known priority operations are still in the chain.
+    ///
+    /// If some of them were replaced, unconfirmed operations from the replaced blocks are retracted
+    /// from the mempool, and the watcher rewinds to process the new chain. Confirmed operations
+    /// can't be retracted since they may be already executed, so their reorg is reported as
+    /// a critical error.
+    async fn handle_reorgs(&mut self) -> anyhow::Result<()> {
+        let mut reverted_blocks = Vec::new();
+        for (&block, &hash) in self.eth_state.block_hashes() {
+            if self.client.get_block_hash(block).await? != Some(hash) {
+                reverted_blocks.push(block);
+            }
+        }
+        let first_reverted_block = match reverted_blocks.first() {
+            Some(block) => *block,
+            None => return Ok(()),
+        };
+        metrics::increment_counter!("eth_watcher.reorgs");
+
+        let reorged_confirmed_ops: Vec<_> = self
+            .eth_state
+            .priority_queue()
+            .iter()
+            .filter(|(_, op)| reverted_blocks.contains(&op.as_ref().eth_block))
+            .map(|(serial_id, _)| *serial_id)
+            .sorted()
+            .collect();
+        if reorged_confirmed_ops.is_empty() {
+            vlog::warn!(
+                "Ethereum chain reorg detected, blocks {:?} were replaced",
+                reverted_blocks
+            );
+        } else {
+            vlog::error!(
+                "Ethereum chain reorg deeper than {} confirmations replaced blocks {:?} \
+                 with the confirmed priority operations {:?}",
+                self.number_of_confirmations_for_event,
+                reverted_blocks,
+                reorged_confirmed_ops
+            );
+            metrics::counter!(
+                "eth_watcher.reorged_confirmed_priority_ops",
+                reorged_confirmed_ops.len() as u64
+            );
+        }
+
+        // All the blocks after the first replaced one belong to the new chain as well.
+        let retracted_ops = self
+            .eth_state
+            .unconfirmed_queue()
+            .iter()
+            .filter(|op| op.eth_block >= first_reverted_block)
+            .map(|op| op.serial_id)
+            .collect();
+        self.retract_priority_ops(retracted_ops).await?;
+        self.eth_state.revert_blocks(first_reverted_block);
+        Ok(())
+    }
+
+    /// Removes unconfirmed priority operations which are no longer in the chain from the mempool.
+    async fn retract_priority_ops(&mut self, serial_ids: Vec<SerialId>) -> anyhow::Result<()> {
+        if serial_ids.is_empty() {
+            return Ok(());
+        }
+        vlog::warn!(
+            "Retracting unconfirmed priority operations {:?} reorged out of the chain",
+            serial_ids
+        );
+
+        let (sender, receiver) = oneshot::channel();
+        self.mempool_tx_sender
+            .send(MempoolTransactionRequest::RetractPriorityOps(
+                serial_ids, sender,
+            ))
+            .await?;
+        receiver.await.expect("Mempool actor was dropped")?;
+        Ok(())
+    }
+
     async fn restore_state_from_eth(&mut self, last_ethereum_block: u64) -> anyhow::Result<()> {
         let new_state = self
             .update_eth_state(last_ethereum_block, PRIORITY_EXPIRATION)
@@ -232,6 +387,12 @@ impl<W: EthClient> EthWatch<W> {
                 BlockNumber::Number(new_block_with_accepted_events.into()),
             )
             .await?;
+        let block_hashes = self
+            .get_block_hashes(
+                &[unconfirmed_queue.as_slice(), priority_queue.as_slice()].concat(),
+                current_ethereum_block,
+            )
+            .await?;
         let priority_queue_map: HashMap<u64, _> = priority_queue
             .iter()
             .cloned()
@@ -295,6 +456,7 @@ impl<W: EthClient> EthWatch<W> {
             priority_queue_map,
             new_tokens,
             new_register_nft_factory_events,
+            block_hashes,
         );
         Ok(state)
     }
@@ -332,6 +494,7 @@ impl<W: EthClient> EthWatch<W> {
     async fn poll_eth_node(&mut self) -> anyhow::Result<()> {
         let start = Instant::now();
         let last_block_number = self.client.block_number().await?;
+        self.handle_reorgs().await?;
 
         if last_block_number > self.eth_state.last_ethereum_block() {
             self.process_new_blocks(last_block_number).await?;
@@ -406,6 +569,8 @@ impl<W: EthClient> EthWatch<W> {
         while let Some(request) = eth_watch_req.next().await {
             match request {
                 EthWatchRequest::PollETHNode => {
+                    // The notifications received from now on may be not covered by this poll.
+                    self.pending_poll.start();
                     if !self.polling_allowed() {
                         // Polling is currently disabled, skip it.
                         continue;
@@ -449,11 +614,45 @@ impl<W: EthClient> EthWatch<W> {
     }
 }
 
+/// Runs the `PollTrigger` in a separate task, which requests the watcher to poll L1
+/// by sending the request created by `poll_request`.
+/// The poll is not requested if the previous request is not started yet, since the polls
+/// fetch the events for the whole block range since the last processed block anyway.
+pub fn run_poll_trigger<S, R>(
+    mut poll_trigger: PollTrigger,
+    subscriber: S,
+    pending_poll: PendingPoll,
+    mut poll_sender: mpsc::Sender<R>,
+    poll_request: impl Fn() -> R + Send + 'static,
+) -> JoinHandle<()>
+where
+    S: EthSubscriber + Send + Sync + 'static,
+    R: Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let reason = poll_trigger.tick(&subscriber).await;
+            if !pending_poll.request() {
+                vlog::debug!(
+                    "Poll of the Ethereum node is already requested: {:?}",
+                    reason
+                );
+                continue;
+            }
+            vlog::debug!("Polling the Ethereum node: {:?}", reason);
+            if poll_sender.send(poll_request()).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
 pub async fn start_eth_watch(
     eth_req_sender: mpsc::Sender<EthWatchRequest>,
     eth_req_receiver: mpsc::Receiver<EthWatchRequest>,
     eth_gateway: EthereumGateway,
     contract_config: &ContractsConfig,
+    eth_client_config: &ETHClientConfig,
     eth_watcher_config: &ETHWatchConfig,
     mempool_req_sender: mpsc::Sender<MempoolTransactionRequest>,
 ) -> JoinHandle<()> {
@@ -461,29 +660,26 @@ pub async fn start_eth_watch(
         eth_gateway,
         contract_config.contract_addr,
         contract_config.governance_addr,
+        eth_client_config.web3_ws_url.clone(),
     );
 
     let mut eth_watch = EthWatch::new(
-        eth_client,
+        eth_client.clone(),
         mempool_req_sender,
         eth_watcher_config.confirmations_for_eth_event,
     );
 
     eth_watch.restore_from_eth_using_latest_block_number().await;
 
+    let pending_poll = eth_watch.pending_poll();
     tokio::spawn(eth_watch.run(eth_req_receiver));
 
-    let poll_interval = eth_watcher_config.poll_interval();
-    tokio::spawn(async move {
-        let mut timer = time::interval(poll_interval);
-
-        loop {
-            timer.tick().await;
-            eth_req_sender
-                .clone()
-                .send(EthWatchRequest::PollETHNode)
-                .await
-                .expect("ETH watch receiver dropped");
-        }
-    })
+    let poll_trigger = PollTrigger::new(eth_watcher_config.poll_interval());
+    run_poll_trigger(
+        poll_trigger,
+        eth_client,
+        pending_poll,
+        eth_req_sender,
+        || EthWatchRequest::PollETHNode,
+    )
 }
@@ -0,0 +1,227 @@
+//! Subscription-based ingestion of the L1 events.
+//!
+//! Instead of polling the Ethereum node on the interval, watchers may subscribe to the new blocks
+//! and the logs of the watched contracts via `eth_subscribe`. Every notification triggers the regular
+//! poll of the watcher, so the events are still fetched through `eth_getLogs` for the whole block range
+//! since the last processed block. Thus, the events emitted while the subscription was down are picked
+//! up by the first poll after the reconnect.
+//!
+//! If the subscription can't be established or gets lost, watchers fall back to polling on the interval
+//! and periodically try to resubscribe.
+//!
+//! Since every poll covers the whole block range, the notifications received while the previous poll
+//! is still pending don't trigger the new polls (see `PendingPoll`).
+
+// Built-in deps
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+// External uses
+use anyhow::format_err;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::time::{self, Instant, Interval};
+use web3::{
+    transports::WebSocket,
+    types::{Address, FilterBuilder},
+    Web3,
+};
+
+/// How long to wait before trying to resubscribe after the subscription failure.
+pub const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(30);
+
+/// If no notifications were received within this interval, the subscription is considered stale.
+/// New blocks are normally produced every ~15 seconds.
+pub const SUBSCRIPTION_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Notification received via the L1 subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub enum L1Event {
+    /// New block was added to the chain.
+    NewHead(u64),
+    /// One of the watched contracts emitted a log in the given block.
+    NewLog { block_number: u64 },
+}
+
+pub type L1EventStream = BoxStream<'static, anyhow::Result<L1Event>>;
+
+#[async_trait::async_trait]
+pub trait EthSubscriber {
+    /// Subscribes to the new blocks and the logs of the watched contracts.
+    /// Returns `None` if the subscriptions are not configured, so the events must be polled.
+    async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>>;
+}
+
+/// Subscriber to the L1 events over the WebSocket connection.
+#[derive(Debug, Clone)]
+pub struct EthWsSubscriber {
+    web3_ws_url: String,
+    contract_addresses: Vec<Address>,
+}
+
+impl EthWsSubscriber {
+    pub fn new(web3_ws_url: String, contract_addresses: Vec<Address>) -> Self {
+        Self {
+            web3_ws_url,
+            contract_addresses,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EthSubscriber for EthWsSubscriber {
+    async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>> {
+        let web3 = Web3::new(WebSocket::new(&self.web3_ws_url).await?);
+
+        let new_heads = web3.eth_subscribe().subscribe_new_heads().await?.map(
+            |header| -> anyhow::Result<L1Event> {
+                let number = header?
+                    .number
+                    .ok_or_else(|| format_err!("Received block header without number"))?;
+                Ok(L1Event::NewHead(number.as_u64()))
+            },
+        );
+
+        let filter = FilterBuilder::default()
+            .address(self.contract_addresses.clone())
+            .build();
+        let logs = web3.eth_subscribe().subscribe_logs(filter).await?.map(
+            |log| -> anyhow::Result<L1Event> {
+                let block_number = log?
+                    .block_number
+                    .ok_or_else(|| format_err!("Received log without block number"))?;
+                Ok(L1Event::NewLog {
+                    block_number: block_number.as_u64(),
+                })
+            },
+        );
+
+        Ok(Some(stream::select(new_heads, logs).boxed()))
+    }
+}
+
+/// Reason for the watcher to poll L1.
+#[derive(Debug, PartialEq)]
+pub enum PollReason {
+    /// The poll interval has passed (the subscription is not available).
+    Interval,
+    /// The subscription was (re)established. Events emitted before that should be fetched.
+    Subscribed,
+    /// The notification was received via the subscription.
+    Event(L1Event),
+    /// The subscription was lost, the watcher falls back to the interval polling.
+    SubscriptionLost,
+}
+
+/// Flag shared by the `PollTrigger` task and the watcher, showing whether the poll was requested
+/// but not started yet. Used to coalesce the poll requests.
+#[derive(Debug, Clone, Default)]
+pub struct PendingPoll(Arc<AtomicBool>);
+
+impl PendingPoll {
+    /// Marks the poll as requested. Returns `false` if the requested poll is not started yet,
+    /// so there is no need to request another one.
+    pub fn request(&self) -> bool {
+        !self.0.swap(true, Ordering::SeqCst)
+    }
+
+    /// Marks the requested poll as started, so the next notifications will request a new one.
+    pub fn start(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Decides when the watcher should poll L1: on every notification of the subscription if it's available,
+/// or on the interval otherwise.
+pub struct PollTrigger {
+    interval: Interval,
+    subscription: Option<L1EventStream>,
+    /// Whether the subscriptions are configured for the client.
+    subscriptions_enabled: bool,
+    /// The subscription is considered stale if there are no notifications until this moment.
+    subscription_deadline: Instant,
+    next_subscription_attempt: Instant,
+    resubscribe_delay: Duration,
+}
+
+impl PollTrigger {
+    pub fn new(poll_interval: Duration) -> Self {
+        Self {
+            interval: time::interval(poll_interval),
+            subscription: None,
+            subscriptions_enabled: true,
+            subscription_deadline: Instant::now(),
+            next_subscription_attempt: Instant::now(),
+            resubscribe_delay: RESUBSCRIBE_DELAY,
+        }
+    }
+
+    /// Sets the delay between the attempts to resubscribe after the subscription failure.
+    pub fn with_resubscribe_delay(mut self, resubscribe_delay: Duration) -> Self {
+        self.resubscribe_delay = resubscribe_delay;
+        self
+    }
+
+    /// Waits until the watcher should poll L1.
+    pub async fn tick<S>(&mut self, subscriber: &S) -> PollReason
+    where
+        S: EthSubscriber + ?Sized,
+    {
+        if self.subscription.is_none()
+            && self.subscriptions_enabled
+            && Instant::now() >= self.next_subscription_attempt
+        {
+            let subscription = subscriber.subscribe().await;
+            self.next_subscription_attempt = Instant::now() + self.resubscribe_delay;
+            match subscription {
+                Ok(Some(subscription)) => {
+                    vlog::info!("Subscribed to the L1 events");
+                    self.subscription = Some(subscription);
+                    self.subscription_deadline = Instant::now() + SUBSCRIPTION_IDLE_TIMEOUT;
+                    return PollReason::Subscribed;
+                }
+                Ok(None) => {
+                    self.subscriptions_enabled = false;
+                }
+                Err(err) => {
+                    vlog::warn!(
+                        "Failed to subscribe to the L1 events: {}. Falling back to polling",
+                        err
+                    );
+                }
+            }
+        }
+
+        let subscription = match self.subscription.as_mut() {
+            Some(subscription) => subscription,
+            None => {
+                self.interval.tick().await;
+                return PollReason::Interval;
+            }
+        };
+
+        let error = match time::timeout_at(self.subscription_deadline, subscription.next()).await {
+            Ok(Some(Ok(event))) => {
+                self.subscription_deadline = Instant::now() + SUBSCRIPTION_IDLE_TIMEOUT;
+                return PollReason::Event(event);
+            }
+            Ok(Some(Err(err))) => err,
+            Ok(None) => format_err!("subscription stream was closed"),
+            Err(_) => format_err!(
+                "no notifications were received for {:?}",
+                SUBSCRIPTION_IDLE_TIMEOUT
+            ),
+        };
+        vlog::warn!(
+            "L1 subscription was lost: {}. Falling back to polling",
+            error
+        );
+        metrics::increment_counter!("eth_watcher.subscription_lost");
+        self.subscription = None;
+        PollReason::SubscriptionLost
+    }
+}
@@ -1,6 +1,7 @@
 use std::cmp::max;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use web3::types::{Address, BlockNumber};
 
@@ -15,11 +16,18 @@ use tokio::sync::RwLock;
 use zksync_mempool::MempoolTransactionRequest;
 
 use super::is_missing_priority_op_error;
-use crate::eth_watch::{client::EthClient, EthWatch};
+use crate::eth_watch::{
+    client::EthClient,
+    run_poll_trigger,
+    subscription::{EthSubscriber, L1Event, L1EventStream, PendingPoll, PollReason, PollTrigger},
+    EthWatch, EthWatchRequest,
+};
 
 struct FakeEthClientData {
     priority_ops: HashMap<u64, Vec<PriorityOp>>,
     last_block_number: u64,
+    /// First blocks of the simulated chain forks.
+    forks: Vec<u64>,
 }
 
 impl FakeEthClientData {
@@ -27,9 +35,20 @@ impl FakeEthClientData {
         Self {
             priority_ops: Default::default(),
             last_block_number: 0,
+            forks: Vec::new(),
         }
     }
 
+    fn block_hash(&self, block_number: u64) -> H256 {
+        // Every fork changes the hashes of all the blocks starting from the fork point.
+        let fork_id = self
+            .forks
+            .iter()
+            .filter(|fork_block| **fork_block <= block_number)
+            .count() as u64;
+        H256::from_low_u64_be(block_number + (fork_id << 32))
+    }
+
     fn add_operations(&mut self, ops: &[PriorityOp]) {
         for op in ops {
             self.last_block_number = max(op.eth_block, self.last_block_number);
@@ -44,15 +63,29 @@ impl FakeEthClientData {
 #[derive(Clone)]
 struct FakeEthClient {
     inner: Arc<RwLock<FakeEthClientData>>,
+    /// Streams returned by the subsequent subscription attempts.
+    /// Subscription fails if there are no streams left.
+    subscriptions: Arc<Mutex<VecDeque<L1EventStream>>>,
 }
 
 impl FakeEthClient {
     fn new() -> Self {
         Self {
             inner: Arc::new(RwLock::new(FakeEthClientData::new())),
+            subscriptions: Default::default(),
         }
     }
 
+    /// Makes the next subscription attempt succeed. Returns the sender of the events for the subscription.
+    fn add_subscription(&self) -> mpsc::UnboundedSender<anyhow::Result<L1Event>> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .push_back(receiver.boxed());
+        sender
+    }
+
     async fn add_operations(&mut self, ops: &[PriorityOp]) {
         self.inner.write().await.add_operations(ops);
     }
@@ -70,6 +103,27 @@ impl FakeEthClient {
         let mut inner = self.inner.write().await;
         inner.last_block_number = block_number;
     }
+
+    /// Simulates the chain reorg: replaces all the blocks starting from `first_block`,
+    /// so the operations from these blocks are replaced with the `new_ops`.
+    async fn reorg(&mut self, first_block: u64, new_ops: &[PriorityOp]) {
+        let mut inner = self.inner.write().await;
+        inner.forks.push(first_block);
+        inner.priority_ops.retain(|block, _| *block < first_block);
+        inner.add_operations(new_ops);
+    }
+}
+
+#[async_trait::async_trait]
+impl EthSubscriber for FakeEthClient {
+    async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>> {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .pop_front()
+            .map(Some)
+            .ok_or_else(|| anyhow::format_err!("Connection refused"))
+    }
 }
 
 #[async_trait::async_trait]
@@ -111,6 +165,15 @@ impl EthClient for FakeEthClient {
         Ok(self.inner.read().await.last_block_number)
     }
 
+    async fn get_block_hash(&self, block_number: u64) -> anyhow::Result<Option<H256>> {
+        let inner = self.inner.read().await;
+        if block_number > inner.last_block_number {
+            Ok(None)
+        } else {
+            Ok(Some(inner.block_hash(block_number)))
+        }
+    }
+
     async fn get_auth_fact(
         &self,
         _address: Address,
@@ -151,7 +214,17 @@ async fn fake_mempool(
                 }
                 channel.send(Ok(())).unwrap_or_default()
             }
+            MempoolTransactionRequest::RetractPriorityOps(serial_ids, channel) => {
+                let mut lock = data.write().await;
+                for serial_id in serial_ids {
+                    if let Some((_, false)) = lock.get(&serial_id) {
+                        lock.remove(&serial_id);
+                    }
+                }
+                channel.send(Ok(())).unwrap_or_default()
+            }
             MempoolTransactionRequest::NewTxsBatch(_, _, _) => unreachable!(),
+            MempoolTransactionRequest::CancelTx(_, _, _) => unreachable!(),
         }
     }
 }
@@ -546,3 +619,228 @@ async fn test_serial_id_gaps() {
     assert_eq!(watcher.eth_state.last_ethereum_block_backup(), 0);
     assert_eq!(watcher.eth_state.last_ethereum_block(), 3);
 }
+
+fn deposit_op(serial_id: SerialId, eth_block: u64) -> PriorityOp {
+    PriorityOp {
+        serial_id,
+        data: ZkSyncPriorityOp::Deposit(Deposit {
+            from: Default::default(),
+            token: TokenId(0),
+            amount: Default::default(),
+            to: [2u8; 20].into(),
+        }),
+        deadline_block: 0,
+        eth_hash: H256::from_low_u64_be(serial_id),
+        eth_block,
+        eth_block_index: Some(1),
+    }
+}
+
+/// Checks that the unconfirmed operations reorged out of the chain are retracted from the mempool
+/// and are accepted again once included into the new chain.
+#[tokio::test]
+async fn test_reorg_of_unconfirmed_ops() {
+    let (sender, receiver) = mpsc::channel(10);
+    let data = Arc::new(RwLock::new(HashMap::new()));
+    tokio::spawn(fake_mempool(receiver, data.clone()));
+    let mut client = FakeEthClient::new();
+    client
+        .add_operations(&[deposit_op(0, 2), deposit_op(1, 8)])
+        .await;
+    client.set_last_block_number(10).await;
+
+    let mut watcher = EthWatch::new(client.clone(), sender, 5);
+    watcher.poll_eth_node().await.unwrap();
+    assert_eq!(watcher.eth_state.priority_queue().len(), 1);
+    assert_eq!(watcher.eth_state.unconfirmed_queue().len(), 1);
+    assert!(!data.read().await.get(&1).unwrap().1);
+
+    // The block with the unconfirmed operation is replaced.
+    client.reorg(7, &[]).await;
+    client.set_last_block_number(11).await;
+    watcher.poll_eth_node().await.unwrap();
+    assert_eq!(watcher.eth_state.last_ethereum_block(), 11);
+    assert!(watcher.eth_state.unconfirmed_queue().is_empty());
+    assert_eq!(watcher.eth_state.priority_queue().len(), 1);
+    {
+        let reader = data.read().await;
+        assert!(reader.get(&1).is_none());
+        assert!(reader.get(&0).unwrap().1);
+    }
+
+    // The operation is included into the new chain.
+    client.add_operations(&[deposit_op(1, 12)]).await;
+    watcher.poll_eth_node().await.unwrap();
+    assert_eq!(watcher.eth_state.unconfirmed_queue().len(), 1);
+    let reader = data.read().await;
+    let (op, confirmed) = reader.get(&1).unwrap();
+    assert_eq!(op.eth_block, 12);
+    assert!(!confirmed);
+}
+
+/// Checks that the reorg of the confirmed operations doesn't retract them,
+/// and the watcher picks up the operations from the new chain.
+#[tokio::test]
+async fn test_reorg_of_confirmed_ops() {
+    let (sender, receiver) = mpsc::channel(10);
+    let data = Arc::new(RwLock::new(HashMap::new()));
+    tokio::spawn(fake_mempool(receiver, data.clone()));
+    let mut client = FakeEthClient::new();
+    client.add_operations(&[deposit_op(0, 2)]).await;
+    client.set_last_block_number(4).await;
+
+    let mut watcher = create_watcher(client.clone(), sender);
+    watcher.poll_eth_node().await.unwrap();
+    assert_eq!(watcher.eth_state.priority_queue().len(), 1);
+    assert!(data.read().await.get(&0).unwrap().1);
+
+    // The operation is moved to the next block by the reorg.
+    client.reorg(2, &[deposit_op(0, 3)]).await;
+    client.set_last_block_number(5).await;
+    watcher.poll_eth_node().await.unwrap();
+    assert_eq!(watcher.eth_state.last_ethereum_block(), 5);
+
+    let op = watcher.eth_state.priority_queue().get(&0).unwrap().as_ref();
+    assert_eq!(op.eth_block, 3);
+    let reader = data.read().await;
+    let (op, confirmed) = reader.get(&0).unwrap();
+    assert_eq!(op.eth_block, 3);
+    assert!(confirmed);
+}
+
+/// Checks that the watcher polls L1 upon the subscription notifications,
+/// and falls back to the interval polling once the subscription is lost.
+#[tokio::test]
+async fn test_poll_trigger() {
+    let client = FakeEthClient::new();
+    let mut poll_trigger =
+        PollTrigger::new(Duration::from_millis(10)).with_resubscribe_delay(Duration::from_secs(0));
+
+    let events = client.add_subscription();
+    assert_eq!(poll_trigger.tick(&client).await, PollReason::Subscribed);
+    events.unbounded_send(Ok(L1Event::NewHead(1))).unwrap();
+    events
+        .unbounded_send(Ok(L1Event::NewLog { block_number: 1 }))
+        .unwrap();
+    assert_eq!(
+        poll_trigger.tick(&client).await,
+        PollReason::Event(L1Event::NewHead(1))
+    );
+    assert_eq!(
+        poll_trigger.tick(&client).await,
+        PollReason::Event(L1Event::NewLog { block_number: 1 })
+    );
+
+    // Connection is dropped, and the node doesn't accept the new ones.
+    drop(events);
+    assert_eq!(
+        poll_trigger.tick(&client).await,
+        PollReason::SubscriptionLost
+    );
+    assert_eq!(poll_trigger.tick(&client).await, PollReason::Interval);
+
+    // Connection is restored.
+    let events = client.add_subscription();
+    assert_eq!(poll_trigger.tick(&client).await, PollReason::Subscribed);
+
+    // Errors reported by the stream also break the subscription.
+    events
+        .unbounded_send(Err(anyhow::format_err!("Invalid notification")))
+        .unwrap();
+    assert_eq!(
+        poll_trigger.tick(&client).await,
+        PollReason::SubscriptionLost
+    );
+}
+
+/// Checks that the watcher fetches the operations upon the subscription notifications,
+/// and the operations missed by the subscription are fetched once it's lost.
+#[tokio::test]
+async fn test_subscription_driven_polling() {
+    async fn wait_for_op(
+        data: &RwLock<HashMap<SerialId, (PriorityOp, bool)>>,
+        serial_id: SerialId,
+    ) {
+        for _ in 0..100 {
+            if data.read().await.contains_key(&serial_id) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Operation {} was not received by the mempool", serial_id);
+    }
+
+    let (sender, receiver) = mpsc::channel(10);
+    let data = Arc::new(RwLock::new(HashMap::new()));
+    tokio::spawn(fake_mempool(receiver, data.clone()));
+    let mut client = FakeEthClient::new();
+    client.set_last_block_number(4).await;
+    let events = client.add_subscription();
+
+    // The interval is long enough, so the watcher polls L1 only upon the subscription events.
+    let (watch_sender, watch_receiver) = mpsc::channel(10);
+    let poll_trigger = PollTrigger::new(Duration::from_secs(3600));
+    let watcher = create_watcher(client.clone(), sender);
+    let pending_poll = watcher.pending_poll();
+    tokio::spawn(watcher.run(watch_receiver));
+    run_poll_trigger(
+        poll_trigger,
+        client.clone(),
+        pending_poll,
+        watch_sender,
+        || EthWatchRequest::PollETHNode,
+    );
+
+    client.add_operations(&[deposit_op(0, 5)]).await;
+    client.set_last_block_number(6).await;
+    events.unbounded_send(Ok(L1Event::NewHead(6))).unwrap();
+    wait_for_op(&data, 0).await;
+
+    // The operation is emitted, but the notification is lost together with the connection.
+    client.add_operations(&[deposit_op(1, 7)]).await;
+    client.set_last_block_number(8).await;
+    drop(events);
+    wait_for_op(&data, 1).await;
+    assert!(data.read().await.get(&1).unwrap().1);
+}
+
+/// Checks that the notifications received while the poll is requested but not started yet
+/// don't produce the new poll requests.
+#[tokio::test]
+async fn test_poll_requests_coalescing() {
+    let client = FakeEthClient::new();
+    let events = client.add_subscription();
+    let pending_poll = PendingPoll::default();
+    let (sender, mut receiver) = mpsc::channel(10);
+    run_poll_trigger(
+        PollTrigger::new(Duration::from_secs(3600)),
+        client,
+        pending_poll.clone(),
+        sender,
+        || EthWatchRequest::PollETHNode,
+    );
+
+    // The poll is requested once the subscription is established.
+    assert!(matches!(
+        receiver.next().await,
+        Some(EthWatchRequest::PollETHNode)
+    ));
+
+    // The poll is not started yet, so it covers the new notifications.
+    events.unbounded_send(Ok(L1Event::NewHead(1))).unwrap();
+    events
+        .unbounded_send(Ok(L1Event::NewLog { block_number: 1 }))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(receiver.try_next().is_err());
+
+    // Once the poll is started, the next notification requests a new one.
+    pending_poll.start();
+    events.unbounded_send(Ok(L1Event::NewHead(2))).unwrap();
+    assert!(matches!(
+        receiver.next().await,
+        Some(EthWatchRequest::PollETHNode)
+    ));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(receiver.try_next().is_err());
+}
@@ -11,6 +11,7 @@ use tokio::task::JoinHandle;
 use zksync_config::{ChainConfig, ZkSyncConfig};
 use zksync_eth_client::EthereumGateway;
 use zksync_mempool::{run_mempool_block_handler, run_mempool_tx_handler};
+use zksync_state::tree_backend::AccountTreeBackend;
 use zksync_storage::ConnectionPool;
 use zksync_types::{tokens::get_genesis_token_list, Token, TokenId, TokenKind};
 
@@ -108,6 +109,7 @@ pub async fn run_core(
         connection_pool.clone(),
         mempool_tx_request_receiver,
         config.chain.state_keeper.block_chunk_sizes.clone(),
+        config.chain.mempool.clone(),
     );
 
     // Run health check api for core
@@ -124,6 +126,7 @@ pub async fn run_core(
         eth_watch_req_receiver,
         eth_gateway.clone(),
         &config.contracts,
+        &config.eth_client,
         &config.eth_watch,
         mempool_tx_request_sender.clone(),
     )
@@ -133,8 +136,12 @@ pub async fn run_core(
     let mut storage_processor = connection_pool.access_storage().await?;
 
     // Start state keeper and root hash calculator.
+    let account_tree =
+        AccountTreeBackend::open(config.chain.state_keeper.account_tree_path.as_deref())
+            .expect("Unable to open the account tree storage");
     let state_keeper_init = ZkSyncStateInitParams::restore_from_db(
         &mut storage_processor,
+        account_tree,
         config.chain.state_keeper.fee_account_addr,
         &config.chain.state_keeper.block_chunk_sizes,
     )
@@ -172,6 +179,7 @@ pub async fn run_core(
         connection_pool.clone(),
         mempool_block_request_receiver,
         config.chain.state_keeper.block_chunk_sizes.clone(),
+        config.chain.state_keeper.transactions_ordering,
     );
 
     // Start token handler.
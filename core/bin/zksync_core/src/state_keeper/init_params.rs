@@ -1,7 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 // External uses
 // Workspace uses
-use zksync_state::state::ZkSyncState;
+use zksync_state::{state::ZkSyncState, tree_backend::AccountTreeBackend};
 use zksync_types::{
     block::{IncompleteBlock, PendingBlock as SendablePendingBlock},
     AccountId, AccountTree, Address, BlockNumber, TokenId, NFT,
@@ -59,12 +59,17 @@ impl ZkSyncStateInitParams {
         }
     }
 
+    /// Restores the state from the database.
+    /// `account_tree` is the tree to restore the state into: if it's persistent, only the changes
+    /// made since it was flushed the last time are loaded.
     pub async fn restore_from_db(
         storage: &mut zksync_storage::StorageProcessor<'_>,
+        account_tree: AccountTreeBackend,
         fee_account_addr: Address,
         available_chunk_sizes: &[usize],
     ) -> Self {
-        let (last_block_number, tree, acc_id_by_addr) = Self::load_account_tree(storage).await;
+        let (last_block_number, tree, acc_id_by_addr) =
+            Self::load_account_tree(storage, account_tree).await;
 
         let unprocessed_priority_op = Self::unprocessed_priority_op_id(storage).await;
         let nfts = Self::load_nft_tokens(storage, last_block_number).await;
@@ -80,7 +85,7 @@ impl ZkSyncStateInitParams {
             Self::load_reverted_blocks(storage, fee_account_id, available_chunk_sizes).await;
 
         let init_params = Self {
-            state: ZkSyncState::new(tree, acc_id_by_addr, nfts),
+            state: ZkSyncState::with_tree_backend(tree, acc_id_by_addr, nfts),
             last_block_number,
             unprocessed_priority_op,
             pending_block,
@@ -100,8 +105,10 @@ impl ZkSyncStateInitParams {
 
     async fn load_account_tree(
         storage: &mut zksync_storage::StorageProcessor<'_>,
-    ) -> (BlockNumber, AccountTree, HashMap<Address, AccountId>) {
-        let mut restored_tree = RestoredTree::new(StateRestoreStorage::new(storage));
+        account_tree: AccountTreeBackend,
+    ) -> (BlockNumber, AccountTreeBackend, HashMap<Address, AccountId>) {
+        let mut restored_tree =
+            RestoredTree::with_tree(StateRestoreStorage::new(storage), account_tree);
         let last_block_number = restored_tree.restore().await;
         (
             last_block_number,
@@ -93,6 +93,10 @@ impl ZkSyncStateKeeper {
         //    separately below.
         // 2. For root hash calculator (`rhc_state`). It will require the state at *last finished block*, so it can keep
         //    working on calculating root hashes for incomplete blocks that we had before the restart.
+        // If the account tree is persistent, both copies share its storage. Only the root hash calculator
+        // writes to it once the block is finished, and the state keeper keeps its changes in memory on top
+        // of it: the storage never contains changes the state keeper is not aware of. The state keeper seals
+        // its changes with the block numbers, so they are dropped once the block is written to the storage.
         let mut sk_state = initial_state.state.clone();
         let rhc_state = initial_state.state.clone();
 
@@ -113,6 +117,7 @@ impl ZkSyncStateKeeper {
             last_block = job.block;
 
             sk_state.apply_account_updates(job.updates.clone());
+            sk_state.seal_tree(job.block);
         }
 
         // Create and fill the queue for root hash calculator.
@@ -763,6 +768,8 @@ impl ZkSyncStateKeeper {
         // pending block and "new" pending block. Actions "create block to be sealed" and "update pending block"
         // should be spearated.
         let current_block = self.pending_block.number;
+        // All the updates of the block are applied to the state at this point.
+        self.state.seal_tree(current_block);
         let next_unprocessed_priority_op = self.pending_block.unprocessed_priority_op_current;
         let new_pending_block = PendingBlock::new(
             self.pending_block.number + 1,
@@ -77,6 +77,8 @@ impl RootHashCalculator {
         self.state.apply_account_updates(job.updates);
 
         let root_hash = self.state.root_hash();
+        // The block state is final now, so it can be written to the persistent tree (if it's used).
+        self.state.flush_tree(job.block);
 
         vlog::info!("Root hash for block #{} is calculated", job.block);
 
@@ -122,9 +122,19 @@ impl StateRestoreDb for MockStateRestoreStorage {
     async fn store_account_tree_cache(
         &mut self,
         block: BlockNumber,
+        previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
         account_tree_cache: SparseMerkleTreeSerializableCacheBN256,
     ) {
-        self.save_cache(block, account_tree_cache);
+        // Mimic the actual storage, which only stores the diff relative to the previous cache.
+        let cache = match previous {
+            Some((_, mut cache)) => {
+                let diff = cache.diff(&account_tree_cache);
+                cache.apply_diff(diff);
+                cache
+            }
+            None => account_tree_cache,
+        };
+        self.save_cache(block, cache);
     }
 
     async fn load_block_hash_from_db(&mut self, block: BlockNumber) -> Fr {
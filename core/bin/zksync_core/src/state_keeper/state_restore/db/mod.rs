@@ -43,9 +43,12 @@ pub trait StateRestoreDb {
     ) -> SparseMerkleTreeSerializableCacheBN256;
 
     /// Saves the account tree cache to the database.
+    /// `previous` is the cache the tree was initialized with, if any: it allows storing
+    /// only the changes made since then.
     async fn store_account_tree_cache(
         &mut self,
         block: BlockNumber,
+        previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
         account_tree_cache: SparseMerkleTreeSerializableCacheBN256,
     );
 
@@ -38,7 +38,7 @@ impl<'a, 'b> StateRestoreDb for StateRestoreStorage<'a, 'b> {
         self.storage
             .chain()
             .tree_cache_schema_bincode()
-            .get_last_block_with_account_tree_cache()
+            .get_last_block_with_account_tree_cache_or_diff()
             .await
             .expect("Can't load the last block with cache")
     }
@@ -85,28 +85,33 @@ impl<'a, 'b> StateRestoreDb for StateRestoreStorage<'a, 'b> {
         &mut self,
         block: BlockNumber,
     ) -> SparseMerkleTreeSerializableCacheBN256 {
-        let cache = self.storage
+        self.storage
             .chain()
             .tree_cache_schema_bincode()
-            .get_account_tree_cache_block(block)
+            .load_account_tree_cache_incremental(block)
             .await
             .expect("Can't load account tree cache")
+            .filter(|(cache_block, _)| *cache_block == block)
             .unwrap_or_else(|| {
                 panic!("Account tree cache was requested for block {}, for which it was checked to exist", block)
-            });
-        SparseMerkleTreeSerializableCacheBN256::decode_bincode(&cache)
+            })
+            .1
     }
 
     async fn store_account_tree_cache(
         &mut self,
         block: BlockNumber,
+        previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
         account_tree_cache: SparseMerkleTreeSerializableCacheBN256,
     ) {
-        let encoded_tree_cache = account_tree_cache.encode_bincode();
         self.storage
             .chain()
             .tree_cache_schema_bincode()
-            .store_account_tree_cache(block, encoded_tree_cache)
+            .store_account_tree_cache_incremental(
+                block,
+                previous.as_ref().map(|(block, cache)| (*block, cache)),
+                &account_tree_cache,
+            )
             .await
             .expect("Unable to store account tree cache in the database");
     }
@@ -91,6 +91,16 @@ async fn cached_state_restore_previous_block() {
 
     // Check that root hash is actually restored.
     assert_eq!(restorer.tree.root_hash(), state_generator.tree.root_hash());
+
+    // Check that cache for the last block is built on top of the previous one.
+    assert_eq!(
+        restorer.storage.load_last_cached_block().await,
+        Some(LAST_BLOCK)
+    );
+    let cache = restorer.storage.load_account_tree_cache(LAST_BLOCK).await;
+    let mut tree_from_cache = StateGenerator::empty_tree();
+    tree_from_cache.set_internals(cache);
+    assert_eq!(tree_from_cache.root_hash(), restorer.tree.root_hash())
 }
 
 /// Checks that if the last block hash is incorrect, restoring panics.
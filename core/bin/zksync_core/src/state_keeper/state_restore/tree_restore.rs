@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 // External uses
 // Workspace uses
-use zksync_types::{Account, AccountId, AccountTree, AccountUpdates, Address, BlockNumber};
+use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
+use zksync_state::tree_backend::AccountTreeBackend;
+use zksync_types::{Account, AccountId, AccountUpdates, Address, BlockNumber};
 // Local uses
 use super::db::StateRestoreDb;
 
@@ -9,15 +11,19 @@ use super::db::StateRestoreDb;
 /// using the database.
 ///
 /// By default, it will try to load the last tree cache and update from there by loading the state difference.
+/// The cache itself is restored from the nearest full cache (checkpoint) and the cache diffs stored after it.
 /// If there is no cache, tree will be recalculated from scratch.
 ///
+/// The persistent tree doesn't use the cache: it's updated starting from the state it was flushed
+/// with, and is rebuilt from scratch if that state can't be used.
+///
 /// If the tree root hash will not match the hash from the database, `RestoredTree` will find the block
 /// at which hashes diverged and panic with the corresponding message containing the block number.
 #[derive(Debug)]
 pub(crate) struct RestoredTree<S: StateRestoreDb> {
     pub(crate) storage: S,
 
-    pub(crate) tree: AccountTree,
+    pub(crate) tree: AccountTreeBackend,
     pub(crate) acc_id_by_addr: HashMap<Address, AccountId>,
 }
 
@@ -26,10 +32,16 @@ where
     S: StateRestoreDb,
 {
     pub(crate) fn new(storage: S) -> Self {
+        Self::with_tree(storage, AccountTreeBackend::default())
+    }
+
+    /// Creates the restorer on top of the provided tree, which may be either the in-memory
+    /// or the persistent one.
+    pub(crate) fn with_tree(storage: S, tree: AccountTreeBackend) -> Self {
         Self {
             storage,
 
-            tree: AccountTree::new(zksync_crypto::params::account_tree_depth()),
+            tree,
             acc_id_by_addr: HashMap::default(),
         }
     }
@@ -40,8 +52,13 @@ where
     pub(crate) async fn restore(&mut self) -> BlockNumber {
         let last_block = self.storage.load_last_committed_block().await;
 
-        if let Some(cached_block) = self.storage.load_last_cached_block().await {
-            self.init_tree_with_cache(cached_block).await;
+        let mut initial_cache = None;
+        let persistent = self.tree.as_in_memory().is_none();
+        if persistent {
+            self.init_persistent_tree(last_block).await;
+        } else if let Some(cached_block) = self.storage.load_last_cached_block().await {
+            let cache = self.init_tree_with_cache(cached_block).await;
+            initial_cache = Some((cached_block, cache));
             self.assert_calculated_root(
                 "Root hash from the cached tree doesn't match the root hash from the database",
                 cached_block,
@@ -72,22 +89,89 @@ where
         }
 
         // At this point tree is restored and is checked to be correct.
-        // Store the cache to speed up the future restarts.
-        self.storage
-            .store_account_tree_cache(last_block, self.tree.get_internals())
-            .await;
+        // Store the tree or its cache to speed up the future restarts, unless it's already there.
+        let initial_cache_block = initial_cache.as_ref().map(|(block, _)| *block);
+        if persistent {
+            self.tree.flush(last_block);
+        } else if initial_cache_block != Some(last_block) {
+            let tree_cache = self
+                .tree
+                .as_in_memory()
+                .expect("Cache is only stored for the in-memory tree")
+                .get_internals();
+            self.storage
+                .store_account_tree_cache(last_block, initial_cache, tree_cache)
+                .await;
+        }
 
         last_block
     }
 
-    async fn init_tree_with_cache(&mut self, cache_block: BlockNumber) {
+    /// Initializes the tree with the cache for the provided block.
+    /// Returns the loaded cache.
+    async fn init_tree_with_cache(
+        &mut self,
+        cache_block: BlockNumber,
+    ) -> SparseMerkleTreeSerializableCacheBN256 {
         let committed_state = self.storage.load_committed_state(cache_block).await;
         let cache = self.storage.load_account_tree_cache(cache_block).await;
 
         for (id, account) in committed_state {
             self.insert_account(id, account);
         }
-        self.tree.set_internals(cache);
+        if let AccountTreeBackend::InMemory(tree) = &mut self.tree {
+            tree.set_internals(cache.clone());
+        }
+        cache
+    }
+
+    /// Brings the persistent tree to the state of the last committed block.
+    /// Only the accounts changed since the last flush are loaded into the tree.
+    async fn init_persistent_tree(&mut self, last_block_number: BlockNumber) {
+        let committed_state = self.storage.load_committed_state(last_block_number).await;
+        self.acc_id_by_addr = committed_state
+            .iter()
+            .map(|(id, account)| (account.address, *id))
+            .collect();
+
+        match self.tree.flushed_block() {
+            Some(flushed_block) if flushed_block <= last_block_number => {
+                if flushed_block < last_block_number {
+                    if let Some(diff) = self
+                        .storage
+                        .load_state_diff(flushed_block, last_block_number)
+                        .await
+                    {
+                        self.apply_state_diff(last_block_number, diff).await;
+                    }
+                }
+                let root_hash_from_db = self
+                    .storage
+                    .load_block_hash_from_db(last_block_number)
+                    .await;
+                if self.tree.root_hash() == root_hash_from_db {
+                    return;
+                }
+                vlog::warn!(
+                    "Persistent account tree flushed at block {} doesn't match the database, rebuilding it",
+                    flushed_block
+                );
+            }
+            Some(flushed_block) => {
+                // The tree is ahead of the database, e.g. the blocks were reverted.
+                vlog::warn!(
+                    "Persistent account tree flushed at block {} is ahead of the last committed block {}, rebuilding it",
+                    flushed_block,
+                    last_block_number
+                );
+            }
+            None => {}
+        }
+
+        self.tree.clear();
+        for (id, account) in committed_state {
+            self.tree.insert(*id, account);
+        }
     }
 
     async fn init_tree_without_cache(&mut self, last_block_number: BlockNumber) {
@@ -110,7 +194,8 @@ where
     /// This function is very slow, but it's OK since the server can not start with an incorrect state anyway.
     async fn find_hash_mismatch_point(&mut self) -> ! {
         // Reset self state, we're starting from scratch.
-        self.tree = AccountTree::new(zksync_crypto::params::account_tree_depth());
+        // The in-memory tree is used, so the persistent one (if any) is left intact.
+        self.tree = AccountTreeBackend::default();
         self.acc_id_by_addr = HashMap::new();
 
         let (current_block, verified_state) = self.storage.load_verified_state().await;
@@ -3,7 +3,7 @@ use std::collections::VecDeque;
 use std::str::FromStr;
 // External uses
 use num::BigUint;
-use zksync_basic_types::{H256, U256};
+use zksync_basic_types::{Address, H256, U256};
 // Workspace uses
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_types::ethereum::{ETHOperation, EthOpId, InsertedOperationResponse};
@@ -48,6 +48,8 @@ pub(super) trait DatabaseInterface {
     ) -> anyhow::Result<()>;
 
     /// Saves a new unconfirmed operation to the database.
+    /// The nonce is taken from the sequence of the `sender` account (`None` stands for the main operator account).
+    #[allow(clippy::too_many_arguments)]
     async fn save_new_eth_tx(
         &self,
         connection: &mut StorageProcessor<'_>,
@@ -55,7 +57,9 @@ pub(super) trait DatabaseInterface {
         op: Option<(i64, AggregatedOperation)>,
         deadline_block: i64,
         used_gas_price: U256,
+        used_priority_fee: Option<U256>,
         raw_tx: Vec<u8>,
+        sender: Option<Address>,
     ) -> anyhow::Result<InsertedOperationResponse>;
 
     /// Adds a tx hash entry associated with some Ethereum operation to the database.
@@ -73,6 +77,42 @@ pub(super) trait DatabaseInterface {
         eth_op_id: EthOpId,
         new_deadline_block: i64,
         new_gas_value: U256,
+        new_priority_fee: Option<U256>,
+    ) -> anyhow::Result<()>;
+
+    /// Assigns a new nonce to the previously started Ethereum operation.
+    /// Hashes of the transactions sent with the old nonce are marked as superseded.
+    async fn reassign_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        new_nonce: U256,
+    ) -> anyhow::Result<()>;
+
+    /// Moves the previously started Ethereum operation to another operator account
+    /// with the new nonce. Hashes of the transactions sent by the old account are marked as superseded.
+    async fn reassign_sender(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        sender: Option<Address>,
+        new_nonce: U256,
+    ) -> anyhow::Result<()>;
+
+    /// Obtains the next nonce from the nonce sequence of the `sender` account.
+    async fn get_next_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+    ) -> anyhow::Result<U256>;
+
+    /// Makes sure that nonces lower than `min_nonce` are never assigned to the new operations
+    /// sent by the `sender` account.
+    async fn advance_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+        min_nonce: U256,
     ) -> anyhow::Result<()>;
 
     /// Marks an operation as completed in the database.
@@ -184,7 +224,9 @@ impl DatabaseInterface for Database {
         op: Option<(i64, AggregatedOperation)>,
         deadline_block: i64,
         used_gas_price: U256,
+        used_priority_fee: Option<U256>,
         raw_tx: Vec<u8>,
+        sender: Option<Address>,
     ) -> anyhow::Result<InsertedOperationResponse> {
         let result = connection
             .ethereum_schema()
@@ -193,7 +235,9 @@ impl DatabaseInterface for Database {
                 op,
                 deadline_block,
                 BigUint::from_str(&used_gas_price.to_string()).unwrap(),
+                used_priority_fee.map(|fee| BigUint::from_str(&fee.to_string()).unwrap()),
                 raw_tx,
+                sender,
             )
             .await?;
 
@@ -218,6 +262,7 @@ impl DatabaseInterface for Database {
         eth_op_id: EthOpId,
         new_deadline_block: i64,
         new_gas_value: U256,
+        new_priority_fee: Option<U256>,
     ) -> anyhow::Result<()> {
         Ok(connection
             .ethereum_schema()
@@ -225,10 +270,77 @@ impl DatabaseInterface for Database {
                 eth_op_id,
                 new_deadline_block,
                 BigUint::from_str(&new_gas_value.to_string()).unwrap(),
+                new_priority_fee.map(|fee| BigUint::from_str(&fee.to_string()).unwrap()),
             )
             .await?)
     }
 
+    async fn reassign_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        new_nonce: U256,
+    ) -> anyhow::Result<()> {
+        Ok(connection
+            .ethereum_schema()
+            .reassign_nonce(eth_op_id, new_nonce.as_u64() as i64)
+            .await?)
+    }
+
+    async fn reassign_sender(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        sender: Option<Address>,
+        new_nonce: U256,
+    ) -> anyhow::Result<()> {
+        Ok(connection
+            .ethereum_schema()
+            .reassign_sender(eth_op_id, sender, new_nonce.as_u64() as i64)
+            .await?)
+    }
+
+    async fn get_next_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+    ) -> anyhow::Result<U256> {
+        let nonce = match sender {
+            Some(address) => {
+                connection
+                    .ethereum_schema()
+                    .get_next_operator_nonce(address)
+                    .await?
+            }
+            None => connection.ethereum_schema().get_next_nonce().await?,
+        };
+        Ok(nonce.into())
+    }
+
+    async fn advance_nonce(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+        min_nonce: U256,
+    ) -> anyhow::Result<()> {
+        let min_nonce = min_nonce.as_u64() as i64;
+        match sender {
+            Some(address) => {
+                connection
+                    .ethereum_schema()
+                    .advance_operator_nonce(address, min_nonce)
+                    .await?
+            }
+            None => {
+                connection
+                    .ethereum_schema()
+                    .advance_nonce(min_nonce)
+                    .await?
+            }
+        }
+        Ok(())
+    }
+
     async fn is_previous_operation_confirmed(
         &self,
         connection: &mut StorageProcessor<'_>,
@@ -1,8 +1,11 @@
 // Built-in deps
 use std::{collections::VecDeque, marker::PhantomData, time::Instant};
 // External deps
+use web3::contract::Options;
 use zksync_basic_types::U256;
+use zksync_config::configs::eth_sender::GasLimit;
 use zksync_eth_client::EthereumGateway;
+use zksync_types::ethereum::ETHOperation;
 // Local deps
 use crate::database::DatabaseInterface;
 
@@ -11,6 +14,64 @@ mod parameters;
 #[cfg(test)]
 mod tests;
 
+/// Type of the Ethereum transactions sent by the `ETHSender`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TxType {
+    /// Transactions with a single gas price, which is paid entirely.
+    Legacy,
+    /// EIP-1559 transactions with the max fee and the max priority fee per gas.
+    Eip1559,
+}
+
+impl TxType {
+    pub fn from_config(config: &GasLimit) -> Self {
+        if config.use_legacy_transactions {
+            Self::Legacy
+        } else {
+            Self::Eip1559
+        }
+    }
+}
+
+/// Fees to be paid by the Ethereum transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct TxFees {
+    /// Gas price for the legacy transaction or max fee per gas for the EIP-1559 one.
+    pub gas_price: U256,
+    /// Max priority fee per gas, set only for the EIP-1559 transaction.
+    pub max_priority_fee_per_gas: Option<U256>,
+}
+
+impl TxFees {
+    pub fn legacy(gas_price: U256) -> Self {
+        Self {
+            gas_price,
+            max_priority_fee_per_gas: None,
+        }
+    }
+
+    /// Returns the fees used by the last sent transaction of the operation.
+    pub fn from_op(op: &ETHOperation) -> Self {
+        Self {
+            gas_price: op.last_used_gas_price,
+            max_priority_fee_per_gas: op.last_used_priority_fee,
+        }
+    }
+
+    /// Sets the fees to the transaction options.
+    pub fn apply(&self, options: &mut Options) {
+        match self.max_priority_fee_per_gas {
+            Some(max_priority_fee_per_gas) => {
+                options.max_fee_per_gas = Some(self.gas_price);
+                options.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            }
+            None => {
+                options.gas_price = Some(self.gas_price);
+            }
+        }
+    }
+}
+
 /// Gas adjuster is an entity capable of scaling the gas price for
 /// all the Ethereum transactions.
 ///
@@ -20,6 +81,10 @@ mod tests;
 /// transactions only), which guarantees that we will increase the
 /// gas price for transactions that were not mined by the network
 /// within a reasonable time.
+///
+/// For EIP-1559 transactions the max fee per gas plays the role of the gas price,
+/// and it's derived from the base fee of the next block. The priority fee is taken
+/// from the config, and for the stuck transactions it's increased along with the max fee.
 #[derive(Debug)]
 pub(super) struct GasAdjuster<DB: DatabaseInterface> {
     /// Collected statistics about recently used gas prices.
@@ -28,11 +93,13 @@ pub(super) struct GasAdjuster<DB: DatabaseInterface> {
     last_price_renewal: Instant,
     /// Timestamp of the last sample added to the `statistics`.
     last_sample_added: Instant,
+    /// Type of the transactions to suggest the fees for.
+    tx_type: TxType,
     _db: PhantomData<DB>,
 }
 
 impl<DB: DatabaseInterface> GasAdjuster<DB> {
-    pub async fn new(db: &DB) -> Self {
+    pub async fn new(db: &DB, tx_type: TxType) -> Self {
         let mut connection = db
             .acquire_connection()
             .await
@@ -45,6 +112,7 @@ impl<DB: DatabaseInterface> GasAdjuster<DB> {
             statistics: GasStatistics::new(gas_price_limit),
             last_price_renewal: Instant::now(),
             last_sample_added: Instant::now(),
+            tx_type,
 
             _db: PhantomData,
         }
@@ -70,27 +138,99 @@ impl<DB: DatabaseInterface> GasAdjuster<DB> {
         Ok(scaled_price)
     }
 
-    /// Calculates a new gas amount for the replacement of the stuck tx.
+    async fn get_suggested_eip1559_fees(
+        &self,
+        ethereum: &EthereumGateway,
+        old_tx_fees: Option<TxFees>,
+    ) -> anyhow::Result<TxFees> {
+        let base_fee = ethereum.get_base_fee().await?;
+        let priority_fee = parameters::priority_fee();
+
+        let fees = if let Some(old_fees) = old_tx_fees {
+            // Stuck transaction, both fees must be scaled up for the replacement to be accepted.
+            // Legacy transaction pays the whole gas price as the priority fee.
+            let old_priority_fee = old_fees
+                .max_priority_fee_per_gas
+                .unwrap_or(old_fees.gas_price);
+            let priority_fee = self.scale_up(old_priority_fee, priority_fee);
+            let max_fee = self.scale_up(old_fees.gas_price, Self::max_fee(base_fee, priority_fee));
+            TxFees {
+                gas_price: max_fee,
+                max_priority_fee_per_gas: Some(priority_fee),
+            }
+        } else {
+            // New transaction, use the network base fee as the base.
+            TxFees {
+                gas_price: Self::max_fee(base_fee, priority_fee),
+                max_priority_fee_per_gas: Some(priority_fee),
+            }
+        };
+        Ok(fees)
+    }
+
+    /// Returns the fees to be used in the transaction without updating the statistics.
+    /// The fees are already cut according to the current max gas price.
+    pub async fn get_suggested_fees(
+        &self,
+        ethereum: &EthereumGateway,
+        old_tx_fees: Option<TxFees>,
+    ) -> anyhow::Result<TxFees> {
+        let fees = match self.tx_type {
+            TxType::Legacy => {
+                let old_tx_gas_price = old_tx_fees.map(|fees| fees.gas_price);
+                TxFees::legacy(self.get_suggested_price(ethereum, old_tx_gas_price).await?)
+            }
+            TxType::Eip1559 => {
+                self.get_suggested_eip1559_fees(ethereum, old_tx_fees)
+                    .await?
+            }
+        };
+
+        // Now, cut the price if it's too big.
+        // Priority fee can't be higher than the max fee, so it's cut as well.
+        let gas_price = self.limit_max(fees.gas_price);
+        Ok(TxFees {
+            gas_price,
+            max_priority_fee_per_gas: fees
+                .max_priority_fee_per_gas
+                .map(|fee| std::cmp::min(fee, gas_price)),
+        })
+    }
+
+    /// Calculates the fees for the new tx or for the replacement of the stuck one.
     /// Replacement price is usually suggested to be at least 10% higher, we make it 15% higher.
-    pub async fn get_gas_price(
+    pub async fn get_fees(
         &mut self,
         ethereum: &EthereumGateway,
-        old_tx_gas_price: Option<U256>,
-    ) -> anyhow::Result<U256> {
-        let scaled_price = self.get_suggested_price(ethereum, old_tx_gas_price).await?;
-        // Now, cut the price if it's too big.
-        let price = self.limit_max(scaled_price);
+        old_tx_fees: Option<TxFees>,
+    ) -> anyhow::Result<TxFees> {
+        let fees = self.get_suggested_fees(ethereum, old_tx_fees).await?;
 
-        if price == self.get_current_max_price() {
+        if fees.gas_price == self.get_current_max_price() {
             // We're suggesting the max price, so we must notify the log
             // entry about it.
-            vlog::warn!("Maximum possible gas price will be used: <{}>", price);
+            vlog::warn!(
+                "Maximum possible gas price will be used: <{}>",
+                fees.gas_price
+            );
         }
 
         // Report used price to be gathered by the statistics module.
-        self.statistics.add_sample(price);
+        self.statistics.add_sample(fees.gas_price);
 
-        Ok(price)
+        Ok(fees)
+    }
+
+    /// Returns the current network gas price, which is used as a sample for statistics.
+    /// For EIP-1559 transactions it's the max fee per gas for the new transaction.
+    async fn get_network_price(&self, ethereum: &EthereumGateway) -> anyhow::Result<U256> {
+        match self.tx_type {
+            TxType::Legacy => ethereum.get_gas_price().await,
+            TxType::Eip1559 => {
+                let base_fee = ethereum.get_base_fee().await?;
+                Ok(Self::max_fee(base_fee, parameters::priority_fee()))
+            }
+        }
     }
 
     /// Performs an actualization routine for `GasAdjuster`:
@@ -99,7 +239,7 @@ impl<DB: DatabaseInterface> GasAdjuster<DB> {
     pub async fn keep_updated(&mut self, ethereum: &EthereumGateway, db: &DB) {
         if self.last_sample_added.elapsed() >= parameters::sample_adding_interval() {
             // Report the current price to be gathered by the statistics module.
-            match ethereum.get_gas_price().await {
+            match self.get_network_price(ethereum).await {
                 Ok(network_price) => {
                     self.statistics.add_sample(network_price);
 
@@ -155,6 +295,12 @@ impl<DB: DatabaseInterface> GasAdjuster<DB> {
         std::cmp::max(current_network_price, replacement_price)
     }
 
+    /// Max fee per gas for the new EIP-1559 transaction.
+    /// Doubled base fee keeps the transaction valid even if the base fee grows for a few blocks in a row.
+    fn max_fee(base_fee: U256, priority_fee: U256) -> U256 {
+        base_fee * U256::from(2) + priority_fee
+    }
+
     fn limit_max(&self, price: U256) -> U256 {
         let limit = self.get_current_max_price();
 
@@ -5,6 +5,7 @@
 //!   gas price suggested by `GasAdjuster`.
 //! - Maximum gas price scale: multiplier to be applied to the average gas price to
 //!   calculate the upper limit for gas price in `GasAdjuster`.
+//! - Priority fee: max priority fee per gas to be used in EIP-1559 transactions.
 //!
 //! The module uses a child module `parameters_impl` which contains two implementations
 //! for functions declared in module: one for the actual usage, and one for tests.
@@ -13,6 +14,8 @@
 
 // Built-in deps.
 use std::time::Duration;
+// Workspace deps
+use zksync_basic_types::U256;
 
 /// Obtains the interval for renewing the maximum gas price.
 ///
@@ -41,12 +44,22 @@ pub fn sample_adding_interval() -> Duration {
     parameters_impl::sample_adding_interval()
 }
 
+/// Obtains the max priority fee per gas for EIP-1559 transactions.
+///
+/// This value is not cached internally, as it may be changed for the already running
+/// server by an administrator. This may be required if existing settings aren't flexible
+/// enough to match the current network price.
+pub fn priority_fee() -> U256 {
+    parameters_impl::priority_fee()
+}
+
 // Actual methods implementation for non-test purposes.
 #[cfg(not(test))]
 mod parameters_impl {
     // Built-in deps.
     use std::time::Duration;
     // Workspace deps
+    use zksync_basic_types::U256;
     use zksync_config::configs::eth_sender::ETHSenderConfig;
 
     /// Obtains the interval for renewing the maximum gas price.
@@ -78,6 +91,16 @@ mod parameters_impl {
         let config = ETHSenderConfig::from_env();
         config.gas_price_limit.sample_interval()
     }
+
+    /// Obtains the max priority fee per gas for EIP-1559 transactions.
+    ///
+    /// This value is not cached internally, as it may be changed for the already running
+    /// server by an administrator. This may be required if existing settings aren't flexible
+    /// enough to match the current network price.
+    pub fn priority_fee() -> U256 {
+        let config = ETHSenderConfig::from_env();
+        config.gas_price_limit.priority_fee.into()
+    }
 }
 
 // Hard-coded implementation for tests.
@@ -85,6 +108,8 @@ mod parameters_impl {
 mod parameters_impl {
     // Built-in deps.
     use std::time::Duration;
+    // Workspace deps
+    use zksync_basic_types::U256;
 
     /// `limit_update_interval` version for tests not looking for an environment variable value
     /// but using a zero interval instead.
@@ -103,4 +128,10 @@ mod parameters_impl {
     pub fn sample_adding_interval() -> Duration {
         Duration::from_secs(0)
     }
+
+    /// `priority_fee` version for tests not looking for an environment variable value
+    /// but using a fixed fee (10 wei) instead.
+    pub fn priority_fee() -> U256 {
+        10.into()
+    }
 }
@@ -3,7 +3,10 @@
 use zksync_basic_types::U256;
 // Local uses
 use crate::{
-    gas_adjuster::{parameters::limit_scale_factor, GasStatistics},
+    gas_adjuster::{
+        parameters::{limit_scale_factor, priority_fee},
+        GasStatistics, TxFees, TxType,
+    },
     tests::mock::{default_eth_sender, MockDatabase},
     DatabaseInterface, GasAdjuster,
 };
@@ -17,6 +20,19 @@ async fn eth_and_db_clients() -> (EthereumGateway, MockDatabase) {
     (eth_sender.ethereum, eth_sender.db)
 }
 
+/// Requests the gas price for the legacy transaction from the `GasAdjuster`.
+async fn get_gas_price(
+    gas_adjuster: &mut GasAdjuster<MockDatabase>,
+    ethereum: &EthereumGateway,
+    old_tx_gas_price: Option<U256>,
+) -> U256 {
+    gas_adjuster
+        .get_fees(ethereum, old_tx_gas_price.map(TxFees::legacy))
+        .await
+        .unwrap()
+        .gas_price
+}
+
 /// Scales the gas limit according to the scale factor for GasAdjuster.
 fn scale_gas_limit(value: u64) -> u64 {
     let scale = (limit_scale_factor() * 100.0).round() as u64;
@@ -30,7 +46,7 @@ fn scale_gas_limit(value: u64) -> u64 {
 async fn initial_price() {
     let (mut ethereum, db) = eth_and_db_clients().await;
     let mut connection = db.acquire_connection().await.unwrap();
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Vector of ethereum client prices.
     let test_vector = vec![
@@ -50,7 +66,7 @@ async fn initial_price() {
             .await
             .unwrap();
 
-        let scaled_gas = gas_adjuster.get_gas_price(&ethereum, None).await.unwrap();
+        let scaled_gas = get_gas_price(&mut gas_adjuster, &ethereum, None).await;
         assert_eq!(scaled_gas, eth_client_price.into());
     }
 }
@@ -62,7 +78,7 @@ async fn initial_price() {
 async fn lower_gas_limit() {
     let (mut ethereum, db) = eth_and_db_clients().await;
 
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Test vector of pairs (ethereum client price, price of the last tx, expected price).
     let test_vector = vec![
@@ -83,10 +99,8 @@ async fn lower_gas_limit() {
             .unwrap();
 
         // Check that gas price of 1000 is increased to 1150.
-        let scaled_gas = gas_adjuster
-            .get_gas_price(&ethereum, Some(previous_price.into()))
-            .await
-            .unwrap();
+        let scaled_gas =
+            get_gas_price(&mut gas_adjuster, &ethereum, Some(previous_price.into())).await;
         assert_eq!(scaled_gas, expected_price.into());
     }
 }
@@ -99,7 +113,7 @@ async fn gas_price_limit_restore() {
 
     let (_, db) = eth_and_db_clients().await;
     db.update_gas_price_limit(PRICE_LIMIT).await.unwrap();
-    let gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     assert_eq!(gas_adjuster.get_current_max_price(), PRICE_LIMIT.into());
 }
@@ -115,7 +129,7 @@ async fn initial_upper_gas_limit() {
     let (mut ethereum, db) = eth_and_db_clients().await;
 
     db.update_gas_price_limit(PRICE_LIMIT).await.unwrap();
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Set the gas price in Ethereum, which is greater than the current limit.
     ethereum
@@ -126,7 +140,7 @@ async fn initial_upper_gas_limit() {
         .unwrap();
 
     // Check that gas price of `PRICE_LIMIT` + 1 is clamped to `PRICE_LIMIT`.
-    let scaled_gas = gas_adjuster.get_gas_price(&ethereum, None).await.unwrap();
+    let scaled_gas = get_gas_price(&mut gas_adjuster, &ethereum, None).await;
     assert_eq!(scaled_gas, PRICE_LIMIT.into());
 
     // Check that gas price is clamped even if both the ethereum client price
@@ -139,10 +153,7 @@ async fn initial_upper_gas_limit() {
         .unwrap();
     let previous_price = U256::from(PRICE_LIMIT) * 2;
 
-    let scaled_gas = gas_adjuster
-        .get_gas_price(&ethereum, Some(previous_price))
-        .await
-        .unwrap();
+    let scaled_gas = get_gas_price(&mut gas_adjuster, &ethereum, Some(previous_price)).await;
     assert_eq!(scaled_gas, PRICE_LIMIT.into());
 }
 
@@ -155,7 +166,7 @@ async fn average_gas_price_stored_correctly() {
     let (mut ethereum, db) = eth_and_db_clients().await;
 
     db.update_gas_price_limit(PRICE_LIMIT).await.unwrap();
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     let initial_db_price = db.average_gas_price().await;
     assert_eq!(initial_db_price, 0u64.into()); // Check just in case.
@@ -204,7 +215,7 @@ async fn gas_price_limit_scaling() {
 
     db.update_gas_price_limit(PRICE_LIMIT as i64).await.unwrap();
 
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Set the client price way beyond the limit.
     ethereum
@@ -219,10 +230,8 @@ async fn gas_price_limit_scaling() {
     // Initial phase: stats are not yet initialized, we are based on the DB limit.
     // The reason for the dividing is that we update samples it twice per iteration
     for _ in 0..N_SAMPLES / 2 {
-        let suggested_price = gas_adjuster
-            .get_gas_price(&ethereum, Some(expected_price.into()))
-            .await
-            .unwrap();
+        let suggested_price =
+            get_gas_price(&mut gas_adjuster, &ethereum, Some(expected_price.into())).await;
 
         // Until we call `keep_updated`, the suggested price should not change and should be
         // equal to the limit.
@@ -287,7 +296,7 @@ async fn gas_price_limit_average_basis() {
     let (mut ethereum, db) = eth_and_db_clients().await;
     let mut connection = db.acquire_connection().await.unwrap();
     db.update_gas_price_limit(PRICE_LIMIT as i64).await.unwrap();
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Set the client price way beyond the limit.
     ethereum
@@ -305,10 +314,8 @@ async fn gas_price_limit_average_basis() {
 
         // Request the gas price N times to gather statistics in GasAdjuster.
         for _ in 0..N_SAMPLES {
-            let suggested_price = gas_adjuster
-                .get_gas_price(&ethereum, Some(expected_price.into()))
-                .await
-                .unwrap();
+            let suggested_price =
+                get_gas_price(&mut gas_adjuster, &ethereum, Some(expected_price.into())).await;
 
             let increased_price = increase_gas_price(expected_price);
 
@@ -352,7 +359,7 @@ async fn gas_price_limit_preservation() {
     let (mut ethereum, db) = eth_and_db_clients().await;
     let mut connection = db.acquire_connection().await.unwrap();
     db.update_gas_price_limit(price_limit as i64).await.unwrap();
-    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db).await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Legacy).await;
 
     // Set the client price way beyond the limit.
     ethereum
@@ -368,7 +375,7 @@ async fn gas_price_limit_preservation() {
             // Every time we get the new price (without old price provided), so no scaling
             // involved, every time an Ethereum client price is provided (since it's lower
             // than the limit).
-            let suggested_price = gas_adjuster.get_gas_price(&ethereum, None).await.unwrap();
+            let suggested_price = get_gas_price(&mut gas_adjuster, &ethereum, None).await;
             assert_eq!(suggested_price, SUGGESTED_PRICE.into());
         }
 
@@ -378,3 +385,126 @@ async fn gas_price_limit_preservation() {
         assert_eq!(new_limit, price_limit.into());
     }
 }
+
+/// Checks that for the new EIP-1559 transaction the max fee is based on the base fee
+/// of the next block, and the priority fee is taken from the config.
+#[tokio::test]
+async fn eip1559_initial_fees() {
+    let (mut ethereum, db) = eth_and_db_clients().await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Eip1559).await;
+
+    for base_fee in [0u64, 13, 1000] {
+        ethereum
+            .get_mut_mock()
+            .unwrap()
+            .set_base_fee(base_fee.into())
+            .await
+            .unwrap();
+
+        let fees = gas_adjuster.get_fees(&ethereum, None).await.unwrap();
+        assert_eq!(fees.gas_price, U256::from(base_fee * 2) + priority_fee());
+        assert_eq!(fees.max_priority_fee_per_gas, Some(priority_fee()));
+    }
+}
+
+/// Checks that for the stuck EIP-1559 transaction both the max fee and the priority fee
+/// are increased by at least 15%, so the replacement is accepted by the network.
+#[tokio::test]
+async fn eip1559_replacement_fees() {
+    let (mut ethereum, db) = eth_and_db_clients().await;
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Eip1559).await;
+
+    // Test vector of (base fee, old max fee, old priority fee, expected max fee, expected priority fee).
+    // Priority fee from the config is 10.
+    let test_vector = vec![
+        (40, 100, Some(10), 115, 11), // Base fee is low, both fees are increased by 15%
+        (100, 100, Some(10), 211, 11), // Base fee is higher, new max fee is based on it
+        (40, 100, Some(5), 115, 10),  // Old priority fee is too low, config value is used
+        (40, 100, None, 195, 115),    // Legacy tx pays the whole gas price as the priority fee
+    ];
+
+    for (base_fee, old_max_fee, old_priority_fee, expected_max_fee, expected_priority_fee) in
+        test_vector
+    {
+        ethereum
+            .get_mut_mock()
+            .unwrap()
+            .set_base_fee(U256::from(base_fee))
+            .await
+            .unwrap();
+
+        let old_fees = TxFees {
+            gas_price: old_max_fee.into(),
+            max_priority_fee_per_gas: old_priority_fee.map(U256::from),
+        };
+        let fees = gas_adjuster
+            .get_fees(&ethereum, Some(old_fees))
+            .await
+            .unwrap();
+        assert_eq!(fees.gas_price, expected_max_fee.into());
+        assert_eq!(
+            fees.max_priority_fee_per_gas,
+            Some(expected_priority_fee.into())
+        );
+    }
+}
+
+/// Checks that the max fee of EIP-1559 transaction is clamped according to the current limit,
+/// and the priority fee never exceeds the max fee.
+#[tokio::test]
+async fn eip1559_upper_gas_limit() {
+    // Initial price limit to set.
+    const PRICE_LIMIT: i64 = 1000;
+
+    let (mut ethereum, db) = eth_and_db_clients().await;
+
+    db.update_gas_price_limit(PRICE_LIMIT).await.unwrap();
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Eip1559).await;
+
+    ethereum
+        .get_mut_mock()
+        .unwrap()
+        .set_base_fee(U256::from(PRICE_LIMIT))
+        .await
+        .unwrap();
+
+    let fees = gas_adjuster.get_fees(&ethereum, None).await.unwrap();
+    assert_eq!(fees.gas_price, PRICE_LIMIT.into());
+    assert_eq!(fees.max_priority_fee_per_gas, Some(priority_fee()));
+
+    let old_fees = TxFees {
+        gas_price: PRICE_LIMIT.into(),
+        max_priority_fee_per_gas: Some(PRICE_LIMIT.into()),
+    };
+    let fees = gas_adjuster
+        .get_fees(&ethereum, Some(old_fees))
+        .await
+        .unwrap();
+    assert_eq!(fees.gas_price, PRICE_LIMIT.into());
+    assert_eq!(fees.max_priority_fee_per_gas, Some(PRICE_LIMIT.into()));
+}
+
+/// Checks that in the EIP-1559 mode statistics are gathered based on the base fee
+/// rather than on the network gas price.
+#[tokio::test]
+async fn eip1559_average_gas_price_stored_correctly() {
+    // Initial price limit to set.
+    const PRICE_LIMIT: i64 = 1000;
+
+    let (mut ethereum, db) = eth_and_db_clients().await;
+
+    db.update_gas_price_limit(PRICE_LIMIT).await.unwrap();
+    let mut gas_adjuster: GasAdjuster<MockDatabase> = GasAdjuster::new(&db, TxType::Eip1559).await;
+
+    let base_fee = U256::from(20u64);
+    let mock = ethereum.get_mut_mock().unwrap();
+    mock.set_base_fee(base_fee).await.unwrap();
+    mock.set_gas_price(U256::from(PRICE_LIMIT)).await.unwrap();
+
+    for _ in 0..GasStatistics::GAS_PRICE_SAMPLES_AMOUNT {
+        gas_adjuster.keep_updated(&ethereum, &db).await;
+    }
+
+    let current_db_price = db.average_gas_price().await;
+    assert_eq!(current_db_price, base_fee * 2 + priority_fee());
+}
@@ -4,14 +4,15 @@
 //! every transaction is executed successfully and confirmed.
 
 // Built-in deps
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::time::{Duration, Instant};
 // External uses
 use anyhow::format_err;
+use futures::future;
 use tokio::{task::JoinHandle, time};
 use web3::{
     contract::Options,
-    types::{TransactionReceipt, H256, U256},
+    types::{Address, TransactionReceipt, H256, U256},
 };
 // Workspace uses
 use zksync_config::ETHSenderConfig;
@@ -21,7 +22,7 @@ use zksync_types::ethereum::ETHOperation;
 // Local uses
 use self::{
     database::{Database, DatabaseInterface},
-    gas_adjuster::GasAdjuster,
+    gas_adjuster::{GasAdjuster, TxFees, TxType},
     transactions::*,
     tx_queue::{TxData, TxQueue, TxQueueBuilder},
 };
@@ -95,6 +96,13 @@ enum TxCheckMode {
 /// transaction is still guaranteed to be preserved, since every sent tx has the assigned nonce
 /// which makes it impossible to get sent transactions committed out of order.
 ///
+/// Besides the main operator account, `ETHSender` may use additional operator accounts.
+/// Each account has its own nonce sequence and gas price statistics, so the transactions sent
+/// by different accounts don't block each other (e.g. a stuck `commit` transaction doesn't delay
+/// `execute` ones). `TxQueue` assigns the account to every transaction and makes sure that the
+/// operations sent by different accounts are only sent once the operations they depend on are
+/// confirmed. Transactions of different accounts are sent concurrently.
+///
 /// Internally order of the transaction is determined by the underlying `TxQueue`, which provides
 /// transactions to send for `ETHSender` according to the following priority:
 ///
@@ -115,16 +123,24 @@ struct ETHSender<DB: DatabaseInterface> {
     db: DB,
     /// Ethereum intermediator.
     ethereum: EthereumGateway,
+    /// Additional operator accounts along with the Ethereum intermediators signing on their behalf.
+    additional_operators: Vec<(Address, EthereumGateway)>,
     /// Queue for ordered transaction processing.
     tx_queue: TxQueue,
-    /// Utility for managing the gas price for transactions.
-    gas_adjuster: GasAdjuster<DB>,
+    /// Utilities for managing the gas price for transactions, one per operator account
+    /// (indexed the same way as in the `TxQueue`).
+    gas_adjusters: Vec<GasAdjuster<DB>>,
     /// Settings for the `ETHSender`.
     options: ETHSenderConfig,
 }
 
 impl<DB: DatabaseInterface> ETHSender<DB> {
-    pub async fn new(options: ETHSenderConfig, db: DB, ethereum: EthereumGateway) -> Self {
+    pub async fn new(
+        options: ETHSenderConfig,
+        db: DB,
+        ethereum: EthereumGateway,
+        additional_operators: Vec<(Address, EthereumGateway)>,
+    ) -> Self {
         let mut connection = db
             .acquire_connection()
             .await
@@ -138,7 +154,7 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             .await
             .expect("Can't restore unprocessed operations");
 
-        let ongoing_ops = db
+        let mut ongoing_ops = db
             .load_unconfirmed_operations(&mut transaction)
             .await
             .expect("Can't restore state");
@@ -157,14 +173,79 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             .await
             .expect("Failed loading ETH operations stats");
 
-        let tx_queue = TxQueueBuilder::new(options.sender.max_txs_in_flight as usize)
-            .with_sent_pending_txs(ongoing_ops.len())
+        // Nonce sequences of the operator accounts start from the nonces known to L1,
+        // so the new accounts can be used right away.
+        let operators = std::iter::once((None, &ethereum)).chain(
+            additional_operators
+                .iter()
+                .map(|(address, gateway)| (Some(*address), gateway)),
+        );
+        for (sender, gateway) in operators {
+            let pending_nonce = gateway
+                .pending_nonce()
+                .await
+                .expect("Unable to load the nonce of the operator account");
+            db.advance_nonce(&mut transaction, sender, pending_nonce)
+                .await
+                .expect("Unable to initialize the nonce of the operator account");
+        }
+
+        // Operator accounts removed from the config can't sign the replacement transactions
+        // anymore, so their operations are moved to the main account. Such operations are
+        // considered stuck, so they're resent on the next commitment step.
+        for op in ongoing_ops.iter_mut() {
+            if Self::operator_index(&additional_operators, op.sender).is_some() {
+                continue;
+            }
+            let nonce = db
+                .get_next_nonce(&mut transaction, None)
+                .await
+                .expect("Unable to get the nonce for the reassigned operation");
+            vlog::warn!(
+                "Operator account {:?} of ETH Operation <id: {}, type: {:?}> is not configured, reassigning the operation to the main account with nonce {}",
+                op.sender, op.id, op.op_type, nonce,
+            );
+            db.reassign_sender(&mut transaction, op.id, None, nonce)
+                .await
+                .expect("Unable to reassign the operation");
+            db.update_eth_tx(
+                &mut transaction,
+                op.id,
+                0,
+                op.last_used_gas_price,
+                op.last_used_priority_fee,
+            )
+            .await
+            .expect("Unable to reassign the operation");
+            op.sender = None;
+            op.nonce = nonce;
+            op.last_deadline_block = 0;
+        }
+
+        let operators_count = additional_operators.len() + 1;
+        let mut tx_queue_builder = TxQueueBuilder::new(options.sender.max_txs_in_flight as usize)
+            .with_operators_count(operators_count)
             .with_commit_operations_count(stats.last_committed_block)
             .with_verify_operations_count(stats.last_verified_block)
-            .with_execute_operations_count(stats.last_executed_block)
-            .build();
+            .with_execute_operations_count(stats.last_executed_block);
+        for eth_op in &ongoing_ops {
+            if let Some((_, aggregated_op)) = &eth_op.op {
+                let operator = Self::operator_index(&additional_operators, eth_op.sender)
+                    .expect("Operation is reassigned to the configured account");
+                tx_queue_builder = tx_queue_builder.with_pending_operation(
+                    eth_op.op_type,
+                    aggregated_op.get_block_range(),
+                    operator,
+                );
+            }
+        }
+        let tx_queue = tx_queue_builder.build();
 
-        let gas_adjuster = GasAdjuster::new(&db).await;
+        let tx_type = TxType::from_config(&options.gas_price_limit);
+        let mut gas_adjusters = Vec::with_capacity(operators_count);
+        for _ in 0..operators_count {
+            gas_adjusters.push(GasAdjuster::new(&db, tx_type).await);
+        }
 
         transaction
             .commit()
@@ -176,17 +257,59 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             ongoing_ops,
             db,
             ethereum,
+            additional_operators,
             tx_queue,
-            gas_adjuster,
+            gas_adjusters,
             options,
         }
     }
 
+    /// Returns the index of the operator account used by the `TxQueue`,
+    /// or `None` if the account is not configured.
+    /// The main operator account (denoted as `None`) has index `0`.
+    fn operator_index(
+        additional_operators: &[(Address, EthereumGateway)],
+        sender: Option<Address>,
+    ) -> Option<usize> {
+        match sender {
+            Some(sender) => additional_operators
+                .iter()
+                .position(|(address, _)| *address == sender)
+                .map(|position| position + 1),
+            None => Some(0),
+        }
+    }
+
+    /// Returns the index of the operator account which has sent the ongoing operation.
+    /// Operations of the accounts that are not configured are reassigned on startup,
+    /// so the account is always known.
+    fn sender_index(&self, sender: Option<Address>) -> usize {
+        Self::operator_index(&self.additional_operators, sender)
+            .expect("Ongoing operations are sent only by the configured operator accounts")
+    }
+
+    /// Returns the address of the operator account by its index in the `TxQueue`.
+    fn operator_account(&self, operator: usize) -> Option<Address> {
+        operator
+            .checked_sub(1)
+            .map(|idx| self.additional_operators[idx].0)
+    }
+
+    /// Returns the Ethereum intermediator signing transactions on behalf of the operator account.
+    fn operator_gateway(&self, sender: Option<Address>) -> &EthereumGateway {
+        match self.sender_index(sender).checked_sub(1) {
+            Some(idx) => &self.additional_operators[idx].1,
+            None => &self.ethereum,
+        }
+    }
+
     /// Main routine of `ETHSender`.
     pub async fn run(mut self) {
         // `eth_sender` must perform some of the activities only once per block change.
         // Having `0` as an initial value is to ensure that on the first iteration we will run all the activities.
         let mut last_used_block = 0;
+        // Nonces are reconciled on the startup and then periodically.
+        let mut last_nonce_reconciliation: Option<Instant> = None;
         loop {
             // We perform a loading routine every X seconds.
             tokio::time::sleep(self.options.sender.tx_poll_period()).await;
@@ -197,12 +320,23 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             }
 
             if self.options.sender.is_enabled {
+                let reconciliation_interval = self.options.sender.nonce_reconciliation_interval();
+                if last_nonce_reconciliation
+                    .map_or(true, |time| time.elapsed() >= reconciliation_interval)
+                {
+                    if let Err(e) = self.reconcile_nonces().await {
+                        Self::process_error(e).await;
+                    }
+                    last_nonce_reconciliation = Some(Instant::now());
+                }
                 // ...and proceed them.
                 last_used_block = self.proceed_next_operations(last_used_block).await;
-                // Update the gas adjuster to maintain the up-to-date max gas price limit.
-                self.gas_adjuster
-                    .keep_updated(&self.ethereum, &self.db)
-                    .await;
+                // Update the gas adjusters to maintain the up-to-date max gas price limit.
+                let gateways = std::iter::once(&self.ethereum)
+                    .chain(self.additional_operators.iter().map(|(_, gateway)| gateway));
+                for (gas_adjuster, gateway) in self.gas_adjusters.iter_mut().zip(gateways) {
+                    gas_adjuster.keep_updated(gateway, &self.db).await;
+                }
             }
         }
     }
@@ -258,19 +392,25 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             }
         };
 
+        let mut new_txs = Vec::new();
         while let Some(tx) = self.tx_queue.pop_front() {
-            if let Err(e) = self.initialize_operation(tx.clone(), current_block).await {
-                Self::process_error(e).await;
-                // Return the unperformed operation to the queue, since failing the
-                // operation initialization means that it was not stored in the database.
-                if let Err(err_message) = self.tx_queue.return_popped(tx) {
-                    panic!(
-                        "Failed return previous sent operation to the queue: {}",
-                        err_message
-                    );
+            let operator = tx.operator;
+            match self.initialize_operation(tx.clone(), current_block).await {
+                Ok(signed_tx) => new_txs.push((operator, signed_tx)),
+                Err(e) => {
+                    Self::process_error(e).await;
+                    // Return the unperformed operation to the queue, since failing the
+                    // operation initialization means that it was not stored in the database.
+                    if let Err(err_message) = self.tx_queue.return_popped(tx) {
+                        panic!(
+                            "Failed return previous sent operation to the queue: {}",
+                            err_message
+                        );
+                    }
                 }
             }
         }
+        self.send_new_txs(new_txs).await;
 
         // In `perform_commitment_step` we request the states of transactions. We have requested
         // states for the block with number `last_used_block` on the previous call of
@@ -301,7 +441,15 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
                 match commitment {
                     OperationCommitment::Committed => {
                         // Free a slot for the next tx in the queue.
-                        self.tx_queue.report_commitment();
+                        let operator = self.sender_index(current_op.sender);
+                        let (_, last_block) = current_op
+                            .op
+                            .as_ref()
+                            .expect("Operation not found - can't report commitment")
+                            .1
+                            .get_block_range();
+                        self.tx_queue
+                            .report_commitment(operator, current_op.op_type, last_block);
                     }
                     OperationCommitment::Pending => {
                         // Poll this operation on the next iteration.
@@ -335,14 +483,20 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         }
     }
 
-    /// Stores the new operation in the database and sends the corresponding transaction.
-    async fn initialize_operation(&mut self, tx: TxData, current_block: u64) -> anyhow::Result<()> {
+    /// Stores the new operation in the database and signs the corresponding transaction.
+    /// The signed transaction must be sent by the caller.
+    async fn initialize_operation(
+        &mut self,
+        tx: TxData,
+        current_block: u64,
+    ) -> anyhow::Result<SignedCallResult> {
         let deadline_block = self.get_deadline_block(current_block);
-        let gas_price = self
-            .gas_adjuster
-            .get_gas_price(&self.ethereum, None)
+        let fees = self.gas_adjusters[tx.operator]
+            .get_fees(&self.ethereum, None)
             .await?;
 
+        let sender = self.operator_account(tx.operator);
+
         let mut connection = self.db.acquire_connection().await?;
         let mut transaction = connection.start_transaction().await?;
 
@@ -357,8 +511,10 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
                     tx.op_type,
                     Some(tx.operation.clone()),
                     deadline_block as i64,
-                    gas_price,
+                    fees.gas_price,
+                    fees.max_priority_fee_per_gas,
                     tx.raw.clone(),
+                    sender,
                 )
                 .await?;
 
@@ -367,8 +523,10 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
                 op_type: tx.op_type,
                 op: Some(tx.operation),
                 nonce: assigned_data.nonce,
+                sender,
                 last_deadline_block: deadline_block,
-                last_used_gas_price: gas_price,
+                last_used_gas_price: fees.gas_price,
+                last_used_priority_fee: fees.max_priority_fee_per_gas,
                 used_tx_hashes: vec![], // No hash yet, will be added below.
                 encoded_tx_data: tx.raw,
                 confirmed: false,
@@ -376,7 +534,7 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
             };
 
             // Sign the transaction.
-            let signed_tx = Self::sign_new_tx(&self.ethereum, &new_op).await?;
+            let signed_tx = Self::sign_new_tx(self.operator_gateway(sender), &new_op).await?;
 
             // With signed tx, update the hash in the operation entry and in the db.
             new_op.used_tx_hashes.push(signed_tx.hash);
@@ -391,22 +549,39 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         // so if sending will fail, we won't forget about it.
         self.ongoing_ops.push_back(new_op.clone());
 
-        // After storing all the tx data in the database, we can finally send the tx.
+        // After storing all the tx data in the database, the tx can be sent.
+        transaction.commit().await?;
         vlog::info!(
             "Sending new tx: [ETH Operation <id: {}, type: {:?}>. ETH tx: {}. ZKSync operation: {}]",
             new_op.id, new_op.op_type, self.eth_tx_description(&signed_tx), self.zksync_operation_description(&new_op),
         );
-        if let Err(e) = self.ethereum.send_raw_tx(signed_tx.raw_tx).await {
-            // Sending tx error is not critical: this will result in transaction being considered stuck,
-            // and resent. We can't do anything about this failure either, since it's most probably is not
-            // related to the node logic, so we just log this error and pretend to have this operation
-            // processed.
-            vlog::warn!("Error while sending the operation: {}", e);
-        }
 
-        transaction.commit().await?;
+        Ok(signed_tx)
+    }
 
-        Ok(())
+    /// Sends the transactions of the new operations along with the indices of the operator
+    /// accounts that have signed them.
+    /// Transactions of different accounts are sent concurrently, while the transactions of
+    /// one account are sent in order of their nonces.
+    async fn send_new_txs(&self, txs: Vec<(usize, SignedCallResult)>) {
+        let mut txs_by_operator: BTreeMap<usize, Vec<SignedCallResult>> = BTreeMap::new();
+        for (operator, tx) in txs {
+            txs_by_operator.entry(operator).or_default().push(tx);
+        }
+
+        let ethereum = &self.ethereum;
+        let sends = txs_by_operator.into_values().map(|txs| async move {
+            for tx in txs {
+                if let Err(e) = ethereum.send_raw_tx(tx.raw_tx).await {
+                    // Sending tx error is not critical: this will result in transaction being considered stuck,
+                    // and resent. We can't do anything about this failure either, since it's most probably is not
+                    // related to the node logic, so we just log this error and pretend to have this operation
+                    // processed.
+                    vlog::warn!("Error while sending the operation: {}", e);
+                }
+            }
+        });
+        future::join_all(sends).await;
     }
 
     /// Helper method to obtain the string representation of the Ethereum transaction.
@@ -414,10 +589,19 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
     fn eth_tx_description(&self, tx: &SignedCallResult) -> String {
         // Gas price in gwei (wei / 10^9).
         let gas_price = tx.gas_price / (1_000_000_000);
-        format!(
-            "<hash: {:#x}; gas price: {} gwei; nonce: {}>",
-            tx.hash, gas_price, tx.nonce
-        )
+        match tx.max_priority_fee_per_gas {
+            Some(priority_fee) => format!(
+                "<hash: {:#x}; max fee: {} gwei; priority fee: {} gwei; nonce: {}>",
+                tx.hash,
+                gas_price,
+                priority_fee / (1_000_000_000),
+                tx.nonce
+            ),
+            None => format!(
+                "<hash: {:#x}; gas price: {} gwei; nonce: {}>",
+                tx.hash, gas_price, tx.nonce
+            ),
+        }
     }
 
     /// Helper method to obtain the string representation of the zkSync operation.
@@ -545,6 +729,7 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
                 op.id,
                 deadline_block as i64,
                 new_tx.gas_price,
+                new_tx.max_priority_fee_per_gas,
             )
             .await?;
         self.db
@@ -563,6 +748,180 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         Ok(OperationCommitment::Pending)
     }
 
+    /// Compares the nonces of the ongoing operations with the operator accounts nonces on L1
+    /// and recovers the operations that won't get mined otherwise:
+    ///
+    /// - If the nonce of an operation was consumed by a transaction sent not by `ETHSender`
+    ///   (e.g. someone else used the operator key), the operation gets a new nonce. Since
+    ///   operations must be mined in order, all the following operations that are not mined
+    ///   yet are moved after it as well. Nonces of the moved operations that are not consumed
+    ///   yet are reused, the rest is taken from the nonce sequence.
+    /// - Transactions that fell out of the mempool are re-signed and resubmitted.
+    ///
+    /// Besides that, nonces consumed outside of `ETHSender` are skipped in the nonce sequence,
+    /// so they won't be assigned to the new operations.
+    async fn reconcile_nonces(&mut self) -> anyhow::Result<()> {
+        let start = Instant::now();
+        // Every operator account has its own nonce sequence.
+        for operator in 0..=self.additional_operators.len() {
+            self.reconcile_operator_nonces(operator).await?;
+        }
+        metrics::histogram!("eth_sender.reconcile_nonces", start.elapsed());
+        Ok(())
+    }
+
+    /// Performs the nonce reconciliation for a single operator account.
+    async fn reconcile_operator_nonces(&mut self, operator: usize) -> anyhow::Result<()> {
+        let sender = self.operator_account(operator);
+        let ethereum = self.operator_gateway(sender).clone();
+        let current_block = self.ethereum.block_number().await?.as_u64();
+        // Nonce of the next transaction to be mined.
+        let confirmed_nonce = ethereum.current_nonce().await?;
+        // Nonce of the next transaction to be mined, if all the mempool transactions are mined.
+        let pending_nonce = ethereum.pending_nonce().await?;
+
+        // Operations sent by this account.
+        let operator_ops: Vec<usize> = self
+            .ongoing_ops
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| op.sender == sender)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        // The first operation which nonce was consumed, while none of its transactions is mined.
+        let mut first_superseded = None;
+        for (position, &idx) in operator_ops.iter().enumerate() {
+            let op = &self.ongoing_ops[idx];
+            if op.nonce >= confirmed_nonce {
+                break;
+            }
+            if !self.is_mined(op, current_block).await? {
+                first_superseded = Some(position);
+                break;
+            }
+        }
+        let mut moved_ops = Vec::new();
+        if let Some(first_superseded) = first_superseded {
+            for &idx in operator_ops.iter().skip(first_superseded) {
+                if !self.is_mined(&self.ongoing_ops[idx], current_block).await? {
+                    moved_ops.push(idx);
+                }
+            }
+        }
+
+        let mut connection = self.db.acquire_connection().await?;
+        let mut transaction = connection.start_transaction().await?;
+
+        self.db
+            .advance_nonce(&mut transaction, sender, pending_nonce)
+            .await?;
+
+        // Nonces for the moved operations along with the fees of the transactions they replace.
+        let mut new_nonces: Vec<(U256, Option<TxFees>)> = moved_ops
+            .iter()
+            .map(|idx| &self.ongoing_ops[*idx])
+            .filter(|op| op.nonce >= confirmed_nonce)
+            .map(|op| (op.nonce, Some(TxFees::from_op(op))))
+            .collect();
+        while new_nonces.len() < moved_ops.len() {
+            let nonce = self.db.get_next_nonce(&mut transaction, sender).await?;
+            new_nonces.push((nonce, None));
+        }
+
+        let deadline_block = self.get_deadline_block(current_block);
+        let mut txs_to_send = Vec::new();
+        for (&idx, (new_nonce, replaced_tx_fees)) in moved_ops.iter().zip(new_nonces) {
+            // The replacement transaction must pay more than the one it replaces.
+            let fees = self.gas_adjusters[operator]
+                .get_fees(&self.ethereum, replaced_tx_fees)
+                .await?;
+
+            let op = &mut self.ongoing_ops[idx];
+            vlog::warn!(
+                "Nonce {} of ETH Operation <id: {}, type: {:?}> is consumed by another transaction, reassigning nonce {}",
+                op.nonce, op.id, op.op_type, new_nonce,
+            );
+            op.nonce = new_nonce;
+            op.last_deadline_block = deadline_block;
+            op.last_used_gas_price = fees.gas_price;
+            op.last_used_priority_fee = fees.max_priority_fee_per_gas;
+            let signed_tx = Self::sign_new_tx(&ethereum, op).await?;
+            // Old hashes are still tracked, since one of them may still get mined
+            // if the reused nonce wasn't replaced in time.
+            op.used_tx_hashes.push(signed_tx.hash);
+
+            self.db
+                .reassign_nonce(&mut transaction, op.id, new_nonce)
+                .await?;
+            self.db
+                .update_eth_tx(
+                    &mut transaction,
+                    op.id,
+                    deadline_block as i64,
+                    fees.gas_price,
+                    fees.max_priority_fee_per_gas,
+                )
+                .await?;
+            self.db
+                .add_hash_entry(&mut transaction, op.id, &signed_tx.hash)
+                .await?;
+            txs_to_send.push(signed_tx);
+        }
+
+        // Transactions unknown to the node are resubmitted as is.
+        for &idx in &operator_ops {
+            let op = &mut self.ongoing_ops[idx];
+            if op.nonce < pending_nonce || moved_ops.contains(&idx) {
+                continue;
+            }
+
+            let signed_tx = Self::sign_new_tx(&ethereum, op).await?;
+            if !op.used_tx_hashes.contains(&signed_tx.hash) {
+                op.used_tx_hashes.push(signed_tx.hash);
+                self.db
+                    .add_hash_entry(&mut transaction, op.id, &signed_tx.hash)
+                    .await?;
+            }
+            vlog::warn!(
+                "ETH Operation <id: {}, type: {:?}> is not found in the mempool, resubmitting",
+                op.id,
+                op.op_type,
+            );
+            txs_to_send.push(signed_tx);
+        }
+
+        transaction.commit().await?;
+
+        txs_to_send.sort_by_key(|tx| tx.nonce);
+        for signed_tx in txs_to_send {
+            vlog::info!(
+                "Nonce reconciliation: sending tx {}",
+                self.eth_tx_description(&signed_tx)
+            );
+            if let Err(e) = self.ethereum.send_raw_tx(signed_tx.raw_tx).await {
+                // Same as for the new operations, a failed tx will be considered stuck and resent.
+                vlog::warn!("Error while sending the operation: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether any of the transactions sent for the operation is included in a block.
+    async fn is_mined(&self, op: &ETHOperation, current_block: u64) -> anyhow::Result<bool> {
+        for tx_hash in &op.used_tx_hashes {
+            let status = self
+                .ethereum
+                .get_tx_status(*tx_hash, Some(current_block))
+                .await?;
+            if status.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Handles a transaction execution failure by reporting the issue to the log
     /// and terminating the node.
     async fn failure_handler(&self, receipt: &TransactionReceipt) -> ! {
@@ -656,14 +1015,13 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
                 gas_limit
             );
 
-            Options {
+            let mut options = Options {
                 nonce: Some(op.nonce),
-                gas_price: Some(op.last_used_gas_price),
                 gas: Some(gas_limit),
-                max_fee_per_gas: None,
-                max_priority_fee_per_gas: None,
                 ..Default::default()
-            }
+            };
+            TxFees::from_op(op).apply(&mut options);
+            options
         };
 
         let signed_tx = ethereum
@@ -705,10 +1063,14 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         let tx_options = self.tx_options_from_stuck_tx(stuck_tx).await?;
 
         let raw_tx = stuck_tx.encoded_tx_data.clone();
-        let signed_tx = self.ethereum.sign_prepared_tx(raw_tx, tx_options).await?;
+        let signed_tx = self
+            .operator_gateway(stuck_tx.sender)
+            .sign_prepared_tx(raw_tx, tx_options)
+            .await?;
 
         stuck_tx.last_deadline_block = deadline_block;
         stuck_tx.last_used_gas_price = signed_tx.gas_price;
+        stuck_tx.last_used_priority_fee = signed_tx.max_priority_fee_per_gas;
         stuck_tx.used_tx_hashes.push(signed_tx.hash);
 
         Ok(signed_tx)
@@ -720,11 +1082,11 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         &mut self,
         stuck_tx: &ETHOperation,
     ) -> anyhow::Result<Options> {
-        let old_tx_gas_price = stuck_tx.last_used_gas_price;
+        let old_tx_fees = TxFees::from_op(stuck_tx);
 
-        let new_gas_price = self
-            .gas_adjuster
-            .get_gas_price(&self.ethereum, Some(old_tx_gas_price))
+        let operator = self.sender_index(stuck_tx.sender);
+        let new_tx_fees = self.gas_adjusters[operator]
+            .get_fees(&self.ethereum, Some(old_tx_fees))
             .await?;
         let nonce = stuck_tx.nonce;
         let gas_limit = Self::gas_limit_for_op(stuck_tx);
@@ -736,16 +1098,18 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
         );
 
         vlog::info!(
-            "Replacing tx: hash: {:#x}, old_gas: {}, new_gas: {}, used nonce: {}, gas limit: {}",
+            "Replacing tx: hash: {:#x}, old_gas: {}, new_gas: {}, old_priority_fee: {:?}, new_priority_fee: {:?}, used nonce: {}, gas limit: {}",
             stuck_tx.used_tx_hashes.last().unwrap(),
-            old_tx_gas_price,
-            new_gas_price,
+            old_tx_fees.gas_price,
+            new_tx_fees.gas_price,
+            old_tx_fees.max_priority_fee_per_gas,
+            new_tx_fees.max_priority_fee_per_gas,
             nonce,
             gas_limit,
         );
 
         Ok(Options::with(move |opt| {
-            opt.gas_price = Some(new_gas_price);
+            new_tx_fees.apply(opt);
             opt.nonce = Some(nonce);
             opt.gas = Some(gas_limit);
         }))
@@ -800,12 +1164,13 @@ impl<DB: DatabaseInterface> ETHSender<DB> {
 pub fn run_eth_sender(
     pool: ConnectionPool,
     eth_gateway: EthereumGateway,
+    additional_operators: Vec<(Address, EthereumGateway)>,
     options: ETHSenderConfig,
 ) -> JoinHandle<()> {
     let db = Database::new(pool);
 
     tokio::spawn(async move {
-        let eth_sender = ETHSender::new(options, db, eth_gateway).await;
+        let eth_sender = ETHSender::new(options, db, eth_gateway, additional_operators).await;
 
         eth_sender.run().await
     })
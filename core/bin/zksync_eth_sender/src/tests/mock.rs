@@ -1,12 +1,12 @@
 //! Mocking utilities for tests.
 
 // Built-in deps
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::convert::TryFrom;
 // External uses
 use tokio::sync::RwLock;
 use web3::contract::Options;
-use zksync_basic_types::{BlockNumber, H256, U256};
+use zksync_basic_types::{Address, BlockNumber, H256, U256};
 // Workspace uses
 use zksync_config::configs::eth_sender::{ETHSenderConfig, GasLimit, Sender};
 use zksync_eth_client::EthereumGateway;
@@ -26,6 +26,7 @@ pub(crate) struct MockDatabase {
     aggregated_operations: RwLock<Vec<(i64, AggregatedOperation)>>,
     unprocessed_operations: RwLock<Vec<(i64, AggregatedOperation)>>,
     eth_parameters: RwLock<ETHParams>,
+    operator_nonces: RwLock<HashMap<Address, i64>>,
 }
 
 impl MockDatabase {
@@ -34,16 +35,44 @@ impl MockDatabase {
         eth_operations: Vec<ETHOperation>,
         aggregated_operations: Vec<(i64, AggregatedOperation)>,
         unprocessed_operations: Vec<(i64, AggregatedOperation)>,
-        eth_parameters: ETHParams,
+        mut eth_parameters: ETHParams,
     ) -> Self {
+        // Restored operations have nonces assigned in order, so the nonce sequence
+        // continues right after them.
+        eth_parameters.nonce = eth_parameters.nonce.max(
+            eth_operations
+                .iter()
+                .filter(|op| op.sender.is_none())
+                .count() as i64,
+        );
+        let mut operator_nonces = HashMap::new();
+        for address in eth_operations.iter().filter_map(|op| op.sender) {
+            *operator_nonces.entry(address).or_insert(0) += 1;
+        }
         Self {
             eth_operations: RwLock::new(eth_operations),
             aggregated_operations: RwLock::new(aggregated_operations),
             unprocessed_operations: RwLock::new(unprocessed_operations),
             eth_parameters: RwLock::new(eth_parameters),
+            operator_nonces: RwLock::new(operator_nonces),
         }
     }
 
+    /// Takes the next nonce from the nonce sequence of the operator account.
+    /// Same as for the real storage, sequences of the additional accounts must be initialized first.
+    async fn next_nonce(&self, sender: Option<Address>) -> anyhow::Result<u64> {
+        let mut eth_parameters = self.eth_parameters.write().await;
+        let mut operator_nonces = self.operator_nonces.write().await;
+        let nonce = match sender {
+            Some(address) => operator_nonces.get_mut(&address).ok_or_else(|| {
+                anyhow::format_err!("Nonce of the account {:#x} is not initialized", address)
+            })?,
+            None => &mut eth_parameters.nonce,
+        };
+        *nonce += 1;
+        Ok((*nonce - 1) as u64)
+    }
+
     pub async fn update_gas_price_limit(&self, value: i64) -> anyhow::Result<()> {
         let mut eth_parameters = self.eth_parameters.write().await;
         eth_parameters.gas_price_limit = value;
@@ -204,11 +233,13 @@ impl DatabaseInterface for MockDatabase {
         op: Option<(i64, AggregatedOperation)>,
         deadline_block: i64,
         used_gas_price: U256,
+        used_priority_fee: Option<U256>,
         encoded_tx_data: Vec<u8>,
+        sender: Option<Address>,
     ) -> anyhow::Result<InsertedOperationResponse> {
         let mut eth_operations = self.eth_operations.write().await;
         let id = eth_operations.len() as i64;
-        let nonce = eth_operations.len();
+        let nonce = self.next_nonce(sender).await?;
 
         // Store with the assigned ID.
         let eth_operation = ETHOperation {
@@ -216,8 +247,10 @@ impl DatabaseInterface for MockDatabase {
             op_type,
             op,
             nonce: nonce.into(),
+            sender,
             last_deadline_block: deadline_block as u64,
             last_used_gas_price: used_gas_price,
+            last_used_priority_fee: used_priority_fee,
             used_tx_hashes: vec![],
             encoded_tx_data,
             confirmed: false,
@@ -261,6 +294,7 @@ impl DatabaseInterface for MockDatabase {
         eth_op_id: EthOpId,
         new_deadline_block: i64,
         new_gas_value: U256,
+        new_priority_fee: Option<U256>,
     ) -> anyhow::Result<()> {
         let mut eth_operations = self.eth_operations.write().await;
         let eth_op = eth_operations
@@ -270,6 +304,49 @@ impl DatabaseInterface for MockDatabase {
         if let Some(eth_op) = eth_op {
             eth_op.last_deadline_block = new_deadline_block as u64;
             eth_op.last_used_gas_price = new_gas_value;
+            eth_op.last_used_priority_fee = new_priority_fee;
+        } else {
+            panic!("Attempt to update tx that is not unconfirmed");
+        }
+
+        Ok(())
+    }
+
+    async fn reassign_nonce(
+        &self,
+        _connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        new_nonce: U256,
+    ) -> anyhow::Result<()> {
+        let mut eth_operations = self.eth_operations.write().await;
+        let eth_op = eth_operations
+            .iter_mut()
+            .find(|eth_op| eth_op.id == eth_op_id && !eth_op.confirmed);
+
+        if let Some(eth_op) = eth_op {
+            eth_op.nonce = new_nonce;
+        } else {
+            panic!("Attempt to update tx that is not unconfirmed");
+        }
+
+        Ok(())
+    }
+
+    async fn reassign_sender(
+        &self,
+        _connection: &mut StorageProcessor<'_>,
+        eth_op_id: EthOpId,
+        sender: Option<Address>,
+        new_nonce: U256,
+    ) -> anyhow::Result<()> {
+        let mut eth_operations = self.eth_operations.write().await;
+        let eth_op = eth_operations
+            .iter_mut()
+            .find(|eth_op| eth_op.id == eth_op_id && !eth_op.confirmed);
+
+        if let Some(eth_op) = eth_op {
+            eth_op.sender = sender;
+            eth_op.nonce = new_nonce;
         } else {
             panic!("Attempt to update tx that is not unconfirmed");
         }
@@ -277,6 +354,36 @@ impl DatabaseInterface for MockDatabase {
         Ok(())
     }
 
+    async fn get_next_nonce(
+        &self,
+        _connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+    ) -> anyhow::Result<U256> {
+        Ok(self.next_nonce(sender).await?.into())
+    }
+
+    async fn advance_nonce(
+        &self,
+        _connection: &mut StorageProcessor<'_>,
+        sender: Option<Address>,
+        min_nonce: U256,
+    ) -> anyhow::Result<()> {
+        let min_nonce = min_nonce.as_u64() as i64;
+        match sender {
+            Some(address) => {
+                let mut operator_nonces = self.operator_nonces.write().await;
+                let nonce = operator_nonces.entry(address).or_insert(0);
+                *nonce = min_nonce.max(*nonce);
+            }
+            None => {
+                let mut eth_parameters = self.eth_parameters.write().await;
+                eth_parameters.nonce = eth_parameters.nonce.max(min_nonce);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn confirm_operation(
         &self,
         _connection: &mut StorageProcessor<'_>,
@@ -379,6 +486,7 @@ pub(crate) async fn default_eth_sender() -> ETHSender<MockDatabase> {
         Vec::new(),
         Vec::new(),
         default_eth_parameters(),
+        Vec::new(),
     )
     .await
 }
@@ -393,6 +501,7 @@ pub(crate) async fn concurrent_eth_sender(max_txs_in_flight: u64) -> ETHSender<M
         Vec::new(),
         Vec::new(),
         default_eth_parameters(),
+        Vec::new(),
     )
     .await
 }
@@ -413,6 +522,24 @@ pub(crate) async fn restored_eth_sender(
         aggregated_operations,
         unprocessed_operations,
         eth_parameters,
+        Vec::new(),
+    )
+    .await
+}
+
+/// Creates an `ETHSender` with mock Ethereum connection/database and no operations in DB
+/// which sends transactions from the main and the provided additional operator accounts.
+pub(crate) async fn multi_operator_eth_sender(
+    max_txs_in_flight: u64,
+    additional_operators: Vec<Address>,
+) -> ETHSender<MockDatabase> {
+    build_eth_sender(
+        max_txs_in_flight,
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        default_eth_parameters(),
+        additional_operators,
     )
     .await
 }
@@ -424,8 +551,13 @@ async fn build_eth_sender(
     aggregated_operations: Vec<(i64, AggregatedOperation)>,
     unprocessed_operations: Vec<(i64, AggregatedOperation)>,
     eth_parameters: ETHParams,
+    additional_operators: Vec<Address>,
 ) -> ETHSender<MockDatabase> {
     let ethereum = EthereumGateway::Mock(MockEthereum::default());
+    let additional_operators = additional_operators
+        .into_iter()
+        .map(|address| (address, EthereumGateway::Mock(MockEthereum::default())))
+        .collect();
     let db = MockDatabase::with_restorable_state(
         eth_operations,
         aggregated_operations,
@@ -440,18 +572,23 @@ async fn build_eth_sender(
             wait_confirmations: super::WAIT_CONFIRMATIONS,
             tx_poll_period: 0,
             is_enabled: true,
+            nonce_reconciliation_interval: 0,
             operator_commit_eth_addr: Default::default(),
             operator_private_key: Default::default(),
+            additional_operator_private_keys: Vec::new(),
+            additional_operator_addresses: Vec::new(),
         },
         gas_price_limit: GasLimit {
             default: 1000,
             sample_interval: 15,
             update_interval: 15,
             scale_factor: 1.0f64,
+            priority_fee: 10,
+            use_legacy_transactions: false,
         },
     };
 
-    ETHSender::new(options, db, ethereum).await
+    ETHSender::new(options, db, ethereum, additional_operators).await
 }
 
 /// Behaves the same as `ETHSender::sign_new_tx`, but does not affect nonce.
@@ -464,10 +601,15 @@ pub(crate) async fn create_signed_tx(
     deadline_block: u64,
     nonce: i64,
 ) -> ETHOperation {
-    let options = Options {
+    let fees = eth_sender.gas_adjusters[0]
+        .get_suggested_fees(&eth_sender.ethereum, None)
+        .await
+        .unwrap();
+    let mut options = Options {
         nonce: Some(nonce.into()),
         ..Default::default()
     };
+    fees.apply(&mut options);
 
     let raw_tx = eth_sender.operation_to_raw_tx(&aggregated_operation.1);
     let signed_tx = eth_sender
@@ -483,8 +625,10 @@ pub(crate) async fn create_signed_tx(
         op_type,
         op: Some(aggregated_operation.clone()),
         nonce: signed_tx.nonce,
+        sender: None,
         last_deadline_block: deadline_block,
         last_used_gas_price: signed_tx.gas_price,
+        last_used_priority_fee: signed_tx.max_priority_fee_per_gas,
         used_tx_hashes: vec![signed_tx.hash],
         encoded_tx_data: raw_tx,
         confirmed: false,
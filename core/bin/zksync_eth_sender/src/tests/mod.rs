@@ -1,10 +1,10 @@
 // Local uses
 use self::mock::{
     concurrent_eth_sender, create_signed_tx, default_eth_parameters, default_eth_sender,
-    restored_eth_sender,
+    multi_operator_eth_sender, restored_eth_sender,
 };
 use super::{transactions::TxCheckOutcome, ETHSender, TxCheckMode};
-use web3::types::U64;
+use web3::types::{Address, U64};
 use zksync_eth_client::ethereum_gateway::ExecutedTxStatus;
 
 const EXPECTED_WAIT_TIME_BLOCKS: u64 = 30;
@@ -972,3 +972,252 @@ async fn concurrent_operations_order() {
         }
     }
 }
+
+/// Checks that the transaction dropped from the mempool is resubmitted
+/// during the nonce reconciliation.
+#[tokio::test]
+async fn nonce_reconciliation_resubmits_dropped_tx() {
+    let mut eth_sender = default_eth_sender().await;
+
+    let aggregated_operation = test_data::commit_blocks_operation(0);
+    eth_sender
+        .db
+        .send_aggregated_operation(aggregated_operation)
+        .await
+        .unwrap();
+    eth_sender.load_new_operations().await.unwrap();
+    eth_sender.proceed_next_operations(0).await;
+    let sent_op = eth_sender.ongoing_ops[0].clone();
+
+    // The transaction is evicted from the mempool, so the node doesn't know about the nonce 0.
+    let ethereum = eth_sender.ethereum.get_mut_mock().unwrap();
+    ethereum.drop_sent_txs().await;
+    ethereum.set_nonces(0.into(), 0.into()).await;
+
+    eth_sender.reconcile_nonces().await.unwrap();
+
+    // The same transaction is sent again.
+    assert_eq!(eth_sender.ongoing_ops[0], sent_op);
+    eth_sender.db.assert_stored(&sent_op).await;
+    eth_sender
+        .ethereum
+        .get_mock()
+        .unwrap()
+        .assert_sent(&sent_op.used_tx_hashes[0].as_bytes().to_vec())
+        .await;
+}
+
+/// Checks that if the nonce of the ongoing operation is consumed by another transaction,
+/// the operation along with the following ones gets new nonces and can be confirmed.
+#[tokio::test]
+async fn nonce_reconciliation_reassigns_consumed_nonce() {
+    let mut eth_sender = concurrent_eth_sender(2).await;
+
+    let commit_operation = test_data::commit_blocks_operation(0);
+    let verify_operation = test_data::publish_proof_blocks_onchain_operations(0);
+    for operation in [commit_operation, verify_operation] {
+        eth_sender
+            .db
+            .send_aggregated_operation(operation)
+            .await
+            .unwrap();
+    }
+    eth_sender.load_new_operations().await.unwrap();
+    eth_sender.proceed_next_operations(0).await;
+    let old_commit_op = eth_sender.ongoing_ops[0].clone();
+    let old_verify_op = eth_sender.ongoing_ops[1].clone();
+    assert_eq!(old_commit_op.nonce, 0.into());
+    assert_eq!(old_verify_op.nonce, 1.into());
+
+    // Nonce 0 is consumed by some other transaction, while the verify tx is still pending.
+    eth_sender
+        .ethereum
+        .get_mut_mock()
+        .unwrap()
+        .set_nonces(1.into(), 2.into())
+        .await;
+
+    eth_sender.reconcile_nonces().await.unwrap();
+
+    // Commit operation takes the nonce of the verify one, and the verify operation
+    // gets the next nonce from the sequence.
+    let commit_op = eth_sender.ongoing_ops[0].clone();
+    let verify_op = eth_sender.ongoing_ops[1].clone();
+    assert_eq!(commit_op.nonce, 1.into());
+    assert_eq!(verify_op.nonce, 2.into());
+    // Replacement of the pending verify tx must pay more than the replaced tx.
+    assert!(commit_op.last_used_gas_price > old_verify_op.last_used_gas_price);
+    for (op, old_op) in [(&commit_op, &old_commit_op), (&verify_op, &old_verify_op)] {
+        assert_eq!(op.used_tx_hashes.len(), 2);
+        assert_eq!(op.used_tx_hashes[0], old_op.used_tx_hashes[0]);
+        eth_sender.db.assert_stored(op).await;
+        eth_sender
+            .ethereum
+            .get_mock()
+            .unwrap()
+            .assert_sent(&op.used_tx_hashes[1].as_bytes().to_vec())
+            .await;
+    }
+
+    // The new transactions are mined, and the operations are confirmed.
+    for op in [&commit_op, &verify_op] {
+        eth_sender
+            .ethereum
+            .get_mut_mock()
+            .unwrap()
+            .add_successfull_execution(op.used_tx_hashes[1], WAIT_CONFIRMATIONS)
+            .await;
+    }
+    eth_sender.proceed_next_operations(0).await;
+
+    for mut op in [commit_op, verify_op] {
+        op.confirmed = true;
+        op.final_hash = Some(op.used_tx_hashes[1]);
+        eth_sender.db.assert_confirmed(&op).await;
+    }
+}
+
+/// Checks that nonces consumed by the transactions sent not by `ETHSender`
+/// are not assigned to the new operations.
+#[tokio::test]
+async fn nonce_reconciliation_skips_foreign_nonces() {
+    let mut eth_sender = default_eth_sender().await;
+
+    eth_sender
+        .ethereum
+        .get_mut_mock()
+        .unwrap()
+        .set_nonces(5.into(), 7.into())
+        .await;
+    eth_sender.reconcile_nonces().await.unwrap();
+
+    eth_sender
+        .db
+        .send_aggregated_operation(test_data::commit_blocks_operation(0))
+        .await
+        .unwrap();
+    eth_sender.load_new_operations().await.unwrap();
+    eth_sender.proceed_next_operations(0).await;
+
+    assert_eq!(eth_sender.ongoing_ops[0].nonce, 7.into());
+}
+
+/// Checks that the operations are spread between the operator accounts, each with its own
+/// nonce sequence, and that the operations which don't depend on each other are sent
+/// by different accounts at the same time.
+#[tokio::test]
+async fn multiple_operators() {
+    let additional_operator = Address::repeat_byte(0x01);
+    let mut eth_sender = multi_operator_eth_sender(2, vec![additional_operator]).await;
+
+    let operations = [
+        test_data::commit_blocks_operation(0),
+        test_data::commit_blocks_operation(1),
+        test_data::publish_proof_blocks_onchain_operations(0),
+        test_data::execute_blocks_operations(0),
+    ];
+    for operation in operations.iter().cloned() {
+        eth_sender
+            .db
+            .send_aggregated_operation(operation)
+            .await
+            .unwrap();
+    }
+    eth_sender.load_new_operations().await.unwrap();
+
+    // Commits must be executed in order, so both are sent by the main account.
+    // The verify waits for the commit, since the main account has no capacity left.
+    eth_sender.proceed_next_operations(0).await;
+    assert_eq!(eth_sender.ongoing_ops.len(), 2);
+    let commit_1 = eth_sender.ongoing_ops[0].clone();
+    let commit_2 = eth_sender.ongoing_ops[1].clone();
+    for (nonce, op) in [&commit_1, &commit_2].iter().enumerate() {
+        assert_eq!(op.sender, None);
+        assert_eq!(op.nonce, nonce.into());
+    }
+
+    eth_sender
+        .ethereum
+        .get_mut_mock()
+        .unwrap()
+        .add_successfull_execution(commit_1.used_tx_hashes[0], WAIT_CONFIRMATIONS)
+        .await;
+    eth_sender.proceed_next_operations(0).await;
+    let mut confirmed_commit = commit_1;
+    confirmed_commit.confirmed = true;
+    confirmed_commit.final_hash = Some(confirmed_commit.used_tx_hashes[0]);
+    eth_sender.db.assert_confirmed(&confirmed_commit).await;
+
+    // Once the first commit is confirmed, the verify and the execute for the first block
+    // don't depend on the pending commit, so they're sent by the idle account.
+    eth_sender.proceed_next_operations(0).await;
+    assert_eq!(eth_sender.ongoing_ops.len(), 3);
+    assert_eq!(eth_sender.ongoing_ops[0], commit_2);
+    for (nonce, (op, operation)) in eth_sender
+        .ongoing_ops
+        .iter()
+        .skip(1)
+        .zip(&operations[2..])
+        .enumerate()
+    {
+        assert_eq!(op.op.as_ref().map(|(id, _)| *id), Some(operation.0));
+        assert_eq!(op.sender, Some(additional_operator));
+        // The nonce sequence of the additional account is initialized on startup.
+        assert_eq!(op.nonce, nonce.into());
+        eth_sender.db.assert_stored(op).await;
+        eth_sender
+            .ethereum
+            .get_mock()
+            .unwrap()
+            .assert_sent(&op.used_tx_hashes[0].as_bytes().to_vec())
+            .await;
+    }
+}
+
+/// Checks that the operations sent by the operator account which is removed from the config
+/// are moved to the main account and resent by it.
+#[tokio::test]
+async fn removed_operator_operations_are_reassigned() {
+    let removed_operator = Address::repeat_byte(0x03);
+    let commit_operation = test_data::commit_blocks_operation(0);
+    let stored_op = {
+        // This `eth_sender` is required to generate the input only.
+        let eth_sender = default_eth_sender().await;
+        let deadline_block = eth_sender.get_deadline_block(1);
+        let mut op =
+            create_signed_tx(0, &eth_sender, commit_operation.clone(), deadline_block, 0).await;
+        op.sender = Some(removed_operator);
+        op
+    };
+
+    let mut eth_parameters = default_eth_parameters();
+    eth_parameters.last_committed_block = 1;
+    let mut eth_sender = restored_eth_sender(
+        vec![stored_op.clone()],
+        vec![commit_operation],
+        Vec::new(),
+        eth_parameters,
+    )
+    .await;
+
+    // The operation gets the nonce from the sequence of the main account
+    // and is considered stuck.
+    let op = eth_sender.ongoing_ops[0].clone();
+    assert_eq!(op.sender, None);
+    assert_eq!(op.nonce, 0.into());
+    assert_eq!(op.last_deadline_block, 0);
+    eth_sender.db.assert_stored(&op).await;
+
+    // The replacement transaction is signed by the main account, while the old one is still tracked.
+    eth_sender.proceed_next_operations(0).await;
+    let op = eth_sender.ongoing_ops[0].clone();
+    assert_eq!(op.used_tx_hashes.len(), 2);
+    assert_eq!(op.used_tx_hashes[0], stored_op.used_tx_hashes[0]);
+    eth_sender.db.assert_stored(&op).await;
+    eth_sender
+        .ethereum
+        .get_mock()
+        .unwrap()
+        .assert_sent(&op.used_tx_hashes[1].as_bytes().to_vec())
+        .await;
+}
@@ -6,7 +6,7 @@ use zksync_types::{
 // External uses
 use anyhow::format_err;
 // Local imports
-use self::operation_queue::OperationQueue;
+use self::operation_queue::{OperationQueue, SentOperation};
 
 mod operation_queue;
 
@@ -23,6 +23,9 @@ pub struct TxData {
     pub raw: RawTxData,
     /// Optional zkSync operation.
     pub operation: (i64, AggregatedOperation),
+    /// Index of the operator account which should send the transaction.
+    /// Assigned by the `TxQueue` when the transaction is popped.
+    pub operator: usize,
 }
 
 impl PartialEq for TxData {
@@ -38,6 +41,7 @@ impl TxData {
             op_type: operation.1.get_action_type(),
             raw,
             operation,
+            operator: 0,
         }
     }
 
@@ -53,7 +57,8 @@ impl TxData {
 #[derive(Debug)]
 pub struct TxQueueBuilder {
     max_pending_txs: usize,
-    sent_pending_txs: usize,
+    operators_count: usize,
+    pending_operations: Vec<(AggregatedActionType, SentOperation)>,
 
     commit_operations_count: usize,
     verify_operations_count: usize,
@@ -62,24 +67,46 @@ pub struct TxQueueBuilder {
 
 impl TxQueueBuilder {
     /// Initializes queue building process.
+    /// `max_pending_txs` limits the amount of transactions in flight for every operator account.
     pub fn new(max_pending_txs: usize) -> Self {
         Self {
             max_pending_txs,
-            sent_pending_txs: 0,
+            operators_count: 1,
+            pending_operations: Vec::new(),
             commit_operations_count: 0,
             verify_operations_count: 0,
             execute_operations_count: 0,
         }
     }
 
-    /// Sets the amount of transactions sent to the Ethereum blockchain, but not confirmed yet.
-    pub fn with_sent_pending_txs(self, sent_pending_txs: usize) -> Self {
+    /// Sets the amount of operator accounts sending the transactions.
+    pub fn with_operators_count(self, operators_count: usize) -> Self {
         Self {
-            sent_pending_txs,
+            operators_count,
             ..self
         }
     }
 
+    /// Adds the operation sent to the Ethereum blockchain by the `operator` account,
+    /// but not confirmed yet.
+    /// Operations of the same type preceding the first pending one are considered confirmed.
+    pub fn with_pending_operation(
+        mut self,
+        op_type: AggregatedActionType,
+        block_range: (BlockNumber, BlockNumber),
+        operator: usize,
+    ) -> Self {
+        self.pending_operations.push((
+            op_type,
+            SentOperation {
+                first_block: block_range.0,
+                last_block: block_range.1,
+                operator,
+            },
+        ));
+        self
+    }
+
     /// Sets the amount of operations sent for the `commit` queue.
     pub fn with_commit_operations_count(self, commit_operations_count: usize) -> Self {
         Self {
@@ -106,19 +133,41 @@ impl TxQueueBuilder {
 
     /// Finishes the queue building process.
     pub fn build(self) -> TxQueue {
+        let mut sent_pending_txs = vec![0; self.operators_count];
+        for (_, op) in &self.pending_operations {
+            assert!(
+                op.operator < self.operators_count,
+                "Pending operation is sent by an unknown operator account"
+            );
+            sent_pending_txs[op.operator] += 1;
+        }
+
+        let pending_operations = &self.pending_operations;
+        let operation_queue = |op_type: AggregatedActionType, operations_count: usize| {
+            let unconfirmed = pending_operations
+                .iter()
+                .filter(|(pending_type, _)| *pending_type == op_type)
+                .map(|(_, op)| *op)
+                .collect();
+            OperationQueue::new(BlockNumber(operations_count as u32), unconfirmed)
+        };
+
         TxQueue {
             max_pending_txs: self.max_pending_txs,
-            sent_pending_txs: self.sent_pending_txs,
-
-            commit_operations: OperationQueue::new(BlockNumber(
-                self.commit_operations_count as u32,
-            )),
-            verify_operations: OperationQueue::new(BlockNumber(
-                self.verify_operations_count as u32,
-            )),
-            execute_operations: OperationQueue::new(BlockNumber(
-                self.execute_operations_count as u32,
-            )),
+            sent_pending_txs,
+
+            commit_operations: operation_queue(
+                AggregatedActionType::CommitBlocks,
+                self.commit_operations_count,
+            ),
+            verify_operations: operation_queue(
+                AggregatedActionType::PublishProofBlocksOnchain,
+                self.verify_operations_count,
+            ),
+            execute_operations: operation_queue(
+                AggregatedActionType::ExecuteBlocks,
+                self.execute_operations_count,
+            ),
         }
     }
 }
@@ -127,19 +176,26 @@ impl TxQueueBuilder {
 /// the transaction sending policy. It chooses the next operation to send out of
 /// these queues, using the following rules:
 ///
-/// 1. If the amount of sent transactions is equal to the `MAX_PENDING_TXS` value,
-///   no transaction is yielded until some of already sent ones are committed.
-/// 2. Otherwise, transactions are yielded according to the following policy:
+/// 1. Operations are yielded according to the following policy:
 ///   - If `execute` queue contains elements for some blocks, and `verify` operations
 ///     for corresponding blocks is committed, the `execute` operation is yielded.
 ///   - If `verify` queue contains elements for some blocks, and `commit` operations
 ///     for corresponding blocks is committed, the `verify` operation is yielded.
 ///   - Otherwise, if `commit` queue is not empty, a `commit` operation is yielded.
-/// 3. If all the queues are empty, no operation is returned.
+/// 2. Every operation is assigned to one of the operator accounts, see `TxQueue::select_operator`.
+///   An operation must be executed after the previous operation of the same type and after
+///   the operations it depends on. Each of them must either be confirmed, or be sent by the
+///   same operator account, since the nonce guarantees the order of execution.
+///   Among the accounts satisfying this, the least loaded one is chosen. If the amount of
+///   transactions sent by the operator account is equal to the `MAX_PENDING_TXS` value, it can't
+///   send more transactions until some of already sent ones are committed.
+/// 3. If all the queues are empty or no operator account can send the next operations,
+///   no operation is returned.
 #[derive(Debug)]
 pub struct TxQueue {
     max_pending_txs: usize,
-    sent_pending_txs: usize,
+    /// Amount of transactions in flight for every operator account.
+    sent_pending_txs: Vec<usize>,
 
     commit_operations: OperationQueue,
     verify_operations: OperationQueue,
@@ -153,7 +209,7 @@ impl TxQueue {
 
         vlog::info!(
             "Adding commit operation to the queue. \
-            Sent pending txs count: {}, \
+            Sent pending txs count: {:?}, \
             max pending txs count: {}, \
             size of commit queue: {}",
             self.sent_pending_txs,
@@ -169,7 +225,7 @@ impl TxQueue {
 
         vlog::info!(
             "Adding verify operation to the queue. \
-            Sent pending txs count: {}, \
+            Sent pending txs count: {:?}, \
             max pending txs count: {}, \
             size of verify queue: {}",
             self.sent_pending_txs,
@@ -185,7 +241,7 @@ impl TxQueue {
 
         vlog::info!(
             "Adding execute operation to the queue. \
-            Sent pending txs count: {}, \
+            Sent pending txs count: {:?}, \
             max pending txs count: {}, \
             size of execute queue: {}",
             self.sent_pending_txs,
@@ -197,8 +253,9 @@ impl TxQueue {
 
     /// Returns a previously popped element to the front of the queue.
     pub fn return_popped(&mut self, element: TxData) -> anyhow::Result<()> {
+        let operator = element.operator;
         assert!(
-            self.sent_pending_txs > 0,
+            self.sent_pending_txs[operator] > 0,
             "No transactions are expected to be returned"
         );
 
@@ -221,59 +278,130 @@ impl TxQueue {
 
         // We've incremented the counter when transaction was popped.
         // Now it's returned and counter should be decremented back.
-        self.sent_pending_txs -= 1;
+        self.sent_pending_txs[operator] -= 1;
         Ok(())
     }
 
     /// Gets the next transaction to send, according to the transaction sending policy.
     /// For details, see the structure doc-comment.
     pub fn pop_front(&mut self) -> Option<TxData> {
-        if self.sent_pending_txs >= self.max_pending_txs {
-            return None;
-        }
+        let (op_type, operator) = self.get_next_operation()?;
+        let queue = match op_type {
+            AggregatedActionType::CommitBlocks => &mut self.commit_operations,
+            AggregatedActionType::PublishProofBlocksOnchain => &mut self.verify_operations,
+            AggregatedActionType::ExecuteBlocks => &mut self.execute_operations,
+            AggregatedActionType::CreateProofBlocks => {
+                unreachable!("Proof creation is never queued")
+            }
+        };
+
+        // Take the operation and increment the sent counter of its operator.
+        let mut op = queue.pop_front()?;
+        queue.report_sent(&op, operator);
+        op.operator = operator;
+        self.sent_pending_txs[operator] += 1;
+        Some(op)
+    }
 
-        // Get the next operation and increment the sent counter if needed.
-        match self.get_next_operation() {
-            Some(op) => {
-                self.sent_pending_txs += 1;
-                Some(op)
+    /// Chooses the operator account to send the next operation of the `queue`, which affects
+    /// the blocks up to `last_block`, or returns `None` if it can't be sent right now.
+    ///
+    /// The operation must be executed after the unconfirmed operations of the same type and
+    /// the unconfirmed operations of the `required_queue` for the same blocks. If all of them
+    /// are sent by one account, only this account may send the operation. If there are
+    /// no such operations, any account may send it, and the least loaded one is chosen.
+    fn select_operator(
+        &self,
+        queue: &OperationQueue,
+        required_queue: Option<&OperationQueue>,
+        last_block: BlockNumber,
+    ) -> Option<usize> {
+        let mut operators = queue.unconfirmed_operators(last_block);
+        if let Some(required_queue) = required_queue {
+            if *required_queue.get_last_block_number() < *last_block {
+                // The required operations are not sent yet.
+                return None;
             }
-            None => None,
+            operators.extend(required_queue.unconfirmed_operators(last_block));
+            operators.sort_unstable();
+            operators.dedup();
+        }
+
+        let has_capacity =
+            |operator: &usize| self.sent_pending_txs[*operator] < self.max_pending_txs;
+        match operators.as_slice() {
+            [] => (0..self.sent_pending_txs.len())
+                .filter(has_capacity)
+                .min_by_key(|operator| self.sent_pending_txs[*operator]),
+            [operator] => Some(*operator).filter(has_capacity),
+            // Operations sent by different accounts may be executed in any order,
+            // so we have to wait for some of them to be confirmed.
+            _ => None,
         }
     }
 
-    /// Obtains the next operation from the underlying queues.
+    /// Chooses the type of the next operation to send along with the operator account to send it.
     /// This method does not use/affect `sent_pending_tx` counter.
-    fn get_next_operation(&mut self) -> Option<TxData> {
+    fn get_next_operation(&self) -> Option<(AggregatedActionType, usize)> {
         // 1. Highest priority: execute operations.
         if let Some(next_execute_block) = self.execute_operations.get_next_last_block_number() {
-            let current_verify_block = self.verify_operations.get_last_block_number();
-            if *next_execute_block <= *current_verify_block {
-                return Some(self.execute_operations.pop_front().unwrap());
+            if let Some(operator) = self.select_operator(
+                &self.execute_operations,
+                Some(&self.verify_operations),
+                next_execute_block,
+            ) {
+                return Some((AggregatedActionType::ExecuteBlocks, operator));
             }
         }
 
         // 2. After execute operations we should process verify operation.
         if let Some(next_verify_block) = self.verify_operations.get_next_last_block_number() {
-            let current_commit_block = self.commit_operations.get_last_block_number();
-            if *next_verify_block <= *current_commit_block {
-                return Some(self.verify_operations.pop_front().unwrap());
+            if let Some(operator) = self.select_operator(
+                &self.verify_operations,
+                Some(&self.commit_operations),
+                next_verify_block,
+            ) {
+                return Some((AggregatedActionType::PublishProofBlocksOnchain, operator));
             }
         }
 
         // 3. Finally, check the commit queue.
-        self.commit_operations.pop_front()
+        if let Some(next_commit_block) = self.commit_operations.get_next_last_block_number() {
+            if let Some(operator) =
+                self.select_operator(&self.commit_operations, None, next_commit_block)
+            {
+                return Some((AggregatedActionType::CommitBlocks, operator));
+            }
+        }
+        None
     }
 
     /// Notifies the queue about the transaction being confirmed on the Ethereum blockchain.
-    /// Decrements the amount of transactions "in the fly".
-    pub fn report_commitment(&mut self) {
+    /// Decrements the amount of transactions "in the fly" for the operator account that sent it.
+    pub fn report_commitment(
+        &mut self,
+        operator: usize,
+        op_type: AggregatedActionType,
+        last_block: BlockNumber,
+    ) {
         assert!(
-            self.sent_pending_txs > 0,
+            self.sent_pending_txs[operator] > 0,
             "No transactions are expected to be confirmed"
         );
 
-        self.sent_pending_txs -= 1;
+        self.sent_pending_txs[operator] -= 1;
+        match op_type {
+            AggregatedActionType::CommitBlocks => {
+                self.commit_operations.report_confirmed(last_block)
+            }
+            AggregatedActionType::PublishProofBlocksOnchain => {
+                self.verify_operations.report_confirmed(last_block)
+            }
+            AggregatedActionType::ExecuteBlocks => {
+                self.execute_operations.report_confirmed(last_block)
+            }
+            AggregatedActionType::CreateProofBlocks => {}
+        }
     }
 }
 
@@ -367,7 +495,7 @@ mod tests {
         assert_eq!(queue.pop_front(), None);
 
         // Report that one operation is completed.
-        queue.report_commitment();
+        queue.report_commitment(op_1.operator, op_1.op_type, op_1.get_block_range().1);
 
         // Now we should obtain the next commit operation.
         let op_4 = queue.pop_front().unwrap();
@@ -377,11 +505,11 @@ mod tests {
         assert_eq!(queue.pop_front(), None);
 
         // Report the remaining three operations as completed.
-        assert_eq!(queue.sent_pending_txs, MAX_IN_FLY);
-        for _ in 0..MAX_IN_FLY {
-            queue.report_commitment();
+        assert_eq!(queue.sent_pending_txs, vec![MAX_IN_FLY]);
+        for op in [op_2, op_3, op_4] {
+            queue.report_commitment(op.operator, op.op_type, op.get_block_range().1);
         }
-        assert_eq!(queue.sent_pending_txs, 0);
+        assert_eq!(queue.sent_pending_txs, vec![0]);
 
         // Pop remaining operations.
         let op_5 = queue.pop_front().unwrap();
@@ -393,19 +521,19 @@ mod tests {
         // Though the limit is not met (2 txs in fly, and limit is 3), there should be no txs in the queue.
         assert_eq!(queue.pop_front(), None);
 
-        let pending_count = queue.sent_pending_txs;
+        let pending_count = queue.sent_pending_txs[0];
 
         // Return the operation to the queue.
         queue.return_popped(op_6).unwrap();
 
         // Now, as we've returned tx to queue, pending count should be decremented.
-        assert_eq!(queue.sent_pending_txs, pending_count - 1);
+        assert_eq!(queue.sent_pending_txs[0], pending_count - 1);
 
         let op_6 = queue.pop_front().unwrap();
         assert_eq!(op_6.raw, vec![EXECUTE_MARK, 1]);
 
         // We've popped the tx once again, now pending count should be increased.
-        assert_eq!(queue.sent_pending_txs, pending_count);
+        assert_eq!(queue.sent_pending_txs[0], pending_count);
     }
 
     #[test]
@@ -424,4 +552,144 @@ mod tests {
             ))
             .unwrap();
     }
+
+    /// Checks that operations are spread between the operator accounts, and that an operation
+    /// sent by another account waits for the operations it depends on to be confirmed.
+    #[test]
+    fn multiple_operators() {
+        const MAX_IN_FLY: usize = 2;
+        const COMMIT_MARK: u8 = 0;
+        const VERIFY_MARK: u8 = 1;
+        const EXECUTE_MARK: u8 = 2;
+
+        let mut queue = TxQueueBuilder::new(MAX_IN_FLY)
+            .with_operators_count(2)
+            .build();
+
+        for block in 1..=3 {
+            queue
+                .add_commit_operation(get_tx_data(
+                    AggregatedActionType::CommitBlocks,
+                    BlockNumber(block),
+                    vec![COMMIT_MARK, block as u8],
+                ))
+                .unwrap();
+            queue
+                .add_verify_operation(get_tx_data(
+                    AggregatedActionType::PublishProofBlocksOnchain,
+                    BlockNumber(block),
+                    vec![VERIFY_MARK, block as u8],
+                ))
+                .unwrap();
+            queue
+                .add_execute_operation(get_tx_data(
+                    AggregatedActionType::ExecuteBlocks,
+                    BlockNumber(block),
+                    vec![EXECUTE_MARK, block as u8],
+                ))
+                .unwrap();
+        }
+
+        // Only commits can be sent, since nothing is confirmed yet. Commits must be executed
+        // in order, so the second one is sent by the same operator as the first one.
+        let commit_1 = queue.pop_front().unwrap();
+        assert_eq!(commit_1.raw, vec![COMMIT_MARK, 1]);
+        assert_eq!(commit_1.operator, 0);
+        let commit_2 = queue.pop_front().unwrap();
+        assert_eq!(commit_2.raw, vec![COMMIT_MARK, 2]);
+        assert_eq!(commit_2.operator, 0);
+        // The commit operator has reached the limit, and the verify depends on its commit.
+        assert_eq!(queue.pop_front(), None);
+
+        // Once the first commit is confirmed, the verify doesn't depend on any unconfirmed
+        // operation, so it's sent by the least loaded operator.
+        queue.report_commitment(
+            commit_1.operator,
+            commit_1.op_type,
+            commit_1.get_block_range().1,
+        );
+        let verify_1 = queue.pop_front().unwrap();
+        assert_eq!(verify_1.raw, vec![VERIFY_MARK, 1]);
+        assert_eq!(verify_1.operator, 1);
+
+        // Execute depends on the verify sent by the second operator, so it's sent by the same one.
+        let execute_1 = queue.pop_front().unwrap();
+        assert_eq!(execute_1.raw, vec![EXECUTE_MARK, 1]);
+        assert_eq!(execute_1.operator, 1);
+
+        // The next verify depends on operations sent by both operators, so it has to wait.
+        // Meanwhile, the commit operator has capacity for the next commit.
+        let commit_3 = queue.pop_front().unwrap();
+        assert_eq!(commit_3.raw, vec![COMMIT_MARK, 3]);
+        assert_eq!(commit_3.operator, 0);
+        assert_eq!(queue.pop_front(), None);
+        assert_eq!(queue.sent_pending_txs, vec![2, 2]);
+
+        queue.report_commitment(
+            verify_1.operator,
+            verify_1.op_type,
+            verify_1.get_block_range().1,
+        );
+        // The verify now depends only on the commit sent by the first operator, which is busy.
+        assert_eq!(queue.pop_front(), None);
+
+        queue.report_commitment(
+            commit_2.operator,
+            commit_2.op_type,
+            commit_2.get_block_range().1,
+        );
+        let verify_2 = queue.pop_front().unwrap();
+        assert_eq!(verify_2.raw, vec![VERIFY_MARK, 2]);
+        assert_eq!(verify_2.operator, 0);
+        assert_eq!(queue.sent_pending_txs, vec![2, 1]);
+
+        // Returned operation is accounted for the operator which has popped it.
+        queue.return_popped(verify_2).unwrap();
+        assert_eq!(queue.sent_pending_txs, vec![1, 1]);
+    }
+
+    /// Checks that the unconfirmed operations restored after the restart are taken into account.
+    #[test]
+    fn restored_pending_operations() {
+        const VERIFY_MARK: u8 = 1;
+
+        let mut queue = TxQueueBuilder::new(2)
+            .with_operators_count(2)
+            .with_commit_operations_count(2)
+            .with_pending_operation(
+                AggregatedActionType::CommitBlocks,
+                (BlockNumber(2), BlockNumber(2)),
+                1,
+            )
+            .build();
+        assert_eq!(queue.sent_pending_txs, vec![0, 1]);
+
+        // The first block is confirmed, so its verify may be sent by any operator.
+        queue
+            .add_verify_operation(get_tx_data(
+                AggregatedActionType::PublishProofBlocksOnchain,
+                BlockNumber(1),
+                vec![VERIFY_MARK, 1],
+            ))
+            .unwrap();
+        let verify_1 = queue.pop_front().unwrap();
+        assert_eq!(verify_1.operator, 0);
+
+        // The second commit is sent by the second operator and isn't confirmed yet.
+        queue
+            .add_verify_operation(get_tx_data(
+                AggregatedActionType::PublishProofBlocksOnchain,
+                BlockNumber(2),
+                vec![VERIFY_MARK, 2],
+            ))
+            .unwrap();
+        assert_eq!(queue.pop_front(), None);
+        queue.report_commitment(
+            verify_1.operator,
+            verify_1.op_type,
+            verify_1.get_block_range().1,
+        );
+        let verify_2 = queue.pop_front().unwrap();
+        assert_eq!(verify_2.operator, 1);
+    }
 }
@@ -6,30 +6,55 @@ use anyhow::format_err;
 use crate::tx_queue::TxData;
 use zksync_types::BlockNumber;
 
+/// Operation sent to the Ethereum blockchain, but not confirmed yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentOperation {
+    pub first_block: BlockNumber,
+    pub last_block: BlockNumber,
+    /// Index of the operator account that has sent the operation.
+    pub operator: usize,
+}
+
 /// Ethereum Transaction queue is basically a queue which
-/// contains `TxData` and tracks the last popped block number.
+/// contains `TxData` and tracks the last popped block number
+/// along with the last block of the confirmed operations.
+/// Besides that, it tracks the operator accounts which have sent the operations
+/// that aren't confirmed yet.
 ///
 /// Must receive operations in ascending order of affected blocks.
 #[derive(Debug)]
 pub struct OperationQueue {
     pub(super) elements: VecDeque<TxData>,
     last_block_number: BlockNumber,
+    last_confirmed_block_number: BlockNumber,
+    unconfirmed: VecDeque<SentOperation>,
 }
 
 impl Default for OperationQueue {
     fn default() -> Self {
         Self {
             last_block_number: BlockNumber(0),
+            last_confirmed_block_number: BlockNumber(0),
             elements: VecDeque::new(),
+            unconfirmed: VecDeque::new(),
         }
     }
 }
 
 impl OperationQueue {
-    /// Creates a new empty counter queue with the custom `last_block_number`.
-    pub fn new(last_block_number: BlockNumber) -> Self {
+    /// Creates a new empty counter queue with the custom `last_block_number`
+    /// and the operations sent, but not confirmed yet.
+    /// Operations before the first unconfirmed one are considered confirmed.
+    pub fn new(last_block_number: BlockNumber, mut unconfirmed: Vec<SentOperation>) -> Self {
+        unconfirmed.sort_by_key(|op| op.first_block);
+        let last_confirmed_block_number = unconfirmed
+            .first()
+            .map(|op| op.first_block - 1)
+            .unwrap_or(last_block_number);
         Self {
             last_block_number,
+            last_confirmed_block_number,
+            unconfirmed: unconfirmed.into(),
             ..Default::default()
         }
     }
@@ -41,6 +66,9 @@ impl OperationQueue {
         }
 
         self.last_block_number = BlockNumber(*element.get_block_range().0 - 1);
+        if self.unconfirmed.back().map(|op| op.last_block) == Some(element.get_block_range().1) {
+            self.unconfirmed.pop_back();
+        }
         self.elements.push_front(element);
 
         Ok(())
@@ -86,6 +114,45 @@ impl OperationQueue {
         self.last_block_number
     }
 
+    /// Returns the last block affected by the confirmed operations.
+    pub fn get_last_confirmed_block_number(&self) -> BlockNumber {
+        self.last_confirmed_block_number
+    }
+
+    /// Notifies the queue about the popped operation being sent by the `operator` account.
+    pub fn report_sent(&mut self, element: &TxData, operator: usize) {
+        let (first_block, last_block) = element.get_block_range();
+        self.unconfirmed.push_back(SentOperation {
+            first_block,
+            last_block,
+            operator,
+        });
+    }
+
+    /// Notifies the queue about the operation affecting blocks up to `last_block_number`
+    /// being confirmed on the Ethereum blockchain.
+    pub fn report_confirmed(&mut self, last_block_number: BlockNumber) {
+        self.last_confirmed_block_number =
+            std::cmp::max(self.last_confirmed_block_number, last_block_number);
+        let last_confirmed_block_number = self.last_confirmed_block_number;
+        self.unconfirmed
+            .retain(|op| op.last_block > last_confirmed_block_number);
+    }
+
+    /// Returns the operator accounts of the sent, but not confirmed operations which
+    /// affect the blocks up to `block_number`.
+    pub fn unconfirmed_operators(&self, block_number: BlockNumber) -> Vec<usize> {
+        let mut operators: Vec<_> = self
+            .unconfirmed
+            .iter()
+            .filter(|op| op.first_block <= block_number)
+            .map(|op| op.operator)
+            .collect();
+        operators.sort_unstable();
+        operators.dedup();
+        operators
+    }
+
     /// Returns the value of the next affected block
     /// if will pop the top item out of the queue.
     pub fn get_next_last_block_number(&self) -> Option<BlockNumber> {
@@ -109,7 +176,7 @@ mod tests {
     /// Checks the main operations of the queue: `push_back`, `pop_front` and `get_count`.
     #[test]
     fn basic_operations() {
-        let mut queue: OperationQueue = OperationQueue::new(BlockNumber(0));
+        let mut queue: OperationQueue = OperationQueue::new(BlockNumber(0), Vec::new());
 
         // Create aggregate operations.
 
@@ -166,5 +233,18 @@ mod tests {
         assert_eq!(queue.pop_front().unwrap(), tx_data_2);
         assert_eq!(queue.get_last_block_number(), BlockNumber(2));
         assert!(queue.get_next_last_block_number().is_none());
+
+        // Confirmations are tracked separately and never move back.
+        queue.report_sent(&tx_data_1, 0);
+        queue.report_sent(&tx_data_2, 1);
+        assert_eq!(queue.unconfirmed_operators(BlockNumber(1)), vec![0]);
+        assert_eq!(queue.unconfirmed_operators(BlockNumber(2)), vec![0, 1]);
+        assert_eq!(queue.get_last_confirmed_block_number(), BlockNumber(0));
+        queue.report_confirmed(BlockNumber(1));
+        assert_eq!(queue.unconfirmed_operators(BlockNumber(2)), vec![1]);
+        queue.report_confirmed(BlockNumber(2));
+        queue.report_confirmed(BlockNumber(1));
+        assert_eq!(queue.get_last_confirmed_block_number(), BlockNumber(2));
+        assert!(queue.unconfirmed_operators(BlockNumber(2)).is_empty());
     }
 }
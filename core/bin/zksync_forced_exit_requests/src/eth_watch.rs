@@ -1,6 +1,6 @@
 use chrono::{DateTime, TimeZone, Utc};
 use ethabi::{Address, Hash};
-use futures::channel::mpsc;
+use futures::{channel::mpsc, StreamExt};
 use std::{
     convert::TryFrom,
     ops::Sub,
@@ -21,7 +21,11 @@ use zksync_storage::ConnectionPool;
 use zksync_contracts::forced_exit_contract;
 use zksync_types::H160;
 
-use zksync_core::eth_watch::{get_web3_block_number, WatcherMode};
+use zksync_core::eth_watch::{
+    get_web3_block_number, run_poll_trigger,
+    subscription::{EthSubscriber, EthWsSubscriber, L1EventStream, PendingPoll, PollTrigger},
+    WatcherMode,
+};
 use zksync_mempool::MempoolTransactionRequest;
 use zksync_types::forced_exit_requests::FundsReceivedEvent;
 
@@ -37,6 +41,7 @@ use super::ForcedExitSender;
 /// before repeating the request.
 const RATE_LIMIT_DELAY: Duration = Duration::from_secs(30);
 
+#[derive(Debug, Clone)]
 struct ContractTopics {
     pub funds_received: Hash,
 }
@@ -53,7 +58,7 @@ impl ContractTopics {
 }
 
 #[async_trait::async_trait]
-pub trait EthClient {
+pub trait EthClient: EthSubscriber {
     async fn get_funds_received_events(
         &self,
         from: u64,
@@ -62,22 +67,27 @@ pub trait EthClient {
     async fn block_number(&self) -> anyhow::Result<u64>;
 }
 
+#[derive(Clone)]
 pub struct EthHttpClient {
     web3: Web3<Http>,
     forced_exit_contract: Contract<Http>,
     topics: ContractTopics,
+    subscriber: Option<EthWsSubscriber>,
 }
 
 impl EthHttpClient {
-    pub fn new(web3: Web3<Http>, zksync_contract_addr: H160) -> Self {
+    pub fn new(web3: Web3<Http>, zksync_contract_addr: H160, web3_ws_url: Option<String>) -> Self {
         let forced_exit_contract =
             Contract::new(web3.eth(), zksync_contract_addr, forced_exit_contract());
 
         let topics = ContractTopics::new(forced_exit_contract.abi());
+        let subscriber =
+            web3_ws_url.map(|url| EthWsSubscriber::new(url, vec![zksync_contract_addr]));
         Self {
             web3,
             forced_exit_contract,
             topics,
+            subscriber,
         }
     }
 
@@ -99,6 +109,16 @@ impl EthHttpClient {
     }
 }
 
+#[async_trait::async_trait]
+impl EthSubscriber for EthHttpClient {
+    async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>> {
+        match &self.subscriber {
+            Some(subscriber) => subscriber.subscribe().await,
+            None => Ok(None),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl EthClient for EthHttpClient {
     async fn get_funds_received_events(
@@ -346,7 +366,10 @@ where
         }
     }
 
-    pub async fn run(mut self) {
+    pub async fn run(mut self)
+    where
+        Client: Clone + Send + Sync + 'static,
+    {
         // As infura may be not responsive, we want to retry the query until we've actually got the
         // block number.
         // Normally, however, this loop is not expected to last more than one iteration.
@@ -374,10 +397,21 @@ where
             .await
             .expect("Failed to restore state for ForcedExit eth_watcher");
 
-        let mut timer = time::interval(self.config.poll_interval());
+        // Polls are requested the same way as for the main Ethereum watcher, so the notifications
+        // received while the poll is pending don't result in the extra polls.
+        let pending_poll = PendingPoll::default();
+        let (poll_sender, mut poll_receiver) = mpsc::channel(1);
+        run_poll_trigger(
+            PollTrigger::new(self.config.poll_interval()),
+            self.eth_client.clone(),
+            pending_poll.clone(),
+            poll_sender,
+            || (),
+        );
 
-        loop {
-            timer.tick().await;
+        while poll_receiver.next().await.is_some() {
+            // The notifications received from now on may be not covered by this poll.
+            pending_poll.start();
             self.poll().await;
         }
     }
@@ -390,10 +424,11 @@ pub fn run_forced_exit_contract_watcher(
     forced_exit_minimum_account_age_secs: u64,
     contract: Address,
     web3_url: String,
+    web3_ws_url: Option<String>,
 ) -> JoinHandle<()> {
     let transport = web3::transports::Http::new(&web3_url).unwrap();
     let web3 = web3::Web3::new(transport);
-    let eth_client = EthHttpClient::new(web3, contract);
+    let eth_client = EthHttpClient::new(web3, contract, web3_ws_url);
 
     tokio::spawn(async move {
         // We should not proceed if the feature is disabled
@@ -492,6 +527,13 @@ mod test {
         pub current_block_number: u64,
     }
 
+    #[async_trait::async_trait]
+    impl EthSubscriber for MockEthClient {
+        async fn subscribe(&self) -> anyhow::Result<Option<L1EventStream>> {
+            Ok(None)
+        }
+    }
+
     #[async_trait::async_trait]
     impl EthClient for MockEthClient {
         async fn get_funds_received_events(
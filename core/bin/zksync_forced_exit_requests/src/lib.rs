@@ -24,6 +24,7 @@ pub fn run_forced_exit_requests_actors(
     common: CommonApiConfig,
     contracts: ContractsConfig,
     web3_url: String,
+    web3_ws_url: Option<String>,
 ) -> JoinHandle<()> {
     eth_watch::run_forced_exit_contract_watcher(
         sender,
@@ -32,5 +33,6 @@ pub fn run_forced_exit_requests_actors(
         common.forced_exit_minimum_account_age_secs,
         contracts.forced_exit_addr,
         web3_url,
+        web3_ws_url,
     )
 }
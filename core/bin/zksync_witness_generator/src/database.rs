@@ -6,6 +6,7 @@
 // Built-in
 use std::clone::Clone;
 // Workspace uses
+use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
 use zksync_crypto::proof::{AggregatedProof, SingleProof};
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_types::{
@@ -141,12 +142,22 @@ impl DatabaseInterface for Database {
     async fn load_account_tree_cache(
         &self,
         connection: &mut StorageProcessor<'_>,
-    ) -> anyhow::Result<Option<(BlockNumber, Vec<u8>)>> {
-        let tree_cache = connection
+    ) -> anyhow::Result<Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>> {
+        let last_cached_block = connection
             .chain()
             .tree_cache_schema_bincode()
-            .get_account_tree_cache()
+            .get_last_block_with_account_tree_cache_or_diff()
             .await?;
+        let tree_cache = match last_cached_block {
+            Some(block) => {
+                connection
+                    .chain()
+                    .tree_cache_schema_bincode()
+                    .load_account_tree_cache_incremental(block)
+                    .await?
+            }
+            None => None,
+        };
 
         Ok(tree_cache)
     }
@@ -254,18 +265,23 @@ impl DatabaseInterface for Database {
         &self,
         connection: &mut StorageProcessor<'_>,
         block: BlockNumber,
-        tree_cache: Vec<u8>,
+        previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
+        tree_cache: SparseMerkleTreeSerializableCacheBN256,
     ) -> anyhow::Result<()> {
         connection
             .chain()
             .tree_cache_schema_bincode()
-            .store_account_tree_cache(block, tree_cache)
+            .store_account_tree_cache_incremental(
+                block,
+                previous.as_ref().map(|(block, cache)| (*block, cache)),
+                &tree_cache,
+            )
             .await?;
 
         connection
             .chain()
             .tree_cache_schema_bincode()
-            .remove_old_account_tree_cache(block - NUMBER_OF_STORED_ACCOUNT_TREE_CACHE)
+            .compact_account_tree_cache(block - NUMBER_OF_STORED_ACCOUNT_TREE_CACHE)
             .await?;
 
         Ok(())
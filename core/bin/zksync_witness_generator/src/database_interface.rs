@@ -7,6 +7,7 @@
 use std::clone::Clone;
 use std::marker::{Send, Sync};
 // Workspace uses
+use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
 use zksync_crypto::proof::{AggregatedProof, SingleProof};
 use zksync_storage::StorageProcessor;
 use zksync_types::aggregated_operations::{AggregatedActionType, AggregatedOperation};
@@ -78,7 +79,7 @@ pub trait DatabaseInterface: Send + Sync + Clone + 'static {
     async fn load_account_tree_cache(
         &self,
         connection: &mut StorageProcessor<'_>,
-    ) -> anyhow::Result<Option<(BlockNumber, Vec<u8>)>>;
+    ) -> anyhow::Result<Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>>;
 
     async fn load_idle_prover_job_from_job_queue(
         &self,
@@ -128,11 +129,15 @@ pub trait DatabaseInterface: Send + Sync + Clone + 'static {
         to_block: Option<BlockNumber>,
     ) -> anyhow::Result<Option<(BlockNumber, AccountUpdates)>>;
 
+    /// Stores the account tree cache for the block.
+    /// `previous` is the cache the tree was restored from, so only the changes relative
+    /// to it may be stored.
     async fn store_account_tree_cache(
         &self,
         connection: &mut StorageProcessor<'_>,
         block: BlockNumber,
-        tree_cache_binary: Vec<u8>,
+        previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
+        tree_cache: SparseMerkleTreeSerializableCacheBN256,
     ) -> anyhow::Result<()>;
 
     async fn store_witness(
@@ -1,4 +1,5 @@
 // Built-in
+use std::path::Path;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
@@ -23,10 +24,12 @@ use self::scaler::ScalerOracle;
 use tokio::task::JoinHandle;
 use zksync_circuit::serialization::ProverData;
 use zksync_config::configs::api::ProverApiConfig;
+use zksync_crypto::{circuit::PersistentCircuitAccountTree, params::account_tree_depth};
 use zksync_prover_utils::api::{
     JobRequestData, JobResultData, ProverInputRequest, ProverInputResponse, ProverOutputRequest,
     WorkingOn,
 };
+use zksync_state::tree_backend::DiskTreeStorage;
 use zksync_types::aggregated_operations::{
     AggregatedActionType, AggregatedOperation, BlocksCreateProofOperation,
 };
@@ -429,11 +432,26 @@ pub fn run_prover_server<DB: DatabaseInterface>(
                         start_block,
                         block_step
                     );
+                    // Every generator works on its own set of blocks, so it needs its own tree.
+                    let persistent_tree =
+                        witness_generator_opts
+                            .account_tree_path
+                            .as_ref()
+                            .map(|path| {
+                                let storage =
+                                    DiskTreeStorage::open(Path::new(path).join(offset.to_string()))
+                                        .expect("Unable to open the account tree storage");
+                                PersistentCircuitAccountTree::new(
+                                    account_tree_depth(),
+                                    Arc::new(storage),
+                                )
+                            });
                     let pool_maintainer = witness_generator::WitnessGenerator::new(
                         database.clone(),
                         witness_generator_opts.prepare_data_interval(),
                         BlockNumber(start_block),
                         BlockNumber(block_step),
+                        persistent_tree,
                     );
                     pool_maintainer.start(panic_sender.clone());
                 }
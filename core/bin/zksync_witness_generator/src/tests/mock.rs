@@ -8,6 +8,7 @@ use chrono::Utc;
 use tokio::sync::RwLock;
 use tokio::time::sleep;
 // Workspace uses
+use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
 use zksync_crypto::params::account_tree_depth;
 use zksync_crypto::proof::{AggregatedProof, SingleProof};
 use zksync_storage::chain::tree_cache::records::AccountTreeCache;
@@ -211,11 +212,13 @@ impl DatabaseInterface for MockDatabase {
     async fn load_account_tree_cache(
         &self,
         _: &mut StorageProcessor<'_>,
-    ) -> anyhow::Result<Option<(BlockNumber, Vec<u8>)>> {
+    ) -> anyhow::Result<Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>> {
         let account_tree_cache = self.account_tree_cache.read().await;
         let result = (
             BlockNumber(account_tree_cache.block as u32),
-            account_tree_cache.tree_cache_binary.clone().unwrap(),
+            SparseMerkleTreeSerializableCacheBN256::decode_bincode(
+                account_tree_cache.tree_cache_binary.as_ref().unwrap(),
+            ),
         );
 
         Ok(Some(result))
@@ -343,11 +346,13 @@ impl DatabaseInterface for MockDatabase {
         &self,
         _: &mut StorageProcessor<'_>,
         block: BlockNumber,
-        tree_cache_binary: Vec<u8>,
+        _previous: Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>,
+        tree_cache: SparseMerkleTreeSerializableCacheBN256,
     ) -> anyhow::Result<()> {
         if *block == 0 {
             return Ok(());
         }
+        let tree_cache_binary = tree_cache.encode_bincode();
 
         let mut account_tree_cache = self.account_tree_cache.write().await;
         *account_tree_cache = AccountTreeCache {
@@ -47,6 +47,7 @@ impl Default for MockProverOptions {
             witness_generator: WitnessGenerator {
                 prepare_data_interval: 500,
                 witness_generators: 1,
+                account_tree_path: None,
             },
         };
 
@@ -4,12 +4,11 @@ use std::{thread, time};
 // External
 use futures::channel::mpsc;
 use tokio::time::sleep;
-use zksync_crypto::merkle_tree::parallel_smt::SparseMerkleTreeSerializableCacheBN256;
 // Workspace deps
 use crate::database_interface::DatabaseInterface;
 use zksync_circuit::serialization::ProverData;
 use zksync_circuit::witness::utils::build_block_witness;
-use zksync_crypto::circuit::CircuitAccountTree;
+use zksync_crypto::circuit::{CircuitAccountTree, PersistentCircuitAccountTree};
 use zksync_crypto::params::account_tree_depth;
 use zksync_types::block::Block;
 use zksync_types::BlockNumber;
@@ -28,6 +27,10 @@ pub struct WitnessGenerator<DB: DatabaseInterface> {
 
     start_block: BlockNumber,
     block_step: BlockNumber,
+
+    /// Account tree kept between the blocks. If not set, the tree is restored
+    /// from the cache stored in the database for every block.
+    persistent_tree: Option<PersistentCircuitAccountTree>,
 }
 
 #[derive(Debug)]
@@ -44,12 +47,14 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
         rounds_interval: time::Duration,
         start_block: BlockNumber,
         block_step: BlockNumber,
+        persistent_tree: Option<PersistentCircuitAccountTree>,
     ) -> Self {
         Self {
             database,
             rounds_interval,
             start_block,
             block_step,
+            persistent_tree,
         }
     }
 
@@ -123,9 +128,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
             for (id, account) in accounts {
                 circuit_account_tree.insert(*id, account.into());
             }
-            circuit_account_tree.set_internals(
-                SparseMerkleTreeSerializableCacheBN256::decode_bincode(&account_tree_cache),
-            );
+            circuit_account_tree.set_internals(account_tree_cache.clone());
             if block != cached_block {
                 // There is no relevant cache, so we have to use some outdated cache and update the tree.
                 if *block == *cached_block + 1 {
@@ -161,11 +164,16 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
                 metrics::histogram!("witness_generator", start.elapsed(), "stage" => "recreate_tree_from_cache");
 
                 let start = Instant::now();
-                let tree_cache = circuit_account_tree.get_internals().encode_bincode();
-                metrics::histogram!("tree_cache_size", tree_cache.len() as f64);
+                let tree_cache = circuit_account_tree.get_internals();
 
+                // Only the changes relative to the loaded cache are stored.
                 self.database
-                    .store_account_tree_cache(&mut storage, block, tree_cache)
+                    .store_account_tree_cache(
+                        &mut storage,
+                        block,
+                        Some((cached_block, account_tree_cache)),
+                        tree_cache,
+                    )
                     .await?;
                 metrics::histogram!("witness_generator", start.elapsed(), "stage" => "store_cache");
             } else {
@@ -186,13 +194,12 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
             metrics::histogram!("witness_generator", start.elapsed(), "stage" => "recreate_tree_from_scratch");
 
             let start = Instant::now();
-            let tree_cache = circuit_account_tree.get_internals().encode_bincode();
-            metrics::histogram!("tree_cache_size", tree_cache.len() as f64);
+            let tree_cache = circuit_account_tree.get_internals();
             metrics::histogram!("witness_generator", start.elapsed(), "stage" => "serialize_cache");
 
             let start = Instant::now();
             self.database
-                .store_account_tree_cache(&mut storage, block, tree_cache)
+                .store_account_tree_cache(&mut storage, block, None, tree_cache)
                 .await?;
             metrics::histogram!("witness_generator", start.elapsed(), "stage" => "store_cache");
         }
@@ -216,17 +223,132 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
         Ok(circuit_account_tree)
     }
 
-    async fn prepare_witness_and_save_it(&self, block: Block) -> anyhow::Result<()> {
+    /// Brings the persistent tree to the state after the `block`.
+    ///
+    /// The tree is updated with the accounts changed since the flushed block. If the tree
+    /// is ahead of the `block` or its root hash doesn't match the stored one, the tree is
+    /// rebuilt from scratch.
+    async fn sync_persistent_tree(
+        database: &DB,
+        tree: &mut PersistentCircuitAccountTree,
+        block: BlockNumber,
+    ) -> Result<(), anyhow::Error> {
         let fn_start = Instant::now();
-        let mut storage = self.database.acquire_connection().await?;
+        let mut storage = database.acquire_connection().await?;
 
-        let start = Instant::now();
-        let mut circuit_account_tree = self.load_account_tree(block.block_number - 1).await?;
-        metrics::histogram!("witness_generator", start.elapsed(), "stage" => "load_tree_full");
+        let expected_root_hash = if block != BlockNumber(0) {
+            let storage_block = database
+                .load_block(&mut storage, block)
+                .await?
+                .expect("Block for witness generator must exist");
+            Some(storage_block.new_root_hash)
+        } else {
+            None
+        };
+        let root_hash_matches = |tree: &PersistentCircuitAccountTree| {
+            expected_root_hash.map_or(true, |expected| expected == tree.root_hash())
+        };
+
+        let (_, accounts) = database
+            .load_committed_state(&mut storage, Some(block))
+            .await?;
+        let flushed_block = tree.version().map(|version| BlockNumber(version as u32));
+
+        if flushed_block == Some(block) && root_hash_matches(tree) {
+            metrics::increment_counter!("witness_generator.cache_access", "type" => "hit");
+            return Ok(());
+        }
+        if let Some(flushed_block) = flushed_block.filter(|flushed| *flushed < block) {
+            if block == flushed_block + 1 {
+                metrics::increment_counter!("witness_generator.cache_access", "type" => "off_by_1");
+            } else {
+                metrics::increment_counter!("witness_generator.cache_access", "type" => "miss");
+            }
+
+            let start = Instant::now();
+            if let Some((_, account_updates)) = database
+                .load_state_diff(&mut storage, flushed_block, Some(block))
+                .await?
+            {
+                let mut updated_accounts = account_updates
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>();
+                updated_accounts.sort_unstable();
+                updated_accounts.dedup();
+                for idx in updated_accounts {
+                    tree.insert(*idx, accounts.get(&idx).cloned().unwrap_or_default().into());
+                }
+            }
+            metrics::histogram!("witness_generator", start.elapsed(), "stage" => "update_persistent_tree");
+
+            if root_hash_matches(tree) {
+                tree.flush(u64::from(*block));
+                metrics::histogram!("witness_generator", fn_start.elapsed(), "stage" => "sync_persistent_tree");
+                return Ok(());
+            }
+            vlog::warn!(
+                "Root hash of the persistent tree updated from block {} to block {} doesn't match, rebuilding the tree",
+                flushed_block,
+                block
+            );
+            tree.discard_pending_changes();
+        } else if let Some(flushed_block) = flushed_block {
+            vlog::info!(
+                "Persistent tree is at the block {}, rebuilding it for the block {}",
+                flushed_block,
+                block
+            );
+        }
 
         let start = Instant::now();
-        let witness: ProverData = build_block_witness(&mut circuit_account_tree, &block)?.into();
-        metrics::histogram!("witness_generator", start.elapsed(), "stage" => "build_witness");
+        tree.clear();
+        for (id, account) in accounts {
+            tree.insert(*id, account.into());
+        }
+        assert!(
+            root_hash_matches(tree),
+            "account tree root hash restored incorrectly"
+        );
+        tree.flush(u64::from(*block));
+        metrics::histogram!("witness_generator", start.elapsed(), "stage" => "recreate_persistent_tree");
+
+        metrics::histogram!("witness_generator", fn_start.elapsed(), "stage" => "sync_persistent_tree");
+        Ok(())
+    }
+
+    async fn prepare_witness_and_save_it(&mut self, block: Block) -> anyhow::Result<()> {
+        let fn_start = Instant::now();
+        let mut storage = self.database.acquire_connection().await?;
+
+        let witness: ProverData = if let Some(tree) = self.persistent_tree.as_mut() {
+            let start = Instant::now();
+            Self::sync_persistent_tree(&self.database, tree, block.block_number - 1).await?;
+            metrics::histogram!("witness_generator", start.elapsed(), "stage" => "load_tree_full");
+
+            let start = Instant::now();
+            let witness: anyhow::Result<ProverData> =
+                build_block_witness(tree, &block).map(Into::into);
+            // Building the witness applies the block to the tree, so it's kept for the next block
+            // only if the resulting state is the expected one.
+            match &witness {
+                Ok(_) if tree.root_hash() == block.new_root_hash => {
+                    tree.flush(u64::from(*block.block_number))
+                }
+                _ => tree.discard_pending_changes(),
+            }
+            metrics::histogram!("witness_generator", start.elapsed(), "stage" => "build_witness");
+            witness?
+        } else {
+            let start = Instant::now();
+            let mut circuit_account_tree = self.load_account_tree(block.block_number - 1).await?;
+            metrics::histogram!("witness_generator", start.elapsed(), "stage" => "load_tree_full");
+
+            let start = Instant::now();
+            let witness = build_block_witness(&mut circuit_account_tree, &block)?.into();
+            metrics::histogram!("witness_generator", start.elapsed(), "stage" => "build_witness");
+            witness
+        };
 
         let start = Instant::now();
         self.database
@@ -264,7 +386,7 @@ impl<DB: DatabaseInterface> WitnessGenerator<DB> {
 
     /// Updates witness data in database in an infinite loop,
     /// awaiting `rounds_interval` time between updates.
-    async fn maintain(self) {
+    async fn maintain(mut self) {
         vlog::info!(
             "preparing prover data routine started with start_block({}), block_step({})",
             *self.start_block,
@@ -1,10 +1,11 @@
 use crate::rest::client::{Client, Result};
 
 use zksync_api_types::v02::{
+    account::IncomingAccountProofQuery,
     pagination::{ApiEither, PaginationQuery},
     Response,
 };
-use zksync_types::{tx::TxHash, SerialId};
+use zksync_types::{tx::TxHash, BlockNumber, SerialId};
 
 impl Client {
     pub async fn account_info(
@@ -20,6 +21,19 @@ impl Client {
         .await
     }
 
+    pub async fn account_info_at_block(
+        &self,
+        account_id_or_address: &str,
+        block_number: BlockNumber,
+    ) -> Result<Response> {
+        self.get_with_scope(
+            super::API_V02_SCOPE,
+            &format!("accounts/{}/blocks/{}", account_id_or_address, block_number),
+        )
+        .send()
+        .await
+    }
+
     pub async fn account_full_info(&self, account_id_or_address: &str) -> Result<Response> {
         self.get_with_scope(
             super::API_V02_SCOPE,
@@ -56,4 +70,18 @@ impl Client {
         .send()
         .await
     }
+
+    pub async fn account_proof(
+        &self,
+        account_id_or_address: &str,
+        query: &IncomingAccountProofQuery,
+    ) -> Result<Response> {
+        self.get_with_scope(
+            super::API_V02_SCOPE,
+            &format!("accounts/{}/proof", account_id_or_address),
+        )
+        .query(query)
+        .send()
+        .await
+    }
 }
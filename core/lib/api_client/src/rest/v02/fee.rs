@@ -1,7 +1,7 @@
 // Local uses
 use crate::rest::client::{Client, Result};
 use zksync_api_types::v02::{
-    fee::{ApiTxFeeTypes, BatchFeeRequest, TxFeeRequest, TxInBatchFeeRequest},
+    fee::{ApiTxFeeTypes, BatchFeeRequest, TxFeeQuoteRequest, TxFeeRequest, TxInBatchFeeRequest},
     Response,
 };
 use zksync_types::{Address, TokenLike};
@@ -18,6 +18,7 @@ impl Client {
                 tx_type,
                 address,
                 token_like,
+                sender: None,
             })
             .send()
             .await
@@ -36,4 +37,28 @@ impl Client {
             .send()
             .await
     }
+
+    pub async fn get_tx_fee_quote(
+        &self,
+        tx_type: ApiTxFeeTypes,
+        address: Address,
+        token_like: TokenLike,
+        sender: Address,
+    ) -> Result<Response> {
+        self.post_with_scope(super::API_V02_SCOPE, "fee/quote")
+            .body(&TxFeeQuoteRequest {
+                tx_type,
+                address,
+                token_like,
+                sender,
+            })
+            .send()
+            .await
+    }
+
+    pub async fn get_subsidy_programs(&self) -> Result<Response> {
+        self.get_with_scope(super::API_V02_SCOPE, "fee/subsidies")
+            .send()
+            .await
+    }
 }
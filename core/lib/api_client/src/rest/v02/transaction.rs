@@ -1,6 +1,9 @@
 use crate::rest::client::{Client, Result};
 use zksync_api_types::{
-    v02::{transaction::IncomingTxBatch, Response},
+    v02::{
+        transaction::{CancelTx, IncomingTx, IncomingTxBatch},
+        Response,
+    },
     TxWithSignature,
 };
 use zksync_types::tx::{EthBatchSignatures, TxEthSignatureVariant, TxHash, ZkSyncTx};
@@ -17,6 +20,23 @@ impl Client {
             .await
     }
 
+    /// Submits the transaction which is accepted with the fee guaranteed by the quote.
+    pub async fn submit_tx_with_fee_quote(
+        &self,
+        tx: ZkSyncTx,
+        signature: TxEthSignatureVariant,
+        fee_quote_id: i64,
+    ) -> Result<Response> {
+        self.post_with_scope(super::API_V02_SCOPE, "transactions")
+            .body(&IncomingTx {
+                tx,
+                signature,
+                fee_quote_id: Some(fee_quote_id),
+            })
+            .send()
+            .await
+    }
+
     pub async fn submit_batch(
         &self,
         txs: Vec<TxWithSignature>,
@@ -28,6 +48,27 @@ impl Client {
             .await
     }
 
+    pub async fn cancel_tx(&self, cancel_tx: &CancelTx) -> Result<Response> {
+        self.post_with_scope(super::API_V02_SCOPE, "transactions/cancel")
+            .body(cancel_tx)
+            .send()
+            .await
+    }
+
+    pub async fn simulate_tx(&self, tx: &ZkSyncTx) -> Result<Response> {
+        self.post_with_scope(super::API_V02_SCOPE, "transactions/simulate")
+            .body(tx)
+            .send()
+            .await
+    }
+
+    pub async fn simulate_batch(&self, txs: &[ZkSyncTx]) -> Result<Response> {
+        self.post_with_scope(super::API_V02_SCOPE, "transactions/batches/simulate")
+            .body(txs)
+            .send()
+            .await
+    }
+
     pub async fn tx_status(&self, tx_hash: TxHash) -> Result<Response> {
         self.get_with_scope(
             super::API_V02_SCOPE,
@@ -115,3 +115,11 @@ pub struct IncomingAccountTxsQuery {
     pub token: Option<String>,
     pub second_account: Option<String>,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingAccountProofQuery {
+    pub token: String,
+    /// Finalized block to build the proof for. The last finalized block is used by default.
+    pub block: Option<BlockNumber>,
+}
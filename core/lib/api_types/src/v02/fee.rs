@@ -1,6 +1,11 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use num::BigUint;
 use serde::{Deserialize, Serialize};
-use zksync_types::{tokens::ChangePubKeyFeeTypeArg, Address, BatchFee, Fee, TokenLike, TxFeeTypes};
+use zksync_types::{
+    tokens::ChangePubKeyFeeTypeArg, Address, BatchFee, Fee, OutputFeeType, TokenId, TokenLike,
+    TxFeeTypes,
+};
 use zksync_utils::BigUintSerdeAsRadix10Str;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,6 +17,32 @@ pub struct ApiFee {
     pub zkp_fee: BigUint,
     #[serde(with = "BigUintSerdeAsRadix10Str")]
     pub total_fee: BigUint,
+    /// Subsidy included in the fee, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subsidy: Option<ApiFeeSubsidy>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiFeeSubsidy {
+    /// Name of the subsidy program paying the part of the fee.
+    pub program: String,
+    /// Total fee without the subsidy.
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub normal_fee: BigUint,
+}
+
+/// Subsidy program with its usage.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSubsidyProgram {
+    pub name: String,
+    pub subsidy_percent: u8,
+    pub budget_usd: BigDecimal,
+    pub daily_cap_usd: BigDecimal,
+    pub used_usd: BigDecimal,
+    /// Subsidies paid during the last 24 hours.
+    pub used_last_day_usd: BigDecimal,
 }
 
 impl From<Fee> for ApiFee {
@@ -20,16 +51,35 @@ impl From<Fee> for ApiFee {
             gas_fee: fee.gas_fee,
             zkp_fee: fee.zkp_fee,
             total_fee: fee.total_fee,
+            subsidy: None,
         }
     }
 }
 
+/// Fee guaranteed by the server: until `valid_until`, a single transaction of the quoted type from
+/// `sender` to `address` paying at least `total_fee` in the quoted token is accepted regardless
+/// of the current prices, if it's submitted with the `quote_id`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiFeeQuote {
+    pub quote_id: i64,
+    pub sender: Address,
+    pub address: Address,
+    pub fee_type: OutputFeeType,
+    pub token_id: TokenId,
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub total_fee: BigUint,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+}
+
 impl From<BatchFee> for ApiFee {
     fn from(fee: BatchFee) -> Self {
         ApiFee {
             gas_fee: fee.gas_fee,
             zkp_fee: fee.zkp_fee,
             total_fee: fee.total_fee,
+            subsidy: None,
         }
     }
 }
@@ -77,6 +127,19 @@ pub struct TxFeeRequest {
     pub tx_type: ApiTxFeeTypes,
     pub address: Address,
     pub token_like: TokenLike,
+    /// Address of the transaction initiator, required for the subsidy programs limited to the senders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<Address>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TxFeeQuoteRequest {
+    pub tx_type: ApiTxFeeTypes,
+    pub address: Address,
+    pub token_like: TokenLike,
+    /// Address of the account the quote is given to.
+    pub sender: Address,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
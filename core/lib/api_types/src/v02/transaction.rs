@@ -6,13 +6,24 @@ use serde::{Deserialize, Serialize};
 use zksync_types::{
     tx::{
         ChangePubKey, Close, EthBatchSignatures, ForcedExit, MintNFT, Swap, Transfer,
-        TxEthSignature, TxHash, Withdraw, WithdrawNFT,
+        TxEthSignature, TxEthSignatureVariant, TxHash, TxSignature, Withdraw, WithdrawNFT,
     },
-    AccountId, Address, BlockNumber, EthBlockId, PubKeyHash, SerialId, TokenId, ZkSyncOp,
-    ZkSyncPriorityOp, H256,
+    AccountId, AccountUpdates, Address, BlockNumber, EthBlockId, PubKeyHash, SerialId, TokenId,
+    ZkSyncOp, ZkSyncPriorityOp, ZkSyncTx, H256,
 };
 use zksync_utils::{BigUintSerdeAsRadix10Str, ZeroPrefixHexSerde};
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingTx {
+    pub tx: ZkSyncTx,
+    #[serde(default)]
+    pub signature: TxEthSignatureVariant,
+    /// ID of the fee quote to be honoured if the fee provided by the transaction became too low.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_quote_id: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IncomingTxBatch {
@@ -27,6 +38,8 @@ pub enum TxInBlockStatus {
     Committed,
     Finalized,
     Rejected,
+    /// Transaction was replaced in the mempool by a transaction with a higher fee.
+    Replaced,
 }
 
 impl From<BlockStatus> for TxInBlockStatus {
@@ -296,3 +309,69 @@ impl Toggle2FA {
 pub struct Toggle2FAResponse {
     pub success: bool,
 }
+
+/// Request to remove a pending transaction or a batch of transactions from the mempool.
+/// Must be signed either with the current zkSync key of the account or by the account owner
+/// on Ethereum.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelTx {
+    /// Hash of the transaction or of the batch to cancel.
+    pub tx_hash: TxHash,
+    pub account_id: AccountId,
+    #[serde(with = "ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+    pub signature: Option<TxSignature>,
+    pub eth_signature: Option<TxEthSignature>,
+}
+
+impl CancelTx {
+    /// Message to be signed by either zkSync or Ethereum key of the account.
+    pub fn get_sign_message(&self) -> String {
+        format!(
+            "Cancel pending transaction: {}\n\
+            Account: {}\n\
+            Timestamp: {}",
+            self.tx_hash.to_string(),
+            self.account_id,
+            self.timestamp.timestamp_millis()
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelTxResponse {
+    pub cancelled_txs: Vec<TxHashSerializeWrapper>,
+}
+
+/// Fee that would be collected by the operator for the simulated transaction.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedFee {
+    pub token: TokenId,
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub amount: BigUint,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTx {
+    pub tx_hash: TxHash,
+    pub chunks: usize,
+    pub fee: Option<SimulatedFee>,
+    pub updates: AccountUpdates,
+}
+
+/// Result of the transaction (or batch) execution against the last committed state.
+/// If the execution failed, `txs` is empty and the reason is reported by the state.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TxSimulationResult {
+    pub success: bool,
+    pub txs: Vec<SimulatedTx>,
+    pub chunks: usize,
+    pub fail_reason: Option<String>,
+    /// Index of the failed transaction in the batch, starting from 1.
+    pub failed_tx_index: Option<usize>,
+}
@@ -3,7 +3,7 @@ use num::ToPrimitive;
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     franklin_crypto::{
@@ -55,7 +55,7 @@ impl Witness for ChangePubkeyOffChainWitness<Bn256> {
     type OperationType = ChangePubKeyOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, change_pubkey_offchain: &ChangePubKeyOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, change_pubkey_offchain: &ChangePubKeyOp) -> Self {
         let (valid_from, valid_until) = {
             let time_range = change_pubkey_offchain.tx.time_range.unwrap_or_default();
             (time_range.valid_from, time_range.valid_until)
@@ -148,7 +148,7 @@ impl Witness for ChangePubkeyOffChainWitness<Bn256> {
 
 impl ChangePubkeyOffChainWitness<Bn256> {
     fn apply_data(
-        tree: &mut CircuitAccountTree,
+        tree: &mut dyn CircuitTree,
         change_pubkey_offcahin: ChangePubkeyOffChainData,
     ) -> Self {
         //preparing data and base witness
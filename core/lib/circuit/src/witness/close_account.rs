@@ -9,7 +9,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, le_bit_vector_into_field_element},
     },
     params::{account_tree_depth, ACCOUNT_ID_BIT_WIDTH, CHUNK_BIT_WIDTH, TX_TYPE_BIT_WIDTH},
@@ -41,7 +41,7 @@ impl Witness for CloseAccountWitness<Bn256> {
     type OperationType = CloseOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, close_account: &CloseOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, close_account: &CloseOp) -> Self {
         let close_acoount_data = CloseAccountData {
             account_address: *close_account.account_id,
         };
@@ -93,7 +93,7 @@ impl Witness for CloseAccountWitness<Bn256> {
 }
 
 impl CloseAccountWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, close_account: &CloseAccountData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, close_account: &CloseAccountData) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
@@ -10,7 +10,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -60,7 +60,7 @@ impl Witness for ForcedExitWitness<Bn256> {
     type OperationType = ForcedExitOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, forced_exit: &ForcedExitOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, forced_exit: &ForcedExitOp) -> Self {
         let (valid_from, valid_until) = {
             let time_range = forced_exit.tx.time_range.unwrap_or_default();
             (time_range.valid_from, time_range.valid_until)
@@ -192,7 +192,7 @@ impl Witness for ForcedExitWitness<Bn256> {
 }
 
 impl ForcedExitWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, forced_exit: &ForcedExitData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, forced_exit: &ForcedExitData) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
@@ -9,7 +9,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -58,10 +58,7 @@ impl Witness for FullExitWitness<Bn256> {
     type OperationType = (FullExitOp, bool);
     type CalculateOpsInput = ();
 
-    fn apply_tx(
-        tree: &mut CircuitAccountTree,
-        (full_exit, is_success): &(FullExitOp, bool),
-    ) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, (full_exit, is_success): &(FullExitOp, bool)) -> Self {
         let full_exit = FullExitData {
             token: *full_exit.priority_op.token as u32,
             account_address: *full_exit.priority_op.account_id,
@@ -224,11 +221,7 @@ impl Witness for FullExitWitness<Bn256> {
 }
 
 impl FullExitWitness<Bn256> {
-    fn apply_data(
-        tree: &mut CircuitAccountTree,
-        full_exit: &FullExitData,
-        is_success: bool,
-    ) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, full_exit: &FullExitData, is_success: bool) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
@@ -14,7 +14,7 @@ use zksync_crypto::rescue_poseidon::rescue_hash;
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, le_bit_vector_into_field_element},
     },
     params::{
@@ -67,7 +67,7 @@ impl Witness for MintNFTWitness<Bn256> {
     type OperationType = MintNFTOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, mint_nft: &MintNFTOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, mint_nft: &MintNFTOp) -> Self {
         let mint_nft_data = MintNFTData {
             fee: mint_nft.tx.fee.to_u128().unwrap(),
             fee_token: *mint_nft.tx.fee_token as u32,
@@ -206,7 +206,7 @@ impl Witness for MintNFTWitness<Bn256> {
 }
 
 impl MintNFTWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, mint_nft: &MintNFTData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, mint_nft: &MintNFTData) -> Self {
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << account_tree_depth());
 
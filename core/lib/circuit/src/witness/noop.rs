@@ -4,9 +4,7 @@ use zksync_crypto::franklin_crypto::bellman::pairing::{
     ff::Field,
 };
 // Workspace deps
-use zksync_crypto::circuit::{
-    account::CircuitAccountTree, utils::le_bit_vector_into_field_element,
-};
+use zksync_crypto::circuit::{account::CircuitTree, utils::le_bit_vector_into_field_element};
 use zksync_crypto::params::CHUNK_BIT_WIDTH;
 use zksync_types::operations::NoopOp;
 // Local deps
@@ -16,7 +14,7 @@ use crate::{
     witness::utils::{fr_from, get_audits},
 };
 
-pub fn noop_operation(tree: &CircuitAccountTree, acc_id: u32) -> Operation<Bn256> {
+pub fn noop_operation(tree: &dyn CircuitTree, acc_id: u32) -> Operation<Bn256> {
     let signature_data = SignatureData::init_empty();
     let first_sig_msg = Fr::zero();
     let second_sig_msg = Fr::zero();
@@ -10,7 +10,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -71,7 +71,7 @@ impl Witness for SwapWitness<Bn256> {
     type OperationType = SwapOp;
     type CalculateOpsInput = (SigDataInput, SigDataInput, SigDataInput);
 
-    fn apply_tx(tree: &mut CircuitAccountTree, swap: &SwapOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, swap: &SwapOp) -> Self {
         let order_0 = OrderData {
             account: *swap.accounts.0 as u32,
             recipient: *swap.recipients.0 as u32,
@@ -303,7 +303,7 @@ impl SwapWitness<Bn256> {
         nonce_mask
     }
 
-    fn apply_data(tree: &mut CircuitAccountTree, swap: &SwapData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, swap: &SwapData) -> Self {
         assert_eq!(tree.capacity(), 1 << account_tree_depth());
         let account_0_fe = fr_from(swap.orders.0.account);
         let account_1_fe = fr_from(swap.orders.1.account);
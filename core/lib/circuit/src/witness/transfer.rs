@@ -10,7 +10,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, le_bit_vector_into_field_element},
     },
     params::{
@@ -60,7 +60,7 @@ impl Witness for TransferWitness<Bn256> {
     type OperationType = TransferOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, transfer: &TransferOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, transfer: &TransferOp) -> Self {
         let time_range = transfer.tx.time_range.unwrap_or_default();
         let transfer_data = TransferData {
             amount: transfer.tx.amount.to_u128().unwrap(),
@@ -159,7 +159,7 @@ impl Witness for TransferWitness<Bn256> {
 }
 
 impl TransferWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, transfer: &TransferData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, transfer: &TransferData) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
@@ -8,7 +8,7 @@ use zksync_crypto::franklin_crypto::{
 };
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -59,7 +59,7 @@ impl Witness for TransferToNewWitness<Bn256> {
     type OperationType = TransferToNewOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, transfer_to_new: &TransferToNewOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, transfer_to_new: &TransferToNewOp) -> Self {
         let time_range = transfer_to_new.tx.time_range.unwrap_or_default();
         let transfer_data = TransferToNewData {
             amount: transfer_to_new.tx.amount.to_string().parse().unwrap(),
@@ -183,7 +183,7 @@ impl Witness for TransferToNewWitness<Bn256> {
 }
 
 impl TransferToNewWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, transfer_to_new: &TransferToNewData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, transfer_to_new: &TransferToNewData) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
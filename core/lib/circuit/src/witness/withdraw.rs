@@ -10,7 +10,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -54,7 +54,7 @@ impl Witness for WithdrawWitness<Bn256> {
     type OperationType = WithdrawOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, withdraw: &WithdrawOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, withdraw: &WithdrawOp) -> Self {
         let (valid_from, valid_until) = {
             let time_range = withdraw.tx.time_range.unwrap_or_default();
             (time_range.valid_from, time_range.valid_until)
@@ -160,7 +160,7 @@ impl Witness for WithdrawWitness<Bn256> {
 }
 
 impl WithdrawWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, withdraw: &WithdrawData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, withdraw: &WithdrawData) -> Self {
         //preparing data and base witness
         let before_root = tree.root_hash();
         vlog::debug!("Initial root = {}", before_root);
@@ -11,7 +11,7 @@ use zksync_crypto::franklin_crypto::{
 // Workspace deps
 use zksync_crypto::{
     circuit::{
-        account::CircuitAccountTree,
+        account::CircuitTree,
         utils::{append_be_fixed_width, eth_address_to_fr, le_bit_vector_into_field_element},
     },
     params::{
@@ -65,7 +65,7 @@ impl Witness for WithdrawNFTWitness<Bn256> {
     type OperationType = WithdrawNFTOp;
     type CalculateOpsInput = SigDataInput;
 
-    fn apply_tx(tree: &mut CircuitAccountTree, withdraw_nft: &WithdrawNFTOp) -> Self {
+    fn apply_tx(tree: &mut dyn CircuitTree, withdraw_nft: &WithdrawNFTOp) -> Self {
         let time_range = withdraw_nft.tx.time_range;
         let withdraw_nft_data = WithdrawNFTData {
             fee: withdraw_nft.tx.fee.to_u128().unwrap(),
@@ -232,7 +232,7 @@ impl Witness for WithdrawNFTWitness<Bn256> {
 }
 
 impl WithdrawNFTWitness<Bn256> {
-    fn apply_data(tree: &mut CircuitAccountTree, withdraw_nft: &WithdrawNFTData) -> Self {
+    fn apply_data(tree: &mut dyn CircuitTree, withdraw_nft: &WithdrawNFTData) -> Self {
         let capacity = tree.capacity();
         assert_eq!(capacity, 1 << account_tree_depth());
 
@@ -1,12 +1,13 @@
 use num::{rational::Ratio, BigUint};
 /// External uses
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 /// Built-in uses
 use std::net::SocketAddr;
+use std::str::FromStr;
 use std::time::Duration;
 use zksync_utils::scaled_u64_to_ratio;
 // Workspace uses
-use zksync_types::AccountId;
+use zksync_types::{AccountId, Address, TokenId};
 // Local uses
 use crate::envy_load;
 
@@ -35,7 +36,7 @@ pub struct ApiConfig {
 impl ApiConfig {
     pub fn from_env() -> Self {
         Self {
-            common: envy_load!("common", "API_COMMON_"),
+            common: CommonApiConfig::from_env(),
             admin: envy_load!("admin", "API_ADMIN_"),
             rest: envy_load!("rest", "API_REST_"),
             json_rpc: envy_load!("json_rpc", "API_JSON_RPC_"),
@@ -53,8 +54,33 @@ impl CommonApiConfig {
         scaled_u64_to_ratio(self.max_subsidy_usd_scaled)
     }
 
+    pub fn fee_quote_validity(&self) -> Duration {
+        Duration::from_secs(self.fee_quote_validity_sec)
+    }
+
     pub fn from_env() -> Self {
-        envy_load!("common", "API_COMMON_")
+        let config: Self = envy_load!("common", "API_COMMON_");
+        validate_subsidy_names(&config);
+        config
+    }
+}
+
+// Subsidies are accounted and limited by their names, so each subsidy program and the CPK subsidy
+// must have a distinct name, otherwise they would share the budget.
+fn validate_subsidy_names(config: &CommonApiConfig) {
+    for (idx, program) in config.subsidy_programs.iter().enumerate() {
+        assert!(
+            program.name != config.subsidy_name,
+            "Subsidy program '{}' has the same name as the CPK subsidy",
+            program.name
+        );
+        assert!(
+            config.subsidy_programs[..idx]
+                .iter()
+                .all(|other| other.name != program.name),
+            "Subsidy program name '{}' is not unique",
+            program.name
+        );
     }
 }
 
@@ -122,6 +148,136 @@ pub struct CommonApiConfig {
 
     /// The name of current subsidy. It is needed to conveniently fetch historical data regarding subsidies for different partners
     pub subsidy_name: String,
+
+    /// Time during which the fee quoted by the API is accepted for the quoted transaction type and token.
+    pub fee_quote_validity_sec: u64,
+
+    /// Programs subsidizing the fees of the transactions matching their rules.
+    #[serde(default)]
+    pub subsidy_programs: Vec<SubsidyProgramConfig>,
+}
+
+/// Type of the transactions a subsidy program applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubsidizedTxType {
+    Transfer,
+    Withdraw,
+    FastWithdraw,
+    WithdrawNFT,
+    FastWithdrawNFT,
+    ChangePubKey,
+    MintNFT,
+    Swap,
+}
+
+impl FromStr for SubsidizedTxType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Transfer" => Self::Transfer,
+            "Withdraw" => Self::Withdraw,
+            "FastWithdraw" => Self::FastWithdraw,
+            "WithdrawNFT" => Self::WithdrawNFT,
+            "FastWithdrawNFT" => Self::FastWithdrawNFT,
+            "ChangePubKey" => Self::ChangePubKey,
+            "MintNFT" => Self::MintNFT,
+            "Swap" => Self::Swap,
+            tx_type => return Err(format!("Unknown transaction type '{}'", tx_type)),
+        })
+    }
+}
+
+/// Program paying the given share of the fee for the transactions matching its rules
+/// until its total budget or the daily cap is exhausted.
+/// Empty lists of the rules match any transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubsidyProgramConfig {
+    /// Name of the program, it's used to account the subsidies paid by the program.
+    pub name: String,
+    pub tx_types: Vec<SubsidizedTxType>,
+    pub tokens: Vec<TokenId>,
+    pub senders: Vec<Address>,
+    pub recipients: Vec<Address>,
+    /// Share of the fee paid by the program, in percent.
+    pub subsidy_percent: u8,
+    /// Maximum total amount of the subsidies, in USD scaled by SUBSIDY_USD_AMOUNTS_SCALE.
+    pub budget_usd_scaled: u64,
+    /// Maximum amount of the subsidies during the last 24 hours, in USD scaled by SUBSIDY_USD_AMOUNTS_SCALE.
+    pub daily_cap_usd_scaled: u64,
+}
+
+impl SubsidyProgramConfig {
+    pub fn budget_usd(&self) -> Ratio<BigUint> {
+        scaled_u64_to_ratio(self.budget_usd_scaled)
+    }
+
+    pub fn daily_cap_usd(&self) -> Ratio<BigUint> {
+        scaled_u64_to_ratio(self.daily_cap_usd_scaled)
+    }
+}
+
+impl FromStr for SubsidyProgramConfig {
+    type Err = String;
+
+    /// Parses the program from the
+    /// `<name>:<tx types>:<tokens>:<senders>:<recipients>:<subsidy percent>:<budget usd scaled>:<daily cap usd scaled>`
+    /// format, where the lists are separated by `|` and `*` stands for any value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.trim().split(':').collect();
+        if parts.len() != 8 {
+            return Err(format!(
+                "Invalid subsidy program '{}', expected '<name>:<tx types>:<tokens>:<senders>:<recipients>:<subsidy percent>:<budget usd scaled>:<daily cap usd scaled>'",
+                s
+            ));
+        }
+        fn parse_list<T>(
+            list: &str,
+            parse: impl Fn(&str) -> Result<T, String>,
+        ) -> Result<Vec<T>, String> {
+            if list == "*" {
+                return Ok(Vec::new());
+            }
+            list.split('|').map(parse).collect()
+        }
+        let parse_address = |address: &str| {
+            Address::from_str(address.trim_start_matches("0x"))
+                .map_err(|err| format!("Invalid address '{}': {}", address, err))
+        };
+        let parse_number = |number: &str| {
+            number
+                .parse::<u64>()
+                .map_err(|err| format!("Invalid number '{}': {}", number, err))
+        };
+
+        let subsidy_percent = parse_number(parts[5])?;
+        if subsidy_percent == 0 || subsidy_percent > 100 {
+            return Err(format!(
+                "Subsidy percent must be in the 1..=100 range, got {}",
+                subsidy_percent
+            ));
+        }
+
+        Ok(Self {
+            name: parts[0].to_owned(),
+            tx_types: parse_list(parts[1], str::parse)?,
+            tokens: parse_list(parts[2], |token| {
+                parse_number(token).map(|id| TokenId(id as u32))
+            })?,
+            senders: parse_list(parts[3], parse_address)?,
+            recipients: parse_list(parts[4], parse_address)?,
+            subsidy_percent: subsidy_percent as u8,
+            budget_usd_scaled: parse_number(parts[6])?,
+            daily_cap_usd_scaled: parse_number(parts[7])?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for SubsidyProgramConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -231,12 +387,36 @@ pub struct Web3Config {
     /// Max difference between blocks in `eth_getLogs` method.
     pub max_block_range: u32,
     pub chain_id: u32,
+    /// Port to which the web3 WebSocket JSON RPC server (`eth_subscribe`) is listening.
+    pub ws_port: u16,
+    /// URL to access web3 WebSocket JSON RPC server.
+    pub ws_url: String,
+    /// Max amount of filters installed with `eth_newFilter` and `eth_newBlockFilter` at the same time.
+    pub max_filters: usize,
+    /// Filters which were not polled for this amount of seconds are uninstalled.
+    pub filter_timeout_sec: u64,
+    /// How often the new blocks are checked to notify the `eth_subscribe` subscribers, in milliseconds.
+    pub subscriptions_poll_interval_ms: u64,
+    /// Max amount of subscriptions and filters created over a single WebSocket connection.
+    pub max_subscriptions_per_connection: usize,
 }
 
 impl Web3Config {
     pub fn bind_addr(&self) -> SocketAddr {
         SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
     }
+
+    pub fn ws_bind_addr(&self) -> SocketAddr {
+        SocketAddr::new("0.0.0.0".parse().unwrap(), self.ws_port)
+    }
+
+    pub fn filter_timeout(&self) -> Duration {
+        Duration::from_secs(self.filter_timeout_sec)
+    }
+
+    pub fn subscriptions_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.subscriptions_poll_interval_ms)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -263,6 +443,29 @@ mod tests {
                 subsidized_ips: vec!["127.0.0.1".to_owned()],
                 max_subsidy_usd_scaled: 20000,
                 subsidy_name: String::from("PartnerName"),
+                fee_quote_validity_sec: 600,
+                subsidy_programs: vec![
+                    SubsidyProgramConfig {
+                        name: String::from("PartnerTransfers"),
+                        tx_types: vec![SubsidizedTxType::Transfer, SubsidizedTxType::Withdraw],
+                        tokens: vec![TokenId(0), TokenId(1)],
+                        senders: vec![Address::from_low_u64_be(1)],
+                        recipients: Vec::new(),
+                        subsidy_percent: 100,
+                        budget_usd_scaled: 1000000000,
+                        daily_cap_usd_scaled: 100000000,
+                    },
+                    SubsidyProgramConfig {
+                        name: String::from("CheapUSDC"),
+                        tx_types: Vec::new(),
+                        tokens: vec![TokenId(2)],
+                        senders: Vec::new(),
+                        recipients: Vec::new(),
+                        subsidy_percent: 50,
+                        budget_usd_scaled: 5000000000,
+                        daily_cap_usd_scaled: 500000000,
+                    },
+                ],
             },
             admin: AdminApiConfig {
                 port: 8080,
@@ -284,6 +487,12 @@ mod tests {
                 url: "http://127.0.0.1:3002".into(),
                 max_block_range: 10,
                 chain_id: 240,
+                ws_port: 3003,
+                ws_url: "ws://127.0.0.1:3003".into(),
+                max_filters: 1000,
+                filter_timeout_sec: 300,
+                subscriptions_poll_interval_ms: 1000,
+                max_subscriptions_per_connection: 100,
             },
             private: PrivateApiConfig {
                 port: 8090,
@@ -311,6 +520,8 @@ API_COMMON_ENFORCE_PUBKEY_CHANGE_FEE=true
 API_COMMON_SUBSIDIZED_IPS="127.0.0.1"
 API_COMMON_MAX_SUBSIDY_USD_SCALED=20000
 API_COMMON_SUBSIDY_NAME=PartnerName
+API_COMMON_FEE_QUOTE_VALIDITY_SEC=600
+API_COMMON_SUBSIDY_PROGRAMS="PartnerTransfers:Transfer|Withdraw:0|1:0x0000000000000000000000000000000000000001:*:100:1000000000:100000000,CheapUSDC:*:2:*:*:50:5000000000:500000000"
 API_COMMON_MAX_NUMBER_OF_TRANSACTIONS_PER_BATCH=200
 API_COMMON_MAX_NUMBER_OF_AUTHORS_PER_BATCH=10
 API_TOKEN_INVALIDATE_TOKEN_CACHE_PERIOD_SEC="10"
@@ -327,6 +538,12 @@ API_WEB3_PORT="3002"
 API_WEB3_URL="http://127.0.0.1:3002"
 API_WEB3_CHAIN_ID="240"
 API_WEB3_MAX_BLOCK_RANGE="10"
+API_WEB3_WS_PORT="3003"
+API_WEB3_WS_URL="ws://127.0.0.1:3003"
+API_WEB3_MAX_FILTERS="1000"
+API_WEB3_FILTER_TIMEOUT_SEC="300"
+API_WEB3_SUBSCRIPTIONS_POLL_INTERVAL_MS="1000"
+API_WEB3_MAX_SUBSCRIPTIONS_PER_CONNECTION="100"
 API_PRIVATE_PORT="8090"
 API_PRIVATE_URL="http://127.0.0.1:8090"
 API_PROVER_PORT="8088"
@@ -370,5 +587,60 @@ API_PROMETHEUS_PORT="3312"
             config.web3.bind_addr(),
             SocketAddr::new(bind_broadcast_addr, config.web3.port)
         );
+        assert_eq!(
+            config.common.fee_quote_validity(),
+            Duration::from_secs(config.common.fee_quote_validity_sec)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has the same name as the CPK subsidy")]
+    fn subsidy_program_named_as_cpk_subsidy() {
+        let mut config = expected_config().common;
+        config.subsidy_programs[1].name = config.subsidy_name.clone();
+        validate_subsidy_names(&config);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not unique")]
+    fn subsidy_program_names_are_unique() {
+        let mut config = expected_config().common;
+        config.subsidy_programs[1].name = config.subsidy_programs[0].name.clone();
+        validate_subsidy_names(&config);
+    }
+
+    #[test]
+    fn subsidy_program_parsing() {
+        let program: SubsidyProgramConfig =
+            "CheapUSDC:*:2:*:*:50:5000000000:500000000".parse().unwrap();
+        assert_eq!(program, expected_config().common.subsidy_programs[1]);
+        assert_eq!(
+            program.budget_usd(),
+            Ratio::from_integer(BigUint::from(5000u32))
+        );
+        assert_eq!(
+            program.daily_cap_usd(),
+            Ratio::from_integer(BigUint::from(500u32))
+        );
+
+        // Unknown transaction type.
+        assert!("Program:Deposit:*:*:*:50:100:10"
+            .parse::<SubsidyProgramConfig>()
+            .is_err());
+        // Subsidy percent out of range.
+        assert!("Program:*:*:*:*:101:100:10"
+            .parse::<SubsidyProgramConfig>()
+            .is_err());
+        assert!("Program:*:*:*:*:0:100:10"
+            .parse::<SubsidyProgramConfig>()
+            .is_err());
+        // Invalid address.
+        assert!("Program:*:*:0x01:*:50:100:10"
+            .parse::<SubsidyProgramConfig>()
+            .is_err());
+        // Missing daily cap.
+        assert!("Program:*:*:*:*:50:100"
+            .parse::<SubsidyProgramConfig>()
+            .is_err());
     }
 }
@@ -16,6 +16,8 @@ pub struct ChainConfig {
     pub eth: Eth,
     /// State keeper / block generating configuration.
     pub state_keeper: StateKeeper,
+    /// Limits of the pending transactions pool.
+    pub mempool: Mempool,
 }
 
 impl ChainConfig {
@@ -24,6 +26,7 @@ impl ChainConfig {
             circuit: envy_load!("circuit", "CHAIN_CIRCUIT_"),
             eth: envy_load!("eth", "CHAIN_ETH_"),
             state_keeper: envy_load!("state_keeper", "CHAIN_STATE_KEEPER_"),
+            mempool: envy_load!("mempool", "CHAIN_MEMPOOL_"),
         }
     }
     pub fn max_blocks_to_aggregate(&self) -> u32 {
@@ -74,6 +77,16 @@ pub struct Eth {
     pub network: Network,
 }
 
+/// Order in which ready L2 transactions are taken from the mempool into blocks.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum TransactionsOrdering {
+    /// Transactions are taken in the order of their nonces.
+    Nonce,
+    /// Transactions and batches that pay more (in USD) per chunk are taken first.
+    /// Transactions of the same account are still taken in the order of their nonces.
+    FeePerChunk,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct StateKeeper {
     /// Block sizes to be generated by server. Has to contain only values set in the `supported_block_chunks_sizes`,
@@ -94,6 +107,11 @@ pub struct StateKeeper {
     pub block_prove_deadline: u64,
     pub block_execute_deadline: u64,
     pub max_aggregated_tx_gas: usize,
+    /// Order in which ready L2 transactions are included into blocks.
+    pub transactions_ordering: TransactionsOrdering,
+    /// Path to the on-disk storage of the account tree.
+    /// If not set, the whole tree is kept in memory.
+    pub account_tree_path: Option<String>,
 }
 
 impl StateKeeper {
@@ -115,6 +133,17 @@ impl StateKeeper {
     }
 }
 
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Mempool {
+    /// Maximum amount of pending transactions sent from a single account.
+    pub max_pending_txs_per_account: u32,
+    /// Maximum amount of pending transactions in the mempool. Once the limit is reached,
+    /// the transactions paying the least for a chunk are evicted to free the space for the new ones.
+    pub max_pending_txs: u32,
+    /// Maximum difference between the nonce of the new transaction and the current account nonce.
+    pub max_nonce_gap: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,6 +176,13 @@ mod tests {
                 block_prove_deadline: 3_000,
                 block_execute_deadline: 4_000,
                 max_aggregated_tx_gas: 4_000_000,
+                transactions_ordering: TransactionsOrdering::FeePerChunk,
+                account_tree_path: Some("/var/lib/zksync/account_tree".into()),
+            },
+            mempool: Mempool {
+                max_pending_txs_per_account: 100,
+                max_pending_txs: 50_000,
+                max_nonce_gap: 20,
             },
         }
     }
@@ -175,6 +211,11 @@ CHAIN_STATE_KEEPER_BLOCK_COMMIT_DEADLINE="300"
 CHAIN_STATE_KEEPER_BLOCK_PROVE_DEADLINE="3000"
 CHAIN_STATE_KEEPER_BLOCK_EXECUTE_DEADLINE="4000"
 CHAIN_STATE_KEEPER_MAX_AGGREGATED_TX_GAS="4000000"
+CHAIN_STATE_KEEPER_TRANSACTIONS_ORDERING="FeePerChunk"
+CHAIN_STATE_KEEPER_ACCOUNT_TREE_PATH="/var/lib/zksync/account_tree"
+CHAIN_MEMPOOL_MAX_PENDING_TXS_PER_ACCOUNT="100"
+CHAIN_MEMPOOL_MAX_PENDING_TXS="50000"
+CHAIN_MEMPOOL_MAX_NONCE_GAP="20"
         "#;
         set_env(config);
 
@@ -3,6 +3,16 @@ use serde::Deserialize;
 // Local uses
 use crate::envy_load;
 
+/// Backend signing the transactions of the operator accounts.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum OperatorSignerType {
+    /// Transactions are signed locally with the private key from the `ETHSenderConfig`.
+    PrivateKey,
+    /// Transactions are signed by the remote JSON-RPC signer (e.g. Web3Signer or Clef),
+    /// so the private key is never present on the server host.
+    JsonRpc,
+}
+
 /// Configuration for the Ethereum gateways.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct ETHClientConfig {
@@ -14,6 +24,19 @@ pub struct ETHClientConfig {
     pub gas_price_factor: f64,
     /// Address of the Ethereum node API.
     pub web3_url: Vec<String>,
+    /// Address of the Ethereum node WebSocket API. If set, the watchers subscribe to the
+    /// L1 events instead of polling the node on the interval.
+    pub web3_ws_url: Option<String>,
+    /// Backend signing the operator transactions.
+    pub operator_signer: OperatorSignerType,
+    /// Address of the remote signer API. Required for the `JsonRpc` signer.
+    pub operator_signer_url: Option<String>,
+    /// Amount of the healthiest Ethereum nodes queried in parallel for the read requests
+    /// when several nodes are configured. The first successful response is used.
+    pub multiplexer_hedged_requests: usize,
+    /// Amount of the Ethereum nodes which must agree on the result of the critical read requests
+    /// (block numbers and logs) when several nodes are configured.
+    pub multiplexer_quorum: usize,
 }
 
 impl ETHClientConfig {
@@ -28,6 +51,13 @@ impl ETHClientConfig {
             .cloned()
             .expect("Should be at least one")
     }
+
+    /// Get the remote signer url. Panics if it's not set.
+    pub fn operator_signer_url(&self) -> String {
+        self.operator_signer_url
+            .clone()
+            .expect("Operator signer url is required for the JSON-RPC signer")
+    }
 }
 
 #[cfg(test)]
@@ -43,6 +73,11 @@ mod tests {
                 "http://127.0.0.1:8545".into(),
                 "http://127.0.0.1:8546".into(),
             ],
+            web3_ws_url: Some("ws://127.0.0.1:8546".into()),
+            operator_signer: OperatorSignerType::JsonRpc,
+            operator_signer_url: Some("http://127.0.0.1:9000".into()),
+            multiplexer_hedged_requests: 2,
+            multiplexer_quorum: 2,
         }
     }
 
@@ -52,6 +87,11 @@ mod tests {
 ETH_CLIENT_CHAIN_ID="9"
 ETH_CLIENT_GAS_PRICE_FACTOR="1"
 ETH_CLIENT_WEB3_URL="http://127.0.0.1:8545,http://127.0.0.1:8546"
+ETH_CLIENT_WEB3_WS_URL="ws://127.0.0.1:8546"
+ETH_CLIENT_OPERATOR_SIGNER="JsonRpc"
+ETH_CLIENT_OPERATOR_SIGNER_URL="http://127.0.0.1:9000"
+ETH_CLIENT_MULTIPLEXER_HEDGED_REQUESTS="2"
+ETH_CLIENT_MULTIPLEXER_QUORUM="2"
         "#;
         set_env(config);
 
@@ -31,9 +31,19 @@ impl ETHSenderConfig {
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct Sender {
     /// Private key of the operator account.
-    pub operator_private_key: H256,
+    /// Not required if the transactions are signed by the remote signer, see `ETHClientConfig`.
+    pub operator_private_key: Option<H256>,
     /// Address of the operator account.
     pub operator_commit_eth_addr: Address,
+    /// Private keys of the additional operator accounts. Each account has its own nonce,
+    /// so the operations can be sent to L1 in parallel.
+    /// Used only if the transactions are signed locally.
+    #[serde(default)]
+    pub additional_operator_private_keys: Vec<H256>,
+    /// Addresses of the additional operator accounts which keys are held by the remote signer.
+    /// Used instead of the private keys if the transactions are signed by the remote signer.
+    #[serde(default)]
+    pub additional_operator_addresses: Vec<Address>,
     /// mount of confirmations required to consider L1 transaction committed.
     pub wait_confirmations: u64,
     /// Amount of blocks we will wait before considering L1 transaction stuck.
@@ -44,6 +54,8 @@ pub struct Sender {
     pub max_txs_in_flight: u64,
     /// Whether sender should interact with L1 or not.
     pub is_enabled: bool,
+    /// Interval between the reconciliations of the operator nonce with L1 in seconds.
+    pub nonce_reconciliation_interval: u64,
 }
 
 impl Sender {
@@ -51,6 +63,11 @@ impl Sender {
     pub fn tx_poll_period(&self) -> Duration {
         Duration::from_secs(self.tx_poll_period)
     }
+
+    /// Converts `self.nonce_reconciliation_interval` into `Duration`.
+    pub fn nonce_reconciliation_interval(&self) -> Duration {
+        Duration::from_secs(self.nonce_reconciliation_interval)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -63,6 +80,10 @@ pub struct GasLimit {
     pub sample_interval: u64,
     /// Scale factor for gas price limit (used by GasAdjuster).
     pub scale_factor: f64,
+    /// Max priority fee per gas (in wei) for EIP-1559 transactions.
+    pub priority_fee: u64,
+    /// Whether legacy transactions should be sent instead of EIP-1559 ones.
+    pub use_legacy_transactions: bool,
 }
 
 impl GasLimit {
@@ -90,16 +111,26 @@ mod tests {
                 tx_poll_period: 3,
                 max_txs_in_flight: 3,
                 is_enabled: true,
-                operator_private_key: hash(
+                nonce_reconciliation_interval: 60,
+                operator_private_key: Some(hash(
                     "27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be",
-                ),
+                )),
                 operator_commit_eth_addr: addr("de03a0B5963f75f1C8485B355fF6D30f3093BDE7"),
+                additional_operator_private_keys: vec![
+                    hash("0559b9f000b4e4bbb7fe02e1374cef9623c2ab7c3791204b490e1f229191d104"),
+                    hash("03c807e375d9a70fb5f21984496e018baed148dad00829b58d7ca9e557f2998c"),
+                ],
+                additional_operator_addresses: vec![addr(
+                    "8002cd98cfb563492a6fb3e7c8243b7b9ad4cc92",
+                )],
             },
             gas_price_limit: GasLimit {
                 default: 400000000000,
                 update_interval: 150,
                 sample_interval: 15,
                 scale_factor: 1.0f64,
+                priority_fee: 1500000000,
+                use_legacy_transactions: false,
             },
         }
     }
@@ -112,12 +143,17 @@ ETH_SENDER_SENDER_EXPECTED_WAIT_TIME_BLOCK="30"
 ETH_SENDER_SENDER_TX_POLL_PERIOD="3"
 ETH_SENDER_SENDER_MAX_TXS_IN_FLIGHT="3"
 ETH_SENDER_SENDER_IS_ENABLED="true"
+ETH_SENDER_SENDER_NONCE_RECONCILIATION_INTERVAL="60"
 ETH_SENDER_SENDER_OPERATOR_PRIVATE_KEY="0x27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be"
 ETH_SENDER_SENDER_OPERATOR_COMMIT_ETH_ADDR="0xde03a0B5963f75f1C8485B355fF6D30f3093BDE7"
+ETH_SENDER_SENDER_ADDITIONAL_OPERATOR_PRIVATE_KEYS="0x0559b9f000b4e4bbb7fe02e1374cef9623c2ab7c3791204b490e1f229191d104,0x03c807e375d9a70fb5f21984496e018baed148dad00829b58d7ca9e557f2998c"
+ETH_SENDER_SENDER_ADDITIONAL_OPERATOR_ADDRESSES="0x8002cd98cfb563492a6fb3e7c8243b7b9ad4cc92"
 ETH_SENDER_GAS_PRICE_LIMIT_DEFAULT="400000000000"
 ETH_SENDER_GAS_PRICE_LIMIT_UPDATE_INTERVAL="150"
 ETH_SENDER_GAS_PRICE_LIMIT_SAMPLE_INTERVAL="15"
 ETH_SENDER_GAS_PRICE_LIMIT_SCALE_FACTOR="1"
+ETH_SENDER_GAS_PRICE_LIMIT_PRIORITY_FEE="1500000000"
+ETH_SENDER_GAS_PRICE_LIMIT_USE_LEGACY_TRANSACTIONS="false"
         "#;
         set_env(config);
 
@@ -134,6 +170,10 @@ ETH_SENDER_GAS_PRICE_LIMIT_SCALE_FACTOR="1"
             config.sender.tx_poll_period(),
             Duration::from_secs(config.sender.tx_poll_period)
         );
+        assert_eq!(
+            config.sender.nonce_reconciliation_interval(),
+            Duration::from_secs(config.sender.nonce_reconciliation_interval)
+        );
 
         assert_eq!(
             config.gas_price_limit.update_interval(),
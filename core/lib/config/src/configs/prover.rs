@@ -75,6 +75,9 @@ pub struct WitnessGenerator {
     pub prepare_data_interval: u64,
     /// Amount of witness generator threads.
     pub witness_generators: usize,
+    /// Path to the directory with on-disk storages of the account trees (one per witness generator thread).
+    /// If not set, the trees are restored from the database cache and kept in memory.
+    pub account_tree_path: Option<String>,
 }
 
 impl WitnessGenerator {
@@ -104,6 +107,7 @@ mod tests {
             witness_generator: WitnessGenerator {
                 prepare_data_interval: 500,
                 witness_generators: 2,
+                account_tree_path: Some("/var/lib/zksync/witness_trees".into()),
             },
         }
     }
@@ -119,6 +123,7 @@ PROVER_CORE_GONE_TIMEOUT="60000"
 PROVER_CORE_IDLE_PROVERS="1"
 PROVER_WITNESS_GENERATOR_PREPARE_DATA_INTERVAL="500"
 PROVER_WITNESS_GENERATOR_WITNESS_GENERATORS="2"
+PROVER_WITNESS_GENERATOR_ACCOUNT_TREE_PATH="/var/lib/zksync/witness_trees"
         "#;
         set_env(config);
 
@@ -1,6 +1,8 @@
-use num::{rational::Ratio, BigUint};
+// Built-in uses
+use std::{str::FromStr, time::Duration};
 // External uses
-use serde::Deserialize;
+use num::{rational::Ratio, BigUint};
+use serde::{Deserialize, Deserializer};
 // Workspace uses
 use zksync_types::Address;
 use zksync_utils::scaled_u64_to_ratio;
@@ -13,11 +15,81 @@ pub enum TokenPriceSource {
     CoinMarketCap,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum UniswapVersion {
+    V2,
+    V3,
+}
+
+/// Uniswap pool used for obtaining the time-weighted average price of the token.
+///
+/// Configured as `<v2|v3>:<token>:<pool>:<quote token>`, where the quote token is the zkSync
+/// token the other side of the pool corresponds to (e.g. the zero address for WETH pools).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwapPoolConfig {
+    pub version: UniswapVersion,
+    pub token: Address,
+    pub pool: Address,
+    pub quote_token: Address,
+}
+
+impl FromStr for TwapPoolConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.trim().split(':').collect();
+        if parts.len() != 4 {
+            return Err(format!(
+                "Invalid TWAP pool '{}', expected '<v2|v3>:<token>:<pool>:<quote token>'",
+                s
+            ));
+        }
+        let version = match parts[0].to_lowercase().as_str() {
+            "v2" => UniswapVersion::V2,
+            "v3" => UniswapVersion::V3,
+            version => return Err(format!("Unknown Uniswap version '{}'", version)),
+        };
+        let parse_address = |address: &str| {
+            Address::from_str(address.trim_start_matches("0x"))
+                .map_err(|err| format!("Invalid address '{}': {}", address, err))
+        };
+
+        Ok(Self {
+            version,
+            token: parse_address(parts[1])?,
+            pool: parse_address(parts[2])?,
+            quote_token: parse_address(parts[3])?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for TwapPoolConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// Configuration for the fee ticker.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct TickerConfig {
     /// Indicator of the API to be used for getting token prices.
     pub token_price_source: TokenPriceSource,
+    /// APIs to be used for getting token prices. If several sources are set, the median
+    /// of their prices is used. If not set, only `token_price_source` is used.
+    #[serde(default)]
+    pub token_price_sources: Vec<TokenPriceSource>,
+    /// Maximum deviation of the price reported by the source from the median price, in percent.
+    /// Prices deviating further are considered outliers and ignored.
+    pub price_max_deviation_percent: u32,
+    /// Maximum age of the stored price to calculate fees with or to be used if no price can be obtained
+    /// from the sources, in seconds.
+    pub price_staleness_sec: u64,
+    /// Uniswap pools used as an additional price source for the tokens listed there.
+    #[serde(default)]
+    pub twap_pools: Vec<TwapPoolConfig>,
+    /// Period over which the prices of the Uniswap pools are averaged, in seconds.
+    pub twap_period_sec: u32,
     /// URL of CoinMarketCap API. Can be set to the mock server for local development.
     pub coinmarketcap_base_url: String,
     /// URL of CoinGecko API. Can be set to the mock server for local development.
@@ -40,6 +112,16 @@ pub struct TickerConfig {
     pub number_of_ticker_actors: u8,
     /// Subsidized price for ChangePubKey in cents scaled by SUBSIDY_USD_AMOUNTS_SCALE
     pub subsidy_cpk_price_usd_scaled: u64,
+    /// Number of the latest confirmed Ethereum transactions whose gas prices are used for fees.
+    pub gas_price_samples: u32,
+    /// Lower bound of the L1 gas price used for fees, in gwei.
+    pub min_l1_gas_price_gwei: u64,
+    /// Upper bound of the L1 gas price used for fees, in gwei.
+    pub max_l1_gas_price_gwei: u64,
+    /// Number of the latest blocks used to calculate the average block fill rate.
+    pub fill_rate_blocks: u32,
+    /// Lower bound of the block fill rate used for amortizing the L1 costs of blocks per chunk, in percent.
+    pub min_block_fill_percent: u32,
 }
 
 impl TickerConfig {
@@ -53,11 +135,60 @@ impl TickerConfig {
 
     /// Returns the token price source type and the corresponding API URL.
     pub fn price_source(&self) -> (TokenPriceSource, String) {
-        let url = match self.token_price_source {
+        (
+            self.token_price_source,
+            self.price_source_url(self.token_price_source),
+        )
+    }
+
+    /// Returns the token price sources to be aggregated and the corresponding API URLs.
+    pub fn price_sources(&self) -> Vec<(TokenPriceSource, String)> {
+        if self.token_price_sources.is_empty() {
+            return vec![self.price_source()];
+        }
+        self.token_price_sources
+            .iter()
+            .map(|&source| (source, self.price_source_url(source)))
+            .collect()
+    }
+
+    /// Maximum deviation of the price from the median price, as a fraction of the median.
+    pub fn price_max_deviation(&self) -> Ratio<BigUint> {
+        Ratio::new(
+            BigUint::from(self.price_max_deviation_percent),
+            BigUint::from(100u32),
+        )
+    }
+
+    pub fn price_staleness(&self) -> Duration {
+        Duration::from_secs(self.price_staleness_sec)
+    }
+
+    /// Bounds of the L1 gas price used for fees, in wei.
+    pub fn l1_gas_price_bounds(&self) -> (BigUint, BigUint) {
+        let gwei = BigUint::from(1_000_000_000u64);
+        (
+            BigUint::from(self.min_l1_gas_price_gwei) * &gwei,
+            BigUint::from(self.max_l1_gas_price_gwei) * gwei,
+        )
+    }
+
+    pub fn min_block_fill_rate(&self) -> Ratio<BigUint> {
+        Ratio::new(
+            BigUint::from(self.min_block_fill_percent),
+            BigUint::from(100u32),
+        )
+    }
+
+    pub fn twap_period(&self) -> Duration {
+        Duration::from_secs(self.twap_period_sec as u64)
+    }
+
+    fn price_source_url(&self, source: TokenPriceSource) -> String {
+        match source {
             TokenPriceSource::CoinGecko => self.coingecko_base_url.clone(),
             TokenPriceSource::CoinMarketCap => self.coinmarketcap_base_url.clone(),
-        };
-        (self.token_price_source, url)
+        }
     }
 }
 
@@ -69,6 +200,24 @@ mod tests {
     fn expected_config() -> TickerConfig {
         TickerConfig {
             token_price_source: TokenPriceSource::CoinGecko,
+            token_price_sources: vec![TokenPriceSource::CoinGecko, TokenPriceSource::CoinMarketCap],
+            price_max_deviation_percent: 10,
+            price_staleness_sec: 3600,
+            twap_pools: vec![
+                TwapPoolConfig {
+                    version: UniswapVersion::V2,
+                    token: addr("1f9840a85d5af5bf1d1762f925bdaddc4201f984"),
+                    pool: addr("d3d2e2692501a5c9ca623199d38826e513033a17"),
+                    quote_token: addr("0000000000000000000000000000000000000000"),
+                },
+                TwapPoolConfig {
+                    version: UniswapVersion::V3,
+                    token: addr("514910771af9ca656af840dff83e8264ecf986ca"),
+                    pool: addr("a6cc3c2531fdaa6ae1a3ca84c2855806728693e8"),
+                    quote_token: addr("0000000000000000000000000000000000000000"),
+                },
+            ],
+            twap_period_sec: 1800,
             coinmarketcap_base_url: "http://127.0.0.1:9876".into(),
             coingecko_base_url: "http://127.0.0.1:9876".into(),
             scale_fee_percent: 100,
@@ -80,6 +229,11 @@ mod tests {
             token_market_update_time: 120,
             number_of_ticker_actors: 4,
             subsidy_cpk_price_usd_scaled: 100,
+            gas_price_samples: 20,
+            min_l1_gas_price_gwei: 1,
+            max_l1_gas_price_gwei: 1000,
+            fill_rate_blocks: 50,
+            min_block_fill_percent: 10,
         }
     }
 
@@ -87,6 +241,11 @@ mod tests {
     fn from_env() {
         let config = r#"
 FEE_TICKER_TOKEN_PRICE_SOURCE="CoinGecko"
+FEE_TICKER_TOKEN_PRICE_SOURCES="CoinGecko,CoinMarketCap"
+FEE_TICKER_PRICE_MAX_DEVIATION_PERCENT=10
+FEE_TICKER_PRICE_STALENESS_SEC=3600
+FEE_TICKER_TWAP_POOLS="v2:0x1f9840a85d5af5bf1d1762f925bdaddc4201f984:0xd3d2e2692501a5c9ca623199d38826e513033a17:0x0000000000000000000000000000000000000000,v3:0x514910771af9ca656af840dff83e8264ecf986ca:0xa6cc3c2531fdaa6ae1a3ca84c2855806728693e8:0x0000000000000000000000000000000000000000"
+FEE_TICKER_TWAP_PERIOD_SEC=1800
 FEE_TICKER_COINMARKETCAP_BASE_URL="http://127.0.0.1:9876"
 FEE_TICKER_COINGECKO_BASE_URL="http://127.0.0.1:9876"
 FEE_TICKER_FAST_PROCESSING_COEFF="10"
@@ -99,6 +258,11 @@ FEE_TICKER_NUMBER_OF_TICKER_ACTORS="4"
 FEE_TICKER_SUBSIDIZED_TOKENS_LIMITS=156
 FEE_TICKER_SCALE_FEE_PERCENT=100
 FEE_TICKER_SUBSIDY_CPK_PRICE_USD_SCALED=100
+FEE_TICKER_GAS_PRICE_SAMPLES=20
+FEE_TICKER_MIN_L1_GAS_PRICE_GWEI=1
+FEE_TICKER_MAX_L1_GAS_PRICE_GWEI=1000
+FEE_TICKER_FILL_RATE_BLOCKS=50
+FEE_TICKER_MIN_BLOCK_FILL_PERCENT=10
         "#;
         set_env(config);
 
@@ -128,5 +292,53 @@ FEE_TICKER_SUBSIDY_CPK_PRICE_USD_SCALED=100
             config.price_source(),
             (TokenPriceSource::CoinMarketCap, COINMARKETCAP_URL.into())
         );
+
+        assert_eq!(
+            config.price_sources(),
+            vec![
+                (TokenPriceSource::CoinGecko, COINGECKO_URL.into()),
+                (TokenPriceSource::CoinMarketCap, COINMARKETCAP_URL.into())
+            ]
+        );
+        config.token_price_sources = Vec::new();
+        assert_eq!(
+            config.price_sources(),
+            vec![(TokenPriceSource::CoinMarketCap, COINMARKETCAP_URL.into())]
+        );
+        assert_eq!(
+            config.price_max_deviation(),
+            Ratio::new(BigUint::from(1u32), BigUint::from(10u32))
+        );
+        assert_eq!(config.price_staleness(), Duration::from_secs(3600));
+        assert_eq!(config.twap_period(), Duration::from_secs(1800));
+        assert_eq!(
+            config.l1_gas_price_bounds(),
+            (
+                BigUint::from(1_000_000_000u64),
+                BigUint::from(1_000_000_000_000u64)
+            )
+        );
+        assert_eq!(
+            config.min_block_fill_rate(),
+            Ratio::new(BigUint::from(1u32), BigUint::from(10u32))
+        );
+    }
+
+    #[test]
+    fn twap_pool_parsing() {
+        let pool: TwapPoolConfig = "V3:0x514910771af9ca656af840dff83e8264ecf986ca:0xa6cc3c2531fdaa6ae1a3ca84c2855806728693e8:0x0000000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+        assert_eq!(pool, expected_config().twap_pools[1]);
+
+        assert!("v4:0x514910771af9ca656af840dff83e8264ecf986ca:0xa6cc3c2531fdaa6ae1a3ca84c2855806728693e8:0x0000000000000000000000000000000000000000"
+            .parse::<TwapPoolConfig>()
+            .is_err());
+        assert!("v2:0x514910771af9ca656af840dff83e8264ecf986ca:0x0000000000000000000000000000000000000000"
+            .parse::<TwapPoolConfig>()
+            .is_err());
+        assert!("v2:0x51:0xa6cc3c2531fdaa6ae1a3ca84c2855806728693e8:0x0000000000000000000000000000000000000000"
+            .parse::<TwapPoolConfig>()
+            .is_err());
     }
 }
@@ -19,6 +19,8 @@ const UPGRADE_GATEKEEPER_CONTRACT_FILE: &str =
     "contracts/artifacts/cache/solpp-generated-contracts/UpgradeGatekeeper.sol/UpgradeGatekeeper.json";
 const FORCED_EXIT_CONTRACT_FILE: &str =
     "contracts/artifacts/cache/solpp-generated-contracts/ForcedExit.sol/ForcedExit.json";
+const UNISWAP_V2_PAIR_CONTRACT_FILE: &str = "etc/uniswap-abi/UniswapV2Pair.json";
+const UNISWAP_V3_POOL_CONTRACT_FILE: &str = "etc/uniswap-abi/UniswapV3Pool.json";
 
 fn read_file_to_json_value(path: &str) -> io::Result<serde_json::Value> {
     let zksync_home = std::env::var("ZKSYNC_HOME").unwrap_or_else(|_| ".".into());
@@ -116,3 +118,17 @@ pub fn forced_exit_contract() -> Contract {
         .to_string();
     Contract::load(abi_string.as_bytes()).expect("forced_exit contract abi")
 }
+
+pub fn uniswap_v2_pair_contract() -> Contract {
+    let abi_string = read_file_to_json_value(UNISWAP_V2_PAIR_CONTRACT_FILE)
+        .expect("couldn't read UNISWAP_V2_PAIR_CONTRACT_FILE")
+        .to_string();
+    Contract::load(abi_string.as_bytes()).expect("uniswap v2 pair contract abi")
+}
+
+pub fn uniswap_v3_pool_contract() -> Contract {
+    let abi_string = read_file_to_json_value(UNISWAP_V3_POOL_CONTRACT_FILE)
+        .expect("couldn't read UNISWAP_V3_POOL_CONTRACT_FILE")
+        .to_string();
+    Contract::load(abi_string.as_bytes()).expect("uniswap v3 pool contract abi")
+}
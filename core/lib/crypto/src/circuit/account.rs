@@ -12,12 +12,17 @@ use crate::{
         bellman::pairing::bn256::{Bn256, Fr},
         rescue::RescueEngine,
     },
-    merkle_tree::{hasher::Hasher, RescueHasher, SparseMerkleTree},
+    merkle_tree::{hasher::Hasher, PersistentSparseMerkleTree, RescueHasher, SparseMerkleTree},
     primitives::{GetBits, GetBitsFixed},
+    serialization::FrSerde,
 };
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Account tree used in the `zksync_circuit`.
 pub type CircuitAccountTree = SparseMerkleTree<CircuitAccount<Bn256>, Fr, RescueHasher<Bn256>>;
+/// Account tree used in the `zksync_circuit` which keeps its nodes in the persistent storage.
+pub type PersistentCircuitAccountTree =
+    PersistentSparseMerkleTree<CircuitAccount<Bn256>, RescueHasher<Bn256>>;
 /// Balance tree for accounts used in the `zksync_circuit`.
 pub type CircuitBalanceTree = SparseMerkleTree<Balance<Bn256>, Fr, RescueHasher<Bn256>>;
 
@@ -131,3 +136,141 @@ lazy_static! {
     static ref BALANCE_TREE: CircuitBalanceTree =
         SparseMerkleTree::new(params::balance_tree_depth());
 }
+
+/// Operations on the account tree required to build the block witness.
+///
+/// Implemented both by the in-memory and the persistent account trees,
+/// so the witness can be built without loading the whole tree into memory.
+pub trait CircuitTree {
+    fn capacity(&self) -> u64;
+    fn get(&self, index: u32) -> Option<CircuitAccount<Bn256>>;
+    fn insert(&mut self, index: u32, account: CircuitAccount<Bn256>);
+    fn remove(&mut self, index: u32) -> Option<CircuitAccount<Bn256>>;
+    fn root_hash(&self) -> Fr;
+    fn merkle_path(&self, index: u32) -> Vec<(Fr, bool)>;
+    fn hasher(&self) -> &RescueHasher<Bn256>;
+}
+
+impl CircuitTree for CircuitAccountTree {
+    fn capacity(&self) -> u64 {
+        self.capacity()
+    }
+
+    fn get(&self, index: u32) -> Option<CircuitAccount<Bn256>> {
+        self.get(index).cloned()
+    }
+
+    fn insert(&mut self, index: u32, account: CircuitAccount<Bn256>) {
+        self.insert(index, account)
+    }
+
+    fn remove(&mut self, index: u32) -> Option<CircuitAccount<Bn256>> {
+        self.remove(index)
+    }
+
+    fn root_hash(&self) -> Fr {
+        self.root_hash()
+    }
+
+    fn merkle_path(&self, index: u32) -> Vec<(Fr, bool)> {
+        self.merkle_path(index)
+    }
+
+    fn hasher(&self) -> &RescueHasher<Bn256> {
+        &self.hasher
+    }
+}
+
+impl CircuitTree for PersistentCircuitAccountTree {
+    fn capacity(&self) -> u64 {
+        self.capacity()
+    }
+
+    fn get(&self, index: u32) -> Option<CircuitAccount<Bn256>> {
+        self.get(index)
+    }
+
+    fn insert(&mut self, index: u32, account: CircuitAccount<Bn256>) {
+        self.insert(index, account)
+    }
+
+    fn remove(&mut self, index: u32) -> Option<CircuitAccount<Bn256>> {
+        self.remove(index)
+    }
+
+    fn root_hash(&self) -> Fr {
+        self.root_hash()
+    }
+
+    fn merkle_path(&self, index: u32) -> Vec<(Fr, bool)> {
+        self.merkle_path(index)
+    }
+
+    fn hasher(&self) -> &RescueHasher<Bn256> {
+        &self.hasher
+    }
+}
+
+/// Stored representation of the `CircuitAccount`: only the non-zero balances are kept,
+/// and the balance tree is rebuilt from them once the account is loaded.
+#[derive(Serialize, Deserialize)]
+struct StoredCircuitAccount {
+    #[serde(with = "FrSerde")]
+    nonce: Fr,
+    #[serde(with = "FrSerde")]
+    pub_key_hash: Fr,
+    #[serde(with = "FrSerde")]
+    address: Fr,
+    balances: Vec<StoredBalance>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredBalance {
+    token: u32,
+    #[serde(with = "FrSerde")]
+    value: Fr,
+}
+
+impl Serialize for CircuitAccount<Bn256> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut balances: Vec<_> = self
+            .subtree
+            .items
+            .iter()
+            .filter(|(_, balance)| !balance.value.is_zero())
+            .map(|(token, balance)| StoredBalance {
+                token: *token as u32,
+                value: balance.value,
+            })
+            .collect();
+        balances.sort_by_key(|balance| balance.token);
+
+        StoredCircuitAccount {
+            nonce: self.nonce,
+            pub_key_hash: self.pub_key_hash,
+            address: self.address,
+            balances,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CircuitAccount<Bn256> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stored = StoredCircuitAccount::deserialize(deserializer)?;
+
+        let mut account = CircuitAccount::default();
+        for balance in stored.balances {
+            account.subtree.insert(
+                balance.token,
+                Balance {
+                    value: balance.value,
+                },
+            );
+        }
+        account.nonce = stored.nonce;
+        account.pub_key_hash = stored.pub_key_hash;
+        account.address = stored.address;
+        Ok(account)
+    }
+}
@@ -1,11 +1,14 @@
 pub mod hasher;
 pub mod parallel_smt;
+pub mod persistent_smt;
 pub mod rescue_hasher;
 #[cfg(test)]
 mod tests;
 
 /// Sparse merkle tree used to calculate root hashes for the state in zkSync network.
 pub type SparseMerkleTree<T, H, HH> = parallel_smt::SparseMerkleTree<T, H, HH>;
+/// Sparse merkle tree which keeps its nodes in the persistent storage.
+pub type PersistentSparseMerkleTree<T, HH> = persistent_smt::PersistentSparseMerkleTree<T, HH>;
 /// Default hasher used in the zkSync network for state hash calculations.
 pub type RescueHasher<T> = rescue_hasher::RescueHasher<T>;
 
@@ -119,7 +119,7 @@ where
 }
 
 /// Merkle Tree branch node.
-#[derive(Debug, Clone, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
 pub struct Node {
     depth: Depth,
     index: NodeIndex,
@@ -734,6 +734,91 @@ impl SparseMerkleTreeSerializableCacheBN256 {
     }
 }
 
+impl SparseMerkleTreeSerializableCacheBN256 {
+    /// Calculates the difference between this cache and the cache of the same tree
+    /// taken at a later point of time.
+    /// The size of the diff is proportional to the number of the changed nodes.
+    pub fn diff(&self, newer: &Self) -> SparseMerkleTreeCacheDiffBN256 {
+        let updated_nodes = newer
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(node_ref, node)| self.nodes.get(*node_ref) != Some(*node))
+            .map(|(node_ref, node)| (node_ref, node.clone()))
+            .collect();
+
+        let old_cache: FnvHashMap<_, _> = self.cache.iter().cloned().collect();
+        let new_cache: FnvHashMap<_, _> = newer.cache.iter().cloned().collect();
+        let updated_hashes = newer
+            .cache
+            .iter()
+            .filter(|(idx, hash)| old_cache.get(idx) != Some(hash))
+            .cloned()
+            .collect();
+        let removed_hashes = self
+            .cache
+            .iter()
+            .filter(|(idx, _)| !new_cache.contains_key(idx))
+            .map(|(idx, _)| *idx)
+            .collect();
+
+        SparseMerkleTreeCacheDiffBN256 {
+            root: newer.root,
+            nodes_len: newer.nodes.len(),
+            updated_nodes,
+            updated_hashes,
+            removed_hashes,
+        }
+    }
+
+    /// Applies the diff obtained via [`diff`](Self::diff) to the older cache,
+    /// turning it into the newer one.
+    pub fn apply_diff(&mut self, diff: SparseMerkleTreeCacheDiffBN256) {
+        self.root = diff.root;
+        self.nodes.truncate(diff.nodes_len);
+        for (node_ref, node) in diff.updated_nodes {
+            if node_ref < self.nodes.len() {
+                self.nodes[node_ref] = node;
+            } else {
+                // Nodes are only appended to the tree, so new nodes go in order.
+                assert_eq!(node_ref, self.nodes.len(), "Inconsistent tree cache diff");
+                self.nodes.push(node);
+            }
+        }
+
+        let mut cache: FnvHashMap<_, _> = std::mem::take(&mut self.cache).into_iter().collect();
+        for idx in diff.removed_hashes {
+            cache.remove(&idx);
+        }
+        cache.extend(diff.updated_hashes);
+        self.cache = cache.into_iter().collect();
+    }
+}
+
+/// Difference between two states of the same tree cache, see
+/// [`SparseMerkleTreeSerializableCacheBN256::diff`].
+#[derive(Debug, Clone, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
+pub struct SparseMerkleTreeCacheDiffBN256 {
+    root: NodeRef,
+    nodes_len: usize,
+    updated_nodes: Vec<(NodeRef, Node)>,
+    updated_hashes: Vec<(NodeIndex, [u8; 32])>,
+    removed_hashes: Vec<NodeIndex>,
+}
+
+impl SparseMerkleTreeCacheDiffBN256 {
+    pub fn encode_bincode(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("Unable to encode Merkle Tree cache diff")
+    }
+
+    pub fn decode_bincode(data: &[u8]) -> Self {
+        bincode::decode_from_slice(data, bincode::config::standard())
+            .expect("Unable to decode Merkle Tree cache diff")
+            .0
+    }
+}
+
 impl<T, H> SparseMerkleTree<T, Fr, H>
 where
     T: GetBits,
@@ -0,0 +1,531 @@
+/// Sparse Merkle tree which keeps its nodes in the external key-value storage.
+use super::{hasher::Hasher, TreeMemoryUsage};
+use crate::{convert::FeConvert, primitives::GetBits, Fr};
+
+use fnv::{FnvHashMap, FnvHashSet};
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+/// Index of the node in the tree: root has index 1, and the children of node `N`
+/// have indices `2 * N` and `2 * N + 1`. Thus, leaf of the item `i` has index `(1 << depth) + i`.
+type NodeIndex = u64;
+
+/// Leaf index: 0 <= i < N.
+type ItemIndex = u64;
+
+/// Tree of depth N: 2 ^ N items, 0 <= level < depth
+type Depth = usize;
+
+/// Key prefix for the stored items.
+const ITEM_PREFIX: u8 = b'i';
+/// Key prefix for the stored node hashes.
+const NODE_PREFIX: u8 = b'n';
+/// Key of the version of the stored tree state.
+const VERSION_KEY: &[u8] = b"version";
+
+/// Set of changes to be written to the storage atomically.
+/// `None` value means that the key should be removed.
+pub type TreeStorageBatch = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+/// Key-value storage for the nodes and items of the [`PersistentSparseMerkleTree`].
+///
+/// Only the non-default nodes and items are stored, so the storage size is proportional
+/// to the number of the items in the tree.
+pub trait TreeStorage: Debug + Send + Sync {
+    /// Loads the value stored for the key.
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+    /// Loads all the key-value pairs which keys start with the given prefix, ordered by key.
+    fn scan_prefix(&self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)>;
+    /// Atomically applies the batch of changes.
+    /// The changes are not required to be durable until [`sync`](Self::sync) is called.
+    fn write_batch(&self, batch: TreeStorageBatch);
+    /// Makes all the written changes durable.
+    fn sync(&self) {}
+}
+
+/// Storage which keeps the data in memory. Mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryTreeStorage {
+    data: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl InMemoryTreeStorage {
+    /// Returns the amount of stored key-value pairs.
+    pub fn len(&self) -> usize {
+        self.data.read().expect("Read lock").len()
+    }
+
+    /// Returns `true` if nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TreeStorage for InMemoryTreeStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.data.read().expect("Read lock").get(key).cloned()
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.data
+            .read()
+            .expect("Read lock")
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    fn write_batch(&self, batch: TreeStorageBatch) {
+        let mut data = self.data.write().expect("Write lock");
+        for (key, value) in batch {
+            match value {
+                Some(value) => data.insert(key, value),
+                None => data.remove(&key),
+            };
+        }
+    }
+}
+
+/// Changes that are not yet written to the storage.
+#[derive(Debug, Clone)]
+struct PendingChanges<T> {
+    /// Updated items, `None` stands for the removed item.
+    items: FnvHashMap<ItemIndex, Option<T>>,
+    /// Recalculated node hashes, `None` stands for the default hash for the node depth.
+    nodes: FnvHashMap<NodeIndex, Option<Fr>>,
+    /// Leaves which hashes (and the hashes of their parents) have to be recalculated.
+    dirty_leaves: FnvHashSet<NodeIndex>,
+    /// Versions the updated items were sealed with, see [`PersistentSparseMerkleTree::seal_pending_changes`].
+    /// Items updated after the last seal are absent.
+    sealed_items: FnvHashMap<ItemIndex, u64>,
+}
+
+impl<T> Default for PendingChanges<T> {
+    fn default() -> Self {
+        Self {
+            items: FnvHashMap::default(),
+            nodes: FnvHashMap::default(),
+            dirty_leaves: FnvHashSet::default(),
+            sealed_items: FnvHashMap::default(),
+        }
+    }
+}
+
+/// Sparse Merkle tree which keeps the items and the node hashes in the [`TreeStorage`]
+/// and loads them lazily, so the tree doesn't have to fit into RAM.
+///
+/// The tree yields exactly the same root hashes and Merkle paths as the in-memory
+/// [`SparseMerkleTree`](super::parallel_smt::SparseMerkleTree).
+///
+/// Updates are accumulated in memory and only the affected paths are rehashed once the
+/// root hash is requested. Changes are written to the storage on [`flush`](Self::flush),
+/// which is expected to be called once the state is finalized (e.g. the block is sealed).
+/// Every flush is marked with the version (e.g. the block number), so after the restart
+/// it's known which state the storage corresponds to.
+///
+/// Cloned trees share the same storage, so only one of the clones is expected to be flushed.
+/// Other clones may be ahead of the flushed one: they should seal their changes with the
+/// versions and discard the ones flushed by the other clone, so the changes don't pile up
+/// in memory.
+#[derive(Debug)]
+pub struct PersistentSparseMerkleTree<T, H>
+where
+    T: GetBits,
+    H: Hasher<Fr>,
+{
+    /// Generic hasher for the hash calculation.
+    pub hasher: H,
+    /// Fixed depth of the tree, determining the overall tree capacity.
+    tree_depth: Depth,
+    /// Cache of the hashes for the "default" nodes (e.g. ones that are absent in the tree).
+    prehashed: Vec<Fr>,
+    /// Storage holding the flushed state of the tree.
+    storage: Arc<dyn TreeStorage>,
+    /// Changes applied to the tree since the last flush.
+    /// `RwLock` is required to keep `root_hash` immutable, same as for the in-memory tree.
+    pending: RwLock<PendingChanges<T>>,
+}
+
+// Manual implementation of `Clone` is required, since `RwLock` is not `Clone`.
+impl<T, H> Clone for PersistentSparseMerkleTree<T, H>
+where
+    T: GetBits + Clone,
+    H: Hasher<Fr> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            hasher: self.hasher.clone(),
+            tree_depth: self.tree_depth,
+            prehashed: self.prehashed.clone(),
+            storage: self.storage.clone(),
+            pending: RwLock::new(self.pending.read().expect("Read lock").clone()),
+        }
+    }
+}
+
+impl<T, H> PersistentSparseMerkleTree<T, H>
+where
+    T: GetBits + Default + Clone + Serialize + DeserializeOwned + Send + Sync,
+    H: Hasher<Fr> + Default + Sync,
+{
+    /// Creates a tree of certain depth on top of the storage.
+    /// If the storage already contains the tree, it will be used as the initial state.
+    pub fn new(tree_depth: Depth, storage: Arc<dyn TreeStorage>) -> Self {
+        assert!(tree_depth > 1);
+        let hasher = H::default();
+
+        let mut prehashed = Vec::with_capacity(tree_depth);
+        let mut cur = hasher.hash_bits(T::default().get_bits_le());
+        prehashed.push(cur);
+        for i in 0..tree_depth {
+            cur = hasher.compress(&cur, &cur, i);
+            prehashed.push(cur);
+        }
+        prehashed.reverse();
+
+        Self {
+            hasher,
+            tree_depth,
+            prehashed,
+            storage,
+            pending: RwLock::new(PendingChanges::default()),
+        }
+    }
+
+    /// Returns the capacity of the tree (how many items can the tree hold).
+    pub fn capacity(&self) -> u64 {
+        1 << self.tree_depth
+    }
+
+    /// Obtains the element for a certain index.
+    pub fn get(&self, index: u32) -> Option<T> {
+        let index = ItemIndex::from(index);
+        if let Some(item) = self.pending.read().expect("Read lock").items.get(&index) {
+            return item.clone();
+        }
+        self.load_item(index)
+    }
+
+    /// Returns all the items of the tree ordered by index.
+    /// Note that it requires loading all the items from the storage.
+    pub fn items(&self) -> Vec<(u32, T)> {
+        let pending = self.pending.read().expect("Read lock");
+        let mut items: BTreeMap<ItemIndex, T> = self
+            .storage
+            .scan_prefix(&[ITEM_PREFIX])
+            .into_iter()
+            .filter_map(|(key, bytes)| {
+                let index = key_index(&key);
+                if pending.items.contains_key(&index) {
+                    return None;
+                }
+                let item = serde_json::from_slice(&bytes)
+                    .expect("Unable to decode stored Merkle tree item");
+                Some((index, item))
+            })
+            .collect();
+        for (index, item) in &pending.items {
+            if let Some(item) = item {
+                items.insert(*index, item.clone());
+            }
+        }
+
+        items
+            .into_iter()
+            .map(|(index, item)| (index as u32, item))
+            .collect()
+    }
+
+    /// Inserts an element to the tree.
+    pub fn insert(&mut self, index: u32, item: T) {
+        let index = ItemIndex::from(index);
+        assert!(index < self.capacity());
+
+        self.set_item(index, Some(item));
+    }
+
+    /// Removes an element with a given index, and returns the removed
+    /// element (if it existed in the tree).
+    pub fn remove(&mut self, index: u32) -> Option<T> {
+        let old = self.get(index);
+        self.set_item(ItemIndex::from(index), None);
+        old
+    }
+
+    /// Returns the Merkle root hash of the tree. Only the paths of the items updated
+    /// since the previous invocation are rehashed.
+    pub fn root_hash(&self) -> Fr {
+        let mut pending = self.pending.write().expect("Write lock");
+        self.rehash_dirty_paths(&mut pending);
+        self.node_hash(&pending, 1, 0)
+    }
+
+    /// Creates a proof of existence for a certain element of the tree.
+    /// Returned value is a list of pairs, where the first element is
+    /// the aggregated coupling hash for current layer, and the second is
+    /// the direction.
+    pub fn merkle_path(&self, index: u32) -> Vec<(Fr, bool)> {
+        assert!(ItemIndex::from(index) < self.capacity());
+
+        let mut pending = self.pending.write().expect("Write lock");
+        self.rehash_dirty_paths(&mut pending);
+
+        let mut cur_index: NodeIndex = (1 << self.tree_depth) + ItemIndex::from(index);
+        let mut proof = Vec::with_capacity(self.tree_depth);
+        for depth in (1..=self.tree_depth).rev() {
+            let neighbor_hash = self.node_hash(&pending, cur_index ^ 1, depth);
+            let going_right = (cur_index & 1) != 0;
+            proof.push((neighbor_hash, going_right));
+            cur_index >>= 1;
+        }
+        proof
+    }
+
+    /// Verifies the given proof for the given element and index.
+    pub fn verify_proof(&self, element_index: u32, element: T, proof: Vec<(Fr, bool)>) -> bool {
+        let mut proof_index = 0;
+        let mut aggregated_hash = self.hasher.hash_bits(element.get_bits_le());
+        for (level, (hash, dir)) in proof.into_iter().enumerate() {
+            let (lhs, rhs) = if dir {
+                proof_index |= 1 << level;
+                (hash, aggregated_hash)
+            } else {
+                (aggregated_hash, hash)
+            };
+
+            aggregated_hash = self.hasher.compress(&lhs, &rhs, level);
+        }
+        proof_index == element_index && aggregated_hash == self.root_hash()
+    }
+
+    /// Returns the version passed to the last [`flush`](Self::flush), or `None` if nothing
+    /// was flushed to the storage yet.
+    pub fn version(&self) -> Option<u64> {
+        self.storage.get(VERSION_KEY).map(|bytes| {
+            let mut version = [0u8; 8];
+            version.copy_from_slice(&bytes);
+            u64::from_be_bytes(version)
+        })
+    }
+
+    /// Writes all the pending changes to the storage along with the version of the new state.
+    pub fn flush(&mut self, version: u64) {
+        let mut pending = std::mem::take(self.pending.get_mut().expect("Write lock"));
+        self.rehash_dirty_paths(&mut pending);
+
+        let items = pending.items.into_iter().map(|(index, item)| {
+            let value = item
+                .map(|item| serde_json::to_vec(&item).expect("Unable to encode Merkle tree item"));
+            (item_key(index), value)
+        });
+        let nodes = pending
+            .nodes
+            .into_iter()
+            .map(|(index, hash)| (node_key(index), hash.map(|hash| hash.to_bytes())));
+        let version = (VERSION_KEY.to_vec(), Some(version.to_be_bytes().to_vec()));
+        self.storage
+            .write_batch(items.chain(nodes).chain(Some(version)).collect());
+        self.storage.sync();
+    }
+
+    /// Marks the changes made since the previous seal as the ones belonging to the `version`.
+    /// Once the storage is flushed with this (or later) version by another clone of the tree,
+    /// these changes are discarded by [`discard_flushed_changes`](Self::discard_flushed_changes).
+    pub fn seal_pending_changes(&mut self, version: u64) {
+        let pending = self.pending.get_mut().expect("Write lock");
+        for index in pending.items.keys() {
+            pending.sealed_items.entry(*index).or_insert(version);
+        }
+    }
+
+    /// Drops the sealed changes which are already written to the storage by another clone
+    /// of the tree. Changes made after the flushed version are kept, so the tree state remains
+    /// the same.
+    pub fn discard_flushed_changes(&mut self) {
+        let flushed_version = match self.version() {
+            Some(version) => version,
+            None => return,
+        };
+        let tree_depth = self.tree_depth;
+        let pending = self.pending.get_mut().expect("Write lock");
+        let flushed: Vec<_> = pending
+            .sealed_items
+            .iter()
+            .filter(|(_, version)| **version <= flushed_version)
+            .map(|(index, _)| *index)
+            .collect();
+        if flushed.is_empty() {
+            return;
+        }
+        for index in flushed {
+            pending.items.remove(&index);
+            pending.sealed_items.remove(&index);
+        }
+
+        // Hashes of the nodes depend on both kept and discarded items, so they are
+        // recalculated on top of the storage for the kept items only. Paths of the discarded
+        // items are either not affected by the kept ones, or recalculated along with them.
+        pending.nodes.clear();
+        pending.dirty_leaves = pending
+            .items
+            .keys()
+            .map(|index| (1 << tree_depth) + index)
+            .collect();
+    }
+
+    /// Drops the changes made since the last flush, returning the tree to the stored state.
+    pub fn discard_pending_changes(&mut self) {
+        *self.pending.get_mut().expect("Write lock") = PendingChanges::default();
+    }
+
+    /// Removes everything from the storage, making the tree empty.
+    pub fn clear(&mut self) {
+        self.discard_pending_changes();
+
+        let mut batch: TreeStorageBatch = vec![(VERSION_KEY.to_vec(), None)];
+        for prefix in [ITEM_PREFIX, NODE_PREFIX] {
+            batch.extend(
+                self.storage
+                    .scan_prefix(&[prefix])
+                    .into_iter()
+                    .map(|(key, _)| (key, None)),
+            );
+        }
+        self.storage.write_batch(batch);
+    }
+
+    /// Returns `true` if there are changes that were not flushed to the storage.
+    pub fn has_pending_changes(&self) -> bool {
+        let pending = self.pending.read().expect("Read lock");
+        !pending.items.is_empty() || !pending.nodes.is_empty()
+    }
+
+    /// Roughly calculates the data on the RAM usage for this tree object.
+    /// Only the pending changes are kept in memory, the rest of the tree is in the storage.
+    pub fn memory_stats(&self) -> TreeMemoryUsage {
+        use std::mem::size_of;
+
+        let pending = self.pending.read().expect("Read lock");
+        let items = pending.items.capacity() * (size_of::<ItemIndex>() + size_of::<Option<T>>())
+            + pending.sealed_items.capacity() * (size_of::<ItemIndex>() + size_of::<u64>());
+        let nodes = pending.dirty_leaves.capacity() * size_of::<NodeIndex>();
+        let prehashed = self.prehashed.capacity() * size_of::<Fr>();
+        let cache = pending.nodes.capacity() * (size_of::<NodeIndex>() + size_of::<Option<Fr>>());
+        let allocated_total = items + nodes + prehashed + cache;
+
+        TreeMemoryUsage {
+            items,
+            nodes,
+            prehashed,
+            cache,
+            allocated_total,
+        }
+    }
+
+    fn set_item(&mut self, index: ItemIndex, item: Option<T>) {
+        let leaf_index = (1 << self.tree_depth) + index;
+        let pending = self.pending.get_mut().expect("Write lock");
+        pending.items.insert(index, item);
+        pending.sealed_items.remove(&index);
+        pending.dirty_leaves.insert(leaf_index);
+    }
+
+    /// Loads the item from the storage, ignoring the pending changes.
+    fn load_item(&self, index: ItemIndex) -> Option<T> {
+        self.storage.get(&item_key(index)).map(|bytes| {
+            serde_json::from_slice(&bytes).expect("Unable to decode stored Merkle tree item")
+        })
+    }
+
+    /// Recalculates the hashes of the dirty leaves and all their parents, layer by layer.
+    fn rehash_dirty_paths(&self, pending: &mut PendingChanges<T>) {
+        if pending.dirty_leaves.is_empty() {
+            return;
+        }
+
+        // Leaf hashes are the most expensive to calculate, so they are calculated in parallel.
+        let mut layer: Vec<NodeIndex> = pending.dirty_leaves.drain().collect();
+        let pending_ref = &*pending;
+        let leaf_hashes: Vec<_> = layer
+            .par_iter()
+            .map(|&leaf_index| {
+                let item_index = leaf_index - (1 << self.tree_depth);
+                let item = match pending_ref.items.get(&item_index) {
+                    Some(item) => item.clone(),
+                    None => self.load_item(item_index),
+                };
+                let hash = item.map(|item| self.hasher.hash_bits(item.get_bits_le()));
+                (leaf_index, hash)
+            })
+            .collect();
+        self.store_hashes(pending, leaf_hashes, self.tree_depth);
+
+        for depth in (0..self.tree_depth).rev() {
+            layer = layer.into_iter().map(|index| index >> 1).collect();
+            layer.sort_unstable();
+            layer.dedup();
+
+            let level = self.tree_depth - depth - 1;
+            let pending_ref = &*pending;
+            let hashes: Vec<_> = layer
+                .par_iter()
+                .map(|&index| {
+                    let lhs = self.node_hash(pending_ref, index * 2, depth + 1);
+                    let rhs = self.node_hash(pending_ref, index * 2 + 1, depth + 1);
+                    (index, Some(self.hasher.compress(&lhs, &rhs, level)))
+                })
+                .collect();
+            self.store_hashes(pending, hashes, depth);
+        }
+    }
+
+    /// Stores the calculated hashes of the nodes on the same depth.
+    /// Default hashes are stored as `None`, so they are removed from the storage.
+    fn store_hashes(
+        &self,
+        pending: &mut PendingChanges<T>,
+        hashes: Vec<(NodeIndex, Option<Fr>)>,
+        depth: Depth,
+    ) {
+        for (index, hash) in hashes {
+            let hash = hash.filter(|hash| *hash != self.prehashed[depth]);
+            pending.nodes.insert(index, hash);
+        }
+    }
+
+    /// Obtains the hash of the node, assuming that it is already calculated.
+    /// Nodes absent both in the pending changes and in the storage have default hashes.
+    fn node_hash(&self, pending: &PendingChanges<T>, index: NodeIndex, depth: Depth) -> Fr {
+        let hash = match pending.nodes.get(&index) {
+            Some(hash) => *hash,
+            None => self.storage.get(&node_key(index)).map(|bytes| {
+                Fr::from_bytes(&bytes).expect("Unable to decode stored Merkle tree node")
+            }),
+        };
+        hash.unwrap_or(self.prehashed[depth])
+    }
+}
+
+fn item_key(index: ItemIndex) -> Vec<u8> {
+    let mut key = vec![ITEM_PREFIX];
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+fn node_key(index: NodeIndex) -> Vec<u8> {
+    let mut key = vec![NODE_PREFIX];
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+fn key_index(key: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&key[1..]);
+    u64::from_be_bytes(bytes)
+}
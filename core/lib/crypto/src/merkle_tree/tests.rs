@@ -169,3 +169,159 @@ fn big_test() {
     let root_hash: Fr = crate::ff::from_hex(&input.root_hash).unwrap();
     assert_eq!(root_hash, tree.root_hash());
 }
+
+/// Checks that the persistent tree yields the same root hashes and Merkle paths as the
+/// in-memory one, including the case when it's restored from the storage.
+#[test]
+fn persistent_tree_matches_in_memory_tree() {
+    use crate::merkle_tree::persistent_smt::{InMemoryTreeStorage, PersistentSparseMerkleTree};
+    use std::sync::Arc;
+
+    let depth = 6;
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let storage = Arc::new(InMemoryTreeStorage::default());
+
+    let mut tree = parallel_smt::SparseMerkleTree::<u64, Fr, RescueHasher<Engine>>::new(depth);
+    let mut persistent_tree =
+        PersistentSparseMerkleTree::<u64, RescueHasher<Engine>>::new(depth, storage.clone());
+    assert_eq!(tree.root_hash(), persistent_tree.root_hash());
+
+    for step in 0..40 {
+        let idx = rng.gen::<u32>() % (1 << depth);
+        if step % 5 == 4 {
+            tree.remove(idx);
+            persistent_tree.remove(idx);
+        } else {
+            let item = rng.gen::<u64>();
+            tree.insert(idx, item);
+            persistent_tree.insert(idx, item);
+        }
+        if step % 3 == 0 {
+            assert_eq!(tree.root_hash(), persistent_tree.root_hash());
+        }
+        if step % 10 == 9 {
+            persistent_tree.flush(step);
+            assert!(!persistent_tree.has_pending_changes());
+        }
+    }
+    persistent_tree.insert(0, 1);
+    persistent_tree.discard_pending_changes();
+    assert!(!persistent_tree.has_pending_changes());
+    persistent_tree.flush(40);
+
+    // Nodes are loaded lazily from the storage by the new instance of the tree.
+    let mut restored_tree =
+        PersistentSparseMerkleTree::<u64, RescueHasher<Engine>>::new(depth, storage.clone());
+    assert_eq!(tree.root_hash(), restored_tree.root_hash());
+    for idx in 0..(1 << depth) {
+        assert_eq!(tree.merkle_path(idx), restored_tree.merkle_path(idx));
+        assert_eq!(
+            tree.get(idx).filter(|item| **item != 0).cloned(),
+            restored_tree.get(idx).filter(|item| *item != 0)
+        );
+    }
+    let item = restored_tree.get(3).unwrap_or_default();
+    assert!(restored_tree.verify_proof(3, item, restored_tree.merkle_path(3)));
+    assert_eq!(restored_tree.version(), Some(40));
+
+    restored_tree.clear();
+    assert_eq!(restored_tree.version(), None);
+    assert!(storage.is_empty());
+    assert_eq!(
+        restored_tree.root_hash(),
+        parallel_smt::SparseMerkleTree::<u64, Fr, RescueHasher<Engine>>::new(depth).root_hash()
+    );
+}
+
+/// Checks that the clone of the persistent tree which is ahead of the flushed one
+/// drops the flushed changes and keeps the same state.
+#[test]
+fn persistent_tree_discards_flushed_changes() {
+    use crate::merkle_tree::persistent_smt::{InMemoryTreeStorage, PersistentSparseMerkleTree};
+    use std::sync::Arc;
+
+    let depth = 6;
+    let storage = Arc::new(InMemoryTreeStorage::default());
+    let mut tree = parallel_smt::SparseMerkleTree::<u64, Fr, RescueHasher<Engine>>::new(depth);
+    let mut flushed_tree =
+        PersistentSparseMerkleTree::<u64, RescueHasher<Engine>>::new(depth, storage);
+    let mut tree_ahead = flushed_tree.clone();
+
+    // Item 1 is changed in the first block and restored in the second one, so it must not
+    // be read from the storage after the first block is flushed.
+    let blocks: Vec<Vec<(u32, u64)>> = vec![vec![(1, 10), (2, 20)], vec![(1, 0), (3, 30)]];
+    for (block, updates) in blocks.iter().enumerate() {
+        for &(idx, item) in updates {
+            tree.insert(idx, item);
+            tree_ahead.insert(idx, item);
+        }
+        tree_ahead.seal_pending_changes(block as u64);
+    }
+    tree_ahead.insert(4, 40);
+    tree.insert(4, 40);
+
+    for &(idx, item) in &blocks[0] {
+        flushed_tree.insert(idx, item);
+    }
+    flushed_tree.flush(0);
+    tree_ahead.discard_flushed_changes();
+
+    assert_eq!(tree.root_hash(), tree_ahead.root_hash());
+    assert_eq!(tree_ahead.get(1), Some(0));
+    assert_eq!(tree_ahead.get(2), Some(20));
+    for idx in 0..5 {
+        assert_eq!(tree.merkle_path(idx), tree_ahead.merkle_path(idx));
+    }
+
+    // Once everything sealed is flushed, only the unsealed change is kept.
+    for &(idx, item) in &blocks[1] {
+        flushed_tree.insert(idx, item);
+    }
+    flushed_tree.flush(1);
+    tree_ahead.discard_flushed_changes();
+    assert_eq!(tree.root_hash(), tree_ahead.root_hash());
+    tree_ahead.discard_pending_changes();
+    assert_eq!(tree_ahead.get(4), None);
+    assert_eq!(tree_ahead.root_hash(), flushed_tree.root_hash());
+}
+
+/// Checks that applying the cache diff to the older cache yields the newer cache.
+#[test]
+fn tree_cache_diff() {
+    use crate::merkle_tree::parallel_smt::{
+        SparseMerkleTreeCacheDiffBN256, SparseMerkleTreeSerializableCacheBN256,
+    };
+
+    let depth = 8;
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let mut tree = parallel_smt::SparseMerkleTree::<u64, Fr, RescueHasher<Engine>>::new(depth);
+    for idx in 0..100 {
+        tree.insert(idx, rng.gen());
+    }
+    tree.root_hash();
+    let mut cache = tree.get_internals();
+
+    for _ in 0..3 {
+        // Update some of the existing items and add a few new ones.
+        for _ in 0..5 {
+            tree.insert(rng.gen::<u32>() % (1 << depth), rng.gen());
+        }
+        tree.root_hash();
+        let new_cache = tree.get_internals();
+
+        let diff = cache.diff(&new_cache).encode_bincode();
+        assert!(diff.len() < new_cache.encode_bincode().len());
+        cache.apply_diff(SparseMerkleTreeCacheDiffBN256::decode_bincode(&diff));
+
+        let mut restored_tree =
+            parallel_smt::SparseMerkleTree::<u64, Fr, RescueHasher<Engine>>::new(depth);
+        for (idx, item) in &tree.items {
+            restored_tree.insert(*idx as u32, *item);
+        }
+        restored_tree.set_internals(SparseMerkleTreeSerializableCacheBN256::decode_bincode(
+            &cache.encode_bincode(),
+        ));
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+        assert_eq!(restored_tree.merkle_path(7), tree.merkle_path(7));
+    }
+}
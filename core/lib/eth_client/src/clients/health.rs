@@ -0,0 +1,210 @@
+//! Health tracking for the clients of the `MultiplexerEthereumClient`.
+
+use std::time::Duration;
+
+/// Weight of the newest observation in the exponential moving averages.
+const SMOOTHING_FACTOR: f64 = 0.2;
+/// Latency which halves the health score of the client.
+const REFERENCE_LATENCY_MS: f64 = 500.0;
+/// Lag (in blocks) which halves the health score of the client.
+const REFERENCE_LAG_BLOCKS: f64 = 2.0;
+
+/// Health statistics of a single Ethereum client.
+///
+/// The statistics are updated on every request made through the multiplexer, and
+/// the block height is also updated by the `MultiplexedGatewayWatcher`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientHealth {
+    /// Exponential moving average of the successful requests latency, in milliseconds.
+    pub latency_ms: f64,
+    /// Exponential moving average of the error rate, from 0 (no errors) to 1 (every request fails).
+    pub error_rate: f64,
+    /// The latest block number reported by the client.
+    pub block_number: u64,
+}
+
+/// Snapshot of the client health, exported to the monitoring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientHealthReport {
+    pub name: String,
+    pub score: f64,
+    pub latency_ms: f64,
+    pub error_rate: f64,
+    /// Amount of blocks the client is behind the most advanced one.
+    pub block_lag: u64,
+}
+
+impl ClientHealth {
+    /// Records the successful request.
+    pub fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Self::smooth(self.latency_ms, latency_ms);
+        self.error_rate = Self::smooth(self.error_rate, 0.0);
+    }
+
+    /// Records the failed request.
+    pub fn record_failure(&mut self) {
+        self.error_rate = Self::smooth(self.error_rate, 1.0);
+    }
+
+    /// Records the latest block number reported by the client.
+    pub fn record_block_number(&mut self, block_number: u64) {
+        self.block_number = self.block_number.max(block_number);
+    }
+
+    /// Returns the amount of blocks the client is behind the given block.
+    pub fn lag(&self, latest_block_number: u64) -> u64 {
+        latest_block_number.saturating_sub(self.block_number)
+    }
+
+    /// Calculates the health score of the client, from 0 (unusable) to 1 (perfect).
+    ///
+    /// Each of the latency, error rate and block lag reduces the score independently:
+    /// the score is a product of `1 - error_rate`, `1 / (1 + latency / REFERENCE_LATENCY)`
+    /// and `1 / (1 + lag / REFERENCE_LAG)`.
+    pub fn score(&self, latest_block_number: u64) -> f64 {
+        let latency_factor = 1.0 / (1.0 + self.latency_ms / REFERENCE_LATENCY_MS);
+        let lag_factor = 1.0 / (1.0 + self.lag(latest_block_number) as f64 / REFERENCE_LAG_BLOCKS);
+        (1.0 - self.error_rate) * latency_factor * lag_factor
+    }
+
+    fn smooth(average: f64, value: f64) -> f64 {
+        average + SMOOTHING_FACTOR * (value - average)
+    }
+}
+
+/// Returns the highest block number known to the clients.
+pub fn latest_block_number(health: &[ClientHealth]) -> u64 {
+    health
+        .iter()
+        .map(|health| health.block_number)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Returns the indices of the clients ordered by their health score, the healthiest first.
+/// Clients with equal scores keep their order, except for the `preferred` one, which goes first.
+pub fn rank_clients(health: &[ClientHealth], preferred: usize) -> Vec<usize> {
+    let latest_block_number = latest_block_number(health);
+    let mut indices: Vec<usize> = (0..health.len()).collect();
+    indices.sort_by(|&lhs, &rhs| {
+        let lhs_score = health[lhs].score(latest_block_number);
+        let rhs_score = health[rhs].score(latest_block_number);
+        rhs_score
+            .partial_cmp(&lhs_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| (rhs == preferred).cmp(&(lhs == preferred)))
+    });
+    indices
+}
+
+/// Returns the highest block number reached by at least `quorum` clients.
+pub fn quorum_block_number(mut block_numbers: Vec<u64>, quorum: usize) -> Option<u64> {
+    block_numbers.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+    quorum
+        .checked_sub(1)
+        .and_then(|idx| block_numbers.get(idx))
+        .copied()
+}
+
+/// Returns the value reported by at least `quorum` clients.
+/// Values are considered the same if their keys are equal, so the fields which may
+/// legitimately differ between clients can be left out of the key.
+pub fn quorum_value<'a, T: Clone + 'a, K: PartialEq>(
+    values: impl IntoIterator<Item = &'a T>,
+    quorum: usize,
+    key: impl Fn(&T) -> K,
+) -> Option<T> {
+    let mut counts: Vec<(K, &T, usize)> = Vec::new();
+    for value in values {
+        let value_key = key(value);
+        match counts.iter_mut().find(|(known, _, _)| *known == value_key) {
+            Some((_, _, count)) => *count += 1,
+            None => counts.push((value_key, value, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .find(|(_, _, count)| *count >= quorum)
+        .map(|(_, value, _)| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(latency_ms: f64, error_rate: f64, block_number: u64) -> ClientHealth {
+        ClientHealth {
+            latency_ms,
+            error_rate,
+            block_number,
+        }
+    }
+
+    #[test]
+    fn health_statistics() {
+        let mut client_health = ClientHealth::default();
+        client_health.record_success(Duration::from_millis(100));
+        assert!((client_health.latency_ms - 20.0).abs() < 1e-9);
+        assert_eq!(client_health.error_rate, 0.0);
+
+        client_health.record_failure();
+        assert!((client_health.error_rate - 0.2).abs() < 1e-9);
+        // Failed requests don't affect the latency.
+        assert!((client_health.latency_ms - 20.0).abs() < 1e-9);
+
+        client_health.record_block_number(10);
+        client_health.record_block_number(9);
+        assert_eq!(client_health.block_number, 10);
+        assert_eq!(client_health.lag(12), 2);
+        assert_eq!(client_health.lag(8), 0);
+    }
+
+    #[test]
+    fn health_score() {
+        let perfect = health(0.0, 0.0, 10);
+        assert_eq!(perfect.score(10), 1.0);
+        // Each of the factors reduces the score.
+        assert!((health(REFERENCE_LATENCY_MS, 0.0, 10).score(10) - 0.5).abs() < 1e-9);
+        assert!((health(0.0, 0.5, 10).score(10) - 0.5).abs() < 1e-9);
+        assert!((perfect.score(10 + REFERENCE_LAG_BLOCKS as u64) - 0.5).abs() < 1e-9);
+        assert_eq!(health(0.0, 1.0, 10).score(10), 0.0);
+    }
+
+    #[test]
+    fn clients_ranking() {
+        let clients = vec![
+            // Lagging client.
+            health(50.0, 0.0, 5),
+            // Slow client.
+            health(1000.0, 0.0, 10),
+            // Healthy client.
+            health(50.0, 0.0, 10),
+            // Failing client.
+            health(50.0, 0.9, 10),
+        ];
+        assert_eq!(rank_clients(&clients, 0), vec![2, 1, 0, 3]);
+
+        // Preferred client goes first among the ones with equal scores.
+        let clients = vec![ClientHealth::default(); 3];
+        assert_eq!(rank_clients(&clients, 0), vec![0, 1, 2]);
+        assert_eq!(rank_clients(&clients, 2), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn quorum() {
+        assert_eq!(quorum_block_number(vec![10, 12, 11], 1), Some(12));
+        assert_eq!(quorum_block_number(vec![10, 12, 11], 2), Some(11));
+        assert_eq!(quorum_block_number(vec![10, 12, 11], 3), Some(10));
+        assert_eq!(quorum_block_number(vec![10, 12], 3), None);
+
+        assert_eq!(quorum_value(&[1, 2, 1], 2, |value| *value), Some(1));
+        assert_eq!(quorum_value(&[1, 2, 3], 2, |value| *value), None);
+        assert_eq!(quorum_value(&[1, 2, 3], 1, |value| *value), Some(1));
+        // Only the keys are compared.
+        assert_eq!(
+            quorum_value(&[(1, 'a'), (2, 'b'), (1, 'c')], 2, |value| value.0),
+            Some((1, 'a'))
+        );
+    }
+}
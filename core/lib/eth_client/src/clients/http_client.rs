@@ -29,6 +29,9 @@ use crate::ethereum_gateway::{ExecutedTxStatus, FailureInfo, SignedCallResult};
 /// This is an emergency value, which will not be used normally.
 const FALLBACK_GAS_LIMIT: u64 = 3_000_000;
 
+/// Type identifier of the EIP-1559 transactions.
+const EIP1559_TX_TYPE: u64 = 2;
+
 struct ETHDirectClientInner<S: EthereumSigner> {
     eth_signer: S,
     sender_account: Address,
@@ -141,16 +144,41 @@ impl<S: EthereumSigner> ETHDirectClient<S> {
         Ok(block_number)
     }
 
+    /// Applies the configured gas price factor to the price reported by the node.
+    fn scale_gas_price(&self, price: U256) -> U256 {
+        let percent_gas_price_factor =
+            U256::from((self.inner.gas_price_factor * 100.0).round() as u64);
+        (price * percent_gas_price_factor) / U256::from(100)
+    }
+
     pub async fn get_gas_price(&self) -> Result<U256, anyhow::Error> {
         #[cfg(feature = "with-metrics")]
         let start = Instant::now();
-        let mut network_gas_price = self.inner.web3.eth().gas_price().await?;
-        let percent_gas_price_factor =
-            U256::from((self.inner.gas_price_factor * 100.0).round() as u64);
-        network_gas_price = (network_gas_price * percent_gas_price_factor) / U256::from(100);
+        let network_gas_price = self.inner.web3.eth().gas_price().await?;
         #[cfg(feature = "with-metrics")]
         metrics::histogram!("eth_client.direct.get_gas_price", start.elapsed());
-        Ok(network_gas_price)
+        Ok(self.scale_gas_price(network_gas_price))
+    }
+
+    pub async fn get_base_fee(&self) -> Result<U256, anyhow::Error> {
+        #[cfg(feature = "with-metrics")]
+        let start = Instant::now();
+        let fee_history = self
+            .inner
+            .web3
+            .eth()
+            .fee_history(1.into(), BlockNumber::Latest, None)
+            .await?;
+        // Besides the base fee of the requested block, fee history contains the base fee
+        // of the next block, which is exactly what the new transaction will pay.
+        let base_fee = fee_history
+            .base_fee_per_gas
+            .last()
+            .copied()
+            .ok_or_else(|| anyhow::format_err!("Fee history doesn't contain the base fee"))?;
+        #[cfg(feature = "with-metrics")]
+        metrics::histogram!("eth_client.direct.get_base_fee", start.elapsed());
+        Ok(self.scale_gas_price(base_fee))
     }
 
     pub async fn sign_prepared_tx(
@@ -172,7 +200,7 @@ impl<S: EthereumSigner> ETHDirectClient<S> {
         let start = Instant::now();
 
         // fetch current gas_price
-        let gas_price = match options.gas_price {
+        let gas_price = match options.max_fee_per_gas.or(options.gas_price) {
             Some(gas_price) => gas_price,
             None => self.get_gas_price().await?,
         };
@@ -197,19 +225,31 @@ impl<S: EthereumSigner> ETHDirectClient<S> {
             }
         };
 
+        // EIP-1559 transaction is sent if the max fee per gas is provided, legacy one otherwise.
+        let (transaction_type, max_priority_fee_per_gas) = match options.max_fee_per_gas {
+            Some(_) => {
+                let max_priority_fee_per_gas =
+                    options.max_priority_fee_per_gas.ok_or_else(|| {
+                        anyhow::format_err!("Max priority fee per gas must be set for EIP-1559 tx")
+                    })?;
+                (Some(EIP1559_TX_TYPE.into()), Some(max_priority_fee_per_gas))
+            }
+            None => (None, None),
+        };
+
         // form and sign tx
         let tx = RawTransaction {
             chain_id: self.inner.chain_id,
-            transaction_type: None,
+            transaction_type,
             access_list: None,
-            max_fee_per_gas: Default::default(),
+            max_fee_per_gas: options.max_fee_per_gas,
             nonce,
             to: Some(contract_addr),
             value: options.value.unwrap_or_default(),
             gas_price,
             gas,
             data,
-            max_priority_fee_per_gas: Default::default(),
+            max_priority_fee_per_gas,
         };
 
         let signed_tx = self.inner.eth_signer.sign_transaction(tx).await?;
@@ -228,6 +268,7 @@ impl<S: EthereumSigner> ETHDirectClient<S> {
         Ok(SignedCallResult {
             raw_tx: signed_tx,
             gas_price,
+            max_priority_fee_per_gas,
             nonce,
             hash,
         })
@@ -7,7 +7,7 @@ use tokio::sync::RwLock;
 use web3::contract::tokens::{Detokenize, Tokenize};
 use web3::contract::Options;
 use web3::transports::Http;
-use web3::types::{BlockId, Filter, Log, Transaction, U64};
+use web3::types::{Block, BlockId, Filter, Log, Transaction, U64};
 
 use zksync_types::{TransactionReceipt, H160, H256, U256};
 
@@ -20,8 +20,13 @@ use crate::{
 struct MockEthereumInner {
     block_number: u64,
     gas_price: U256,
+    base_fee: U256,
+    current_nonce: U256,
+    pending_nonce: U256,
     tx_statuses: Arc<RwLock<HashMap<H256, ExecutedTxStatus>>>,
     sent_txs: Arc<RwLock<HashSet<Vec<u8>>>>,
+    block_timestamp: Arc<RwLock<u64>>,
+    contract_calls: Arc<RwLock<HashMap<(Address, String), Vec<ethabi::Token>>>>,
 }
 
 /// Mock Ethereum client is capable of recording all the incoming requests for the further analysis.
@@ -35,8 +40,13 @@ impl Default for MockEthereumInner {
         Self {
             block_number: 1,
             gas_price: 100.into(),
+            base_fee: 40.into(),
+            current_nonce: 0.into(),
+            pending_nonce: 0.into(),
             tx_statuses: Default::default(),
             sent_txs: Default::default(),
+            block_timestamp: Default::default(),
+            contract_calls: Default::default(),
         }
     }
 }
@@ -65,6 +75,11 @@ impl MockEthereum {
         );
     }
 
+    /// Emulates the eviction of all the sent transactions from the mempool.
+    pub async fn drop_sent_txs(&self) {
+        self.inner.sent_txs.write().await.clear();
+    }
+
     /// Adds an response for the sent transaction for `ETHSender` to receive.
     pub async fn add_execution(&mut self, hash: &H256, status: &ExecutedTxStatus) {
         self.inner
@@ -115,6 +130,25 @@ impl MockEthereum {
         Ok(self.inner.block_number.into())
     }
 
+    /// Sets the timestamp of the latest block.
+    pub async fn set_block_timestamp(&self, timestamp: u64) {
+        *self.inner.block_timestamp.write().await = timestamp;
+    }
+
+    /// Sets the output of the contract function to be returned by `call_contract_function`.
+    pub async fn set_contract_call_result(
+        &self,
+        contract_address: Address,
+        func: &str,
+        result: Vec<ethabi::Token>,
+    ) {
+        self.inner
+            .contract_calls
+            .write()
+            .await
+            .insert((contract_address, func.to_string()), result);
+    }
+
     pub async fn get_gas_price(&self) -> anyhow::Result<U256> {
         Ok(self.inner.gas_price)
     }
@@ -124,6 +158,15 @@ impl MockEthereum {
         Ok(self.inner.gas_price)
     }
 
+    pub async fn get_base_fee(&self) -> anyhow::Result<U256> {
+        Ok(self.inner.base_fee)
+    }
+
+    pub async fn set_base_fee(&mut self, val: U256) -> anyhow::Result<U256> {
+        Arc::get_mut(&mut self.inner).unwrap().base_fee = val;
+        Ok(self.inner.base_fee)
+    }
+
     pub async fn send_raw_tx(&self, tx: Vec<u8>) -> Result<H256, anyhow::Error> {
         // Cut hash of transaction
         let mut hash: [u8; 32] = Default::default();
@@ -137,13 +180,23 @@ impl MockEthereum {
         raw_tx: Vec<u8>,
         options: Options,
     ) -> anyhow::Result<SignedCallResult> {
-        let gas_price = options.gas_price.unwrap_or(self.inner.gas_price);
+        // For EIP-1559 transactions max fee per gas is reported as the gas price.
+        let gas_price = options
+            .max_fee_per_gas
+            .or(options.gas_price)
+            .unwrap_or(self.inner.gas_price);
+        let max_priority_fee_per_gas = options
+            .max_fee_per_gas
+            .and(options.max_priority_fee_per_gas);
         let nonce = options.nonce.expect("Nonce must be set for every tx");
 
-        // Nonce and gas_price are appended to distinguish the same transactions
+        // Nonce and fees are appended to distinguish the same transactions
         // with different gas by their hash in tests.
         let mut data_for_hash = raw_tx.clone();
         data_for_hash.append(&mut ethabi::encode(gas_price.into_tokens().as_ref()));
+        if let Some(priority_fee) = max_priority_fee_per_gas {
+            data_for_hash.append(&mut ethabi::encode(priority_fee.into_tokens().as_ref()));
+        }
         data_for_hash.append(&mut ethabi::encode(nonce.into_tokens().as_ref()));
         let hash = Self::fake_sha256(data_for_hash.as_ref()); // Okay for test purposes.
                                                               // Concatenate raw_tx plus hash for test purposes
@@ -152,6 +205,7 @@ impl MockEthereum {
         Ok(SignedCallResult {
             raw_tx: new_raw_tx,
             gas_price,
+            max_priority_fee_per_gas,
             nonce,
             hash,
         })
@@ -165,11 +219,19 @@ impl MockEthereum {
     }
 
     pub async fn pending_nonce(&self) -> Result<U256, Error> {
-        unreachable!()
+        Ok(self.inner.pending_nonce)
     }
 
     pub async fn current_nonce(&self) -> Result<U256, Error> {
-        unreachable!()
+        Ok(self.inner.current_nonce)
+    }
+
+    /// Sets the nonces of the sender account: the confirmed one and the one
+    /// including the transactions from the mempool.
+    pub async fn set_nonces(&mut self, current_nonce: U256, pending_nonce: U256) {
+        let inner = Arc::get_mut(&mut self.inner).unwrap();
+        inner.current_nonce = current_nonce;
+        inner.pending_nonce = pending_nonce;
     }
 
     pub async fn sender_eth_balance(&self) -> Result<U256, Error> {
@@ -238,15 +300,24 @@ impl MockEthereum {
         todo!()
     }
 
+    /// Returns the latest block regardless of the requested one.
+    pub async fn block(&self, _id: BlockId) -> anyhow::Result<Option<Block<H256>>> {
+        Ok(Some(Block {
+            number: Some(self.inner.block_number.into()),
+            timestamp: (*self.inner.block_timestamp.read().await).into(),
+            ..Default::default()
+        }))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn call_contract_function<R, A, B, P>(
         &self,
-        _func: &str,
+        func: &str,
         _params: P,
         _from: A,
         _options: Options,
         _block: B,
-        _token_address: Address,
+        token_address: Address,
         _erc20_abi: ethabi::Contract,
     ) -> Result<R, anyhow::Error>
     where
@@ -255,7 +326,21 @@ impl MockEthereum {
         B: Into<Option<BlockId>>,
         P: Tokenize,
     {
-        todo!()
+        let tokens = self
+            .inner
+            .contract_calls
+            .read()
+            .await
+            .get(&(token_address, func.to_string()))
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "No result set for the call of {} on {:?}",
+                    func,
+                    token_address
+                )
+            })?;
+        R::from_tokens(tokens).map_err(|err| anyhow::format_err!("{}", err))
     }
 
     pub fn create_contract(
@@ -1,81 +1,313 @@
 use ethabi::Contract;
+use futures::{stream::FuturesUnordered, Future, StreamExt};
+use std::fmt::Display;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use web3::{
     contract::tokens::{Detokenize, Tokenize},
     contract::Options,
     transports::Http,
-    types::{Address, BlockId, Filter, Log, Transaction, U64},
+    types::{Address, Block, BlockId, Filter, Log, Transaction, U64},
 };
-use zksync_eth_signer::PrivateKeySigner;
+use zksync_eth_signer::{EthereumSigner, EthereumSignerBackend};
 use zksync_types::{TransactionReceipt, H160, H256, U256};
 
+use crate::clients::health::{self, ClientHealth, ClientHealthReport};
 use crate::ethereum_gateway::{ExecutedTxStatus, FailureInfo, SignedCallResult};
 use crate::ETHDirectClient;
 
-#[derive(Debug, Default)]
-struct MultiplexerEthereumClientInner {
-    clients: Vec<(String, ETHDirectClient<PrivateKeySigner>)>,
+/// Interval between the probes of all the clients. Requests are sent to the healthiest
+/// clients only, so without probes the demoted clients would never get a chance to recover.
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+/// Probes which take longer than this are considered failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct MultiplexerEthereumClientInner<S: EthereumSigner> {
+    clients: Vec<(String, ETHDirectClient<S>)>,
+    health: Mutex<Vec<ClientHealth>>,
     preferred: AtomicUsize,
+    /// Amount of the healthiest clients queried in parallel for the read requests.
+    hedged_requests: usize,
+    /// Amount of the clients which must agree on the result of the critical read requests.
+    quorum: usize,
+    probe_interval: Duration,
+    last_probe: Mutex<Instant>,
+}
+
+/// Ethereum client which distributes the requests across several Ethereum nodes.
+///
+/// Clients are ordered by their health score, which is calculated from the latency,
+/// error rate and block height lag of the client (see `ClientHealth`). Requests are sent
+/// to the healthiest clients first, failing over to the next ones on errors. Read requests
+/// are hedged, i.e. sent to several clients at once, and the critical ones (block numbers
+/// and logs) require an agreement of the quorum of clients.
+#[derive(Debug, Clone)]
+pub struct MultiplexerEthereumClient<S: EthereumSigner = EthereumSignerBackend> {
+    inner: Arc<MultiplexerEthereumClientInner<S>>,
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct MultiplexerEthereumClient {
-    inner: Arc<MultiplexerEthereumClientInner>,
+impl<S: EthereumSigner> Default for MultiplexerEthereumClient<S> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(MultiplexerEthereumClientInner {
+                clients: Vec::new(),
+                health: Mutex::new(Vec::new()),
+                preferred: AtomicUsize::new(0),
+                hedged_requests: 1,
+                quorum: 1,
+                probe_interval: PROBE_INTERVAL,
+                last_probe: Mutex::new(Instant::now()),
+            }),
+        }
+    }
 }
 
+/// Sends the request to the clients in order of their health: the first `$hedged` clients
+/// are queried in parallel, and the rest are tried one by one until the request succeeds.
 macro_rules! multiple_call {
-    ($self:expr, $func:ident($($attr:expr),*)) => {
-        for (name, client) in $self.clients() {
-            match client.$func($($attr.clone()),*).await {
-                Ok(res) => return Ok(res),
-                Err(err) => vlog::error!("Error in interface: {}, {} ", name, err),
+    ($self:expr, $hedged:expr, $func:ident($($attr:expr),*)) => {
+        $self.schedule_probe();
+        let clients = $self.ranked_clients();
+        let hedged = std::cmp::max($hedged, 1);
+        let mut requests: FuturesUnordered<_> = clients
+            .iter()
+            .take(hedged)
+            .map(|&(idx, client)| timed(idx, client.$func($($attr.clone()),*)))
+            .collect();
+        while let Some((idx, latency, result)) = requests.next().await {
+            if let Some(res) = $self.record_result(idx, latency, result) {
+                return Ok(res);
+            }
+        }
+        for &(idx, client) in clients.iter().skip(hedged) {
+            let (idx, latency, result) = timed(idx, client.$func($($attr.clone()),*)).await;
+            if let Some(res) = $self.record_result(idx, latency, result) {
+                return Ok(res);
             }
         }
         anyhow::bail!("All interfaces was wrong please try again")
     };
 }
 
-impl MultiplexerEthereumClient {
+/// Sends the request to all the clients in parallel and collects the successful responses
+/// along with the indices of the clients until `$agreement` returns the agreed value.
+/// The rest of the requests are not awaited once the agreement is reached.
+/// Returns the agreed value (if any) and the collected responses.
+macro_rules! quorum_call {
+    ($self:expr, $func:ident($($attr:expr),*), $agreement:expr) => {{
+        $self.schedule_probe();
+        let mut requests: FuturesUnordered<_> = $self
+            .ranked_clients()
+            .into_iter()
+            .map(|(idx, client)| timed(idx, client.$func($($attr.clone()),*)))
+            .collect();
+        let mut responses = Vec::new();
+        let mut agreed = None;
+        while let Some((idx, latency, result)) = requests.next().await {
+            if let Some(res) = $self.record_result(idx, latency, result) {
+                responses.push((idx, res));
+                agreed = $agreement(responses.as_slice());
+                if agreed.is_some() {
+                    break;
+                }
+            }
+        }
+        (agreed, responses)
+    }};
+}
+
+/// Fields identifying the log: block hash, transaction hash, log index, address, topics and data.
+type LogKey = (
+    Option<H256>,
+    Option<H256>,
+    Option<U256>,
+    Address,
+    Vec<H256>,
+    Vec<u8>,
+);
+
+/// Returns the fields identifying the log. The rest of the fields (e.g. `log_type` or
+/// `removed`) are not returned by every node, so they are not compared.
+fn log_key(log: &Log) -> LogKey {
+    (
+        log.block_hash,
+        log.transaction_hash,
+        log.log_index,
+        log.address,
+        log.topics.clone(),
+        log.data.0.clone(),
+    )
+}
+
+async fn timed<F: Future>(idx: usize, request: F) -> (usize, Duration, F::Output) {
+    let start = Instant::now();
+    let result = request.await;
+    (idx, start.elapsed(), result)
+}
+
+// Probes are run in the background, so the clients must be `'static`.
+impl<S: EthereumSigner + 'static> MultiplexerEthereumClient<S> {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn add_client(
-        &mut self,
-        name: String,
-        client: ETHDirectClient<PrivateKeySigner>,
-    ) -> &mut Self {
-        Arc::get_mut(&mut self.inner)
+    pub fn add_client(&mut self, name: String, client: ETHDirectClient<S>) -> &mut Self {
+        let inner = Arc::get_mut(&mut self.inner).unwrap();
+        inner.clients.push((name, client));
+        inner
+            .health
+            .get_mut()
             .unwrap()
-            .clients
-            .push((name, client));
+            .push(ClientHealth::default());
+        self
+    }
+
+    /// Sets the amount of the healthiest clients queried in parallel for the read requests.
+    pub fn with_hedged_requests(&mut self, hedged_requests: usize) -> &mut Self {
+        Arc::get_mut(&mut self.inner).unwrap().hedged_requests = hedged_requests.max(1);
         self
     }
 
+    /// Sets the amount of the clients which must agree on the block numbers and logs.
+    /// The quorum is limited by the amount of clients.
+    pub fn with_quorum(&mut self, quorum: usize) -> &mut Self {
+        Arc::get_mut(&mut self.inner).unwrap().quorum = quorum.max(1);
+        self
+    }
+
+    /// Sets the interval between the probes of all the clients.
+    pub fn with_probe_interval(&mut self, probe_interval: Duration) -> &mut Self {
+        Arc::get_mut(&mut self.inner).unwrap().probe_interval = probe_interval;
+        self
+    }
+
+    /// Makes the client preferred among the clients with the same health score.
     pub fn prioritize_client(&self, name: &str) -> bool {
-        if let Some(idx) = self.inner.clients.iter().position(|(key, _)| key == name) {
+        if let Some(idx) = self.client_index(name) {
             self.inner.preferred.swap(idx, Ordering::Acquire) != idx
         } else {
             false
         }
     }
 
-    pub fn clients(&self) -> impl Iterator<Item = (&str, &ETHDirectClient<PrivateKeySigner>)> {
-        let preferred = self.inner.preferred.load(Ordering::Relaxed);
+    /// Records the latest block number reported by the client.
+    pub fn report_block_number(&self, name: &str, block_number: u64) {
+        if let Some(idx) = self.client_index(name) {
+            self.inner.health.lock().unwrap()[idx].record_block_number(block_number);
+        }
+    }
+
+    /// Returns the health of each client.
+    pub fn health_report(&self) -> Vec<ClientHealthReport> {
+        let health = self.inner.health.lock().unwrap();
+        let latest_block_number = health::latest_block_number(&health);
         self.inner
             .clients
-            .get(preferred)
+            .iter()
+            .zip(health.iter())
+            .map(|((name, _), client_health)| ClientHealthReport {
+                name: name.clone(),
+                score: client_health.score(latest_block_number),
+                latency_ms: client_health.latency_ms,
+                error_rate: client_health.error_rate,
+                block_lag: client_health.lag(latest_block_number),
+            })
+            .collect()
+    }
+
+    /// Returns the clients ordered by their health, the healthiest first.
+    pub fn clients(&self) -> impl Iterator<Item = (&str, &ETHDirectClient<S>)> {
+        self.ranked_clients()
             .into_iter()
-            .chain(self.inner.clients.get(..preferred).unwrap_or(&[]).iter())
-            .chain(
-                self.inner
-                    .clients
-                    .get(1 + preferred..)
-                    .unwrap_or(&[])
-                    .iter(),
-            )
-            .map(|(name, client)| (name.as_str(), client))
+            .map(move |(idx, client)| (self.inner.clients[idx].0.as_str(), client))
+    }
+
+    /// Requests the block number from every client and records the results in their health.
+    /// Unlike the regular requests, probes reach the demoted clients as well, so their
+    /// health score can recover.
+    pub async fn probe_clients(&self) {
+        let results: Vec<_> = self
+            .inner
+            .clients
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, client))| {
+                timed(
+                    idx,
+                    tokio::time::timeout(PROBE_TIMEOUT, client.block_number()),
+                )
+            })
+            .collect::<FuturesUnordered<_>>()
+            .collect()
+            .await;
+        for (idx, latency, result) in results {
+            let result = result
+                .map_err(|_| anyhow::format_err!("Probe timed out"))
+                .and_then(|result| result);
+            if let Some(block_number) = self.record_result(idx, latency, result) {
+                self.inner.health.lock().unwrap()[idx].record_block_number(block_number.as_u64());
+            }
+        }
+    }
+
+    /// Starts the probe of all the clients in the background once per the probe interval.
+    fn schedule_probe(&self) {
+        {
+            let mut last_probe = self.inner.last_probe.lock().unwrap();
+            if last_probe.elapsed() < self.inner.probe_interval {
+                return;
+            }
+            *last_probe = Instant::now();
+        }
+        let client = self.clone();
+        tokio::spawn(async move { client.probe_clients().await });
+    }
+
+    fn client_index(&self, name: &str) -> Option<usize> {
+        self.inner.clients.iter().position(|(key, _)| key == name)
+    }
+
+    fn ranked_clients(&self) -> Vec<(usize, &ETHDirectClient<S>)> {
+        let preferred = self.inner.preferred.load(Ordering::Relaxed);
+        let ranking = health::rank_clients(&self.inner.health.lock().unwrap(), preferred);
+        ranking
+            .into_iter()
+            .map(|idx| (idx, &self.inner.clients[idx].1))
+            .collect()
+    }
+
+    fn record_result<T, E: Display>(
+        &self,
+        idx: usize,
+        latency: Duration,
+        result: Result<T, E>,
+    ) -> Option<T> {
+        let mut health = self.inner.health.lock().unwrap();
+        match result {
+            Ok(res) => {
+                health[idx].record_success(latency);
+                Some(res)
+            }
+            Err(err) => {
+                health[idx].record_failure();
+                vlog::error!(
+                    "Error in interface: {}, {} ",
+                    self.inner.clients[idx].0,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    fn hedged_requests(&self) -> usize {
+        self.inner.hedged_requests
+    }
+
+    fn quorum(&self) -> usize {
+        self.inner.quorum.min(self.inner.clients.len())
     }
 
     pub fn create_contract(
@@ -91,23 +323,53 @@ impl MultiplexerEthereumClient {
     }
 
     pub async fn pending_nonce(&self) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, pending_nonce());
+        multiple_call!(self, self.hedged_requests(), pending_nonce());
     }
 
     pub async fn current_nonce(&self) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, current_nonce());
+        multiple_call!(self, self.hedged_requests(), current_nonce());
     }
 
+    /// Returns the block number reached by the quorum of clients.
+    /// The block number of the first `quorum` clients to respond is used.
     pub async fn block_number(&self) -> Result<U64, anyhow::Error> {
-        multiple_call!(self, block_number());
+        let quorum = self.quorum();
+        if quorum <= 1 {
+            multiple_call!(self, self.hedged_requests(), block_number());
+        }
+
+        let (block_number, responses) =
+            quorum_call!(self, block_number(), |responses: &[(usize, U64)]| {
+                health::quorum_block_number(
+                    responses.iter().map(|(_, num)| num.as_u64()).collect(),
+                    quorum,
+                )
+            });
+        {
+            let mut health = self.inner.health.lock().unwrap();
+            for (idx, block_number) in &responses {
+                health[*idx].record_block_number(block_number.as_u64());
+            }
+        }
+        block_number.map(U64::from).ok_or_else(|| {
+            anyhow::format_err!("Less than {} interfaces returned the block number", quorum)
+        })
+    }
+
+    pub async fn block(&self, id: BlockId) -> Result<Option<Block<H256>>, anyhow::Error> {
+        multiple_call!(self, self.hedged_requests(), block(id));
     }
 
     pub async fn get_gas_price(&self) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, get_gas_price());
+        multiple_call!(self, self.hedged_requests(), get_gas_price());
+    }
+
+    pub async fn get_base_fee(&self) -> Result<U256, anyhow::Error> {
+        multiple_call!(self, self.hedged_requests(), get_base_fee());
     }
 
     pub async fn sender_eth_balance(&self) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, sender_eth_balance());
+        multiple_call!(self, self.hedged_requests(), sender_eth_balance());
     }
 
     pub async fn sign_prepared_tx(
@@ -115,7 +377,7 @@ impl MultiplexerEthereumClient {
         data: Vec<u8>,
         options: Options,
     ) -> Result<SignedCallResult, anyhow::Error> {
-        multiple_call!(self, sign_prepared_tx(data, options));
+        multiple_call!(self, 1, sign_prepared_tx(data, options));
     }
 
     pub async fn sign_prepared_tx_for_addr(
@@ -126,30 +388,31 @@ impl MultiplexerEthereumClient {
     ) -> Result<SignedCallResult, anyhow::Error> {
         multiple_call!(
             self,
+            1,
             sign_prepared_tx_for_addr(data, contract_addr, options)
         );
     }
 
     pub async fn send_raw_tx(&self, tx: Vec<u8>) -> Result<H256, anyhow::Error> {
-        multiple_call!(self, send_raw_tx(tx));
+        multiple_call!(self, 1, send_raw_tx(tx));
     }
 
     pub async fn tx_receipt(
         &self,
         tx_hash: H256,
     ) -> Result<Option<TransactionReceipt>, anyhow::Error> {
-        multiple_call!(self, tx_receipt(tx_hash));
+        multiple_call!(self, self.hedged_requests(), tx_receipt(tx_hash));
     }
 
     pub async fn failure_reason(
         &self,
         tx_hash: H256,
     ) -> Result<Option<FailureInfo>, anyhow::Error> {
-        multiple_call!(self, failure_reason(tx_hash));
+        multiple_call!(self, self.hedged_requests(), failure_reason(tx_hash));
     }
 
     pub async fn eth_balance(&self, address: Address) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, eth_balance(address));
+        multiple_call!(self, self.hedged_requests(), eth_balance(address));
     }
 
     pub async fn allowance(
@@ -157,7 +420,11 @@ impl MultiplexerEthereumClient {
         token_address: Address,
         erc20_abi: Contract,
     ) -> Result<U256, anyhow::Error> {
-        multiple_call!(self, allowance(token_address, erc20_abi));
+        multiple_call!(
+            self,
+            self.hedged_requests(),
+            allowance(token_address, erc20_abi)
+        );
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -179,6 +446,7 @@ impl MultiplexerEthereumClient {
     {
         multiple_call!(
             self,
+            self.hedged_requests(),
             call_contract_function(func, params, from, options, block, token_address, erc20_abi)
         );
     }
@@ -199,6 +467,7 @@ impl MultiplexerEthereumClient {
     {
         multiple_call!(
             self,
+            self.hedged_requests(),
             call_main_contract_function(func, params, from, options, block)
         );
     }
@@ -208,11 +477,35 @@ impl MultiplexerEthereumClient {
         hash: H256,
         current_block: Option<u64>,
     ) -> Result<Option<ExecutedTxStatus>, anyhow::Error> {
-        multiple_call!(self, get_tx_status(hash, current_block));
+        multiple_call!(
+            self,
+            self.hedged_requests(),
+            get_tx_status(hash, current_block)
+        );
     }
 
+    /// Returns the logs matching the filter, as agreed by the quorum of clients.
     pub async fn logs(&self, filter: Filter) -> anyhow::Result<Vec<Log>> {
-        multiple_call!(self, logs(filter));
+        let quorum = self.quorum();
+        if quorum <= 1 {
+            multiple_call!(self, self.hedged_requests(), logs(filter));
+        }
+
+        let (logs, responses) =
+            quorum_call!(self, logs(filter), |responses: &[(usize, Vec<Log>)]| {
+                health::quorum_value(
+                    responses.iter().map(|(_, logs)| logs),
+                    quorum,
+                    |logs: &Vec<Log>| logs.iter().map(log_key).collect::<Vec<_>>(),
+                )
+            });
+        logs.ok_or_else(|| {
+            anyhow::format_err!(
+                "Less than {} of {} interfaces agreed on the logs",
+                quorum,
+                responses.len()
+            )
+        })
     }
 
     pub fn encode_tx_data<P: Tokenize + Clone>(&self, func: &str, params: P) -> Vec<u8> {
@@ -224,6 +517,44 @@ impl MultiplexerEthereumClient {
     }
 
     pub async fn get_tx(&self, hash: H256) -> Result<Option<Transaction>, anyhow::Error> {
-        multiple_call!(self, get_tx(hash));
+        multiple_call!(self, self.hedged_requests(), get_tx(hash));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_are_compared_by_key() {
+        let log = Log {
+            address: Address::repeat_byte(1),
+            topics: vec![H256::repeat_byte(2)],
+            data: vec![3, 4].into(),
+            block_hash: Some(H256::repeat_byte(5)),
+            block_number: Some(6.into()),
+            transaction_hash: Some(H256::repeat_byte(7)),
+            transaction_index: Some(0.into()),
+            log_index: Some(1.into()),
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        };
+        let same_log = Log {
+            transaction_log_index: Some(1.into()),
+            log_type: Some("mined".into()),
+            removed: Some(false),
+            ..log.clone()
+        };
+        let other_log = Log {
+            data: vec![3].into(),
+            ..log.clone()
+        };
+
+        let responses = vec![vec![log.clone()], vec![other_log], vec![same_log]];
+        let agreed = health::quorum_value(&responses, 2, |logs: &Vec<Log>| {
+            logs.iter().map(log_key).collect::<Vec<_>>()
+        });
+        assert_eq!(agreed, Some(vec![log]));
     }
 }
@@ -1,14 +1,15 @@
 use web3::contract::tokens::{Detokenize, Tokenize};
 use web3::contract::{Contract, Options};
 use web3::transports::Http;
-use web3::types::{Address, BlockId, Filter, Log, Transaction, U64};
+use web3::types::{Address, Block, BlockId, Filter, Log, Transaction, U64};
 
 use std::fmt::Debug;
-use zksync_config::{ETHClientConfig, ETHSenderConfig};
+use zksync_config::{configs::eth_client::OperatorSignerType, ETHClientConfig, ETHSenderConfig};
 use zksync_contracts::zksync_contract;
-use zksync_eth_signer::PrivateKeySigner;
+use zksync_eth_signer::{EthereumSigner, EthereumSignerBackend, JsonRpcSigner, PrivateKeySigner};
 use zksync_types::{TransactionReceipt, H160, H256, U256};
 
+use crate::clients::health::ClientHealthReport;
 use crate::clients::mock::MockEthereum;
 use crate::clients::multiplexer::MultiplexerEthereumClient;
 use crate::ETHDirectClient;
@@ -16,7 +17,10 @@ use crate::ETHDirectClient;
 #[derive(Debug, Clone, PartialEq)]
 pub struct SignedCallResult {
     pub raw_tx: Vec<u8>,
+    /// Gas price of the legacy transaction or max fee per gas of the EIP-1559 one.
     pub gas_price: U256,
+    /// Max priority fee per gas, set only for EIP-1559 transactions.
+    pub max_priority_fee_per_gas: Option<U256>,
     pub nonce: U256,
     pub hash: H256,
 }
@@ -41,10 +45,14 @@ pub struct FailureInfo {
     pub gas_limit: U256,
 }
 
+/// Gateway to the Ethereum network.
+///
+/// Transactions are signed by the `EthereumSigner`. By default, the signer backend is chosen
+/// at runtime according to the `ETHClientConfig`.
 #[derive(Debug, Clone)]
-pub enum EthereumGateway {
-    Direct(ETHDirectClient<PrivateKeySigner>),
-    Multiplexed(MultiplexerEthereumClient),
+pub enum EthereumGateway<S: EthereumSigner = EthereumSignerBackend> {
+    Direct(ETHDirectClient<S>),
+    Multiplexed(MultiplexerEthereumClient<S>),
     Mock(MockEthereum),
 }
 
@@ -53,6 +61,58 @@ impl EthereumGateway {
         eth_client_config: &ETHClientConfig,
         eth_sender_config: &ETHSenderConfig,
         main_contract: Address,
+    ) -> Self {
+        Self::with_operator(
+            eth_client_config,
+            eth_sender_config.sender.operator_commit_eth_addr,
+            eth_sender_config.sender.operator_private_key,
+            main_contract,
+        )
+    }
+
+    /// Creates the gateway which sends transactions on behalf of the given operator account.
+    /// Depending on the `ETHClientConfig`, transactions are signed either with the provided
+    /// private key or by the remote signer (the private key is not required then).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the private key is not provided for the local signer.
+    pub fn with_operator(
+        eth_client_config: &ETHClientConfig,
+        operator_eth_addr: Address,
+        operator_private_key: Option<H256>,
+        main_contract: Address,
+    ) -> Self {
+        let eth_signer: EthereumSignerBackend = match eth_client_config.operator_signer {
+            OperatorSignerType::PrivateKey => PrivateKeySigner::new(
+                operator_private_key
+                    .expect("Operator private key is required for the local signer"),
+            )
+            .into(),
+            OperatorSignerType::JsonRpc => JsonRpcSigner::with_address(
+                eth_client_config.operator_signer_url(),
+                operator_eth_addr,
+                None,
+            )
+            .into(),
+        };
+        Self::with_signer(
+            eth_client_config,
+            operator_eth_addr,
+            eth_signer,
+            main_contract,
+        )
+    }
+}
+
+impl<S: EthereumSigner + 'static> EthereumGateway<S> {
+    /// Creates the gateway which sends transactions on behalf of the given operator account,
+    /// signing them with the provided signer.
+    pub fn with_signer(
+        eth_client_config: &ETHClientConfig,
+        operator_eth_addr: Address,
+        eth_signer: S,
+        main_contract: Address,
     ) -> Self {
         if eth_client_config.web3_url.len() == 1 {
             let transport = web3::transports::Http::new(&eth_client_config.web3_url()).unwrap();
@@ -60,14 +120,17 @@ impl EthereumGateway {
             EthereumGateway::Direct(ETHDirectClient::new(
                 transport,
                 zksync_contract(),
-                eth_sender_config.sender.operator_commit_eth_addr,
-                PrivateKeySigner::new(eth_sender_config.sender.operator_private_key),
+                operator_eth_addr,
+                eth_signer,
                 main_contract,
                 eth_client_config.chain_id,
                 eth_client_config.gas_price_factor,
             ))
         } else {
             let mut client = MultiplexerEthereumClient::new();
+            client
+                .with_hedged_requests(eth_client_config.multiplexer_hedged_requests)
+                .with_quorum(eth_client_config.multiplexer_quorum);
 
             let contract = zksync_contract();
             for web3_url in eth_client_config.web3_url.iter().cloned() {
@@ -77,8 +140,8 @@ impl EthereumGateway {
                     ETHDirectClient::new(
                         transport,
                         contract.clone(),
-                        eth_sender_config.sender.operator_commit_eth_addr,
-                        PrivateKeySigner::new(eth_sender_config.sender.operator_private_key),
+                        operator_eth_addr,
+                        eth_signer.clone(),
                         main_contract,
                         eth_client_config.chain_id,
                         eth_client_config.gas_price_factor,
@@ -100,7 +163,7 @@ macro_rules! delegate_call {
     }
 }
 
-impl EthereumGateway {
+impl<S: EthereumSigner + 'static> EthereumGateway<S> {
     /// Returns the next *expected* nonce with respect to the transactions
     /// in the mempool.
     ///
@@ -123,9 +186,19 @@ impl EthereumGateway {
         delegate_call!(self.block_number())
     }
 
+    pub async fn block(&self, id: BlockId) -> Result<Option<Block<H256>>, anyhow::Error> {
+        delegate_call!(self.block(id))
+    }
+
     pub async fn get_gas_price(&self) -> Result<U256, anyhow::Error> {
         delegate_call!(self.get_gas_price())
     }
+
+    /// Returns the base fee per gas of the next block, obtained via `eth_feeHistory`.
+    pub async fn get_base_fee(&self) -> Result<U256, anyhow::Error> {
+        delegate_call!(self.get_base_fee())
+    }
+
     /// Returns the account balance.
     pub async fn sender_eth_balance(&self) -> Result<U256, anyhow::Error> {
         delegate_call!(self.sender_eth_balance())
@@ -269,6 +342,15 @@ impl EthereumGateway {
         matches!(self, EthereumGateway::Multiplexed(_))
     }
 
+    /// Returns the health of the Ethereum nodes used by the multiplexed gateway.
+    /// Other gateways don't track the health of the nodes, so the report is empty for them.
+    pub fn health_report(&self) -> Vec<ClientHealthReport> {
+        match self {
+            EthereumGateway::Multiplexed(c) => c.health_report(),
+            EthereumGateway::Direct(_) | EthereumGateway::Mock(_) => Vec::new(),
+        }
+    }
+
     pub fn get_mut_mock(&mut self) -> Option<&mut MockEthereum> {
         match self {
             EthereumGateway::Mock(ref mut m) => Some(m),
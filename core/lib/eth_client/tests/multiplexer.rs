@@ -0,0 +1,241 @@
+//! Checks the failover of the `MultiplexerEthereumClient` with the failing and slow nodes.
+//!
+//! Ethereum nodes are replaced with the local stand-in servers, which report the configured
+//! block number and can be made failing or slow during the test.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{post, web, App, HttpResponse, HttpServer, Responder};
+use futures::future::{AbortHandle, Abortable};
+use serde_json::{json, Value};
+use web3::transports::Http;
+use web3::types::{FilterBuilder, U64};
+
+use zksync_eth_client::{ETHDirectClient, MultiplexerEthereumClient};
+use zksync_eth_signer::PrivateKeySigner;
+use zksync_types::{Address, H256};
+
+/// Any request to the client is expected to complete within this time,
+/// unless it waits for the slow node.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const SLOW_NODE_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+struct NodeState {
+    block_number: AtomicU64,
+    failing: AtomicBool,
+    slow: AtomicBool,
+}
+
+#[post("/")]
+async fn node_rpc(request: web::Json<Value>, state: web::Data<Arc<NodeState>>) -> impl Responder {
+    if state.slow.load(Ordering::SeqCst) {
+        tokio::time::sleep(SLOW_NODE_DELAY).await;
+    }
+
+    let response = if state.failing.load(Ordering::SeqCst) {
+        json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32000, "message": "Node is unavailable" },
+        })
+    } else {
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "eth_blockNumber" => json!(U64::from(state.block_number.load(Ordering::SeqCst))),
+            "eth_getLogs" => json!([]),
+            method => panic!("Unexpected request {}", method),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    };
+    HttpResponse::Ok().json(response)
+}
+
+/// Stand-in Ethereum node running on a free local port.
+struct TestNode {
+    url: String,
+    state: Arc<NodeState>,
+    abort_handle: AbortHandle,
+}
+
+impl TestNode {
+    fn run(block_number: u64) -> Self {
+        let state = Arc::new(NodeState::default());
+        state.block_number.store(block_number, Ordering::SeqCst);
+
+        let server_state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(server_state.clone()))
+                .service(node_rpc)
+        })
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .expect("Can't bind the stand-in node");
+        let url = format!("http://{}/", server.addrs()[0]);
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        actix_rt::spawn(Abortable::new(server.run(), abort_registration));
+        Self {
+            url,
+            state,
+            abort_handle,
+        }
+    }
+
+    fn set_failing(&self, failing: bool) {
+        self.state.failing.store(failing, Ordering::SeqCst);
+    }
+
+    fn set_slow(&self, slow: bool) {
+        self.state.slow.store(slow, Ordering::SeqCst);
+    }
+}
+
+impl Drop for TestNode {
+    fn drop(&mut self) {
+        self.abort_handle.abort();
+    }
+}
+
+fn multiplexer(
+    nodes: &[TestNode],
+    quorum: usize,
+    probe_interval: Duration,
+) -> MultiplexerEthereumClient<PrivateKeySigner> {
+    let mut client = MultiplexerEthereumClient::new();
+    client
+        .with_quorum(quorum)
+        .with_probe_interval(probe_interval);
+    for node in nodes {
+        client.add_client(
+            node.url.clone(),
+            ETHDirectClient::new(
+                Http::new(&node.url).unwrap(),
+                ethabi::Contract::load(&b"[]"[..]).unwrap(),
+                Address::repeat_byte(0x01),
+                PrivateKeySigner::new(H256::repeat_byte(0x01)),
+                Address::repeat_byte(0x02),
+                9,
+                1.0,
+            ),
+        );
+    }
+    client
+}
+
+fn error_rate(client: &MultiplexerEthereumClient<PrivateKeySigner>, node: &TestNode) -> f64 {
+    client
+        .health_report()
+        .into_iter()
+        .find(|report| report.name == node.url)
+        .unwrap()
+        .error_rate
+}
+
+/// Checks that the requests fail over to the healthy node, and that the failing node
+/// is demoted, so it's not queried first anymore.
+#[actix_rt::test]
+async fn failing_client_is_demoted() {
+    let nodes = [TestNode::run(10), TestNode::run(10)];
+    nodes[0].set_failing(true);
+    let client = multiplexer(&nodes, 1, Duration::from_secs(3600));
+
+    let block_number = tokio::time::timeout(REQUEST_TIMEOUT, client.block_number())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block_number, U64::from(10));
+
+    assert!(error_rate(&client, &nodes[0]) > 0.0);
+    assert_eq!(error_rate(&client, &nodes[1]), 0.0);
+    let (preferred, _) = client.clients().next().unwrap();
+    assert_eq!(preferred, nodes[1].url);
+
+    // The request fails only if none of the nodes responds.
+    nodes[1].set_failing(true);
+    client.block_number().await.unwrap_err();
+}
+
+/// Checks that the quorum requests return as soon as the quorum agrees,
+/// without waiting for the slow node.
+#[actix_rt::test]
+async fn quorum_requests_do_not_wait_for_slow_client() {
+    let nodes = [TestNode::run(10), TestNode::run(12), TestNode::run(11)];
+    nodes[1].set_slow(true);
+    let client = multiplexer(&nodes, 2, Duration::from_secs(3600));
+
+    let block_number = tokio::time::timeout(REQUEST_TIMEOUT, client.block_number())
+        .await
+        .expect("Quorum request waited for the slow node")
+        .unwrap();
+    assert_eq!(block_number, U64::from(10));
+
+    let logs = tokio::time::timeout(
+        REQUEST_TIMEOUT,
+        client.logs(FilterBuilder::default().build()),
+    )
+    .await
+    .expect("Quorum request waited for the slow node")
+    .unwrap();
+    assert!(logs.is_empty());
+
+    // The quorum can't be reached if only one node responds.
+    nodes[0].set_failing(true);
+    nodes[1].set_slow(false);
+    nodes[1].set_failing(true);
+    tokio::time::timeout(REQUEST_TIMEOUT, client.block_number())
+        .await
+        .unwrap()
+        .unwrap_err();
+}
+
+/// Checks that the demoted node is probed periodically, so its health score recovers
+/// once the node is back, even though the requests are served by the other node.
+#[actix_rt::test]
+async fn demoted_client_recovers() {
+    let nodes = [TestNode::run(10), TestNode::run(10)];
+    nodes[0].set_failing(true);
+    let client = multiplexer(&nodes, 1, Duration::from_secs(3600));
+    for _ in 0..5 {
+        client.block_number().await.unwrap();
+    }
+    let demoted_error_rate = error_rate(&client, &nodes[0]);
+    assert!(demoted_error_rate > 0.0);
+    let (preferred, _) = client.clients().next().unwrap();
+    assert_eq!(preferred, nodes[1].url);
+
+    // Requests are served by the healthy node, so the node which is back isn't queried.
+    nodes[0].set_failing(false);
+    client.block_number().await.unwrap();
+    assert_eq!(error_rate(&client, &nodes[0]), demoted_error_rate);
+
+    client.probe_clients().await;
+    assert!(error_rate(&client, &nodes[0]) < demoted_error_rate);
+    for _ in 0..20 {
+        client.probe_clients().await;
+    }
+    assert!(error_rate(&client, &nodes[0]) < 0.1);
+}
+
+/// Checks that the probes are started by the requests once per the probe interval.
+#[actix_rt::test]
+async fn clients_are_probed_periodically() {
+    let nodes = [TestNode::run(10), TestNode::run(10)];
+    let client = multiplexer(&nodes, 1, Duration::from_millis(100));
+
+    nodes[1].set_failing(true);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    client.block_number().await.unwrap();
+
+    // The second node isn't queried for the request, but the probe started by it
+    // reaches the node in the background.
+    tokio::time::timeout(REQUEST_TIMEOUT, async {
+        while error_rate(&client, &nodes[1]) == 0.0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Client was not probed");
+}
@@ -0,0 +1,157 @@
+//! Checks that the operator transactions can be signed by the remote JSON-RPC signer.
+//!
+//! The remote signer is replaced with a local stand-in server holding the operator key,
+//! while the gateway is created without the private key, the same way as on the server host.
+
+use std::convert::TryFrom;
+
+use actix_web::{post, web, App, HttpResponse, HttpServer, Responder};
+use futures::future::{AbortHandle, Abortable};
+use serde_json::{json, Value};
+use web3::contract::Options;
+
+use zksync_config::{configs::eth_client::OperatorSignerType, ETHClientConfig};
+use zksync_eth_client::EthereumGateway;
+use zksync_eth_signer::{EthereumSigner, PrivateKeySigner, RawTransaction};
+use zksync_types::{tx::PackedEthSignature, Address, H256};
+
+const OPERATOR_PRIVATE_KEY: &str =
+    "27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be";
+
+#[derive(Clone)]
+struct SignerState {
+    private_key: H256,
+}
+
+/// Stand-in for the remote signer: signs the transactions of the only account it holds the key for.
+#[post("/")]
+async fn signer_rpc(request: web::Json<Value>, state: web::Data<SignerState>) -> impl Responder {
+    let address = PackedEthSignature::address_from_private_key(&state.private_key).unwrap();
+    let method = request["method"].as_str().unwrap_or_default();
+    let response = match method {
+        "eth_accounts" => json!({ "jsonrpc": "2.0", "id": request["id"], "result": [address] }),
+        "eth_signTransaction" if request["params"][0]["from"] != json!(address) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32000, "message": "Unknown account" },
+        }),
+        "eth_signTransaction" => {
+            let signer = PrivateKeySigner::new(state.private_key);
+            let raw_tx = signer
+                .sign_transaction(RawTransaction::try_from(&request["params"][0]).unwrap())
+                .await
+                .unwrap();
+            json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "raw": format!("0x{}", hex::encode(raw_tx)) },
+            })
+        }
+        _ => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32601, "message": format!("Method {} is not supported", method) },
+        }),
+    };
+    HttpResponse::Ok().json(response)
+}
+
+/// Runs the stand-in signer on a free local port and returns its URL.
+fn run_signer(private_key: H256) -> (String, AbortHandle) {
+    let state = SignerState { private_key };
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(signer_rpc)
+    })
+    .workers(1)
+    .bind("127.0.0.1:0")
+    .expect("Can't bind the stand-in signer");
+    let url = format!("http://{}/", server.addrs()[0]);
+
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    actix_rt::spawn(Abortable::new(server.run(), abort_registration));
+    (url, abort_handle)
+}
+
+fn eth_client_config(
+    operator_signer: OperatorSignerType,
+    signer_url: Option<String>,
+) -> ETHClientConfig {
+    ETHClientConfig {
+        chain_id: 9,
+        gas_price_factor: 1.0,
+        // The node is never queried, since all the transaction parameters are provided.
+        web3_url: vec!["http://127.0.0.1:8545".into()],
+        web3_ws_url: None,
+        operator_signer,
+        operator_signer_url: signer_url,
+        multiplexer_hedged_requests: 1,
+        multiplexer_quorum: 1,
+    }
+}
+
+fn legacy_options(nonce: u64) -> Options {
+    Options {
+        nonce: Some(nonce.into()),
+        gas: Some(3_000_000.into()),
+        gas_price: Some(10_000_000_000u64.into()),
+        ..Default::default()
+    }
+}
+
+fn eip1559_options(nonce: u64) -> Options {
+    Options {
+        nonce: Some(nonce.into()),
+        gas: Some(3_000_000.into()),
+        max_fee_per_gas: Some(10_000_000_000u64.into()),
+        max_priority_fee_per_gas: Some(1_000_000_000u64.into()),
+        ..Default::default()
+    }
+}
+
+/// Checks that the gateway created without the operator private key signs the transactions
+/// with the remote signer, and that they are the same as the ones signed locally.
+#[actix_rt::test]
+async fn operator_transactions_signed_remotely() {
+    let private_key: H256 = OPERATOR_PRIVATE_KEY.parse().unwrap();
+    let operator = PackedEthSignature::address_from_private_key(&private_key).unwrap();
+    let main_contract = Address::repeat_byte(0x11);
+    let (signer_url, abort_handle) = run_signer(private_key);
+    let remote_config = eth_client_config(OperatorSignerType::JsonRpc, Some(signer_url));
+
+    let remote_gateway =
+        EthereumGateway::with_operator(&remote_config, operator, None, main_contract);
+    let local_gateway = EthereumGateway::with_operator(
+        &eth_client_config(OperatorSignerType::PrivateKey, None),
+        operator,
+        Some(private_key),
+        main_contract,
+    );
+
+    for options in [legacy_options(1), eip1559_options(2)] {
+        let remote_tx = remote_gateway
+            .sign_prepared_tx(vec![1, 2, 3], options.clone())
+            .await
+            .unwrap();
+        let local_tx = local_gateway
+            .sign_prepared_tx(vec![1, 2, 3], options)
+            .await
+            .unwrap();
+        assert_eq!(remote_tx, local_tx);
+    }
+
+    // The signer doesn't hold the key of the other account, so its transactions are not signed.
+    let unknown_operator_gateway = EthereumGateway::with_operator(
+        &remote_config,
+        Address::repeat_byte(0x22),
+        None,
+        main_contract,
+    );
+    unknown_operator_gateway
+        .sign_prepared_tx(vec![1, 2, 3], legacy_options(1))
+        .await
+        .unwrap_err();
+
+    abort_handle.abort();
+}
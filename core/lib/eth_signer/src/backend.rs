@@ -0,0 +1,48 @@
+use crate::raw_ethereum_tx::RawTransaction;
+use crate::{EthereumSigner, JsonRpcSigner, PrivateKeySigner, SignerError};
+
+use zksync_types::tx::TxEthSignature;
+use zksync_types::Address;
+
+/// Signer which backend is chosen at runtime (e.g. from the configuration).
+#[derive(Debug, Clone)]
+pub enum EthereumSignerBackend {
+    PrivateKey(PrivateKeySigner),
+    JsonRpc(JsonRpcSigner),
+}
+
+impl From<PrivateKeySigner> for EthereumSignerBackend {
+    fn from(signer: PrivateKeySigner) -> Self {
+        Self::PrivateKey(signer)
+    }
+}
+
+impl From<JsonRpcSigner> for EthereumSignerBackend {
+    fn from(signer: JsonRpcSigner) -> Self {
+        Self::JsonRpc(signer)
+    }
+}
+
+#[async_trait::async_trait]
+impl EthereumSigner for EthereumSignerBackend {
+    async fn sign_message(&self, message: &[u8]) -> Result<TxEthSignature, SignerError> {
+        match self {
+            Self::PrivateKey(signer) => signer.sign_message(message).await,
+            Self::JsonRpc(signer) => signer.sign_message(message).await,
+        }
+    }
+
+    async fn sign_transaction(&self, raw_tx: RawTransaction) -> Result<Vec<u8>, SignerError> {
+        match self {
+            Self::PrivateKey(signer) => signer.sign_transaction(raw_tx).await,
+            Self::JsonRpc(signer) => signer.sign_transaction(raw_tx).await,
+        }
+    }
+
+    async fn get_address(&self) -> Result<Address, SignerError> {
+        match self {
+            Self::PrivateKey(signer) => signer.get_address().await,
+            Self::JsonRpc(signer) => signer.get_address().await,
+        }
+    }
+}
@@ -97,10 +97,12 @@ impl EthereumSigner for JsonRpcSigner {
         let json: Value = serde_json::from_value(ret)
             .map_err(|err| SignerError::SigningFailed(err.to_string()))?;
 
+        // Some signers (e.g. Geth or Clef) return an object with the raw transaction
+        // and its decoded representation, while others (e.g. Web3Signer) return just the raw transaction.
         let raw_tx: Option<&str> = json
-            .get("raw")
-            .and_then(|value| value.as_str())
-            .map(|value| &value["0x".len()..]);
+            .as_str()
+            .or_else(|| json.get("raw").and_then(|value| value.as_str()))
+            .map(|value| value.trim_start_matches("0x"));
 
         if let Some(raw_tx) = raw_tx {
             hex::decode(raw_tx).map_err(|err| SignerError::DecodeRawTxFailed(err.to_string()))
@@ -115,6 +117,21 @@ impl EthereumSigner for JsonRpcSigner {
 }
 
 impl JsonRpcSigner {
+    /// Creates the signer for the known address without making any requests to the server.
+    /// If `signer_type` is not set, only transactions can be signed.
+    pub fn with_address(
+        rpc_addr: impl Into<String>,
+        address: Address,
+        signer_type: Option<SignerType>,
+    ) -> Self {
+        Self {
+            rpc_addr: rpc_addr.into(),
+            client: reqwest::Client::new(),
+            address: Some(address),
+            signer_type,
+        }
+    }
+
     pub async fn new(
         rpc_addr: impl Into<String>,
         address_or_index: Option<AddressOrIndex>,
@@ -290,6 +307,7 @@ impl JsonRpcSigner {
 mod messages {
     use crate::RawTransaction;
     use hex::encode;
+    use web3::types::U64;
     use zksync_types::Address;
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -341,27 +359,33 @@ mod messages {
         pub fn sign_transaction(from: Address, tx_data: RawTransaction) -> Self {
             let mut params = Vec::new();
 
+            let mut tx = serde_json::json!({
+                "from": serde_json::to_value(from).expect("serialization fail"),
+                "gas": serde_json::to_value(tx_data.gas).expect("serialization fail"),
+                "value": serde_json::to_value(tx_data.value).expect("serialization fail"),
+                "data": serde_json::to_value(format!("0x{}", encode(tx_data.data))).expect("serialization fail"),
+                "nonce": serde_json::to_value(tx_data.nonce).expect("serialization fail"),
+            });
             // Parameter `To` is optional, so we add it only if it is not None
-            let tx = if let Some(to) = tx_data.to {
-                serde_json::json!({
-                    "from": serde_json::to_value(from).expect("serialization fail"),
-                    "to": serde_json::to_value(to).expect("serialization fail"),
-                    "gas": serde_json::to_value(tx_data.gas).expect("serialization fail"),
-                    "gasPrice": serde_json::to_value(tx_data.gas_price).expect("serialization fail"),
-                    "value": serde_json::to_value(tx_data.value).expect("serialization fail"),
-                    "data": serde_json::to_value(format!("0x{}", encode(tx_data.data))).expect("serialization fail"),
-                    "nonce": serde_json::to_value(tx_data.nonce).expect("serialization fail"),
-                })
+            if let Some(to) = tx_data.to {
+                tx["to"] = serde_json::to_value(to).expect("serialization fail");
+            }
+            // Chain ID is only known for the transactions created by the `ETHDirectClient`.
+            if tx_data.chain_id != 0 {
+                tx["chainId"] =
+                    serde_json::to_value(U64::from(tx_data.chain_id)).expect("serialization fail");
+            }
+            // EIP-1559 transactions don't have the gas price, the fees are set instead.
+            if let Some(transaction_type) = tx_data.transaction_type {
+                tx["type"] = serde_json::to_value(transaction_type).expect("serialization fail");
+                tx["maxFeePerGas"] =
+                    serde_json::to_value(tx_data.max_fee_per_gas).expect("serialization fail");
+                tx["maxPriorityFeePerGas"] = serde_json::to_value(tx_data.max_priority_fee_per_gas)
+                    .expect("serialization fail");
             } else {
-                serde_json::json!({
-                    "from": serde_json::to_value(from).expect("serialization fail"),
-                    "gas": serde_json::to_value(tx_data.gas).expect("serialization fail"),
-                    "gasPrice": serde_json::to_value(tx_data.gas_price).expect("serialization fail"),
-                    "value": serde_json::to_value(tx_data.value).expect("serialization fail"),
-                    "data": serde_json::to_value(format!("0x{}", encode(tx_data.data))).expect("serialization fail"),
-                    "nonce": serde_json::to_value(tx_data.nonce).expect("serialization fail"),
-                })
-            };
+                tx["gasPrice"] =
+                    serde_json::to_value(tx_data.gas_price).expect("serialization fail");
+            }
             params.push(tx);
             Self::create("eth_signTransaction", params)
         }
@@ -375,6 +399,7 @@ mod tests {
     use jsonrpc_core::{Failure, Id, Output, Success, Version};
     use parity_crypto::publickey::{Generator, KeyPair, Random};
     use serde_json::json;
+    use std::convert::TryFrom;
 
     use zksync_types::{
         tx::{PackedEthSignature, TxEthSignature},
@@ -382,7 +407,7 @@ mod tests {
     };
 
     use super::{is_signature_from_address, messages::JsonRpcRequest};
-    use crate::{EthereumSigner, JsonRpcSigner, RawTransaction};
+    use crate::{EthereumSigner, JsonRpcSigner, PrivateKeySigner, RawTransaction};
 
     #[post("/")]
     async fn index(req: web::Json<JsonRpcRequest>, state: web::Data<State>) -> impl Responder {
@@ -405,10 +430,14 @@ mod tests {
                 create_success(json!(signature))
             }
             "eth_signTransaction" => {
-                let tx_value = json!(req.params[0].clone()).to_string();
-                let tx = tx_value.as_bytes();
-                let hex_data = hex::encode(tx);
-                create_success(json!({ "raw": hex_data }))
+                // Signs the transaction the same way a real signer does, so the result
+                // can be compared with the one of the local signer.
+                let signer = PrivateKeySigner::new(**state.key_pairs[0].secret());
+                let raw_tx = signer
+                    .sign_transaction(RawTransaction::try_from(&req.params[0]).unwrap())
+                    .await
+                    .unwrap();
+                create_success(json!({ "raw": format!("0x{}", hex::encode(raw_tx)) }))
             }
             _ => create_fail(req.method.clone()),
         };
@@ -501,4 +530,58 @@ mod tests {
         assert_ne!(transaction_signature.len(), 0);
         abort_handle.abort();
     }
+
+    /// Legacy and EIP-1559 transactions with the same parameters.
+    fn test_transactions() -> Vec<RawTransaction> {
+        let legacy_tx = RawTransaction {
+            chain_id: 9,
+            nonce: 1.into(),
+            to: Some(Address::repeat_byte(0x11)),
+            value: 0.into(),
+            gas_price: 10_000_000_000u64.into(),
+            gas: 3_000_000.into(),
+            data: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let eip1559_tx = RawTransaction {
+            transaction_type: Some(2.into()),
+            max_fee_per_gas: Some(legacy_tx.gas_price),
+            max_priority_fee_per_gas: Some(1_000_000_000u64.into()),
+            ..legacy_tx.clone()
+        };
+        vec![legacy_tx, eip1559_tx]
+    }
+
+    /// Checks that the transaction sent to the signer is parsed back unchanged.
+    #[test]
+    fn sign_transaction_request_roundtrip() {
+        for tx in test_transactions() {
+            let request = JsonRpcRequest::sign_transaction(Address::repeat_byte(0x22), tx.clone());
+            assert_eq!(RawTransaction::try_from(&request.params[0]).unwrap(), tx);
+        }
+    }
+
+    /// Checks that transactions signed by the remote signer are the same as the ones
+    /// signed locally with the same key, both for legacy and EIP-1559 transactions.
+    #[actix_rt::test]
+    async fn remote_transaction_signing() {
+        let key_pair = Random.generate();
+        let local_signer = PrivateKeySigner::new(**key_pair.secret());
+        let (address, abort_handle) = run_server(State {
+            key_pairs: vec![key_pair.clone()],
+        });
+        // The address is known in advance, so the signer makes no requests until signing.
+        let remote_signer = JsonRpcSigner::with_address(address, key_pair.address(), None);
+        assert_eq!(
+            remote_signer.get_address().await.unwrap(),
+            key_pair.address()
+        );
+
+        for tx in test_transactions() {
+            let remote_signature = remote_signer.sign_transaction(tx.clone()).await.unwrap();
+            let local_signature = local_signer.sign_transaction(tx).await.unwrap();
+            assert_eq!(remote_signature, local_signature);
+        }
+        abort_handle.abort();
+    }
 }
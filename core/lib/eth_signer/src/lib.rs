@@ -6,10 +6,12 @@ use error::SignerError;
 use zksync_types::tx::TxEthSignature;
 use zksync_types::Address;
 
+pub use backend::EthereumSignerBackend;
 pub use json_rpc_signer::JsonRpcSigner;
 pub use pk_signer::PrivateKeySigner;
 pub use raw_ethereum_tx::RawTransaction;
 
+pub mod backend;
 pub mod error;
 pub mod json_rpc_signer;
 pub mod pk_signer;
@@ -8,10 +8,13 @@
 //! We can refactor this code and adapt it for our needs better, but I prefer to reuse as much code as we can.
 //! In the case where it will be possible to use only the web3 library without copy-paste, the changes will be small and simple
 //! Link to @Deniallugo's PR to web3: https://github.com/tomusdrw/rust-web3/pull/630
+use std::convert::TryFrom;
+
 use rlp::RlpStream;
+use serde::Deserialize;
 use web3::{
     signing::{self, Signature},
-    types::{AccessList, Address, SignedTransaction, U256, U64},
+    types::{AccessList, Address, Bytes, SignedTransaction, U256, U64},
 };
 
 const LEGACY_TX_ID: u64 = 0;
@@ -44,6 +47,51 @@ pub struct RawTransaction {
     pub max_priority_fee_per_gas: Option<U256>,
 }
 
+/// Transaction in the format of the `eth_signTransaction` request parameters.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionRequest {
+    nonce: U256,
+    to: Option<Address>,
+    gas: U256,
+    gas_price: Option<U256>,
+    value: U256,
+    data: Bytes,
+    chain_id: Option<U64>,
+    #[serde(rename = "type")]
+    transaction_type: Option<U64>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+}
+
+/// Parses the transaction from the `eth_signTransaction` request parameters,
+/// which is the inverse of how the `JsonRpcSigner` sends it.
+impl TryFrom<&serde_json::Value> for RawTransaction {
+    type Error = serde_json::Error;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        let tx = TransactionRequest::deserialize(value)?;
+        // EIP-1559 transactions have no gas price, the max fee is used instead.
+        let gas_price = tx
+            .gas_price
+            .or(tx.max_fee_per_gas)
+            .ok_or_else(|| <Self::Error as serde::de::Error>::missing_field("gasPrice"))?;
+        Ok(Self {
+            nonce: tx.nonce,
+            to: tx.to,
+            gas: tx.gas,
+            gas_price,
+            value: tx.value,
+            data: tx.data.0,
+            chain_id: tx.chain_id.unwrap_or_default().as_u64(),
+            transaction_type: tx.transaction_type,
+            access_list: None,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+        })
+    }
+}
+
 /// A transaction used for RLP encoding, hashing and signing.
 #[derive(Debug)]
 pub struct Transaction {
@@ -115,8 +115,8 @@ impl MultiplexedGatewayWatcher {
         }
     }
 
-    /// Checks multiplexed client gateways and prioritizes one with longest chain,
-    /// most frequent hash and lowest latency.
+    /// Checks multiplexed client gateways, reports their block heights and prioritizes one
+    /// with longest chain, most frequent hash and lowest latency.
     async fn check_client_gateways(&self) {
         // Fetch latest block for each client.
         // Each request will resolve to (client key, client latest block) pair.
@@ -160,6 +160,13 @@ impl MultiplexedGatewayWatcher {
             .collect()
             .await;
 
+        // Block heights are taken into account in the health scores of the clients.
+        for (key, block, _) in &client_latest_blocks {
+            if let Some(number) = block.number {
+                self.client.report_block_number(key, number.as_u64());
+            }
+        }
+
         // Latest hash distribution across all clients.
         let hash_counts =
             client_latest_blocks
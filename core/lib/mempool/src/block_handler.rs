@@ -1,6 +1,7 @@
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt;
 
+use zksync_config::configs::chain::TransactionsOrdering;
 use zksync_types::{
     mempool::SignedTxVariant,
     tx::{error::TxAddError, TxHash},
@@ -47,6 +48,7 @@ pub(crate) struct MempoolBlocksHandler {
     pub mempool_state: MempoolState,
     pub requests: mpsc::Receiver<MempoolBlocksRequest>,
     pub max_block_size_chunks: usize,
+    pub transactions_ordering: TransactionsOrdering,
 }
 
 impl MempoolBlocksHandler {
@@ -61,7 +63,7 @@ impl MempoolBlocksHandler {
         // will be empty unless the server is restarted after reverting blocks.
         let mut tx_queue = self
             .mempool_state
-            .get_transaction_queue(executed_txs)
+            .get_transaction_queue(executed_txs, self.transactions_ordering)
             .await?;
 
         let (txs, priority_ops, chunks_left) = tx_queue
@@ -72,6 +74,7 @@ impl MempoolBlocksHandler {
                 &self.mempool_state,
             )
             .await?;
+        let txs = self.mempool_state.mark_txs_proposed(txs).await?;
 
         if !priority_ops.is_empty() || !txs.is_empty() {
             vlog::debug!(
@@ -113,6 +116,9 @@ impl MempoolBlocksHandler {
         // Remove any possible duplicates of already executed transactions
         // from the database.
         self.mempool_state.collect_garbage().await;
+        // The state keeper is restored from the database, so the transactions proposed
+        // before the restart and not executed in the restored pending block are pending again.
+        self.mempool_state.reset_proposed_txs().await;
         while let Some(request) = self.requests.next().await {
             match request {
                 MempoolBlocksRequest::GetBlock(block) => {
@@ -16,6 +16,7 @@ use futures::channel::mpsc;
 use tokio::task::JoinHandle;
 
 // Workspace uses
+use zksync_config::configs::chain::{Mempool as MempoolConfig, TransactionsOrdering};
 use zksync_storage::ConnectionPool;
 
 // Local uses
@@ -38,6 +39,7 @@ pub fn run_mempool_tx_handler(
     db_pool: ConnectionPool,
     tx_requests: mpsc::Receiver<MempoolTransactionRequest>,
     block_chunk_sizes: Vec<usize>,
+    config: MempoolConfig,
 ) -> JoinHandle<()> {
     let mempool_state = MempoolState::new(db_pool.clone());
     let max_block_size_chunks = *block_chunk_sizes
@@ -49,6 +51,7 @@ pub fn run_mempool_tx_handler(
         mempool_state,
         requests: tx_requests,
         max_block_size_chunks,
+        config,
     };
     tokio::spawn(handler.run())
 }
@@ -58,6 +61,7 @@ pub fn run_mempool_block_handler(
     db_pool: ConnectionPool,
     block_requests: mpsc::Receiver<MempoolBlocksRequest>,
     block_chunk_sizes: Vec<usize>,
+    transactions_ordering: TransactionsOrdering,
 ) -> JoinHandle<()> {
     let mempool_state = MempoolState::new(db_pool);
     let max_block_size_chunks = *block_chunk_sizes
@@ -69,6 +73,7 @@ pub fn run_mempool_block_handler(
         mempool_state,
         requests: block_requests,
         max_block_size_chunks,
+        transactions_ordering,
     };
 
     tokio::spawn(blocks_handler.run())
@@ -1,9 +1,25 @@
-use crate::MempoolState;
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, VecDeque};
+use async_trait::async_trait;
+use num::{rational::Ratio, BigUint};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use zksync_config::configs::chain::TransactionsOrdering;
 use zksync_types::mempool::SignedTxVariant;
 use zksync_types::tx::error::TxAddError;
-use zksync_types::PriorityOp;
+use zksync_types::{Address, Nonce, PriorityOp};
+
+/// Source of the execution costs used to select transactions for a block.
+#[async_trait]
+pub(crate) trait TxCostEstimator {
+    /// Returns the amount of chunks required to execute the transaction or batch.
+    async fn required_chunks(&self, element: &SignedTxVariant) -> Result<usize, TxAddError>;
+
+    /// Returns the USD fee paid for every chunk of the given transactions,
+    /// in the same order as the provided transactions.
+    async fn fee_per_chunk_usd(
+        &self,
+        txs: &[SignedTxVariant],
+    ) -> Result<Vec<Ratio<BigUint>>, TxAddError>;
+}
 
 #[derive(Debug, Clone)]
 struct MempoolPendingTransaction {
@@ -35,6 +51,109 @@ impl PartialOrd for MempoolPendingTransaction {
     }
 }
 
+/// The first not yet selected transaction of some account, used for ordering by fee.
+/// Heads are compared by the fee per chunk first and by the arrival order afterwards,
+/// so the earliest transaction wins among the equally paying ones.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct AccountQueueHead {
+    fee_per_chunk: Ratio<BigUint>,
+    arrival: Reverse<usize>,
+    account_idx: usize,
+}
+
+/// Returns the nonce used to order transactions.
+/// According to our convention in batch `fee transaction` would be the last one, so we would use nonce from it as a key for sort
+fn tx_variant_nonce(tx: &SignedTxVariant) -> Nonce {
+    match tx {
+        SignedTxVariant::Tx(tx) => tx.tx.nonce(),
+        SignedTxVariant::Batch(batch) => batch
+            .txs
+            .last()
+            .expect("batch must contain at least one transaction")
+            .tx
+            .nonce(),
+    }
+}
+
+/// Returns the account which nonce order should be preserved for the transaction.
+/// Same as for the nonce, the `fee transaction` of the batch is used.
+fn tx_variant_account(tx: &SignedTxVariant) -> Address {
+    match tx {
+        SignedTxVariant::Tx(tx) => tx.tx.account(),
+        SignedTxVariant::Batch(batch) => batch
+            .txs
+            .last()
+            .expect("batch must contain at least one transaction")
+            .tx
+            .account(),
+    }
+}
+
+type AccountQueue = VecDeque<(usize, SignedTxVariant, Ratio<BigUint>)>;
+
+/// Splits transactions into queues of every account sorted by the nonce.
+/// Every transaction is accompanied by its position in the original list.
+fn group_by_account(txs: Vec<(SignedTxVariant, Ratio<BigUint>)>) -> Vec<AccountQueue> {
+    let mut account_indices = HashMap::new();
+    let mut account_queues: Vec<Vec<(usize, SignedTxVariant, Ratio<BigUint>)>> = Vec::new();
+    for (arrival, (tx, fee_per_chunk)) in txs.into_iter().enumerate() {
+        let account_idx = *account_indices
+            .entry(tx_variant_account(&tx))
+            .or_insert_with(|| {
+                account_queues.push(Vec::new());
+                account_queues.len() - 1
+            });
+        account_queues[account_idx].push((arrival, tx, fee_per_chunk));
+    }
+
+    account_queues
+        .into_iter()
+        .map(|mut queue| {
+            queue.sort_by_key(|(_, tx, _)| tx_variant_nonce(tx));
+            VecDeque::from(queue)
+        })
+        .collect()
+}
+
+/// Orders transactions by the fee they pay per chunk, starting from the most expensive ones.
+/// Transactions of every account are still returned in the order of their nonces, so an account
+/// competes for the block space with the fee of its transaction with the lowest nonce.
+fn order_by_fee_per_chunk(
+    txs: Vec<(SignedTxVariant, Ratio<BigUint>)>,
+) -> VecDeque<SignedTxVariant> {
+    let mut account_queues = group_by_account(txs);
+
+    let mut heads: BinaryHeap<_> = account_queues
+        .iter()
+        .enumerate()
+        .filter_map(|(account_idx, queue)| {
+            queue
+                .front()
+                .map(|(arrival, _, fee_per_chunk)| AccountQueueHead {
+                    fee_per_chunk: fee_per_chunk.clone(),
+                    arrival: Reverse(*arrival),
+                    account_idx,
+                })
+        })
+        .collect();
+
+    let mut result = VecDeque::new();
+    while let Some(head) = heads.pop() {
+        let queue = &mut account_queues[head.account_idx];
+        let (_, tx, _) = queue.pop_front().expect("account queue head must exist");
+        result.push_back(tx);
+
+        if let Some((arrival, _, fee_per_chunk)) = queue.front() {
+            heads.push(AccountQueueHead {
+                fee_per_chunk: fee_per_chunk.clone(),
+                arrival: Reverse(*arrival),
+                account_idx: head.account_idx,
+            });
+        }
+    }
+    result
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct MempoolTransactionsQueue {
     /// Transactions ready for execution.
@@ -43,17 +162,21 @@ pub(crate) struct MempoolTransactionsQueue {
     pending_l2_transactions: BinaryHeap<MempoolPendingTransaction>,
 
     l1_transactions: VecDeque<PriorityOp>,
+    /// Order in which ready L2 transactions are selected.
+    ordering: TransactionsOrdering,
 }
 
 impl MempoolTransactionsQueue {
     pub(crate) fn new(
         l1_transactions: VecDeque<PriorityOp>,
         l2_transactions: VecDeque<SignedTxVariant>,
+        ordering: TransactionsOrdering,
     ) -> Self {
         let mut res = Self {
             ready_l2_transactions: Default::default(),
             pending_l2_transactions: Default::default(),
             l1_transactions,
+            ordering,
         };
         // Due to complexity of json structure in database for transactions it's easier and safer
         // to add even not ready txs to mempool and prepare them before when it's needed.
@@ -107,16 +230,7 @@ impl MempoolTransactionsQueue {
             }
 
             // Now transactions should be sorted by the nonce (transaction natural order)
-            // According to our convention in batch `fee transaction` would be the last one, so we would use nonce from it as a key for sort
-            ready_pending_l2_operations.sort_by_key(|tx| match tx {
-                SignedTxVariant::Tx(tx) => tx.tx.nonce(),
-                SignedTxVariant::Batch(batch) => batch
-                    .txs
-                    .last()
-                    .expect("batch must contain at least one transaction")
-                    .tx
-                    .nonce(),
-            });
+            ready_pending_l2_operations.sort_by_key(tx_variant_nonce);
 
             VecDeque::<SignedTxVariant>::from(ready_pending_l2_operations)
         };
@@ -125,13 +239,24 @@ impl MempoolTransactionsQueue {
             .append(&mut ready_pending_l2_operations);
     }
 
+    /// Reorders ready transactions, so the ones paying more for a chunk go first.
+    fn reorder_ready_l2_transactions_by_fee(&mut self, fees_per_chunk: Vec<Ratio<BigUint>>) {
+        let ready_l2_transactions = std::mem::take(&mut self.ready_l2_transactions);
+        self.ready_l2_transactions = order_by_fee_per_chunk(
+            ready_l2_transactions
+                .into_iter()
+                .zip(fees_per_chunk)
+                .collect(),
+        );
+    }
+
     /// Collect txs depending on desired chunks and execution time
     pub(crate) async fn select_transactions(
         &mut self,
         chunks: usize,
         current_unprocessed_priority_op: u64,
         block_timestamp: u64,
-        mempool_state: &MempoolState,
+        mempool_state: &impl TxCostEstimator,
     ) -> Result<(Vec<SignedTxVariant>, Vec<PriorityOp>, usize), TxAddError> {
         let (chunks_left, priority_ops) =
             self.select_l1_transactions(chunks, current_unprocessed_priority_op);
@@ -179,9 +304,15 @@ impl MempoolTransactionsQueue {
         &mut self,
         mut chunks_left: usize,
         block_timestamp: u64,
-        mempool_state: &MempoolState,
+        mempool_state: &impl TxCostEstimator,
     ) -> Result<(usize, Vec<SignedTxVariant>), TxAddError> {
         self.prepare_new_ready_l2_transactions(block_timestamp);
+        if self.ordering == TransactionsOrdering::FeePerChunk {
+            let fees_per_chunk = mempool_state
+                .fee_per_chunk_usd(self.ready_l2_transactions.make_contiguous())
+                .await?;
+            self.reorder_ready_l2_transactions_by_fee(fees_per_chunk);
+        }
 
         let mut txs_for_commit = Vec::new();
 
@@ -230,6 +361,87 @@ mod tests {
         })
     }
 
+    fn get_transfer_with_fee(from: Address, nonce: u32, fee: u32) -> SignedTxVariant {
+        let transfer = Transfer::new(
+            AccountId(4242),
+            from,
+            Address::random(),
+            TokenId(0),
+            500u32.into(),
+            fee.into(),
+            Nonce(nonce),
+            Default::default(),
+            None,
+        );
+
+        SignedTxVariant::Tx(SignedZkSyncTx {
+            tx: ZkSyncTx::Transfer(Box::new(transfer)),
+            eth_sign_data: None,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Fee per chunk of the transaction, assuming that the fee token costs exactly 1 USD per unit.
+    fn fee_per_chunk(tx: &SignedTxVariant) -> Ratio<BigUint> {
+        let txs = tx.get_transactions();
+        let fee: BigUint = txs.iter().map(|tx| tx.tx.get_fee_info().unwrap().3).sum();
+        let chunks: usize = txs.iter().map(|tx| tx.tx.min_chunks()).sum();
+        Ratio::new(fee, BigUint::from(chunks))
+    }
+
+    /// Cost estimator that doesn't need the database: every transaction requires
+    /// its minimal amount of chunks and every fee token costs 1 USD per unit.
+    struct MockCostEstimator;
+
+    #[async_trait]
+    impl TxCostEstimator for MockCostEstimator {
+        async fn required_chunks(&self, element: &SignedTxVariant) -> Result<usize, TxAddError> {
+            Ok(element
+                .get_transactions()
+                .iter()
+                .map(|tx| tx.tx.min_chunks())
+                .sum())
+        }
+
+        async fn fee_per_chunk_usd(
+            &self,
+            txs: &[SignedTxVariant],
+        ) -> Result<Vec<Ratio<BigUint>>, TxAddError> {
+            Ok(txs.iter().map(fee_per_chunk).collect())
+        }
+    }
+
+    /// Selects transactions for consecutive blocks with the given capacity until all of them
+    /// are executed. As in the block handler, the queue is rebuilt for every block from
+    /// the transactions that were not executed yet.
+    async fn select_blocks(
+        mut txs: Vec<SignedTxVariant>,
+        block_timestamp: u64,
+        block_chunks: usize,
+    ) -> Vec<Vec<SignedTxVariant>> {
+        let mut blocks = vec![];
+        while !txs.is_empty() {
+            let mut transactions_queue = MempoolTransactionsQueue::new(
+                VecDeque::new(),
+                txs.iter().cloned().collect(),
+                TransactionsOrdering::FeePerChunk,
+            );
+            let (block, _, _) = transactions_queue
+                .select_transactions(block_chunks, 0, block_timestamp, &MockCostEstimator)
+                .await
+                .unwrap();
+            assert!(!block.is_empty(), "No transactions fit into the block");
+
+            txs.retain(|tx| {
+                !block
+                    .iter()
+                    .any(|executed| executed.hashes() == tx.hashes())
+            });
+            blocks.push(block);
+        }
+        blocks
+    }
+
     fn get_withdraw() -> SignedTxVariant {
         let withdraw = Withdraw::new(
             AccountId(3),
@@ -256,6 +468,7 @@ mod tests {
             ready_l2_transactions: VecDeque::new(),
             pending_l2_transactions: BinaryHeap::new(),
             l1_transactions: Default::default(),
+            ordering: TransactionsOrdering::Nonce,
         };
 
         transactions_queue.add_l1_transactions(vec![
@@ -360,6 +573,7 @@ mod tests {
             ready_l2_transactions: VecDeque::new(),
             pending_l2_transactions: BinaryHeap::new(),
             l1_transactions: Default::default(),
+            ordering: TransactionsOrdering::Nonce,
         };
 
         let withdraw0 = get_withdraw();
@@ -416,4 +630,67 @@ mod tests {
             );
         }
     }
+
+    /// Checks that with the fee ordering transactions paying more per chunk
+    /// are included into earlier blocks.
+    #[tokio::test]
+    async fn test_fee_per_chunk_ordering() {
+        let cheap = get_transfer_with_fee(Address::random(), 1, 10);
+        let medium = get_transfer_with_fee(Address::random(), 2, 50);
+        let expensive = get_transfer_with_fee(Address::random(), 3, 100);
+
+        // Cheap transaction has the lowest nonce, so it would go first with the nonce ordering.
+        let txs = vec![cheap.clone(), medium.clone(), expensive.clone()];
+
+        // Every block fits two transfers.
+        let blocks = select_blocks(txs, 0, 4).await;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].len(), 2);
+        assert_eq!(blocks[0][0].hashes(), expensive.hashes());
+        assert_eq!(blocks[0][1].hashes(), medium.hashes());
+        assert_eq!(blocks[1].len(), 1);
+        assert_eq!(blocks[1][0].hashes(), cheap.hashes());
+    }
+
+    /// Checks that the fee ordering doesn't break the nonce order of transactions
+    /// sent from the same account.
+    #[tokio::test]
+    async fn test_fee_per_chunk_ordering_keeps_nonce_order() {
+        let first_account = Address::random();
+        let second_account = Address::random();
+
+        let first_account_cheap = get_transfer_with_fee(first_account, 5, 10);
+        let first_account_expensive = get_transfer_with_fee(first_account, 6, 1000);
+        let second_account_medium = get_transfer_with_fee(second_account, 1, 100);
+        let second_account_cheap = get_transfer_with_fee(second_account, 2, 1);
+
+        let txs = vec![
+            first_account_expensive.clone(),
+            second_account_cheap.clone(),
+            first_account_cheap.clone(),
+            second_account_medium.clone(),
+        ];
+
+        // Every block fits a single transfer.
+        let blocks = select_blocks(txs, 0, 2).await;
+        let order: Vec<_> = blocks
+            .into_iter()
+            .map(|block| {
+                assert_eq!(block.len(), 1);
+                block[0].hashes()
+            })
+            .collect();
+
+        // The expensive transaction of the first account cannot be executed before
+        // the cheap one with the lower nonce, so the second account goes first.
+        assert_eq!(
+            order,
+            vec![
+                second_account_medium.hashes(),
+                first_account_cheap.hashes(),
+                first_account_expensive.hashes(),
+                second_account_cheap.hashes(),
+            ]
+        );
+    }
 }
@@ -1,14 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 
+use async_trait::async_trait;
+use num::{rational::Ratio, BigUint, Zero};
+
+use zksync_config::configs::chain::TransactionsOrdering;
 use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_types::{
     mempool::{SignedTxVariant, SignedTxsBatch},
     tx::{error::TxAddError, TxHash},
-    Address, TransferOp, TransferToNewOp, ZkSyncTx,
+    Address, TokenId, TokenLike, TransferOp, TransferToNewOp, ZkSyncTx,
 };
 
-use crate::MempoolTransactionsQueue;
+use crate::mempool_transactions_queue::{MempoolTransactionsQueue, TxCostEstimator};
 
 #[derive(Debug, Clone)]
 pub(crate) struct MempoolState {
@@ -98,6 +102,74 @@ impl MempoolState {
         }
     }
 
+    /// Returns the USD price of the smallest unit of the token, e.g. of one wei for ETH.
+    /// Tokens without a known price are valued at zero.
+    async fn token_unit_price_usd(
+        storage: &mut StorageProcessor<'_>,
+        token_id: TokenId,
+        prices: &mut HashMap<TokenId, Ratio<BigUint>>,
+    ) -> Result<Ratio<BigUint>, TxAddError> {
+        if let Some(price) = prices.get(&token_id) {
+            return Ok(price.clone());
+        }
+
+        let token = storage
+            .tokens_schema()
+            .get_token(TokenLike::Id(token_id))
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+        let ticker_price = storage
+            .tokens_schema()
+            .get_historical_ticker_price(token_id)
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+
+        let price = match (token, ticker_price) {
+            (Some(token), Some(ticker_price)) => {
+                ticker_price.usd_price / BigUint::from(10u32).pow(u32::from(token.decimals))
+            }
+            _ => Ratio::from_integer(BigUint::zero()),
+        };
+        prices.insert(token_id, price.clone());
+        Ok(price)
+    }
+
+    /// Calculates the fee paid for every chunk of the given transactions, converted to USD
+    /// using the token prices stored by the fee ticker.
+    /// The result is in the same order as the provided transactions.
+    pub async fn fee_per_chunk_usd(
+        &self,
+        txs: &[SignedTxVariant],
+    ) -> Result<Vec<Ratio<BigUint>>, TxAddError> {
+        let start = Instant::now();
+        let mut storage = self
+            .db_pool
+            .access_storage()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+        let mut existing_accounts = HashSet::new();
+        let mut prices = HashMap::new();
+
+        let mut scores = Vec::with_capacity(txs.len());
+        for element in txs {
+            let mut chunks = 0;
+            let mut fee_usd = Ratio::from_integer(BigUint::zero());
+            for tx in element.get_transactions() {
+                chunks += self
+                    .chunks_for_tx_with_cache(&tx.tx, &mut storage, &mut existing_accounts)
+                    .await?;
+                if let Some((_, TokenLike::Id(token_id), _, fee)) = tx.tx.get_fee_info() {
+                    let price =
+                        Self::token_unit_price_usd(&mut storage, token_id, &mut prices).await?;
+                    fee_usd += price * fee;
+                }
+            }
+            scores.push(fee_usd / BigUint::from(chunks.max(1)));
+        }
+        metrics::histogram!("mempool_state.fee_per_chunk_usd", start.elapsed());
+        Ok(scores)
+    }
+
     pub async fn collect_garbage(&self) {
         let mut storage = self.db_pool.access_storage().await.expect("Db error");
         // Remove any possible duplicates of already executed transactions
@@ -110,6 +182,49 @@ impl MempoolState {
             .expect("Db error");
     }
 
+    /// Returns the transactions proposed for execution before the restart back to the queue,
+    /// since the state keeper has lost the ones it didn't persist.
+    pub async fn reset_proposed_txs(&self) {
+        let mut storage = self.db_pool.access_storage().await.expect("Db error");
+        storage
+            .chain()
+            .mempool_schema()
+            .reset_proposed_txs()
+            .await
+            .expect("Db error");
+    }
+
+    /// Marks the transactions as proposed for execution, so they can't be replaced or cancelled anymore.
+    /// Transactions removed from the mempool in the meantime are filtered out.
+    pub async fn mark_txs_proposed(
+        &self,
+        txs: Vec<SignedTxVariant>,
+    ) -> Result<Vec<SignedTxVariant>, TxAddError> {
+        if txs.is_empty() {
+            return Ok(txs);
+        }
+
+        let mut storage = self
+            .db_pool
+            .access_storage()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+        let tx_hashes: Vec<_> = txs.iter().flat_map(|tx| tx.hashes()).collect();
+        let marked_txs: HashSet<_> = storage
+            .chain()
+            .mempool_schema()
+            .mark_txs_proposed(&tx_hashes)
+            .await
+            .map_err(|_| TxAddError::DbError)?
+            .into_iter()
+            .collect();
+
+        Ok(txs
+            .into_iter()
+            .filter(|tx| tx.hashes().iter().all(|hash| marked_txs.contains(hash)))
+            .collect())
+    }
+
     pub fn new(db_pool: ConnectionPool) -> Self {
         Self { db_pool }
     }
@@ -117,6 +232,7 @@ impl MempoolState {
     pub async fn get_transaction_queue(
         &self,
         executed_txs: &[TxHash],
+        ordering: TransactionsOrdering,
     ) -> Result<MempoolTransactionsQueue, TxAddError> {
         let mut storage = self
             .db_pool
@@ -144,8 +260,22 @@ impl MempoolState {
             .await
             .map_err(|_| TxAddError::DbError)?;
 
-        let transactions_queue = MempoolTransactionsQueue::new(priority_ops, mempool_txs);
+        let transactions_queue = MempoolTransactionsQueue::new(priority_ops, mempool_txs, ordering);
 
         Ok(transactions_queue)
     }
 }
+
+#[async_trait]
+impl TxCostEstimator for MempoolState {
+    async fn required_chunks(&self, element: &SignedTxVariant) -> Result<usize, TxAddError> {
+        MempoolState::required_chunks(self, element).await
+    }
+
+    async fn fee_per_chunk_usd(
+        &self,
+        txs: &[SignedTxVariant],
+    ) -> Result<Vec<Ratio<BigUint>>, TxAddError> {
+        MempoolState::fee_per_chunk_usd(self, txs).await
+    }
+}
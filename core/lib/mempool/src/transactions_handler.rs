@@ -1,15 +1,23 @@
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt;
 
-use zksync_storage::ConnectionPool;
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use zksync_config::configs::chain::Mempool as MempoolConfig;
+use zksync_storage::{ConnectionPool, StorageProcessor};
 use zksync_types::{
-    mempool::SignedTxsBatch,
-    tx::{error::TxAddError, TxEthSignature},
-    PriorityOp, SignedZkSyncTx,
+    mempool::{SignedTxVariant, SignedTxsBatch},
+    tx::{error::TxAddError, TxEthSignature, TxHash},
+    Address, Nonce, PriorityOp, SerialId, SignedZkSyncTx,
 };
+use zksync_utils::ratio_to_big_decimal;
 
 use crate::state::MempoolState;
 
+/// Precision of the USD fee per chunk stored with the mempool transactions.
+const FEE_PER_CHUNK_PRECISION: usize = 18;
+
 #[derive(Debug)]
 pub enum MempoolTransactionRequest {
     /// Add new transaction to mempool, transaction should be previously checked
@@ -23,6 +31,9 @@ pub enum MempoolTransactionRequest {
         bool,
         oneshot::Sender<Result<(), TxAddError>>,
     ),
+    /// Remove unconfirmed priority ops which are no longer present in the Ethereum chain
+    /// (e.g. after the reorg). Confirmed priority ops are never removed.
+    RetractPriorityOps(Vec<SerialId>, oneshot::Sender<Result<(), TxAddError>>),
     /// Add a new batch of transactions to the mempool. All transactions in batch must
     /// be either executed successfully, or otherwise fail all together.
     /// Invariants for each individual transaction in the batch are the same as in
@@ -32,6 +43,14 @@ pub enum MempoolTransactionRequest {
         Vec<TxEthSignature>,
         oneshot::Sender<Result<(), TxAddError>>,
     ),
+    /// Remove a pending transaction (or a batch) sent from the given account.
+    /// The cancellation request should be previously authorized by the account owner.
+    /// oneshot is used to receive hashes of the removed transactions.
+    CancelTx(
+        Address,
+        TxHash,
+        oneshot::Sender<Result<Vec<TxHash>, TxAddError>>,
+    ),
 }
 
 pub(crate) struct MempoolTransactionsHandler {
@@ -39,9 +58,168 @@ pub(crate) struct MempoolTransactionsHandler {
     pub mempool_state: MempoolState,
     pub requests: mpsc::Receiver<MempoolTransactionRequest>,
     pub max_block_size_chunks: usize,
+    pub config: MempoolConfig,
 }
 
 impl MempoolTransactionsHandler {
+    /// Checks that the transaction nonce is neither outdated nor too far ahead of the account nonce.
+    fn check_nonce(&self, tx: &SignedZkSyncTx, account_nonce: Nonce) -> Result<(), TxAddError> {
+        if tx.nonce() < account_nonce {
+            return Err(TxAddError::NonceMismatch);
+        }
+        if *tx.nonce() - *account_nonce > self.config.max_nonce_gap {
+            return Err(TxAddError::NonceGapTooBig);
+        }
+        Ok(())
+    }
+
+    /// Checks that accounts won't exceed the limit of pending transactions
+    /// after adding `new_txs` transactions from each of them.
+    async fn check_accounts_pending_txs(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        new_txs: HashMap<Address, u32>,
+    ) -> Result<(), TxAddError> {
+        for (address, new_txs_count) in new_txs {
+            let pending_txs = storage
+                .chain()
+                .mempool_schema()
+                .get_account_pending_txs_count(address)
+                .await
+                .map_err(|_| TxAddError::DbError)?;
+
+            if pending_txs + new_txs_count > self.config.max_pending_txs_per_account {
+                return Err(TxAddError::TooManyPendingTxs);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the USD fee paid for every chunk of the transaction or the whole batch,
+    /// in the form stored in the mempool.
+    async fn fee_per_chunk(&self, txs: SignedTxVariant) -> Result<BigDecimal, TxAddError> {
+        let fee_per_chunk = self
+            .mempool_state
+            .fee_per_chunk_usd(&[txs])
+            .await?
+            .pop()
+            .expect("fee is calculated for every transaction");
+        Ok(ratio_to_big_decimal(
+            &fee_per_chunk,
+            FEE_PER_CHUNK_PRECISION,
+        ))
+    }
+
+    /// Makes room for `new_txs_count` transactions in the mempool if the total limit of pending
+    /// transactions is reached. Transactions paying the least for a chunk are evicted, and the new
+    /// transactions are rejected if not enough transactions paying less than `fee_per_chunk`
+    /// can be evicted.
+    async fn ensure_mempool_capacity(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        new_txs_count: u32,
+        fee_per_chunk: &BigDecimal,
+    ) -> Result<(), TxAddError> {
+        if new_txs_count > self.config.max_pending_txs {
+            return Err(TxAddError::MempoolFull);
+        }
+
+        let mut transaction = storage
+            .start_transaction()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+        let mempool_size = transaction
+            .chain()
+            .mempool_schema()
+            .get_mempool_size()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+        let excess = (mempool_size + new_txs_count).saturating_sub(self.config.max_pending_txs);
+        if excess == 0 {
+            return Ok(());
+        }
+
+        // Every evicted transaction makes the previous one of the account a candidate,
+        // so the candidates are selected until enough transactions are evicted.
+        // Nothing is evicted if the new transactions are rejected in the end.
+        let mut evicted_txs = Vec::new();
+        while (evicted_txs.len() as u32) < excess {
+            let candidates = transaction
+                .chain()
+                .mempool_schema()
+                .get_eviction_candidates(excess - evicted_txs.len() as u32, fee_per_chunk)
+                .await
+                .map_err(|_| TxAddError::DbError)?;
+            let evicted = transaction
+                .chain()
+                .mempool_schema()
+                .evict_txs(&candidates)
+                .await
+                .map_err(|err| {
+                    vlog::error!("Mempool storage access error: {}", err);
+                    TxAddError::DbError
+                })?;
+            if evicted.is_empty() {
+                return Err(TxAddError::MempoolFull);
+            }
+            evicted_txs.extend(evicted);
+        }
+        transaction
+            .commit()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+
+        vlog::info!(
+            "Mempool is full, evicted {} transactions: {:?}",
+            evicted_txs.len(),
+            evicted_txs
+        );
+        metrics::counter!("mempool.evicted_txs", evicted_txs.len() as u64);
+        Ok(())
+    }
+
+    /// Replaces the pending transaction with the same account and nonce by the new one.
+    /// The replacement is only allowed if the new transaction pays a strictly higher fee
+    /// in the same token, and the pending transaction is not proposed for execution yet.
+    async fn replace_tx(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        pending_tx: SignedZkSyncTx,
+        tx: &SignedZkSyncTx,
+        fee_per_chunk: &BigDecimal,
+    ) -> Result<(), TxAddError> {
+        let is_fee_higher = match (pending_tx.tx.get_fee_info(), tx.tx.get_fee_info()) {
+            (Some((_, pending_token, _, pending_fee)), Some((_, token, _, fee))) => {
+                pending_token == token && fee > pending_fee
+            }
+            _ => false,
+        };
+        if !is_fee_higher {
+            return Err(TxAddError::ReplacementFeeTooLow);
+        }
+
+        let replaced = storage
+            .chain()
+            .mempool_schema()
+            .replace_tx(pending_tx.hash(), tx, fee_per_chunk)
+            .await
+            .map_err(|err| {
+                vlog::error!("Mempool storage access error: {}", err);
+                TxAddError::DbError
+            })?;
+        if !replaced {
+            return Err(TxAddError::TxAlreadyExecuted);
+        }
+
+        vlog::info!(
+            "Pending transaction {} was replaced by {}",
+            pending_tx.hash().to_string(),
+            tx.hash().to_string()
+        );
+        metrics::increment_counter!("mempool.replaced_txs");
+        Ok(())
+    }
+
     async fn add_tx(&mut self, tx: SignedZkSyncTx) -> Result<(), TxAddError> {
         // Correctness should be checked by `signature_checker`, thus
         // `tx.check_correctness()` is not invoked here.
@@ -58,20 +236,37 @@ impl MempoolTransactionsHandler {
             .await
             .map_err(|_| TxAddError::DbError)?
             .unwrap_or_default();
+        self.check_nonce(&tx, nonce)?;
 
-        if tx.nonce() < nonce {
-            return Err(TxAddError::NonceMismatch);
-        }
-
-        storage
+        let pending_tx = storage
             .chain()
             .mempool_schema()
-            .insert_tx(&tx)
+            .get_pending_tx_by_nonce(tx.account(), tx.nonce())
             .await
-            .map_err(|err| {
-                vlog::error!("Mempool storage access error: {}", err);
-                TxAddError::DbError
-            })?;
+            .map_err(|_| TxAddError::DbError)?;
+
+        let fee_per_chunk = self.fee_per_chunk(SignedTxVariant::Tx(tx.clone())).await?;
+        if let Some(pending_tx) = pending_tx {
+            self.replace_tx(&mut storage, pending_tx, &tx, &fee_per_chunk)
+                .await?;
+        } else {
+            let mut new_txs = HashMap::new();
+            new_txs.insert(tx.account(), 1);
+            self.check_accounts_pending_txs(&mut storage, new_txs)
+                .await?;
+            self.ensure_mempool_capacity(&mut storage, 1, &fee_per_chunk)
+                .await?;
+
+            storage
+                .chain()
+                .mempool_schema()
+                .insert_tx(&tx, &fee_per_chunk)
+                .await
+                .map_err(|err| {
+                    vlog::error!("Mempool storage access error: {}", err);
+                    TxAddError::DbError
+                })?;
+        }
 
         let labels = vec![
             ("stage", "mempool".to_string()),
@@ -133,6 +328,25 @@ impl MempoolTransactionsHandler {
         Ok(())
     }
 
+    async fn retract_priority_ops(&mut self, ids: Vec<SerialId>) -> Result<(), TxAddError> {
+        let mut storage = self.db_pool.access_storage().await.map_err(|err| {
+            vlog::error!("Mempool storage access error: {}", err);
+            TxAddError::DbError
+        })?;
+        storage
+            .chain()
+            .mempool_schema()
+            .remove_unconfirmed_priority_ops(&ids)
+            .await
+            .map_err(|err| {
+                vlog::error!("Mempool storage access error: {}", err);
+                TxAddError::DbError
+            })?;
+
+        metrics::counter!("mempool.retracted_priority_ops", ids.len() as u64);
+        Ok(())
+    }
+
     async fn add_batch(
         &mut self,
         txs: Vec<SignedZkSyncTx>,
@@ -160,16 +374,25 @@ impl MempoolTransactionsHandler {
                 .await
                 .map_err(|_| TxAddError::DbError)?
                 .unwrap_or_default();
-
-            if tx.nonce() < nonce {
-                return Err(TxAddError::NonceMismatch);
-            }
+            self.check_nonce(tx, nonce)?;
         }
 
         if self.mempool_state.chunks_for_batch(&batch).await? > self.max_block_size_chunks {
             return Err(TxAddError::BatchTooBig);
         }
 
+        let mut new_txs = HashMap::new();
+        for tx in &batch.txs {
+            *new_txs.entry(tx.account()).or_insert(0) += 1;
+        }
+        self.check_accounts_pending_txs(&mut storage, new_txs)
+            .await?;
+        let fee_per_chunk = self
+            .fee_per_chunk(SignedTxVariant::Batch(batch.clone()))
+            .await?;
+        self.ensure_mempool_capacity(&mut storage, batch.txs.len() as u32, &fee_per_chunk)
+            .await?;
+
         for tx in &batch.txs {
             let labels = vec![
                 ("stage", "mempool".to_string()),
@@ -183,7 +406,7 @@ impl MempoolTransactionsHandler {
         storage
             .chain()
             .mempool_schema()
-            .insert_batch(&batch.txs, eth_signatures)
+            .insert_batch(&batch.txs, eth_signatures, &fee_per_chunk)
             .await
             .map_err(|err| {
                 vlog::warn!("Mempool storage access error: {}", err);
@@ -193,6 +416,66 @@ impl MempoolTransactionsHandler {
         Ok(())
     }
 
+    /// Removes the pending transaction or batch sent from the account and notifies
+    /// the event listeners about the cancellation. Transactions that are already proposed
+    /// for execution can't be cancelled.
+    async fn cancel_tx(
+        &mut self,
+        address: Address,
+        tx_hash: TxHash,
+    ) -> Result<Vec<TxHash>, TxAddError> {
+        let mut storage = self.db_pool.access_storage().await.map_err(|err| {
+            vlog::error!("Mempool storage access error: {}", err);
+            TxAddError::DbError
+        })?;
+        let mut transaction = storage
+            .start_transaction()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+
+        let cancelled_txs = transaction
+            .chain()
+            .mempool_schema()
+            .remove_account_pending_txs(address, tx_hash)
+            .await
+            .map_err(|err| {
+                vlog::error!("Mempool storage access error: {}", err);
+                TxAddError::DbError
+            })?;
+        if cancelled_txs.is_empty() {
+            let executed = transaction
+                .chain()
+                .mempool_schema()
+                .is_tx_executed(tx_hash)
+                .await
+                .map_err(|err| {
+                    vlog::error!("Mempool storage access error: {}", err);
+                    TxAddError::DbError
+                })?;
+            return Err(if executed {
+                TxAddError::TxAlreadyExecuted
+            } else {
+                TxAddError::PendingTxNotFound
+            });
+        }
+
+        transaction
+            .event_schema()
+            .store_cancelled_transaction_event(&cancelled_txs)
+            .await
+            .map_err(|err| {
+                vlog::error!("Mempool storage access error: {}", err);
+                TxAddError::DbError
+            })?;
+        transaction
+            .commit()
+            .await
+            .map_err(|_| TxAddError::DbError)?;
+
+        metrics::counter!("mempool.cancelled_txs", cancelled_txs.len() as u64);
+        Ok(cancelled_txs.iter().map(|tx| tx.hash()).collect())
+    }
+
     pub async fn run(mut self) {
         vlog::info!("Transaction mempool handler is running");
         while let Some(request) = self.requests.next().await {
@@ -209,6 +492,14 @@ impl MempoolTransactionsHandler {
                     let tx_add_result = self.add_priority_ops(ops, confirmed).await;
                     resp.send(tx_add_result).unwrap_or_default();
                 }
+                MempoolTransactionRequest::RetractPriorityOps(ids, resp) => {
+                    let retract_result = self.retract_priority_ops(ids).await;
+                    resp.send(retract_result).unwrap_or_default();
+                }
+                MempoolTransactionRequest::CancelTx(address, tx_hash, resp) => {
+                    let cancel_result = self.cancel_tx(address, tx_hash).await;
+                    resp.send(cancel_result).unwrap_or_default();
+                }
             }
         }
     }
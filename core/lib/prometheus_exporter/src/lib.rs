@@ -8,6 +8,7 @@ use std::ops::Add;
 use std::time::{Duration, Instant};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
+use zksync_eth_client::EthereumGateway;
 use zksync_storage::{ConnectionPool, QueryResult, StorageProcessor};
 use zksync_token_db_cache::TokenDBCache;
 use zksync_types::aggregated_operations::AggregatedActionType::*;
@@ -15,6 +16,7 @@ use zksync_types::block::IncompleteBlock;
 use zksync_types::{ExecutedOperations, TokenId};
 
 const QUERY_INTERVAL: Duration = Duration::from_secs(30);
+const ETH_CLIENT_HEALTH_INTERVAL: Duration = Duration::from_secs(10);
 
 pub fn run_operation_counter(connection_pool: ConnectionPool) -> JoinHandle<()> {
     tokio::spawn(async move {
@@ -62,6 +64,35 @@ async fn prometheus_exporter_iteration(connection_pool: ConnectionPool) -> Query
     Ok(())
 }
 
+/// Periodically exports the health of the Ethereum nodes used by the multiplexed gateway.
+pub fn run_eth_client_health_exporter(eth_gateway: EthereumGateway) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            // Node URLs may contain the API keys, so the nodes are labeled by their index.
+            for (idx, report) in eth_gateway.health_report().into_iter().enumerate() {
+                let labels = vec![("client", idx.to_string())];
+                metrics::gauge!("eth_client.multiplexed.health_score", report.score, &labels);
+                metrics::gauge!(
+                    "eth_client.multiplexed.latency_ms",
+                    report.latency_ms,
+                    &labels
+                );
+                metrics::gauge!(
+                    "eth_client.multiplexed.error_rate",
+                    report.error_rate,
+                    &labels
+                );
+                metrics::gauge!(
+                    "eth_client.multiplexed.block_lag",
+                    report.block_lag as f64,
+                    &labels
+                );
+            }
+            sleep(ETH_CLIENT_HEALTH_INTERVAL).await;
+        }
+    })
+}
+
 /// Extract volumes from block
 fn get_volumes(txs: &[ExecutedOperations]) -> HashMap<TokenId, BigUint> {
     let mut volumes: HashMap<TokenId, BigUint> = HashMap::new();
@@ -1,5 +1,6 @@
 pub mod handler;
 pub mod state;
+pub mod tree_backend;
 
 pub mod error;
 #[cfg(test)]
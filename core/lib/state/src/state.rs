@@ -1,17 +1,18 @@
 use num::BigUint;
 use std::collections::{HashMap, HashSet};
 
-use zksync_crypto::{merkle_tree::TreeMemoryUsage, params, params::NFT_STORAGE_ACCOUNT_ID, Fr};
+use zksync_crypto::{merkle_tree::TreeMemoryUsage, params::NFT_STORAGE_ACCOUNT_ID, Fr};
 use zksync_types::{
     helpers::reverse_updates,
     operations::{TransferOp, TransferToNewOp, ZkSyncOp},
     Account, AccountId, AccountMap, AccountTree, AccountUpdate, AccountUpdates, Address,
-    SignedZkSyncTx, TokenId, ZkSyncPriorityOp, ZkSyncTx, NFT,
+    BlockNumber, SignedZkSyncTx, TokenId, ZkSyncPriorityOp, ZkSyncTx, NFT,
 };
 
 use crate::{
     error::{OpError, TxBatchError},
     handler::{error::CloseOpError, TxHandler},
+    tree_backend::AccountTreeBackend,
     tx_ext::TxCheck,
 };
 
@@ -25,7 +26,7 @@ pub struct OpSuccess {
 #[derive(Debug, Clone)]
 pub struct ZkSyncState {
     /// Accounts stored in a sparse Merkle tree
-    balance_tree: AccountTree,
+    balance_tree: AccountTreeBackend,
 
     account_id_by_address: HashMap<Address, AccountId>,
 
@@ -64,10 +65,8 @@ impl TransferOutcome {
 
 impl ZkSyncState {
     pub fn empty() -> Self {
-        let tree_depth = params::account_tree_depth();
-        let balance_tree = AccountTree::new(tree_depth);
         Self {
-            balance_tree,
+            balance_tree: AccountTreeBackend::default(),
             account_id_by_address: HashMap::new(),
             next_free_id: AccountId(0),
             nfts: HashMap::new(),
@@ -91,22 +90,49 @@ impl ZkSyncState {
         empty
     }
 
+    /// Creates a state that contains only a subset of the network accounts, e.g. to execute
+    /// transactions touching these accounts without restoring the whole tree.
+    /// The next free account ID can't be derived from such a subset, so it has to be provided.
+    pub fn from_partial_acc_map(accounts: AccountMap, next_free_id: AccountId) -> Self {
+        let mut state = Self::from_acc_map(accounts);
+        state.next_free_id = std::cmp::max(state.next_free_id, next_free_id);
+        state
+    }
+
     pub fn new(
         balance_tree: AccountTree,
         account_id_by_address: HashMap<Address, AccountId>,
         nfts: HashMap<TokenId, NFT>,
     ) -> Self {
+        Self::with_tree_backend(balance_tree.into(), account_id_by_address, nfts)
+    }
+
+    /// Creates a state on top of the provided account tree, which may be either
+    /// the in-memory or the persistent one.
+    ///
+    /// For the persistent tree, `account_id_by_address` must contain all the accounts
+    /// stored in the tree: it's used to find the next free account ID without loading
+    /// the whole tree from the storage.
+    pub fn with_tree_backend(
+        balance_tree: AccountTreeBackend,
+        account_id_by_address: HashMap<Address, AccountId>,
+        nfts: HashMap<TokenId, NFT>,
+    ) -> Self {
+        let indices: Vec<u32> = match balance_tree.as_in_memory() {
+            Some(tree) => tree.items.keys().map(|index| *index as u32).collect(),
+            None => account_id_by_address.values().map(|id| **id).collect(),
+        };
         let mut next_free_id = 0;
-        for index in balance_tree.items.keys() {
-            if *index != NFT_STORAGE_ACCOUNT_ID.0 as u64 {
-                next_free_id = std::cmp::max(next_free_id, *index + 1);
+        for index in indices {
+            if index != NFT_STORAGE_ACCOUNT_ID.0 {
+                next_free_id = std::cmp::max(next_free_id, index + 1);
             }
         }
 
         Self {
             balance_tree,
             account_id_by_address,
-            next_free_id: AccountId(next_free_id as u32),
+            next_free_id: AccountId(next_free_id),
             nfts,
         }
     }
@@ -117,15 +143,9 @@ impl ZkSyncState {
 
     pub fn get_accounts(&self) -> Vec<(u32, Account)> {
         self.balance_tree
-            .items
-            .iter()
-            .filter_map(|a| {
-                if a.1 == &Account::default() {
-                    None
-                } else {
-                    Some((*a.0 as u32, a.1.clone()))
-                }
-            })
+            .items()
+            .into_iter()
+            .filter(|(_, account)| account != &Account::default())
             .collect()
     }
 
@@ -141,8 +161,7 @@ impl ZkSyncState {
         let account = self
             .balance_tree
             .get(*account_id)
-            .filter(|acc| !acc.is_default())
-            .cloned();
+            .filter(|acc| !acc.is_default());
         metrics::histogram!("state.get_account", start.elapsed());
 
         account
@@ -514,8 +533,29 @@ impl ZkSyncState {
         }
     }
 
-    pub fn get_balance_tree(&self) -> AccountTree {
-        self.balance_tree.clone()
+    /// Returns the copy of the account tree.
+    /// Returns `None` if the tree is persistent, since it would require loading the whole
+    /// tree into memory.
+    pub fn get_balance_tree(&self) -> Option<AccountTree> {
+        self.balance_tree.as_in_memory().cloned()
+    }
+
+    /// Writes the pending account tree changes to the storage if the tree is persistent,
+    /// marking them as the state after the `block`.
+    pub fn flush_tree(&mut self, block: BlockNumber) {
+        self.balance_tree.flush(block);
+    }
+
+    /// Marks the account tree changes made so far as the state after the `block`, so they can be
+    /// dropped from memory once the block is written to the persistent tree storage by another
+    /// copy of the state.
+    pub fn seal_tree(&mut self, block: BlockNumber) {
+        self.balance_tree.seal(block);
+    }
+
+    /// Returns the block which state was the last one written to the persistent tree storage.
+    pub fn flushed_tree_block(&self) -> Option<BlockNumber> {
+        self.balance_tree.flushed_block()
     }
 
     pub fn get_account_addresses(&self) -> HashMap<Address, AccountId> {
@@ -527,8 +567,13 @@ impl ZkSyncState {
 mod tests {
     use super::*;
     use crate::tests::{AccountState::*, PlasmaTestBuilder};
+    use std::sync::Arc;
     use vlog::sentry::types::Utc;
-    use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
+    use zksync_crypto::{
+        merkle_tree::persistent_smt::InMemoryTreeStorage,
+        params,
+        rand::{Rng, SeedableRng, XorShiftRng},
+    };
     use zksync_types::{
         tx::{Transfer, Withdraw},
         Nonce,
@@ -1070,6 +1115,77 @@ mod tests {
         assert_eq!(*state.next_free_id, 10);
     }
 
+    /// Checks that the state backed by the persistent tree matches the in-memory one
+    /// and can be reopened from the storage.
+    #[test]
+    fn persistent_tree_backend() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let mut accounts = AccountMap::default();
+        for id in [0, 1, 3, 8] {
+            let mut account = Account::default_with_address(&Address::from(rng.gen::<[u8; 20]>()));
+            account.add_balance(TokenId(0), &BigUint::from(rng.gen::<u64>()));
+            accounts.insert(AccountId(id), account);
+        }
+        let account_id_by_address: HashMap<_, _> = accounts
+            .iter()
+            .map(|(id, account)| (account.address, *id))
+            .collect();
+
+        let storage = Arc::new(InMemoryTreeStorage::default());
+        let in_memory_state = ZkSyncState::from_acc_map(accounts.clone());
+        let mut persistent_state = ZkSyncState::with_tree_backend(
+            AccountTreeBackend::persistent(storage.clone()),
+            account_id_by_address.clone(),
+            HashMap::new(),
+        );
+        for (id, account) in accounts {
+            persistent_state.insert_account(id, account);
+        }
+        assert_eq!(persistent_state.root_hash(), in_memory_state.root_hash());
+        assert_eq!(
+            persistent_state.balance_tree.merkle_path(3),
+            in_memory_state.balance_tree.merkle_path(3)
+        );
+        persistent_state.flush_tree(BlockNumber(1));
+        assert!(!storage.is_empty());
+        assert_eq!(persistent_state.flushed_tree_block(), Some(BlockNumber(1)));
+
+        let restored_state = ZkSyncState::with_tree_backend(
+            AccountTreeBackend::persistent(storage),
+            account_id_by_address,
+            HashMap::new(),
+        );
+        assert_eq!(*restored_state.next_free_id, 9);
+        assert_eq!(restored_state.root_hash(), in_memory_state.root_hash());
+        assert_eq!(
+            restored_state.get_account(AccountId(8)),
+            in_memory_state.get_account(AccountId(8))
+        );
+        assert!(restored_state.get_balance_tree().is_none());
+    }
+
+    /// Checks that the state created from a subset of accounts uses the provided next free ID.
+    #[test]
+    fn from_partial_acc_map() {
+        let mut accounts = AccountMap::default();
+        accounts.insert(
+            AccountId(3),
+            Account::default_with_address(&Address::random()),
+        );
+        accounts.insert(
+            NFT_STORAGE_ACCOUNT_ID,
+            Account::default_with_address(&Address::random()),
+        );
+
+        let state = ZkSyncState::from_partial_acc_map(accounts.clone(), AccountId(10));
+        assert_eq!(*state.next_free_id, 10);
+        assert!(state.get_account(AccountId(3)).is_some());
+
+        // The provided ID can't be lower than the one derived from the accounts.
+        let state = ZkSyncState::from_partial_acc_map(accounts, AccountId(2));
+        assert_eq!(*state.next_free_id, 4);
+    }
+
     /// Checks if insert_account panics if account has id greater that next_free_id.
     #[should_panic(
         expected = "assertion failed: id == NFT_STORAGE_ACCOUNT_ID || id <= self.next_free_id"
@@ -0,0 +1,212 @@
+//! Storage backends for the account tree of the `ZkSyncState`.
+
+use std::{path::Path, sync::Arc};
+
+use zksync_crypto::{
+    merkle_tree::{
+        persistent_smt::{TreeStorage, TreeStorageBatch},
+        TreeMemoryUsage,
+    },
+    params, Fr,
+};
+use zksync_types::{Account, AccountTree, BlockNumber, PersistentAccountTree};
+
+/// Tree storage which keeps the data in the on-disk key-value store.
+#[derive(Debug, Clone)]
+pub struct DiskTreeStorage {
+    db: sled::Db,
+}
+
+impl DiskTreeStorage {
+    /// Opens the store at the given path, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, sled::Error> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+impl TreeStorage for DiskTreeStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.db
+            .get(key)
+            .expect("Unable to read the account tree storage")
+            .map(|value| value.to_vec())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.db
+            .scan_prefix(prefix)
+            .map(|entry| {
+                let (key, value) = entry.expect("Unable to read the account tree storage");
+                (key.to_vec(), value.to_vec())
+            })
+            .collect()
+    }
+
+    fn write_batch(&self, batch: TreeStorageBatch) {
+        let mut sled_batch = sled::Batch::default();
+        for (key, value) in batch {
+            match value {
+                Some(value) => sled_batch.insert(key, value),
+                None => sled_batch.remove(key),
+            }
+        }
+        self.db
+            .apply_batch(sled_batch)
+            .expect("Unable to write to the account tree storage");
+    }
+
+    fn sync(&self) {
+        self.db
+            .flush()
+            .expect("Unable to flush the account tree storage");
+    }
+}
+
+/// Account tree used by the `ZkSyncState`.
+///
+/// The in-memory tree is the fastest one, but it requires the whole tree to fit into RAM.
+/// The persistent tree keeps the nodes in the [`TreeStorage`] and only loads the required ones.
+/// Both yield the same root hashes and Merkle paths.
+#[derive(Debug, Clone)]
+pub enum AccountTreeBackend {
+    InMemory(AccountTree),
+    Persistent(PersistentAccountTree),
+}
+
+impl Default for AccountTreeBackend {
+    fn default() -> Self {
+        Self::InMemory(AccountTree::new(params::account_tree_depth()))
+    }
+}
+
+impl From<AccountTree> for AccountTreeBackend {
+    fn from(tree: AccountTree) -> Self {
+        Self::InMemory(tree)
+    }
+}
+
+impl From<PersistentAccountTree> for AccountTreeBackend {
+    fn from(tree: PersistentAccountTree) -> Self {
+        Self::Persistent(tree)
+    }
+}
+
+impl AccountTreeBackend {
+    /// Creates the persistent tree stored on disk at `path`, or the in-memory one
+    /// if the path is not provided.
+    pub fn open(path: Option<&str>) -> Result<Self, sled::Error> {
+        Ok(match path {
+            Some(path) => Self::persistent(Arc::new(DiskTreeStorage::open(path)?)),
+            None => Self::default(),
+        })
+    }
+
+    /// Creates the persistent tree on top of the storage.
+    /// If the storage already contains the tree, it is used as is.
+    pub fn persistent(storage: Arc<dyn TreeStorage>) -> Self {
+        Self::Persistent(PersistentAccountTree::new(
+            params::account_tree_depth(),
+            storage,
+        ))
+    }
+
+    pub fn get(&self, index: u32) -> Option<Account> {
+        match self {
+            Self::InMemory(tree) => tree.get(index).cloned(),
+            Self::Persistent(tree) => tree.get(index),
+        }
+    }
+
+    pub fn insert(&mut self, index: u32, account: Account) {
+        match self {
+            Self::InMemory(tree) => tree.insert(index, account),
+            Self::Persistent(tree) => tree.insert(index, account),
+        }
+    }
+
+    pub fn remove(&mut self, index: u32) -> Option<Account> {
+        match self {
+            Self::InMemory(tree) => tree.remove(index),
+            Self::Persistent(tree) => tree.remove(index),
+        }
+    }
+
+    /// Returns all the accounts stored in the tree.
+    /// For the persistent tree, it requires loading all the accounts from the storage.
+    pub fn items(&self) -> Vec<(u32, Account)> {
+        match self {
+            Self::InMemory(tree) => tree
+                .items
+                .iter()
+                .map(|(index, account)| (*index as u32, account.clone()))
+                .collect(),
+            Self::Persistent(tree) => tree.items(),
+        }
+    }
+
+    pub fn root_hash(&self) -> Fr {
+        match self {
+            Self::InMemory(tree) => tree.root_hash(),
+            Self::Persistent(tree) => tree.root_hash(),
+        }
+    }
+
+    pub fn merkle_path(&self, index: u32) -> Vec<(Fr, bool)> {
+        match self {
+            Self::InMemory(tree) => tree.merkle_path(index),
+            Self::Persistent(tree) => tree.merkle_path(index),
+        }
+    }
+
+    pub fn memory_stats(&self) -> TreeMemoryUsage {
+        match self {
+            Self::InMemory(tree) => tree.memory_stats(),
+            Self::Persistent(tree) => tree.memory_stats(),
+        }
+    }
+
+    /// Writes the pending changes to the storage, marking them as the state after the `block`.
+    /// No-op for the in-memory tree.
+    pub fn flush(&mut self, block: BlockNumber) {
+        if let Self::Persistent(tree) = self {
+            tree.flush(u64::from(*block));
+        }
+    }
+
+    /// Marks the changes made so far as the ones of the `block` and drops the changes
+    /// already flushed to the storage by another clone of the tree, which is behind this one.
+    /// No-op for the in-memory tree.
+    pub fn seal(&mut self, block: BlockNumber) {
+        if let Self::Persistent(tree) = self {
+            tree.seal_pending_changes(u64::from(*block));
+            tree.discard_flushed_changes();
+        }
+    }
+
+    /// Returns the block which state was the last one flushed to the storage.
+    /// Always `None` for the in-memory tree.
+    pub fn flushed_block(&self) -> Option<BlockNumber> {
+        match self {
+            Self::InMemory(_) => None,
+            Self::Persistent(tree) => tree.version().map(|block| BlockNumber(block as u32)),
+        }
+    }
+
+    /// Removes all the accounts from the tree, including the ones written to the storage.
+    pub fn clear(&mut self) {
+        match self {
+            Self::InMemory(tree) => *tree = AccountTree::new(params::account_tree_depth()),
+            Self::Persistent(tree) => tree.clear(),
+        }
+    }
+
+    /// Returns the in-memory tree, or `None` if the tree is persistent.
+    pub fn as_in_memory(&self) -> Option<&AccountTree> {
+        match self {
+            Self::InMemory(tree) => Some(tree),
+            Self::Persistent(_) => None,
+        }
+    }
+}
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS mempool_txs_fee_per_chunk_idx;
+DROP INDEX IF EXISTS mempool_txs_account_nonce_idx;
+DROP INDEX IF EXISTS mempool_txs_primary_account_address_idx;
+ALTER TABLE mempool_txs DROP COLUMN fee_per_chunk;
+ALTER TABLE mempool_txs DROP COLUMN nonce;
+ALTER TABLE mempool_txs DROP COLUMN primary_account_address;
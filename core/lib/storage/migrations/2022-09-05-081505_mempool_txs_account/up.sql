@@ -0,0 +1,13 @@
+-- Account that signed the transaction, used to limit the number of pending transactions per account.
+-- Transactions stored before this migration have no account set and are not counted.
+ALTER TABLE mempool_txs ADD COLUMN primary_account_address bytea;
+-- Nonce of the transaction, so the transactions of an account can be ordered without parsing them.
+ALTER TABLE mempool_txs ADD COLUMN nonce BIGINT;
+-- USD fee paid for every chunk of the transaction (or the whole batch), used to choose transactions
+-- to evict once the mempool is full. It's not known for transactions stored before this migration,
+-- so such transactions are evicted first.
+ALTER TABLE mempool_txs ADD COLUMN fee_per_chunk NUMERIC;
+
+CREATE INDEX IF NOT EXISTS mempool_txs_primary_account_address_idx ON mempool_txs USING hash (primary_account_address);
+CREATE INDEX IF NOT EXISTS mempool_txs_account_nonce_idx ON mempool_txs (primary_account_address, nonce);
+CREATE INDEX IF NOT EXISTS mempool_txs_fee_per_chunk_idx ON mempool_txs (fee_per_chunk ASC NULLS FIRST);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS mempool_replaced_txs (
+    tx_hash TEXT PRIMARY KEY,
+    replaced_by TEXT NOT NULL,
+    tx JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    replaced_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
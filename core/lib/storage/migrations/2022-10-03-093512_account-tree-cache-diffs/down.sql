@@ -0,0 +1 @@
+DROP TABLE IF EXISTS account_tree_cache_diffs;
@@ -0,0 +1,8 @@
+-- Account tree cache changes made in the block relative to the previous block with cache.
+-- Full caches stored in `account_tree_cache` serve as checkpoints for these diffs.
+CREATE TABLE account_tree_cache_diffs
+(
+    block BIGINT REFERENCES blocks (number) ON UPDATE CASCADE ON DELETE CASCADE,
+    diff_binary BYTEA NOT NULL,
+    PRIMARY KEY (block)
+);
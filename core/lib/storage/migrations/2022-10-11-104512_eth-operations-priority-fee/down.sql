@@ -0,0 +1 @@
+ALTER TABLE eth_operations DROP last_used_priority_fee;
@@ -0,0 +1 @@
+ALTER TABLE eth_tx_hashes DROP superseded;
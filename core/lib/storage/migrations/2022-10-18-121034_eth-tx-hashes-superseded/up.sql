@@ -0,0 +1 @@
+ALTER TABLE eth_tx_hashes ADD superseded BOOLEAN NOT NULL DEFAULT false;
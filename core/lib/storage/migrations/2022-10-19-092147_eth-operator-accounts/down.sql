@@ -0,0 +1,2 @@
+DROP TABLE eth_operator_nonces;
+ALTER TABLE eth_operations DROP sender;
@@ -0,0 +1,10 @@
+-- Address of the additional operator account which sent the operation,
+-- `NULL` stands for the main operator account.
+ALTER TABLE eth_operations ADD sender BYTEA;
+
+-- Nonces of the additional operator accounts.
+-- Nonce of the main operator account is stored in the `eth_parameters` table.
+CREATE TABLE eth_operator_nonces (
+    address BYTEA PRIMARY KEY,
+    nonce BIGINT NOT NULL
+);
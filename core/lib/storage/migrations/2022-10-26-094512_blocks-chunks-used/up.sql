@@ -0,0 +1 @@
+ALTER TABLE blocks ADD COLUMN chunks_used BIGINT;
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS fee_quotes (
+    id BIGSERIAL PRIMARY KEY,
+    -- Account the quote was given to and the recipient of the quoted transaction.
+    sender bytea NOT NULL,
+    recipient bytea NOT NULL,
+    -- Serialized `OutputFeeType` of the quoted transaction.
+    fee_type jsonb NOT NULL,
+    token_id INT NOT NULL,
+    amount NUMERIC NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    valid_until TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Hash of the transaction which has used the quote.
+    tx_hash bytea
+);
+
+CREATE INDEX IF NOT EXISTS fee_quotes_sender_index ON fee_quotes (sender, recipient, fee_type, token_id);
+CREATE INDEX IF NOT EXISTS fee_quotes_valid_until_index ON fee_quotes (valid_until);
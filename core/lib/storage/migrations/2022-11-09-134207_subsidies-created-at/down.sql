@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS subsidies_type_created_at_index;
+ALTER TABLE subsidies DROP COLUMN IF EXISTS created_at;
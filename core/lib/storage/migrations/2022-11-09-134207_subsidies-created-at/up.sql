@@ -0,0 +1,2 @@
+ALTER TABLE subsidies ADD COLUMN created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
+CREATE INDEX IF NOT EXISTS subsidies_type_created_at_index ON subsidies (subsidy_type, created_at);
@@ -0,0 +1,3 @@
+-- Set once the transaction is handed over to the state keeper. Such transactions may already be executed
+-- in memory, so they can't be replaced or cancelled anymore.
+ALTER TABLE mempool_txs ADD COLUMN proposed BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,7 @@
+-- Hashes of the Ethereum transactions submitted via `eth_sendRawTransaction`, so the resulting
+-- zkSync transactions can be queried by the hash returned to the client.
+CREATE TABLE web3_raw_transactions (
+    eth_hash bytea PRIMARY KEY,
+    tx_hash bytea NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
@@ -6,7 +6,8 @@ use sqlx::{types::BigDecimal, Acquire};
 // Workspace imports
 use zksync_crypto::params::{MIN_NFT_TOKEN_ID, NFT_STORAGE_ACCOUNT_ID, NFT_TOKEN_ID};
 use zksync_types::{
-    Account, AccountId, AccountUpdates, Address, BlockNumber, Nonce, PubKeyHash, TokenId,
+    Account, AccountId, AccountUpdate, AccountUpdates, Address, BlockNumber, Nonce, PubKeyHash,
+    TokenId,
 };
 // Local imports
 use self::records::*;
@@ -238,14 +239,99 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
             .await?
             .0 as i64;
 
+        let account_diff = AccountSchema(&mut transaction)
+            .account_diffs_in_range(account_id, last_verified_block, i64::from(u32::MAX))
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect::<AccountUpdates>();
+
+        // Apply all the diffs to obtain the most recent account state.
+        let account_state = account_diff
+            .into_iter()
+            .map(|(_, upd)| upd)
+            .fold(account.clone(), Account::apply_update);
+
+        transaction.commit().await?;
+
+        metrics::histogram!(
+            "sql.chain.account.last_committed_state_for_account",
+            start.elapsed()
+        );
+        Ok(((last_block, account), account_state))
+    }
+
+    /// Restores the state of the account as of the given block by replaying the stored
+    /// account diffs.
+    ///
+    /// If the block is not older than the last verified one, the diffs are applied on top of
+    /// the last verified state of the account, otherwise they are replayed from the genesis.
+    /// Returns the state along with the number of the last block (not greater than the
+    /// requested one) in which the account was updated.
+    pub async fn account_state_for_block(
+        &mut self,
+        account_id: AccountId,
+        block_number: BlockNumber,
+    ) -> QueryResult<(BlockNumber, Option<Account>)> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let last_verified_block = BlockSchema(&mut transaction)
+            .get_last_verified_confirmed_block()
+            .await?;
+
+        // `accounts` table contains the state as of the last verified block, so it can be
+        // used as a starting point only for the blocks which are not older than that one.
+        let (from_block, mut last_update_block, account) = if block_number >= last_verified_block {
+            let (last_block, account) = AccountSchema(&mut transaction)
+                .account_and_last_block(account_id)
+                .await?;
+            (i64::from(*last_verified_block), last_block, account)
+        } else {
+            (-1, 0, None)
+        };
+
+        let account_diff = AccountSchema(&mut transaction)
+            .account_diffs_in_range(account_id, from_block, i64::from(*block_number))
+            .await?;
+        if let Some(last_diff) = account_diff.last() {
+            last_update_block = last_diff.block_number();
+        }
+
+        let account_state = account_diff
+            .into_iter()
+            .map(|diff| {
+                let (_, update): (AccountId, AccountUpdate) = diff.into();
+                update
+            })
+            .fold(account, Account::apply_update);
+
+        transaction.commit().await?;
+
+        metrics::histogram!("sql.chain.account.account_state_for_block", start.elapsed());
+        Ok((BlockNumber(last_update_block as u32), account_state))
+    }
+
+    /// Loads all the stored diffs of the account in the `(from_block, to_block]` range,
+    /// ordered in the way they should be applied.
+    async fn account_diffs_in_range(
+        &mut self,
+        account_id: AccountId,
+        from_block: i64,
+        to_block: i64,
+    ) -> QueryResult<Vec<StorageAccountDiff>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
         let account_balance_diff = sqlx::query_as!(
             StorageAccountUpdate,
             "
                 SELECT * FROM account_balance_updates
-                WHERE account_id = $1 AND block_number > $2
+                WHERE account_id = $1 AND block_number > $2 AND block_number <= $3
             ",
             i64::from(*account_id),
-            last_verified_block
+            from_block,
+            to_block
         )
         .fetch_all(transaction.conn())
         .await?;
@@ -254,10 +340,11 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
             StorageAccountCreation,
             "
                 SELECT * FROM account_creates
-                WHERE account_id = $1 AND block_number > $2
+                WHERE account_id = $1 AND block_number > $2 AND block_number <= $3
             ",
             i64::from(*account_id),
-            last_verified_block
+            from_block,
+            to_block
         )
         .fetch_all(transaction.conn())
         .await?;
@@ -266,10 +353,11 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
             StorageAccountPubkeyUpdate,
             "
                 SELECT * FROM account_pubkey_updates
-                WHERE account_id = $1 AND block_number > $2
+                WHERE account_id = $1 AND block_number > $2 AND block_number <= $3
             ",
             i64::from(*account_id),
-            last_verified_block
+            from_block,
+            to_block
         )
         .fetch_all(transaction.conn())
         .await?;
@@ -277,54 +365,38 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
             StorageMintNFTUpdate,
             "
                 SELECT * FROM mint_nft_updates
-                WHERE creator_account_id = $1 AND block_number > $2
+                WHERE creator_account_id = $1 AND block_number > $2 AND block_number <= $3
             ",
             *account_id as i32,
-            last_verified_block
+            from_block,
+            to_block
         )
         .fetch_all(transaction.conn())
         .await?;
+        transaction.commit().await?;
 
         // Chain the diffs, converting them into `StorageAccountDiff`.
-        let account_diff = {
-            let mut account_diff = Vec::new();
-            account_diff.extend(
-                account_balance_diff
-                    .into_iter()
-                    .map(StorageAccountDiff::from),
-            );
-            account_diff.extend(
-                account_creation_diff
-                    .into_iter()
-                    .map(StorageAccountDiff::from),
-            );
-            account_diff.extend(
-                account_pubkey_diff
-                    .into_iter()
-                    .map(StorageAccountDiff::from),
-            );
-            account_diff.extend(mint_nft_updates.into_iter().map(StorageAccountDiff::from));
-            account_diff.sort_by(StorageAccountDiff::cmp_order);
-
-            account_diff
+        let mut account_diff = Vec::new();
+        account_diff.extend(
+            account_balance_diff
                 .into_iter()
-                .map(Into::into)
-                .collect::<AccountUpdates>()
-        };
-
-        // Apply all the diffs to obtain the most recent account state.
-        let account_state = account_diff
-            .into_iter()
-            .map(|(_, upd)| upd)
-            .fold(account.clone(), Account::apply_update);
-
-        transaction.commit().await?;
-
-        metrics::histogram!(
-            "sql.chain.account.last_committed_state_for_account",
-            start.elapsed()
+                .map(StorageAccountDiff::from),
         );
-        Ok(((last_block, account), account_state))
+        account_diff.extend(
+            account_creation_diff
+                .into_iter()
+                .map(StorageAccountDiff::from),
+        );
+        account_diff.extend(
+            account_pubkey_diff
+                .into_iter()
+                .map(StorageAccountDiff::from),
+        );
+        account_diff.extend(mint_nft_updates.into_iter().map(StorageAccountDiff::from));
+        account_diff.sort_by(StorageAccountDiff::cmp_order);
+
+        metrics::histogram!("sql.chain.account.account_diffs_in_range", start.elapsed());
+        Ok(account_diff)
     }
 
     /// Loads the last verified state for the account (i.e. the one obtained in the last block
@@ -448,6 +520,26 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
         Ok(address)
     }
 
+    /// Returns the ID which will be assigned to the next account created in the network,
+    /// taking the committed blocks into account.
+    pub async fn next_free_account_id(&mut self) -> QueryResult<AccountId> {
+        let start = Instant::now();
+        let max_id = sqlx::query!(
+            "SELECT MAX(account_id) AS max_id FROM account_creates
+            WHERE is_create = true AND account_id <> $1",
+            i64::from(*NFT_STORAGE_ACCOUNT_ID)
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .max_id;
+
+        let next_free_id = max_id
+            .map(|id| AccountId(id as u32 + 1))
+            .unwrap_or_default();
+        metrics::histogram!("sql.chain.account.next_free_account_id", start.elapsed());
+        Ok(next_free_id)
+    }
+
     /// Obtains the last committed block that affects the account.
     pub async fn last_committed_block_with_update_for_acc(
         &mut self,
@@ -575,6 +667,83 @@ impl<'a, 'c> AccountSchema<'a, 'c> {
         Ok(balance)
     }
 
+    /// Returns the number of NFTs owned by the account as of the given block.
+    pub async fn get_account_nft_balance_for_block(
+        &mut self,
+        address: Address,
+        block_number: BlockNumber,
+    ) -> QueryResult<u32> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let account_id = transaction
+            .chain()
+            .account_schema()
+            .account_id_by_address(address)
+            .await?;
+        let account_id = match account_id {
+            Some(id) if id != NFT_STORAGE_ACCOUNT_ID => id,
+            // Special account ID is treated the same way as the non-existing account.
+            _ => return Ok(0),
+        };
+
+        let balance = sqlx::query!(
+            r#"
+                SELECT COUNT(*) FROM (
+                    SELECT DISTINCT ON (coin_id) new_balance FROM account_balance_updates
+                    WHERE account_id = $1 AND block_number <= $2 AND coin_id >= $3 AND coin_id < $4
+                    ORDER BY coin_id, block_number DESC, update_order_id DESC
+                ) AS last_updates
+                WHERE new_balance = 1
+            "#,
+            i64::from(account_id.0),
+            i64::from(block_number.0),
+            MIN_NFT_TOKEN_ID as i32,
+            NFT_TOKEN_ID.0 as i32
+        )
+        .fetch_one(transaction.conn())
+        .await?
+        .count
+        .unwrap_or(0) as u32;
+
+        transaction.commit().await?;
+        metrics::histogram!(
+            "sql.chain.account.get_account_nft_balance_for_block",
+            start.elapsed()
+        );
+
+        Ok(balance)
+    }
+
+    /// Returns the owner of the NFT as of the given block.
+    pub async fn get_nft_owner_for_block(
+        &mut self,
+        token_id: TokenId,
+        block_number: BlockNumber,
+    ) -> QueryResult<Option<AccountId>> {
+        let start = Instant::now();
+
+        let record = sqlx::query!(
+            r#"
+                SELECT account_id FROM (
+                    SELECT DISTINCT ON (account_id) account_id, new_balance FROM account_balance_updates
+                    WHERE coin_id = $1 AND block_number <= $2 AND account_id != $3
+                    ORDER BY account_id, block_number DESC, update_order_id DESC
+                ) AS last_updates
+                WHERE new_balance = 1
+            "#,
+            token_id.0 as i32,
+            i64::from(block_number.0),
+            i64::from(NFT_STORAGE_ACCOUNT_ID.0)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+        let owner_id = record.map(|record| AccountId(record.account_id as u32));
+
+        metrics::histogram!("sql.chain.account.get_nft_owner_for_block", start.elapsed());
+        Ok(owner_id)
+    }
+
     pub async fn get_nft_owner(&mut self, token_id: TokenId) -> QueryResult<Option<AccountId>> {
         let start = Instant::now();
 
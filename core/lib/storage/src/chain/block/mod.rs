@@ -18,8 +18,8 @@ use zksync_types::{
 };
 // Local imports
 use self::records::{
-    BlockTransactionItem, StorageBlock, StorageBlockDetails, StorageBlockMetadata,
-    StoragePendingBlock, StorageRootHash, TransactionItem,
+    BlockChunksUsage, BlockTransactionItem, StorageBlock, StorageBlockDetails,
+    StorageBlockMetadata, StoragePendingBlock, StorageRootHash, TransactionItem,
 };
 use crate::{
     chain::operations::{
@@ -736,6 +736,33 @@ impl<'a, 'c> BlockSchema<'a, 'c> {
         Ok(maybe_block_chunks.map(|val| val.chunks_left as usize))
     }
 
+    /// Returns the amount of used chunks and the size of the latest blocks, newest first.
+    /// Blocks stored without the amount of used chunks are skipped.
+    pub async fn load_recent_blocks_chunks(
+        &mut self,
+        limit: u32,
+    ) -> QueryResult<Vec<BlockChunksUsage>> {
+        let start = Instant::now();
+        let records = sqlx::query!(
+            r#"SELECT chunks_used AS "chunks_used!", block_size FROM blocks
+            WHERE chunks_used IS NOT NULL
+            ORDER BY number DESC
+            LIMIT $1"#,
+            i64::from(limit)
+        )
+        .fetch_all(self.0.conn())
+        .await?;
+        metrics::histogram!("sql.chain.block.load_recent_blocks_chunks", start.elapsed());
+
+        Ok(records
+            .into_iter()
+            .map(|record| BlockChunksUsage {
+                chunks_used: record.chunks_used as usize,
+                block_size: record.block_size as usize,
+            })
+            .collect())
+    }
+
     /// Helper method for retrieving pending blocks from the database.
     async fn load_storage_pending_block(&mut self) -> QueryResult<Option<StoragePendingBlock>> {
         let start = Instant::now();
@@ -912,16 +939,17 @@ impl<'a, 'c> BlockSchema<'a, 'c> {
             verify_gas_limit: block.verify_gas_limit.as_u64() as i64,
             commitment: block.block_commitment.as_bytes().to_vec(),
             timestamp: Some(block.timestamp as i64),
+            chunks_used: Some(block.chunks_used() as i64),
         };
 
         // Save new completed block.
         sqlx::query!("
-            INSERT INTO blocks (number, root_hash, fee_account_id, unprocessed_prior_op_before, unprocessed_prior_op_after, block_size, commit_gas_limit, verify_gas_limit, commitment, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            INSERT INTO blocks (number, root_hash, fee_account_id, unprocessed_prior_op_before, unprocessed_prior_op_after, block_size, commit_gas_limit, verify_gas_limit, commitment, timestamp, chunks_used)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             ",
             new_block.number, new_block.root_hash, new_block.fee_account_id, new_block.unprocessed_prior_op_before,
             new_block.unprocessed_prior_op_after, new_block.block_size, new_block.commit_gas_limit, new_block.verify_gas_limit,
-            new_block.commitment, new_block.timestamp,
+            new_block.commitment, new_block.timestamp, new_block.chunks_used,
         ).execute(transaction.conn())
         .await?;
 
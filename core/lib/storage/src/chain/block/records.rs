@@ -20,6 +20,14 @@ pub struct StorageBlock {
     pub verify_gas_limit: i64,
     pub commitment: Vec<u8>,
     pub timestamp: Option<i64>,
+    pub chunks_used: Option<i64>,
+}
+
+/// Capacity usage of the block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockChunksUsage {
+    pub chunks_used: usize,
+    pub block_size: usize,
 }
 
 #[derive(Debug, FromRow)]
@@ -1,29 +1,35 @@
 // Built-in deps
 use std::{collections::VecDeque, convert::TryFrom, str::FromStr, time::Instant};
 // External imports
+use chrono::{DateTime, Duration, Utc};
 use itertools::Itertools;
+use sqlx::types::BigDecimal;
 // Workspace imports
 use zksync_api_types::v02::pagination::PaginationDirection;
 use zksync_api_types::v02::transaction::{
-    ApiTxBatch, BatchStatus, TxHashSerializeWrapper, TxInBlockStatus,
+    ApiTxBatch, BatchStatus, Transaction, TxData, TxHashSerializeWrapper, TxInBlockStatus,
 };
 use zksync_types::{
     block::IncompleteBlock,
     mempool::SignedTxVariant,
     tx::{TxEthSignature, TxHash},
-    AccountId, Address, BlockNumber, ExecutedOperations, ExecutedPriorityOp, ExecutedTx,
-    PriorityOp, SerialId, SignedZkSyncTx, ZkSyncPriorityOp, H256,
+    AccountId, Address, BlockNumber, ExecutedOperations, ExecutedPriorityOp, ExecutedTx, Nonce,
+    PriorityOp, SerialId, SignedZkSyncTx, ZkSyncPriorityOp, ZkSyncTx, H256,
 };
 // Local imports
-use self::records::{MempoolPriorityOp, MempoolTx, QueuedBatchTx, RevertedBlock};
+use self::records::{MempoolPriorityOp, MempoolTx, QueuedBatchTx, ReplacedTx, RevertedBlock};
 use crate::{QueryResult, StorageProcessor};
 
 use crate::chain::operations::records::{
     StoredExecutedPriorityOperation, StoredExecutedTransaction,
 };
+use crate::chain::operations_ext::records::StorageTxData;
 
 pub mod records;
 
+/// Number of days during which the replaced transactions are still reported as replaced.
+const REPLACED_TXS_RETENTION_DAYS: i64 = 7;
+
 /// Schema for persisting transactions awaiting for the execution.
 ///
 /// This schema holds the transactions that are received by the `mempool` module, but not yet have
@@ -138,11 +144,13 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
     }
 
     /// Adds a new transactions batch to the mempool schema.
+    /// `fee_per_chunk` is the USD fee paid for every chunk of the whole batch.
     /// Returns id of the inserted batch
     pub async fn insert_batch(
         &mut self,
         txs: &[SignedZkSyncTx],
         eth_signatures: Vec<TxEthSignature>,
+        fee_per_chunk: &BigDecimal,
     ) -> QueryResult<i64> {
         let start = Instant::now();
         if txs.is_empty() {
@@ -165,14 +173,19 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
                 .eth_sign_data
                 .as_ref()
                 .map(|sd| serde_json::to_value(sd).expect("failed to encode EthSignData"));
+            let primary_account_address = first_tx_data.account().as_bytes().to_vec();
+            let nonce = *first_tx_data.nonce() as i64;
 
             sqlx::query!(
-                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data)
-                VALUES ($1, $2, $3, $4)",
+                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, primary_account_address, nonce, fee_per_chunk)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)",
                 tx_hash,
                 tx,
                 first_tx_data.created_at,
                 eth_sign_data,
+                primary_account_address,
+                nonce,
+                fee_per_chunk,
             )
             .execute(transaction.conn())
             .await?;
@@ -199,15 +212,20 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
                 .eth_sign_data
                 .as_ref()
                 .map(|sd| serde_json::to_value(sd).expect("failed to encode EthSignData"));
+            let primary_account_address = tx_data.account().as_bytes().to_vec();
+            let nonce = *tx_data.nonce() as i64;
 
             sqlx::query!(
-                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id)
-                VALUES ($1, $2, $3, $4, $5)",
+                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id, primary_account_address, nonce, fee_per_chunk)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
                 tx_hash,
                 tx,
                 tx_data.created_at,
                 eth_sign_data,
-                batch_id
+                batch_id,
+                primary_account_address,
+                nonce,
+                fee_per_chunk,
             )
             .execute(transaction.conn())
             .await?;
@@ -241,7 +259,12 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
     }
 
     /// Adds a new transaction to the mempool schema.
-    pub async fn insert_tx(&mut self, tx_data: &SignedZkSyncTx) -> QueryResult<()> {
+    /// `fee_per_chunk` is the USD fee paid for every chunk of the transaction.
+    pub async fn insert_tx(
+        &mut self,
+        tx_data: &SignedZkSyncTx,
+        fee_per_chunk: &BigDecimal,
+    ) -> QueryResult<()> {
         let start = Instant::now();
         let tx_hash = hex::encode(tx_data.tx.hash().as_ref());
         let tx = serde_json::to_value(&tx_data.tx)?;
@@ -251,15 +274,20 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
             .eth_sign_data
             .as_ref()
             .map(|sd| serde_json::to_value(sd).expect("failed to encode EthSignData"));
+        let primary_account_address = tx_data.account().as_bytes().to_vec();
+        let nonce = *tx_data.nonce() as i64;
 
         sqlx::query!(
-            "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id)
-            VALUES ($1, $2, $3, $4, $5)",
+            "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id, primary_account_address, nonce, fee_per_chunk)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
             tx_hash,
             tx,
             tx_data.created_at,
             eth_sign_data,
             batch_id,
+            primary_account_address,
+            nonce,
+            fee_per_chunk,
         )
         .execute(self.0.conn())
         .await?;
@@ -353,6 +381,187 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
         Ok(mempool_tx)
     }
 
+    /// Returns a pending transaction sent from the given account with the given nonce.
+    /// Transactions from batches and reverted transactions are not taken into account,
+    /// since they can't be replaced.
+    pub async fn get_pending_tx_by_nonce(
+        &mut self,
+        address: Address,
+        nonce: Nonce,
+    ) -> QueryResult<Option<SignedZkSyncTx>> {
+        let start = Instant::now();
+
+        let mempool_tx = sqlx::query_as!(
+            MempoolTx,
+            "SELECT * from mempool_txs
+            WHERE primary_account_address = $1 AND nonce = $2 AND batch_id = 0 AND reverted = false
+            ORDER BY id
+            LIMIT 1",
+            address.as_bytes(),
+            i64::from(*nonce)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "get_pending_tx_by_nonce");
+        mempool_tx
+            .map(SignedZkSyncTx::try_from)
+            .transpose()
+            .map_err(anyhow::Error::from)
+    }
+
+    /// Atomically replaces the pending transaction with a new one.
+    /// The new transaction takes the place of the replaced one in the queue,
+    /// while the replaced transaction is remembered to be reported as replaced.
+    ///
+    /// `fee_per_chunk` is the USD fee paid for every chunk of the new transaction.
+    ///
+    /// Returns `false` if the transaction was already proposed for execution and can't be replaced.
+    pub async fn replace_tx(
+        &mut self,
+        replaced_tx_hash: TxHash,
+        tx_data: &SignedZkSyncTx,
+        fee_per_chunk: &BigDecimal,
+    ) -> QueryResult<bool> {
+        let start = Instant::now();
+        let replaced_tx_hash = hex::encode(replaced_tx_hash.as_ref());
+        let tx_hash = hex::encode(tx_data.tx.hash().as_ref());
+        let tx = serde_json::to_value(&tx_data.tx)?;
+        let eth_sign_data = tx_data
+            .eth_sign_data
+            .as_ref()
+            .map(|sd| serde_json::to_value(sd).expect("failed to encode EthSignData"));
+
+        let mut transaction = self.0.start_transaction().await?;
+
+        // Lock the replaced transaction, so it can't be proposed for execution in the meantime.
+        let replaced_tx = sqlx::query!(
+            "SELECT id FROM mempool_txs
+            WHERE tx_hash = $1 AND proposed = false
+            FOR UPDATE",
+            replaced_tx_hash,
+        )
+        .fetch_optional(transaction.conn())
+        .await?;
+        if replaced_tx.is_none() {
+            metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "replace_tx");
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "INSERT INTO mempool_replaced_txs (tx_hash, replaced_by, tx, created_at)
+            SELECT tx_hash, $2, tx, created_at FROM mempool_txs
+            WHERE tx_hash = $1",
+            replaced_tx_hash,
+            tx_hash,
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        sqlx::query!(
+            "UPDATE mempool_txs
+            SET tx_hash = $2, tx = $3, created_at = $4, eth_sign_data = $5, fee_per_chunk = $6
+            WHERE tx_hash = $1",
+            replaced_tx_hash,
+            tx_hash,
+            tx,
+            tx_data.created_at,
+            eth_sign_data,
+            fee_per_chunk,
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "replace_tx");
+        Ok(true)
+    }
+
+    /// Marks transactions as handed over to the state keeper. Proposed transactions may be executed
+    /// at any moment, so they can't be replaced anymore.
+    ///
+    /// Returns hashes of the marked transactions, i.e. the ones that were not removed from the mempool.
+    pub async fn mark_txs_proposed(&mut self, txs: &[TxHash]) -> QueryResult<Vec<TxHash>> {
+        let start = Instant::now();
+        let tx_hashes: Vec<_> = txs.iter().map(hex::encode).collect();
+
+        let marked_txs = sqlx::query!(
+            "UPDATE mempool_txs SET proposed = true
+            WHERE tx_hash = ANY($1)
+            RETURNING tx_hash",
+            &tx_hashes
+        )
+        .fetch_all(self.0.conn())
+        .await?
+        .into_iter()
+        .map(|tx| TxHash::from_str(&format!("0x{}", tx.tx_hash)).expect("Incorrect tx hash stored"))
+        .collect();
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "mark_txs_proposed");
+        Ok(marked_txs)
+    }
+
+    /// Returns hash of the transaction that replaced the transaction with the given hash.
+    pub async fn get_replacement_tx_hash(
+        &mut self,
+        tx_hash: TxHash,
+    ) -> QueryResult<Option<TxHash>> {
+        let start = Instant::now();
+
+        let replaced_by = sqlx::query!(
+            "SELECT replaced_by FROM mempool_replaced_txs
+            WHERE tx_hash = $1",
+            hex::encode(tx_hash.as_ref())
+        )
+        .fetch_optional(self.0.conn())
+        .await?
+        .map(|row| {
+            TxHash::from_str(&format!("0x{}", row.replaced_by)).expect("Incorrect tx hash stored")
+        });
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "get_replacement_tx_hash");
+        Ok(replaced_by)
+    }
+
+    /// Returns the data of the replaced transaction in the API v0.2 format.
+    pub async fn get_replaced_tx_data(&mut self, tx_hash: TxHash) -> QueryResult<Option<TxData>> {
+        let start = Instant::now();
+
+        let replaced_tx = sqlx::query_as!(
+            ReplacedTx,
+            "SELECT * FROM mempool_replaced_txs
+            WHERE tx_hash = $1",
+            hex::encode(tx_hash.as_ref())
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        let tx_data = replaced_tx
+            .map(|replaced_tx| -> QueryResult<TxData> {
+                let tx: ZkSyncTx = serde_json::from_value(replaced_tx.tx)?;
+                let tx_hash = TxHash::from_str(&format!("0x{}", replaced_tx.tx_hash))
+                    .expect("Incorrect tx hash stored");
+                Ok(TxData {
+                    tx: Transaction {
+                        tx_hash,
+                        block_index: None,
+                        block_number: None,
+                        op: StorageTxData::tx_data_from_zksync_tx(tx, None),
+                        status: TxInBlockStatus::Replaced,
+                        fail_reason: None,
+                        created_at: Some(replaced_tx.created_at),
+                        batch_id: None,
+                    },
+                    eth_signature: None,
+                })
+            })
+            .transpose()?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "get_replaced_tx_data");
+        Ok(tx_data)
+    }
+
     /// Removes transactions that are already committed.
     /// Though it's unlikely that mempool schema will ever contain a committed
     /// transaction, it's better to ensure that we won't process the same transaction
@@ -399,10 +608,44 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
 
         self.remove_txs(&tx_hashes_to_remove).await?;
 
+        // Replaced transactions are still reported as replaced for some time after the replacement.
+        self.remove_replaced_txs(Utc::now() - Duration::days(REPLACED_TXS_RETENTION_DAYS))
+            .await?;
+
         metrics::histogram!("sql.chain.mempool.collect_garbage", start.elapsed());
         Ok(())
     }
 
+    /// Forgets the transactions replaced before the given moment, so they are not reported as replaced anymore.
+    pub async fn remove_replaced_txs(&mut self, replaced_before: DateTime<Utc>) -> QueryResult<()> {
+        let start = Instant::now();
+
+        sqlx::query!(
+            "DELETE FROM mempool_replaced_txs WHERE replaced_at < $1",
+            replaced_before
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "remove_replaced_txs");
+        Ok(())
+    }
+
+    /// Returns the transactions proposed for execution back to the queue.
+    /// Transactions executed by the state keeper only in memory are lost on restart, so this method
+    /// is expected to be invoked on the server start, once the already executed transactions are removed
+    /// by [`MempoolSchema::collect_garbage`].
+    pub async fn reset_proposed_txs(&mut self) -> QueryResult<()> {
+        let start = Instant::now();
+
+        sqlx::query!("UPDATE mempool_txs SET proposed = false WHERE proposed = true")
+            .execute(self.0.conn())
+            .await?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "reset_proposed_txs");
+        Ok(())
+    }
+
     pub async fn insert_priority_ops(
         &mut self,
         ops: &[PriorityOp],
@@ -575,6 +818,186 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
         Ok(())
     }
 
+    /// Removes the priority operations that haven't been confirmed yet, e.g. because they
+    /// were reorged out of the Ethereum chain. Confirmed operations are left intact.
+    pub async fn remove_unconfirmed_priority_ops(&mut self, ids: &[SerialId]) -> QueryResult<()> {
+        let start = Instant::now();
+        let ids: Vec<_> = ids.iter().map(|v| *v as i64).collect();
+        sqlx::query!(
+            "DELETE FROM mempool_priority_operations WHERE serial_id=ANY($1) AND confirmed = false",
+            &ids
+        )
+        .execute(self.0.conn())
+        .await?;
+        metrics::histogram!("sql.chain", start.elapsed(), "schema" => "mempool", "method" => "remove_unconfirmed_priority_ops");
+        Ok(())
+    }
+
+    /// Returns the number of transactions sent from the given account that are awaiting for the execution.
+    pub async fn get_account_pending_txs_count(&mut self, address: Address) -> QueryResult<u32> {
+        let start = Instant::now();
+
+        let count = sqlx::query!(
+            "SELECT COUNT(*) from mempool_txs
+            WHERE primary_account_address = $1",
+            address.as_bytes()
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .count;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "get_account_pending_txs_count");
+        Ok(count.unwrap_or(0) as u32)
+    }
+
+    /// Returns hashes of at most `limit` transactions that can be evicted from the mempool, starting
+    /// from the ones paying the least for a chunk. Only transactions paying less than `max_fee_per_chunk`
+    /// are returned.
+    ///
+    /// Only the transaction with the highest nonce of every account is a candidate, so the remaining
+    /// transactions of the account stay executable. Reverted transactions are never evicted, since
+    /// they have to be re-applied in the same order. Transactions proposed for execution are not
+    /// evicted either.
+    pub async fn get_eviction_candidates(
+        &mut self,
+        limit: u32,
+        max_fee_per_chunk: &BigDecimal,
+    ) -> QueryResult<Vec<TxHash>> {
+        let start = Instant::now();
+
+        let candidates = sqlx::query!(
+            "SELECT tx_hash FROM mempool_txs
+            WHERE reverted = false AND proposed = false
+                AND (fee_per_chunk IS NULL OR fee_per_chunk < $2)
+                AND NOT EXISTS (
+                    SELECT 1 FROM mempool_txs later
+                    WHERE later.primary_account_address = mempool_txs.primary_account_address
+                        AND later.nonce > mempool_txs.nonce
+                )
+            ORDER BY fee_per_chunk ASC NULLS FIRST, id ASC
+            LIMIT $1",
+            i64::from(limit),
+            max_fee_per_chunk,
+        )
+        .fetch_all(self.0.conn())
+        .await?
+        .into_iter()
+        .map(|tx| TxHash::from_str(&format!("0x{}", tx.tx_hash)).expect("Incorrect tx hash stored"))
+        .collect();
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "get_eviction_candidates");
+        Ok(candidates)
+    }
+
+    /// Removes the given transactions from the mempool to free the space for the new ones.
+    /// If a transaction belongs to a batch, the whole batch is removed.
+    /// Reverted transactions are never evicted, since they have to be re-applied in the same order.
+    /// Transactions proposed for execution are not evicted either.
+    ///
+    /// Returns hashes of the removed transactions.
+    pub async fn evict_txs(&mut self, txs: &[TxHash]) -> QueryResult<Vec<TxHash>> {
+        let start = Instant::now();
+        let tx_hashes: Vec<_> = txs.iter().map(hex::encode).collect();
+
+        let evicted_txs = sqlx::query!(
+            "DELETE FROM mempool_txs
+            WHERE (
+                tx_hash = ANY($1)
+                OR batch_id IN (SELECT batch_id FROM mempool_txs WHERE tx_hash = ANY($1) AND batch_id <> 0)
+            ) AND reverted = false AND proposed = false
+            RETURNING tx_hash",
+            &tx_hashes
+        )
+        .fetch_all(self.0.conn())
+        .await?
+        .into_iter()
+        .map(|tx| TxHash::from_str(&format!("0x{}", tx.tx_hash)).expect("Incorrect tx hash stored"))
+        .collect();
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "evict_txs");
+        Ok(evicted_txs)
+    }
+
+    /// Removes the pending transaction sent from the given account. If the transaction belongs
+    /// to a batch, or `tx_hash` is a batch hash, the whole batch is removed. Nothing is removed
+    /// if any of the matching transactions is sent from another account, or is already proposed
+    /// for execution.
+    ///
+    /// Returns the removed transactions.
+    pub async fn remove_account_pending_txs(
+        &mut self,
+        address: Address,
+        tx_hash: TxHash,
+    ) -> QueryResult<Vec<SignedZkSyncTx>> {
+        let start = Instant::now();
+
+        let removed_txs = sqlx::query_as!(
+            MempoolTx,
+            r#"
+            WITH cancelled AS (
+                SELECT id, primary_account_address, proposed FROM mempool_txs
+                WHERE reverted = false AND (
+                    tx_hash = $1
+                    OR batch_id IN (
+                        SELECT batch_id FROM mempool_txs WHERE tx_hash = $1 AND batch_id <> 0
+                        UNION
+                        SELECT batch_id FROM txs_batches_hashes WHERE batch_hash = $2
+                    )
+                )
+            )
+            DELETE FROM mempool_txs
+            WHERE id IN (SELECT id FROM cancelled)
+                AND proposed = false
+                AND NOT EXISTS (
+                    SELECT 1 FROM cancelled
+                    WHERE primary_account_address IS DISTINCT FROM $3 OR proposed = true
+                )
+            RETURNING *
+            "#,
+            hex::encode(tx_hash.as_ref()),
+            tx_hash.as_ref(),
+            address.as_bytes()
+        )
+        .fetch_all(self.0.conn())
+        .await?
+        .into_iter()
+        .map(SignedZkSyncTx::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "remove_account_pending_txs");
+        Ok(removed_txs)
+    }
+
+    /// Checks whether the transaction or the batch with the given hash is already executed,
+    /// or is proposed for execution and may be executed in the pending block at any moment.
+    pub async fn is_tx_executed(&mut self, tx_hash: TxHash) -> QueryResult<bool> {
+        let start = Instant::now();
+
+        let executed = sqlx::query!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM mempool_txs
+                WHERE proposed = true AND (
+                    tx_hash = $1
+                    OR batch_id IN (SELECT batch_id FROM txs_batches_hashes WHERE batch_hash = $2)
+                )
+            ) OR EXISTS (
+                SELECT 1 FROM executed_transactions
+                WHERE tx_hash = $2
+                    OR batch_id IN (SELECT batch_id FROM txs_batches_hashes WHERE batch_hash = $2)
+            ) AS "executed!"
+            "#,
+            hex::encode(tx_hash.as_ref()),
+            tx_hash.as_ref()
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .executed;
+
+        metrics::histogram!("sql.chain", start.elapsed(), "mempool" => "is_tx_executed");
+        Ok(executed)
+    }
+
     /// Returns mempool size.
     pub async fn get_mempool_size(&mut self) -> QueryResult<u32> {
         let start = Instant::now();
@@ -909,14 +1332,16 @@ impl<'a, 'c> MempoolSchema<'a, 'c> {
             .await?;
 
             sqlx::query!(
-                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id, next_priority_op_serial_id, reverted)
-                VALUES ($1, $2, $3, $4, $5, $6, true)",
+                "INSERT INTO mempool_txs (tx_hash, tx, created_at, eth_sign_data, batch_id, next_priority_op_serial_id, reverted, primary_account_address, nonce)
+                VALUES ($1, $2, $3, $4, $5, $6, true, $7, $8)",
                 tx_hash,
                 tx_value,
                 created_at,
                 eth_sign_data,
                 batch_id.unwrap_or(0i64),
                 next_priority_op_serial_id as i64,
+                primary_account_address,
+                *nonce as i64,
             )
             .execute(transaction.conn())
             .await?;
@@ -3,7 +3,7 @@ use std::convert::TryFrom;
 
 // External imports
 use chrono::{DateTime, Utc};
-use sqlx::FromRow;
+use sqlx::{types::BigDecimal, FromRow};
 
 // Workspace imports
 use zksync_types::{PriorityOp, SignedZkSyncTx, H256};
@@ -33,6 +33,14 @@ pub(crate) struct MempoolTx {
     pub next_priority_op_serial_id: Option<i64>,
     #[allow(dead_code)]
     pub reverted: bool,
+    #[allow(dead_code)]
+    pub primary_account_address: Option<Vec<u8>>,
+    #[allow(dead_code)]
+    pub nonce: Option<i64>,
+    #[allow(dead_code)]
+    pub proposed: bool,
+    #[allow(dead_code)]
+    pub fee_per_chunk: Option<BigDecimal>,
 }
 
 impl TryFrom<MempoolTx> for SignedZkSyncTx {
@@ -50,6 +58,17 @@ impl TryFrom<MempoolTx> for SignedZkSyncTx {
     }
 }
 
+#[derive(Debug, FromRow)]
+pub(crate) struct ReplacedTx {
+    pub tx_hash: String,
+    #[allow(dead_code)]
+    pub replaced_by: String,
+    pub tx: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    #[allow(dead_code)]
+    pub replaced_at: DateTime<Utc>,
+}
+
 #[derive(Debug, FromRow, PartialEq)]
 pub(crate) struct QueuedBatchTx {
     pub tx_hash: String,
@@ -1571,6 +1571,29 @@ impl<'a, 'c> OperationsExtSchema<'a, 'c> {
         Ok(result)
     }
 
+    /// Stores the hash of the Ethereum transaction submitted via web3 API which resulted in the zkSync transaction.
+    /// Both `tx_data_for_web3` and `web3_receipt_by_hash` look the transaction up by either of hashes.
+    pub async fn store_web3_raw_transaction(
+        &mut self,
+        eth_hash: &[u8],
+        tx_hash: TxHash,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "INSERT INTO web3_raw_transactions (eth_hash, tx_hash) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            eth_hash,
+            tx_hash.as_ref(),
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!(
+            "sql.chain.operations_ext.store_web3_raw_transaction",
+            start.elapsed()
+        );
+        Ok(())
+    }
+
     pub async fn tx_data_for_web3(&mut self, hash: &[u8]) -> QueryResult<Option<Web3TxData>> {
         let start = Instant::now();
 
@@ -1578,15 +1601,16 @@ impl<'a, 'c> OperationsExtSchema<'a, 'c> {
             Web3TxData,
             r#"
                 WITH transaction AS (
+                    -- The transaction submitted via `eth_sendRawTransaction` is reported under the requested hash.
                     SELECT
-                        tx_hash,
+                        $1::bytea AS tx_hash,
                         block_number,
                         nonce,
                         block_index,
                         from_account,
                         to_account
                     FROM executed_transactions
-                    WHERE tx_hash = $1
+                    WHERE tx_hash IN ($1, (SELECT tx_hash FROM web3_raw_transactions WHERE eth_hash = $1))
                 ), priority_op AS (
                     SELECT
                         tx_hash,
@@ -1634,8 +1658,9 @@ impl<'a, 'c> OperationsExtSchema<'a, 'c> {
             Web3TxReceipt,
             r#"
                 WITH transaction AS (
+                    -- The transaction submitted via `eth_sendRawTransaction` is reported under the requested hash.
                     SELECT
-                        tx_hash,
+                        $1::bytea AS tx_hash,
                         block_number,
                         operation,
                         block_index,
@@ -1643,7 +1668,7 @@ impl<'a, 'c> OperationsExtSchema<'a, 'c> {
                         to_account,
                         success
                     FROM executed_transactions
-                    WHERE tx_hash = $1
+                    WHERE tx_hash IN ($1, (SELECT tx_hash FROM web3_raw_transactions WHERE eth_hash = $1))
                 ), priority_op AS (
                     SELECT
                         tx_hash,
@@ -2,11 +2,18 @@
 use std::time::Instant;
 // External imports
 // Workspace imports
+use zksync_crypto::merkle_tree::parallel_smt::{
+    SparseMerkleTreeCacheDiffBN256, SparseMerkleTreeSerializableCacheBN256,
+};
 use zksync_types::BlockNumber;
 // Local imports
 use super::records::AccountTreeCache;
 use crate::{QueryResult, StorageProcessor};
 
+/// Maximum distance between two full account tree caches (checkpoints).
+/// Caches for the blocks in between are stored as diffs relative to the previous cached block.
+pub const ACCOUNT_TREE_CACHE_CHECKPOINT_INTERVAL: u32 = 100;
+
 /// Tree cache schema contains methods to store/load Merkle tree cache.
 ///
 /// This schema is used to interact with caches encoded as *binary* data (using `bincode` protocol).
@@ -131,12 +138,20 @@ impl<'a, 'c> TreeCacheSchemaBincode<'a, 'c> {
         last_block: BlockNumber,
     ) -> QueryResult<()> {
         let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
         sqlx::query!(
             "DELETE FROM account_tree_cache WHERE block > $1",
             *last_block as i64
         )
-        .execute(self.0.conn())
+        .execute(transaction.conn())
         .await?;
+        sqlx::query!(
+            "DELETE FROM account_tree_cache_diffs WHERE block > $1",
+            *last_block as i64
+        )
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
 
         metrics::histogram!(
             "sql.chain.tree_cache.bincode.remove_new_account_tree_cache",
@@ -164,4 +179,263 @@ impl<'a, 'c> TreeCacheSchemaBincode<'a, 'c> {
         );
         Ok(())
     }
+
+    /// Stores the diff of the account tree cache for a block relative to the cache
+    /// of the previous block that has either the full cache or the diff.
+    /// Expects `diff` to be encoded according to the `bincode` protocol.
+    pub async fn store_account_tree_cache_diff(
+        &mut self,
+        block: BlockNumber,
+        diff: Vec<u8>,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        if *block == 0 {
+            return Ok(());
+        }
+
+        sqlx::query!(
+            "
+            INSERT INTO account_tree_cache_diffs (block, diff_binary)
+            VALUES ($1, $2)
+            ON CONFLICT (block)
+            DO UPDATE SET diff_binary = $2
+            ",
+            *block as i64,
+            diff,
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.store_account_tree_cache_diff",
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    /// Loads the account tree cache diffs for the blocks in range `(from_block, to_block]`
+    /// ordered by the block number.
+    pub async fn get_account_tree_cache_diffs(
+        &mut self,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> QueryResult<Vec<(BlockNumber, Vec<u8>)>> {
+        let start = Instant::now();
+        let diffs = sqlx::query!(
+            "
+            SELECT block, diff_binary FROM account_tree_cache_diffs
+            WHERE block > $1 AND block <= $2
+            ORDER BY block
+            ",
+            *from_block as i64,
+            *to_block as i64,
+        )
+        .fetch_all(self.0.conn())
+        .await?
+        .into_iter()
+        .map(|record| (BlockNumber(record.block as u32), record.diff_binary))
+        .collect();
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.get_account_tree_cache_diffs",
+            start.elapsed()
+        );
+        Ok(diffs)
+    }
+
+    /// Gets the number of the latest block for which the account tree cache can be restored,
+    /// either from the full cache or from the diff.
+    /// Returns `None` if there are no caches in the database.
+    pub async fn get_last_block_with_account_tree_cache_or_diff(
+        &mut self,
+    ) -> QueryResult<Option<BlockNumber>> {
+        let start = Instant::now();
+
+        let last_block = sqlx::query!(
+            r#"
+            SELECT GREATEST(
+                (SELECT MAX(block) FROM account_tree_cache WHERE tree_cache_binary IS NOT NULL),
+                (SELECT MAX(block) FROM account_tree_cache_diffs)
+            ) AS "block"
+            "#
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .block;
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.get_last_block_with_account_tree_cache_or_diff",
+            start.elapsed()
+        );
+        Ok(last_block.map(|block| BlockNumber(block as u32)))
+    }
+
+    /// Gets the latest full account tree cache (checkpoint) stored for a block not greater than `block`.
+    pub async fn get_account_tree_cache_checkpoint(
+        &mut self,
+        block: BlockNumber,
+    ) -> QueryResult<Option<(BlockNumber, Vec<u8>)>> {
+        let start = Instant::now();
+        let account_tree_cache = sqlx::query_as!(
+            AccountTreeCache,
+            "
+            SELECT * FROM account_tree_cache
+            WHERE block <= $1 AND tree_cache_binary IS NOT NULL
+            ORDER BY block DESC
+            LIMIT 1
+            ",
+            *block as i64
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.get_account_tree_cache_checkpoint",
+            start.elapsed()
+        );
+        Ok(account_tree_cache.map(|w| {
+            (
+                BlockNumber(w.block as u32),
+                w.tree_cache_binary
+                    .expect("Must be 'some' because of condition in query"),
+            )
+        }))
+    }
+
+    /// Restores the account tree cache for the latest cached block not greater than `block`.
+    /// The cache is built from the nearest full cache (checkpoint) and the diffs stored after it.
+    /// Returns the block number and the associated cache.
+    pub async fn load_account_tree_cache_incremental(
+        &mut self,
+        block: BlockNumber,
+    ) -> QueryResult<Option<(BlockNumber, SparseMerkleTreeSerializableCacheBN256)>> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let (checkpoint_block, checkpoint) = match TreeCacheSchemaBincode(&mut transaction)
+            .get_account_tree_cache_checkpoint(block)
+            .await?
+        {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        let diffs = TreeCacheSchemaBincode(&mut transaction)
+            .get_account_tree_cache_diffs(checkpoint_block, block)
+            .await?;
+        transaction.commit().await?;
+
+        let mut cache_block = checkpoint_block;
+        let mut cache = SparseMerkleTreeSerializableCacheBN256::decode_bincode(&checkpoint);
+        for (diff_block, diff) in diffs {
+            cache.apply_diff(SparseMerkleTreeCacheDiffBN256::decode_bincode(&diff));
+            cache_block = diff_block;
+        }
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.load_account_tree_cache_incremental",
+            start.elapsed()
+        );
+        Ok(Some((cache_block, cache)))
+    }
+
+    /// Stores the account tree cache for a block.
+    ///
+    /// If `previous` is the cache of the latest cached block, only the diff relative to it is stored.
+    /// Otherwise, or if the last full cache is older than [`ACCOUNT_TREE_CACHE_CHECKPOINT_INTERVAL`]
+    /// blocks, the full cache is stored as a new checkpoint.
+    /// The size of the stored data is reported as the `tree_cache_size` metric.
+    pub async fn store_account_tree_cache_incremental(
+        &mut self,
+        block: BlockNumber,
+        previous: Option<(BlockNumber, &SparseMerkleTreeSerializableCacheBN256)>,
+        tree_cache: &SparseMerkleTreeSerializableCacheBN256,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        if *block == 0 {
+            return Ok(());
+        }
+        let mut transaction = self.0.start_transaction().await?;
+
+        let last_cached_block = TreeCacheSchemaBincode(&mut transaction)
+            .get_last_block_with_account_tree_cache_or_diff()
+            .await?;
+        let last_checkpoint = TreeCacheSchemaBincode(&mut transaction)
+            .get_last_block_with_account_tree_cache()
+            .await?;
+
+        let diff_base = previous.filter(|(previous_block, _)| {
+            Some(*previous_block) == last_cached_block && *previous_block < block
+        });
+        let checkpoint_is_recent = last_checkpoint.map_or(false, |checkpoint| {
+            *block - *checkpoint < ACCOUNT_TREE_CACHE_CHECKPOINT_INTERVAL
+        });
+        match diff_base {
+            Some((_, previous_cache)) if checkpoint_is_recent => {
+                let diff = previous_cache.diff(tree_cache).encode_bincode();
+                metrics::histogram!("tree_cache_size", diff.len() as f64, "type" => "diff");
+                TreeCacheSchemaBincode(&mut transaction)
+                    .store_account_tree_cache_diff(block, diff)
+                    .await?;
+            }
+            _ => {
+                let tree_cache = tree_cache.encode_bincode();
+                metrics::histogram!("tree_cache_size", tree_cache.len() as f64, "type" => "checkpoint");
+                TreeCacheSchemaBincode(&mut transaction)
+                    .store_account_tree_cache(block, tree_cache)
+                    .await?;
+            }
+        }
+        transaction.commit().await?;
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.store_account_tree_cache_incremental",
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    /// Removes the caches that are not required to restore the account tree cache
+    /// for the blocks starting from `keep_from_block`.
+    /// All the checkpoints older than the nearest checkpoint to `keep_from_block`
+    /// are removed along with the diffs that precede it.
+    pub async fn compact_account_tree_cache(
+        &mut self,
+        keep_from_block: BlockNumber,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let checkpoint_block = sqlx::query!(
+            "
+            SELECT MAX(block) FROM account_tree_cache
+            WHERE block <= $1 AND tree_cache_binary IS NOT NULL
+            ",
+            *keep_from_block as i64
+        )
+        .fetch_one(transaction.conn())
+        .await?
+        .max;
+
+        if let Some(checkpoint_block) = checkpoint_block {
+            sqlx::query!(
+                "DELETE FROM account_tree_cache WHERE block < $1",
+                checkpoint_block
+            )
+            .execute(transaction.conn())
+            .await?;
+            sqlx::query!(
+                "DELETE FROM account_tree_cache_diffs WHERE block <= $1",
+                checkpoint_block
+            )
+            .execute(transaction.conn())
+            .await?;
+        }
+        transaction.commit().await?;
+
+        metrics::histogram!(
+            "sql.chain.tree_cache.bincode.compact_account_tree_cache",
+            start.elapsed()
+        );
+        Ok(())
+    }
 }
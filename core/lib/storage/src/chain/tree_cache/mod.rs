@@ -7,4 +7,7 @@ mod bincode_schema;
 mod json_schema;
 pub mod records;
 
-pub use self::{bincode_schema::TreeCacheSchemaBincode, json_schema::TreeCacheSchemaJSON};
+pub use self::{
+    bincode_schema::{TreeCacheSchemaBincode, ACCOUNT_TREE_CACHE_CHECKPOINT_INTERVAL},
+    json_schema::TreeCacheSchemaJSON,
+};
@@ -11,7 +11,7 @@ use zksync_types::{
     event::{
         account::AccountStateChangeStatus, block::BlockStatus, transaction::TransactionStatus,
     },
-    BlockNumber, H256, U256,
+    Address, BlockNumber, H256, U256,
 };
 // Local imports
 use self::records::{ETHOperationData, ETHParams, ETHStats, ETHTxHash, StorageETHOperation};
@@ -93,19 +93,25 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
                 .expect("Stored operation type must have a valid value");
             let last_used_gas_price =
                 U256::from_str(&eth_op.last_used_gas_price.to_string()).unwrap();
+            let last_used_priority_fee = eth_op
+                .last_used_priority_fee
+                .map(|fee| U256::from_str(&fee.to_string()).unwrap());
             let used_tx_hashes = eth_tx_hashes
                 .iter()
                 .map(|entry| H256::from_slice(&entry.tx_hash))
                 .collect();
             let final_hash = eth_op.final_hash.map(|hash| H256::from_slice(&hash));
+            let sender = eth_op.sender.map(|address| Address::from_slice(&address));
 
             let eth_op = ETHOperation {
                 id: eth_op.id,
                 op_type,
                 op,
                 nonce: eth_op.nonce.into(),
+                sender,
                 last_deadline_block: eth_op.last_deadline_block as u64,
                 last_used_gas_price,
+                last_used_priority_fee,
                 used_tx_hashes,
                 encoded_tx_data: eth_op.raw_tx,
                 confirmed: eth_op.confirmed,
@@ -215,32 +221,47 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
 
     /// Stores the sent (but not confirmed yet) Ethereum transaction in the database.
     /// Returns the `ETHOperation` object containing the assigned nonce and operation ID.
+    ///
+    /// `last_used_priority_fee` is only set for EIP-1559 transactions.
+    #[allow(clippy::too_many_arguments)]
     pub async fn save_new_eth_tx(
         &mut self,
         op_type: AggregatedActionType,
         operation: Option<(i64, AggregatedOperation)>,
         last_deadline_block: i64,
         last_used_gas_price: BigUint,
+        last_used_priority_fee: Option<BigUint>,
         raw_tx: Vec<u8>,
+        sender: Option<Address>,
     ) -> QueryResult<InsertedOperationResponse> {
         let start = Instant::now();
         let mut transaction = self.0.start_transaction().await?;
 
         // It's important to assign nonce within the same db transaction
         // as saving the operation to avoid the state divergence.
-        let nonce = EthereumSchema(&mut transaction).get_next_nonce().await?;
+        let nonce = match sender {
+            Some(address) => {
+                EthereumSchema(&mut transaction)
+                    .get_next_operator_nonce(address)
+                    .await?
+            }
+            None => EthereumSchema(&mut transaction).get_next_nonce().await?,
+        };
 
         // Create and insert the operation.
 
         // Obtain the operation ID for the follow-up queried.
         let last_used_gas_price = BigDecimal::from(BigInt::from(last_used_gas_price));
+        let last_used_priority_fee =
+            last_used_priority_fee.map(|fee| BigDecimal::from(BigInt::from(fee)));
         let eth_op_id = sqlx::query!(
             "
-                INSERT INTO eth_operations (op_type, nonce, last_deadline_block, last_used_gas_price, raw_tx)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO eth_operations (op_type, nonce, last_deadline_block, last_used_gas_price, last_used_priority_fee, raw_tx, sender)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
                 RETURNING id
             ",
-            op_type.to_string(), nonce, last_deadline_block, last_used_gas_price, raw_tx,
+            op_type.to_string(), nonce, last_deadline_block, last_used_gas_price, last_used_priority_fee, raw_tx,
+            sender.as_ref().map(|address| address.as_bytes()),
         )
         .fetch_one(transaction.conn())
         .await?
@@ -323,21 +344,24 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
     }
 
     /// Updates the Ethereum operation by adding a new tx data.
-    /// The new deadline block / gas value are placed instead of old values to the main entry.
+    /// The new deadline block / gas values are placed instead of old values to the main entry.
     pub async fn update_eth_tx(
         &mut self,
         eth_op_id: i64,
         new_deadline_block: i64,
         new_gas_value: BigUint,
+        new_priority_fee: Option<BigUint>,
     ) -> QueryResult<()> {
         let start = Instant::now();
         // Update the stored tx.
         let new_gas_price = BigDecimal::from(BigInt::from(new_gas_value));
+        let new_priority_fee = new_priority_fee.map(|fee| BigDecimal::from(BigInt::from(fee)));
         sqlx::query!(
             "UPDATE eth_operations 
-            SET last_used_gas_price = $1, last_deadline_block = $2
-            WHERE id = $3",
+            SET last_used_gas_price = $1, last_used_priority_fee = $2, last_deadline_block = $3
+            WHERE id = $4",
             new_gas_price,
+            new_priority_fee,
             new_deadline_block,
             eth_op_id
         )
@@ -348,6 +372,65 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
         Ok(())
     }
 
+    /// Assigns the new nonce to the Ethereum operation.
+    /// All the tx hashes sent for the operation so far are marked as superseded, since
+    /// they were signed with the old nonce.
+    pub async fn reassign_nonce(&mut self, eth_op_id: i64, new_nonce: i64) -> QueryResult<()> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        sqlx::query!(
+            "UPDATE eth_tx_hashes SET superseded = true WHERE eth_op_id = $1",
+            eth_op_id
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            "UPDATE eth_operations SET nonce = $1 WHERE id = $2",
+            new_nonce,
+            eth_op_id
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await?;
+        metrics::histogram!("sql.ethereum.reassign_nonce", start.elapsed());
+        Ok(())
+    }
+
+    /// Moves the Ethereum operation to another operator account (`None` stands for the main one)
+    /// with the new nonce from its sequence.
+    /// Same as for `reassign_nonce`, all the tx hashes sent for the operation so far are marked
+    /// as superseded.
+    pub async fn reassign_sender(
+        &mut self,
+        eth_op_id: i64,
+        sender: Option<Address>,
+        new_nonce: i64,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        sqlx::query!(
+            "UPDATE eth_tx_hashes SET superseded = true WHERE eth_op_id = $1",
+            eth_op_id
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            "UPDATE eth_operations SET sender = $1, nonce = $2 WHERE id = $3",
+            sender.as_ref().map(|address| address.as_bytes()),
+            new_nonce,
+            eth_op_id
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await?;
+        metrics::histogram!("sql.ethereum.reassign_sender", start.elapsed());
+        Ok(())
+    }
+
     /// Updates the stats counter with the new operation reported.
     /// This method should be called once **per operation**. It means that if transaction
     /// for some operation was stuck, and another transaction was created for it, this method
@@ -468,6 +551,31 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
         Ok(average_gas_price)
     }
 
+    /// Loads the gas prices of the latest confirmed Ethereum operations, newest first.
+    pub async fn load_recent_gas_prices(&mut self, limit: u32) -> QueryResult<Vec<U256>> {
+        let start = Instant::now();
+        let records = sqlx::query!(
+            "SELECT last_used_gas_price FROM eth_operations
+            WHERE confirmed = true
+            ORDER BY id DESC
+            LIMIT $1",
+            i64::from(limit)
+        )
+        .fetch_all(self.0.conn())
+        .await?;
+
+        let gas_prices = records
+            .into_iter()
+            .map(|record| {
+                U256::from_dec_str(&record.last_used_gas_price.to_string())
+                    .expect("Invalid gas price stored in DB")
+            })
+            .collect();
+
+        metrics::histogram!("sql.ethereum.load_recent_gas_prices", start.elapsed());
+        Ok(gas_prices)
+    }
+
     /// Loads the stored Ethereum operations stats.
     pub async fn load_stats(&mut self) -> QueryResult<ETHStats> {
         let start = Instant::now();
@@ -612,6 +720,72 @@ impl<'a, 'c> EthereumSchema<'a, 'c> {
         Ok(old_nonce_value)
     }
 
+    /// Same as `get_next_nonce`, but for the additional operator account.
+    /// The nonce of the account must be initialized by `advance_operator_nonce` first.
+    pub async fn get_next_operator_nonce(&mut self, address: Address) -> QueryResult<i64> {
+        let start = Instant::now();
+        let nonce = sqlx::query!(
+            r#"UPDATE eth_operator_nonces
+            SET nonce = nonce + 1
+            WHERE address = $1
+            RETURNING nonce - 1 AS "nonce!""#,
+            address.as_bytes()
+        )
+        .fetch_optional(self.0.conn())
+        .await?
+        .ok_or_else(|| {
+            format_err!(
+                "Nonce of the operator account {:?} is not initialized",
+                address
+            )
+        })?
+        .nonce;
+
+        metrics::histogram!("sql.ethereum.get_next_operator_nonce", start.elapsed());
+        Ok(nonce)
+    }
+
+    /// Makes sure that the next nonce returned by `get_next_nonce` is not less than `min_nonce`.
+    /// Used when nonces of the operator account were consumed by transactions sent
+    /// not by the `ETHSender`.
+    pub async fn advance_nonce(&mut self, min_nonce: i64) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "UPDATE eth_parameters
+            SET nonce = GREATEST(nonce, $1)
+            WHERE id = true",
+            min_nonce
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.ethereum.advance_nonce", start.elapsed());
+        Ok(())
+    }
+
+    /// Same as `advance_nonce`, but for the additional operator account.
+    /// If the nonce of the account isn't stored yet, it's initialized with `min_nonce`.
+    pub async fn advance_operator_nonce(
+        &mut self,
+        address: Address,
+        min_nonce: i64,
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "INSERT INTO eth_operator_nonces (address, nonce)
+            VALUES ($1, $2)
+            ON CONFLICT (address)
+            DO UPDATE SET nonce = GREATEST(eth_operator_nonces.nonce, $2)",
+            address.as_bytes(),
+            min_nonce
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.ethereum.advance_operator_nonce", start.elapsed());
+        Ok(())
+    }
+
     /// Method that internally initializes the `eth_parameters` table.
     /// Since in db tests the database is empty, we must provide a possibility
     /// to initialize required db fields.
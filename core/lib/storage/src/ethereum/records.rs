@@ -15,6 +15,8 @@ pub struct StorageETHOperation {
     pub last_deadline_block: i64,
     pub last_used_gas_price: BigDecimal,
     pub created_at: Option<DateTime<Utc>>,
+    pub last_used_priority_fee: Option<BigDecimal>,
+    pub sender: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, FromRow, PartialEq)]
@@ -30,6 +32,8 @@ pub struct ETHOperationData {
     pub agg_op_id: Option<i64>,
     pub arguments: Option<serde_json::Value>,
     pub created_at: Option<DateTime<Utc>>,
+    pub last_used_priority_fee: Option<BigDecimal>,
+    pub sender: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, FromRow, PartialEq)]
@@ -37,6 +41,7 @@ pub struct ETHTxHash {
     pub id: i64,
     pub eth_op_id: i64,
     pub tx_hash: Vec<u8>,
+    pub superseded: bool,
 }
 
 #[derive(Debug, FromRow, PartialEq)]
@@ -13,7 +13,7 @@ use zksync_types::{
         transaction::{TransactionEvent, TransactionStatus},
         EventId,
     },
-    BlockNumber,
+    BlockNumber, SignedZkSyncTx,
 };
 // Local uses
 use crate::{QueryResult, StorageProcessor};
@@ -94,6 +94,34 @@ impl<'a, 'c> EventSchema<'a, 'c> {
         Ok(events)
     }
 
+    /// Load the events of the given type from the database with the `id` greater than `from`.
+    pub async fn fetch_new_events_of_type(
+        &mut self,
+        from: EventId,
+        event_type: EventType,
+    ) -> QueryResult<Vec<StoredEvent>> {
+        let start = Instant::now();
+        let events = sqlx::query_as!(
+            StoredEvent,
+            r#"
+            SELECT
+                id,
+                block_number,
+                event_type as "event_type!: EventType",
+                event_data
+            FROM events WHERE id > $1 AND event_type = $2
+            ORDER BY id ASC
+            "#,
+            *from as i64,
+            event_type as EventType,
+        )
+        .fetch_all(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.event.fetch_new_events_of_type", start.elapsed());
+        Ok(events)
+    }
+
     /// Load the id of the latest event in the database.
     /// Returns `None` if the `events` table is empty.
     pub async fn get_last_event_id(&mut self) -> QueryResult<Option<EventId>> {
@@ -287,4 +315,43 @@ impl<'a, 'c> EventSchema<'a, 'c> {
         metrics::histogram!("sql.event.store_queued_transaction_event", start.elapsed());
         Ok(())
     }
+
+    /// Store `Cancelled` events for the transactions removed from the mempool by their senders.
+    /// Since cancelled transactions never get into a block, events are bound to the last saved block.
+    pub async fn store_cancelled_transaction_event(
+        &mut self,
+        txs: &[SignedZkSyncTx],
+    ) -> QueryResult<()> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+
+        let block_number = transaction
+            .chain()
+            .block_schema()
+            .get_last_saved_block()
+            .await?;
+        let events: Vec<serde_json::Value> = txs
+            .iter()
+            .filter_map(|tx| {
+                let transaction_event = TransactionEvent::from_cancelled_tx(tx, block_number)?;
+
+                Some(
+                    serde_json::to_value(transaction_event)
+                        .expect("couldn't serialize transaction event"),
+                )
+            })
+            .collect();
+
+        transaction
+            .event_schema()
+            .store_event_data(block_number, EventType::Transaction, &events)
+            .await?;
+        transaction.commit().await?;
+
+        metrics::histogram!(
+            "sql.event.store_cancelled_transaction_event",
+            start.elapsed()
+        );
+        Ok(())
+    }
 }
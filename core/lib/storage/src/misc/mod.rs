@@ -1,10 +1,13 @@
 // Built-in deps
 use std::time::Instant;
 // External imports
+use chrono::{DateTime, Utc};
 use sqlx::types::BigDecimal;
+// Workspace imports
+use zksync_types::{tx::TxHash, Address, OutputFeeType, TokenId};
 // Local imports
 
-use self::records::Subsidy;
+use self::records::{FeeQuoteUsage, StorageFeeQuote, Subsidy};
 use crate::{QueryResult, StorageProcessor};
 use num::ToPrimitive;
 
@@ -40,6 +43,68 @@ impl<'a, 'c> MiscSchema<'a, 'c> {
         Ok(())
     }
 
+    /// Stores the subsidy if the total amount of the subsidies of its type fits the budget, and the amount
+    /// of the ones stored since `cap_since` fits the cap, both in USD scaled by 10^6.
+    /// Returns `false` without storing the subsidy otherwise.
+    pub async fn store_subsidy_within_limits(
+        &mut self,
+        subsidy: Subsidy,
+        budget_usd_scaled: u64,
+        cap_usd_scaled: u64,
+        cap_since: DateTime<Utc>,
+    ) -> QueryResult<bool> {
+        let start = Instant::now();
+        let mut transaction = self.0.start_transaction().await?;
+        // The concurrent subsidies of the same type are stored one by one, so together they can't exceed
+        // the limits. The lock is released once the transaction is finished.
+        sqlx::query!(
+            r#"SELECT 1 AS "locked!" FROM pg_advisory_xact_lock(hashtext($1))"#,
+            subsidy.subsidy_type
+        )
+        .fetch_one(transaction.conn())
+        .await?;
+
+        let used = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(usd_amount_scale6), 0) as "total!",
+                COALESCE(SUM(usd_amount_scale6) FILTER (WHERE created_at >= $2), 0) as "since!"
+            FROM subsidies
+            WHERE subsidy_type = $1
+            "#,
+            subsidy.subsidy_type,
+            cap_since
+        )
+        .fetch_one(transaction.conn())
+        .await?;
+
+        let new_usd = BigDecimal::from(subsidy.usd_amount_scaled);
+        let fits = used.total + &new_usd <= BigDecimal::from(budget_usd_scaled)
+            && used.since + &new_usd <= BigDecimal::from(cap_usd_scaled);
+        if fits {
+            transaction.misc_schema().store_subsidy(subsidy).await?;
+        }
+        transaction.commit().await?;
+
+        metrics::histogram!("sql.misc.store_subsidy_within_limits", start.elapsed());
+        Ok(fits)
+    }
+
+    /// Removes the subsidy of the given type stored for the transaction.
+    pub async fn remove_subsidy(&mut self, tx_hash: TxHash, subsidy_type: &str) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "DELETE FROM subsidies WHERE tx_hash = $1 AND subsidy_type = $2",
+            tx_hash.as_ref(),
+            subsidy_type
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.misc.remove_subsidy", start.elapsed());
+        Ok(())
+    }
+
     /// Loads tokens from the database starting from the given id with the given limit in the ascending order.
     pub async fn get_total_used_subsidy_for_type(
         &mut self,
@@ -64,4 +129,173 @@ impl<'a, 'c> MiscSchema<'a, 'c> {
         metrics::histogram!("sql.token.get_total_used_subsidy_for_type", start.elapsed());
         Ok(sum)
     }
+
+    /// Loads the total amount of subsidies of the given type stored since the given time,
+    /// in USD scaled by 10^6.
+    pub async fn get_used_subsidy_for_type_since(
+        &mut self,
+        subsidy_type: &str,
+        since: DateTime<Utc>,
+    ) -> QueryResult<BigDecimal> {
+        let start = Instant::now();
+        let sum = sqlx::query!(
+            r#"
+            SELECT SUM(usd_amount_scale6) as total FROM subsidies
+            WHERE subsidy_type = $1 AND created_at >= $2
+            "#,
+            subsidy_type,
+            since
+        )
+        .fetch_one(self.0.conn())
+        .await?
+        .total
+        .unwrap_or_else(|| BigDecimal::from(0));
+
+        metrics::histogram!("sql.misc.get_used_subsidy_for_type_since", start.elapsed());
+        Ok(sum)
+    }
+
+    /// Stores the fee quoted to the sender for the transaction of the given type
+    /// to the given recipient paying fee in the given token.
+    pub async fn store_fee_quote(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        fee_type: OutputFeeType,
+        token_id: TokenId,
+        amount: BigDecimal,
+        valid_until: DateTime<Utc>,
+    ) -> QueryResult<StorageFeeQuote> {
+        let start = Instant::now();
+        let fee_type = serde_json::to_value(fee_type).expect("Failed to serialize fee type");
+        let quote = sqlx::query_as!(
+            StorageFeeQuote,
+            r#"
+            INSERT INTO fee_quotes ( sender, recipient, fee_type, token_id, amount, valid_until )
+            VALUES ( $1, $2, $3, $4, $5, $6 )
+            RETURNING *
+            "#,
+            sender.as_bytes(),
+            recipient.as_bytes(),
+            fee_type,
+            token_id.0 as i32,
+            amount,
+            valid_until
+        )
+        .fetch_one(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.misc.store_fee_quote", start.elapsed());
+        Ok(quote)
+    }
+
+    /// Loads the latest unused quote for the same transaction which stays valid at least until `valid_until`.
+    pub async fn find_unused_fee_quote(
+        &mut self,
+        sender: Address,
+        recipient: Address,
+        fee_type: OutputFeeType,
+        token_id: TokenId,
+        valid_until: DateTime<Utc>,
+    ) -> QueryResult<Option<StorageFeeQuote>> {
+        let start = Instant::now();
+        let fee_type = serde_json::to_value(fee_type).expect("Failed to serialize fee type");
+        let quote = sqlx::query_as!(
+            StorageFeeQuote,
+            r#"
+            SELECT * FROM fee_quotes
+            WHERE sender = $1 AND recipient = $2 AND fee_type = $3 AND token_id = $4
+                AND valid_until >= $5 AND tx_hash IS NULL
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            sender.as_bytes(),
+            recipient.as_bytes(),
+            fee_type,
+            token_id.0 as i32,
+            valid_until
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.misc.find_unused_fee_quote", start.elapsed());
+        Ok(quote)
+    }
+
+    /// Loads the fee quote by its ID.
+    pub async fn get_fee_quote(&mut self, id: i64) -> QueryResult<Option<StorageFeeQuote>> {
+        let start = Instant::now();
+        let quote = sqlx::query_as!(
+            StorageFeeQuote,
+            "SELECT * FROM fee_quotes WHERE id = $1",
+            id
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.misc.get_fee_quote", start.elapsed());
+        Ok(quote)
+    }
+
+    /// Marks the unexpired quote as used by the transaction, so it can't be used by any other one.
+    /// Only the transaction which has actually marked the quote as used may release it.
+    pub async fn use_fee_quote(&mut self, id: i64, tx_hash: TxHash) -> QueryResult<FeeQuoteUsage> {
+        let start = Instant::now();
+        let updated = sqlx::query!(
+            "UPDATE fee_quotes SET tx_hash = $2 WHERE id = $1 AND valid_until > now() AND tx_hash IS NULL",
+            id,
+            tx_hash.as_ref()
+        )
+        .execute(self.0.conn())
+        .await?
+        .rows_affected();
+
+        let usage = if updated == 1 {
+            FeeQuoteUsage::Used
+        } else {
+            let already_used = sqlx::query!(
+                "SELECT id FROM fee_quotes WHERE id = $1 AND valid_until > now() AND tx_hash = $2",
+                id,
+                tx_hash.as_ref()
+            )
+            .fetch_optional(self.0.conn())
+            .await?
+            .is_some();
+            if already_used {
+                FeeQuoteUsage::AlreadyUsed
+            } else {
+                FeeQuoteUsage::Unavailable
+            }
+        };
+
+        metrics::histogram!("sql.misc.use_fee_quote", start.elapsed());
+        Ok(usage)
+    }
+
+    /// Makes the quote available again if the transaction which has used it was not accepted.
+    pub async fn release_fee_quote(&mut self, id: i64, tx_hash: TxHash) -> QueryResult<()> {
+        let start = Instant::now();
+        sqlx::query!(
+            "UPDATE fee_quotes SET tx_hash = NULL WHERE id = $1 AND tx_hash = $2",
+            id,
+            tx_hash.as_ref()
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        metrics::histogram!("sql.misc.release_fee_quote", start.elapsed());
+        Ok(())
+    }
+
+    /// Removes the expired fee quotes, returns the number of removed quotes.
+    pub async fn remove_expired_fee_quotes(&mut self) -> QueryResult<u64> {
+        let start = Instant::now();
+        let removed = sqlx::query!("DELETE FROM fee_quotes WHERE valid_until <= now()")
+            .execute(self.0.conn())
+            .await?
+            .rows_affected();
+
+        metrics::histogram!("sql.misc.remove_expired_fee_quotes", start.elapsed());
+        Ok(removed)
+    }
 }
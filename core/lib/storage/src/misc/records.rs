@@ -1,5 +1,6 @@
 // External imports
-use sqlx::types::BigDecimal;
+use chrono::{DateTime, Utc};
+use sqlx::{types::BigDecimal, FromRow};
 // Workspace imports
 // Local imports
 use zksync_types::{tx::TxHash, TokenId};
@@ -13,3 +14,30 @@ pub struct Subsidy {
     pub full_cost_token: BigDecimal,
     pub subsidy_type: String,
 }
+
+/// Fee amount guaranteed by the server to the sender for the transaction of the given type
+/// to the given recipient paying fee in the given token.
+#[derive(Debug, Clone, FromRow)]
+pub struct StorageFeeQuote {
+    pub id: i64,
+    pub sender: Vec<u8>,
+    pub recipient: Vec<u8>,
+    pub fee_type: serde_json::Value,
+    pub token_id: i32,
+    pub amount: BigDecimal,
+    pub created_at: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    /// Hash of the transaction which has used the quote.
+    pub tx_hash: Option<Vec<u8>>,
+}
+
+/// Result of an attempt to use the fee quote by the transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeQuoteUsage {
+    /// The quote was unused and is now used by the transaction.
+    Used,
+    /// The quote has already been used by the same transaction.
+    AlreadyUsed,
+    /// The quote is expired or has already been used by another transaction.
+    Unavailable,
+}
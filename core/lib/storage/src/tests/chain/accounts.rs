@@ -323,6 +323,77 @@ async fn test_get_balance(mut storage: StorageProcessor<'_>) -> QueryResult<()>
     Ok(())
 }
 
+/// Checks that the state of the account can be restored as of any committed block.
+#[db_test]
+async fn test_account_state_for_block(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let _lock = ACCOUNT_MUTEX.lock().await;
+    let address = Address::random();
+    let account_id = AccountId(1);
+    let updates1 = vec![
+        (
+            account_id,
+            AccountUpdate::Create {
+                address,
+                nonce: Nonce(0),
+            },
+        ),
+        (
+            account_id,
+            AccountUpdate::UpdateBalance {
+                old_nonce: Nonce(0),
+                new_nonce: Nonce(1),
+                balance_update: (TokenId(0), BigUint::zero(), BigUint::from(100u32)),
+            },
+        ),
+    ];
+    let updates2 = vec![(
+        account_id,
+        AccountUpdate::UpdateBalance {
+            old_nonce: Nonce(1),
+            new_nonce: Nonce(2),
+            balance_update: (TokenId(1), BigUint::zero(), BigUint::from(500u32)),
+        },
+    )];
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(2), &updates1, 0)
+        .await?;
+    storage
+        .chain()
+        .state_schema()
+        .commit_state_update(BlockNumber(4), &updates2, 0)
+        .await?;
+
+    // Account doesn't exist before its creation.
+    let (_, account) = AccountSchema(&mut storage)
+        .account_state_for_block(account_id, BlockNumber(1))
+        .await?;
+    assert!(account.is_none());
+
+    for (block, expected_last_update, expected_nonce, expected_token_balance) in [
+        (2, 2, 1, 0u32),
+        (3, 2, 1, 0u32),
+        (4, 4, 2, 500u32),
+        (5, 4, 2, 500u32),
+    ] {
+        let (last_update, account) = AccountSchema(&mut storage)
+            .account_state_for_block(account_id, BlockNumber(block))
+            .await?;
+        let account = account.expect("Account should exist");
+        assert_eq!(last_update, BlockNumber(expected_last_update));
+        assert_eq!(account.address, address);
+        assert_eq!(account.nonce, Nonce(expected_nonce));
+        assert_eq!(account.get_balance(TokenId(0)), BigUint::from(100u32));
+        assert_eq!(
+            account.get_balance(TokenId(1)),
+            BigUint::from(expected_token_balance)
+        );
+    }
+
+    Ok(())
+}
+
 #[db_test]
 async fn test_get_account_nft_balance(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     let address = Address::random();
@@ -17,7 +17,10 @@ use super::operations_ext::{
 };
 use crate::{
     chain::{
-        block::{records::StorageBlockDetails, BlockSchema},
+        block::{
+            records::{BlockChunksUsage, StorageBlockDetails},
+            BlockSchema,
+        },
         operations::OperationsSchema,
         state::StateSchema,
     },
@@ -275,7 +278,9 @@ async fn test_find_block_by_height_or_hash(mut storage: StorageProcessor<'_>) ->
                 Some((id, op)),
                 100,
                 100u32.into(),
+                None,
                 Default::default(),
+                None,
             )
             .await?;
 
@@ -318,7 +323,9 @@ async fn test_find_block_by_height_or_hash(mut storage: StorageProcessor<'_>) ->
                         Some((id, op)),
                         100,
                         100u32.into(),
+                        None,
                         Default::default(),
+                        None,
                     )
                     .await?;
                 EthereumSchema(&mut storage)
@@ -483,7 +490,9 @@ async fn test_block_page(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
                 Some((id, op)),
                 100,
                 100u32.into(),
+                None,
                 Default::default(),
+                None,
             )
             .await?;
         EthereumSchema(&mut storage)
@@ -516,7 +525,9 @@ async fn test_block_page(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
                     Some((id, op)),
                     100,
                     100u32.into(),
+                    None,
                     Default::default(),
+                    None,
                 )
                 .await?;
             EthereumSchema(&mut storage)
@@ -618,7 +629,9 @@ async fn unconfirmed_transaction(mut storage: StorageProcessor<'_>) -> QueryResu
                 Some((id, op)),
                 100,
                 100u32.into(),
+                None,
                 Default::default(),
+                None,
             )
             .await?;
         EthereumSchema(&mut storage)
@@ -655,7 +668,9 @@ async fn unconfirmed_transaction(mut storage: StorageProcessor<'_>) -> QueryResu
                     Some((id, op)),
                     100,
                     100u32.into(),
+                    None,
                     Default::default(),
+                    None,
                 )
                 .await?;
             EthereumSchema(&mut storage)
@@ -1393,3 +1408,40 @@ async fn test_incomplete_block_logic(mut storage: StorageProcessor<'_>) -> Query
 
     Ok(())
 }
+
+/// Checks that the amount of used chunks is stored with the block and reported for the latest blocks.
+#[db_test]
+async fn test_load_recent_blocks_chunks(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let mut setup = TransactionsHistoryTestSetup::new();
+    setup.add_block(1);
+    setup.add_block(2);
+    for block in &setup.blocks {
+        BlockSchema(&mut storage)
+            .finish_incomplete_block(block.clone())
+            .await?;
+    }
+
+    let expected_chunks = |block: &Block| BlockChunksUsage {
+        chunks_used: block.chunks_used(),
+        block_size: block.block_chunks_size,
+    };
+    assert_ne!(setup.blocks[1].chunks_used(), 0);
+
+    let blocks_chunks = BlockSchema(&mut storage)
+        .load_recent_blocks_chunks(10)
+        .await?;
+    assert_eq!(
+        blocks_chunks,
+        vec![
+            expected_chunks(&setup.blocks[1]),
+            expected_chunks(&setup.blocks[0])
+        ]
+    );
+
+    let blocks_chunks = BlockSchema(&mut storage)
+        .load_recent_blocks_chunks(1)
+        .await?;
+    assert_eq!(blocks_chunks, vec![expected_chunks(&setup.blocks[1])]);
+
+    Ok(())
+}
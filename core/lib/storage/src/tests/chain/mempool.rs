@@ -1,6 +1,8 @@
 // External imports
 use chrono::Utc;
+use sqlx::types::BigDecimal;
 // Workspace imports
+use zksync_api_types::v02::transaction::TxInBlockStatus;
 use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
 use zksync_types::{
     block::{Block, ExecutedOperations},
@@ -141,7 +143,7 @@ async fn store_load(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     let txs = zksync_txs();
     for tx in &txs {
         MempoolSchema(&mut storage)
-            .insert_tx(&tx.clone())
+            .insert_tx(&tx.clone(), &Default::default())
             .await
             .expect("Can't insert txs");
     }
@@ -183,24 +185,28 @@ async fn store_load_batch(mut storage: StorageProcessor<'_>) -> QueryResult<()>
     let elements_count = alone_txs_1.len() + alone_txs_2.len() + 3; // Amount of alone txs + amount of batches.
 
     for tx in alone_txs_1 {
-        MempoolSchema(&mut storage).insert_tx(tx).await?;
+        MempoolSchema(&mut storage)
+            .insert_tx(tx, &Default::default())
+            .await?;
     }
 
     // Store the first batch with a signature.
     MempoolSchema(&mut storage)
-        .insert_batch(batch_1, batch_1_signature.clone())
+        .insert_batch(batch_1, batch_1_signature.clone(), &Default::default())
         .await?;
     // Store the second one with multiple signatures.
     MempoolSchema(&mut storage)
-        .insert_batch(batch_2, batch_2_signatures.clone())
+        .insert_batch(batch_2, batch_2_signatures.clone(), &Default::default())
         .await?;
 
     for tx in alone_txs_2 {
-        MempoolSchema(&mut storage).insert_tx(tx).await?;
+        MempoolSchema(&mut storage)
+            .insert_tx(tx, &Default::default())
+            .await?;
     }
 
     MempoolSchema(&mut storage)
-        .insert_batch(batch_3, vec![])
+        .insert_batch(batch_3, vec![], &Default::default())
         .await?;
 
     // Load the txs and check that they match the expected list.
@@ -234,7 +240,9 @@ async fn remove_txs(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     // Insert several txs into the mempool schema.
     let txs = zksync_txs();
     for tx in &txs {
-        MempoolSchema(&mut storage).insert_tx(&tx.clone()).await?;
+        MempoolSchema(&mut storage)
+            .insert_tx(&tx.clone(), &Default::default())
+            .await?;
     }
 
     // Remove several txs from the schema.
@@ -265,7 +273,7 @@ async fn collect_garbage(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     let txs = zksync_txs();
     for tx in &txs {
         MempoolSchema(&mut storage)
-            .insert_tx(&tx.clone())
+            .insert_tx(&tx.clone(), &Default::default())
             .await
             .expect("Can't insert txs");
     }
@@ -333,8 +341,10 @@ async fn contains_and_get_tx(mut storage: StorageProcessor<'_>) -> QueryResult<(
             vec![gen_eth_sign_data("test message".to_owned()).signature; txs.len() - 1];
 
         let mut mempool = MempoolSchema(&mut storage);
-        mempool.insert_tx(single_tx).await?;
-        mempool.insert_batch(batch, batch_signature).await?;
+        mempool.insert_tx(single_tx, &Default::default()).await?;
+        mempool
+            .insert_batch(batch, batch_signature, &Default::default())
+            .await?;
     }
 
     // Make sure that the memory pool now responds that these transactions exist.
@@ -361,7 +371,7 @@ async fn contains_and_get_tx(mut storage: StorageProcessor<'_>) -> QueryResult<(
 async fn test_get_batch_info_from_mempool(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
     let txs = gen_transfers(5);
     MempoolSchema(&mut storage)
-        .insert_batch(&txs, Vec::new())
+        .insert_batch(&txs, Vec::new(), &Default::default())
         .await?;
 
     let tx_hashes: Vec<TxHash> = txs.into_iter().map(|tx| tx.hash()).collect();
@@ -512,3 +522,467 @@ async fn test_return_executed_txs_to_mempool(mut storage: StorageProcessor<'_>)
     assert_eq!(block_tx.variance_name(), "FullExit");
     Ok(())
 }
+
+/// Checks that pending transactions are counted for the account they were sent from.
+#[db_test]
+async fn account_pending_txs_count(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let txs = gen_transfers(4);
+    let account = txs[0].account();
+
+    MempoolSchema(&mut storage)
+        .insert_tx(&txs[0], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_tx(&txs[1], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_batch(&txs[2..4], vec![], &Default::default())
+        .await?;
+
+    assert_eq!(
+        MempoolSchema(&mut storage)
+            .get_account_pending_txs_count(account)
+            .await?,
+        1
+    );
+    assert_eq!(
+        MempoolSchema(&mut storage)
+            .get_account_pending_txs_count(txs[2].account())
+            .await?,
+        1
+    );
+    assert_eq!(
+        MempoolSchema(&mut storage)
+            .get_account_pending_txs_count(Address::random())
+            .await?,
+        0
+    );
+
+    // Removed transactions are not pending anymore.
+    MempoolSchema(&mut storage)
+        .remove_tx(txs[0].hash().as_ref())
+        .await?;
+    assert_eq!(
+        MempoolSchema(&mut storage)
+            .get_account_pending_txs_count(account)
+            .await?,
+        0
+    );
+
+    Ok(())
+}
+
+/// Checks that only the given transactions are evicted, and that batches are evicted as a whole.
+#[db_test]
+async fn evict_txs(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let txs = gen_transfers(4);
+
+    MempoolSchema(&mut storage)
+        .insert_tx(&txs[0], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_batch(&txs[1..3], vec![], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_tx(&txs[3], &Default::default())
+        .await?;
+
+    let mut evicted = MempoolSchema(&mut storage)
+        .evict_txs(&[txs[1].hash(), TxHash::default()])
+        .await?;
+    evicted.sort();
+    let mut expected = vec![txs[1].hash(), txs[2].hash()];
+    expected.sort();
+    assert_eq!(evicted, expected);
+
+    assert_eq!(MempoolSchema(&mut storage).get_mempool_size().await?, 2);
+    let txs_from_db = MempoolSchema(&mut storage).load_txs(&[]).await?;
+    assert_eq!(unwrap_tx(txs_from_db[0].clone()).hash(), txs[0].hash());
+
+    Ok(())
+}
+
+/// Checks that the cheapest transactions are chosen for the eviction first, and that only
+/// the transaction with the highest nonce of every account can be chosen.
+#[db_test]
+async fn eviction_candidates(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let transfer = |from: Address, nonce: u32| {
+        let transfer = Transfer::new(
+            AccountId(1),
+            from,
+            Address::random(),
+            TokenId(0),
+            100u32.into(),
+            10u32.into(),
+            Nonce(nonce),
+            Default::default(),
+            None,
+        );
+        SignedZkSyncTx {
+            tx: ZkSyncTx::Transfer(Box::new(transfer)),
+            eth_sign_data: None,
+            created_at: Utc::now(),
+        }
+    };
+    let first_account = Address::random();
+    let second_account = Address::random();
+
+    let first_account_cheap = transfer(first_account, 1);
+    let first_account_expensive = transfer(first_account, 2);
+    let second_account_medium = transfer(second_account, 1);
+    let second_account_cheap = transfer(second_account, 2);
+
+    for &(tx, fee_per_chunk) in &[
+        (&first_account_cheap, 1),
+        (&first_account_expensive, 1000),
+        (&second_account_medium, 100),
+        (&second_account_cheap, 10),
+    ] {
+        MempoolSchema(&mut storage)
+            .insert_tx(tx, &BigDecimal::from(fee_per_chunk))
+            .await?;
+    }
+
+    // The cheapest transaction of the first account can't be evicted before the expensive
+    // one with the higher nonce.
+    let candidates = MempoolSchema(&mut storage)
+        .get_eviction_candidates(10, &BigDecimal::from(10_000))
+        .await?;
+    assert_eq!(
+        candidates,
+        vec![second_account_cheap.hash(), first_account_expensive.hash()]
+    );
+
+    // Transactions paying at least the given fee are not chosen.
+    let candidates = MempoolSchema(&mut storage)
+        .get_eviction_candidates(10, &BigDecimal::from(1000))
+        .await?;
+    assert_eq!(candidates, vec![second_account_cheap.hash()]);
+
+    let candidates = MempoolSchema(&mut storage)
+        .get_eviction_candidates(1, &BigDecimal::from(10_000))
+        .await?;
+    assert_eq!(candidates, vec![second_account_cheap.hash()]);
+
+    // Proposed transactions are never evicted, and they still protect
+    // the transactions of the account with lower nonces.
+    MempoolSchema(&mut storage)
+        .mark_txs_proposed(&[second_account_cheap.hash()])
+        .await?;
+    let candidates = MempoolSchema(&mut storage)
+        .get_eviction_candidates(10, &BigDecimal::from(10_000))
+        .await?;
+    assert_eq!(candidates, vec![first_account_expensive.hash()]);
+
+    Ok(())
+}
+
+/// Checks that the pending transaction can be replaced by a transaction with the same nonce.
+#[db_test]
+async fn replace_tx(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let from = Address::random();
+    let transfer_with_fee = |fee: u32| {
+        let transfer = Transfer::new(
+            AccountId(1),
+            from,
+            Address::random(),
+            TokenId(0),
+            100u32.into(),
+            fee.into(),
+            Nonce(5),
+            Default::default(),
+            None,
+        );
+        SignedZkSyncTx {
+            tx: ZkSyncTx::Transfer(Box::new(transfer)),
+            eth_sign_data: None,
+            created_at: Utc::now(),
+        }
+    };
+    let pending_tx = transfer_with_fee(10);
+    let replacement_tx = transfer_with_fee(20);
+    let other_txs = gen_transfers(2);
+
+    MempoolSchema(&mut storage)
+        .insert_tx(&other_txs[0], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_tx(&pending_tx, &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_tx(&other_txs[1], &Default::default())
+        .await?;
+
+    let found_tx = MempoolSchema(&mut storage)
+        .get_pending_tx_by_nonce(from, Nonce(5))
+        .await?
+        .expect("pending tx not found");
+    assert_eq!(found_tx.hash(), pending_tx.hash());
+    assert!(MempoolSchema(&mut storage)
+        .get_pending_tx_by_nonce(from, Nonce(6))
+        .await?
+        .is_none());
+
+    assert!(
+        MempoolSchema(&mut storage)
+            .replace_tx(pending_tx.hash(), &replacement_tx, &Default::default())
+            .await?
+    );
+
+    // The replacement takes the place of the replaced transaction in the queue.
+    let txs_from_db = MempoolSchema(&mut storage).load_txs(&[]).await?;
+    let hashes: Vec<_> = txs_from_db
+        .into_iter()
+        .map(|tx| unwrap_tx(tx).hash())
+        .collect();
+    assert_eq!(
+        hashes,
+        vec![
+            other_txs[0].hash(),
+            replacement_tx.hash(),
+            other_txs[1].hash()
+        ]
+    );
+
+    assert_eq!(
+        MempoolSchema(&mut storage)
+            .get_replacement_tx_hash(pending_tx.hash())
+            .await?,
+        Some(replacement_tx.hash())
+    );
+    assert!(MempoolSchema(&mut storage)
+        .get_replacement_tx_hash(replacement_tx.hash())
+        .await?
+        .is_none());
+
+    let replaced_tx_data = MempoolSchema(&mut storage)
+        .get_replaced_tx_data(pending_tx.hash())
+        .await?
+        .expect("replaced tx data not found");
+    assert_eq!(replaced_tx_data.tx.tx_hash, pending_tx.hash());
+    assert_eq!(replaced_tx_data.tx.status, TxInBlockStatus::Replaced);
+
+    // Once the transaction is proposed for execution, it can't be replaced anymore.
+    let marked = MempoolSchema(&mut storage)
+        .mark_txs_proposed(&[replacement_tx.hash(), TxHash::default()])
+        .await?;
+    assert_eq!(marked, vec![replacement_tx.hash()]);
+    assert!(
+        !MempoolSchema(&mut storage)
+            .replace_tx(
+                replacement_tx.hash(),
+                &transfer_with_fee(30),
+                &Default::default()
+            )
+            .await?
+    );
+
+    // The replaced transaction is still reported once its replacement is not pending anymore.
+    MempoolSchema(&mut storage)
+        .remove_tx(replacement_tx.hash().as_ref())
+        .await?;
+    MempoolSchema(&mut storage).collect_garbage().await?;
+    assert!(MempoolSchema(&mut storage)
+        .get_replacement_tx_hash(pending_tx.hash())
+        .await?
+        .is_some());
+
+    // It's forgotten only after the retention period.
+    MempoolSchema(&mut storage)
+        .remove_replaced_txs(Utc::now() - chrono::Duration::hours(1))
+        .await?;
+    assert!(MempoolSchema(&mut storage)
+        .get_replacement_tx_hash(pending_tx.hash())
+        .await?
+        .is_some());
+    MempoolSchema(&mut storage)
+        .remove_replaced_txs(Utc::now() + chrono::Duration::hours(1))
+        .await?;
+    assert!(MempoolSchema(&mut storage)
+        .get_replacement_tx_hash(pending_tx.hash())
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+/// Checks that proposed transactions can be replaced again once they are returned to the queue.
+#[db_test]
+async fn reset_proposed_txs(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let txs = gen_transfers(2);
+    for tx in &txs {
+        MempoolSchema(&mut storage)
+            .insert_tx(tx, &Default::default())
+            .await?;
+    }
+    MempoolSchema(&mut storage)
+        .mark_txs_proposed(&[txs[0].hash()])
+        .await?;
+    assert!(
+        MempoolSchema(&mut storage)
+            .is_tx_executed(txs[0].hash())
+            .await?
+    );
+
+    MempoolSchema(&mut storage).reset_proposed_txs().await?;
+    assert!(
+        !MempoolSchema(&mut storage)
+            .is_tx_executed(txs[0].hash())
+            .await?
+    );
+    assert!(
+        !MempoolSchema(&mut storage)
+            .is_tx_executed(txs[1].hash())
+            .await?
+    );
+
+    Ok(())
+}
+
+/// Checks that only the pending transactions of the given account can be removed,
+/// and that batches are removed as a whole.
+#[db_test]
+async fn remove_account_pending_txs(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let txs = gen_transfers(4);
+    let alone_tx = &txs[0];
+    let mixed_batch = &txs[1..3];
+    let other_tx = &txs[3];
+
+    MempoolSchema(&mut storage)
+        .insert_tx(alone_tx, &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_batch(mixed_batch, vec![], &Default::default())
+        .await?;
+    MempoolSchema(&mut storage)
+        .insert_tx(other_tx, &Default::default())
+        .await?;
+
+    // The transaction can't be removed by another account.
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(other_tx.account(), alone_tx.hash())
+        .await?;
+    assert!(removed.is_empty());
+
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(alone_tx.account(), alone_tx.hash())
+        .await?;
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].hash(), alone_tx.hash());
+
+    // The batch contains a transaction of another account, so it can't be removed.
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(mixed_batch[0].account(), mixed_batch[0].hash())
+        .await?;
+    assert!(removed.is_empty());
+
+    // Removing a batch of a single account removes all of its transactions.
+    let from = Address::random();
+    let account_batch: Vec<_> = gen_transfers(2)
+        .into_iter()
+        .map(|mut tx| {
+            if let ZkSyncTx::Transfer(transfer) = &mut tx.tx {
+                transfer.from = from;
+            }
+            tx
+        })
+        .collect();
+    MempoolSchema(&mut storage)
+        .insert_batch(&account_batch, vec![], &Default::default())
+        .await?;
+
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(from, account_batch[1].hash())
+        .await?;
+    assert_eq!(removed.len(), 2);
+
+    let txs_from_db = MempoolSchema(&mut storage).load_txs(&[]).await?;
+    assert_eq!(txs_from_db.len(), 2);
+    assert!(
+        MempoolSchema(&mut storage)
+            .contains_tx(other_tx.hash())
+            .await?
+    );
+    assert!(
+        !MempoolSchema(&mut storage)
+            .contains_tx(alone_tx.hash())
+            .await?
+    );
+
+    Ok(())
+}
+
+/// Checks that transactions can't be cancelled once they are put into the pending block.
+#[db_test]
+async fn cancel_executed_txs(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let txs = gen_transfers(3);
+    for tx in &txs {
+        MempoolSchema(&mut storage)
+            .insert_tx(tx, &Default::default())
+            .await?;
+    }
+
+    // The transaction is handed over to the state keeper, so it may already be executed in memory.
+    MempoolSchema(&mut storage)
+        .mark_txs_proposed(&[txs[0].hash()])
+        .await?;
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(txs[0].account(), txs[0].hash())
+        .await?;
+    assert!(removed.is_empty());
+    assert!(
+        MempoolSchema(&mut storage)
+            .contains_tx(txs[0].hash())
+            .await?
+    );
+    assert!(
+        MempoolSchema(&mut storage)
+            .is_tx_executed(txs[0].hash())
+            .await?
+    );
+
+    // The transaction is stored as a part of the pending block.
+    let executed_tx = NewExecutedTransaction {
+        block_number: 1,
+        tx_hash: txs[1].hash().as_ref().to_vec(),
+        tx: Default::default(),
+        operation: Default::default(),
+        from_account: Default::default(),
+        to_account: None,
+        success: true,
+        fail_reason: None,
+        block_index: None,
+        primary_account_address: Default::default(),
+        nonce: Default::default(),
+        created_at: chrono::Utc::now(),
+        eth_sign_data: None,
+        batch_id: None,
+        affected_accounts: Vec::new(),
+        used_tokens: Vec::new(),
+    };
+    OperationsSchema(&mut storage)
+        .store_executed_tx(executed_tx)
+        .await?;
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(txs[1].account(), txs[1].hash())
+        .await?;
+    assert!(removed.is_empty());
+    assert!(
+        MempoolSchema(&mut storage)
+            .is_tx_executed(txs[1].hash())
+            .await?
+    );
+
+    // The transaction that is not executed yet can still be cancelled.
+    assert!(
+        !MempoolSchema(&mut storage)
+            .is_tx_executed(txs[2].hash())
+            .await?
+    );
+    let removed = MempoolSchema(&mut storage)
+        .remove_account_pending_txs(txs[2].account(), txs[2].hash())
+        .await?;
+    assert_eq!(removed.len(), 1);
+
+    Ok(())
+}
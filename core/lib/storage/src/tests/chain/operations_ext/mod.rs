@@ -110,7 +110,15 @@ async fn confirm_eth_op(
     let eth_tx_hash = dummy_ethereum_tx_hash(op.0);
     let response = storage
         .ethereum_schema()
-        .save_new_eth_tx(op_type, Some(op), 100, 100u32.into(), Default::default())
+        .save_new_eth_tx(
+            op_type,
+            Some(op),
+            100,
+            100u32.into(),
+            None,
+            Default::default(),
+            None,
+        )
         .await?;
     storage
         .ethereum_schema()
@@ -675,7 +683,7 @@ async fn get_batch_info(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
         let batch_id = storage
             .chain()
             .mempool_schema()
-            .insert_batch(&txs, Vec::new())
+            .insert_batch(&txs, Vec::new(), &Default::default())
             .await?;
         setup.blocks[i]
             .block_transactions
@@ -925,7 +933,11 @@ async fn tx_receipt(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
             panic!("Should be L2 tx")
         }
     };
-    storage.chain().mempool_schema().insert_tx(&tx).await?;
+    storage
+        .chain()
+        .mempool_schema()
+        .insert_tx(&tx, &Default::default())
+        .await?;
     let l2_receipt = storage
         .chain()
         .operations_ext_schema()
@@ -1003,7 +1015,11 @@ async fn tx_data(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
             panic!("Should be L2 tx")
         }
     };
-    storage.chain().mempool_schema().insert_tx(&tx).await?;
+    storage
+        .chain()
+        .mempool_schema()
+        .insert_tx(&tx, &Default::default())
+        .await?;
     let l2_data = storage
         .chain()
         .operations_ext_schema()
@@ -1,5 +1,11 @@
 // External imports
 // Workspace imports
+use zksync_crypto::{
+    merkle_tree::{
+        parallel_smt::SparseMerkleTreeSerializableCacheBN256, RescueHasher, SparseMerkleTree,
+    },
+    Engine, Fr,
+};
 use zksync_types::BlockNumber;
 // Local imports
 use crate::{
@@ -281,3 +287,150 @@ async fn different_cache_types_interoperability(
 
     Ok(())
 }
+
+/// Checks that the account tree cache is stored as diffs between the checkpoints,
+/// restored from the nearest checkpoint and compacted correctly.
+#[db_test]
+async fn incremental_account_tree_cache(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    type Tree = SparseMerkleTree<u64, Fr, RescueHasher<Engine>>;
+
+    let mut tree = Tree::new(8);
+    let mut caches = Vec::new();
+    for block_number in 1..=5 {
+        BlockSchema(&mut storage)
+            .save_full_block(gen_sample_block(
+                BlockNumber(block_number),
+                BLOCK_SIZE_CHUNKS,
+                Default::default(),
+            ))
+            .await?;
+        tree.insert(block_number * 3, u64::from(block_number));
+        tree.root_hash();
+        caches.push((tree.items.clone(), tree.root_hash(), tree.get_internals()));
+    }
+    // Checks that the restored cache corresponds to the tree state at the given block.
+    let check_cache = |block: u32, cache: SparseMerkleTreeSerializableCacheBN256| {
+        let (items, root_hash, _) = &caches[block as usize - 1];
+        let mut restored_tree = Tree::new(8);
+        for (idx, item) in items {
+            restored_tree.insert(*idx as u32, *item);
+        }
+        restored_tree.set_internals(cache);
+        assert_eq!(restored_tree.root_hash(), *root_hash);
+    };
+
+    // The first cache is stored as a checkpoint, the following ones as diffs.
+    TreeCacheSchemaBincode(&mut storage)
+        .store_account_tree_cache_incremental(BlockNumber(1), None, &caches[0].2)
+        .await?;
+    for block_number in 2..=4 {
+        let previous = &caches[block_number as usize - 2].2;
+        TreeCacheSchemaBincode(&mut storage)
+            .store_account_tree_cache_incremental(
+                BlockNumber(block_number),
+                Some((BlockNumber(block_number - 1), previous)),
+                &caches[block_number as usize - 1].2,
+            )
+            .await?;
+    }
+    assert_eq!(
+        TreeCacheSchemaBincode(&mut storage)
+            .get_last_block_with_account_tree_cache()
+            .await?,
+        Some(BlockNumber(1))
+    );
+    assert_eq!(
+        TreeCacheSchemaBincode(&mut storage)
+            .get_last_block_with_account_tree_cache_or_diff()
+            .await?,
+        Some(BlockNumber(4))
+    );
+    assert_eq!(
+        TreeCacheSchemaBincode(&mut storage)
+            .get_account_tree_cache_diffs(BlockNumber(1), BlockNumber(4))
+            .await?
+            .len(),
+        3
+    );
+
+    for block_number in 1..=4 {
+        let (block, cache) = TreeCacheSchemaBincode(&mut storage)
+            .load_account_tree_cache_incremental(BlockNumber(block_number))
+            .await?
+            .unwrap();
+        assert_eq!(block, BlockNumber(block_number));
+        check_cache(block_number, cache);
+    }
+    // There is no cache for the 5th block, so the latest one is returned.
+    let (block, _) = TreeCacheSchemaBincode(&mut storage)
+        .load_account_tree_cache_incremental(BlockNumber(5))
+        .await?
+        .unwrap();
+    assert_eq!(block, BlockNumber(4));
+
+    // The previous cache doesn't correspond to the last cached block, so the checkpoint is stored.
+    TreeCacheSchemaBincode(&mut storage)
+        .store_account_tree_cache_incremental(
+            BlockNumber(5),
+            Some((BlockNumber(3), &caches[2].2)),
+            &caches[4].2,
+        )
+        .await?;
+    assert_eq!(
+        TreeCacheSchemaBincode(&mut storage)
+            .get_last_block_with_account_tree_cache()
+            .await?,
+        Some(BlockNumber(5))
+    );
+
+    // Nothing can be removed until there is a checkpoint after the first one.
+    TreeCacheSchemaBincode(&mut storage)
+        .compact_account_tree_cache(BlockNumber(4))
+        .await?;
+    let (_, cache) = TreeCacheSchemaBincode(&mut storage)
+        .load_account_tree_cache_incremental(BlockNumber(4))
+        .await?
+        .unwrap();
+    check_cache(4, cache);
+
+    TreeCacheSchemaBincode(&mut storage)
+        .compact_account_tree_cache(BlockNumber(5))
+        .await?;
+    assert!(TreeCacheSchemaBincode(&mut storage)
+        .load_account_tree_cache_incremental(BlockNumber(4))
+        .await?
+        .is_none());
+    assert!(TreeCacheSchemaBincode(&mut storage)
+        .get_account_tree_cache_diffs(BlockNumber(0), BlockNumber(5))
+        .await?
+        .is_empty());
+    let (block, cache) = TreeCacheSchemaBincode(&mut storage)
+        .load_account_tree_cache_incremental(BlockNumber(5))
+        .await?
+        .unwrap();
+    assert_eq!(block, BlockNumber(5));
+    check_cache(5, cache);
+
+    // Reverting blocks removes the diffs as well.
+    BlockSchema(&mut storage)
+        .save_full_block(gen_sample_block(
+            BlockNumber(6),
+            BLOCK_SIZE_CHUNKS,
+            Default::default(),
+        ))
+        .await?;
+    TreeCacheSchemaBincode(&mut storage)
+        .store_account_tree_cache_diff(BlockNumber(6), vec![1, 2, 3])
+        .await?;
+    TreeCacheSchemaBincode(&mut storage)
+        .remove_new_account_tree_cache(BlockNumber(5))
+        .await?;
+    assert_eq!(
+        TreeCacheSchemaBincode(&mut storage)
+            .get_last_block_with_account_tree_cache_or_diff()
+            .await?,
+        Some(BlockNumber(5))
+    );
+
+    Ok(())
+}
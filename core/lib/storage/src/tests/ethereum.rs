@@ -5,7 +5,7 @@ use std::str::FromStr;
 use zksync_types::{
     aggregated_operations::{AggregatedActionType, AggregatedOperation},
     ethereum::ETHOperation,
-    BlockNumber, H256, U256,
+    Address, BlockNumber, H256, U256,
 };
 // Local imports
 use crate::test_data::{gen_unique_aggregated_operation, BLOCK_SIZE_CHUNKS};
@@ -23,7 +23,9 @@ pub struct EthereumTxParams {
     hash: H256,
     deadline_block: u64,
     gas_price: BigUint,
+    priority_fee: Option<BigUint>,
     raw_tx: Vec<u8>,
+    sender: Option<Address>,
 }
 
 impl EthereumTxParams {
@@ -36,7 +38,9 @@ impl EthereumTxParams {
             hash: H256::from_low_u64_ne(op_id as u64),
             deadline_block: 100,
             gas_price: 1000u32.into(),
+            priority_fee: None,
             raw_tx: Default::default(),
+            sender: None,
         }
     }
 
@@ -44,6 +48,10 @@ impl EthereumTxParams {
         let op_type = AggregatedActionType::from_str(self.op_type.as_ref())
             .expect("Stored operation type must have a valid value");
         let last_used_gas_price = U256::from_str(&self.gas_price.to_string()).unwrap();
+        let last_used_priority_fee = self
+            .priority_fee
+            .as_ref()
+            .map(|fee| U256::from_str(&fee.to_string()).unwrap());
         let used_tx_hashes = vec![self.hash];
 
         ETHOperation {
@@ -51,8 +59,10 @@ impl EthereumTxParams {
             op_type,
             op: self.op.clone(),
             nonce: nonce.into(),
+            sender: self.sender,
             last_deadline_block: self.deadline_block,
             last_used_gas_price,
+            last_used_priority_fee,
             used_tx_hashes,
             encoded_tx_data: self.raw_tx.clone(),
             confirmed: false,
@@ -116,7 +126,9 @@ async fn ethereum_storage(mut storage: StorageProcessor<'_>) -> QueryResult<()>
             params.op.clone(),
             params.deadline_block as i64,
             params.gas_price.clone(),
+            params.priority_fee.clone(),
             params.raw_tx.clone(),
+            None,
         )
         .await?;
     EthereumSchema(&mut storage)
@@ -148,15 +160,18 @@ async fn ethereum_storage(mut storage: StorageProcessor<'_>) -> QueryResult<()>
         .get_aggregated_op_that_affects_block(AggregatedActionType::CreateProofBlocks, block_number)
         .await?;
 
-    // Create one more Ethereum transaction.
-    let params_2 = EthereumTxParams::new("CommitBlocks".into(), op);
+    // Create one more Ethereum transaction, this time an EIP-1559 one.
+    let mut params_2 = EthereumTxParams::new("CommitBlocks".into(), op);
+    params_2.priority_fee = Some(100u32.into());
     let response_2 = EthereumSchema(&mut storage)
         .save_new_eth_tx(
             AggregatedActionType::CreateProofBlocks,
             params_2.op.clone(),
             params_2.deadline_block as i64,
             params_2.gas_price.clone(),
+            params_2.priority_fee.clone(),
             params_2.raw_tx.clone(),
+            None,
         )
         .await?;
     EthereumSchema(&mut storage)
@@ -193,6 +208,12 @@ async fn ethereum_storage(mut storage: StorageProcessor<'_>) -> QueryResult<()>
     assert_eq!(updated_stats.last_verified_block, 0);
     assert_eq!(updated_stats.last_executed_block, 0);
 
+    // Only the gas price of the confirmed transaction is reported.
+    let gas_prices = EthereumSchema(&mut storage)
+        .load_recent_gas_prices(10)
+        .await?;
+    assert_eq!(gas_prices, vec![U256::from(1000u32)]);
+
     Ok(())
 }
 
@@ -270,7 +291,9 @@ async fn ethereum_unprocessed(mut storage: StorageProcessor<'_>) -> QueryResult<
             params.op.clone(),
             params.deadline_block as i64,
             params.gas_price.clone(),
+            params.priority_fee.clone(),
             params.raw_tx.clone(),
+            None,
         )
         .await?;
     EthereumSchema(&mut storage)
@@ -328,7 +351,9 @@ async fn ethereum_unprocessed(mut storage: StorageProcessor<'_>) -> QueryResult<
             verify_params.op,
             verify_params.deadline_block as i64,
             verify_params.gas_price.clone(),
+            verify_params.priority_fee.clone(),
             verify_params.raw_tx.clone(),
+            None,
         )
         .await?;
     EthereumSchema(&mut storage)
@@ -399,3 +424,163 @@ async fn test_update_eth_parameters(mut storage: StorageProcessor<'_>) -> QueryR
 
     Ok(())
 }
+
+/// Checks that the nonce of the Ethereum operation can be reassigned and that
+/// the nonce sequence can be advanced, but never moved back.
+#[db_test]
+async fn ethereum_nonce_reconciliation(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    EthereumSchema(&mut storage).initialize_eth_data().await?;
+
+    let block_number = BlockNumber(1);
+    OperationsSchema(&mut storage)
+        .store_aggregated_action(gen_unique_aggregated_operation(
+            block_number,
+            AggregatedActionType::CommitBlocks,
+            BLOCK_SIZE_CHUNKS,
+        ))
+        .await?;
+    let op = OperationsSchema(&mut storage)
+        .get_aggregated_op_that_affects_block(AggregatedActionType::CommitBlocks, block_number)
+        .await?;
+
+    let params = EthereumTxParams::new("CommitBlocks".into(), op);
+    let response = EthereumSchema(&mut storage)
+        .save_new_eth_tx(
+            AggregatedActionType::CommitBlocks,
+            params.op.clone(),
+            params.deadline_block as i64,
+            params.gas_price.clone(),
+            params.priority_fee.clone(),
+            params.raw_tx.clone(),
+            None,
+        )
+        .await?;
+    EthereumSchema(&mut storage)
+        .add_hash_entry(response.id, &params.hash)
+        .await?;
+    assert_eq!(response.nonce, 0.into());
+
+    // Nonces 0..10 are used by some other transactions.
+    EthereumSchema(&mut storage).advance_nonce(10).await?;
+    // Advancing to the lower nonce has no effect.
+    EthereumSchema(&mut storage).advance_nonce(5).await?;
+    let new_nonce = EthereumSchema(&mut storage).get_next_nonce().await?;
+    assert_eq!(new_nonce, 10);
+
+    EthereumSchema(&mut storage)
+        .reassign_nonce(response.id, new_nonce)
+        .await?;
+    let new_hash = H256::from_low_u64_ne(42);
+    EthereumSchema(&mut storage)
+        .add_hash_entry(response.id, &new_hash)
+        .await?;
+
+    // The operation has the new nonce, superseded hashes are still tracked
+    // since any of them may still be the one to get mined.
+    let unconfirmed_operations = EthereumSchema(&mut storage)
+        .load_unconfirmed_operations()
+        .await?;
+    let eth_op = unconfirmed_operations[0].clone();
+    let mut expected_op = params.to_eth_op(eth_op.id, new_nonce as u64);
+    expected_op.used_tx_hashes.push(new_hash);
+    assert_eq!(eth_op, expected_op);
+
+    Ok(())
+}
+
+/// Checks that the operations of the additional operator account use its own nonce sequence.
+#[db_test]
+async fn ethereum_operator_accounts(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    EthereumSchema(&mut storage).initialize_eth_data().await?;
+    let operator = Address::repeat_byte(0x11);
+
+    let mut ops = Vec::new();
+    for block_number in 1..=2 {
+        let block_number = BlockNumber(block_number);
+        OperationsSchema(&mut storage)
+            .store_aggregated_action(gen_unique_aggregated_operation(
+                block_number,
+                AggregatedActionType::CommitBlocks,
+                BLOCK_SIZE_CHUNKS,
+            ))
+            .await?;
+        let op = OperationsSchema(&mut storage)
+            .get_aggregated_op_that_affects_block(AggregatedActionType::CommitBlocks, block_number)
+            .await?;
+        ops.push(op);
+    }
+
+    // Nonce of the additional operator account must be initialized first.
+    let mut params = EthereumTxParams::new("CommitBlocks".into(), ops[0].clone());
+    params.sender = Some(operator);
+    assert!(EthereumSchema(&mut storage)
+        .get_next_operator_nonce(operator)
+        .await
+        .is_err());
+    EthereumSchema(&mut storage)
+        .advance_operator_nonce(operator, 7)
+        .await?;
+
+    let response = EthereumSchema(&mut storage)
+        .save_new_eth_tx(
+            AggregatedActionType::CommitBlocks,
+            params.op.clone(),
+            params.deadline_block as i64,
+            params.gas_price.clone(),
+            params.priority_fee.clone(),
+            params.raw_tx.clone(),
+            params.sender,
+        )
+        .await?;
+    EthereumSchema(&mut storage)
+        .add_hash_entry(response.id, &params.hash)
+        .await?;
+    assert_eq!(response.nonce, 7.into());
+
+    // The main operator account nonce sequence is not affected.
+    let main_params = EthereumTxParams::new("CommitBlocks".into(), ops[1].clone());
+    let main_response = EthereumSchema(&mut storage)
+        .save_new_eth_tx(
+            AggregatedActionType::CommitBlocks,
+            main_params.op.clone(),
+            main_params.deadline_block as i64,
+            main_params.gas_price.clone(),
+            main_params.priority_fee.clone(),
+            main_params.raw_tx.clone(),
+            main_params.sender,
+        )
+        .await?;
+    EthereumSchema(&mut storage)
+        .add_hash_entry(main_response.id, &main_params.hash)
+        .await?;
+    assert_eq!(main_response.nonce, 0.into());
+
+    let unconfirmed_operations = EthereumSchema(&mut storage)
+        .load_unconfirmed_operations()
+        .await?;
+    assert_eq!(unconfirmed_operations.len(), 2);
+    assert_eq!(unconfirmed_operations[0], params.to_eth_op(response.id, 7));
+    assert_eq!(
+        unconfirmed_operations[1],
+        main_params.to_eth_op(main_response.id, 0)
+    );
+    assert_eq!(
+        EthereumSchema(&mut storage)
+            .get_next_operator_nonce(operator)
+            .await?,
+        8
+    );
+
+    // The operation of the additional operator account may be moved to the main one.
+    EthereumSchema(&mut storage)
+        .reassign_sender(response.id, None, 1)
+        .await?;
+    let unconfirmed_operations = EthereumSchema(&mut storage)
+        .load_unconfirmed_operations()
+        .await?;
+    let mut expected_op = params.to_eth_op(response.id, 1);
+    expected_op.sender = None;
+    assert_eq!(unconfirmed_operations[0], expected_op);
+
+    Ok(())
+}
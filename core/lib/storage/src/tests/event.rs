@@ -72,7 +72,9 @@ async fn store_operation(
             Some((id, op)),
             100,
             100u32.into(),
+            None,
             Default::default(),
+            None,
         )
         .await?;
     storage
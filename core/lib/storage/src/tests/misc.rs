@@ -1,8 +1,16 @@
+use chrono::{Duration, Utc};
 use sqlx::types::BigDecimal;
-use zksync_types::TokenId;
+use zksync_types::{
+    tokens::ChangePubKeyFeeTypeArg,
+    tx::{ChangePubKeyType, TxHash},
+    Address, OutputFeeType, TokenId,
+};
 
 use crate::tests::db_test;
-use crate::{misc::records::Subsidy, misc::MiscSchema};
+use crate::{
+    misc::records::{FeeQuoteUsage, Subsidy},
+    misc::MiscSchema,
+};
 use crate::{QueryResult, StorageProcessor};
 
 fn get_subsidy(name: String, value: u64) -> Subsidy {
@@ -54,3 +62,280 @@ async fn stored_subsidy(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
 
     Ok(())
 }
+
+/// Checks that the subsidies are accounted by the time they were stored.
+#[db_test]
+async fn subsidy_usage_since(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let subsidy_name = "subsidy".to_string();
+    let before = Utc::now() - Duration::minutes(1);
+
+    MiscSchema(&mut storage)
+        .store_subsidy(get_subsidy(subsidy_name.clone(), 10))
+        .await?;
+    MiscSchema(&mut storage)
+        .store_subsidy(get_subsidy("another_subsidy".to_string(), 45))
+        .await?;
+    MiscSchema(&mut storage)
+        .store_subsidy(get_subsidy(subsidy_name.clone(), 15))
+        .await?;
+
+    let used = MiscSchema(&mut storage)
+        .get_used_subsidy_for_type_since(&subsidy_name, before)
+        .await?;
+    assert_eq!(used, BigDecimal::from(25));
+
+    let used = MiscSchema(&mut storage)
+        .get_used_subsidy_for_type_since(&subsidy_name, Utc::now() + Duration::minutes(1))
+        .await?;
+    assert_eq!(used, BigDecimal::from(0));
+
+    Ok(())
+}
+
+/// Checks that the subsidies are stored only if they fit both the budget and the cap,
+/// and that the stored subsidy can be removed.
+#[db_test]
+async fn subsidy_within_limits(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let subsidy_name = "limited_subsidy".to_string();
+    let since_before = Utc::now() - Duration::minutes(1);
+    let since_after = Utc::now() + Duration::minutes(1);
+
+    assert!(
+        MiscSchema(&mut storage)
+            .store_subsidy_within_limits(
+                get_subsidy(subsidy_name.clone(), 10),
+                30,
+                20,
+                since_before
+            )
+            .await?
+    );
+    // Exceeds the cap.
+    assert!(
+        !MiscSchema(&mut storage)
+            .store_subsidy_within_limits(
+                get_subsidy(subsidy_name.clone(), 15),
+                30,
+                20,
+                since_before
+            )
+            .await?
+    );
+    // The subsidies stored before the cap period don't count for the cap.
+    assert!(
+        MiscSchema(&mut storage)
+            .store_subsidy_within_limits(get_subsidy(subsidy_name.clone(), 15), 30, 20, since_after)
+            .await?
+    );
+    // Exceeds the budget.
+    assert!(
+        !MiscSchema(&mut storage)
+            .store_subsidy_within_limits(
+                get_subsidy(subsidy_name.clone(), 10),
+                30,
+                100,
+                since_after
+            )
+            .await?
+    );
+    let used = MiscSchema(&mut storage)
+        .get_total_used_subsidy_for_type(&subsidy_name)
+        .await?;
+    assert_eq!(used, BigDecimal::from(25));
+
+    let tx_hash = TxHash::from_slice(&[1; 32]).unwrap();
+    let subsidy = Subsidy {
+        tx_hash,
+        ..get_subsidy(subsidy_name.clone(), 5)
+    };
+    assert!(
+        MiscSchema(&mut storage)
+            .store_subsidy_within_limits(subsidy, 30, 100, since_before)
+            .await?
+    );
+    MiscSchema(&mut storage)
+        .remove_subsidy(tx_hash, &subsidy_name)
+        .await?;
+    let used = MiscSchema(&mut storage)
+        .get_total_used_subsidy_for_type(&subsidy_name)
+        .await?;
+    assert_eq!(used, BigDecimal::from(25));
+
+    Ok(())
+}
+
+/// Checks that the unused fee quotes are found for the exact transaction, that a quote can only be used
+/// by a single transaction until it expires, and that the expired quotes are removed.
+#[db_test]
+async fn stored_fee_quotes(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
+    let sender = Address::repeat_byte(1);
+    let recipient = Address::repeat_byte(2);
+    let cpk_fee_type = OutputFeeType::ChangePubKey(ChangePubKeyFeeTypeArg::ContractsV4Version(
+        ChangePubKeyType::ECDSA,
+    ));
+    let valid_until = Utc::now() + Duration::minutes(10);
+
+    let quote = MiscSchema(&mut storage)
+        .store_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            BigDecimal::from(100),
+            valid_until,
+        )
+        .await?;
+    MiscSchema(&mut storage)
+        .store_fee_quote(
+            sender,
+            sender,
+            cpk_fee_type,
+            TokenId(1),
+            BigDecimal::from(50),
+            valid_until,
+        )
+        .await?;
+    let expired_quote = MiscSchema(&mut storage)
+        .store_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            BigDecimal::from(10),
+            Utc::now() - Duration::minutes(1),
+        )
+        .await?;
+
+    let loaded = MiscSchema(&mut storage)
+        .get_fee_quote(quote.id)
+        .await?
+        .expect("Quote must be stored");
+    assert_eq!(loaded.sender, sender.as_bytes());
+    assert_eq!(loaded.recipient, recipient.as_bytes());
+    assert_eq!(loaded.amount, BigDecimal::from(100));
+    assert!(loaded.tx_hash.is_none());
+
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            Utc::now(),
+        )
+        .await?
+        .expect("Quote must be found");
+    assert_eq!(found.id, quote.id);
+    // The quote expires too early.
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            valid_until + Duration::minutes(1),
+        )
+        .await?;
+    assert!(found.is_none());
+    // The quote was made for another token, transaction type or sender.
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(2),
+            Utc::now(),
+        )
+        .await?;
+    assert!(found.is_none());
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::TransferToNew,
+            TokenId(1),
+            Utc::now(),
+        )
+        .await?;
+    assert!(found.is_none());
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            recipient,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            Utc::now(),
+        )
+        .await?;
+    assert!(found.is_none());
+
+    // The quote can be used by the single transaction only.
+    let tx_hash = TxHash::from_slice(&[1; 32]).unwrap();
+    let another_tx_hash = TxHash::from_slice(&[2; 32]).unwrap();
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(quote.id, tx_hash)
+            .await?,
+        FeeQuoteUsage::Used
+    );
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(quote.id, tx_hash)
+            .await?,
+        FeeQuoteUsage::AlreadyUsed
+    );
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(quote.id, another_tx_hash)
+            .await?,
+        FeeQuoteUsage::Unavailable
+    );
+    let found = MiscSchema(&mut storage)
+        .find_unused_fee_quote(
+            sender,
+            recipient,
+            OutputFeeType::Transfer,
+            TokenId(1),
+            Utc::now(),
+        )
+        .await?;
+    assert!(found.is_none());
+
+    // The released quote can be used by another transaction.
+    MiscSchema(&mut storage)
+        .release_fee_quote(quote.id, another_tx_hash)
+        .await?;
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(quote.id, another_tx_hash)
+            .await?,
+        FeeQuoteUsage::Unavailable
+    );
+    MiscSchema(&mut storage)
+        .release_fee_quote(quote.id, tx_hash)
+        .await?;
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(quote.id, another_tx_hash)
+            .await?,
+        FeeQuoteUsage::Used
+    );
+
+    // The expired quote can't be used.
+    assert_eq!(
+        MiscSchema(&mut storage)
+            .use_fee_quote(expired_quote.id, tx_hash)
+            .await?,
+        FeeQuoteUsage::Unavailable
+    );
+
+    // Quotes expired in the other tests may be removed as well.
+    let removed = MiscSchema(&mut storage).remove_expired_fee_quotes().await?;
+    assert!(removed >= 1);
+    assert!(MiscSchema(&mut storage)
+        .get_fee_quote(expired_quote.id)
+        .await?
+        .is_none());
+
+    Ok(())
+}
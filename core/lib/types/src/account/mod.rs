@@ -16,10 +16,15 @@ use zksync_utils::BigUintSerdeWrapper;
 // Local uses
 use super::{AccountId, AccountUpdates, Address, Fr, Nonce, TokenId, NFT};
 
-pub use self::{account_update::AccountUpdate, pubkey_hash::PubKeyHash};
+pub use self::{
+    account_update::AccountUpdate,
+    proof::{AccountMerkleProof, MerklePathNode},
+    pubkey_hash::PubKeyHash,
+};
 
 mod account_update;
 pub mod error;
+mod proof;
 mod pubkey_hash;
 
 /// zkSync network account.
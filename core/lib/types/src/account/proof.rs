@@ -0,0 +1,125 @@
+// External uses
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+// Workspace uses
+use zksync_crypto::{circuit::account::CircuitAccount, serialization::FrSerde};
+use zksync_utils::BigUintSerdeAsRadix10Str;
+// Local uses
+use super::PubKeyHash;
+use crate::{AccountId, AccountTree, Address, BlockNumber, Engine, Fr, Nonce, TokenId};
+
+/// Element of the Merkle path from a leaf to the root of the tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MerklePathNode {
+    /// Hash of the sibling node on the current level.
+    #[serde(with = "FrSerde")]
+    pub hash: Fr,
+    /// Whether the node on the path is the right child of its parent.
+    pub is_right: bool,
+}
+
+/// Proof of inclusion of the account balance into the state tree of the block.
+///
+/// Consists of the path from the balance leaf to the root of the account balance tree,
+/// the account leaf data and the path from the account leaf to the root of the account tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMerkleProof {
+    pub block_number: BlockNumber,
+    /// Root hash of the account tree committed for the block.
+    #[serde(with = "FrSerde")]
+    pub root_hash: Fr,
+    pub account_id: AccountId,
+    pub address: Address,
+    pub pub_key_hash: PubKeyHash,
+    pub nonce: Nonce,
+    /// Root hash of the account balance tree.
+    #[serde(with = "FrSerde")]
+    pub balance_tree_root: Fr,
+    pub token_id: TokenId,
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub balance: BigUint,
+    pub balance_path: Vec<MerklePathNode>,
+    pub account_path: Vec<MerklePathNode>,
+}
+
+impl AccountMerkleProof {
+    /// Builds a proof for the balance of the account in the given tree.
+    /// Non-existing accounts are represented by the empty leaf, so the proof can
+    /// be used to show that the account has no balance as well.
+    pub fn new(
+        tree: &AccountTree,
+        block_number: BlockNumber,
+        account_id: AccountId,
+        token_id: TokenId,
+    ) -> Self {
+        let account = tree.get(*account_id).cloned().unwrap_or_default();
+        let balance = account.get_balance(token_id);
+
+        let circuit_account = CircuitAccount::<Engine>::from(account.clone());
+        let balance_path = to_path_nodes(circuit_account.subtree.merkle_path(*token_id));
+        let account_path = to_path_nodes(tree.merkle_path(*account_id));
+
+        Self {
+            block_number,
+            root_hash: tree.root_hash(),
+            account_id,
+            address: account.address,
+            pub_key_hash: account.pub_key_hash,
+            nonce: account.nonce,
+            balance_tree_root: circuit_account.subtree.root_hash(),
+            token_id,
+            balance,
+            balance_path,
+            account_path,
+        }
+    }
+}
+
+fn to_path_nodes(path: Vec<(Fr, bool)>) -> Vec<MerklePathNode> {
+    path.into_iter()
+        .map(|(hash, is_right)| MerklePathNode { hash, is_right })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Account;
+    use zksync_crypto::{circuit::account::Balance, ff::PrimeField, params::account_tree_depth};
+
+    /// Checks that the paths in the proof lead to the roots of the corresponding trees.
+    #[test]
+    fn account_proof_paths() {
+        let mut tree = AccountTree::new(account_tree_depth());
+        let mut account = Account::default_with_address(&Address::random());
+        account.set_balance(TokenId(2), 1000u32.into());
+        tree.insert(5, account.clone());
+        tree.insert(7, Account::default_with_address(&Address::random()));
+
+        let proof = AccountMerkleProof::new(&tree, BlockNumber(1), AccountId(5), TokenId(2));
+        assert_eq!(proof.balance, 1000u32.into());
+        assert_eq!(proof.address, account.address);
+
+        let account_path = proof
+            .account_path
+            .iter()
+            .map(|node| (node.hash, node.is_right))
+            .collect();
+        assert!(tree.verify_proof(5, account.clone(), account_path));
+
+        let circuit_account = CircuitAccount::<Engine>::from(account);
+        let balance_path = proof
+            .balance_path
+            .iter()
+            .map(|node| (node.hash, node.is_right))
+            .collect();
+        let balance = Balance {
+            value: Fr::from_str("1000").unwrap(),
+        };
+        assert!(circuit_account
+            .subtree
+            .verify_proof(2, balance, balance_path));
+    }
+}
@@ -359,7 +359,8 @@ impl Block {
         self.processed_priority_ops.1 - self.processed_priority_ops.0
     }
 
-    fn chunks_used(&self) -> usize {
+    /// Returns the amount of chunks used by the block operations.
+    pub fn chunks_used(&self) -> usize {
         self.block_transactions
             .iter()
             .filter_map(ExecutedOperations::get_executed_op)
@@ -4,7 +4,7 @@
 use thiserror::Error;
 // Local uses
 use crate::aggregated_operations::{AggregatedActionType, AggregatedOperation};
-use zksync_basic_types::{H256, U256};
+use zksync_basic_types::{Address, H256, U256};
 
 /// Numerical identifier of the Ethereum operation.
 pub type EthOpId = i64;
@@ -20,10 +20,17 @@ pub struct ETHOperation {
     pub op: Option<(i64, AggregatedOperation)>,
     /// Used nonce (fixed for all the sent transactions).
     pub nonce: U256,
+    /// Address of the additional operator account sending the transactions.
+    /// `None` stands for the main operator account.
+    pub sender: Option<Address>,
     /// Deadline block of the last sent transaction.
     pub last_deadline_block: u64,
     /// Gas price used in the last sent transaction.
+    /// For EIP-1559 transactions it's the max fee per gas.
     pub last_used_gas_price: U256,
+    /// Max priority fee per gas used in the last sent transaction.
+    /// Set only for EIP-1559 transactions.
+    pub last_used_priority_fee: Option<U256>,
     /// Hashes of all the sent transactions.
     pub used_tx_hashes: Vec<H256>,
     /// Tx payload (not signed).
@@ -59,8 +66,10 @@ impl PartialEq for ETHOperation {
         // However, the volatile fields (e.g. `used_tx_hashes` and `confirmed`) may vary
         // for the same operation in different states, so we compare them as well.
         (self.id == other.id)
+            && (self.sender == other.sender)
             && (self.last_deadline_block == other.last_deadline_block)
             && (self.last_used_gas_price == other.last_used_gas_price)
+            && (self.last_used_priority_fee == other.last_used_priority_fee)
             && (self.used_tx_hashes == other.used_tx_hashes)
             && (self.confirmed == other.confirmed)
             && (self.final_hash == other.final_hash)
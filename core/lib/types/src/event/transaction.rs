@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 // Workspace uses
 // Local uses
 use super::account::AccountStateChangeStatus;
-use crate::{block::ExecutedOperations, AccountId, BlockNumber, TokenId};
+use crate::{block::ExecutedOperations, AccountId, BlockNumber, SignedZkSyncTx, TokenId};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +15,7 @@ pub enum TransactionStatus {
     Committed,
     Finalized,
     Rejected,
+    Cancelled,
 }
 
 /// All possible types of operations in the zkSync network.
@@ -89,6 +90,24 @@ impl TransactionEvent {
         })
     }
 
+    /// Creates transaction event for the transaction cancelled by its sender
+    /// before being included into the block.
+    ///
+    /// Returns [`None`] for [close](crate::tx::Close) operation.
+    pub fn from_cancelled_tx(tx: &SignedZkSyncTx, block_number: BlockNumber) -> Option<Self> {
+        Some(Self {
+            tx_hash: tx.tx.hash().to_string(),
+            account_id: tx.account_id().ok()?,
+            token_id: tx.token_id(),
+            block_number,
+            tx: serde_json::to_value(&tx.tx).unwrap(),
+            status: TransactionStatus::Cancelled,
+            fail_reason: None,
+            created_at: tx.created_at,
+            tx_type: OnceCell::default(),
+        })
+    }
+
     pub fn tx_type(&self) -> TransactionType {
         *self
             .tx_type
@@ -58,7 +58,7 @@ mod utils;
 #[cfg(test)]
 mod tests;
 
-pub use self::account::{Account, AccountUpdate, PubKeyHash};
+pub use self::account::{Account, AccountMerkleProof, AccountUpdate, MerklePathNode, PubKeyHash};
 pub use self::block::{ExecutedOperations, ExecutedPriorityOp, ExecutedTx};
 pub use self::fee::{BatchFee, Fee, OutputFeeType, TotalFee};
 pub use self::operations::{
@@ -82,11 +82,12 @@ pub use zksync_basic_types::*;
 pub type AccountMap = zksync_crypto::fnv::FnvHashMap<AccountId, Account>;
 pub type AccountUpdates = Vec<(AccountId, AccountUpdate)>;
 pub type AccountTree = SparseMerkleTree<Account, Fr, RescueHasher<Engine>>;
+pub type PersistentAccountTree = PersistentSparseMerkleTree<Account, RescueHasher<Engine>>;
 pub type SerialId = u64;
 
 use crate::block::Block;
 pub use zksync_crypto::{
-    merkle_tree::{RescueHasher, SparseMerkleTree},
+    merkle_tree::{PersistentSparseMerkleTree, RescueHasher, SparseMerkleTree},
     Engine, Fr,
 };
 
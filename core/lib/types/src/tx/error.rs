@@ -57,6 +57,24 @@ pub enum TxAddError {
 
     #[error("Too many Ethereum signatures provided")]
     EthSignaturesLimitExceeded,
+
+    #[error("Tx nonce is too far ahead of the account nonce")]
+    NonceGapTooBig,
+
+    #[error("Too many pending transactions from the account")]
+    TooManyPendingTxs,
+
+    #[error("Mempool is full")]
+    MempoolFull,
+
+    #[error("Replacement transaction must pay a strictly higher fee in the same token")]
+    ReplacementFeeTooLow,
+
+    #[error("There is no pending transaction of the account with such hash")]
+    PendingTxNotFound,
+
+    #[error("Transaction is already executed")]
+    TxAlreadyExecuted,
 }
 
 #[derive(Error, Debug, Copy, Clone, Serialize, Deserialize)]
@@ -13,7 +13,7 @@ use num::BigUint;
 use zksync_types::{
     network::Network,
     tx::{PackedEthSignature, TxHash, ZkSyncTx},
-    Address, TokenLike, TxFeeTypes,
+    AccountId, AccountMerkleProof, Address, BlockNumber, TokenLike, TxFeeTypes,
 };
 
 // Local uses
@@ -241,6 +241,19 @@ impl RpcProvider {
         self.send_and_deserialize(&msg).await
     }
 
+    /// Requests the Merkle proof of the account balance in the given token.
+    /// If `block` is not specified, the proof for the last verified block is returned.
+    /// The proof can be checked with `utils::verify_account_proof`.
+    pub async fn account_proof(
+        &self,
+        account_id: AccountId,
+        token: impl Into<TokenLike>,
+        block: Option<BlockNumber>,
+    ) -> Result<AccountMerkleProof, ClientError> {
+        let msg = JsonRpcRequest::account_proof(account_id, token.into(), block);
+        self.send_and_deserialize(&msg).await
+    }
+
     /// Performs a POST query to the JSON RPC endpoint,
     /// and decodes the response, returning the decoded `serde_json::Value`.
     /// `Ok` is returned only for successful calls, for any kind of error
@@ -324,7 +337,7 @@ mod messages {
     use serde::Serialize;
     use zksync_types::{
         tx::{PackedEthSignature, TxEthSignature, TxHash, ZkSyncTx},
-        Address, TokenLike, TxFeeTypes,
+        AccountId, Address, BlockNumber, TokenLike, TxFeeTypes,
     };
 
     #[derive(Debug, Serialize)]
@@ -425,5 +438,13 @@ mod messages {
             let params = json_values![tx_types, addresses, token_like];
             Self::create("get_txs_batch_fee_in_wei", params)
         }
+
+        pub fn account_proof(
+            account_id: AccountId,
+            token: TokenLike,
+            block: Option<BlockNumber>,
+        ) -> Self {
+            Self::create("get_account_proof", json_values![account_id, token, block])
+        }
     }
 }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use num::BigUint;
 use serde::{Deserialize, Serialize};
 
-use zksync_types::{AccountId, Address, Nonce, PubKeyHash, Token, TokenId, H256};
+use zksync_types::{tx::TxHash, AccountId, Address, Nonce, PubKeyHash, Token, TokenId, H256};
 use zksync_utils::{BigUintSerdeAsRadix10Str, BigUintSerdeWrapper};
 
 pub type Tokens = HashMap<String, Token>;
@@ -72,6 +72,8 @@ pub struct TransactionInfo {
     pub success: Option<bool>,
     pub fail_reason: Option<String>,
     pub block: Option<BlockInfo>,
+    /// Hash of the transaction that replaced this one in the mempool, if any.
+    pub replaced_by: Option<TxHash>,
 }
 
 impl TransactionInfo {
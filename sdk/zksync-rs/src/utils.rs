@@ -6,9 +6,16 @@ use sha2::{Digest, Sha256};
 
 use zksync_crypto::bellman::{pairing::ff::PrimeField, PrimeFieldRepr};
 use zksync_crypto::franklin_crypto::alt_babyjubjub::fs::FsRepr;
-use zksync_crypto::{priv_key_from_fs, Fs, PrivateKey};
+use zksync_crypto::{
+    circuit::{account::Balance, utils::eth_address_to_fr},
+    ff::Field,
+    merkle_tree::{hasher::Hasher, RescueHasher},
+    params,
+    primitives::{GetBits, GetBitsFixed},
+    priv_key_from_fs, Engine, Fr, Fs, PrivateKey,
+};
 use zksync_eth_signer::EthereumSigner;
-use zksync_types::{AccountId, U256};
+use zksync_types::{AccountId, AccountMerkleProof, MerklePathNode, U256};
 
 use crate::{error::ClientError, provider::Provider, wallet::Wallet};
 
@@ -141,3 +148,72 @@ mod tests {
         assert_eq!(pk_err, ClientError::SeedTooShort);
     }
 }
+
+/// Checks the Merkle proof of the account balance obtained from the server.
+///
+/// Recomputes the root of the account balance tree and the root of the account tree
+/// using the Rescue hash function and compares them with the ones provided in the proof.
+/// To make sure the proof corresponds to the actual network state, `proof.root_hash` must be
+/// additionally compared with the root hash of the block stored in the zkSync contract.
+pub fn verify_account_proof(proof: &AccountMerkleProof) -> bool {
+    if proof.balance_path.len() != params::balance_tree_depth()
+        || proof.account_path.len() != params::account_tree_depth()
+    {
+        return false;
+    }
+    let hasher = RescueHasher::<Engine>::default();
+
+    let balance_value = match Fr::from_str(&proof.balance.to_string()) {
+        Some(value) => value,
+        None => return false,
+    };
+    let balance_leaf = Balance::<Engine> {
+        value: balance_value,
+    }
+    .get_bits_le();
+    let (balance_root, token_index) = fold_merkle_path(&hasher, balance_leaf, &proof.balance_path);
+    if balance_root != proof.balance_tree_root || token_index != *proof.token_id {
+        return false;
+    }
+
+    // Account leaf layout must match the one used by `CircuitAccount`.
+    let state_root = hasher.hash_elements(vec![proof.balance_tree_root, Fr::zero()]);
+    let mut state_root_bits = state_root.get_bits_le_fixed(params::FR_BIT_WIDTH);
+    state_root_bits.resize(params::FR_BIT_WIDTH_PADDED, false);
+
+    let nonce = Fr::from_str(&proof.nonce.to_string()).expect("Nonce fits into the field");
+    let mut account_leaf = Vec::with_capacity(params::LEAF_DATA_BIT_WIDTH);
+    account_leaf.extend(nonce.get_bits_le_fixed(params::NONCE_BIT_WIDTH));
+    account_leaf.extend(
+        proof
+            .pub_key_hash
+            .as_fr()
+            .get_bits_le_fixed(params::NEW_PUBKEY_HASH_WIDTH),
+    );
+    account_leaf.extend(eth_address_to_fr(&proof.address).get_bits_le_fixed(params::ADDRESS_WIDTH));
+    account_leaf.extend(state_root_bits);
+
+    let (root_hash, account_index) = fold_merkle_path(&hasher, account_leaf, &proof.account_path);
+    root_hash == proof.root_hash && account_index == *proof.account_id
+}
+
+/// Hashes the leaf up to the root along the path, returning the root hash
+/// and the index of the leaf restored from the path directions.
+fn fold_merkle_path(
+    hasher: &RescueHasher<Engine>,
+    leaf: Vec<bool>,
+    path: &[MerklePathNode],
+) -> (Fr, u32) {
+    let mut index = 0u32;
+    let mut hash = hasher.hash_bits(leaf);
+    for (level, node) in path.iter().enumerate() {
+        let (lhs, rhs) = if node.is_right {
+            index |= 1 << level;
+            (node.hash, hash)
+        } else {
+            (hash, node.hash)
+        };
+        hash = hasher.compress(&lhs, &rhs, level);
+    }
+    (hash, index)
+}
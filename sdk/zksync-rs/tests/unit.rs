@@ -59,6 +59,38 @@ fn test_tokens_cache() {
     assert!(!tokens_cache.is_eth((&token_dai.symbol as &str).into()));
 }
 
+#[test]
+fn test_verify_account_proof() {
+    use zksync_crypto::params::account_tree_depth;
+    use zksync_types::{Account, AccountMerkleProof, AccountTree, Address, BlockNumber};
+
+    let mut tree = AccountTree::new(account_tree_depth());
+    let mut account = Account::default_with_address(&Address::random());
+    account.nonce = Nonce(3);
+    account.set_balance(TokenId(1), 12345u32.into());
+    tree.insert(4, account);
+    tree.insert(9, Account::default_with_address(&Address::random()));
+
+    let proof = AccountMerkleProof::new(&tree, BlockNumber(1), AccountId(4), TokenId(1));
+    assert!(verify_account_proof(&proof));
+
+    // Proof for the balance that is not set is valid as well.
+    let empty_proof = AccountMerkleProof::new(&tree, BlockNumber(1), AccountId(4), TokenId(2));
+    assert!(verify_account_proof(&empty_proof));
+
+    let mut tampered_proof = proof.clone();
+    tampered_proof.balance = 12346u32.into();
+    assert!(!verify_account_proof(&tampered_proof));
+
+    let mut tampered_proof = proof.clone();
+    tampered_proof.nonce = Nonce(4);
+    assert!(!verify_account_proof(&tampered_proof));
+
+    let mut tampered_proof = proof;
+    tampered_proof.account_id = AccountId(5);
+    assert!(!verify_account_proof(&tampered_proof));
+}
+
 fn priv_key_from_raw(raw: &[u8]) -> Option<PrivateKey> {
     use zksync_crypto::{
         bellman::{pairing::ff::PrimeField, PrimeFieldRepr},
@@ -305,6 +305,7 @@ export interface TransactionReceipt {
     success?: boolean;
     failReason?: string;
     block?: BlockInfo;
+    replacedBy?: string;
 }
 
 export interface PriorityOperationReceipt {
@@ -498,7 +499,7 @@ export interface ApiL1TxReceipt {
     id: number;
 }
 
-export type L2TxStatus = 'queued' | 'committed' | 'finalized' | 'rejected';
+export type L2TxStatus = 'queued' | 'committed' | 'finalized' | 'rejected' | 'replaced';
 
 export interface ApiL2TxReceipt {
     txHash: string;